@@ -0,0 +1,138 @@
+use std::time::Instant;
+
+use chrono::Utc;
+use ratatui::{
+    layout::{Alignment, Constraint, Layout, Rect},
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, Clear, Paragraph},
+    Frame,
+};
+
+use crate::logo::{self, OCEANIC_NEXT};
+use crate::readingstats::Stats;
+use crate::storage::PocketItem;
+use crate::TableRow;
+
+/// How long every "you saved this" teaser stays on the screen
+const TEASER_SECS: u64 = 15;
+
+/// Only items older than that are worth a nudge
+const MIN_TEASER_AGE_DAYS: i64 = 30;
+
+pub struct Teaser {
+    pub title: String,
+    pub saved_ago: String,
+}
+
+/// Idle screen that hides the reading list until any key is pressed
+pub struct AmbientScreen {
+    started: Instant,
+    seed: usize,
+    teasers: Vec<Teaser>,
+}
+
+impl AmbientScreen {
+    pub fn new<'a>(items: impl Iterator<Item = &'a PocketItem>) -> Self {
+        let now = Utc::now().timestamp();
+        let teasers = items
+            .filter_map(|item| {
+                let age_days = (now - item.time_added() as i64) / (24 * 60 * 60);
+                (age_days >= MIN_TEASER_AGE_DAYS).then(|| Teaser {
+                    title: item.title().to_string(),
+                    saved_ago: format_age(age_days),
+                })
+            })
+            .collect();
+        Self {
+            started: Instant::now(),
+            // start from a different teaser every time the screen shows up
+            seed: now as usize,
+            teasers,
+        }
+    }
+
+    pub fn current_teaser(&self) -> Option<&Teaser> {
+        if self.teasers.is_empty() {
+            return None;
+        }
+        let step = (self.started.elapsed().as_secs() / TEASER_SECS) as usize;
+        self.teasers
+            .get(self.seed.wrapping_add(step) % self.teasers.len())
+    }
+}
+
+fn format_age(days: i64) -> String {
+    let (count, unit) = if days >= 365 {
+        (days / 365, "year")
+    } else {
+        (days / 30, "month")
+    };
+    format!(
+        "you saved this {} {}{} ago",
+        count,
+        unit,
+        if count == 1 { "" } else { "s" }
+    )
+}
+
+pub fn render(f: &mut Frame, screen: &AmbientScreen, today_stats: &Stats, area: Rect) {
+    f.render_widget(Clear, area);
+    f.render_widget(
+        Block::default().style(Style::default().bg(OCEANIC_NEXT.base_00)),
+        area,
+    );
+    let rects = Layout::vertical([
+        Constraint::Min(5),
+        Constraint::Length(2),
+        Constraint::Length(4),
+    ])
+    .split(area);
+
+    logo::render(f, rects[0]);
+
+    let stats = Paragraph::new(Line::from(vec![
+        Span::styled("Today: ", Style::default().fg(OCEANIC_NEXT.base_04)),
+        Span::styled(
+            format!("{} added, {} read", today_stats.added(), today_stats.read()),
+            Style::default().fg(OCEANIC_NEXT.base_0b),
+        ),
+    ]))
+    .alignment(Alignment::Center);
+    f.render_widget(stats, rects[1]);
+
+    let mut lines = match screen.current_teaser() {
+        Some(teaser) => vec![
+            Line::from(Span::styled(
+                teaser.saved_ago.clone(),
+                Style::default().fg(OCEANIC_NEXT.base_04),
+            )),
+            Line::from(Span::styled(
+                teaser.title.clone(),
+                Style::default()
+                    .fg(OCEANIC_NEXT.base_0a)
+                    .add_modifier(Modifier::BOLD),
+            )),
+        ],
+        None => Vec::new(),
+    };
+    lines.push(Line::from(Span::styled(
+        "press any key",
+        Style::default()
+            .fg(OCEANIC_NEXT.base_03)
+            .add_modifier(Modifier::DIM),
+    )));
+    f.render_widget(Paragraph::new(lines).alignment(Alignment::Center), rects[2]);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn formats_age_of_saved_item() {
+        assert_eq!(format_age(45), "you saved this 1 month ago");
+        assert_eq!(format_age(200), "you saved this 6 months ago");
+        assert_eq!(format_age(3 * 365 + 10), "you saved this 3 years ago");
+    }
+}
@@ -0,0 +1,134 @@
+use std::io::Read;
+
+use base64::Engine;
+use dom_query::Document;
+use log::error;
+use reqwest::blocking::Client;
+use reqwest::Url;
+use serde::{Deserialize, Serialize};
+
+/// Stylesheets and images bigger than that stay remote
+const MAX_RESOURCE_BYTES: u64 = 10 * 1024 * 1024;
+
+/// What to keep next to the converted markdown of a downloaded article
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum ArchiveMode {
+    #[default]
+    Off,
+    /// html exactly as it was fetched
+    Html,
+    /// html with stylesheets and images inlined, scripts dropped
+    SingleFile,
+}
+
+/// Content type and body of a fetched resource
+type Resource = (String, Vec<u8>);
+
+fn fetch(client: &Client, url: &Url) -> anyhow::Result<Resource> {
+    let response = client.get(url.clone()).send()?.error_for_status()?;
+    let content_type = response
+        .headers()
+        .get(reqwest::header::CONTENT_TYPE)
+        .and_then(|v| v.to_str().ok())
+        .unwrap_or("application/octet-stream")
+        .to_string();
+    let mut bytes = Vec::new();
+    response
+        .take(MAX_RESOURCE_BYTES + 1)
+        .read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > MAX_RESOURCE_BYTES {
+        anyhow::bail!("resource is larger than {} bytes", MAX_RESOURCE_BYTES);
+    }
+    Ok((content_type, bytes))
+}
+
+/**
+Page as a single html file that opens without network. `url()` references inside stylesheets
+(fonts, backgrounds) are not inlined. Resources that fail to download are linked by absolute url.
+*/
+pub fn single_file(html: &str, page_url: &str, client: &Client) -> String {
+    inline_resources(html, page_url, |url| {
+        fetch(client, url)
+            .map_err(|e| error!("Failed to archive {}: {:#}", url, e))
+            .ok()
+    })
+}
+
+fn inline_resources(
+    html: &str,
+    page_url: &str,
+    fetch: impl Fn(&Url) -> Option<Resource>,
+) -> String {
+    let document = Document::from(html);
+    let base = Url::parse(page_url).ok();
+    let resolve = |link: &str| match &base {
+        Some(base) => base.join(link).ok(),
+        None => Url::parse(link).ok(),
+    };
+
+    document.select("script").remove();
+    document
+        .select(r#"link[rel="preload"], link[rel="prefetch"], link[rel="modulepreload"]"#)
+        .remove();
+
+    for link in document.select(r#"link[rel="stylesheet"][href]"#).iter() {
+        let Some(url) = link.attr("href").and_then(|href| resolve(&href)) else {
+            continue;
+        };
+        match fetch(&url) {
+            Some((_, css)) => {
+                link.replace_with_html(format!("<style>{}</style>", String::from_utf8_lossy(&css)))
+            }
+            None => link.set_attr("href", url.as_str()),
+        }
+    }
+
+    for img in document.select("img[src]").iter() {
+        let Some(url) = img.attr("src").and_then(|src| resolve(&src)) else {
+            continue;
+        };
+        // srcset would win over the inlined src
+        img.remove_attr("srcset");
+        match fetch(&url) {
+            Some((content_type, bytes)) => img.set_attr(
+                "src",
+                &format!(
+                    "data:{};base64,{}",
+                    content_type,
+                    base64::engine::general_purpose::STANDARD.encode(bytes)
+                ),
+            ),
+            None => img.set_attr("src", url.as_str()),
+        }
+    }
+    document.html().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn inlines_styles_and_images_and_drops_scripts() {
+        let html = r#"<html><head>
+            <link rel="stylesheet" href="/main.css"><script src="app.js"></script>
+            </head><body>
+            <img src="img/a.png" srcset="img/a@2x.png 2x"><img src="https://cdn.example.com/missing.png">
+            </body></html>"#;
+        let archived = inline_resources(html, "https://example.com/post/1", |url| {
+            match url.as_str() {
+                "https://example.com/main.css" => Some(("text/css".to_string(), b"p{}".to_vec())),
+                "https://example.com/post/img/a.png" => {
+                    Some(("image/png".to_string(), b"png".to_vec()))
+                }
+                _ => None,
+            }
+        });
+        assert!(archived.contains("<style>p{}</style>"));
+        assert!(!archived.contains("<script"));
+        assert!(archived.contains(r#"src="data:image/png;base64,cG5n""#));
+        assert!(!archived.contains("srcset"));
+        assert!(archived.contains(r#"src="https://cdn.example.com/missing.png""#));
+    }
+}
@@ -43,7 +43,7 @@ struct ServerInfo {
 impl PocketAuth {
     pub fn new() -> anyhow::Result<Self> {
         Ok(PocketAuth {
-            client: Client::new(),
+            client: crate::network::async_client_builder().build()?,
             runtime: Runtime::new().context("Failed to create Tokio runtime")?,
         })
     }
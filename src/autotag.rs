@@ -0,0 +1,94 @@
+use std::collections::HashMap;
+
+use crate::tagtree;
+
+/// Tags configured for the domain rank above any title match
+const DOMAIN_RULE_SCORE: usize = 10;
+
+fn words(text: &str) -> Vec<String> {
+    text.to_lowercase()
+        .split(|ch: char| !ch.is_alphanumeric() && ch != '+' && ch != '#')
+        .filter(|word| !word.is_empty())
+        .map(String::from)
+        .collect()
+}
+
+/// Number of title words the tag is made of, 0 when some of them are missing. The last level of
+/// hierarchical tags is matched, so `lang/rust` is suggested for a title about Rust
+fn title_score(tag: &str, title_words: &[String]) -> usize {
+    let tag_words = words(tagtree::label(tag));
+    let in_title = |word: &String| {
+        title_words
+            .iter()
+            .any(|title_word| title_word == word || *title_word == format!("{}s", word))
+    };
+    if !tag_words.is_empty() && tag_words.iter().all(in_title) {
+        tag_words.len()
+    } else {
+        0
+    }
+}
+
+fn domain_matches(domain: &str, rule: &str) -> bool {
+    let rule = rule.trim_start_matches("www.").to_lowercase();
+    domain == rule || domain.ends_with(&format!(".{}", rule))
+}
+
+/// Tags for an item, best first: the ones `rules` set for its domain, then known tags found in
+/// the title, longer matches first
+pub fn suggest(
+    title: &str,
+    url: &str,
+    rules: &HashMap<String, Vec<String>>,
+    known_tags: &[String],
+) -> Vec<String> {
+    let domain = url
+        .trim_start_matches("http://")
+        .trim_start_matches("https://")
+        .trim_start_matches("www.")
+        .split('/')
+        .next()
+        .unwrap_or("")
+        .to_lowercase();
+    let mut scores: HashMap<&str, usize> = HashMap::new();
+    for (rule, tags) in rules {
+        if domain_matches(&domain, rule) {
+            for tag in tags {
+                *scores.entry(tag.as_str()).or_default() += DOMAIN_RULE_SCORE;
+            }
+        }
+    }
+    let title_words = words(title);
+    for tag in known_tags {
+        let score = title_score(tag, &title_words);
+        if score > 0 {
+            *scores.entry(tag.as_str()).or_default() += score;
+        }
+    }
+    let mut ranked: Vec<(&str, usize)> = scores.into_iter().collect();
+    ranked.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(b.0)));
+    ranked.into_iter().map(|(tag, _)| tag.to_string()).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_domain_rules_above_title_words() {
+        let rules = HashMap::from([("lwn.net".to_string(), vec!["linux".to_string()])]);
+        let known = ["lang/rust", "machine learning", "go", "kernel", "linux"].map(String::from);
+        let suggested = suggest(
+            "Rust in the kernel: machine learning drivers",
+            "https://www.lwn.net/Articles/1",
+            &rules,
+            &known,
+        );
+        assert_eq!(
+            suggested,
+            ["linux", "machine learning", "kernel", "lang/rust"]
+        );
+
+        assert!(suggest("Going places", "https://notlwn.net/a", &rules, &known).is_empty());
+    }
+}
@@ -0,0 +1,511 @@
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File};
+use std::hash::{DefaultHasher, Hash, Hasher};
+use std::io::Read;
+use std::path::Path;
+
+use anyhow::{bail, Context};
+use chrono::DateTime;
+use itertools::Itertools;
+use serde::Deserialize;
+use serde_json::Map;
+
+use crate::browserhistory;
+use crate::pocket::{GetPocketSync, NewItem};
+use crate::storage::{self, Pocket, PocketItem};
+
+/// Folders every browser puts its bookmarks in, they say nothing about the links
+const ROOT_FOLDERS: [&str; 6] = [
+    "Bookmarks",
+    "Bookmarks Menu",
+    "Bookmarks Toolbar",
+    "Bookmarks bar",
+    "Other bookmarks",
+    "Mobile bookmarks",
+];
+
+/// Urls sent to Pocket in one request
+const PUSH_BATCH_SIZE: usize = 100;
+
+/// Link of a bookmarks export or of another read-later service, with its tags
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Bookmark {
+    pub url: String,
+    pub title: String,
+    /// Unix seconds
+    pub added: Option<i64>,
+    pub tags: Vec<String>,
+    pub archived: bool,
+    pub favorite: bool,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ImportFormat {
+    /// Netscape bookmark file of Firefox and Chrome
+    Bookmarks,
+    /// CSV export of Instapaper
+    Instapaper,
+    /// Export of Omnivore, the zip or the `metadata_*.json` files in it
+    Omnivore,
+    /// CSV backup of Raindrop.io
+    Raindrop,
+}
+
+impl ImportFormat {
+    pub fn parse(name: &str) -> anyhow::Result<Self> {
+        Ok(match name {
+            "bookmarks" => Self::Bookmarks,
+            "instapaper" => Self::Instapaper,
+            "omnivore" => Self::Omnivore,
+            "raindrop" => Self::Raindrop,
+            _ => bail!(
+                "Unknown import format {}, expected bookmarks, instapaper, omnivore or raindrop",
+                name
+            ),
+        })
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct ImportReport {
+    pub imported: usize,
+    /// Already in the library or in the file twice
+    pub duplicates: usize,
+    /// Not web pages, e.g. `place:` queries and bookmarklets
+    pub skipped: usize,
+    pub failed: Vec<String>,
+}
+
+impl ImportReport {
+    pub fn summary(&self) -> String {
+        let mut summary = format!(
+            "Imported {} items, {} already saved, {} skipped",
+            self.imported, self.duplicates, self.skipped
+        );
+        if !self.failed.is_empty() {
+            summary.push_str(&format!(", {} failed:", self.failed.len()));
+            for failure in &self.failed {
+                summary.push_str(&format!("\n  {}", failure));
+            }
+        }
+        summary
+    }
+}
+
+fn unescape(text: &str) -> String {
+    text.replace("&lt;", "<")
+        .replace("&gt;", ">")
+        .replace("&quot;", "\"")
+        .replace("&#39;", "'")
+        .replace("&amp;", "&")
+}
+
+/// Value of `name` in the attributes of a tag, names are case-insensitive
+fn attribute(tag: &str, name: &str) -> Option<String> {
+    let lower = tag.to_ascii_lowercase();
+    let pattern = format!(" {}=\"", name.to_ascii_lowercase());
+    let start = lower.find(&pattern)? + pattern.len();
+    let end = start + tag[start..].find('"')?;
+    Some(unescape(&tag[start..end]))
+}
+
+/// Text up to the closing tag, `rest` is what follows it
+fn text_until<'a>(html: &'a str, closing: &str) -> (String, &'a str) {
+    match html.to_ascii_lowercase().find(closing) {
+        Some(end) => (unescape(html[..end].trim()), &html[end + closing.len()..]),
+        None => (unescape(html.trim()), ""),
+    }
+}
+
+/// Netscape bookmark file as exported by Firefox and Chrome. Folders are `<H3>` headers
+/// followed by a `<DL>` list of their links and subfolders
+pub fn parse(html: &str) -> Vec<Bookmark> {
+    let mut bookmarks = Vec::new();
+    // open lists, with the folder each of them belongs to
+    let mut folders: Vec<Option<String>> = Vec::new();
+    let mut pending_folder = None;
+    let mut rest = html;
+    while let Some(start) = rest.find('<') {
+        let Some(end) = rest[start..].find('>').map(|end| start + end) else {
+            break;
+        };
+        let tag = &rest[start..=end];
+        rest = &rest[end + 1..];
+        let name = tag[1..]
+            .split(|c: char| c.is_whitespace() || c == '>')
+            .next()
+            .unwrap_or_default()
+            .to_ascii_lowercase();
+        match name.as_str() {
+            "h3" => {
+                let (folder, after) = text_until(rest, "</h3>");
+                pending_folder = Some(folder);
+                rest = after;
+            }
+            "dl" => folders.push(pending_folder.take()),
+            "/dl" => {
+                folders.pop();
+            }
+            "a" => {
+                let (title, after) = text_until(rest, "</a>");
+                rest = after;
+                let Some(url) = attribute(tag, "href") else {
+                    continue;
+                };
+                let mut tags: Vec<String> = folders
+                    .iter()
+                    .flatten()
+                    .filter(|folder| !ROOT_FOLDERS.contains(&folder.as_str()))
+                    .map(|folder| folder.to_lowercase())
+                    .collect();
+                tags.extend(
+                    attribute(tag, "tags")
+                        .unwrap_or_default()
+                        .split(',')
+                        .map(|tag| tag.trim().to_lowercase())
+                        .filter(|tag| !tag.is_empty()),
+                );
+                let tags = tags.into_iter().unique().collect();
+                bookmarks.push(Bookmark {
+                    url,
+                    title,
+                    added: attribute(tag, "add_date").and_then(|date| date.parse().ok()),
+                    tags,
+                    ..Bookmark::default()
+                });
+            }
+            _ => {}
+        }
+    }
+    bookmarks
+}
+
+/// Records of a CSV file with a header line, by column name. Quoted fields may have commas,
+/// doubled quotes and line breaks
+fn parse_csv(text: &str) -> Vec<HashMap<String, String>> {
+    let mut records: Vec<Vec<String>> = Vec::new();
+    let mut record = Vec::new();
+    let mut field = String::new();
+    let mut quoted = false;
+    let mut chars = text.trim_start_matches('\u{feff}').chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' if quoted && chars.peek() == Some(&'"') => {
+                field.push('"');
+                chars.next();
+            }
+            '"' => quoted = !quoted,
+            ',' if !quoted => record.push(std::mem::take(&mut field)),
+            '\r' if !quoted => {}
+            '\n' if !quoted => {
+                record.push(std::mem::take(&mut field));
+                records.push(std::mem::take(&mut record));
+            }
+            _ => field.push(c),
+        }
+    }
+    if !field.is_empty() || !record.is_empty() {
+        record.push(field);
+        records.push(record);
+    }
+    let mut records = records.into_iter();
+    let header = records.next().unwrap_or_default();
+    records
+        .filter(|record| record.iter().any(|field| !field.is_empty()))
+        .map(|record| header.iter().cloned().zip(record).collect())
+        .collect()
+}
+
+fn rfc3339_secs(date: &str) -> Option<i64> {
+    DateTime::parse_from_rfc3339(date)
+        .ok()
+        .map(|date| date.timestamp())
+}
+
+/// Columns `URL,Title,Selection,Folder,Timestamp` and `Tags` (a JSON list) in newer exports.
+/// The Unread, Archive and Starred folders are states, other folders become tags
+fn parse_instapaper(csv: &str) -> Vec<Bookmark> {
+    parse_csv(csv)
+        .into_iter()
+        .map(|record| {
+            let field = |name: &str| record.get(name).cloned().unwrap_or_default();
+            let folder = field("Folder");
+            let mut tags: Vec<String> = serde_json::from_str(&field("Tags")).unwrap_or_default();
+            if !matches!(folder.as_str(), "Unread" | "Archive" | "Starred" | "") {
+                tags.insert(0, folder.to_lowercase());
+            }
+            Bookmark {
+                url: field("URL"),
+                title: field("Title"),
+                added: field("Timestamp").parse().ok(),
+                tags,
+                archived: folder == "Archive",
+                favorite: folder == "Starred",
+            }
+        })
+        .collect()
+}
+
+/// Columns `id,title,note,excerpt,url,folder,tags,created,cover,highlights,favorite`, tags
+/// are separated by commas within their field
+fn parse_raindrop(csv: &str) -> Vec<Bookmark> {
+    parse_csv(csv)
+        .into_iter()
+        .map(|record| {
+            let field = |name: &str| record.get(name).cloned().unwrap_or_default();
+            let mut tags: Vec<String> = field("tags")
+                .split(',')
+                .map(|tag| tag.trim().to_lowercase())
+                .filter(|tag| !tag.is_empty())
+                .collect();
+            let folder = field("folder");
+            if !folder.is_empty() && folder != "Unsorted" {
+                tags.insert(0, folder.to_lowercase());
+            }
+            Bookmark {
+                url: field("url"),
+                title: field("title"),
+                added: rfc3339_secs(&field("created")),
+                tags: tags.into_iter().unique().collect(),
+                archived: false,
+                favorite: field("favorite") == "true",
+            }
+        })
+        .collect()
+}
+
+#[derive(Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct OmnivoreItem {
+    url: String,
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    labels: Vec<String>,
+    saved_at: Option<String>,
+    #[serde(default)]
+    state: String,
+}
+
+/// Every `metadata_*.json` of the export is a list of saved pages
+fn parse_omnivore(json: &str) -> anyhow::Result<Vec<Bookmark>> {
+    let items: Vec<OmnivoreItem> = serde_json::from_str(json)?;
+    Ok(items
+        .into_iter()
+        .map(|item| Bookmark {
+            url: item.url,
+            title: item.title,
+            added: item.saved_at.as_deref().and_then(rfc3339_secs),
+            tags: item
+                .labels
+                .iter()
+                .map(|label| label.to_lowercase())
+                .collect(),
+            archived: item.state == "Archived",
+            favorite: false,
+        })
+        .collect())
+}
+
+fn read_omnivore(path: &Path) -> anyhow::Result<Vec<Bookmark>> {
+    if path.extension().is_none_or(|extension| extension != "zip") {
+        return parse_omnivore(&fs::read_to_string(path)?);
+    }
+    let mut archive = zip::ZipArchive::new(File::open(path)?)?;
+    let mut bookmarks = Vec::new();
+    for index in 0..archive.len() {
+        let mut file = archive.by_index(index)?;
+        let name = file
+            .name()
+            .rsplit('/')
+            .next()
+            .unwrap_or_default()
+            .to_string();
+        if name.starts_with("metadata_") && name.ends_with(".json") {
+            let mut json = String::new();
+            file.read_to_string(&mut json)?;
+            bookmarks.extend(parse_omnivore(&json).with_context(|| format!("Broken {}", name))?);
+        }
+    }
+    Ok(bookmarks)
+}
+
+fn read(format: ImportFormat, path: &Path) -> anyhow::Result<Vec<Bookmark>> {
+    let text =
+        || fs::read_to_string(path).with_context(|| format!("Failed to read {}", path.display()));
+    Ok(match format {
+        ImportFormat::Bookmarks => parse(&text()?),
+        ImportFormat::Instapaper => parse_instapaper(&text()?),
+        ImportFormat::Raindrop => parse_raindrop(&text()?),
+        ImportFormat::Omnivore => read_omnivore(path)
+            .with_context(|| format!("Failed to read the Omnivore export {}", path.display()))?,
+    })
+}
+
+/// Item that lives in the delta only, its id can't clash with Pocket's numeric ones
+fn local_item(bookmark: &Bookmark) -> PocketItem {
+    let mut hasher = DefaultHasher::new();
+    bookmark.url.hash(&mut hasher);
+    let item_id = format!("{}{:x}", storage::LOCAL_ID_PREFIX, hasher.finish());
+    let added = bookmark
+        .added
+        .unwrap_or_else(|| chrono::Utc::now().timestamp())
+        .to_string();
+    let tags: Map<String, serde_json::Value> = bookmark
+        .tags
+        .iter()
+        .map(|tag| {
+            (
+                tag.clone(),
+                serde_json::json!({ "item_id": item_id, "tag": tag }),
+            )
+        })
+        .collect();
+    PocketItem {
+        item_id: item_id.clone(),
+        favorite: if bookmark.favorite { "1" } else { "0" }.to_string(),
+        status: if bookmark.archived { "1" } else { "0" }.to_string(),
+        time_added: added.clone(),
+        time_updated: added,
+        time_read: "0".to_string(),
+        time_favorited: "0".to_string(),
+        given_title: Some(bookmark.title.clone()),
+        resolved_title: Some(bookmark.title.clone()),
+        resolved_url: Some(bookmark.url.clone()),
+        tags,
+        ..Default::default()
+    }
+}
+
+/// Imports the links of an export that aren't in the library yet (`saved_urls` are normalized
+/// with `browserhistory::normalize_url`). They are added to Pocket with `push`, the next sync
+/// brings them in; otherwise they are kept as local items in the delta
+pub fn import(
+    path: &Path,
+    format: ImportFormat,
+    saved_urls: &HashSet<String>,
+    delta_file: &Path,
+    push: Option<&GetPocketSync>,
+) -> anyhow::Result<ImportReport> {
+    let mut report = ImportReport::default();
+    let mut seen = saved_urls.clone();
+    let mut new_bookmarks = Vec::new();
+    for bookmark in read(format, path)? {
+        if !bookmark.url.starts_with("http://") && !bookmark.url.starts_with("https://") {
+            report.skipped += 1;
+        } else if !seen.insert(browserhistory::normalize_url(&bookmark.url)) {
+            report.duplicates += 1;
+        } else {
+            new_bookmarks.push(bookmark);
+        }
+    }
+
+    match push {
+        Some(client) => {
+            for batch in new_bookmarks.chunks(PUSH_BATCH_SIZE) {
+                let items: Vec<NewItem> = batch
+                    .iter()
+                    .map(|bookmark| NewItem {
+                        url: &bookmark.url,
+                        tags: &bookmark.tags,
+                        time_added: bookmark.added,
+                        archived: bookmark.archived,
+                        favorite: bookmark.favorite,
+                    })
+                    .collect();
+                let outcomes = client.add_all(&items)?;
+                for (bookmark, outcome) in batch.iter().zip(outcomes) {
+                    match outcome {
+                        Ok(()) => report.imported += 1,
+                        Err(e) => report.failed.push(format!("{}: {}", bookmark.url, e)),
+                    }
+                }
+            }
+        }
+        None => {
+            let list: Map<String, serde_json::Value> = new_bookmarks
+                .iter()
+                .map(local_item)
+                .map(|item| Ok((item.item_id.clone(), serde_json::to_value(&item)?)))
+                .collect::<anyhow::Result<_>>()?;
+            report.imported = list.len();
+            storage::append_to_delta(
+                delta_file,
+                &Pocket {
+                    list,
+                    ..Pocket::default()
+                },
+            )?;
+        }
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folders_become_tags() {
+        let html = r#"<!DOCTYPE NETSCAPE-Bookmark-file-1>
+<TITLE>Bookmarks</TITLE>
+<H1>Bookmarks Menu</H1>
+<DL><p>
+    <DT><H3 ADD_DATE="1700000000">Bookmarks Toolbar</H3>
+    <DL><p>
+        <DT><H3>Rust</H3>
+        <DL><p>
+            <DT><A HREF="https://blog.rust-lang.org/" ADD_DATE="1700000100" TAGS="lang,News">Rust &amp; friends</A>
+        </DL><p>
+        <DT><A HREF="place:sort=8">Recent</A>
+    </DL><p>
+    <DT><A href="https://lwn.net/">LWN</A>
+</DL>"#;
+        let bookmarks = parse(html);
+        assert_eq!(
+            bookmarks[0],
+            Bookmark {
+                url: "https://blog.rust-lang.org/".to_string(),
+                title: "Rust & friends".to_string(),
+                added: Some(1700000100),
+                tags: vec!["rust".to_string(), "lang".to_string(), "news".to_string()],
+                ..Bookmark::default()
+            }
+        );
+        assert_eq!(bookmarks[1].url, "place:sort=8");
+        assert!(bookmarks[1].tags.is_empty());
+        assert_eq!(bookmarks[2].title, "LWN");
+
+        let item = local_item(&bookmarks[0]);
+        assert!(storage::is_local_id(&item.item_id));
+        assert_eq!(item.tags.len(), 3);
+    }
+
+    #[test]
+    fn reads_other_read_later_services() {
+        let instapaper = "URL,Title,Selection,Folder,Timestamp,Tags\n\
+            https://a.org/1,\"Commas, \"\"quotes\"\"\",,Archive,1700000000,\"[\"\"db\"\"]\"\n\
+            https://a.org/2,Two,,Rust,1700000100,\n";
+        let items = parse_instapaper(instapaper);
+        assert_eq!(items[0].title, "Commas, \"quotes\"");
+        assert!(items[0].archived);
+        assert_eq!(items[0].tags, ["db"]);
+        assert_eq!(items[1].tags, ["rust"]);
+        assert_eq!(items[1].added, Some(1700000100));
+
+        let raindrop =
+            "id,title,note,excerpt,url,folder,tags,created,cover,highlights,favorite\r\n\
+            1,Post,,,https://b.org/,Reading,\"go, Rust\",2023-11-14T22:13:20.000Z,,,true\r\n";
+        let items = parse_raindrop(raindrop);
+        assert_eq!(items[0].tags, ["reading", "go", "rust"]);
+        assert_eq!(items[0].added, Some(1700000000));
+        assert!(items[0].favorite);
+
+        let omnivore = r#"[{"id":"x","url":"https://c.org/","title":"C","labels":["Later"],
+            "savedAt":"2023-11-14T22:13:20.000Z","state":"Archived"}]"#;
+        let items = parse_omnivore(omnivore).unwrap();
+        assert_eq!(items[0].tags, ["later"]);
+        assert!(items[0].archived);
+        assert_eq!(local_item(&items[0]).status, "1");
+    }
+}
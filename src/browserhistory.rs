@@ -0,0 +1,194 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use anyhow::Context;
+use log::{error, info};
+use rusqlite::Connection;
+
+use crate::config::BrowserHistoryConfig;
+
+/// Seconds between 1601-01-01 (chrome's epoch) and 1970-01-01
+const CHROME_EPOCH_OFFSET: i64 = 11_644_473_600;
+
+/**
+Last visit time (unix seconds) per normalized url from every configured browser.
+Browsers keep their databases locked while running, so a copy of the file (and its WAL) is queried.
+A browser that fails to load is logged and skipped.
+*/
+pub fn last_visits(config: &BrowserHistoryConfig) -> HashMap<String, u64> {
+    let mut visits = HashMap::new();
+    let firefox = config.firefox.clone().or_else(default_firefox_history);
+    let chrome = config.chrome.clone().or_else(default_chrome_history);
+
+    let sources = [
+        (
+            firefox,
+            "SELECT p.url, MAX(v.visit_date) FROM moz_places p \
+             JOIN moz_historyvisits v ON v.place_id = p.id GROUP BY p.url",
+            firefox_time_to_unix as fn(i64) -> i64,
+        ),
+        (
+            chrome,
+            "SELECT url, last_visit_time FROM urls",
+            chrome_time_to_unix,
+        ),
+    ];
+    for (path, query, to_unix) in sources {
+        if let Some(path) = path {
+            match load_visits(&path, query, to_unix) {
+                Ok(rows) => {
+                    info!("Loaded {} urls from {}", rows.len(), path.display());
+                    for (url, ts) in rows {
+                        let entry = visits.entry(normalize_url(&url)).or_insert(0);
+                        *entry = (*entry).max(ts);
+                    }
+                }
+                Err(e) => error!("Failed to read browser history {}: {:#}", path.display(), e),
+            }
+        }
+    }
+    visits
+}
+
+fn load_visits(
+    path: &Path,
+    query: &str,
+    to_unix: fn(i64) -> i64,
+) -> anyhow::Result<Vec<(String, u64)>> {
+    let copy = std::env::temp_dir().join(format!("pkt-tui-history-{}", std::process::id()));
+    let wal_copy = with_suffix(&copy, "-wal");
+    fs::copy(path, &copy).context("Failed to copy history database")?;
+    // recent visits might not be checkpointed into the main file yet
+    let wal = with_suffix(path, "-wal");
+    if wal.exists() {
+        fs::copy(&wal, &wal_copy).context("Failed to copy history WAL")?;
+    }
+    let result = (|| {
+        let conn = Connection::open(&copy)?;
+        let mut stmt = conn.prepare(query)?;
+        let rows = stmt
+            .query_map([], |row| {
+                Ok((row.get::<_, String>(0)?, row.get::<_, Option<i64>>(1)?))
+            })?
+            .filter_map(|row| row.ok())
+            .filter_map(|(url, ts)| {
+                ts.map(to_unix)
+                    .filter(|ts| *ts > 0)
+                    .map(|ts| (url, ts as u64))
+            })
+            .collect();
+        Ok(rows)
+    })();
+    for file in [copy.clone(), wal_copy, with_suffix(&copy, "-shm")] {
+        let _ = fs::remove_file(file);
+    }
+    result
+}
+
+fn with_suffix(path: &Path, suffix: &str) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(suffix);
+    PathBuf::from(name)
+}
+
+/// Scheme, `www.`, fragment and trailing slash don't make a different page
+/// Parameters added by newsletters and feeds that point to the same page
+fn is_tracking_param(param: &str) -> bool {
+    let name = param.split('=').next().unwrap_or(param);
+    name.starts_with("utm_") || matches!(name, "fbclid" | "gclid" | "mc_cid" | "mc_eid")
+}
+
+pub fn normalize_url(url: &str) -> String {
+    let url = url
+        .trim_start_matches("http://")
+        .trim_start_matches("https://")
+        .trim_start_matches("www.");
+    let url = url.split('#').next().unwrap_or(url);
+    let (path, query) = url.split_once('?').unwrap_or((url, ""));
+    let query: Vec<&str> = query
+        .split('&')
+        .filter(|param| !param.is_empty() && !is_tracking_param(param))
+        .collect();
+    let path = path.trim_end_matches('/');
+    if query.is_empty() {
+        path.to_string()
+    } else {
+        format!("{}?{}", path, query.join("&"))
+    }
+}
+
+fn home_dir() -> Option<PathBuf> {
+    std::env::var_os("HOME").map(PathBuf::from)
+}
+
+fn default_chrome_history() -> Option<PathBuf> {
+    let home = home_dir()?;
+    [
+        ".config/google-chrome/Default/History",
+        ".config/chromium/Default/History",
+        "Library/Application Support/Google/Chrome/Default/History",
+    ]
+    .iter()
+    .map(|p| home.join(p))
+    .find(|p| p.exists())
+}
+
+/// Firefox keeps history per profile, the first profile with a history database wins
+fn default_firefox_history() -> Option<PathBuf> {
+    let home = home_dir()?;
+    [
+        ".mozilla/firefox",
+        "Library/Application Support/Firefox/Profiles",
+    ]
+    .iter()
+    .filter_map(|dir| fs::read_dir(home.join(dir)).ok())
+    .flat_map(|entries| entries.filter_map(|e| e.ok()))
+    .map(|entry| entry.path().join("places.sqlite"))
+    .find(|p| p.exists())
+}
+
+/// Firefox keeps microseconds since unix epoch
+fn firefox_time_to_unix(ts: i64) -> i64 {
+    ts / 1_000_000
+}
+
+/// Chrome keeps microseconds since 1601-01-01
+fn chrome_time_to_unix(ts: i64) -> i64 {
+    ts / 1_000_000 - CHROME_EPOCH_OFFSET
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn reads_chrome_history() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("History");
+        let conn = Connection::open(&path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE urls (url TEXT, last_visit_time INTEGER);
+             INSERT INTO urls VALUES ('https://www.example.com/post/#intro', 13350000000000000);
+             INSERT INTO urls VALUES ('https://example.com/never', 0);",
+        )
+        .unwrap();
+        drop(conn);
+
+        let config = BrowserHistoryConfig {
+            enabled: true,
+            firefox: Some(dir.path().join("missing.sqlite")),
+            chrome: Some(path),
+        };
+        let visits = last_visits(&config);
+        assert_eq!(visits.len(), 1);
+        assert_eq!(
+            visits.get(&normalize_url("http://example.com/post")),
+            Some(&(13_350_000_000 - CHROME_EPOCH_OFFSET as u64))
+        );
+        assert_eq!(
+            normalize_url("https://www.example.com/post/?utm_source=rss&id=3#top"),
+            "example.com/post?id=3"
+        );
+    }
+}
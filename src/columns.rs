@@ -0,0 +1,58 @@
+use serde::{Deserialize, Serialize};
+
+/// Column of the item table, `columns` in config.json lists them in order
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Column {
+    /// Day added, the group name when grouped, the queue position of pinned items
+    Date,
+    /// Title with the type and tags below it
+    Title,
+    Tags,
+    Domain,
+    WordCount,
+    ReadingTime,
+    Authors,
+    /// Site or authors, the way the domain statistics count items
+    Source,
+}
+
+impl Column {
+    /// The layout without a `columns` setting
+    pub fn defaults() -> Vec<Column> {
+        vec![Column::Date, Column::Title]
+    }
+
+    /// Width of the columns that don't depend on the shown items
+    pub fn fixed_width(self) -> Option<u16> {
+        match self {
+            Column::Date | Column::Title => None,
+            Column::Tags | Column::Authors => Some(24),
+            Column::Domain | Column::Source => Some(22),
+            Column::WordCount => Some(6),
+            Column::ReadingTime => Some(7),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn columns_are_read_by_name() {
+        let columns: Vec<Column> =
+            serde_json::from_str(r#"["date", "title", "reading_time", "source"]"#).unwrap();
+        assert_eq!(
+            columns,
+            [
+                Column::Date,
+                Column::Title,
+                Column::ReadingTime,
+                Column::Source
+            ]
+        );
+        assert!(serde_json::from_str::<Vec<Column>>(r#"["date", "colour"]"#).is_err());
+        assert_eq!(Column::Title.fixed_width(), None);
+    }
+}
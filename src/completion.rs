@@ -0,0 +1,147 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem},
+    Frame,
+};
+
+use crate::TableColors;
+
+const MAX_SHOWN: usize = 6;
+
+/// Candidates for the word before the cursor of a prompt, Tab and Shift-Tab cycle through them
+#[derive(Clone, Default)]
+pub struct Completions {
+    /// Where the completed word starts in the input
+    word_start: usize,
+    candidates: Vec<String>,
+    /// Candidate put into the input, `None` until the first Tab
+    selected: Option<usize>,
+}
+
+/// Start of the word that ends at the cursor
+pub fn word_start(input: &str, cursor: usize, separators: &[char]) -> usize {
+    input[..cursor].rfind(separators).map_or(0, |pos| {
+        pos + input[pos..].chars().next().map_or(1, char::len_utf8)
+    })
+}
+
+impl Completions {
+    /// Options starting with the typed part of the word, ignoring case
+    pub fn new(input: &str, word_start: usize, cursor: usize, options: &[String]) -> Self {
+        let typed = input[word_start..cursor].to_lowercase();
+        let mut candidates: Vec<String> = options
+            .iter()
+            .filter(|option| {
+                option.to_lowercase().starts_with(&typed) && option.len() > typed.len()
+            })
+            .cloned()
+            .collect();
+        candidates.sort_by_key(|candidate| candidate.to_lowercase());
+        candidates.dedup();
+        Self {
+            word_start,
+            candidates,
+            selected: None,
+        }
+    }
+
+    /// Puts the next candidate (previous one for a negative step) in place of the word. Returns
+    /// the cursor after it, `None` when there is nothing to complete
+    pub fn cycle(&mut self, input: &mut String, cursor: usize, step: isize) -> Option<usize> {
+        if self.candidates.is_empty() {
+            return None;
+        }
+        let count = self.candidates.len() as isize;
+        let next = match self.selected {
+            Some(selected) => (selected as isize + step).rem_euclid(count),
+            None if step < 0 => count - 1,
+            None => 0,
+        } as usize;
+        let candidate = &self.candidates[next];
+        input.replace_range(self.word_start..cursor, candidate);
+        self.selected = Some(next);
+        Some(self.word_start + candidate.len())
+    }
+
+    /// Rest of the first candidate, shown dimmed after the cursor until Tab is pressed
+    pub fn hint(&self, cursor: usize) -> Option<&str> {
+        if self.selected.is_some() {
+            return None;
+        }
+        let typed = cursor - self.word_start;
+        self.candidates
+            .first()
+            .and_then(|candidate| candidate.get(typed..))
+    }
+}
+
+/// Menu of the candidates above the prompt in `area`
+pub fn render(f: &mut Frame, completions: &Completions, colors: &TableColors, area: Rect) {
+    if completions.candidates.is_empty() {
+        return;
+    }
+    let first_shown = completions
+        .selected
+        .map_or(0, |selected| (selected + 1).saturating_sub(MAX_SHOWN));
+    let shown = completions.candidates.len().min(MAX_SHOWN);
+    let height = shown as u16 + 2;
+    let menu = Rect::new(
+        area.x + 1,
+        area.y.saturating_sub(height),
+        32.min(area.width.saturating_sub(2)),
+        height.min(area.y),
+    );
+    let items: Vec<ListItem> = completions
+        .candidates
+        .iter()
+        .enumerate()
+        .skip(first_shown)
+        .take(shown)
+        .map(|(i, candidate)| {
+            let style = if Some(i) == completions.selected {
+                Style::default().fg(Color::Black).bg(Color::White)
+            } else {
+                Style::default().fg(colors.row_fg)
+            };
+            ListItem::new(candidate.as_str()).style(style)
+        })
+        .collect();
+    f.render_widget(Clear, menu);
+    f.render_widget(
+        List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title_bottom(format!(" Tab {} ", completions.candidates.len()))
+                    .border_style(Style::new().fg(colors.footer_border_color))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().bg(Color::Black)),
+        menu,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn cycles_through_candidates_in_both_directions() {
+        let tags = ["rust", "Rustls", "ruby", "go"].map(String::from);
+        let mut input = "lang tag:ru".to_string();
+        let start = word_start(&input, input.len(), &[' ']) + "tag:".len();
+        let mut completions = Completions::new(&input, start, input.len(), &tags);
+        assert_eq!(completions.hint(input.len()), Some("by"));
+
+        let end = input.len();
+        let cursor = completions.cycle(&mut input, end, 1).unwrap();
+        assert_eq!(input, "lang tag:ruby");
+        let cursor = completions.cycle(&mut input, cursor, 1).unwrap();
+        assert_eq!(input, "lang tag:rust");
+        let cursor = completions.cycle(&mut input, cursor, -1).unwrap();
+        assert_eq!(input, "lang tag:ruby");
+        completions.cycle(&mut input, cursor, -1).unwrap();
+        assert_eq!(input, "lang tag:Rustls");
+    }
+}
@@ -0,0 +1,182 @@
+use anyhow::Context;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::archive::ArchiveMode;
+use crate::columns::Column;
+use crate::downloads::DownloadsConfig;
+use crate::extraction::ExtractionConfig;
+use crate::images::ImagesConfig;
+use crate::logging::LoggingConfig;
+use crate::network::NetworkConfig;
+use crate::notes::NotesExportConfig;
+use crate::popup::{PopupGeometry, PopupKind};
+use crate::prss::filters::FeedFilter;
+use crate::readingstats::{StatsPanel, StatsPeriods, WeekStart};
+use crate::share::ShareTemplate;
+use crate::statesync::StateSyncConfig;
+use crate::summary::SummaryConfig;
+use crate::termcaps::TerminalConfig;
+
+pub const CONFIG_FILE: &str = "config.json";
+
+/// User settings from `config.json`. Every field is optional, missing ones fall back to defaults
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    /// Queries like `tag:security OR domain:lwn.net` to get alerted about
+    pub watch_queries: Vec<String>,
+    pub browser_history: BrowserHistoryConfig,
+    /// Seconds of inactivity before the ambient screen shows up, 0 disables it
+    pub ambient_after_secs: u64,
+    /// Sync and RSS fetch age in the footer turns red past this many minutes, 0 disables it
+    pub stale_after_mins: u64,
+    /// Days between checks of the local list against the whole list in Pocket, 0 disables them
+    pub verify_sync_days: u64,
+    /// Time zone for dates and day grouping: `local`, `utc` or an IANA name like `Europe/Berlin`
+    pub timezone: String,
+    /// `rolling` (last 7/30 days) or `calendar` (current week/month) stats
+    pub stats_periods: StatsPeriods,
+    /// `monday` or `sunday`, used by calendar stats
+    pub week_start: WeekStart,
+    /// Downloaded articles are also written as notes when set
+    pub notes_export: Option<NotesExportConfig>,
+    /// Pocket Premium: download articles from the permanent library copy when there is one
+    pub permanent_copies: bool,
+    pub images: ImagesConfig,
+    /// `off`, `html` or `single_file`: original page kept next to the downloaded markdown
+    pub archive: ArchiveMode,
+    /// Readability settings and user agent for downloads, with per-domain overrides
+    pub extraction: ExtractionConfig,
+    pub downloads: DownloadsConfig,
+    /// Per feed url (or `*` for all feeds) language and NSFW filters
+    pub rss_filters: HashMap<String, FeedFilter>,
+    /// Feed items already saved to Pocket are hidden instead of marked as saved
+    pub rss_hide_saved: bool,
+    /// Hidden feed items are forgotten after this many days, 0 keeps them forever
+    pub rss_hidden_expiry_days: u64,
+    /// Cap on the items of all feeds together, the newest are kept
+    pub rss_max_items: Option<usize>,
+    /// Command that plays podcast and video enclosures, e.g. `mpv`; the browser is used when unset
+    pub media_player: Option<String>,
+    /// Shell command that reads text from stdin aloud for `P`, e.g. `espeak-ng --stdin`; off
+    /// unless set
+    pub tts: Option<String>,
+    /// Desktop notifications for new RSS items and finished downloads
+    pub notifications: bool,
+    /// Top image of the selected item in the excerpt pane, on terminals with kitty or sixel graphics
+    pub thumbnails: bool,
+    /// Proxy, extra CA certificates and request timeout for every connection
+    pub network: NetworkConfig,
+    /// Size and position per popup, e.g. `{"help": {"width": 30, "position": "right"}}`
+    pub popups: HashMap<PopupKind, PopupGeometry>,
+    /// Tags suggested in the tag editor per domain, e.g. `{"lwn.net": ["linux"]}`
+    pub tag_rules: HashMap<String, Vec<String>>,
+    /// Formats offered by `y` to copy the selected item, see `ShareTemplate` for placeholders
+    pub share_templates: Vec<ShareTemplate>,
+    /// Endpoint for `gs` summaries of downloaded articles, off unless set
+    pub summary: Option<SummaryConfig>,
+    /// Search and the tag popup filter match typed letters in order with gaps, best matches first
+    pub fuzzy_search: bool,
+    /// Titles in the table are OSC 8 hyperlinks to the item, Ctrl+click opens them in kitty,
+    /// wezterm and other terminals that support them
+    pub hyperlinks: bool,
+    /// Titles too long for the column continue on the second line of the row, in place of the tags
+    pub wrap_titles: bool,
+    /// Columns of the item table in order: `date`, `title`, `tags`, `domain`, `word_count`,
+    /// `reading_time`, `authors`, `source`
+    pub columns: Vec<Column>,
+    /// Reading stats above the table: `expanded`, `collapsed` to one line or `hidden`
+    pub stats_panel: StatsPanel,
+    /// Mouse capture and keyboard enhancements, both on unless the terminal can't do them
+    pub terminal: TerminalConfig,
+    /// WebDAV collection or git repository that the reading queue, hidden RSS items, feeds and
+    /// read events are shared through, off unless set
+    pub state_sync: Option<StateSyncConfig>,
+    /// Level, file and rotation of the log, `PKT_TUI_LOG` and `PKT_TUI_LOG_FILE` override them
+    pub logging: LoggingConfig,
+}
+
+impl Default for Config {
+    fn default() -> Self {
+        Self {
+            watch_queries: Vec::new(),
+            browser_history: BrowserHistoryConfig::default(),
+            ambient_after_secs: 300,
+            stale_after_mins: 60,
+            verify_sync_days: 7,
+            timezone: "local".to_string(),
+            stats_periods: StatsPeriods::default(),
+            week_start: WeekStart::default(),
+            notes_export: None,
+            permanent_copies: false,
+            images: ImagesConfig::default(),
+            archive: ArchiveMode::default(),
+            extraction: ExtractionConfig::default(),
+            downloads: DownloadsConfig::default(),
+            rss_filters: HashMap::new(),
+            rss_hide_saved: false,
+            rss_hidden_expiry_days: 90,
+            rss_max_items: None,
+            media_player: None,
+            tts: None,
+            notifications: false,
+            thumbnails: false,
+            network: NetworkConfig::default(),
+            popups: HashMap::new(),
+            tag_rules: HashMap::new(),
+            share_templates: ShareTemplate::defaults(),
+            summary: None,
+            fuzzy_search: false,
+            wrap_titles: false,
+            hyperlinks: false,
+            columns: Column::defaults(),
+            stats_panel: StatsPanel::default(),
+            terminal: TerminalConfig::default(),
+            state_sync: None,
+            logging: LoggingConfig::default(),
+        }
+    }
+}
+
+/// Marks items as read when their url was visited in the browser after the item was saved
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BrowserHistoryConfig {
+    pub enabled: bool,
+    /// Path to firefox `places.sqlite`, the first found profile is used when not set
+    pub firefox: Option<PathBuf>,
+    /// Path to chrome `History` database, the default profile is used when not set
+    pub chrome: Option<PathBuf>,
+}
+
+impl Config {
+    pub fn load(path: &Path) -> anyhow::Result<Self> {
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content)
+                .with_context(|| format!("Failed to parse {}", path.display())),
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(Self::default()),
+            Err(e) => Err(e.into()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn missing_fields_fall_back_to_defaults() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(CONFIG_FILE);
+        assert!(Config::load(&path).unwrap().watch_queries.is_empty());
+
+        fs::write(&path, r#"{"watch_queries": ["tag:rust"]}"#).unwrap();
+        assert_eq!(Config::load(&path).unwrap().watch_queries, ["tag:rust"]);
+
+        fs::write(&path, "{").unwrap();
+        assert!(Config::load(&path).is_err());
+    }
+}
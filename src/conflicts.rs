@@ -0,0 +1,124 @@
+use std::collections::{BTreeSet, HashMap};
+use std::time::Instant;
+
+use crate::storage::PocketItem;
+use crate::TableRow;
+
+/// Title and tags of an item, the fields edited both here and in other Pocket apps
+#[derive(Clone, Debug, PartialEq)]
+pub struct ItemState {
+    pub title: String,
+    pub tags: BTreeSet<String>,
+}
+
+impl ItemState {
+    pub fn of(item: &PocketItem) -> Self {
+        Self {
+            // renames are kept with newlines replaced, Pocket's titles may have them
+            title: item.title().replace('\n', " ").trim().to_string(),
+            tags: item.tags().cloned().collect(),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Resolution {
+    KeepLocal,
+    KeepRemote,
+    /// Tags of both sides, the title from Pocket
+    MergeTags,
+}
+
+/// Item edited in this session that came back from a sync different from how it was left
+#[derive(Clone, Debug, PartialEq)]
+pub struct Conflict {
+    pub item_id: String,
+    pub local: ItemState,
+    pub remote: ItemState,
+}
+
+impl Conflict {
+    /// Title and tags the item ends up with
+    pub fn resolved(&self, resolution: Resolution) -> ItemState {
+        match resolution {
+            Resolution::KeepLocal => self.local.clone(),
+            Resolution::KeepRemote => self.remote.clone(),
+            Resolution::MergeTags => ItemState {
+                title: self.remote.title.clone(),
+                tags: self.local.tags.union(&self.remote.tags).cloned().collect(),
+            },
+        }
+    }
+}
+
+/// How items were left by renames and tag edits since the last sync
+#[derive(Default)]
+pub struct LocalEdits {
+    edits: HashMap<String, ItemState>,
+    /// Kept after the edits are taken, a sync that started earlier may not have them yet
+    last_edit: Option<Instant>,
+}
+
+impl LocalEdits {
+    /// Called after an edit is applied to `item`
+    pub fn record(&mut self, item: &PocketItem) {
+        self.edits.insert(item.item_id.clone(), ItemState::of(item));
+        self.last_edit = Some(Instant::now());
+    }
+
+    pub fn edited_since(&self, time: Instant) -> bool {
+        self.last_edit.is_some_and(|edit| edit >= time)
+    }
+
+    /// Edited items that differ in the synced list, the edits are forgotten afterwards: what
+    /// matches went through, deleted items have nothing left to resolve
+    pub fn take_conflicts(&mut self, synced: &[PocketItem]) -> Vec<Conflict> {
+        let edits = std::mem::take(&mut self.edits);
+        synced
+            .iter()
+            .filter_map(|item| {
+                let local = edits.get(&item.item_id)?;
+                let remote = ItemState::of(item);
+                (*local != remote).then(|| Conflict {
+                    item_id: item.item_id.clone(),
+                    local: local.clone(),
+                    remote,
+                })
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tutorial::sample_item;
+
+    #[test]
+    fn edits_changed_elsewhere_conflict() {
+        let mut edits = LocalEdits::default();
+        let started = Instant::now();
+        assert!(!edits.edited_since(started));
+        edits.record(&sample_item(
+            1,
+            0,
+            "Mine",
+            "https://a.org/1",
+            &["rust"],
+            None,
+        ));
+        edits.record(&sample_item(2, 0, "Same", "https://a.org/2", &["go"], None));
+        let synced = [
+            sample_item(1, 0, "Theirs", "https://a.org/1", &["db"], None),
+            sample_item(2, 0, "Same", "https://a.org/2", &["go"], None),
+        ];
+        let conflicts = edits.take_conflicts(&synced);
+        assert_eq!(conflicts.len(), 1);
+        let merged = conflicts[0].resolved(Resolution::MergeTags);
+        assert_eq!(merged.title, "Theirs");
+        assert_eq!(merged.tags.iter().collect::<Vec<_>>(), ["db", "rust"]);
+        assert_eq!(conflicts[0].resolved(Resolution::KeepLocal).title, "Mine");
+        assert!(edits.take_conflicts(&synced).is_empty());
+        assert!(edits.edited_since(started));
+    }
+}
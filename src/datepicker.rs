@@ -0,0 +1,222 @@
+use chrono::{Datelike, Days, Months, NaiveDate, Weekday};
+use ratatui::{
+    layout::Rect,
+    style::{Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph},
+    Frame,
+};
+
+use crate::logo::OCEANIC_NEXT;
+use crate::TableColors;
+
+/// What the picked date is used for
+#[derive(Clone, Copy, PartialEq)]
+pub enum DatePickerPurpose {
+    Jump,
+    /// Two dates are picked, the first one is the start of the range
+    Range,
+}
+
+/// Month calendar moved around with vim keys, opened with `gd` and `gr`
+#[derive(Clone)]
+pub struct DatePicker {
+    pub purpose: DatePickerPurpose,
+    cursor: NaiveDate,
+    range_start: Option<NaiveDate>,
+    week_start: Weekday,
+}
+
+pub enum DatePickerAction {
+    Continue,
+    Jump(NaiveDate),
+    Range(NaiveDate, NaiveDate),
+    Cancel,
+}
+
+impl DatePicker {
+    pub fn new(purpose: DatePickerPurpose, today: NaiveDate, week_start: Weekday) -> Self {
+        Self {
+            purpose,
+            cursor: today,
+            range_start: None,
+            week_start,
+        }
+    }
+
+    fn move_days(&mut self, days: i64) {
+        let moved = if days >= 0 {
+            self.cursor.checked_add_days(Days::new(days as u64))
+        } else {
+            self.cursor.checked_sub_days(Days::new(days.unsigned_abs()))
+        };
+        self.cursor = moved.unwrap_or(self.cursor);
+    }
+
+    fn move_months(&mut self, months: i32) {
+        let moved = if months >= 0 {
+            self.cursor.checked_add_months(Months::new(months as u32))
+        } else {
+            self.cursor
+                .checked_sub_months(Months::new(months.unsigned_abs()))
+        };
+        self.cursor = moved.unwrap_or(self.cursor);
+    }
+
+    pub fn handle_key(&mut self, key: crossterm::event::KeyCode) -> DatePickerAction {
+        use crossterm::event::KeyCode::*;
+        match key {
+            Esc | Char('q') => return DatePickerAction::Cancel,
+            Char('h') | Left => self.move_days(-1),
+            Char('l') | Right => self.move_days(1),
+            Char('k') | Up => self.move_days(-7),
+            Char('j') | Down => self.move_days(7),
+            Char('H') | PageUp => self.move_months(-1),
+            Char('L') | PageDown => self.move_months(1),
+            Char('K') => self.move_months(-12),
+            Char('J') => self.move_months(12),
+            Enter => match (self.purpose, self.range_start) {
+                (DatePickerPurpose::Jump, _) => return DatePickerAction::Jump(self.cursor),
+                (DatePickerPurpose::Range, None) => self.range_start = Some(self.cursor),
+                (DatePickerPurpose::Range, Some(start)) => {
+                    let (from, to) = if start <= self.cursor {
+                        (start, self.cursor)
+                    } else {
+                        (self.cursor, start)
+                    };
+                    return DatePickerAction::Range(from, to);
+                }
+            },
+            _ => {}
+        }
+        DatePickerAction::Continue
+    }
+
+    /// Days of the cursor's month as weeks, `None` pads the first and the last week
+    fn weeks(&self) -> Vec<Vec<Option<NaiveDate>>> {
+        let first = self
+            .cursor
+            .with_day(1)
+            .expect("every month has a first day");
+        let padding = (first.weekday().num_days_from_monday() + 7
+            - self.week_start.num_days_from_monday()) as usize
+            % 7;
+        let mut days: Vec<Option<NaiveDate>> = vec![None; padding];
+        days.extend(
+            first
+                .iter_days()
+                .take_while(|day| day.month() == first.month())
+                .map(Some),
+        );
+        days.chunks(7).map(|week| week.to_vec()).collect()
+    }
+
+    fn in_range(&self, day: NaiveDate) -> bool {
+        self.range_start.is_some_and(|start| {
+            (start <= day && day <= self.cursor) || (self.cursor <= day && day <= start)
+        })
+    }
+}
+
+/// Hint in the footer area, the calendar pops up above it
+pub fn render(f: &mut Frame, picker: &DatePicker, colors: &TableColors, area: Rect) {
+    let hint = match (picker.purpose, picker.range_start) {
+        (DatePickerPurpose::Jump, _) => "Jump to date: Enter - jump".to_string(),
+        (DatePickerPurpose::Range, None) => "Added between: Enter - pick the first day".to_string(),
+        (DatePickerPurpose::Range, Some(start)) => format!(
+            "Added between {} and ...: Enter - pick the last day",
+            start.format("%Y-%m-%d")
+        ),
+    };
+    f.render_widget(
+        Paragraph::new(format!(
+            "{} | h/l - day | j/k - week | H/L - month | J/K - year | Esc - cancel",
+            hint
+        ))
+        .style(Style::new().fg(colors.row_fg).bg(colors.buffer_bg))
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::new().fg(colors.footer_border_color))
+                .border_type(BorderType::Rounded),
+        ),
+        area,
+    );
+
+    let mut lines = vec![Line::from(
+        (0..7)
+            .map(|i| {
+                let weekday = picker.week_start.num_days_from_monday() + i;
+                let name = Weekday::try_from((weekday % 7) as u8).expect("0..7 is a weekday");
+                Span::raw(format!(" {:>2} ", &name.to_string()[..2]))
+            })
+            .collect::<Vec<_>>(),
+    )
+    .style(Style::default().fg(OCEANIC_NEXT.base_04))];
+    for week in picker.weeks() {
+        let spans: Vec<Span> = week
+            .iter()
+            .map(|day| match day {
+                Some(day) => {
+                    let style = if *day == picker.cursor {
+                        Style::default()
+                            .bg(OCEANIC_NEXT.base_0d)
+                            .fg(OCEANIC_NEXT.base_00)
+                            .add_modifier(Modifier::BOLD)
+                    } else if picker.in_range(*day) {
+                        Style::default().bg(OCEANIC_NEXT.base_02).fg(colors.row_fg)
+                    } else {
+                        Style::default().fg(colors.row_fg)
+                    };
+                    Span::styled(format!(" {:>2} ", day.day()), style)
+                }
+                None => Span::raw("    "),
+            })
+            .collect();
+        lines.push(Line::from(spans));
+    }
+
+    let height = lines.len() as u16 + 2;
+    let width = 7 * 4 + 2;
+    let popup = Rect::new(
+        area.x + 1,
+        area.y.saturating_sub(height),
+        width.min(area.width.saturating_sub(2)),
+        height.min(area.y),
+    );
+    f.render_widget(Clear, popup);
+    f.render_widget(
+        Paragraph::new(lines).block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" {} ", picker.cursor.format("%B %Y")))
+                .border_style(Style::new().fg(colors.footer_border_color))
+                .border_type(BorderType::Rounded),
+        ),
+        popup,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyCode;
+
+    #[test]
+    fn picks_a_range_in_either_order() {
+        let day = |d| NaiveDate::from_ymd_opt(2024, 3, d).unwrap();
+        let mut picker = DatePicker::new(DatePickerPurpose::Range, day(15), Weekday::Mon);
+        // March 2024 starts on a Friday
+        assert_eq!(picker.weeks()[0][..4], [None; 4]);
+        assert_eq!(picker.weeks()[0][4], Some(day(1)));
+        assert_eq!(picker.weeks().concat().iter().flatten().count(), 31);
+
+        picker.handle_key(KeyCode::Enter);
+        picker.handle_key(KeyCode::Char('k'));
+        picker.handle_key(KeyCode::Char('h'));
+        match picker.handle_key(KeyCode::Enter) {
+            DatePickerAction::Range(from, to) => assert_eq!((from, to), (day(7), day(15))),
+            _ => panic!("expected a range"),
+        }
+    }
+}
@@ -0,0 +1,140 @@
+use crate::storage::PocketItem;
+use crate::{tagtree, tutorial};
+
+/// Enough items for the footer, the stats and scrolling to look like a real reading list
+const ITEM_COUNT: usize = 240;
+/// Items are spread over this many days back from today
+const SPAN_DAYS: usize = 400;
+
+/// (tag, site, subjects)
+const TOPICS: [(&str, &str, [&str; 4]); 8] = [
+    (
+        "lang/rust",
+        "blog.rust-lang.org",
+        [
+            "async traits",
+            "the borrow checker",
+            "const generics",
+            "error handling",
+        ],
+    ),
+    (
+        "lang/go",
+        "go.dev",
+        ["generics", "the scheduler", "structured logging", "fuzzing"],
+    ),
+    (
+        "distributed",
+        "martinfowler.com",
+        [
+            "consensus",
+            "event sourcing",
+            "leader election",
+            "idempotency",
+        ],
+    ),
+    (
+        "databases",
+        "use-the-index-luke.com",
+        ["B-trees", "query planners", "write-ahead logs", "MVCC"],
+    ),
+    (
+        "lowlevel",
+        "lwn.net",
+        ["io_uring", "CPU caches", "memory allocators", "eBPF"],
+    ),
+    (
+        "tools",
+        "jvns.ca",
+        [
+            "git internals",
+            "terminal emulators",
+            "tmux",
+            "shell scripting",
+        ],
+    ),
+    (
+        "ml",
+        "arxiv.org",
+        ["transformers", "embeddings", "quantization", "retrieval"],
+    ),
+    (
+        "career",
+        "staffeng.com",
+        ["code review", "design docs", "on-call", "technical debt"],
+    ),
+];
+
+const TEMPLATES: [&str; 6] = [
+    "A practical guide to {}",
+    "What I learned about {} the hard way",
+    "{} explained",
+    "Deep dive: {}",
+    "Why {} is harder than it looks",
+    "Notes on {}",
+];
+
+/// Multiplicative hash so that neighbouring items get unrelated topics and ages
+fn mix(i: usize, salt: usize) -> usize {
+    (i.wrapping_mul(2654435761)
+        .wrapping_add(salt.wrapping_mul(40503)))
+        >> 7
+}
+
+fn demo_item(i: usize) -> PocketItem {
+    let (tag, site, subjects) = TOPICS[mix(i, 1) % TOPICS.len()];
+    let subject = subjects[mix(i, 2) % subjects.len()];
+    let title = TEMPLATES[mix(i, 3) % TEMPLATES.len()].replace("{}", subject);
+    let title = title[..1].to_uppercase() + &title[1..];
+    let slug = subject.to_lowercase().replace(' ', "-");
+    // every fifth item is a talk and every seventh a paper, the rest are articles
+    let (url, authors) = if i.is_multiple_of(5) {
+        (
+            format!("https://www.youtube.com/watch?v=demo{}", i),
+            Some(vec![format!("YT:{} talks", tagtree::label(tag))]),
+        )
+    } else if i.is_multiple_of(7) {
+        (format!("https://{}/papers/{}-{}.pdf", site, slug, i), None)
+    } else {
+        (format!("https://{}/posts/{}-{}", site, slug, i), None)
+    };
+    let mut tags = vec![tag];
+    match mix(i, 4) % 6 {
+        0 => tags.push("read"),
+        1 => tags.push("top"),
+        2 => tags.push("downloaded"),
+        _ => {}
+    }
+    let days_ago = (mix(i, 5) % SPAN_DAYS) as i64;
+    let mut item = tutorial::sample_item(i + 1, days_ago, &title, &url, &tags, authors);
+    item.word_count = (400 + mix(i, 6) % 5000).to_string();
+    item.excerpt = format!(
+        "Generated demo item about {}. Nothing here comes from a Pocket account.",
+        subject
+    );
+    item
+}
+
+/// Generated reading list for `--demo`, the same on every start
+pub fn sample_items() -> Vec<PocketItem> {
+    (0..ITEM_COUNT).map(demo_item).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::TableRow;
+
+    #[test]
+    fn generates_a_stable_mixed_list() {
+        let items = sample_items();
+        assert_eq!(items.len(), ITEM_COUNT);
+        assert_eq!(items[3].title(), sample_items()[3].title());
+        for item_type in ["article", "video", "pdf"] {
+            assert!(items.iter().any(|item| item.item_type() == item_type));
+        }
+        let ids: std::collections::HashSet<&str> =
+            items.iter().map(|item| item.item_id.as_str()).collect();
+        assert_eq!(ids.len(), ITEM_COUNT);
+    }
+}
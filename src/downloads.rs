@@ -0,0 +1,507 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use dom_smoothie::{Article, Readability};
+use log::error;
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+
+use crate::pocket::GetPocketSync;
+use crate::statefile::StateFile;
+use crate::storage::PocketItem;
+use crate::{archive, config, events, images, markdown, notes, pdfindex, storage, utils, TableRow};
+
+const DOWNLOAD_QUEUE_FILE: &str = "download_queue.json";
+
+/// Types without a configured limit are downloaded one at a time
+const DEFAULT_CONCURRENCY: usize = 1;
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct DownloadsConfig {
+    /// Parallel downloads per item type, e.g. `{"article": 4, "pdf": 1}`
+    pub concurrency: HashMap<String, usize>,
+}
+
+impl Default for DownloadsConfig {
+    fn default() -> Self {
+        Self {
+            concurrency: HashMap::from([("article".to_string(), 4), ("pdf".to_string(), 1)]),
+        }
+    }
+}
+
+impl DownloadsConfig {
+    fn limit(&self, kind: &str) -> usize {
+        self.concurrency
+            .get(kind)
+            .copied()
+            .unwrap_or(DEFAULT_CONCURRENCY)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct DownloadJob {
+    pub item_id: String,
+    /// Item type: `article` or `pdf`
+    pub kind: String,
+    pub title: String,
+}
+
+/// Outcome of a finished job. Pdf downloads return the title found in the document
+pub type DownloadResult = anyhow::Result<Option<String>>;
+
+#[derive(Default, Serialize, Deserialize)]
+struct SavedQueue {
+    paused: bool,
+    pending: Vec<DownloadJob>,
+}
+
+/**
+Downloads running in background threads. Pending jobs start in queue order as long as their type
+is under its concurrency limit. Pending and running jobs are kept in `download_queue.json`, so
+an interrupted queue picks up where it stopped on the next start.
+*/
+pub struct DownloadQueue {
+    pending: Vec<DownloadJob>,
+    running: Vec<DownloadJob>,
+    /// Jobs that failed in this session with the error, `r` in the popup queues them again
+    failed: Vec<(DownloadJob, String)>,
+    paused: bool,
+    file: StateFile,
+    sender: Sender<(DownloadJob, DownloadResult)>,
+    receiver: Receiver<(DownloadJob, DownloadResult)>,
+}
+
+impl DownloadQueue {
+    pub fn new() -> Self {
+        Self::with_file(StateFile::default())
+    }
+
+    pub fn load() -> Self {
+        Self::with_file(StateFile::at(DOWNLOAD_QUEUE_FILE))
+    }
+
+    fn with_file(file: StateFile) -> Self {
+        let saved: SavedQueue = file.load();
+        let (sender, receiver) = mpsc::channel();
+        Self {
+            pending: saved.pending,
+            running: Vec::new(),
+            failed: Vec::new(),
+            paused: saved.paused,
+            file,
+            sender,
+            receiver,
+        }
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        // running jobs go back to the front of the queue if the app is closed mid-download
+        self.file.save(&SavedQueue {
+            paused: self.paused,
+            pending: self
+                .running
+                .iter()
+                .chain(self.pending.iter())
+                .cloned()
+                .collect(),
+        })
+    }
+
+    pub fn pending(&self) -> &[DownloadJob] {
+        &self.pending
+    }
+
+    pub fn running(&self) -> &[DownloadJob] {
+        &self.running
+    }
+
+    pub fn failed(&self) -> &[(DownloadJob, String)] {
+        &self.failed
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.pending.is_empty() && self.running.is_empty()
+    }
+
+    fn contains(&self, item_id: &str) -> bool {
+        self.pending
+            .iter()
+            .chain(self.running.iter())
+            .any(|job| job.item_id == item_id)
+    }
+
+    /// Returns false if the item is already queued or downloading
+    pub fn enqueue(&mut self, job: DownloadJob) -> anyhow::Result<bool> {
+        if self.contains(&job.item_id) {
+            return Ok(false);
+        }
+        self.pending.push(job);
+        self.save()?;
+        Ok(true)
+    }
+
+    pub fn remove_pending(&mut self, idx: usize) -> anyhow::Result<()> {
+        if idx < self.pending.len() {
+            self.pending.remove(idx);
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Moves a pending job by `delta` positions, returns its new position
+    pub fn move_pending(&mut self, idx: usize, delta: isize) -> anyhow::Result<usize> {
+        if idx >= self.pending.len() {
+            return Ok(idx);
+        }
+        let new_idx = (idx as isize + delta).clamp(0, self.pending.len() as isize - 1) as usize;
+        if new_idx != idx {
+            let job = self.pending.remove(idx);
+            self.pending.insert(new_idx, job);
+            self.save()?;
+        }
+        Ok(new_idx)
+    }
+
+    pub fn retry_failed(&mut self) -> anyhow::Result<()> {
+        for (job, _) in std::mem::take(&mut self.failed) {
+            self.enqueue(job)?;
+        }
+        Ok(())
+    }
+
+    /// Running downloads finish, no new ones start while paused
+    pub fn toggle_pause(&mut self) -> anyhow::Result<()> {
+        self.paused = !self.paused;
+        self.save()
+    }
+
+    /// Takes the pending jobs that can start now and marks them as running
+    pub fn ready_jobs(&mut self, config: &DownloadsConfig) -> Vec<DownloadJob> {
+        if self.paused {
+            return Vec::new();
+        }
+        let mut running: HashMap<String, usize> = HashMap::new();
+        for job in &self.running {
+            *running.entry(job.kind.clone()).or_insert(0) += 1;
+        }
+        let mut ready = Vec::new();
+        let mut i = 0;
+        while i < self.pending.len() {
+            let count = running.entry(self.pending[i].kind.clone()).or_insert(0);
+            if *count < config.limit(&self.pending[i].kind) {
+                *count += 1;
+                let job = self.pending.remove(i);
+                self.running.push(job.clone());
+                ready.push(job);
+            } else {
+                i += 1;
+            }
+        }
+        ready
+    }
+
+    pub fn spawn<F>(&self, job: DownloadJob, download: F)
+    where
+        F: FnOnce() -> DownloadResult + Send + 'static,
+    {
+        let sender = self.sender.clone();
+        thread::spawn(move || {
+            let result = download();
+            let _ = sender.send((job, result));
+            events::wake();
+        });
+    }
+
+    /// Jobs finished successfully since the last call, failed ones are kept in `failed`
+    pub fn finished(&mut self) -> anyhow::Result<Vec<(DownloadJob, Option<String>)>> {
+        let mut succeeded = Vec::new();
+        let mut any_finished = false;
+        for (job, result) in self.receiver.try_iter() {
+            any_finished = true;
+            self.running
+                .retain(|running| running.item_id != job.item_id);
+            match result {
+                Ok(title) => succeeded.push((job, title)),
+                Err(e) => {
+                    error!("Failed to download {}: {:#}", job.title, e);
+                    self.failed.push((job, format!("{:#}", e)));
+                }
+            }
+        }
+        if any_finished {
+            self.save()?;
+        }
+        Ok(succeeded)
+    }
+}
+
+pub fn article_path(item: &PocketItem) -> PathBuf {
+    // Create sanitized filename from title
+    // let title = item.title();
+    // let filename = sanitize_filename::sanitize(title); //sanitazie_filename might be redundant dependency
+    let filename = if item.item_id.is_empty() {
+        "untitled"
+    } else {
+        item.item_id.as_str()
+    };
+    Path::new("articles").join(format!("{}.md", filename))
+}
+
+/// Everything a download needs, owned so it can run on a worker thread
+pub struct Downloader {
+    pub pocket_client: GetPocketSync,
+    pub client: Client,
+    pub config: config::Config,
+}
+
+impl Downloader {
+    /// Premium users get the copy Pocket saved, it survives paywalls and dead links
+    pub fn permanent_copy(&self, url: &str) -> Option<String> {
+        if !self.config.permanent_copies {
+            return None;
+        }
+        self.pocket_client.permanent_copy(url).unwrap_or_else(|e| {
+            error!("{:#}", e);
+            None
+        })
+    }
+
+    pub fn download_page(&self, url: &str) -> anyhow::Result<String> {
+        let user_agent = self.config.extraction.for_url(url).user_agent;
+        let response = self
+            .client
+            .get(url)
+            .header("User-Agent", user_agent)
+            .header(
+                "Accept",
+                "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8",
+            )
+            .header("Accept-Language", "en-US,en;q=0.5")
+            .header("Connection", "keep-alive")
+            .header("Upgrade-Insecure-Requests", "1")
+            .header("Sec-Fetch-Dest", "document")
+            .header("Sec-Fetch-Mode", "navigate")
+            .header("Sec-Fetch-Site", "none")
+            .header("Sec-Fetch-User", "?1")
+            .send()?;
+        let status = response.status();
+        let html_content = response
+            .text()
+            .unwrap_or_else(|_| "No response body".to_string());
+        if !status.is_success() {
+            return Err(anyhow::anyhow!(
+                "Failed to download article: HTTP {} - {}",
+                status,
+                html_content
+            ));
+        }
+        Ok(html_content)
+    }
+
+    /// Markdown of the whole page, readability's text and the article part of the markdown
+    fn extract(&self, url: &str, html_content: &str) -> anyhow::Result<(String, Article, String)> {
+        let md = html2md::rewrite_html(html_content, true);
+
+        // Configure and parse with dom_smoothie
+        let settings = self.config.extraction.for_url(url);
+        let cfg = settings.readability_config();
+
+        let mut readability = Readability::new(html_content, Some(url), Some(cfg))?;
+        // Readability::new(md.as_str(), Some(item.url()), Some(cfg))?;
+        let article: Article = readability.parse()?;
+
+        let result = markdown::normalize_markdown(&md, &article.text_content, &settings.boundaries);
+        Ok((md, article, result))
+    }
+
+    /// Article text of a page that isn't saved anywhere, e.g. for previews
+    pub fn readable_markdown(&self, url: &str) -> anyhow::Result<String> {
+        let html_content = self.download_page(url)?;
+        let (_, _, result) = self.extract(url, &html_content)?;
+        Ok(result)
+    }
+
+    /// Runs readability over the page and writes the markdown (and the note, if configured)
+    pub fn convert_article(&self, item: &PocketItem, html_content: &str) -> anyhow::Result<()> {
+        // Create articles directory if it doesn't exist
+        fs::create_dir_all("articles")?;
+        let path = article_path(item);
+        let filename = path
+            .file_stem()
+            .map(|stem| stem.to_string_lossy().to_string())
+            .unwrap_or_default();
+
+        let (md, article, result) = self.extract(item.url(), html_content)?;
+
+        let mut content = String::new();
+
+        // Add article content
+        content.push_str(&article.text_content);
+        content.push_str("--------\n\n");
+        content.push_str(&md);
+        content.push_str("--------\n\n");
+        content.push_str(&result);
+        if self.config.images.enabled {
+            content = images::localize_images(
+                &content,
+                &self.client,
+                &self.config.images,
+                &Path::new("articles").join("assets").join(&filename),
+                &format!("assets/{}", filename),
+            )?;
+        }
+
+        // Save to file
+        fs::write(&path, content)?;
+
+        // Frontmatter and tags are only for notes, the file above is parsed by epub export
+        if let Some(notes_config) = &self.config.notes_export {
+            let date = item.date();
+            notes::write_note(
+                notes_config,
+                &notes::NoteData {
+                    item_id: &item.item_id,
+                    title: item.title(),
+                    url: item.url(),
+                    date: &date,
+                    tags: item.tags().map(|tag| tag.as_str()).collect(),
+                    authors: item
+                        .authors
+                        .iter()
+                        .flatten()
+                        .map(|author| author.as_str())
+                        .collect(),
+                    highlights: &item.annotations,
+                    markdown: &result,
+                },
+            )?;
+        }
+        Ok(())
+    }
+
+    pub fn download_article(&self, item: &PocketItem) -> anyhow::Result<()> {
+        let html_content = match self.permanent_copy(item.url()) {
+            Some(html) => html,
+            None => self.download_page(item.url())?,
+        };
+        self.convert_article(item, &html_content)?;
+
+        let path = article_path(item);
+        match self.config.archive {
+            archive::ArchiveMode::Off => {}
+            archive::ArchiveMode::Html => fs::write(path.with_extension("html"), &html_content)?,
+            archive::ArchiveMode::SingleFile => fs::write(
+                path.with_extension("html"),
+                archive::single_file(&html_content, item.url(), &self.client),
+            )?,
+        }
+
+        // Mark as downloaded in Pocket, read-only mode keeps the local copy only
+        if !self.pocket_client.is_read_only() && !storage::is_local_id(&item.item_id) {
+            self.pocket_client
+                .mark_as_downloaded(item.id().parse::<usize>()?)?;
+        }
+        Ok(())
+    }
+
+    /// Returns the title found in the pdf, with author and page count when known
+    pub fn download_pdf(&self, item: &PocketItem) -> anyhow::Result<Option<String>> {
+        // Create pdfs directory if it doesn't exist
+        fs::create_dir_all("pdfs")?;
+
+        // Extract filename from URL
+        let url = item.url();
+        let filename = url
+            .split('/')
+            .last()
+            .unwrap_or("download.pdf")
+            .replace("%20", "_");
+
+        // Construct full path
+        let path = Path::new("pdfs").join(&filename);
+
+        let response = self.client.get(url).send()?.error_for_status()?;
+        let content = response.bytes()?;
+        fs::write(&path, content)?;
+        if !self.pocket_client.is_read_only() && !storage::is_local_id(&item.item_id) {
+            self.pocket_client
+                .mark_as_downloaded(item.id().parse::<usize>()?)?;
+        }
+
+        let pdf_info = utils::extract_pdf_title(path.as_path())?;
+        if let Some(info) = &pdf_info {
+            pdfindex::store(&item.item_id, &info.text)?;
+        }
+        Ok(pdf_info.and_then(|info| info.display_title()))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn job(id: &str, kind: &str) -> DownloadJob {
+        DownloadJob {
+            item_id: id.to_string(),
+            kind: kind.to_string(),
+            title: id.to_string(),
+        }
+    }
+
+    fn ids(jobs: &[DownloadJob]) -> Vec<&str> {
+        jobs.iter().map(|job| job.item_id.as_str()).collect()
+    }
+
+    #[test]
+    fn starts_jobs_within_per_type_limits() {
+        let config = DownloadsConfig {
+            concurrency: HashMap::from([("article".to_string(), 2)]),
+        };
+        let mut queue = DownloadQueue::new();
+        for (id, kind) in [
+            ("1", "pdf"),
+            ("2", "pdf"),
+            ("3", "article"),
+            ("4", "article"),
+        ] {
+            assert!(queue.enqueue(job(id, kind)).unwrap());
+        }
+        assert!(queue.enqueue(job("5", "article")).unwrap());
+        assert!(!queue.enqueue(job("1", "pdf")).unwrap());
+        assert_eq!(queue.move_pending(4, -4).unwrap(), 0);
+
+        queue.toggle_pause().unwrap();
+        assert!(queue.ready_jobs(&config).is_empty());
+        queue.toggle_pause().unwrap();
+
+        assert_eq!(ids(&queue.ready_jobs(&config)), ["5", "1", "3"]);
+        assert_eq!(ids(queue.pending()), ["2", "4"]);
+        assert!(queue.ready_jobs(&config).is_empty());
+
+        queue.spawn(job("1", "pdf"), || Ok(Some("title".to_string())));
+        queue.spawn(job("3", "article"), || Err(anyhow::anyhow!("HTTP 404")));
+        let mut finished = Vec::new();
+        while queue.running().len() > 1 {
+            finished.extend(queue.finished().unwrap());
+        }
+        assert_eq!(finished, [(job("1", "pdf"), Some("title".to_string()))]);
+        assert_eq!(
+            queue.failed(),
+            [(job("3", "article"), "HTTP 404".to_string())]
+        );
+        assert_eq!(ids(&queue.ready_jobs(&config)), ["2", "4"]);
+        assert_eq!(ids(queue.running()), ["5", "2", "4"]);
+
+        queue.retry_failed().unwrap();
+        assert_eq!(ids(queue.pending()), ["3"]);
+    }
+}
@@ -0,0 +1,243 @@
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::Path;
+
+use pulldown_cmark::{html, Event, Parser};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipWriter};
+
+/// Separator between sections of a downloaded article file, see `download_and_convert_article`
+const SECTION_SEPARATOR: &str = "--------\n\n";
+
+pub struct EpubChapter {
+    pub title: String,
+    pub url: String,
+    pub date_added: String,
+    pub markdown: String,
+}
+
+/// Downloaded article keeps raw text, raw markdown and normalized markdown. The last one is the most readable
+pub fn article_markdown(file_content: &str) -> &str {
+    file_content
+        .rsplit(SECTION_SEPARATOR)
+        .next()
+        .unwrap_or(file_content)
+}
+
+/// Raw text and raw markdown of a downloaded article, `None` for files in another format
+pub fn article_sections(file_content: &str) -> Option<(&str, &str)> {
+    let (plain, rest) = file_content.split_once(SECTION_SEPARATOR)?;
+    let (raw_markdown, _) = rest.rsplit_once(SECTION_SEPARATOR)?;
+    Some((plain, raw_markdown))
+}
+
+fn escape_xml(text: &str) -> String {
+    text.replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+        .replace('"', "&quot;")
+}
+
+/// Raw html from the page is escaped, it's rarely valid xhtml
+fn markdown_to_xhtml(markdown: &str) -> String {
+    let parser = Parser::new(markdown).map(|event| match event {
+        Event::Html(raw) | Event::InlineHtml(raw) => Event::Text(raw),
+        other => other,
+    });
+    let mut output = String::new();
+    html::push_html(&mut output, parser);
+    output
+}
+
+fn chapter_xhtml(chapter: &EpubChapter) -> String {
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head><title>{title}</title></head>
+<body>
+<h1>{title}</h1>
+<p><a href="{url}">{url}</a><br />Saved {date}</p>
+{body}
+</body>
+</html>
+"#,
+        title = escape_xml(&chapter.title),
+        url = escape_xml(&chapter.url),
+        date = escape_xml(&chapter.date_added),
+        body = markdown_to_xhtml(&chapter.markdown),
+    )
+}
+
+fn content_opf(book_title: &str, identifier: &str, chapters: &[EpubChapter]) -> String {
+    let modified = chrono::Utc::now().format("%Y-%m-%dT%H:%M:%SZ");
+    let manifest: String = (1..=chapters.len())
+        .map(|i| {
+            format!(
+                "    <item id=\"chapter-{i}\" href=\"chapter-{i}.xhtml\" media-type=\"application/xhtml+xml\"/>\n"
+            )
+        })
+        .collect();
+    let spine: String = (1..=chapters.len())
+        .map(|i| format!("    <itemref idref=\"chapter-{i}\"/>\n"))
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<package xmlns="http://www.idpf.org/2007/opf" version="3.0" unique-identifier="book-id">
+  <metadata xmlns:dc="http://purl.org/dc/elements/1.1/">
+    <dc:identifier id="book-id">{identifier}</dc:identifier>
+    <dc:title>{title}</dc:title>
+    <dc:creator>pkt-tui</dc:creator>
+    <dc:language>en</dc:language>
+    <meta property="dcterms:modified">{modified}</meta>
+  </metadata>
+  <manifest>
+    <item id="nav" href="nav.xhtml" media-type="application/xhtml+xml" properties="nav"/>
+    <item id="ncx" href="toc.ncx" media-type="application/x-dtbncx+xml"/>
+{manifest}  </manifest>
+  <spine toc="ncx">
+{spine}  </spine>
+</package>
+"#,
+        identifier = escape_xml(identifier),
+        title = escape_xml(book_title),
+    )
+}
+
+fn nav_xhtml(chapters: &[EpubChapter]) -> String {
+    let entries: String = chapters
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| {
+            format!(
+                "      <li><a href=\"chapter-{}.xhtml\">{}</a></li>\n",
+                i + 1,
+                escape_xml(&chapter.title)
+            )
+        })
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<!DOCTYPE html>
+<html xmlns="http://www.w3.org/1999/xhtml" xmlns:epub="http://www.idpf.org/2007/ops">
+<head><title>Contents</title></head>
+<body>
+  <nav epub:type="toc">
+    <h1>Contents</h1>
+    <ol>
+{entries}    </ol>
+  </nav>
+</body>
+</html>
+"#
+    )
+}
+
+/// EPUB 2 table of contents, older e-readers don't understand nav.xhtml
+fn toc_ncx(book_title: &str, identifier: &str, chapters: &[EpubChapter]) -> String {
+    let points: String = chapters
+        .iter()
+        .enumerate()
+        .map(|(i, chapter)| {
+            format!(
+                "    <navPoint id=\"chapter-{n}\" playOrder=\"{n}\"><navLabel><text>{title}</text></navLabel><content src=\"chapter-{n}.xhtml\"/></navPoint>\n",
+                n = i + 1,
+                title = escape_xml(&chapter.title)
+            )
+        })
+        .collect();
+    format!(
+        r#"<?xml version="1.0" encoding="utf-8"?>
+<ncx xmlns="http://www.daisy.org/z3986/2005/ncx/" version="2005-1">
+  <head><meta name="dtb:uid" content="{identifier}"/></head>
+  <docTitle><text>{title}</text></docTitle>
+  <navMap>
+{points}  </navMap>
+</ncx>
+"#,
+        identifier = escape_xml(identifier),
+        title = escape_xml(book_title),
+    )
+}
+
+const CONTAINER_XML: &str = r#"<?xml version="1.0" encoding="utf-8"?>
+<container version="1.0" xmlns="urn:oasis:names:tc:opendocument:xmlns:container">
+  <rootfiles>
+    <rootfile full-path="OEBPS/content.opf" media-type="application/oebps-package+xml"/>
+  </rootfiles>
+</container>
+"#;
+
+pub fn write_epub(path: &Path, book_title: &str, chapters: &[EpubChapter]) -> anyhow::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+    let identifier = format!("pkt-tui-{}", chrono::Utc::now().timestamp());
+    let mut zip = ZipWriter::new(File::create(path)?);
+
+    // mimetype has to be the first entry and it must not be compressed
+    zip.start_file(
+        "mimetype",
+        SimpleFileOptions::default().compression_method(CompressionMethod::Stored),
+    )?;
+    zip.write_all(b"application/epub+zip")?;
+
+    let options = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    let mut add = |name: &str, content: String| -> anyhow::Result<()> {
+        zip.start_file(name, options)?;
+        zip.write_all(content.as_bytes())?;
+        Ok(())
+    };
+    add("META-INF/container.xml", CONTAINER_XML.to_string())?;
+    add(
+        "OEBPS/content.opf",
+        content_opf(book_title, &identifier, chapters),
+    )?;
+    add("OEBPS/nav.xhtml", nav_xhtml(chapters))?;
+    add("OEBPS/toc.ncx", toc_ncx(book_title, &identifier, chapters))?;
+    for (i, chapter) in chapters.iter().enumerate() {
+        add(
+            &format!("OEBPS/chapter-{}.xhtml", i + 1),
+            chapter_xhtml(chapter),
+        )?;
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn writes_mimetype_first_and_escapes_titles() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("book.epub");
+        let chapters = vec![EpubChapter {
+            title: "Rust & <friends>".to_string(),
+            url: "https://example.com/?a=1&b=2".to_string(),
+            date_added: "2024-01-01".to_string(),
+            markdown: article_markdown("raw text--------\n\n# Heading\n\n<div>html</div>")
+                .to_string(),
+        }];
+        write_epub(&path, "Reading list", &chapters).unwrap();
+
+        let mut archive = zip::ZipArchive::new(File::open(&path).unwrap()).unwrap();
+        let first = archive.by_index(0).unwrap();
+        assert_eq!(first.name(), "mimetype");
+        assert_eq!(first.compression(), CompressionMethod::Stored);
+        drop(first);
+
+        let mut chapter = String::new();
+        archive
+            .by_name("OEBPS/chapter-1.xhtml")
+            .unwrap()
+            .read_to_string(&mut chapter)
+            .unwrap();
+        assert!(chapter.contains("<title>Rust &amp; &lt;friends&gt;</title>"));
+        assert!(chapter.contains("<h1>Heading</h1>"));
+        assert!(!chapter.contains("raw text"));
+        assert!(!chapter.contains("<div>"));
+    }
+}
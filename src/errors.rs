@@ -1,11 +1,8 @@
 use std::{io::stdout, panic};
 
 use color_eyre::{config::HookBuilder, eyre};
-use crossterm::{
-    event::DisableMouseCapture,
-    execute,
-    terminal::{disable_raw_mode, LeaveAlternateScreen},
-};
+
+use crate::termcaps;
 
 /**
 This replaces the standard color_eyre panic and error hooks with hooks that
@@ -34,7 +31,6 @@ pub fn install_hooks() -> color_eyre::Result<()> {
 }
 
 pub fn restore() -> color_eyre::Result<()> {
-    let _ = disable_raw_mode();
-    execute!(stdout(), LeaveAlternateScreen, DisableMouseCapture)?;
+    termcaps::leave(&mut stdout())?;
     Ok(())
 }
@@ -0,0 +1,68 @@
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::sync::OnceLock;
+use std::time::{Duration, Instant};
+
+use crossterm::event::{self, Event};
+
+/// The screen is redrawn at least this often, so clocks and timeouts stay current without input
+pub const TICK_RATE: Duration = Duration::from_millis(250);
+
+/// How often waiting for input looks for wake ups from background threads
+const WAKEUP_CHECK: Duration = Duration::from_millis(20);
+
+static WAKER: OnceLock<Sender<()>> = OnceLock::new();
+
+/// Receiving end of `wake`, owned by the main loop
+pub struct Wakeups {
+    receiver: Receiver<()>,
+}
+
+impl Wakeups {
+    /// Only the first instance receives wake ups, there is a single main loop
+    pub fn new() -> Self {
+        let (sender, receiver) = mpsc::channel();
+        let _ = WAKER.set(sender);
+        Self { receiver }
+    }
+
+    fn take(&self) -> bool {
+        self.receiver.try_iter().count() > 0
+    }
+}
+
+/// Called by background threads after changing something that is on screen
+pub fn wake() {
+    if let Some(sender) = WAKER.get() {
+        let _ = sender.send(());
+    }
+}
+
+/// Next input event. None once `timeout` passes or a background thread calls `wake`, the caller
+/// returns to the main loop, which redraws
+pub fn next_event(wakeups: &Wakeups, timeout: Duration) -> std::io::Result<Option<Event>> {
+    let deadline = Instant::now() + timeout;
+    loop {
+        let left = deadline.saturating_duration_since(Instant::now());
+        if event::poll(left.min(WAKEUP_CHECK))? {
+            return event::read().map(Some);
+        }
+        if wakeups.take() || left.is_zero() {
+            return Ok(None);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn wake_reaches_the_main_loop_once() {
+        let wakeups = Wakeups::new();
+        assert!(!wakeups.take());
+        wake();
+        wake();
+        assert!(wakeups.take());
+        assert!(!wakeups.take());
+    }
+}
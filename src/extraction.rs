@@ -0,0 +1,151 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::markdown::BoundaryRules;
+
+pub const DEFAULT_USER_AGENT: &str = "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36";
+
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum TextMode {
+    /// text as it is in the page
+    Raw,
+    /// keeps paragraphs and list structure
+    Formatted,
+}
+
+/// Per-domain settings, anything not set comes from the top level extraction config
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtractionOverride {
+    pub max_elements_to_parse: Option<usize>,
+    pub text_mode: Option<TextMode>,
+    pub user_agent: Option<String>,
+    pub boundaries: Option<BoundaryRules>,
+}
+
+/// How downloaded articles are fetched and run through readability
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ExtractionConfig {
+    /// 0 means no limit
+    pub max_elements_to_parse: usize,
+    pub text_mode: TextMode,
+    pub user_agent: String,
+    /// Where the article starts and ends in the converted page
+    pub boundaries: BoundaryRules,
+    /// Keyed by domain, `example.com` also covers `blog.example.com`
+    pub domains: HashMap<String, ExtractionOverride>,
+}
+
+impl Default for ExtractionConfig {
+    fn default() -> Self {
+        Self {
+            max_elements_to_parse: 9000,
+            text_mode: TextMode::Formatted,
+            user_agent: DEFAULT_USER_AGENT.to_string(),
+            boundaries: BoundaryRules::default(),
+            domains: HashMap::new(),
+        }
+    }
+}
+
+/// Extraction config resolved for a single url
+#[derive(Debug, PartialEq)]
+pub struct ExtractionSettings {
+    pub max_elements_to_parse: usize,
+    pub text_mode: TextMode,
+    pub user_agent: String,
+    pub boundaries: BoundaryRules,
+}
+
+impl ExtractionConfig {
+    /// The most specific domain override wins
+    pub fn for_url(&self, url: &str) -> ExtractionSettings {
+        let host = reqwest::Url::parse(url)
+            .ok()
+            .and_then(|url| url.host_str().map(str::to_lowercase))
+            .unwrap_or_default();
+        let domain_override = self
+            .domains
+            .iter()
+            .filter(|(domain, _)| {
+                let domain = domain.to_lowercase();
+                host == domain || host.ends_with(&format!(".{}", domain))
+            })
+            .max_by_key(|(domain, _)| domain.len())
+            .map(|(_, domain_override)| domain_override);
+
+        let mut settings = ExtractionSettings {
+            max_elements_to_parse: self.max_elements_to_parse,
+            text_mode: self.text_mode,
+            user_agent: self.user_agent.clone(),
+            boundaries: self.boundaries.clone(),
+        };
+        if let Some(domain_override) = domain_override {
+            if let Some(max) = domain_override.max_elements_to_parse {
+                settings.max_elements_to_parse = max;
+            }
+            if let Some(text_mode) = domain_override.text_mode {
+                settings.text_mode = text_mode;
+            }
+            if let Some(user_agent) = &domain_override.user_agent {
+                settings.user_agent = user_agent.clone();
+            }
+            if let Some(boundaries) = &domain_override.boundaries {
+                settings.boundaries = boundaries.clone();
+            }
+        }
+        settings
+    }
+}
+
+impl ExtractionSettings {
+    pub fn readability_config(&self) -> dom_smoothie::Config {
+        dom_smoothie::Config {
+            max_elements_to_parse: self.max_elements_to_parse,
+            text_mode: match self.text_mode {
+                TextMode::Raw => dom_smoothie::TextMode::Raw,
+                TextMode::Formatted => dom_smoothie::TextMode::Formatted,
+            },
+            ..Default::default()
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn most_specific_domain_override_wins() {
+        let config: ExtractionConfig = serde_json::from_str(
+            r#"{
+                "max_elements_to_parse": 5000,
+                "domains": {
+                    "example.com": {"text_mode": "raw", "user_agent": "curl/8.0"},
+                    "blog.example.com": {"max_elements_to_parse": 0}
+                }
+            }"#,
+        )
+        .unwrap();
+
+        let plain = config.for_url("https://news.other.org/a");
+        assert_eq!(plain.max_elements_to_parse, 5000);
+        assert_eq!(plain.text_mode, TextMode::Formatted);
+        assert_eq!(plain.user_agent, DEFAULT_USER_AGENT);
+
+        let blog = config.for_url("https://blog.example.com/post");
+        assert_eq!(blog.max_elements_to_parse, 0);
+        assert_eq!(blog.text_mode, TextMode::Formatted);
+
+        let site = config.for_url("https://www.example.com/post");
+        assert_eq!(site.text_mode, TextMode::Raw);
+        assert_eq!(site.user_agent, "curl/8.0");
+        assert_eq!(
+            config.for_url("https://notexample.com").text_mode,
+            TextMode::Formatted
+        );
+    }
+}
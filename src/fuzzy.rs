@@ -0,0 +1,150 @@
+use std::ops::Range;
+
+/// Per matched character
+const MATCH: i64 = 16;
+/// Extra for a character matched right after the previous one
+const CONSECUTIVE: i64 = 12;
+/// Extra for a match at the start of a word, after a separator or a lowercase-uppercase change
+const WORD_START: i64 = 10;
+/// Extra for matching the first character of the text
+const PREFIX: i64 = 6;
+/// Per character skipped between two matched ones
+const GAP: i64 = 1;
+const NONE: i64 = i64::MIN / 2;
+
+fn lower(ch: char) -> char {
+    ch.to_lowercase().next().unwrap_or(ch)
+}
+
+fn bonus(prev: Option<char>, ch: char) -> i64 {
+    match prev {
+        None => WORD_START + PREFIX,
+        Some(prev) if !prev.is_alphanumeric() => WORD_START,
+        Some(prev) if prev.is_lowercase() && ch.is_uppercase() => WORD_START,
+        _ => 0,
+    }
+}
+
+/// Best score of the pattern characters found in order in `text`, and the char indices they
+/// matched at. Whitespace in the pattern is ignored
+fn align(pattern: &str, text: &str) -> Option<(i64, Vec<usize>)> {
+    let pattern: Vec<char> = pattern
+        .chars()
+        .filter(|ch| !ch.is_whitespace())
+        .map(lower)
+        .collect();
+    if pattern.is_empty() {
+        return Some((0, Vec::new()));
+    }
+    let chars: Vec<char> = text.chars().collect();
+    let lowered: Vec<char> = chars.iter().copied().map(lower).collect();
+    let mut rest = lowered.iter();
+    if !pattern.iter().all(|p| rest.any(|ch| ch == p)) {
+        return None;
+    }
+
+    // matched[i][j]: pattern[..=i] placed with pattern[i] at j, best[i][j]: placed within [..=j]
+    let (m, n) = (pattern.len(), chars.len());
+    let mut matched = vec![vec![NONE; n]; m];
+    let mut best = vec![vec![NONE; n]; m];
+    for i in 0..m {
+        // trailing characters after the last match cost nothing
+        let gap = if i + 1 == m { 0 } else { GAP };
+        for j in 0..n {
+            if lowered[j] == pattern[i] {
+                let bonus = bonus(j.checked_sub(1).map(|prev| chars[prev]), chars[j]);
+                matched[i][j] = if i == 0 {
+                    MATCH + bonus
+                } else if j > 0 {
+                    MATCH + (best[i - 1][j - 1] + bonus).max(matched[i - 1][j - 1] + CONSECUTIVE)
+                } else {
+                    NONE
+                };
+            }
+            let skipped = if j > 0 { best[i][j - 1] - gap } else { NONE };
+            best[i][j] = matched[i][j].max(skipped);
+        }
+    }
+
+    let mut positions = vec![0; m];
+    let mut must_match = false;
+    let mut j = n;
+    for i in (0..m).rev() {
+        while j > 0 {
+            j -= 1;
+            if matched[i][j] > NONE / 2 && (must_match || matched[i][j] == best[i][j]) {
+                must_match =
+                    i > 0 && j > 0 && matched[i][j] == matched[i - 1][j - 1] + CONSECUTIVE + MATCH;
+                positions[i] = j;
+                break;
+            }
+        }
+    }
+    Some((best[m - 1][n - 1], positions))
+}
+
+/// Score of `text` for the fuzzy `pattern`, higher is better, `None` when it doesn't match
+pub fn score(pattern: &str, text: &str) -> Option<i64> {
+    align(pattern, text).map(|(score, _)| score)
+}
+
+/// Byte ranges of `text` matched by `pattern`, neighbouring characters merged into one range
+pub fn positions(pattern: &str, text: &str) -> Vec<Range<usize>> {
+    let Some((_, positions)) = align(pattern, text) else {
+        return Vec::new();
+    };
+    let mut ranges: Vec<Range<usize>> = Vec::new();
+    let mut positions = positions.into_iter().peekable();
+    for (index, (start, ch)) in text.char_indices().enumerate() {
+        if positions.peek() != Some(&index) {
+            continue;
+        }
+        positions.next();
+        let end = start + ch.len_utf8();
+        match ranges.last_mut() {
+            Some(last) if last.end == start => last.end = end,
+            _ => ranges.push(start..end),
+        }
+    }
+    ranges
+}
+
+/// Candidates matching `pattern`, best first; equally good ones keep their order
+pub fn rank<T, F>(pattern: &str, candidates: impl IntoIterator<Item = T>, text: F) -> Vec<T>
+where
+    F: Fn(&T) -> &str,
+{
+    let mut scored: Vec<(i64, T)> = candidates
+        .into_iter()
+        .filter_map(|candidate| score(pattern, text(&candidate)).map(|score| (score, candidate)))
+        .collect();
+    scored.sort_by_key(|(score, _)| std::cmp::Reverse(*score));
+    scored.into_iter().map(|(_, candidate)| candidate).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranks_word_starts_and_runs_first() {
+        assert_eq!(score("rst", "Go generics"), None);
+        assert_eq!(
+            rank(
+                "asyn",
+                [
+                    "Why a sync story",
+                    "Go generics",
+                    "Async Rust",
+                    "Notes on async traits"
+                ],
+                |text| *text
+            ),
+            ["Async Rust", "Notes on async traits", "Why a sync story"]
+        );
+        assert_eq!(positions("bc", "borrow checker"), [0..1, 7..8]);
+        let ranges = positions("ck er", "borrow checker");
+        assert_eq!((ranges.len(), ranges.first()), (1, Some(&(10..14))));
+        assert!(score("lwn", "lwn.net").unwrap() > score("lwn", "lower network").unwrap());
+    }
+}
@@ -0,0 +1,156 @@
+use std::collections::HashMap;
+
+use serde::{Deserialize, Serialize};
+
+use crate::statefile::StateFile;
+use crate::storage::PocketItem;
+use crate::TableRow;
+
+pub const HIGHLIGHTS_FILE: &str = "highlights.json";
+
+#[derive(Clone, Debug, PartialEq, Serialize, Deserialize)]
+pub struct Highlight {
+    pub text: String,
+    /// Unix seconds, unknown for the highlights made in Pocket
+    pub created_at: Option<i64>,
+}
+
+/// Passages saved in the reader, by item id. Pocket's own highlights come with the items
+pub struct Highlights {
+    by_item: HashMap<String, Vec<Highlight>>,
+    file: StateFile,
+}
+
+impl Highlights {
+    pub fn new() -> Self {
+        Self {
+            by_item: HashMap::new(),
+            file: StateFile::default(),
+        }
+    }
+
+    pub fn load() -> Self {
+        let file = StateFile::at(HIGHLIGHTS_FILE);
+        Self {
+            by_item: file.load(),
+            file,
+        }
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        self.file.save(&self.by_item)
+    }
+
+    pub fn add(&mut self, item_id: &str, text: &str) -> anyhow::Result<()> {
+        self.by_item
+            .entry(item_id.to_string())
+            .or_default()
+            .push(Highlight {
+                text: text.to_string(),
+                created_at: Some(chrono::Utc::now().timestamp()),
+            });
+        self.save()
+    }
+
+    /// Pocket's highlights of the item first, then the ones saved here
+    pub fn of(&self, item: &PocketItem) -> Vec<Highlight> {
+        item.annotations
+            .iter()
+            .map(|quote| Highlight {
+                text: quote.clone(),
+                created_at: None,
+            })
+            .chain(
+                self.by_item
+                    .get(&item.item_id)
+                    .into_iter()
+                    .flatten()
+                    .cloned(),
+            )
+            .collect()
+    }
+}
+
+/// Highlights of every item that has some, grouped under a link to the item
+pub fn to_markdown(items: &[PocketItem], highlights: &Highlights) -> String {
+    let mut markdown = String::from("# Highlights\n");
+    for item in items {
+        let quotes = highlights.of(item);
+        if quotes.is_empty() {
+            continue;
+        }
+        markdown.push_str(&format!("\n## [{}]({})\n\n", item.title(), item.url()));
+        for quote in quotes {
+            markdown.push_str(&format!("> {}\n\n", quote.text));
+        }
+    }
+    markdown
+}
+
+fn csv_field(text: &str) -> String {
+    format!("\"{}\"", text.replace('"', "\"\""))
+}
+
+/// Readwise's CSV import: `Highlight,Title,Author,URL,Note,Location,Date`
+pub fn to_readwise_csv(items: &[PocketItem], highlights: &Highlights) -> String {
+    let mut csv = String::from("Highlight,Title,Author,URL,Note,Location,Date\n");
+    for item in items {
+        let author = item
+            .authors
+            .iter()
+            .flatten()
+            .next()
+            .cloned()
+            .unwrap_or_default();
+        for quote in highlights.of(item) {
+            let date = quote
+                .created_at
+                .and_then(|time| chrono::DateTime::from_timestamp(time, 0))
+                .map(|time| time.format("%Y-%m-%d %H:%M:%S").to_string())
+                .unwrap_or_default();
+            csv.push_str(
+                &[
+                    csv_field(&quote.text),
+                    csv_field(item.title()),
+                    csv_field(&author),
+                    csv_field(item.url()),
+                    csv_field(""),
+                    csv_field(""),
+                    csv_field(&date),
+                ]
+                .join(","),
+            );
+            csv.push('\n');
+        }
+    }
+    csv
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tutorial::sample_item;
+
+    #[test]
+    fn exports_pocket_and_local_highlights() {
+        let mut highlights = Highlights::new();
+        let mut item = sample_item(1, 0, "Memory", "https://a.org/1", &[], None);
+        item.annotations = vec!["From Pocket".to_string()];
+        highlights.add("1", "Said \"cache\"").unwrap();
+        let items = [
+            item,
+            sample_item(2, 0, "Nothing", "https://a.org/2", &[], None),
+        ];
+
+        assert_eq!(highlights.of(&items[0]).len(), 2);
+        let markdown = to_markdown(&items, &highlights);
+        assert!(markdown.contains("## [Memory](https://a.org/1)\n\n> From Pocket\n\n> Said"));
+        assert!(!markdown.contains("Nothing"));
+
+        let csv = to_readwise_csv(&items, &highlights);
+        let rows: Vec<&str> = csv.lines().collect();
+        assert_eq!(rows.len(), 3);
+        assert!(rows[2].starts_with("\"Said \"\"cache\"\"\",\"Memory\",\"\",\"https://a.org/1\""));
+        assert!(rows[1].ends_with(",\"\""));
+    }
+}
@@ -0,0 +1,157 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::Read;
+use std::path::Path;
+
+use log::{error, info};
+use pulldown_cmark::{Event, Parser, Tag};
+use reqwest::blocking::Client;
+use serde::{Deserialize, Serialize};
+
+/// Saving images of downloaded articles, so they are readable offline
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ImagesConfig {
+    pub enabled: bool,
+    /// Bigger images are left as remote links
+    pub max_bytes: u64,
+}
+
+impl Default for ImagesConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            max_bytes: 5 * 1024 * 1024,
+        }
+    }
+}
+
+/// Remote (http/https) image urls referenced by the markdown, without duplicates
+fn image_urls(markdown: &str) -> Vec<String> {
+    let mut urls: Vec<String> = Vec::new();
+    for event in Parser::new(markdown) {
+        if let Event::Start(Tag::Image { dest_url, .. }) = event {
+            let url = dest_url.to_string();
+            if (url.starts_with("http://") || url.starts_with("https://")) && !urls.contains(&url) {
+                urls.push(url);
+            }
+        }
+    }
+    urls
+}
+
+/// 64-bit FNV-1a. Unlike std's `DefaultHasher` it stays the same across Rust releases
+fn fnv1a(text: &str) -> u64 {
+    text.bytes().fold(0xcbf2_9ce4_8422_2325, |hash, byte| {
+        (hash ^ u64::from(byte)).wrapping_mul(0x0000_0100_0000_01b3)
+    })
+}
+
+/// Same url always maps to the same file, so re-downloading an article doesn't pile up copies
+pub fn local_file_name(url: &str) -> String {
+    let path = url.split(['?', '#']).next().unwrap_or(url);
+    let extension = path
+        .rsplit('/')
+        .next()
+        .and_then(|name| name.rsplit_once('.'))
+        .map(|(_, ext)| ext.to_lowercase())
+        .filter(|ext| {
+            matches!(
+                ext.as_str(),
+                "png" | "jpg" | "jpeg" | "gif" | "webp" | "svg" | "avif"
+            )
+        })
+        .unwrap_or_else(|| "img".to_string());
+    format!("{:016x}.{}", fnv1a(url), extension)
+}
+
+fn rewrite_links(markdown: &str, local_links: &HashMap<String, String>) -> String {
+    local_links
+        .iter()
+        .fold(markdown.to_string(), |md, (url, local)| {
+            md.replace(&format!("]({}", url), &format!("]({}", local))
+        })
+}
+
+pub fn download_image(client: &Client, url: &str, max_bytes: u64) -> anyhow::Result<Vec<u8>> {
+    let response = client.get(url).send()?.error_for_status()?;
+    if response.content_length().is_some_and(|len| len > max_bytes) {
+        anyhow::bail!("image is larger than {} bytes", max_bytes);
+    }
+    // content length can be missing or lie
+    let mut bytes = Vec::new();
+    response.take(max_bytes + 1).read_to_end(&mut bytes)?;
+    if bytes.len() as u64 > max_bytes {
+        anyhow::bail!("image is larger than {} bytes", max_bytes);
+    }
+    Ok(bytes)
+}
+
+/**
+Downloads images referenced by the markdown into `assets_dir` and points the links to the local copies.
+`link_prefix` is the path of `assets_dir` relative to the markdown file.
+Images that fail to download (or are too big) keep their remote links.
+*/
+pub fn localize_images(
+    markdown: &str,
+    client: &Client,
+    config: &ImagesConfig,
+    assets_dir: &Path,
+    link_prefix: &str,
+) -> anyhow::Result<String> {
+    let urls = image_urls(markdown);
+    if urls.is_empty() {
+        return Ok(markdown.to_string());
+    }
+    fs::create_dir_all(assets_dir)?;
+    let mut local_links = HashMap::new();
+    for url in urls {
+        let name = local_file_name(&url);
+        let path = assets_dir.join(&name);
+        if !path.exists() {
+            match download_image(client, &url, config.max_bytes) {
+                Ok(bytes) => fs::write(&path, bytes)?,
+                Err(e) => {
+                    error!("Failed to download image {}: {:#}", url, e);
+                    continue;
+                }
+            }
+        }
+        local_links.insert(url, format!("{}/{}", link_prefix, name));
+    }
+    info!(
+        "Saved {} images into {}",
+        local_links.len(),
+        assets_dir.display()
+    );
+    Ok(rewrite_links(markdown, &local_links))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rewrites_remote_images_to_local_files() {
+        let markdown = "![chart](https://example.com/img/chart.PNG?w=800 \"Chart\")\n\n\
+                        [not an image](https://example.com/page.png)\n\n\
+                        ![local](assets/already.png) ![again](https://example.com/img/chart.PNG?w=800)";
+        let urls = image_urls(markdown);
+        assert_eq!(urls, ["https://example.com/img/chart.PNG?w=800"]);
+
+        let name = local_file_name(&urls[0]);
+        assert!(name.ends_with(".png"));
+        assert_eq!(name, local_file_name(&urls[0]));
+        assert!(local_file_name("https://example.com/pixel").ends_with(".img"));
+        // names must not change between builds, the reference value of FNV-1a
+        assert_eq!(fnv1a("a"), 0xaf63_dc4c_8601_ec8c);
+
+        let links = HashMap::from([(urls[0].clone(), format!("assets/1/{}", name))]);
+        let rewritten = rewrite_links(markdown, &links);
+        assert_eq!(
+            rewritten.matches(&format!("](assets/1/{}", name)).count(),
+            2
+        );
+        assert!(rewritten.contains("[not an image](https://example.com/page.png)"));
+    }
+}
@@ -0,0 +1,357 @@
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use KeyContext::*;
+
+/// Where a binding works: the list itself or the popup or mode that takes the input
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum KeyContext {
+    List,
+    Tags,
+    DocType,
+    DomainStats,
+    Authors,
+    Watch,
+    FailedActions,
+    Downloads,
+    BoundaryPreview,
+    Favorites,
+    Archive,
+    Summary,
+    Reader,
+    Qr,
+    Share,
+    Logs,
+    ContextMenu,
+    RssTriage,
+    SyncDiff,
+    Conflicts,
+}
+
+impl KeyContext {
+    fn title(&self) -> &'static str {
+        match self {
+            KeyContext::List => "List",
+            KeyContext::Tags => "Tag Popup",
+            KeyContext::DocType => "Document Types",
+            KeyContext::DomainStats => "Domain Statistics",
+            KeyContext::Authors => "Authors",
+            KeyContext::Watch => "Watched Topics",
+            KeyContext::FailedActions => "Failed Actions",
+            KeyContext::Downloads => "Download Queue",
+            KeyContext::BoundaryPreview => "Boundary Preview",
+            KeyContext::Favorites => "Favorites",
+            KeyContext::Archive => "Archive",
+            KeyContext::Summary => "Summary",
+            KeyContext::Reader => "Reader",
+            KeyContext::Qr => "QR Code",
+            KeyContext::Share => "Copy for Sharing",
+            KeyContext::Logs => "Logs",
+            KeyContext::ContextMenu => "Menu",
+            KeyContext::RssTriage => "RSS Triage",
+            KeyContext::SyncDiff => "Since Last Sync",
+            KeyContext::Conflicts => "Sync Conflicts",
+        }
+    }
+}
+
+pub struct Binding {
+    pub context: KeyContext,
+    /// Group of the list bindings, popups have one group each
+    pub section: &'static str,
+    pub keys: &'static str,
+    pub description: &'static str,
+}
+
+const fn list(section: &'static str, keys: &'static str, description: &'static str) -> Binding {
+    Binding {
+        context: KeyContext::List,
+        section,
+        keys,
+        description,
+    }
+}
+
+const fn popup(context: KeyContext, keys: &'static str, description: &'static str) -> Binding {
+    Binding {
+        context,
+        section: "",
+        keys,
+        description,
+    }
+}
+
+/// Keys listed by the help popup, keep in sync with the input handling in main.rs
+pub const BINDINGS: &[Binding] = &[
+    list("Navigation", "j/k, ↑/↓", "Move selection"),
+    list("Navigation", "Ctrl+d", "Page down"),
+    list("Navigation", "Ctrl+u", "Page up"),
+    list("Navigation", "gg", "Jump to start"),
+    list("Navigation", "G", "Jump to end"),
+    list("Navigation", "gd", "Calendar jump"),
+    list("Navigation", "'", "Jump to title"),
+    list("Navigation", "gf", "Favorites"),
+    list("Navigation", "ga", "Archive"),
+    list("Navigation", "Alt+arrows", "Resize popup"),
+    list("Navigation", "Alt+f", "Fullscreen popup"),
+    list("Actions", "Enter", "Open in browser"),
+    list("Actions", "T", "Edit tags"),
+    list("Actions", "t", "Toggle \"top\" tag"),
+    list("Actions", "f/F", "Favorite & archive"),
+    list("Actions", "d", "Delete article"),
+    list("Actions", "r/R", "Rename article"),
+    list("Actions", "U", "Edit URL"),
+    list("Actions", "a", "Add from clipboard"),
+    list("Actions", "Q", "Refresh data"),
+    list("Actions", "w", "Queue download"),
+    list("Actions", "p", "Pin to queue"),
+    list("Actions", "J/K", "Move pinned item"),
+    list("Actions", "E", "Export to EPUB"),
+    list("Actions", "X", "Re-extract article"),
+    list("Actions", "D", "Download queue"),
+    list("Actions", "B", "Boundary preview"),
+    list("Actions", "gs", "Summarize article"),
+    list("Actions", "gq", "QR code of link"),
+    list("Actions", "y", "Copy for sharing"),
+    list("Actions", "e", "Read & highlight"),
+    list("Actions", "P", "Listen/pause"),
+    list("Actions", "M", "Stop listening"),
+    list("Actions", "v", "Excerpt pane"),
+    list("Actions", "n", "RSS triage"),
+    list("Filters", "/", "Search title/URL"),
+    list("Filters", "/ Ctrl+n", "Jump, don't filter"),
+    list("Filters", "n/N", "Next/prev match"),
+    list("Filters", "z", "Show tags popup"),
+    list("Filters", "i", "Filter by type"),
+    list("Filters", "s", "Filter by domain"),
+    list("Filters", "S", "Domain statistics"),
+    list("Filters", "A", "Browse authors"),
+    list("Filters", "H", "Stats panel"),
+    list("Filters", "W", "Watched topics"),
+    list("Filters", "gr", "Added between days"),
+    list("Filters", "o", "Cycle grouping"),
+    list("Filters", "c", "Fold group"),
+    list("Filters", "Esc", "Clear filter"),
+    list("Commands", ":compact", "Compact storage"),
+    list("Commands", ":verify", "Check storage"),
+    list("Commands", ":rebuild", "Re-download list"),
+    list("Commands", ":logs", "Recent errors"),
+    list("Commands", ":highlights", "Export highlights"),
+    list("Commands", ":readwise", "Readwise CSV"),
+    list("Commands", ":tagall T", "Tag the view"),
+    list("Commands", ":untagall T", "Untag the view"),
+    list("Exit", "ZZ", "Save and quit"),
+    list("Exit", "?", "This help"),
+    popup(Tags, "↑/↓", "Move selection"),
+    popup(Tags, "Enter", "Apply tag filter"),
+    popup(Tags, "Type", "Filter tags"),
+    popup(Tags, "Tab", "Sort by count/name"),
+    popup(Tags, "←/→", "Fold tag tree"),
+    popup(Tags, "Esc", "Exit popup"),
+    popup(DocType, "1", "All items"),
+    popup(DocType, "2", "Articles"),
+    popup(DocType, "3", "Videos"),
+    popup(DocType, "4", "PDFs"),
+    popup(DocType, "Esc", "Close"),
+    popup(DomainStats, "j/k", "Move selection"),
+    popup(DomainStats, "Enter", "Filter by site"),
+    popup(DomainStats, "Esc", "Close"),
+    popup(Authors, "j/k", "Move selection"),
+    popup(Authors, "Space", "Choose author"),
+    popup(Authors, "Enter", "Filter by authors"),
+    popup(Authors, "Esc/q", "Close"),
+    popup(Watch, "j/k", "Move selection"),
+    popup(Watch, "Enter", "Open in browser"),
+    popup(Watch, "Esc", "Close"),
+    popup(FailedActions, "r", "Retry all"),
+    popup(FailedActions, "Esc", "Close"),
+    popup(Downloads, "j/k", "Move selection"),
+    popup(Downloads, "J/K", "Move job down/up"),
+    popup(Downloads, "x", "Remove job"),
+    popup(Downloads, "Space", "Pause/resume"),
+    popup(Downloads, "r", "Retry failed"),
+    popup(Downloads, "a", "Queue the view"),
+    popup(Downloads, "Esc/q", "Close"),
+    popup(BoundaryPreview, "j/k", "Scroll"),
+    popup(BoundaryPreview, "PgUp/PgDn", "Scroll a page"),
+    popup(BoundaryPreview, "r", "Reload rules"),
+    popup(BoundaryPreview, "X", "Re-extract article"),
+    popup(BoundaryPreview, "Esc/q", "Close"),
+    popup(Favorites, "j/k", "Move selection"),
+    popup(Favorites, "Enter", "Open in browser"),
+    popup(Favorites, "Esc/q", "Close"),
+    popup(Archive, "j/k", "Move selection"),
+    popup(Archive, "PgUp/PgDn", "Move by a page"),
+    popup(Archive, "Enter", "Open in browser"),
+    popup(Archive, "Esc/q", "Close"),
+    popup(Summary, "j/k", "Scroll"),
+    popup(Summary, "r", "Summarize again"),
+    popup(Summary, "Esc/q", "Close"),
+    popup(Reader, "j/k", "Move cursor"),
+    popup(Reader, "PgUp/PgDn", "Move a page"),
+    popup(Reader, "v", "Select lines"),
+    popup(Reader, "y/Enter", "Save highlight"),
+    popup(Reader, "c", "Continue reading"),
+    popup(Reader, "Esc/q", "Close"),
+    popup(Qr, "Esc/q/Enter", "Close"),
+    popup(Share, "j/k", "Move selection"),
+    popup(Share, "Enter/y", "Copy"),
+    popup(Share, "1-9", "Copy by number"),
+    popup(Share, "Esc/q", "Close"),
+    popup(Logs, "j/k", "Move selection"),
+    popup(Logs, "PgUp/PgDn", "Move a page"),
+    popup(Logs, "l", "Cycle level"),
+    popup(Logs, "r", "Reload"),
+    popup(Logs, "y/Y", "Copy entry/all"),
+    popup(Logs, "Esc/q", "Close"),
+    popup(ContextMenu, "j/k", "Move selection"),
+    popup(ContextMenu, "Enter", "Run action"),
+    popup(ContextMenu, "Esc/q", "Close"),
+    popup(RssTriage, "j/k", "Move selection"),
+    popup(RssTriage, "h/l, Tab", "Switch source"),
+    popup(RssTriage, "Space", "Mark item"),
+    popup(RssTriage, "a", "Add to Pocket"),
+    popup(RssTriage, "d", "Hide items"),
+    popup(RssTriage, "D", "Hide the feed"),
+    popup(RssTriage, "u", "Undo hiding"),
+    popup(RssTriage, "H", "Hidden items"),
+    popup(RssTriage, "R", "Retry feeds"),
+    popup(RssTriage, "p", "Preview"),
+    popup(RssTriage, "J/K", "Scroll preview"),
+    popup(RssTriage, "f", "Fetch full text"),
+    popup(RssTriage, "r", "Read full-screen"),
+    popup(RssTriage, "e", "Play media"),
+    popup(RssTriage, "[/]", "Previous/next feed"),
+    popup(RssTriage, "c", "Fold feed"),
+    popup(RssTriage, "s", "Sort by date"),
+    popup(RssTriage, "Enter", "Open in browser"),
+    popup(RssTriage, "Esc/q", "Back to the list"),
+    popup(SyncDiff, "j/k", "Move selection"),
+    popup(SyncDiff, "Enter", "Jump to new item"),
+    popup(SyncDiff, "Esc/q", "Close"),
+    popup(Conflicts, "j/k", "Move selection"),
+    popup(Conflicts, "l", "Keep local edit"),
+    popup(Conflicts, "r", "Keep Pocket's"),
+    popup(Conflicts, "m", "Merge tags"),
+    popup(Conflicts, "Esc/q", "Keep all Pocket's"),
+];
+
+/// Legend of the list below its bindings
+const INDICATORS: [(&str, &str); 4] = [
+    ("⭐", "Top article"),
+    ("[dim]", "Read article"),
+    ("📌 n", "Queued item"),
+    ("░▒▓", "Day stats"),
+];
+
+/// Inner width of a section box
+const BOX_WIDTH: usize = 32;
+const KEYS_WIDTH: usize = 11;
+
+/// Cuts `text` to `width` columns and pads it with spaces up to them
+fn fit(text: &str, width: usize) -> String {
+    let mut result = String::new();
+    let mut used = 0;
+    for ch in text.chars() {
+        let ch_width = ch.width().unwrap_or(0);
+        if used + ch_width > width {
+            break;
+        }
+        result.push(ch);
+        used += ch_width;
+    }
+    result + &" ".repeat(width - used)
+}
+
+fn section_box(title: &str, rows: &[(&str, &str)]) -> Vec<String> {
+    let mut lines = vec![format!(
+        "┌─ {} {}┐",
+        title,
+        "─".repeat(BOX_WIDTH.saturating_sub(title.width() + 3))
+    )];
+    for (keys, description) in rows {
+        let keys = if keys.width() > KEYS_WIDTH {
+            format!("{} ", keys)
+        } else {
+            fit(keys, KEYS_WIDTH + 1)
+        };
+        lines.push(format!(
+            "│ {} │",
+            fit(&format!("{}{}", keys, description), BOX_WIDTH - 2)
+        ));
+    }
+    lines.push(format!("└{}┘", "─".repeat(BOX_WIDTH)));
+    lines
+}
+
+/// Sections side by side, each goes under the shorter column
+fn two_columns(sections: Vec<Vec<String>>) -> String {
+    let mut columns: [Vec<String>; 2] = [Vec::new(), Vec::new()];
+    for section in sections {
+        let shorter = if columns[1].len() < columns[0].len() {
+            1
+        } else {
+            0
+        };
+        columns[shorter].extend(section);
+    }
+    let height = columns[0].len().max(columns[1].len());
+    (0..height)
+        .map(|i| {
+            let left = columns[0].get(i).map_or("", String::as_str);
+            let right = columns[1].get(i).map_or("", String::as_str);
+            format!("  {}  {}", fit(left, BOX_WIDTH + 2), right)
+                .trim_end()
+                .to_string()
+        })
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+/// Help for what takes the input now: all list bindings, or the ones of the open popup
+pub fn help_text(context: KeyContext) -> String {
+    let rows = |section: &str| -> Vec<(&str, &str)> {
+        BINDINGS
+            .iter()
+            .filter(|binding| binding.context == context && binding.section == section)
+            .map(|binding| (binding.keys, binding.description))
+            .collect()
+    };
+    let mut sections = Vec::new();
+    if context == KeyContext::List {
+        let mut titles: Vec<&str> = Vec::new();
+        for binding in BINDINGS.iter().filter(|b| b.context == context) {
+            if !titles.contains(&binding.section) {
+                titles.push(binding.section);
+            }
+        }
+        for title in titles {
+            sections.push(section_box(title, &rows(title)));
+        }
+        sections.push(section_box("Item Indicators", &INDICATORS));
+    } else {
+        sections.push(section_box(context.title(), &rows("")));
+        sections.push(section_box("Help", &[("?/Esc", "Close help")]));
+    }
+    format!("\n{}\n", two_columns(sections))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn help_shows_the_bindings_of_the_context() {
+        let list_help = help_text(KeyContext::List);
+        assert!(list_help.contains("┌─ Navigation ─"));
+        assert!(list_help.contains("│ gs          Summarize article  │"));
+        assert!(!list_help.contains("Cycle level"));
+        // boxes keep their width next to each other, emoji included
+        assert!(list_help
+            .lines()
+            .all(|line| [0, BOX_WIDTH + 4, 2 * BOX_WIDTH + 8].contains(&line.width())));
+
+        let logs_help = help_text(KeyContext::Logs);
+        assert!(logs_help.contains("Cycle level"));
+        assert!(!logs_help.contains("Summarize article"));
+    }
+}
@@ -0,0 +1,97 @@
+use std::collections::VecDeque;
+use std::sync::{Mutex, OnceLock};
+
+use chrono::Local;
+use log::Level;
+
+/// Older entries are dropped, the whole session stays in `log.txt`
+const CAPACITY: usize = 500;
+
+static RECENT: OnceLock<Mutex<LogBuffer>> = OnceLock::new();
+
+#[derive(Clone, Debug, PartialEq)]
+pub struct LogEntry {
+    pub time: String,
+    pub level: Level,
+    pub message: String,
+}
+
+impl LogEntry {
+    /// Line copied to the clipboard
+    pub fn to_line(&self) -> String {
+        format!("{} {} {}", self.time, self.level, self.message)
+    }
+}
+
+#[derive(Default)]
+struct LogBuffer {
+    entries: VecDeque<LogEntry>,
+}
+
+impl LogBuffer {
+    fn push(&mut self, entry: LogEntry) {
+        if self.entries.len() == CAPACITY {
+            self.entries.pop_front();
+        }
+        self.entries.push_back(entry);
+    }
+}
+
+/// Called by the logger for every record, keeps info and above for the `:logs` popup
+pub fn record(level: Level, message: &str) {
+    if level > Level::Info {
+        return;
+    }
+    let buffer = RECENT.get_or_init(|| Mutex::new(LogBuffer::default()));
+    if let Ok(mut buffer) = buffer.lock() {
+        buffer.push(LogEntry {
+            time: Local::now().format("%H:%M:%S").to_string(),
+            level,
+            message: message.to_string(),
+        });
+    }
+}
+
+/// Entries at `min_level` or more severe, oldest first
+pub fn recent(min_level: Level) -> Vec<LogEntry> {
+    RECENT
+        .get()
+        .and_then(|buffer| buffer.lock().ok())
+        .map(|buffer| {
+            buffer
+                .entries
+                .iter()
+                .filter(|entry| entry.level <= min_level)
+                .cloned()
+                .collect()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_the_latest_entries_and_filters_by_level() {
+        let mut buffer = LogBuffer::default();
+        for i in 0..CAPACITY + 2 {
+            buffer.push(LogEntry {
+                time: String::new(),
+                level: Level::Info,
+                message: i.to_string(),
+            });
+        }
+        assert_eq!(buffer.entries.len(), CAPACITY);
+        assert_eq!(buffer.entries[0].message, "2");
+
+        record(Level::Error, "Failed to sync");
+        record(Level::Debug, "noise");
+        let errors = recent(Level::Error);
+        assert_eq!(errors.len(), 1);
+        assert!(errors[0].to_line().ends_with("ERROR Failed to sync"));
+        assert!(recent(Level::Trace)
+            .iter()
+            .all(|entry| entry.message != "noise"));
+    }
+}
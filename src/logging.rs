@@ -0,0 +1,208 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+
+use chrono::Local;
+use log::LevelFilter;
+use serde::{Deserialize, Serialize};
+
+use crate::logbuffer;
+
+/// Overrides `logging.level` from config.json, e.g. `PKT_TUI_LOG=debug`
+const LEVEL_ENV: &str = "PKT_TUI_LOG";
+/// Overrides `logging.file`
+const FILE_ENV: &str = "PKT_TUI_LOG_FILE";
+
+/// Values following these keys in logged requests and responses never reach the log
+const SECRET_KEYS: [&str; 5] = [
+    "access_token",
+    "consumer_key",
+    "api_key",
+    "refresh_token",
+    "password",
+];
+const REDACTED: &str = "[redacted]";
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct LoggingConfig {
+    /// `off`, `error`, `warn`, `info`, `debug` or `trace`
+    pub level: String,
+    pub file: PathBuf,
+    /// The file is rotated to `log.txt.1`, `log.txt.2`, ... past this size, 0 never rotates
+    pub max_size_mb: u64,
+    /// Rotated files kept next to the current one
+    pub keep_files: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            level: "info".to_string(),
+            file: PathBuf::from("log.txt"),
+            max_size_mb: 10,
+            keep_files: 3,
+        }
+    }
+}
+
+/// Log file that moves itself aside once it grows past `max_bytes`
+struct RotatingFile {
+    path: PathBuf,
+    file: File,
+    size: u64,
+    max_bytes: u64,
+    keep_files: usize,
+}
+
+fn rotated(path: &Path, index: usize) -> PathBuf {
+    let mut name = path.as_os_str().to_owned();
+    name.push(format!(".{}", index));
+    PathBuf::from(name)
+}
+
+impl RotatingFile {
+    fn open(path: &Path, max_bytes: u64, keep_files: usize) -> io::Result<Self> {
+        let file = OpenOptions::new().create(true).append(true).open(path)?;
+        let size = file.metadata()?.len();
+        Ok(Self {
+            path: path.to_path_buf(),
+            file,
+            size,
+            max_bytes,
+            keep_files,
+        })
+    }
+
+    fn rotate(&mut self) -> io::Result<()> {
+        if self.keep_files > 0 {
+            let _ = fs::remove_file(rotated(&self.path, self.keep_files));
+            for index in (1..self.keep_files).rev() {
+                let from = rotated(&self.path, index);
+                if from.exists() {
+                    fs::rename(from, rotated(&self.path, index + 1))?;
+                }
+            }
+            fs::rename(&self.path, rotated(&self.path, 1))?;
+        }
+        self.file = File::create(&self.path)?;
+        self.size = 0;
+        Ok(())
+    }
+}
+
+impl Write for RotatingFile {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        if self.max_bytes > 0 && self.size > 0 && self.size + buf.len() as u64 > self.max_bytes {
+            self.rotate()?;
+        }
+        let written = self.file.write(buf)?;
+        self.size += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        self.file.flush()
+    }
+}
+
+/// Replaces what follows `key` up to the next delimiter. With `assignment` only `key: value`,
+/// `"key":"value"` and `key=value` count, otherwise any separator does (`Bearer value`)
+fn redact_values(text: &str, key: &str, assignment: bool) -> String {
+    let mut result = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(pos) = rest.find(key) {
+        let starts_word = !rest[..pos].ends_with(|c: char| c.is_alphanumeric() || c == '_');
+        let (before, after) = rest.split_at(pos + key.len());
+        result.push_str(before);
+        let value = after.trim_start_matches(['"', ':', '=', ' ']);
+        let separator = &after[..after.len() - value.len()];
+        let is_assignment = separator.contains([':', '=']);
+        if !starts_word || separator.is_empty() || (assignment && !is_assignment) {
+            rest = after;
+            continue;
+        }
+        result.push_str(separator);
+        let value_len = value
+            .find(['"', '&', ',', '}', ' ', '\n'])
+            .unwrap_or(value.len());
+        if value_len > 0 {
+            result.push_str(REDACTED);
+        }
+        rest = &value[value_len..];
+    }
+    result.push_str(rest);
+    result
+}
+
+/// Tokens and keys removed from a log message
+pub fn redact(message: &str) -> String {
+    let redacted = SECRET_KEYS.iter().fold(message.to_string(), |text, key| {
+        redact_values(&text, key, true)
+    });
+    redact_values(&redacted, "Bearer", false)
+}
+
+fn level(config: &LoggingConfig) -> LevelFilter {
+    let level = std::env::var(LEVEL_ENV).unwrap_or_else(|_| config.level.clone());
+    level.parse().unwrap_or(LevelFilter::Info)
+}
+
+/// Sets up the logger, `PKT_TUI_LOG` and `PKT_TUI_LOG_FILE` take precedence over the config
+pub fn init(config: &LoggingConfig) -> anyhow::Result<()> {
+    let path = std::env::var(FILE_ENV).map_or_else(|_| config.file.clone(), PathBuf::from);
+    let target = RotatingFile::open(&path, config.max_size_mb * 1024 * 1024, config.keep_files)?;
+    env_logger::Builder::new()
+        .target(env_logger::Target::Pipe(Box::new(target)))
+        .filter(None, level(config))
+        .format(|buf, record| {
+            let message = redact(&record.args().to_string());
+            logbuffer::record(record.level(), &message);
+            writeln!(
+                buf,
+                "({} {} {}:{}) {}",
+                Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
+                record.level(),
+                record.file().unwrap_or("unknown"),
+                record.line().unwrap_or(0),
+                message
+            )
+        })
+        .init();
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn redacts_tokens_in_requests_and_responses() {
+        assert_eq!(
+            redact(r#"{"consumer_key":"1234-abcd", "access_token": "xyz","count":1}"#),
+            r#"{"consumer_key":"[redacted]", "access_token": "[redacted]","count":1}"#
+        );
+        assert_eq!(
+            redact("GET /v3/get?access_token=xyz&count=1 Authorization: Bearer sk-1"),
+            "GET /v3/get?access_token=[redacted]&count=1 Authorization: Bearer [redacted]"
+        );
+        assert_eq!(
+            redact("missing access_token in my_api_key_docs"),
+            "missing access_token in my_api_key_docs"
+        );
+    }
+
+    #[test]
+    fn rotates_past_the_size_limit() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("log.txt");
+        let mut file = RotatingFile::open(&path, 10, 2).unwrap();
+        for line in ["first\n", "second\n", "third\n", "fourth\n"] {
+            file.write_all(line.as_bytes()).unwrap();
+        }
+        assert_eq!(fs::read_to_string(&path).unwrap(), "fourth\n");
+        assert_eq!(fs::read_to_string(rotated(&path, 1)).unwrap(), "third\n");
+        assert_eq!(fs::read_to_string(rotated(&path, 2)).unwrap(), "second\n");
+        assert!(!rotated(&path, 3).exists());
+    }
+}
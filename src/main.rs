@@ -1,50 +1,115 @@
 #![allow(clippy::enum_glob_use, clippy::wildcard_imports)]
 
+mod ambient;
+mod archive;
 mod auth;
+mod autotag;
+mod bookmarks;
+mod browserhistory;
+mod columns;
+mod completion;
+mod config;
+mod conflicts;
+mod datepicker;
+mod demo;
+mod downloads;
+mod epub;
 mod errors;
+mod events;
+mod extraction;
+mod fuzzy;
+mod highlights;
+mod images;
+mod keymap;
+mod logbuffer;
+mod logging;
 mod logo;
 mod markdown;
+mod mdview;
+mod network;
+mod notes;
+mod notifications;
+mod pdfindex;
 mod pocket;
+mod popup;
+mod progress;
 mod prss;
+mod qr;
+mod queue;
 mod readingstats;
+mod remote;
+mod rsstriage;
+mod safemode;
+mod share;
+mod statefile;
+mod statesync;
 pub mod storage;
+mod summary;
+mod syncdiff;
+mod syncverify;
+mod tageditor;
+mod tagtree;
+mod termcaps;
+mod textwidth;
+mod thumbnails;
+mod timezone;
+mod titles;
+mod toast;
 mod tokenstorage;
+mod transaction;
+mod tts;
+mod tutorial;
 mod utils;
+mod watch;
 
 use anyhow::Context;
-use chrono::{DateTime, Local, Utc};
-use crossterm::{
-    event::{
-        self, DisableMouseCapture, EnableMouseCapture, Event, KeyCode, KeyEventKind, KeyModifiers,
-        KeyboardEnhancementFlags, MouseEvent, MouseEventKind, PushKeyboardEnhancementFlags,
-    },
-    execute,
-    terminal::{disable_raw_mode, enable_raw_mode, EnterAlternateScreen, LeaveAlternateScreen},
+use chrono::{Local, NaiveDate, Utc};
+use columns::Column;
+use completion::Completions;
+use crossterm::event::{
+    self, Event, KeyCode, KeyEventKind, KeyModifiers, MouseEvent, MouseEventKind,
 };
-use dom_smoothie::{Article, Config, Readability};
+use datepicker::{DatePicker, DatePickerAction, DatePickerPurpose};
+use downloads::{DownloadJob, DownloadQueue, Downloader};
+use events::{Wakeups, TICK_RATE};
+use highlights::Highlights;
 use itertools::Itertools;
-use log::{error, LevelFilter};
-use pocket::{GetPocketSync, SendResponse};
+use log::{error, info};
+use pdfindex::PdfIndex;
+use pocket::{BatchAction, BatchOutcome, GetPocketSync, NewItem, SendResponse};
+use popup::{PopupKind, PopupLayouts};
 use prss::{RssFeedItem, RssManager};
+use queue::ReadingQueue;
 use ratatui::{prelude::*, widgets::*};
 use rayon::prelude::*;
-use readingstats::{render_stats, TotalStats};
+use readingstats::{render_stats, StatsPanel, TotalStats};
 use reqwest::blocking::Client;
+use rsstriage::{FullText, RssTriageState};
 use serde_json::json;
 use std::{
+    collections::HashMap,
     error::Error,
     fs::{self, File},
     io::{self, Write},
     ops::Range,
-    path::Path,
-    sync::{Arc, Mutex},
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc, Arc, Mutex,
+    },
     thread::{self},
     time::{Duration, Instant},
 };
 use storage::{PocketItem, PocketItemUpdate};
 use style::palette::tailwind;
+use tageditor::{TagEditor, TagEditorAction, TagTarget};
+use thumbnails::Thumbnails;
+use titles::TitleOverrides;
+use transaction::Transaction;
 use tui_textarea::{CursorMove, TextArea};
+use tutorial::{Tutorial, TutorialAction};
 use unicode_width::UnicodeWidthStr;
+use watch::{WatchMatch, WatchQuery};
 
 const PALETTES: [tailwind::Palette; 4] = [
     tailwind::BLUE,
@@ -53,8 +118,14 @@ const PALETTES: [tailwind::Palette; 4] = [
     tailwind::RED,
 ];
 const INFO_TEXT: &str = "(ZZ) quit | gg/G/j/k  - start,end,↓,↑ | ? - Help";
-const ITEM_HEIGHT: usize = 4;
+const RSS_TRIAGE_INFO_TEXT: &str =
+    "(Esc) back | space - mark | a - add to Pocket | d - hide | h/l - source | p - preview | J/K - scroll preview | e - play media | f - full text | [/] - feed | c - fold | s - sort | D - hide feed";
+/// Lines of a list row: title, type with tags and the spacing below
+const ITEM_HEIGHT: usize = 3;
 const DELTA_FILE: &str = "snapshot_updates.db";
+const FOOTER_HEIGHT: u16 = 3;
+const EXCERPT_PANE_HEIGHT: u16 = 6;
+const SEARCH_DEBOUNCE: Duration = Duration::from_millis(150);
 
 pub struct Base16Palette {
     pub base_00: Color,
@@ -123,16 +194,14 @@ impl TableRow for PocketItem {
 
     fn date(&self) -> String {
         let timestamp = self.time_added.parse::<i64>().unwrap();
-        let naive = DateTime::from_timestamp(timestamp, 0).unwrap();
-        let datetime: DateTime<Utc> = naive.to_utc();
-        let newdate = datetime.format("%Y-%m-%d");
+        let newdate = timezone::date_of(timestamp).format("%Y-%m-%d");
         format!("{}", newdate)
     }
 
     fn title(&self) -> &str {
-        &self
-            .given_title
+        self.title_override
             .as_deref()
+            .or(self.given_title.as_deref())
             .unwrap_or(&self.resolved_title.as_deref().unwrap_or("[empty]"))
     }
 
@@ -163,7 +232,7 @@ impl TableRow for PocketItem {
     }
 
     fn rename_title_to(&mut self, new_title: String) {
-        self.given_title = Some(new_title);
+        self.title_override = Some(new_title);
     }
 
     fn time_added(&self) -> u64 {
@@ -191,6 +260,10 @@ pub struct RssFeedState {
     pub has_updates: bool,
     pub error: Option<String>,
     pub items_processed: bool,
+    /// When at least one feed was last fetched successfully
+    pub last_fetched: Arc<Mutex<Option<Instant>>>,
+    /// Last success and last error of every feed, updated while fetching
+    pub feed_health: Arc<Mutex<prss::feed_health::FeedHealthLog>>,
 }
 
 impl RssFeedState {
@@ -201,6 +274,8 @@ impl RssFeedState {
             has_updates: false,
             error: None,
             items_processed: false,
+            last_fetched: Arc::new(Mutex::new(None)),
+            feed_health: Arc::new(Mutex::new(prss::feed_health::FeedHealthLog::new())),
         }
     }
     pub fn mark_items_processed(&mut self) {
@@ -209,99 +284,61 @@ impl RssFeedState {
     }
 }
 
-pub struct RssFeedPopupState {
-    pub items: Vec<RssFeedItem>,
-    pub selected_index: usize,
-    pub scroll_offset: usize,
-    pub visible_items: usize,
-    hidden_items: prss::hidden_items::HiddenItems,
-    status_message: Option<(String, Instant)>, // Message and timestamp
-    pending_pocket_item: Option<RssFeedItem>,  // Store item waiting for tags
-    show_description: bool,
-    pub changes_made: bool,
+/// Words per minute for the reading time of articles
+const READING_SPEED_WPM: usize = 225;
+
+/// Shown items summed up for the footer, recomputed when the filters change
+#[derive(Debug, Default, PartialEq)]
+struct ViewSummary {
+    articles: usize,
+    videos: usize,
+    pdfs: usize,
+    oldest_added: Option<u64>,
+    /// Estimated from the word count Pocket has for articles
+    reading_mins: usize,
 }
 
-impl RssFeedPopupState {
-    pub fn new(mut items: Vec<RssFeedItem>, visible_items: usize) -> anyhow::Result<Self> {
-        let hidden_items = prss::hidden_items::HiddenItems::load()?;
-        items.retain(|item| !hidden_items.is_hidden(&item.item_id));
-
-        Ok(Self {
-            items,
-            selected_index: 0,
-            scroll_offset: 0,
-            visible_items,
-            hidden_items,
-            status_message: None,
-            pending_pocket_item: None,
-            show_description: false,
-            changes_made: false,
-        })
-    }
-
-    pub fn prepare_add_to_pocket(&mut self) -> Option<RssFeedItem> {
-        if let Some(selected_item) = self.items.get(self.selected_index).cloned() {
-            self.pending_pocket_item = Some(selected_item.clone());
-            Some(selected_item)
-        } else {
-            None
-        }
-    }
-    pub fn move_selection(&mut self, delta: isize) {
-        let new_index = self.selected_index as isize + delta;
-        self.selected_index = new_index.clamp(0, self.items.len() as isize - 1) as usize;
-        if self.selected_index < self.scroll_offset {
-            self.scroll_offset = self.selected_index;
-        } else if self.selected_index >= self.scroll_offset + self.visible_items {
-            self.scroll_offset = self.selected_index - self.visible_items + 1;
-        }
-    }
-    pub fn hide_current_item(&mut self) -> anyhow::Result<()> {
-        if let Some(item) = self.items.get(self.selected_index) {
-            self.hidden_items.hide_item(item.item_id.clone())?;
-            self.items.remove(self.selected_index);
-            if self.selected_index >= self.items.len() && self.items.len() > 0 {
-                self.selected_index = self.items.len() - 1;
+impl ViewSummary {
+    fn of<'a>(items: impl Iterator<Item = &'a PocketItem>) -> Self {
+        let mut summary = Self::default();
+        for item in items {
+            match item.item_type() {
+                "video" => summary.videos += 1,
+                "pdf" => summary.pdfs += 1,
+                _ => summary.articles += 1,
             }
+            let added = item.time_added();
+            summary.oldest_added = Some(summary.oldest_added.map_or(added, |o| o.min(added)));
+            let words = item.word_count.parse::<usize>().unwrap_or(0);
+            summary.reading_mins += words.div_ceil(READING_SPEED_WPM);
         }
-        Ok(())
-    }
-    pub fn set_status(&mut self, message: String) {
-        self.status_message = Some((message, Instant::now()));
+        summary
     }
 
-    pub fn add_current_to_pocket(
-        &mut self,
-        pocket_client: &GetPocketSync,
-        tags_input: &str,
-    ) -> anyhow::Result<()> {
-        if let Some(item) = self.pending_pocket_item.take() {
-            // Parse tags in the application code
-            let tags: Vec<String> = tags_input
-                .split(',')
-                .map(|t| t.trim().to_string())
-                .filter(|t| !t.is_empty())
-                .collect();
-
-            // Add to Pocket with parsed tags
-            pocket_client.add(&item.link, &tags)?;
-
-            // Hide the item
-            self.hidden_items.hide_item(item.item_id.clone())?;
-
-            // Remove from current list
-            self.items.remove(self.selected_index);
-            if self.selected_index >= self.items.len() && self.items.len() > 0 {
-                self.selected_index = self.items.len() - 1;
-            }
-
-            // Set success message
-            self.set_status(format!("✓ Added to Pocket with {} tags", tags.len()));
-            self.changes_made = true;
-            Ok(())
-        } else {
-            Err(anyhow::anyhow!("No item selected"))
+    /// e.g. `12 art · 3 vid · 1 pdf | oldest 2y | ~5h to read`
+    fn footer_text(&self, now: i64) -> String {
+        let mut text = format!(
+            "{} art · {} vid · {} pdf",
+            self.articles, self.videos, self.pdfs
+        );
+        if let Some(oldest) = self.oldest_added {
+            let days = (now - oldest as i64).max(0) / 86400;
+            let age = match days {
+                0..=59 => format!("{}d", days),
+                60..=729 => format!("{}mo", days / 30),
+                _ => format!("{}y", days / 365),
+            };
+            text.push_str(&format!(" | oldest {}", age));
+        }
+        if self.reading_mins > 0 {
+            let time = if self.reading_mins < 60 {
+                format!("{}m", self.reading_mins)
+            } else {
+                format!("{}h", self.reading_mins / 60)
+            };
+            text.push_str(&format!(" | ~{} to read", time));
         }
+        text
     }
 }
 
@@ -327,23 +364,26 @@ impl ReadingStats {
     }
 }
 
-fn collect_stats(items: &Vec<impl TableRow>, start_idx: usize) -> ReadingStats {
+/// Items of each type added on `date`
+fn collect_stats<'a, T: TableRow + 'a>(
+    items: impl Iterator<Item = &'a T>,
+    date: &str,
+) -> ReadingStats {
     let mut stats = ReadingStats::new();
-    let mut idx = start_idx;
-    let current_date = items.get(start_idx).unwrap().date();
-    while idx < items.len() && items.get(idx).unwrap().date() == current_date {
-        let item = items.get(idx).unwrap();
+    for item in items.filter(|item| item.date() == date) {
         match item.item_type() {
             "article" => stats.articles_total += 1,
             "video" => stats.videos_total += 1,
             "pdf" => stats.pdfs_total += 1,
             _ => {} // do nothing
         }
-        idx += 1;
     }
     stats
 }
 
+/// Tag popup entry of the items without tags, picking it filters them
+const UNTAGGED: &str = "(untagged)";
+
 struct TagPopupState {
     tags: Vec<(String, usize)>,
     filtered_tags: Vec<(String, usize)>,
@@ -351,23 +391,100 @@ struct TagPopupState {
     scroll_offset: usize,
     visible_items: usize,
     filter: String,
+    /// Most used tags first instead of alphabetical order, toggled with Tab
+    sort_by_count: bool,
+    /// Tags with children, see `tagtree`
+    parents: std::collections::HashSet<String>,
+    /// Parents with their children hidden
+    collapsed: std::collections::HashSet<String>,
+    /// Typed filter ranks tags by `fuzzy` score instead of keeping those containing it
+    fuzzy: bool,
 }
 
 impl TagPopupState {
     fn new(tags: Vec<(String, usize)>, visible_items: usize) -> Self {
-        Self {
-            filtered_tags: tags.clone(),
+        let parents = tags
+            .iter()
+            .filter_map(|(tag, _)| tagtree::parent(tag))
+            .map(String::from)
+            .collect();
+        let mut state = Self {
+            filtered_tags: Vec::new(),
             tags,
             selected_index: 0,
             scroll_offset: 0,
             visible_items,
             filter: String::new(),
+            sort_by_count: false,
+            parents,
+            collapsed: std::collections::HashSet::new(),
+            fuzzy: false,
+        };
+        state.sort();
+        state
+    }
+
+    /// Children follow their parent, the untagged entry stays on top in both orders
+    fn sort(&mut self) {
+        let by_count = self.sort_by_count;
+        let counts: Vec<(String, usize)> = self.tags.clone();
+        let counts: std::collections::HashMap<&str, usize> = counts
+            .iter()
+            .map(|(tag, count)| (tag.as_str(), *count))
+            .collect();
+        self.tags.sort_by(|a, b| {
+            (b.0 == UNTAGGED)
+                .cmp(&(a.0 == UNTAGGED))
+                .then_with(|| tagtree::tree_order(&a.0, &b.0, &counts, by_count))
+        });
+        self.apply_filter();
+    }
+
+    fn is_folded(&self, tag: &str) -> bool {
+        tagtree::with_parents(tag)
+            .filter(|parent| *parent != tag)
+            .any(|parent| self.collapsed.contains(parent))
+    }
+
+    /// Folds the selected parent, or the parent of the selected tag, and unfolds it back
+    fn fold_selected(&mut self, fold: bool) {
+        let Some((tag, _)) = self.filtered_tags.get(self.selected_index) else {
+            return;
+        };
+        let target = if fold && !self.parents.contains(tag) {
+            tagtree::parent(tag)
+        } else {
+            Some(tag.as_str())
+        };
+        let Some(target) = target
+            .filter(|target| self.parents.contains(*target))
+            .map(String::from)
+        else {
+            return;
+        };
+        if fold {
+            self.collapsed.insert(target.clone());
+        } else {
+            self.collapsed.remove(&target);
+        }
+        self.apply_filter();
+        if let Some(idx) = self
+            .filtered_tags
+            .iter()
+            .position(|(tag, _)| *tag == target)
+        {
+            self.move_selection(idx as isize);
         }
     }
 
+    fn toggle_sort(&mut self) {
+        self.sort_by_count = !self.sort_by_count;
+        self.sort();
+    }
+
     fn move_selection(&mut self, delta: isize) {
         let new_index = self.selected_index as isize + delta;
-        self.selected_index = new_index.clamp(0, self.tags.len() as isize - 1) as usize;
+        self.selected_index = new_index.clamp(0, self.filtered_tags.len() as isize - 1) as usize;
 
         if self.selected_index < self.scroll_offset {
             self.scroll_offset = self.selected_index;
@@ -382,13 +499,28 @@ impl TagPopupState {
             .map(|(tag, _)| tag.clone())
     }
 
+    /// Typed filter lists matching tags flat, without it the tree shows what isn't folded
     fn apply_filter(&mut self) {
-        self.filtered_tags = self
-            .tags
-            .iter()
-            .filter(|(tag, _)| tag.to_lowercase().contains(&self.filter.to_lowercase()))
-            .cloned()
-            .collect();
+        let filter = self.filter.to_lowercase();
+        let filtered_tags = if self.fuzzy && !filter.is_empty() {
+            fuzzy::rank(&filter, self.tags.iter(), |(tag, _)| tag.as_str())
+                .into_iter()
+                .cloned()
+                .collect()
+        } else {
+            self.tags
+                .iter()
+                .filter(|(tag, _)| {
+                    if filter.is_empty() {
+                        !self.is_folded(tag)
+                    } else {
+                        tag.to_lowercase().contains(&filter)
+                    }
+                })
+                .cloned()
+                .collect()
+        };
+        self.filtered_tags = filtered_tags;
         self.selected_index = 0;
         self.scroll_offset = 0;
     }
@@ -435,7 +567,14 @@ impl DocTypePopupState {
 
 enum LoadingType {
     Refresh,
-    Download,
+    Reextract,
+    Rebuild,
+    /// Adds the tag to every shown item, or removes it
+    BulkTag {
+        tag: String,
+        remove: bool,
+    },
+    Summarize,
 }
 
 struct RefreshingPopup {
@@ -457,14 +596,14 @@ impl RefreshingPopup {
 }
 
 struct DomainStatsPopupState {
-    stats: Vec<(String, usize)>,
+    stats: Vec<(SourceFilter, usize)>,
     selected_index: usize,
     scroll_offset: usize,
     visible_items: usize,
 }
 
 impl DomainStatsPopupState {
-    fn new(stats: Vec<(String, usize)>, visible_items: usize) -> Self {
+    fn new(stats: Vec<(SourceFilter, usize)>, visible_items: usize) -> Self {
         Self {
             stats,
             selected_index: 0,
@@ -486,2163 +625,6665 @@ impl DomainStatsPopupState {
     }
 }
 
-struct HelpPopupState {
-    content: String,
+/// Every author in the list with the number of their items, several can be chosen at once
+struct AuthorsPopupState {
+    authors: Vec<(String, usize)>,
+    chosen: std::collections::BTreeSet<String>,
+    state: ListState,
 }
 
-#[derive(Clone)]
-enum Confirmation {
-    DeletePocketItem,
-}
+impl AuthorsPopupState {
+    fn new(items: &[PocketItem]) -> Self {
+        let mut counts = std::collections::HashMap::new();
+        for author in items.iter().flat_map(|item| item.authors.iter().flatten()) {
+            *counts.entry(author.clone()).or_insert(0) += 1;
+        }
+        let mut authors: Vec<(String, usize)> = counts.into_iter().collect();
+        authors.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        Self {
+            authors,
+            chosen: std::collections::BTreeSet::new(),
+            state: ListState::default().with_selected(Some(0)),
+        }
+    }
 
-#[derive(Clone)]
-struct SearchMode {
-    search: String,
-    normal_mode_positions: (usize, usize),
-}
+    fn move_selection(&mut self, delta: isize) {
+        let last = self.authors.len().saturating_sub(1) as isize;
+        let selected = self.state.selected().unwrap_or(0) as isize + delta;
+        self.state.select(Some(selected.clamp(0, last) as usize));
+    }
 
-impl SearchMode {
-    pub fn new(normal_mode_positions: (usize, usize)) -> Self {
-        SearchMode {
-            search: String::new(),
-            normal_mode_positions,
+    fn toggle_chosen(&mut self) {
+        let Some((author, _)) = self.state.selected().and_then(|idx| self.authors.get(idx)) else {
+            return;
+        };
+        if !self.chosen.remove(author) {
+            self.chosen.insert(author.clone());
         }
     }
-}
-
-#[derive(Clone)]
-enum CommandType {
-    RenameItem,
-    JumpToDate,
-    Tags,
-}
 
-#[derive(Clone)]
-struct TextSuggestion {
-    full_text: String,
-    completion: String,
+    /// The chosen authors, or the selected one when none are
+    fn filter(&self) -> Option<SourceFilter> {
+        let writers: Vec<String> = if self.chosen.is_empty() {
+            let (author, _) = self.authors.get(self.state.selected()?)?;
+            vec![author.clone()]
+        } else {
+            self.chosen.iter().cloned().collect()
+        };
+        Some(SourceFilter::Writers(writers))
+    }
 }
 
-#[derive(Clone)]
-pub struct CommandEnterMode {
-    prompt: String,
-    current_enter: String,
-    cursor_pos: usize,
-    command_type: CommandType,
-    current_suggestion: Option<TextSuggestion>,
+struct WatchPopupState {
+    matches: Vec<WatchMatch>,
+    selected_index: usize,
+    scroll_offset: usize,
+    visible_items: usize,
 }
 
-impl CommandEnterMode {
-    fn new_empty(prompt: String, command_type: CommandType) -> Self {
+impl WatchPopupState {
+    fn new(matches: Vec<WatchMatch>, visible_items: usize) -> Self {
         Self {
-            prompt,
-            current_enter: String::new(),
-            cursor_pos: 0,
-            command_type,
-            current_suggestion: None,
+            matches,
+            selected_index: 0,
+            scroll_offset: 0,
+            visible_items,
         }
     }
-    fn new(prompt: String, current_enter: String, command_type: CommandType) -> Self {
-        let cursor_pos = current_enter.len();
+
+    fn move_selection(&mut self, delta: isize) {
+        let new_index = self.selected_index as isize + delta;
+        self.selected_index = new_index.clamp(0, self.matches.len() as isize - 1) as usize;
+
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        } else if self.selected_index >= self.scroll_offset + self.visible_items {
+            self.scroll_offset = self.selected_index - self.visible_items + 1;
+        }
+    }
+}
+
+/// Favorited items, also the archived ones the main list leaves out
+struct FavoritesPopupState {
+    items: Vec<PocketItem>,
+    selected_index: usize,
+    scroll_offset: usize,
+    visible_items: usize,
+}
+
+impl FavoritesPopupState {
+    fn new(items: Vec<PocketItem>, visible_items: usize) -> Self {
         Self {
-            prompt,
-            current_enter,
-            cursor_pos,
-            command_type,
-            current_suggestion: None,
+            items,
+            selected_index: 0,
+            scroll_offset: 0,
+            visible_items,
         }
     }
-    fn update_suggestion(&mut self, suggestions: &[String]) {
-        // Get the current text being typed
-        let current_text = match self.command_type {
-            CommandType::Tags => {
-                // For tags, look at text after the last comma
-                self.current_enter
-                    .split(',')
-                    .last()
-                    .map(|s| s.trim())
-                    .unwrap_or("")
-            }
-            _ => &self.current_enter,
-        };
 
-        error!("Tag: {}, suggestions: {:?}", current_text, suggestions);
-        if current_text.len() >= 2 {
-            // Find matching suggestions
-            let matching_texts: Vec<&String> = suggestions
-                .iter()
-                .filter(|text| {
-                    text.to_lowercase()
-                        .starts_with(&current_text.to_lowercase())
-                        && text.len() > current_text.len()
-                })
-                .collect();
+    fn move_selection(&mut self, delta: isize) {
+        let new_index = self.selected_index as isize + delta;
+        self.selected_index = new_index.clamp(0, self.items.len() as isize - 1) as usize;
 
-            // Take the first matching tag as suggestion
-            if let Some(suggestion) = matching_texts.first() {
-                let completion = suggestion[current_text.len()..].to_string();
-                self.current_suggestion = Some(TextSuggestion {
-                    full_text: suggestion.to_string(),
-                    completion,
-                });
-            } else {
-                self.current_suggestion = None;
-            }
-        } else {
-            self.current_suggestion = None;
-        }
-    }
-
-    fn complete_suggestion(&mut self) -> bool {
-        if let Some(suggestion) = &self.current_suggestion {
-            // Get everything before the current tag
-            let prefix = self
-                .current_enter
-                .rsplit_once(',')
-                .map(|(before, _)| format!("{},", before))
-                .unwrap_or_default();
-
-            // Get the current incomplete tag
-            let current_tag = self
-                .current_enter
-                .split(',')
-                .last()
-                .map(|s| s.trim())
-                .unwrap_or("");
-
-            // Complete the tag
-            self.current_enter = if prefix.is_empty() {
-                format!("{}, ", suggestion.full_text)
-            } else {
-                format!("{} {}, ", prefix, suggestion.full_text)
-            };
-            self.cursor_pos = self.current_enter.len();
-            self.current_suggestion = None;
-            true
-        } else {
-            false
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        } else if self.selected_index >= self.scroll_offset + self.visible_items {
+            self.scroll_offset = self.selected_index - self.visible_items + 1;
         }
     }
 }
 
-enum AppMode {
-    Initialize,
-    Normal,
-    Search(SearchMode),
-    Confirmation(Confirmation),
-    MulticharNormalModeEnter(String),
-    CommandEnter(CommandEnterMode),
-    Refreshing(RefreshingPopup),
-    Error(String),
+/// Items archived in Pocket, fetched a page at a time as the selection gets close to the end
+struct ArchivePopupState {
+    items: Vec<PocketItem>,
+    selected_index: usize,
+    scroll_offset: usize,
+    visible_items: usize,
+    /// Where the next page starts, the number of items Pocket returned so far
+    offset: u32,
+    /// A short page came back, there is nothing more to fetch
+    complete: bool,
+    /// Page being fetched in the background
+    loading: Option<mpsc::Receiver<anyhow::Result<storage::Pocket>>>,
 }
 
-struct FilteredItems<T> {
-    pub items: Vec<T>,
-    is_filter_on: bool,
-    filtered: Vec<usize>,
-}
+impl ArchivePopupState {
+    /// Items left below the selection when the next page is fetched
+    const PREFETCH: usize = 10;
 
-impl<T> FilteredItems<T> {
-    pub fn non_archived(data: Vec<PocketItem>) -> FilteredItems<PocketItem> {
-        let filtered = data
-            .into_iter()
-            .filter(|x| x.status != "1")
-            .collect::<Vec<PocketItem>>();
-        let data_vec_size = filtered.len();
-        FilteredItems {
-            items: filtered,
-            is_filter_on: false,
-            filtered: Vec::with_capacity(data_vec_size),
+    fn new(visible_items: usize) -> Self {
+        Self {
+            items: Vec::new(),
+            selected_index: 0,
+            scroll_offset: 0,
+            visible_items,
+            offset: 0,
+            complete: false,
+            loading: None,
         }
     }
 
-    pub fn new(data: Vec<T>) -> Self {
-        let data_vec_size = data.len();
-        FilteredItems {
-            items: data,
-            is_filter_on: false,
-            filtered: Vec::with_capacity(data_vec_size),
-        }
+    fn needs_page(&self) -> bool {
+        !self.complete
+            && self.loading.is_none()
+            && self.selected_index + Self::PREFETCH >= self.items.len()
     }
 
-    pub fn len(&self) -> usize {
-        if !self.is_filter_on {
-            self.items.len()
-        } else {
-            self.filtered.len()
-        }
+    /// Appends a fetched page in the order Pocket returned it
+    fn add_page(&mut self, page: storage::Pocket) {
+        let returned = page.list.len() as u32;
+        self.offset += returned;
+        self.complete = returned < pocket::ARCHIVE_PAGE_SIZE;
+        self.items.extend(
+            page.pocket_items()
+                .into_values()
+                .sorted_by_key(|item| item.sort_id),
+        );
     }
 
-    pub fn iter(&self) -> Box<dyn Iterator<Item = &T> + '_> {
-        if !self.is_filter_on {
-            Box::new(self.items.iter())
-        } else {
-            Box::new(self.filtered.iter().map(|i| &self.items[*i]))
+    fn move_selection(&mut self, delta: isize) {
+        if self.items.is_empty() {
+            return;
         }
-    }
+        let new_index = self.selected_index as isize + delta;
+        self.selected_index = new_index.clamp(0, self.items.len() as isize - 1) as usize;
 
-    pub fn clear_filter(&mut self) {
-        self.is_filter_on = false;
-        self.filtered.clear();
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        } else if self.selected_index >= self.scroll_offset + self.visible_items {
+            self.scroll_offset = self.selected_index - self.visible_items + 1;
+        }
     }
+}
 
-    pub fn apply_filter<P>(&mut self, mut predicate: P)
-    where
-        P: FnMut(&T) -> bool,
-    {
-        self.is_filter_on = true;
-        self.filtered.clear();
-        self.items
-            .iter()
-            .enumerate()
-            .filter(|(_, x)| predicate(x))
-            .for_each(|(i, _)| self.filtered.push(i));
+/// Items edited here and elsewhere since the last sync, resolved one by one
+struct ConflictsPopupState {
+    conflicts: Vec<conflicts::Conflict>,
+    selected_index: usize,
+}
+
+impl ConflictsPopupState {
+    fn move_selection(&mut self, delta: isize) {
+        let new_index = self.selected_index as isize + delta;
+        self.selected_index = new_index.clamp(0, self.conflicts.len() as isize - 1) as usize;
     }
+}
 
-    fn get_mut(&mut self, idx: usize) -> Option<&mut T> {
-        if !self.is_filter_on {
-            self.items.get_mut(idx)
-        } else {
-            self.filtered
-                .get(idx)
-                .map(|index| self.items.get_mut(*index))
-                .flatten()
-        }
-    }
+/// Changes brought by the last sync, shown when there are any
+struct SyncDiffPopupState {
+    diff: syncdiff::SyncDiff,
+    selected_index: usize,
+    scroll_offset: usize,
+    visible_items: usize,
+}
 
-    fn get(&self, idx: usize) -> Option<&T> {
-        if !self.is_filter_on {
-            self.items.get(idx)
-        } else {
-            self.filtered
-                .get(idx)
-                .map(|index| self.items.get(*index))
-                .flatten()
+impl SyncDiffPopupState {
+    fn new(diff: syncdiff::SyncDiff, visible_items: usize) -> Self {
+        Self {
+            diff,
+            selected_index: 0,
+            scroll_offset: 0,
+            visible_items,
         }
     }
 
-    fn remove(&mut self, idx: usize) {
-        if !self.is_filter_on {
-            self.items.remove(idx);
-        } else {
-            self.filtered
-                .get(idx)
-                .map(|index| self.items.remove(*index));
-        }
-    }
+    fn move_selection(&mut self, delta: isize) {
+        let new_index = self.selected_index as isize + delta;
+        self.selected_index = new_index.clamp(0, self.diff.changes.len() as isize - 1) as usize;
 
-    fn index(&self, range: Range<usize>) -> Vec<&T> {
-        if !self.is_filter_on {
-            self.items[range].iter().collect()
-        } else {
-            if self.filtered.is_empty() {
-                Vec::new()
-            } else {
-                let start = range.start;
-                let end = std::cmp::min(range.end, self.filtered.len());
-                self.filtered[start..end]
-                    .iter()
-                    .map(|i| &self.items[*i])
-                    .collect()
-            }
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        } else if self.selected_index >= self.scroll_offset + self.visible_items {
+            self.scroll_offset = self.selected_index - self.visible_items + 1;
         }
     }
 }
 
-#[derive(Clone, PartialEq)]
-enum ItemTypeFilter {
-    All,
-    Article,
-    Video,
-    PDF,
+struct FailedAction {
+    action: BatchAction,
+    title: String,
+    error: String,
 }
 
-#[derive(PartialEq)]
-enum TagSelectionMode {
-    Normal,
-    Filtering,
+/// Actions of a bulk request that Pocket rejected, they can be retried without resending the rest
+struct FailedActionsPopupState {
+    failed: Vec<FailedAction>,
 }
-const SCROLL_STEP: usize = 1; // Number of items to scroll at once
 
-struct App {
-    virtual_state: TableState,
-    state: TableState,
-    items: FilteredItems<PocketItem>,
-    longest_item_lens: (u16, u16, u16), // order is (name, address, email)
-    scroll_state: ScrollbarState,
-    colors: TableColors,
-    color_index: usize,
-    app_mode: AppMode,
-    stats: TotalStats,
-    pocket_client: GetPocketSync,
-    tag_popup_state: Option<TagPopupState>,
-    doc_type_popup_state: Option<DocTypePopupState>,
-    selected_tag_filter: Option<String>,
-    active_search_filter: Option<String>,
-    item_type_filter: ItemTypeFilter,
-    domain_filter: Option<String>,
-    tag_selection_mode: TagSelectionMode,
-    scroll_accumulator: f32,
-    last_click_time: Option<std::time::Instant>,
-    last_click_position: Option<(u16, u16)>,
-    domain_stats_popup_state: Option<DomainStatsPopupState>,
-    help_popup_state: Option<HelpPopupState>,
-    rss_feed_popup_state: Option<RssFeedPopupState>,
-    download_client: Client,
-    cached_tags: Vec<String>,
-    rss_feed_state: RssFeedState,
+/// Where the list rows were drawn last time, the table has no header so rows start at the top
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+struct TableLayout {
+    area: Rect,
+    /// Item in the top row
+    first_item: usize,
+    /// Rows drawn in full, the table leaves out a row that doesn't fit
+    rows: usize,
 }
 
-impl App {
-    fn new(data_vec: Vec<PocketItem>, pocket_client: GetPocketSync, stats: TotalStats) -> App {
-        let cached_tags = data_vec
-            .iter()
-            .flat_map(|item| item.tags().map(|tag| tag.to_string()))
-            .collect::<std::collections::HashSet<_>>()
-            .into_iter()
-            .collect();
-        App {
-            virtual_state: TableState::default().with_selected(0),
-            state: TableState::default().with_selected(0),
-            longest_item_lens: constraint_len_calculator(&data_vec),
-            // scroll_state: ScrollbarState::new((data_vec.len() - 1) * ITEM_HEIGHT),
-            scroll_state: ScrollbarState::new(1), //todo: fix this
-            colors: TableColors::new(&PALETTES[0]),
-            color_index: 0,
-            items: FilteredItems::<PocketItem>::non_archived(data_vec),
-            app_mode: AppMode::Initialize,
-            pocket_client,
-            stats,
-            tag_popup_state: None,
-            doc_type_popup_state: None,
-            selected_tag_filter: None,
-            active_search_filter: None,
-            item_type_filter: ItemTypeFilter::All,
-            domain_filter: None,
-            tag_selection_mode: TagSelectionMode::Normal,
-            scroll_accumulator: 0.0,
-            last_click_time: None,
-            last_click_position: None,
-            domain_stats_popup_state: None,
-            help_popup_state: None,
-            download_client: Client::new(),
-            rss_feed_popup_state: None,
-            cached_tags,
-            rss_feed_state: RssFeedState::new(),
+impl TableLayout {
+    fn item_at(&self, row: u16) -> Option<usize> {
+        if row < self.area.y || row >= self.area.bottom() {
+            return None;
         }
+        let visible_row = (row - self.area.y) as usize / ITEM_HEIGHT;
+        (visible_row < self.rows).then_some(self.first_item + visible_row)
     }
+}
 
-    fn handle_neovim_edit(&mut self) -> anyhow::Result<Option<String>> {
-        // Create a temporary file
-        let temp_path = format!("/tmp/pocket_tui_{}.txt", std::process::id());
-        File::create(&temp_path)?;
+/// Parts of the footer that react to a click, filters are cleared and the RSS badge opens triage
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum FooterChip {
+    Search,
+    Navigation,
+    Tag,
+    Domain,
+    DateRange,
+    DocType,
+    Rss,
+}
 
-        // Save terminal state and switch to normal mode for neovim
-        disable_raw_mode()?;
-        execute!(io::stdout(), LeaveAlternateScreen)?;
+/// Screen columns of the `chips` spans when `spans` are drawn into `area` with `alignment`
+fn footer_chip_areas(
+    spans: &[Span],
+    chips: &[(std::ops::Range<usize>, FooterChip)],
+    alignment: Alignment,
+    area: Rect,
+) -> Vec<(Rect, FooterChip)> {
+    let widths: Vec<u16> = spans.iter().map(|span| span.width() as u16).collect();
+    let total: u16 = widths.iter().sum();
+    let start = match alignment {
+        Alignment::Center => area.x + area.width.saturating_sub(total) / 2,
+        _ => area.x,
+    };
+    chips
+        .iter()
+        .map(|(range, chip)| {
+            let x = start + widths[..range.start].iter().sum::<u16>();
+            let width = widths[range.clone()].iter().sum::<u16>();
+            (Rect::new(x, area.y, width, 1).intersection(area), *chip)
+        })
+        .collect()
+}
 
-        // Launch neovim
-        let status = std::process::Command::new("nvim")
-            .arg(&temp_path)
-            .status()
-            .context("Failed to start neovim")?;
+#[derive(Clone, Copy, Debug, PartialEq)]
+enum ContextAction {
+    Open,
+    Delete,
+    Tag,
+    Download,
+    FilterByDomain,
+}
 
-        // Restore terminal state for Ratatui
-        enable_raw_mode()?;
-        execute!(
-            io::stdout(),
-            EnterAlternateScreen,
-            EnableMouseCapture,
-            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
-        )?;
+/// Entries of the right-click menu with the keys doing the same in normal mode
+const CONTEXT_MENU: [(ContextAction, &str); 5] = [
+    (ContextAction::Open, "Open          Enter"),
+    (ContextAction::Delete, "Delete        d"),
+    (ContextAction::Tag, "Edit tags     T"),
+    (ContextAction::Download, "Download      w"),
+    (ContextAction::FilterByDomain, "Same site     s"),
+];
+const CONTEXT_MENU_WIDTH: u16 = 23;
 
-        let result = if status.success() {
-            let content = fs::read_to_string(&temp_path)?;
-            fs::remove_file(&temp_path)?;
-            Ok(Some(content))
-        } else {
-            Ok(None)
-        };
+/// Actions on the row under the mouse, opened with a right click where the click was
+struct ContextMenuState {
+    column: u16,
+    row: u16,
+    selected_index: usize,
+}
 
-        // Clean up temp file if it still exists
-        if Path::new(&temp_path).exists() {
-            fs::remove_file(&temp_path)?;
-        }
+impl ContextMenuState {
+    fn move_selection(&mut self, delta: isize) {
+        let new_index = self.selected_index as isize + delta;
+        self.selected_index = new_index.clamp(0, CONTEXT_MENU.len() as isize - 1) as usize;
+    }
 
-        // Queue a redraw of the UI
-        crossterm::queue!(
-            io::stdout(),
-            crossterm::terminal::Clear(crossterm::terminal::ClearType::All)
-        )?;
-        io::stdout().flush()?;
+    /// Below and right of the click, moved up or left when it doesn't fit into `frame`
+    fn area(&self, frame: Rect) -> Rect {
+        let width = CONTEXT_MENU_WIDTH.min(frame.width);
+        let height = (CONTEXT_MENU.len() as u16 + 2).min(frame.height);
+        let x = self.column.min(frame.x + frame.width - width);
+        let y = self.row.min(frame.y + frame.height - height);
+        Rect::new(x, y, width, height)
+    }
 
-        result
+    /// Menu entry at a screen position, none outside of the menu
+    fn action_at(&self, frame: Rect, column: u16, row: u16) -> Option<ContextAction> {
+        let inner = self.area(frame).inner(Margin::new(1, 1));
+        if !inner.contains(Position::new(column, row)) {
+            return None;
+        }
+        CONTEXT_MENU
+            .get((row - inner.y) as usize)
+            .map(|(action, _)| *action)
     }
+}
 
-    //// ------- tmux based popup. working but requires tmux
-    // fn handle_neovim_edit(&mut self) -> anyhow::Result<Option<String>> {
-    //     if !self.is_inside_tmux() {
-    //         return Err(anyhow::anyhow!("Must be running inside tmux session"));
-    //     }
+struct HelpPopupState {
+    content: String,
+}
 
-    //     // Create a temporary file
-    //     let temp_path = format!("/tmp/pocket_tui_{}.txt", std::process::id());
-    //     File::create(&temp_path)?;
+/// Recent log entries of the session, `l` switches the least severe level shown
+struct LogsPopupState {
+    min_level: log::Level,
+    entries: Vec<logbuffer::LogEntry>,
+    selected_index: usize,
+    scroll_offset: usize,
+    visible_items: usize,
+}
 
-    //     // Calculate dimensions for the popup (80% of terminal size)
-    //     let terminal_size = crossterm::terminal::size()?;
-    //     let width = (terminal_size.0 as f32 * 0.8) as u16;
-    //     let height = (terminal_size.1 as f32 * 0.8) as u16;
-    //     let x = (terminal_size.0 - width) / 2;
-    //     let y = (terminal_size.1 - height) / 2;
+impl LogsPopupState {
+    fn new(visible_items: usize) -> Self {
+        let mut state = Self {
+            min_level: log::Level::Warn,
+            entries: Vec::new(),
+            selected_index: 0,
+            scroll_offset: 0,
+            visible_items,
+        };
+        state.reload();
+        state
+    }
 
-    //     // Launch tmux popup with neovim without disturbing current terminal
-    //     let tmux_cmd = format!(
-    //         "tmux popup -E -d '{}' -w {} -h {} -x {} -y {} 'nvim {}'",
-    //         std::env::current_dir()?.display(),
-    //         width,
-    //         height,
-    //         x,
-    //         y,
-    //         temp_path
-    //     );
+    /// Takes the entries logged since, the newest one gets selected
+    fn reload(&mut self) {
+        self.entries = logbuffer::recent(self.min_level);
+        self.selected_index = 0;
+        self.scroll_offset = 0;
+        self.move_selection(self.entries.len() as isize);
+    }
 
-    //     let output = std::process::Command::new("sh")
-    //         .arg("-c")
-    //         .arg(&tmux_cmd)
-    //         .output()
-    //         .context("Failed to start tmux popup with neovim")?;
+    fn cycle_level(&mut self) {
+        self.min_level = match self.min_level {
+            log::Level::Error => log::Level::Warn,
+            log::Level::Warn => log::Level::Info,
+            _ => log::Level::Error,
+        };
+        self.reload();
+    }
 
-    //     let result = if output.status.success() {
-    //         // Read the content after editing
-    //         let content = fs::read_to_string(&temp_path)?;
-    //         fs::remove_file(&temp_path)?;
-    //         Ok(Some(content))
-    //     } else {
-    //         Ok(None)
-    //     };
+    fn move_selection(&mut self, delta: isize) {
+        let new_index = self.selected_index as isize + delta;
+        self.selected_index = new_index.clamp(0, (self.entries.len() as isize - 1).max(0)) as usize;
 
-    //     // Clean up temp file if it still exists
-    //     if Path::new(&temp_path).exists() {
-    //         fs::remove_file(&temp_path)?;
-    //     }
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        } else if self.selected_index >= self.scroll_offset + self.visible_items {
+            self.scroll_offset = self.selected_index - self.visible_items + 1;
+        }
+    }
+}
 
-    //     result
-    // }
+/// Bullet points from the summary endpoint for a downloaded article
+struct SummaryPopupState {
+    title: String,
+    summary: String,
+    scroll: usize,
+}
 
-    fn is_tmux_available() -> bool {
-        std::process::Command::new("tmux")
-            .arg("-V")
-            .output()
-            .is_ok()
-    }
+/// Templates from the config to copy the selected item with
+struct SharePopupState {
+    selected_index: usize,
+}
 
-    fn is_inside_tmux(&self) -> bool {
-        std::env::var("TMUX").is_ok()
-    }
+/// Link of an item as a QR code, for reading it on a phone
+struct QrPopupState {
+    title: String,
+    url: String,
+    lines: Vec<String>,
+}
 
-    pub fn start_rss_feed_loading(&mut self) -> anyhow::Result<()> {
-        let subscription_manager = RssManager::new();
-        let feeds = subscription_manager.load_subscriptions()?;
-        if feeds.is_empty() {
-            return Ok(());
-        }
+/// Lines of a downloaded article marked as kept or cut by the content boundary rules
+struct BoundaryPreviewState {
+    item_id: String,
+    title: String,
+    lines: Vec<(bool, String)>,
+    scroll: usize,
+}
 
-        if let Ok(mut is_loading) = self.rss_feed_state.is_loading.lock() {
-            if *is_loading {
-                return Ok(());
-            } else {
-                *is_loading = true;
+/// Columns the article is wrapped at in the reader
+const READER_WIDTH: usize = 80;
+
+/// Downloaded article read line by line, lines between `anchor` and `cursor` become a highlight
+struct ReaderPopupState {
+    item_id: String,
+    title: String,
+    /// Downloaded file, the reading progress is kept next to it
+    article: PathBuf,
+    lines: Vec<String>,
+    cursor: usize,
+    /// Where the selection started, `v` toggles it
+    anchor: Option<usize>,
+    scroll_offset: usize,
+    visible_items: usize,
+    /// Line the last reading stopped at, until `c` continues from there
+    resume: Option<usize>,
+}
+
+impl ReaderPopupState {
+    fn new(item: &PocketItem, article: PathBuf, markdown: &str, visible_items: usize) -> Self {
+        let mut lines = Vec::new();
+        for line in markdown.lines().map(str::trim_end) {
+            let mut rest = line;
+            loop {
+                let (first, remaining) = textwidth::split_line(rest, READER_WIDTH);
+                lines.push(first.to_string());
+                if remaining.is_empty() {
+                    break;
+                }
+                rest = remaining;
             }
         }
+        let resume = progress::load(&article)
+            .map(|progress| progress.line)
+            .filter(|line| (1..lines.len()).contains(line));
+        Self {
+            item_id: item.item_id.clone(),
+            title: item.title().to_string(),
+            article,
+            lines,
+            cursor: 0,
+            anchor: None,
+            scroll_offset: 0,
+            visible_items,
+            resume,
+        }
+    }
 
-        let client = reqwest::blocking::ClientBuilder::new()
-            .timeout(Duration::from_secs(10))
-            .build()?;
+    fn continue_reading(&mut self) {
+        if let Some(line) = self.resume.take() {
+            self.move_cursor(line as isize - self.cursor as isize);
+        }
+    }
 
-        let items_arc = self.rss_feed_state.items.clone();
-        let hidden_items = prss::hidden_items::HiddenItems::load()?;
-        let is_loading_arc = self.rss_feed_state.is_loading.clone();
-        thread::spawn(move || {
-            let results = Arc::new(Mutex::new(Vec::new()));
+    /// The cursor, or the earlier stop when reading didn't continue past it
+    fn progress(&self) -> progress::ReadingProgress {
+        progress::ReadingProgress {
+            line: self
+                .resume
+                .map_or(self.cursor, |line| line.max(self.cursor)),
+            lines: self.lines.len(),
+        }
+    }
 
-            feeds.par_iter().for_each(|url| {
-                match RssManager::fetch_and_parse_feed(&client, url) {
-                    Ok(items) => {
-                        if let Ok(mut results_guard) = results.lock() {
-                            results_guard.extend(items);
-                        }
-                    }
-                    Err(e) => error!("Error fetching {}: {}", url, e),
-                }
-                thread::sleep(Duration::from_millis(100));
-            });
+    fn move_cursor(&mut self, delta: isize) {
+        let new_index = self.cursor as isize + delta;
+        self.cursor = new_index.clamp(0, (self.lines.len() as isize - 1).max(0)) as usize;
 
-            if let Ok(mut items_guard) = items_arc.lock() {
-                if let Ok(results_guard) = results.lock() {
-                    // Filter out hidden items
-                    let new_items: Vec<RssFeedItem> = results_guard
-                        .iter()
-                        .filter(|item| !hidden_items.is_hidden(&item.item_id))
-                        .cloned()
-                        .collect();
-                    *items_guard = new_items;
+        if self.cursor < self.scroll_offset {
+            self.scroll_offset = self.cursor;
+        } else if self.cursor >= self.scroll_offset + self.visible_items {
+            self.scroll_offset = self.cursor - self.visible_items + 1;
+        }
+    }
 
-                    if let Ok(mut is_loading) = is_loading_arc.lock() {
-                        *is_loading = false;
-                    } else {
-                        panic!("is_loading lock error"); //todo
-                    }
-                }
-            }
-        });
+    fn toggle_selection(&mut self) {
+        self.anchor = match self.anchor {
+            Some(_) => None,
+            None => Some(self.cursor),
+        };
+    }
 
-        Ok(())
+    /// First and last selected line, the cursor line alone when nothing is selected
+    fn selection(&self) -> (usize, usize) {
+        let anchor = self.anchor.unwrap_or(self.cursor);
+        (anchor.min(self.cursor), anchor.max(self.cursor))
     }
-    pub fn close_rss_feed_popup(&mut self) -> anyhow::Result<()> {
-        if let Some(popup_state) = &self.rss_feed_popup_state {
-            // Check if any changes were made
-            if popup_state.changes_made {
-                // Switch to refreshing mode with proper loading message
-                self.app_mode = AppMode::Refreshing(RefreshingPopup::new(
-                    "Refreshing Pocket data ⏳".to_string(),
-                    LoadingType::Refresh,
-                ));
 
-                // Mark RSS items as processed
-                self.rss_feed_state.mark_items_processed();
-            }
+    /// Selected lines joined back into one passage
+    fn selected_text(&self) -> String {
+        let (first, last) = self.selection();
+        self.lines[first..=last]
+            .iter()
+            .map(|line| line.trim())
+            .filter(|line| !line.is_empty())
+            .join(" ")
+    }
+}
 
-            // Start a new RSS feed check in the background
-            self.start_rss_feed_loading()?;
+/// Selection is an index into the pending jobs, running and failed ones are only shown
+struct DownloadsPopupState {
+    selected: usize,
+}
+
+#[derive(Clone)]
+enum Confirmation {
+    DeletePocketItem,
+}
+
+#[derive(Clone)]
+struct SearchMode {
+    search: String,
+    /// Query the list is filtered by, lags behind `search` while typing
+    applied: String,
+    /// Keystroke that isn't applied yet, filtering waits for a pause in typing
+    typed_at: Option<Instant>,
+    normal_mode_positions: (usize, usize),
+    /// Values for the `tag:` or `domain:` operator being typed
+    completions: Completions,
+    /// Jumps to the first match instead of filtering, toggled with Ctrl+n
+    navigate: bool,
+}
+
+/// Typed after `'`, the selection jumps to a matching title while the list stays as it is
+#[derive(Clone)]
+struct TypeAhead {
+    prefix: String,
+    /// Selection before the jump, `Esc` goes back to it
+    started_at: usize,
+    /// Some title matches the prefix
+    found: bool,
+}
+
+/// First title from `from` on, wrapping around, that starts with `prefix` ignoring case. When
+/// none does, the first one `prefix` matches fuzzily
+fn type_ahead_match<'a>(
+    prefix: &str,
+    len: usize,
+    from: usize,
+    title: impl Fn(usize) -> &'a str,
+) -> Option<usize> {
+    let prefix_lower = prefix.to_lowercase();
+    let order = (0..len).map(|step| (from + step) % len);
+    order
+        .clone()
+        .find(|&index| title(index).to_lowercase().starts_with(&prefix_lower))
+        .or_else(|| {
+            order
+                .clone()
+                .find(|&index| fuzzy::score(prefix, title(index)).is_some())
+        })
+}
+
+/// Every non-empty line of `text` as a web link, `None` when some line isn't one
+fn pasted_urls(text: &str) -> Option<Vec<String>> {
+    let urls: Vec<String> = text
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty())
+        .map(|line| {
+            reqwest::Url::parse(line)
+                .ok()
+                .filter(|url| matches!(url.scheme(), "http" | "https"))
+                .map(|_| line.to_string())
+        })
+        .collect::<Option<_>>()?;
+    (!urls.is_empty()).then_some(urls)
+}
+
+const TAG_OPERATOR: &str = "tag:";
+const DOMAIN_OPERATOR: &str = "domain:";
+
+/// Search input split into `tag:` and `domain:` operators and the text looked up in titles, urls
+/// and downloaded PDFs
+struct SearchQuery {
+    text: String,
+    tags: Vec<String>,
+    domains: Vec<String>,
+    /// The text matches titles and urls with `fuzzy` instead of as a substring
+    fuzzy: bool,
+}
+
+impl SearchQuery {
+    fn parse(filter_lower: &str) -> Self {
+        let mut query = SearchQuery {
+            text: String::new(),
+            tags: Vec::new(),
+            domains: Vec::new(),
+            fuzzy: false,
+        };
+        let mut words = Vec::new();
+        for word in filter_lower.split_whitespace() {
+            if let Some(tag) = word.strip_prefix(TAG_OPERATOR) {
+                query.tags.push(tag.to_string());
+            } else if let Some(domain) = word.strip_prefix(DOMAIN_OPERATOR) {
+                query.domains.push(domain.to_string());
+            } else {
+                words.push(word);
+            }
         }
+        query.text = if query.operators() == 0 {
+            filter_lower.to_string()
+        } else {
+            words.join(" ")
+        };
+        query
+    }
 
-        // Clear the popup state
-        self.rss_feed_popup_state = None;
-        Ok(())
+    fn operators(&self) -> usize {
+        self.tags.len() + self.domains.len()
     }
-    fn switch_to_tags_mode(&mut self, initial_tags: Option<String>) {
-        self.app_mode = AppMode::CommandEnter(CommandEnterMode::new(
-            "Enter tags (comma separated): ".to_string(),
-            initial_tags.unwrap_or_default(),
-            CommandType::Tags,
-        ));
+
+    fn is_ranked(&self) -> bool {
+        self.fuzzy && !self.text.trim().is_empty()
     }
-    fn process_add_to_pocket_with_tags(&mut self) -> anyhow::Result<()> {
-        if let Some(popup_state) = &mut self.rss_feed_popup_state {
-            if let Some(_item) = popup_state.prepare_add_to_pocket() {
-                self.switch_to_tags_mode(None);
+}
+
+/// Byte ranges of `text` matching the lowercase `term`, ignoring case
+fn match_ranges(text: &str, term: &str) -> Vec<Range<usize>> {
+    let mut ranges = Vec::new();
+    if term.is_empty() {
+        return ranges;
+    }
+    let mut start = 0;
+    while let Some(first) = text[start..].chars().next() {
+        let mut term_chars = term.chars().peekable();
+        let mut end = None;
+        for (i, ch) in text[start..].char_indices() {
+            if !ch
+                .to_lowercase()
+                .all(|lower| term_chars.next() == Some(lower))
+            {
+                break;
+            }
+            if term_chars.peek().is_none() {
+                end = Some(start + i + ch.len_utf8());
+                break;
             }
         }
-        Ok(())
-    }
-    fn switch_to_edit_tags_mode(&mut self) {
-        if let Some(idx) = self.virtual_state.selected() {
-            if let Some(item) = self.items.get(idx) {
-                // Get current tags and join them with commas
-                let current_tags = item.tags().join(", ");
-                self.switch_to_tags_mode(Some(current_tags));
+        match end {
+            Some(end) => {
+                ranges.push(start..end);
+                start = end;
             }
+            None => start += first.len_utf8(),
         }
     }
+    ranges
+}
 
-    fn complete_add_to_pocket(&mut self, tags: String) -> anyhow::Result<()> {
-        if let Some(popup_state) = &mut self.rss_feed_popup_state {
-            if let Err(e) = popup_state.add_current_to_pocket(&self.pocket_client, &tags) {
-                popup_state.set_status(format!("Error: {}", e));
-            }
+/// `text` split into spans with the matches of `term` highlighted, the characters a `fuzzy`
+/// search matched when it is on
+fn highlighted<'a>(text: &'a str, term: Option<&str>, fuzzy: bool, style: Style) -> Vec<Span<'a>> {
+    let highlight = style.bg(OCEANIC_NEXT.base_0a).fg(OCEANIC_NEXT.base_00);
+    let mut spans = Vec::new();
+    let mut last = 0;
+    let ranges = match term {
+        Some(term) if fuzzy => fuzzy::positions(term, text),
+        Some(term) => match_ranges(text, term),
+        None => Vec::new(),
+    };
+    for range in ranges {
+        if range.start > last {
+            spans.push(Span::styled(&text[last..range.start], style));
         }
-        Ok(())
+        spans.push(Span::styled(&text[range.clone()], highlight));
+        last = range.end;
     }
+    if last < text.len() || spans.is_empty() {
+        spans.push(Span::styled(&text[last..], style));
+    }
+    spans
+}
 
-    fn update_tags(&mut self, tags: String) -> anyhow::Result<()> {
-        // Handle RSS item tags
-        if let Some(popup_state) = &mut self.rss_feed_popup_state {
-            popup_state.add_current_to_pocket(&self.pocket_client, &tags)?;
-            return Ok(());
+impl SearchMode {
+    pub fn new(normal_mode_positions: (usize, usize)) -> Self {
+        SearchMode {
+            search: String::new(),
+            applied: String::new(),
+            typed_at: None,
+            normal_mode_positions,
+            completions: Completions::default(),
+            navigate: false,
         }
+    }
+}
 
-        // Handle pocket item tags
-        if let Some(idx) = self.virtual_state.selected() {
-            if let Some(item) = self.items.get_mut(idx) {
-                let item_id = item.id().parse::<usize>()?;
+#[derive(Clone)]
+enum CommandType {
+    RenameItem,
+    EditUrl,
+    /// `:` commands, see `App::run_command`
+    Command,
+}
 
-                // Parse the new tags
-                let new_tag_set: Vec<String> = tags
-                    .split(',')
-                    .map(|t| t.trim().to_string())
-                    .filter(|t| !t.is_empty())
-                    .collect();
+#[derive(Clone)]
+pub struct CommandEnterMode {
+    prompt: String,
+    current_enter: String,
+    cursor_pos: usize,
+    command_type: CommandType,
+    /// Tags for the word before the cursor
+    completions: Completions,
+}
 
-                // Update tags in Pocket
-                self.pocket_client.update_tags(item_id, &new_tag_set)?;
+impl CommandEnterMode {
+    fn new_empty(prompt: String, command_type: CommandType) -> Self {
+        Self {
+            prompt,
+            current_enter: String::new(),
+            cursor_pos: 0,
+            command_type,
+            completions: Completions::default(),
+        }
+    }
+    fn new(prompt: String, current_enter: String, command_type: CommandType) -> Self {
+        let cursor_pos = current_enter.len();
+        Self {
+            prompt,
+            current_enter,
+            cursor_pos,
+            command_type,
+            completions: Completions::default(),
+        }
+    }
 
-                // Update local item
-                // First, remove all existing tags
-                let existing_tags: Vec<String> = item.tags().map(|t| t.to_string()).collect();
-                for tag in existing_tags {
-                    item.remove_tag(&tag);
-                }
+    /// Tags are completed once two characters of a word are typed
+    fn update_completions(&mut self, tags: &[String]) {
+        let start = completion::word_start(&self.current_enter, self.cursor_pos, &[' ', ',']);
+        self.completions = if self.cursor_pos - start >= 2 {
+            Completions::new(&self.current_enter, start, self.cursor_pos, tags)
+        } else {
+            Completions::default()
+        };
+    }
 
-                // Then add the new tags
-                for tag in new_tag_set {
-                    item.add_tag(&tag);
-                }
-            }
+    fn cycle_completions(&mut self, step: isize) {
+        if let Some(cursor) = self
+            .completions
+            .cycle(&mut self.current_enter, self.cursor_pos, step)
+        {
+            self.cursor_pos = cursor;
         }
-        Ok(())
     }
+}
 
-    fn download_current_pdf(&mut self) -> anyhow::Result<()> {
-        if let Some(idx) = self.virtual_state.selected() {
-            if let Some(item) = self.items.get(idx) {
-                if item.item_type() == "pdf" {
-                    // Create pdfs directory if it doesn't exist
-                    fs::create_dir_all("pdfs")?;
-
-                    // Extract filename from URL
-                    let url = item.url();
-                    let filename = url
-                        .split('/')
-                        .last()
-                        .unwrap_or("download.pdf")
-                        .replace("%20", "_");
-
-                    // Construct full path
-                    let mut path = std::path::PathBuf::from("pdfs");
-                    path.push(&filename);
-
-                    // Download the file in a separate thread
-                    let download_url = url.to_string();
-                    let path_clone = path.clone();
-                    let client = self.download_client.clone();
-
-                    // thread::spawn(move || -> anyhow::Result<()> {
-                    let response = client.get(&download_url).send()?;
-                    let content = response.bytes()?;
-                    std::fs::write(path_clone, content)?;
-                    //
-                    self.pocket_client
-                        .mark_as_downloaded(item.id().parse::<usize>()?)?;
+enum AppMode {
+    Initialize,
+    Normal,
+    Search(SearchMode),
+    Confirmation(Confirmation),
+    MulticharNormalModeEnter(String),
+    TypeAhead(TypeAhead),
+    CommandEnter(CommandEnterMode),
+    TagEdit(TagEditor),
+    DatePicker(DatePicker),
+    Refreshing(RefreshingPopup),
+    Error(String),
+    Ambient(ambient::AmbientScreen),
+    /// Full-screen RSS triage, its state lives in `App::rss_triage_state`
+    RssTriage,
+    Quit,
+}
 
-                    let pdf_info = utils::extract_pdf_title(path.as_path())?;
-                    if let Some(title) = pdf_info.and_then(|info| info.title) {
-                        self.rename_current_item(title)?;
-                    }
-                }
-            }
+struct FilteredItems<T> {
+    pub items: Vec<T>,
+    is_filter_on: bool,
+    filtered: Vec<usize>,
+}
+
+impl<T> FilteredItems<T> {
+    pub fn non_archived(data: Vec<PocketItem>) -> FilteredItems<PocketItem> {
+        let filtered = data
+            .into_iter()
+            .filter(|x| x.status != "1")
+            .collect::<Vec<PocketItem>>();
+        let data_vec_size = filtered.len();
+        FilteredItems {
+            items: filtered,
+            is_filter_on: false,
+            filtered: Vec::with_capacity(data_vec_size),
         }
-        Ok(())
     }
 
-    fn download_and_convert_article(&mut self) -> anyhow::Result<()> {
-        if let Some(idx) = self.virtual_state.selected() {
-            if let Some(item) = self.items.get(idx) {
-                if item.item_type() == "article" {
-                    // Create articles directory if it doesn't exist
-                    fs::create_dir_all("articles")?;
-
-                    // Create sanitized filename from title
-                    // let title = item.title();
-                    // let filename = sanitize_filename::sanitize(title); //sanitazie_filename might be redundant dependency
-                    let filename = item.item_id.clone();
-                    let filename = if filename.is_empty() {
-                        "untitled".to_string()
-                    } else {
-                        filename
-                    };
-                    let path = Path::new("articles").join(format!("{}.md", filename));
-
-                    // Download the article content
-                    let response = self.download_client
-                                        .get(item.url())
-                                        .header("User-Agent", "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/121.0.0.0 Safari/537.36")
-                                        .header("Accept", "text/html,application/xhtml+xml,application/xml;q=0.9,image/webp,*/*;q=0.8")
-                                        .header("Accept-Language", "en-US,en;q=0.5")
-                                        .header("Connection", "keep-alive")
-                                        .header("Upgrade-Insecure-Requests", "1")
-                                        .header("Sec-Fetch-Dest", "document")
-                                        .header("Sec-Fetch-Mode", "navigate")
-                                        .header("Sec-Fetch-Site", "none")
-                                        .header("Sec-Fetch-User", "?1")
-                                        .send()?;
-                    let status = response.status();
-                    let html_content = response
-                        .text()
-                        .unwrap_or_else(|_| "No response body".to_string());
-                    if !status.is_success() {
-                        return Err(anyhow::anyhow!(
-                            "Failed to download article: HTTP {} - {}",
-                            status,
-                            html_content
-                        ));
-                    }
-                    let md = html2md::rewrite_html(&html_content, true);
+    pub fn new(data: Vec<T>) -> Self {
+        let data_vec_size = data.len();
+        FilteredItems {
+            items: data,
+            is_filter_on: false,
+            filtered: Vec::with_capacity(data_vec_size),
+        }
+    }
 
-                    // Configure and parse with dom_smoothie
-                    let cfg = Config {
-                        max_elements_to_parse: 9000,
-                        text_mode: dom_smoothie::TextMode::Formatted,
-                        ..Default::default()
-                    };
+    pub fn len(&self) -> usize {
+        if !self.is_filter_on {
+            self.items.len()
+        } else {
+            self.filtered.len()
+        }
+    }
 
-                    let mut readability =
-                        Readability::new(html_content.as_str(), Some(item.url()), Some(cfg))?;
-                    // Readability::new(md.as_str(), Some(item.url()), Some(cfg))?;
-                    let article: Article = readability.parse()?;
-
-                    // Create markdown content with metadata and article details
-                    let mut content = String::new();
-
-                    // Add YAML frontmatter
-                    // content.push_str("---\n");
-                    // content.push_str(&format!("title: {}\n", article.title));
-                    // content.push_str(&format!("url: {}\n", item.url()));
-                    // content.push_str(&format!("date_added: {}\n", item.date()));
-
-                    // // Add optional metadata if available
-                    // if let Some(byline) = article.byline {
-                    //     content.push_str(&format!("author: {}\n", byline));
-                    // }
-                    // if let Some(site_name) = article.site_name {
-                    //     content.push_str(&format!("site_name: {}\n", site_name));
-                    // }
-                    // if let Some(published_time) = article.published_time {
-                    //     content.push_str(&format!("published_time: {}\n", published_time));
-                    // }
-                    // if let Some(modified_time) = article.modified_time {
-                    //     content.push_str(&format!("modified_time: {}\n", modified_time));
-                    // }
-                    // if let Some(excerpt) = article.excerpt {
-                    //     content.push_str(&format!("excerpt: {}\n", excerpt));
-                    // }
-                    // content.push_str("---\n\n");
-
-                    // Add article content
-                    let result = markdown::normalize_markdown(&md, &article.text_content);
-                    content.push_str(&article.text_content);
-                    content.push_str("--------\n\n");
-                    content.push_str(&md);
-                    content.push_str("--------\n\n");
-                    content.push_str(&result);
-
-                    // Save to file
-                    fs::write(&path, content)?;
-
-                    // Mark as downloaded in Pocket
-                    self.pocket_client
-                        .mark_as_downloaded(item.id().parse::<usize>()?)?;
-                }
-            }
+    pub fn iter(&self) -> Box<dyn Iterator<Item = &T> + '_> {
+        if !self.is_filter_on {
+            Box::new(self.items.iter())
+        } else {
+            Box::new(self.filtered.iter().map(|i| &self.items[*i]))
         }
-        Ok(())
     }
 
-    // /// Checks if a line is a markdown header
-    // fn is_header(line: &str) -> bool {
-    //     line.trim_start().starts_with('#')
-    // }
+    pub fn clear_filter(&mut self) {
+        self.is_filter_on = false;
+        self.filtered.clear();
+    }
 
-    // /// Checks if a line should stay attached to the previous line
-    // fn should_stay_attached(line: &str) -> bool {
-    //     // Headers should be followed by their content
-    //     Self::is_header(line) ||
-    //     // List items should stay together
-    //     line.trim_start().starts_with('*') ||
-    //     line.trim_start().starts_with('-') ||
-    //     line.trim_start().starts_with(|c: char| c.is_ascii_digit() && line.contains(". ")) ||
-    //     // Code blocks should stay together
-    //     line.trim_start().starts_with('`') ||
-    //     // Continuation of a sentence (no capital letter at start)
-    //     (!line.trim_start().is_empty() &&
-    //      !Self::is_header(line) &&
-    //      line.trim_start().chars().next()
-    //          .map(|c| !c.is_uppercase())
-    //          .unwrap_or(false))
-    // }
-
-    // /// Normalizes markdown content by:
-    // /// 1. Removing preamble/postamble content not present in plain text
-    // /// 2. Restoring proper paragraph separation while preserving markdown formatting
-    // pub fn normalize_markdown(markdown: &str, plain: &str) -> String {
-    //     // First, find the start of actual content
-    //     let first_plain_para = plain.split("\n\n").next().unwrap_or("").trim();
-
-    //     let markdown_lines: Vec<&str> = markdown.lines().collect();
-    //     let mut start_idx = 0;
-
-    //     // Find content start
-    //     for (i, window) in markdown_lines.windows(3).enumerate() {
-    //         let combined = window.join(" ");
-    //         if combined.contains(first_plain_para) {
-    //             start_idx = i;
-    //             break;
-    //         }
-    //     }
-
-    //     // Find content end
-    //     let mut end_idx = markdown_lines.len();
-    //     for (i, line) in markdown_lines.iter().enumerate().rev() {
-    //         if line.contains("## Related posts")
-    //             || line.contains("Blog Comments")
-    //             || line.contains("Contents")
-    //         {
-    //             end_idx = i;
-    //             break;
-    //         }
-    //     }
-
-    //     // Process content while preserving markdown formatting
-    //     let mut result = Vec::new();
-    //     let mut current_group = Vec::new();
+    pub fn apply_filter<P>(&mut self, mut predicate: P)
+    where
+        P: FnMut(&T) -> bool,
+    {
+        self.is_filter_on = true;
+        self.filtered.clear();
+        self.items
+            .iter()
+            .enumerate()
+            .filter(|(_, x)| predicate(x))
+            .for_each(|(i, _)| self.filtered.push(i));
+    }
 
-    //     for (i, line) in markdown_lines[start_idx..end_idx].iter().enumerate() {
-    //         let trimmed = line.trim();
-    //         if trimmed.is_empty() {
-    //             if !current_group.is_empty() {
-    //                 result.push(current_group.join("\n"));
-    //                 current_group.clear();
-    //             }
-    //             continue;
-    //         }
+    /// Reorders the shown items, equal keys keep their order
+    pub fn sort_filtered_by_key<K, F>(&mut self, mut key: F)
+    where
+        K: Ord,
+        F: FnMut(&T) -> K,
+    {
+        let items = &self.items;
+        self.filtered.sort_by_cached_key(|i| key(&items[*i]));
+    }
 
-    //         // Check if this line should be kept with the previous content
-    //         if i > 0 && Self::should_stay_attached(trimmed) {
-    //             current_group.push(trimmed);
-    //         } else {
-    //             if !current_group.is_empty() {
-    //                 result.push(current_group.join("\n"));
-    //                 current_group.clear();
-    //             }
-    //             current_group.push(trimmed);
-    //         }
-    //     }
+    /// Filters only what the current filter lets through, for predicates that are stricter
+    pub fn narrow_filter<P>(&mut self, mut predicate: P)
+    where
+        P: FnMut(&T) -> bool,
+    {
+        if !self.is_filter_on {
+            return self.apply_filter(predicate);
+        }
+        let items = &self.items;
+        self.filtered.retain(|i| predicate(&items[*i]));
+    }
 
-    //     // Add final group if any
-    //     if !current_group.is_empty() {
-    //         result.push(current_group.join("\n"));
-    //     }
+    /// Orders the shown items group by group: groups in the order of their first item, items
+    /// in their current order. Collapsed groups show only their first item. Returns the size of
+    /// every group
+    pub fn group<K, C>(
+        &mut self,
+        key: K,
+        is_collapsed: C,
+    ) -> std::collections::HashMap<String, usize>
+    where
+        K: Fn(&T) -> String,
+        C: Fn(&str) -> bool,
+    {
+        if !self.is_filter_on {
+            self.is_filter_on = true;
+            self.filtered = (0..self.items.len()).collect();
+        }
+        let keys: Vec<String> = self.filtered.iter().map(|i| key(&self.items[*i])).collect();
+        let mut ranks = std::collections::HashMap::new();
+        let mut sizes = std::collections::HashMap::new();
+        for key in &keys {
+            let next_rank = ranks.len();
+            ranks.entry(key.as_str()).or_insert(next_rank);
+            *sizes.entry(key.clone()).or_insert(0) += 1;
+        }
+        let mut order: Vec<usize> = (0..keys.len()).collect();
+        order.sort_by_key(|pos| ranks[keys[*pos].as_str()]);
+        let mut shown_collapsed = std::collections::HashSet::new();
+        self.filtered = order
+            .into_iter()
+            .filter(|pos| {
+                let key = keys[*pos].as_str();
+                !is_collapsed(key) || shown_collapsed.insert(key)
+            })
+            .map(|pos| self.filtered[pos])
+            .collect();
+        sizes
+    }
 
-    //     // Join paragraphs with double newlines
-    //     let content = result
-    //         .into_iter()
-    //         .filter(|p| !p.is_empty())
-    //         .collect::<Vec<_>>()
-    //         .join("\n\n");
+    fn get_mut(&mut self, idx: usize) -> Option<&mut T> {
+        if !self.is_filter_on {
+            self.items.get_mut(idx)
+        } else {
+            self.filtered
+                .get(idx)
+                .map(|index| self.items.get_mut(*index))
+                .flatten()
+        }
+    }
 
-    //     // Clean up the final string while preserving markdown structure
-    //     content
-    //         .split("\n\n")
-    //         .map(|para| para.trim())
-    //         .filter(|para| !para.is_empty())
-    //         .collect::<Vec<_>>()
-    //         .join("\n\n")
-    // }
+    fn get(&self, idx: usize) -> Option<&T> {
+        if !self.is_filter_on {
+            self.items.get(idx)
+        } else {
+            self.filtered
+                .get(idx)
+                .map(|index| self.items.get(*index))
+                .flatten()
+        }
+    }
 
-    pub fn show_rss_feed_popup(&mut self) -> anyhow::Result<()> {
-        if let Ok(is_loading) = self.rss_feed_state.is_loading.lock() {
-            if (*is_loading) {
-                self.app_mode = AppMode::Error("RSS feed is being updated.".to_string());
-                return Ok(());
-            }
+    fn remove(&mut self, idx: usize) {
+        if !self.is_filter_on {
+            self.items.remove(idx);
+        } else {
+            self.filtered
+                .get(idx)
+                .map(|index| self.items.remove(*index));
         }
-        if let Ok(items_guard) = self.rss_feed_state.items.lock() {
-            if items_guard.is_empty() {
-                self.app_mode = AppMode::Error("No RSS updates available (yet)".to_string());
-                return Ok(());
+    }
+
+    fn index(&self, range: Range<usize>) -> Vec<&T> {
+        if !self.is_filter_on {
+            self.items[range].iter().collect()
+        } else {
+            if self.filtered.is_empty() {
+                Vec::new()
+            } else {
+                let start = range.start;
+                let end = std::cmp::min(range.end, self.filtered.len());
+                self.filtered[start..end]
+                    .iter()
+                    .map(|i| &self.items[*i])
+                    .collect()
             }
         }
-        let visible_items = 33;
-        let items = if let Ok(items_guard) = self.rss_feed_state.items.lock() {
-            items_guard.to_vec()
-        } else {
-            Vec::new()
-        };
+    }
+}
+
+#[derive(Clone, PartialEq)]
+enum ItemTypeFilter {
+    All,
+    Article,
+    Video,
+    PDF,
+}
 
-        // Create popup state with current items
-        self.rss_feed_popup_state = Some(RssFeedPopupState::new(items, visible_items)?);
+/// What the rows of the main table are grouped under, switched with `o`
+#[derive(Clone, Copy, PartialEq)]
+enum GroupBy {
+    /// Day headers in the date column, the default
+    Date,
+    Domain,
+    /// First tag in alphabetical order
+    Tag,
+    Month,
+}
 
-        // If we need to refresh the items, do it in the background
-        if !self.rss_feed_state.items_processed {
-            self.start_rss_feed_loading()?;
+impl GroupBy {
+    fn next(self) -> Self {
+        match self {
+            GroupBy::Date => GroupBy::Domain,
+            GroupBy::Domain => GroupBy::Tag,
+            GroupBy::Tag => GroupBy::Month,
+            GroupBy::Month => GroupBy::Date,
         }
+    }
 
-        Ok(())
+    fn name(self) -> &'static str {
+        match self {
+            GroupBy::Date => "date",
+            GroupBy::Domain => "domain",
+            GroupBy::Tag => "tag",
+            GroupBy::Month => "month",
+        }
     }
 
-    pub fn handle_rss_feed_selection(&mut self) -> anyhow::Result<()> {
-        if let Some(popup_state) = &self.rss_feed_popup_state {
-            if let Some(selected_item) = popup_state.items.get(popup_state.selected_index) {
-                if !selected_item.link.is_empty() {
-                    webbrowser::open(&selected_item.link)
-                        .context("Failed to open link in browser")?;
-                }
+    fn key(self, item: &PocketItem) -> String {
+        match self {
+            GroupBy::Date => item.date(),
+            GroupBy::Domain => {
+                App::extract_domain(item.url()).unwrap_or_else(|| "unknown".to_string())
             }
+            GroupBy::Tag => item
+                .tags()
+                .min()
+                .cloned()
+                .unwrap_or_else(|| "untagged".to_string()),
+            GroupBy::Month => item.date()[..7].to_string(),
         }
-        // self.rss_feed_popup_state = None;
-        Ok(())
-    }
-    fn show_help_popup(&mut self) -> anyhow::Result<()> {
-        let content = fs::read_to_string("help.txt")?;
-        self.help_popup_state = Some(HelpPopupState { content });
-        Ok(())
     }
+}
 
-    fn refresh_data(&mut self) -> anyhow::Result<()> {
-        let delta_file = Path::new("snapshot_updates.db");
-        let mut stats = TotalStats::new();
-        let items = reload_data(delta_file, &self.pocket_client, &mut stats)?;
-        self.cached_tags = items
-            .iter()
-            .flat_map(|item| item.tags().map(|tag| tag.to_string()))
-            .collect::<std::collections::HashSet<_>>()
-            .into_iter()
-            .collect();
-        self.stats = stats;
-        self.items = FilteredItems::<PocketItem>::non_archived(items);
-        self.apply_filter();
-        Ok(())
-    }
+/// Where an item comes from: the authors of videos and Medium posts, the site of everything else
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+enum SourceFilter {
+    Domain(String),
+    Authors(Vec<String>),
+    /// Anything written by one of them, chosen in the authors popup
+    Writers(Vec<String>),
+}
 
-    fn show_tag_popup(&mut self) {
-        let tag_counts: Vec<(String, usize)> = self
-            .items
-            .iter()
-            .filter(|item| {
-                !item.tags().any(|tag| tag == "read") // Exclude read items
-                                                      // item.favorite != "1" // Exclude favorited items
-            })
-            .flat_map(|item| item.tags().map(|tag| tag.to_string()))
-            .fold(std::collections::HashMap::new(), |mut acc, tag| {
-                *acc.entry(tag).or_insert(0) += 1;
-                acc
-            })
-            .into_iter()
-            .collect();
+impl SourceFilter {
+    /// None when there is nothing to tell the source by, like a video without authors
+    fn of(item: &PocketItem) -> Option<Self> {
+        if item.item_type() == "video" || item.url().contains("medium") {
+            item.authors
+                .clone()
+                .filter(|authors| !authors.is_empty())
+                .map(SourceFilter::Authors)
+        } else {
+            App::extract_domain(item.url()).map(SourceFilter::Domain)
+        }
+    }
 
-        let mut sorted_tag_counts = tag_counts;
-        sorted_tag_counts.sort_by(|a, b| a.0.cmp(&b.0).then(b.1.cmp(&a.1))); // sort by alfabet then by counts
+    fn matches(&self, item: &PocketItem) -> bool {
+        match self {
+            SourceFilter::Writers(writers) => item
+                .authors
+                .iter()
+                .flatten()
+                .any(|author| writers.contains(author)),
+            _ => Self::of(item).as_ref() == Some(self),
+        }
+    }
 
-        let visible_items = 26; // Adjust this value based on your UI
-        self.tag_popup_state = Some(TagPopupState::new(sorted_tag_counts, visible_items));
-        self.tag_selection_mode = TagSelectionMode::Normal;
+    fn label(&self) -> String {
+        match self {
+            SourceFilter::Domain(domain) => domain.clone(),
+            SourceFilter::Authors(authors) => authors.join(", "),
+            SourceFilter::Writers(writers) => format!("by {}", writers.join(", ")),
+        }
     }
+}
 
-    fn show_domain_stats(&mut self) {
-        // Create a hashmap to store domain/author counts
-        let mut counts = std::collections::HashMap::new();
+/// Type, source and date filters, tags in the tag popup are counted within them
+struct ScopeFilter {
+    item_type: ItemTypeFilter,
+    source: Option<SourceFilter>,
+    date_range: Option<(String, String)>,
+}
 
-        // Count domains/authors for each item
-        for item in self.items.iter() {
-            let key = if item.item_type() == "video" || item.url().contains("medium") {
-                // For videos, use author IDs if available
-                match &item.authors {
-                    Some(authors) if !authors.is_empty() => authors.join(", "),
-                    _ => "IGNORE".to_string(),
-                }
-            } else {
-                // For non-videos, use domain
-                Self::extract_domain(item.url()).unwrap_or_else(|| "IGNORE".to_string())
-            };
-            if key != "IGNORE" {
-                *counts.entry(key).or_insert(0) += 1;
-            }
-        }
+impl ScopeFilter {
+    fn matches(&self, item: &PocketItem) -> bool {
+        let type_matches = match self.item_type {
+            ItemTypeFilter::All => true,
+            ItemTypeFilter::Article => item.item_type() == "article",
+            ItemTypeFilter::Video => item.item_type() == "video",
+            ItemTypeFilter::PDF => item.item_type() == "pdf",
+        };
 
-        // Convert to vector and sort by count (descending)
-        let mut stats: Vec<(String, usize)> = counts.into_iter().collect();
-        stats.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+        let source_matches = self
+            .source
+            .as_ref()
+            .is_none_or(|source| source.matches(item));
 
-        // Take top 20
-        stats.truncate(40);
+        let date_matches = match &self.date_range {
+            Some((from, to)) => (from.as_str()..=to.as_str()).contains(&item.date().as_str()),
+            None => true,
+        };
 
-        let visible_items = 23; //todo: this needs to be figoured out based on popup size.
-        self.domain_stats_popup_state = Some(DomainStatsPopupState::new(stats, visible_items));
+        type_matches && source_matches && date_matches
     }
+}
 
-    pub fn apply_filter(&mut self) {
-        self.items.apply_filter(|item| {
-            let title_matches = match &self.active_search_filter {
-                Some(filter) => {
-                    let filter_lower = filter.to_lowercase();
-                    item.title().to_lowercase().contains(&filter_lower)
-                        || item.url().contains(&filter_lower)
-                }
-                None => true,
-            };
-
-            let tag_matches = match &self.selected_tag_filter {
-                Some(tag) => item.tags().any(|t| t == tag),
-                None => true,
-            };
-
-            let type_matches = match self.item_type_filter {
-                ItemTypeFilter::All => true,
-                ItemTypeFilter::Article => item.item_type() == "article",
-                ItemTypeFilter::Video => item.item_type() == "video",
-                ItemTypeFilter::PDF => item.item_type() == "pdf",
-            };
-
-            let domain_matches = match &self.domain_filter {
-                Some(domain) => Self::extract_domain(item.url())
-                    .map(|item_domain| item_domain == *domain)
-                    .unwrap_or(false),
-                None => true,
-            };
+#[derive(PartialEq)]
+enum TagSelectionMode {
+    Normal,
+    Filtering,
+}
+const SCROLL_STEP: usize = 1; // Number of items to scroll at once
 
-            title_matches && tag_matches && type_matches && domain_matches
-        });
-        self.virtual_state.select(Some(0));
-        *self.virtual_state.offset_mut() = 0;
-    }
+struct App {
+    virtual_state: TableState,
+    state: TableState,
+    items: FilteredItems<PocketItem>,
+    longest_item_lens: (u16, u16, u16), // order is (name, address, email)
+    scroll_state: ScrollbarState,
+    colors: TableColors,
+    color_index: usize,
+    app_mode: AppMode,
+    stats: TotalStats,
+    pocket_client: GetPocketSync,
+    tag_popup_state: Option<TagPopupState>,
+    doc_type_popup_state: Option<DocTypePopupState>,
+    selected_tag_filter: Option<String>,
+    active_search_filter: Option<String>,
+    /// Search that `n`/`N` jump through without filtering the list
+    search_navigation: Option<String>,
+    item_type_filter: ItemTypeFilter,
+    source_filter: Option<SourceFilter>,
+    /// Only items added between these days, both included
+    date_range: Option<(NaiveDate, NaiveDate)>,
+    group_by: GroupBy,
+    /// Groups of the current grouping showing only their first item
+    collapsed_groups: std::collections::HashSet<String>,
+    /// Items in every group, counted before collapsing
+    group_sizes: std::collections::HashMap<String, usize>,
+    view_summary: ViewSummary,
+    toasts: toast::Toasts,
+    tag_selection_mode: TagSelectionMode,
+    scroll_accumulator: f32,
+    last_click_time: Option<std::time::Instant>,
+    last_click_position: Option<(u16, u16)>,
+    /// Where the footer chips were drawn last time, for clicks
+    footer_chips: Vec<(Rect, FooterChip)>,
+    table_layout: TableLayout,
+    /// Track of the list scrollbar as drawn last time
+    scrollbar_area: Rect,
+    dragging_scrollbar: bool,
+    domain_stats_popup_state: Option<DomainStatsPopupState>,
+    authors_popup_state: Option<AuthorsPopupState>,
+    help_popup_state: Option<HelpPopupState>,
+    rss_triage_state: Option<RssTriageState>,
+    download_client: Client,
+    cached_tags: Vec<String>,
+    rss_feed_state: RssFeedState,
+    safe_mode: bool,
+    tutorial: Option<Tutorial>,
+    reading_queue: ReadingQueue,
+    /// Stats above the table, cycled with `H`
+    stats_panel: StatsPanel,
+    /// Titles given with `r`, shown instead of Pocket's
+    title_overrides: TitleOverrides,
+    /// Passages saved in the reader
+    highlights: Highlights,
+    config: config::Config,
+    watch_queries: Vec<WatchQuery>,
+    watch_matches: Arc<Mutex<Vec<WatchMatch>>>,
+    watch_popup_state: Option<WatchPopupState>,
+    favorites_popup_state: Option<FavoritesPopupState>,
+    archive_popup_state: Option<ArchivePopupState>,
+    last_input: Instant,
+    failed_actions_popup_state: Option<FailedActionsPopupState>,
+    popup_layouts: PopupLayouts,
+    download_queue: DownloadQueue,
+    downloads_popup_state: Option<DownloadsPopupState>,
+    boundary_preview_state: Option<BoundaryPreviewState>,
+    summary_popup_state: Option<SummaryPopupState>,
+    reader_popup_state: Option<ReaderPopupState>,
+    qr_popup_state: Option<QrPopupState>,
+    share_popup_state: Option<SharePopupState>,
+    /// Links from the clipboard waiting for their tags
+    pasted_urls: Vec<String>,
+    /// Percent of downloaded articles read in the reader, by item id
+    reading_progress: HashMap<String, u8>,
+    /// Article being read aloud, `P` pauses it
+    playback: Option<tts::Playback>,
+    logs_popup_state: Option<LogsPopupState>,
+    sync_diff_popup_state: Option<SyncDiffPopupState>,
+    conflicts_popup_state: Option<ConflictsPopupState>,
+    /// Renames and tag edits since the last sync, checked against what the sync brings
+    local_edits: conflicts::LocalEdits,
+    context_menu_state: Option<ContextMenuState>,
+    /// Last successful sync with Pocket, shown in the footer
+    last_synced: Option<Instant>,
+    /// Sync started on startup, the list is shown from the local copy until it is done
+    background_sync: Option<mpsc::Receiver<anyhow::Result<()>>>,
+    background_sync_started: Option<Instant>,
+    /// Items fetched so far when there was no local copy yet and the sync gets the whole list
+    bootstrap_progress: Option<Arc<AtomicUsize>>,
+    pdf_index: PdfIndex,
+    /// Accepts `pkt-tui --remote` commands, not started in tutorial mode
+    control_socket: Option<remote::ControlSocket>,
+    /// Excerpt of the selected item below the table, toggled with `v`
+    show_excerpt: bool,
+    thumbnails: Thumbnails,
+    /// Background threads wake the main loop up to show their results right away
+    wakeups: Wakeups,
+}
 
-    fn show_doc_type_popup(&mut self) {
-        self.doc_type_popup_state = Some(DocTypePopupState::new());
+impl App {
+    fn new(data_vec: Vec<PocketItem>, pocket_client: GetPocketSync, stats: TotalStats) -> App {
+        let cached_tags = data_vec
+            .iter()
+            .flat_map(|item| item.tags().map(|tag| tag.to_string()))
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect();
+        App {
+            virtual_state: TableState::default().with_selected(0),
+            state: TableState::default().with_selected(0),
+            longest_item_lens: constraint_len_calculator(&data_vec),
+            // scroll_state: ScrollbarState::new((data_vec.len() - 1) * ITEM_HEIGHT),
+            scroll_state: ScrollbarState::new(1), //todo: fix this
+            colors: TableColors::new(&PALETTES[0]),
+            color_index: 0,
+            items: FilteredItems::<PocketItem>::non_archived(data_vec),
+            app_mode: AppMode::Initialize,
+            pocket_client,
+            stats,
+            tag_popup_state: None,
+            doc_type_popup_state: None,
+            selected_tag_filter: None,
+            active_search_filter: None,
+            search_navigation: None,
+            item_type_filter: ItemTypeFilter::All,
+            source_filter: None,
+            date_range: None,
+            group_by: GroupBy::Date,
+            collapsed_groups: std::collections::HashSet::new(),
+            group_sizes: std::collections::HashMap::new(),
+            view_summary: ViewSummary::default(),
+            toasts: toast::Toasts::default(),
+            tag_selection_mode: TagSelectionMode::Normal,
+            scroll_accumulator: 0.0,
+            last_click_time: None,
+            last_click_position: None,
+            footer_chips: Vec::new(),
+            table_layout: TableLayout::default(),
+            scrollbar_area: Rect::default(),
+            dragging_scrollbar: false,
+            domain_stats_popup_state: None,
+            authors_popup_state: None,
+            help_popup_state: None,
+            download_client: network::blocking_client(None)
+                .expect("Failed to build the download client"),
+            rss_triage_state: None,
+            cached_tags,
+            rss_feed_state: RssFeedState::new(),
+            safe_mode: false,
+            tutorial: None,
+            reading_queue: ReadingQueue::new(),
+            stats_panel: StatsPanel::default(),
+            title_overrides: TitleOverrides::new(),
+            highlights: Highlights::new(),
+            config: config::Config::default(),
+            watch_queries: Vec::new(),
+            watch_matches: Arc::new(Mutex::new(Vec::new())),
+            watch_popup_state: None,
+            favorites_popup_state: None,
+            archive_popup_state: None,
+            last_input: Instant::now(),
+            failed_actions_popup_state: None,
+            popup_layouts: PopupLayouts::new(std::collections::HashMap::new()),
+            download_queue: DownloadQueue::new(),
+            downloads_popup_state: None,
+            boundary_preview_state: None,
+            summary_popup_state: None,
+            reader_popup_state: None,
+            qr_popup_state: None,
+            share_popup_state: None,
+            pasted_urls: Vec::new(),
+            reading_progress: HashMap::new(),
+            playback: None,
+            logs_popup_state: None,
+            sync_diff_popup_state: None,
+            conflicts_popup_state: None,
+            local_edits: conflicts::LocalEdits::default(),
+            context_menu_state: None,
+            last_synced: None,
+            background_sync: None,
+            background_sync_started: None,
+            bootstrap_progress: None,
+            pdf_index: PdfIndex::new(),
+            control_socket: None,
+            show_excerpt: false,
+            thumbnails: Thumbnails::disabled(),
+            wakeups: Wakeups::new(),
+        }
     }
 
-    fn select_doc_type(&mut self, filter: ItemTypeFilter) {
-        self.doc_type_popup_state = None;
-        if self.item_type_filter != filter {
-            self.item_type_filter = filter;
-            self.apply_filter();
+    fn advance_tutorial(&mut self, action: TutorialAction) {
+        if let Some(tutorial) = &mut self.tutorial {
+            tutorial.advance(action);
         }
     }
 
-    fn set_item_type_filter(&mut self, filter: ItemTypeFilter) {
-        self.item_type_filter = filter;
-        self.apply_filter();
-    }
+    fn handle_neovim_edit(&mut self) -> anyhow::Result<Option<String>> {
+        // Create a temporary file
+        let temp_path = format!("/tmp/pocket_tui_{}.txt", std::process::id());
+        File::create(&temp_path)?;
 
-    fn select_tag(&mut self) {
-        if let Some(tag_popup_state) = &self.tag_popup_state {
-            if let Some((selected_tag, _)) = tag_popup_state
-                .filtered_tags
-                .get(tag_popup_state.selected_index)
-            {
-                self.selected_tag_filter = Some(selected_tag.clone());
-                self.tag_popup_state = None;
-                self.apply_filter();
-            }
+        // Save terminal state and switch to normal mode for neovim
+        termcaps::leave(&mut io::stdout())?;
+
+        // Launch neovim
+        let status = std::process::Command::new("nvim")
+            .arg(&temp_path)
+            .status()
+            .context("Failed to start neovim")?;
+
+        // Restore terminal state for Ratatui
+        termcaps::enter(&mut io::stdout())?;
+
+        let result = if status.success() {
+            let content = fs::read_to_string(&temp_path)?;
+            fs::remove_file(&temp_path)?;
+            Ok(Some(content))
+        } else {
+            Ok(None)
+        };
+
+        // Clean up temp file if it still exists
+        if Path::new(&temp_path).exists() {
+            fs::remove_file(&temp_path)?;
         }
-    }
 
-    fn clear_tag_filter(&mut self) {
-        self.selected_tag_filter = None;
-        self.apply_filter();
-    }
+        // Queue a redraw of the UI
+        crossterm::queue!(
+            io::stdout(),
+            crossterm::terminal::Clear(crossterm::terminal::ClearType::All)
+        )?;
+        io::stdout().flush()?;
 
-    fn set_search_filter(&mut self, filter: String) {
-        self.active_search_filter = Some(filter);
-        self.apply_filter();
+        result
     }
 
-    fn clear_search_filter(&mut self) {
-        self.active_search_filter = None;
-        self.apply_filter();
-    }
+    //// ------- tmux based popup. working but requires tmux
+    // fn handle_neovim_edit(&mut self) -> anyhow::Result<Option<String>> {
+    //     if !self.is_inside_tmux() {
+    //         return Err(anyhow::anyhow!("Must be running inside tmux session"));
+    //     }
 
-    fn clear_all_filters(&mut self) {
-        self.active_search_filter = None;
-        self.selected_tag_filter = None;
-        self.domain_filter = None;
-        self.items.clear_filter();
-    }
+    //     // Create a temporary file
+    //     let temp_path = format!("/tmp/pocket_tui_{}.txt", std::process::id());
+    //     File::create(&temp_path)?;
 
-    fn extract_domain(url: &str) -> Option<String> {
-        let url = url
-            .trim_start_matches("http://")
-            .trim_start_matches("https://")
-            .trim_start_matches("www.");
+    //     // Calculate dimensions for the popup (80% of terminal size)
+    //     let terminal_size = crossterm::terminal::size()?;
+    //     let width = (terminal_size.0 as f32 * 0.8) as u16;
+    //     let height = (terminal_size.1 as f32 * 0.8) as u16;
+    //     let x = (terminal_size.0 - width) / 2;
+    //     let y = (terminal_size.1 - height) / 2;
 
-        url.split('/').next().map(|s| s.to_string())
+    //     // Launch tmux popup with neovim without disturbing current terminal
+    //     let tmux_cmd = format!(
+    //         "tmux popup -E -d '{}' -w {} -h {} -x {} -y {} 'nvim {}'",
+    //         std::env::current_dir()?.display(),
+    //         width,
+    //         height,
+    //         x,
+    //         y,
+    //         temp_path
+    //     );
+
+    //     let output = std::process::Command::new("sh")
+    //         .arg("-c")
+    //         .arg(&tmux_cmd)
+    //         .output()
+    //         .context("Failed to start tmux popup with neovim")?;
+
+    //     let result = if output.status.success() {
+    //         // Read the content after editing
+    //         let content = fs::read_to_string(&temp_path)?;
+    //         fs::remove_file(&temp_path)?;
+    //         Ok(Some(content))
+    //     } else {
+    //         Ok(None)
+    //     };
+
+    //     // Clean up temp file if it still exists
+    //     if Path::new(&temp_path).exists() {
+    //         fs::remove_file(&temp_path)?;
+    //     }
+
+    //     result
+    // }
+
+    fn is_tmux_available() -> bool {
+        std::process::Command::new("tmux")
+            .arg("-V")
+            .output()
+            .is_ok()
     }
 
-    fn filter_by_video_authors(&mut self, target_authors: &[String]) {
-        self.items.apply_filter(|item| {
-            if item.item_type() == "video" {
-                // For videos, check if any authors match
-                if let Some(item_authors) = &item.authors {
-                    item_authors
-                        .iter()
-                        .any(|author| target_authors.iter().any(|target| author.contains(target)))
-                } else {
-                    false
-                }
-            } else {
-                false
-            }
-        });
-        self.virtual_state.select(Some(0));
-        *self.virtual_state.offset_mut() = 0;
+    fn is_inside_tmux(&self) -> bool {
+        std::env::var("TMUX").is_ok()
     }
-    fn filter_by_current_domain(&mut self) -> anyhow::Result<()> {
-        if let Some(idx) = self.virtual_state.selected() {
-            if let Some(item) = self.items.get(idx).cloned() {
-                if item.item_type() == "video" {
-                    // For videos, use authors as the filter criteria
-                    match &item.authors {
-                        Some(authors) if !authors.is_empty() => {
-                            // Use authors as filter
-                            self.domain_filter = Some(authors.join(", "));
-                            self.filter_by_video_authors(authors);
-                        }
-                        _ => {
-                            // No authors available
-                            self.domain_filter = Some("N/A".to_string());
-                            self.apply_filter();
-                        }
-                    }
-                } else {
-                    // Regular domain filtering for non-video content
-                    if let Some(domain) = Self::extract_domain(item.url()) {
-                        self.domain_filter = Some(domain);
-                        self.apply_filter();
-                    }
-                }
-            }
+
+    pub fn start_rss_feed_loading(&mut self) -> anyhow::Result<()> {
+        if self.safe_mode || self.pocket_client.is_dry_run() {
+            return Ok(());
+        }
+        let subscription_manager = RssManager::new();
+        let feeds = subscription_manager.load_subscriptions()?;
+        if feeds.is_empty() {
+            return Ok(());
         }
-        Ok(())
-    }
 
-    fn _apply_video_author_filter(&mut self, target_authors: &[String]) {
-        self.items.apply_filter(|item| {
-            if item.item_type() == "video" {
-                // For videos, check if any authors match
-                if let Some(item_authors) = &item.authors {
-                    item_authors
-                        .iter()
-                        .any(|author| target_authors.contains(author))
-                } else {
-                    false
-                }
+        if let Ok(mut is_loading) = self.rss_feed_state.is_loading.lock() {
+            if *is_loading {
+                return Ok(());
             } else {
-                // Non-video items don't match when filtering by video author
-                false
+                *is_loading = true;
             }
-        });
-    }
+        }
 
-    fn clear_domain_filter(&mut self) {
-        self.domain_filter = None;
-        self.apply_filter();
-    }
-    pub fn next(&mut self) {
-        let i = match self.virtual_state.selected() {
-            Some(i) => {
-                if i < self.items.len() - 1 {
-                    i + 1
-                } else {
-                    self.items.len() - 1
+        let client = network::blocking_client(Some(Duration::from_secs(10)))?;
+
+        let items_arc = self.rss_feed_state.items.clone();
+        let mut hidden_items = prss::hidden_items::HiddenItems::load()?;
+        match hidden_items.expire(
+            self.config.rss_hidden_expiry_days,
+            chrono::Utc::now().timestamp(),
+        ) {
+            Ok(0) => {}
+            Ok(expired) => info!("Forgot {} hidden RSS items", expired),
+            Err(e) => error!("Failed to compact hidden RSS items: {:#}", e),
+        }
+        // feed health is bookkeeping only, it never blocks loading the feeds
+        let feed_health = self.rss_feed_state.feed_health.clone();
+        if let Ok(mut health) = feed_health.lock() {
+            *health = prss::feed_health::FeedHealthLog::load();
+        }
+        let is_loading_arc = self.rss_feed_state.is_loading.clone();
+        let last_fetched_arc = self.rss_feed_state.last_fetched.clone();
+        let watch_queries = self.watch_queries.clone();
+        let rss_filters = self.config.rss_filters.clone();
+        let rss_max_items = self.config.rss_max_items;
+        let notify = self.config.notifications;
+        let watch_matches_arc = self.watch_matches.clone();
+        let feed_cache = prss::feed_cache::FeedCache::load();
+        let keep = move |url: &str, items: Vec<RssFeedItem>| match prss::filters::for_feed(
+            &rss_filters,
+            url,
+        ) {
+            Some(filter) => filter.apply(items, chrono::Utc::now()),
+            None => items,
+        };
+        // cached items show up right away, the fetch below replaces them
+        if let Ok(mut items_guard) = items_arc.lock() {
+            if items_guard.is_empty() {
+                *items_guard = feeds
+                    .iter()
+                    .filter_map(|feed| feed_cache.get(&feed.url).map(|cached| (&feed.url, cached)))
+                    .flat_map(|(url, feed)| keep(url, feed.items.clone()))
+                    .filter(|item| !hidden_items.is_hidden(&item.item_id))
+                    .collect();
+                prss::filters::newest_first(&mut items_guard, rss_max_items);
+            }
+        }
+        thread::spawn(move || {
+            let results = Arc::new(Mutex::new(Vec::new()));
+            let feed_cache = Mutex::new(feed_cache);
+
+            let fetched_any = std::sync::atomic::AtomicBool::new(false);
+            let retry_policy = prss::RetryPolicy::default();
+            let deadline = Instant::now() + prss::MAX_TOTAL_FETCH_TIME;
+            feeds.par_iter().for_each(|subscription| {
+                let url = subscription.url.as_str();
+                let cached = feed_cache
+                    .lock()
+                    .ok()
+                    .and_then(|cache| cache.get(url).cloned());
+                let cached_items = || cached.clone().map(|feed| feed.items).unwrap_or_default();
+                if Instant::now() >= deadline {
+                    error!("Skipping {}: out of time for this refresh", url);
+                    if let Ok(mut results_guard) = results.lock() {
+                        results_guard.extend(keep(url, cached_items()));
+                    }
+                    return;
+                }
+                let validators = cached.as_ref().map(|feed| &feed.validators);
+                let items = match retry_policy.run(deadline, || {
+                    RssManager::fetch_and_parse_feed(&client, subscription, validators)
+                }) {
+                    Ok(fetch) => {
+                        fetched_any.store(true, std::sync::atomic::Ordering::Relaxed);
+                        if let Ok(mut health) = feed_health.lock() {
+                            health.record_success(url);
+                        }
+                        match fetch {
+                            prss::FeedFetch::NotModified => cached_items(),
+                            prss::FeedFetch::Fetched { items, validators } => {
+                                if let Ok(mut cache) = feed_cache.lock() {
+                                    let feed = prss::feed_cache::CachedFeed {
+                                        validators,
+                                        items: items.clone(),
+                                    };
+                                    cache.store(url, feed);
+                                }
+                                items
+                            }
+                        }
+                    }
+                    Err(e) => {
+                        let streak = feed_health
+                            .lock()
+                            .map(|mut health| health.record_failure(url, &e))
+                            .unwrap_or_default();
+                        error!(
+                            "Error fetching {} ({} failures in a row): {:#}",
+                            url, streak, e
+                        );
+                        // items from the last successful fetch stay while the feed is failing
+                        cached_items()
+                    }
+                };
+                if let Ok(mut results_guard) = results.lock() {
+                    results_guard.extend(keep(url, items));
+                }
+            });
+            if let Ok(cache) = feed_cache.lock() {
+                if let Err(e) = cache.save() {
+                    error!("Failed to save feed cache: {:#}", e);
+                }
+            }
+            if let Ok(health) = feed_health.lock() {
+                if let Err(e) = health.save() {
+                    error!("Failed to save feed health: {:#}", e);
+                }
+            }
+            if fetched_any.into_inner() {
+                if let Ok(mut last_fetched) = last_fetched_arc.lock() {
+                    *last_fetched = Some(Instant::now());
                 }
             }
-            None => 0,
-        };
-        self.virtual_state.select(Some(i));
-        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
-    }
 
-    pub fn previous(&mut self) {
-        let i = match self.virtual_state.selected() {
-            Some(i) => {
-                if i > 0 {
-                    i - 1
-                } else {
-                    0
+            if let Ok(mut items_guard) = items_arc.lock() {
+                if let Ok(results_guard) = results.lock() {
+                    // Filter out hidden items
+                    let mut new_items: Vec<RssFeedItem> = results_guard
+                        .iter()
+                        .filter(|item| !hidden_items.is_hidden(&item.item_id))
+                        .cloned()
+                        .collect();
+                    prss::filters::newest_first(&mut new_items, rss_max_items);
+
+                    let known_ids: std::collections::HashSet<&str> = items_guard
+                        .iter()
+                        .map(|item| item.item_id.as_str())
+                        .collect();
+                    let unseen: Vec<&RssFeedItem> = new_items
+                        .iter()
+                        .filter(|item| !known_ids.contains(item.item_id.as_str()))
+                        .collect();
+                    // on the very first load everything is unseen, that's not news
+                    if notify && !known_ids.is_empty() && !unseen.is_empty() {
+                        let sources: Vec<&str> = unseen
+                            .iter()
+                            .map(|item| item.source.as_str())
+                            .sorted()
+                            .dedup()
+                            .collect();
+                        notifications::notify(
+                            "New RSS items".to_string(),
+                            notifications::new_rss_items_body(&sources, unseen.len()),
+                        );
+                    }
+                    let matches = watch::find_matches(&watch_queries, unseen.iter().copied());
+                    if let Ok(mut watch_matches) = watch_matches_arc.lock() {
+                        watch_matches.extend(matches);
+                    }
+                    *items_guard = new_items;
+
+                    if let Ok(mut is_loading) = is_loading_arc.lock() {
+                        *is_loading = false;
+                    } else {
+                        panic!("is_loading lock error"); //todo
+                    }
                 }
             }
-            None => 0,
-        };
-        self.virtual_state.select(Some(i));
-        if i < self.virtual_state.offset() {
-            *self.virtual_state.offset_mut() = i
-        }
-        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
-    }
+            events::wake();
+        });
 
-    pub fn set_colors(&mut self) {
-        self.colors = TableColors::new(&PALETTES[self.color_index]);
+        Ok(())
     }
+    pub fn close_rss_triage(&mut self) -> anyhow::Result<()> {
+        self.app_mode = AppMode::Normal;
+        if let Some(popup_state) = &self.rss_triage_state {
+            // Check if any changes were made
+            if popup_state.changes_made {
+                // Switch to refreshing mode with proper loading message
+                self.app_mode = AppMode::Refreshing(RefreshingPopup::new(
+                    "Refreshing Pocket data ⏳".to_string(),
+                    LoadingType::Refresh,
+                ));
 
-    fn open_current_url(&mut self) -> anyhow::Result<()> {
-        if let Some(idx) = self.virtual_state.selected() {
-            if let Some(item) = self.items.get_mut(idx) {
-                self.pocket_client
-                    .mark_as_read(item.id().parse::<usize>()?)?;
-                item.add_tag("read");
-                webbrowser::open(&item.url()).context("Failed to open link in a browser")?;
+                // Mark RSS items as processed
+                self.rss_feed_state.mark_items_processed();
             }
+
+            // Start a new RSS feed check in the background
+            self.start_rss_feed_loading()?;
         }
+
+        self.rss_triage_state = None;
         Ok(())
     }
-
-    //todo: usize conversion is dumb
-    fn delete_article(&mut self) -> anyhow::Result<()> {
+    /// `tagged` is the title and url of the edited item, used for the suggested tags
+    fn switch_to_tags_mode(
+        &mut self,
+        target: TagTarget,
+        initial_tags: Vec<String>,
+        tagged: Option<(String, String)>,
+    ) {
+        let suggested = tagged.map_or_else(Vec::new, |(title, url)| {
+            autotag::suggest(&title, &url, &self.config.tag_rules, &self.cached_tags)
+        });
+        self.app_mode = AppMode::TagEdit(TagEditor::new(
+            target,
+            initial_tags,
+            self.cached_tags.clone(),
+            suggested,
+        ));
+    }
+    fn process_add_to_pocket_with_tags(&mut self) -> anyhow::Result<()> {
+        if let Some(triage_state) = &mut self.rss_triage_state {
+            if triage_state.prepare_add_to_pocket() {
+                // suggestions only make sense for a single item
+                let tagged = match triage_state.pending_items() {
+                    [item] => Some((item.title.clone(), item.link.clone())),
+                    _ => None,
+                };
+                self.switch_to_tags_mode(TagTarget::RssItems, Vec::new(), tagged);
+            }
+        }
+        Ok(())
+    }
+    fn switch_to_edit_tags_mode(&mut self) {
         if let Some(idx) = self.virtual_state.selected() {
             if let Some(item) = self.items.get(idx) {
-                self.pocket_client.delete(item.id().parse::<usize>()?)?;
-
-                // Log the deletion in the storage.delta
-                let delta_record = storage::PocketItemUpdate::Delete {
-                    item_id: item.id(),
-                    timestamp: Some(Utc::now().timestamp().try_into().unwrap()),
-                };
-                let delta_file = Path::new("snapshot_updates.db");
-                // this is needed to enrich delete event with timestamp. looks like pocket api erases this info
-                storage::append_delete_to_delta(delta_file, &delta_record)?;
+                let current_tags = item.tags().cloned().collect();
+                let tagged = Some((item.title().to_string(), item.url().to_string()));
+                self.switch_to_tags_mode(TagTarget::PocketItem, current_tags, tagged);
             }
-            self.items.remove(idx);
         }
+    }
+
+    /// Links in the clipboard, one per line, are added to Pocket once their tags are entered
+    fn paste_urls(&mut self) -> anyhow::Result<()> {
+        let content = cli_clipboard::get_contents()
+            .map_err(|e| anyhow::anyhow!("Failed to read the clipboard: {}", e))?;
+        let Some(urls) = pasted_urls(&content) else {
+            anyhow::bail!("The clipboard doesn't hold links, one per line");
+        };
+        let tagged = match urls.as_slice() {
+            [url] => Some((String::new(), url.clone())),
+            _ => None,
+        };
+        self.pasted_urls = urls;
+        self.switch_to_tags_mode(TagTarget::PastedUrls, Vec::new(), tagged);
         Ok(())
     }
 
-    fn toggle_top_tag(&mut self) -> anyhow::Result<()> {
+    fn add_pasted_urls(&mut self, tags: &[String]) -> anyhow::Result<()> {
+        let urls = std::mem::take(&mut self.pasted_urls);
+        let items: Vec<NewItem> = urls
+            .iter()
+            .map(|url| NewItem {
+                url,
+                tags,
+                ..Default::default()
+            })
+            .collect();
+        let failed: Vec<String> = urls
+            .iter()
+            .zip(self.pocket_client.add_all(&items)?)
+            .filter_map(|(url, outcome)| outcome.err().map(|err| format!("{}: {}", url, err)))
+            .collect();
+        if !failed.is_empty() {
+            anyhow::bail!(
+                "{} of {} not added, {}",
+                failed.len(),
+                urls.len(),
+                failed.join("; ")
+            );
+        }
+        Ok(())
+    }
+
+    fn update_tags(&mut self, target: TagTarget, new_tag_set: Vec<String>) -> anyhow::Result<()> {
+        if target == TagTarget::PastedUrls {
+            return self.add_pasted_urls(&new_tag_set);
+        }
+        if target == TagTarget::RssItems {
+            if let Some(triage_state) = &mut self.rss_triage_state {
+                if let Err(e) =
+                    triage_state.add_pending_to_pocket(&self.pocket_client, &new_tag_set)
+                {
+                    triage_state.set_status(format!("Error: {:#}", e));
+                }
+                self.advance_tutorial(TutorialAction::RssTriage);
+            }
+            return Ok(());
+        }
+
+        // Handle pocket item tags
         if let Some(idx) = self.virtual_state.selected() {
             if let Some(item) = self.items.get_mut(idx) {
-                if !item.tags().any(|x| x == "top") {
-                    self.pocket_client
-                        .mark_as_top(item.id().parse::<usize>()?)?;
-                    item.add_tag("top");
-                } else {
+                let local = storage::is_local_id(&item.item_id);
+                if !local {
+                    // Update tags in Pocket
                     self.pocket_client
-                        .unmark_as_top(item.id().parse::<usize>()?)?;
-                    item.remove_tag("top");
+                        .update_tags(item.id().parse::<usize>()?, &new_tag_set)?;
+                }
+
+                // Update local item
+                // First, remove all existing tags
+                let existing_tags: Vec<String> = item.tags().map(|t| t.to_string()).collect();
+                for tag in existing_tags {
+                    item.remove_tag(&tag);
+                }
+
+                // Then add the new tags
+                for tag in new_tag_set {
+                    item.add_tag(&tag);
+                }
+                if !local {
+                    self.local_edits.record(item);
+                } else if !self.pocket_client.is_dry_run() {
+                    storage::append_local_item(Path::new(DELTA_FILE), item)?;
                 }
+                self.advance_tutorial(TutorialAction::Tag);
             }
         }
         Ok(())
     }
 
-    fn fav_and_archive_article(&mut self) -> anyhow::Result<()> {
-        if let Some(idx) = self.virtual_state.selected() {
-            if let Some(item) = self.items.get(idx) {
-                self.pocket_client
-                    .fav_and_archive(item.id().parse::<usize>()?)?;
+    fn downloader(&self) -> anyhow::Result<Downloader> {
+        Ok(Downloader {
+            pocket_client: self.pocket_client.try_clone()?,
+            client: self.download_client.clone(),
+            config: self.config.clone(),
+        })
+    }
+
+    /// Adds articles and pdfs to the background download queue
+    fn queue_downloads<'a>(
+        &mut self,
+        items: impl Iterator<Item = &'a PocketItem>,
+    ) -> anyhow::Result<usize> {
+        if self.pocket_client.is_dry_run() {
+            anyhow::bail!("Downloads are disabled when Pocket isn't connected");
+        }
+        let mut queued = 0;
+        for item in items.filter(|item| matches!(item.item_type(), "article" | "pdf")) {
+            let job = DownloadJob {
+                item_id: item.item_id.clone(),
+                kind: item.item_type().to_string(),
+                title: item.title().to_string(),
+            };
+            if self.download_queue.enqueue(job)? {
+                queued += 1;
             }
-            self.items.remove(idx);
         }
-        Ok(())
+        Ok(queued)
     }
 
-    fn switch_to_search_mode(&mut self) {
-        self.app_mode = AppMode::Search(SearchMode::new((
-            self.virtual_state.offset(),
-            self.virtual_state.selected().unwrap(),
-        )));
+    fn queue_current_download(&mut self) -> anyhow::Result<()> {
+        let current = self
+            .virtual_state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+            .cloned();
+        self.queue_downloads(current.iter())?;
+        Ok(())
     }
 
-    fn switch_to_confirmation(&mut self, confirm_type: Confirmation) {
-        self.app_mode = AppMode::Confirmation(confirm_type)
+    /// Queues every article and pdf of the current view that isn't downloaded yet
+    fn queue_view_downloads(&mut self) -> anyhow::Result<usize> {
+        let items: Vec<PocketItem> = self
+            .items
+            .iter()
+            .filter(|item| !item.tags().any(|tag| tag == "downloaded"))
+            .cloned()
+            .collect();
+        self.queue_downloads(items.iter())
     }
 
-    fn switch_to_normal_mode(&mut self) {
-        self.app_mode = AppMode::Normal;
+    /// Picks up finished downloads and starts the next ones, called on every loop iteration
+    fn process_download_queue(&mut self) -> anyhow::Result<()> {
+        for (job, pdf_title) in self.download_queue.finished()? {
+            if let Some(item) = self
+                .items
+                .items
+                .iter_mut()
+                .find(|item| item.item_id == job.item_id)
+            {
+                item.add_tag("downloaded");
+            }
+            if let Some(title) = pdf_title {
+                self.rename_item(&job.item_id, title)?;
+            }
+            if job.kind == "pdf" {
+                self.pdf_index.reload(&job.item_id)?;
+            }
+            if self.config.notifications {
+                notifications::notify("Download finished".to_string(), job.title.clone());
+            }
+        }
+        for job in self.download_queue.ready_jobs(&self.config.downloads) {
+            let item = self
+                .items
+                .items
+                .iter()
+                .find(|item| item.item_id == job.item_id)
+                .cloned();
+            let downloader = self.downloader();
+            self.download_queue.spawn(job, move || {
+                let item = item.ok_or_else(|| anyhow::anyhow!("Item is no longer in the list"))?;
+                let downloader = downloader?;
+                match item.item_type() {
+                    "pdf" => downloader.download_pdf(&item),
+                    _ => downloader.download_article(&item).map(|_| None),
+                }
+            });
+        }
+        Ok(())
     }
 
-    fn switch_to_normal_mode_from(&mut self, from: AppMode) {
-        self.app_mode = AppMode::Normal;
-        match from {
-            AppMode::Search(x) => {
-                self.apply_filter();
-                *self.virtual_state.offset_mut() = x.normal_mode_positions.0;
-                self.virtual_state.select(Some(x.normal_mode_positions.1));
+    /**
+    Converts an already downloaded article again with the current extraction settings. Uses the
+    archived html when there is one, a single-file archive re-extracts fine as well.
+    */
+    fn reextract_article(&mut self) -> anyhow::Result<()> {
+        let Some(item) = self
+            .virtual_state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+        else {
+            return Ok(());
+        };
+        let path = downloads::article_path(item);
+        if item.item_type() != "article" || !path.exists() {
+            anyhow::bail!("Article isn't downloaded yet. Download it with 'w' first");
+        }
+        let downloader = self.downloader()?;
+        let archived = path.with_extension("html");
+        let html_content = if archived.exists() {
+            fs::read_to_string(&archived)?
+        } else {
+            match downloader.permanent_copy(item.url()) {
+                Some(html) => html,
+                None => downloader.download_page(item.url())?,
             }
-            _ => {} // do nothing
+        };
+        downloader.convert_article(item, &html_content)
+    }
+
+    fn boundary_preview(&self, item: &PocketItem) -> anyhow::Result<BoundaryPreviewState> {
+        let path = downloads::article_path(item);
+        if !path.exists() {
+            anyhow::bail!("Article isn't downloaded yet. Download it with 'w' first");
         }
+        let content = fs::read_to_string(&path)?;
+        let (plain, raw_markdown) = epub::article_sections(&content).ok_or_else(|| {
+            anyhow::anyhow!(
+                "Unexpected format of {}, re-extract it with 'X'",
+                path.display()
+            )
+        })?;
+        let rules = self.config.extraction.for_url(item.url()).boundaries;
+        Ok(BoundaryPreviewState {
+            item_id: item.item_id.clone(),
+            title: item.title().to_string(),
+            lines: markdown::boundary_preview(raw_markdown, plain, &rules),
+            scroll: 0,
+        })
     }
 
-    fn scroll_down(&mut self) {
-        let page_size = 13;
-        let i = match self.virtual_state.selected() {
-            Some(i) => {
-                if (i + page_size) > self.items.len() - 1 {
-                    (i + page_size) % self.items.len()
-                } else {
-                    i + page_size
-                }
-            }
-            None => 0,
+    /// Shows which lines of the downloaded article the boundary rules keep, for tuning the rules
+    fn show_boundary_preview(&mut self) -> anyhow::Result<()> {
+        let Some(item) = self
+            .virtual_state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+        else {
+            return Ok(());
         };
-        if self.virtual_state.offset() < self.virtual_state.selected().unwrap_or(0) {
-            *self.virtual_state.offset_mut() = self.virtual_state.selected().unwrap_or(0);
-        } else {
-            self.virtual_state.select(Some(i));
-            *self.virtual_state.offset_mut() = i;
+        match self.boundary_preview(item) {
+            Ok(preview) => self.boundary_preview_state = Some(preview),
+            Err(e) => self.app_mode = AppMode::Error(e.to_string()),
         }
+        Ok(())
     }
 
-    fn scroll_up(&mut self) {
-        let page_size = 13;
-        let i = match self.virtual_state.selected() {
-            Some(i) => {
-                if i > page_size {
-                    i - page_size
-                } else {
-                    0
-                }
-            }
-            None => 0,
+    fn downloaded_article(&self) -> anyhow::Result<Option<(&PocketItem, PathBuf)>> {
+        let Some(item) = self
+            .virtual_state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+        else {
+            return Ok(None);
         };
-        if self.virtual_state.offset() < self.virtual_state.selected().unwrap_or(0) {
-            self.virtual_state.select(Some(self.virtual_state.offset()));
-        } else {
-            self.virtual_state.select(Some(i));
-            *self.virtual_state.offset_mut() = i;
+        let path = downloads::article_path(item);
+        if item.item_type() != "article" || !path.exists() {
+            anyhow::bail!("Article isn't downloaded yet. Download it with 'w' first");
         }
+        Ok(Some((item, path)))
     }
 
-    fn scroll_to_end(&mut self) {
-        self.virtual_state.select(Some(self.items.len() - 1));
+    /// Summary of the selected downloaded article, the endpoint is only asked when none is cached
+    fn show_summary(&mut self) -> anyhow::Result<()> {
+        if self.config.summary.is_none() {
+            anyhow::bail!("Summaries are off, set \"summary\" in config.json");
+        }
+        let Some((item, path)) = self.downloaded_article()? else {
+            return Ok(());
+        };
+        match summary::cached(&path) {
+            Some(summary) => {
+                self.summary_popup_state = Some(SummaryPopupState {
+                    title: item.title().to_string(),
+                    summary,
+                    scroll: 0,
+                })
+            }
+            None => {
+                self.app_mode = AppMode::Refreshing(RefreshingPopup::new(
+                    "Summarizing article ⏳".to_string(),
+                    LoadingType::Summarize,
+                ))
+            }
+        }
+        Ok(())
     }
 
-    fn scroll_to_begining(&mut self) {
-        self.virtual_state.select(Some(0));
-        *self.virtual_state.offset_mut() = 0;
+    fn summarize_article(&mut self) -> anyhow::Result<()> {
+        let Some(config) = self.config.summary.clone() else {
+            return Ok(());
+        };
+        let Some((item, path)) = self.downloaded_article()? else {
+            return Ok(());
+        };
+        let title = item.title().to_string();
+        let summary = summary::summarize(&config, &path, &title)?;
+        self.summary_popup_state = Some(SummaryPopupState {
+            title,
+            summary,
+            scroll: 0,
+        });
+        Ok(())
     }
 
-    fn switch_to_rename_mode(&mut self, with_current_title: bool) {
-        if let Some(idx) = self.virtual_state.selected() {
-            let initial_text = if with_current_title {
-                self.items.get(idx).map_or("".to_string(), |item| {
-                    if item.title().is_empty() {
-                        item.url().to_string()
-                    } else {
-                        item.title().to_string()
-                    }
-                })
-            } else {
-                String::new()
-            };
-
-            self.app_mode = AppMode::CommandEnter(CommandEnterMode::new(
-                "Rename to (control+v to paste): ".to_string(),
-                initial_text.clone(),
-                CommandType::RenameItem,
-            ));
+    /// The selected item formatted with the share template at `index`
+    fn copy_for_sharing(&mut self, index: usize) {
+        let Some(item) = self
+            .virtual_state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+        else {
+            return;
+        };
+        let Some(template) = self.config.share_templates.get(index) else {
+            return;
+        };
+        match cli_clipboard::set_contents(template.render(item)) {
+            Ok(()) => self.toasts.success(format!("Copied as {}", template.name)),
+            Err(e) => self.toasts.error(format!("Failed to copy: {}", e)),
         }
+        self.share_popup_state = None;
     }
 
-    fn rename_current_item(&mut self, current_enter: String) -> anyhow::Result<()> {
-        if let Some(idx) = self.virtual_state.selected() {
-            if let Some(item) = self.items.get_mut(idx) {
-                let normalized_title = current_enter.replace('\n', " ").trim().to_string();
-                self.pocket_client.rename(
-                    item.id().parse::<usize>()?,
-                    item.url(),
-                    &normalized_title,
-                    item.time_added(),
-                )?;
-                item.rename_title_to(current_enter);
-            }
-        }
+    fn show_qr_code(&mut self) -> anyhow::Result<()> {
+        let Some(item) = self
+            .virtual_state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+        else {
+            return Ok(());
+        };
+        let code = qr::QrCode::encode(item.url())
+            .ok_or_else(|| anyhow::anyhow!("The link is too long for a QR code"))?;
+        self.qr_popup_state = Some(QrPopupState {
+            title: item.title().to_string(),
+            url: item.url().to_string(),
+            lines: code.to_lines(2),
+        });
         Ok(())
     }
 
-    fn jump_to_date(&mut self, current_enter: String) -> anyhow::Result<()> {
-        match self
-            .items
-            .iter()
-            .enumerate()
-            .find(|(_, data)| &data.date() <= &current_enter)
-        {
-            Some((idx, _)) => {
-                self.virtual_state.select(Some(idx));
-                *self.virtual_state.offset_mut() = idx;
-                self.scroll_state = self.scroll_state.position(idx * ITEM_HEIGHT);
-            }
-            None => {} /*do nothing*/
-        }
+    /// Opens the selected downloaded article in the reader, where passages are saved as highlights
+    fn show_reader(&mut self) -> anyhow::Result<()> {
+        let Some((item, path)) = self.downloaded_article()? else {
+            return Ok(());
+        };
+        let content = fs::read_to_string(&path)?;
+        self.reader_popup_state = Some(ReaderPopupState::new(
+            item,
+            path,
+            epub::article_markdown(&content),
+            30,
+        ));
         Ok(())
     }
 
-    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> anyhow::Result<()> {
-        match mouse_event.kind {
-            MouseEventKind::Down(event::MouseButton::Left) => {
-                let current_time = std::time::Instant::now();
-                let current_position = (mouse_event.column, mouse_event.row);
-
-                if let (Some(last_time), Some(last_position)) =
-                    (self.last_click_time, self.last_click_position)
-                {
-                    if current_time.duration_since(last_time) < Duration::from_millis(500)
-                        && current_position == last_position
-                    {
-                        // Double click detected
-                        self.open_current_url()?;
-                    }
-                }
-
-                self.last_click_time = Some(current_time);
-                self.last_click_position = Some(current_position);
+    /// Remembers where the reading stopped, for the table and for continuing later
+    fn close_reader(&mut self) -> anyhow::Result<()> {
+        let Some(reader) = self.reader_popup_state.take() else {
+            return Ok(());
+        };
+        let progress = reader.progress();
+        progress::save(&reader.article, progress)?;
+        self.reading_progress
+            .insert(reader.item_id, progress.percent());
+        Ok(())
+    }
 
-                // Calculate the clicked row index
-                let clicked_row = (mouse_event.row as usize).saturating_sub(1) / ITEM_HEIGHT
-                    + self.virtual_state.offset();
-                if clicked_row < self.items.len() {
-                    self.virtual_state.select(Some(clicked_row));
-                    self.scroll_state = self.scroll_state.position(clicked_row * ITEM_HEIGHT);
-                }
-            }
-            MouseEventKind::ScrollDown => self.scroll(0.2),
-            MouseEventKind::ScrollUp => self.scroll(-0.2),
-            _ => {}
+    fn save_highlight(&mut self) -> anyhow::Result<()> {
+        let Some(reader) = &mut self.reader_popup_state else {
+            return Ok(());
+        };
+        let text = reader.selected_text();
+        reader.anchor = None;
+        if text.is_empty() {
+            return Ok(());
         }
+        self.highlights.add(&reader.item_id, &text)?;
+        self.toasts.success("Highlight saved");
         Ok(())
     }
-    fn scroll(&mut self, delta: f32) {
-        self.scroll_accumulator += delta;
 
-        while self.scroll_accumulator >= 1.0 {
-            // self.next();
-            self.mousescroll_down();
-            self.scroll_accumulator -= 1.0;
+    /// Reads the selected downloaded article aloud, or pauses and resumes the one being read
+    fn toggle_playback(&mut self) -> anyhow::Result<()> {
+        if let Some(playback) = &mut self.playback {
+            return playback.toggle_pause();
         }
+        let Some(command) = self.config.tts.clone() else {
+            anyhow::bail!("Text-to-speech is off, set \"tts\" in config.json");
+        };
+        let Some((item, path)) = self.downloaded_article()? else {
+            return Ok(());
+        };
+        let content = fs::read_to_string(path)?;
+        let text = epub::article_sections(&content)
+            .map_or(epub::article_markdown(&content), |(plain, _)| plain);
+        let title = item.title().to_string();
+        self.playback = Some(tts::Playback::start(
+            &command,
+            &title,
+            format!("{}.\n\n{}", title, text),
+        )?);
+        Ok(())
+    }
 
-        while self.scroll_accumulator <= -1.0 {
-            // self.previous();
-            self.mousescroll_up();
-            self.scroll_accumulator += 1.0;
+    /// Forgets the playback once the command is done
+    fn process_playback(&mut self) {
+        if self
+            .playback
+            .as_mut()
+            .is_some_and(|playback| !playback.is_running())
+        {
+            self.playback = None;
         }
     }
 
-    fn mousescroll_down(&mut self) {
-        let new_index = self
-            .virtual_state
-            .selected()
-            .map(|i| (i + SCROLL_STEP).min(self.items.len() - 1))
-            .unwrap_or(0);
-        self.virtual_state.select(Some(new_index));
-        self.scroll_state = self.scroll_state.position(new_index * ITEM_HEIGHT);
+    /// Writes the highlights of all items to `exports/`, as markdown or Readwise's CSV
+    fn export_highlights(&mut self, readwise: bool) -> anyhow::Result<()> {
+        let (content, extension) = if readwise {
+            let csv = highlights::to_readwise_csv(&self.items.items, &self.highlights);
+            (csv, "csv")
+        } else {
+            let markdown = highlights::to_markdown(&self.items.items, &self.highlights);
+            (markdown, "md")
+        };
+        let path = Path::new("exports").join(format!(
+            "highlights-{}.{}",
+            Local::now().format("%Y-%m-%d-%H%M%S"),
+            extension
+        ));
+        fs::create_dir_all("exports")?;
+        fs::write(&path, content)?;
+        self.toasts
+            .success(format!("Exported highlights to {}", path.display()));
+        Ok(())
     }
 
-    fn mousescroll_up(&mut self) {
-        let new_index = self
-            .virtual_state
-            .selected()
-            .map(|i| i.saturating_sub(SCROLL_STEP))
-            .unwrap_or(0);
-        self.virtual_state.select(Some(new_index));
-        self.scroll_state = self.scroll_state.position(new_index * ITEM_HEIGHT);
+    /// Re-reads the rules from config.json, so they can be edited while the preview is open
+    fn reload_boundary_preview(&mut self) -> anyhow::Result<()> {
+        if !self.safe_mode && !self.pocket_client.is_dry_run() {
+            self.config.extraction =
+                config::Config::load(Path::new(config::CONFIG_FILE))?.extraction;
+        }
+        let Some(item) = self.boundary_preview_state.as_ref().and_then(|state| {
+            self.items
+                .items
+                .iter()
+                .find(|item| item.item_id == state.item_id)
+        }) else {
+            return Ok(());
+        };
+        let mut preview = self.boundary_preview(item)?;
+        if let Some(state) = &self.boundary_preview_state {
+            preview.scroll = state.scroll.min(preview.lines.len().saturating_sub(1));
+        }
+        self.boundary_preview_state = Some(preview);
+        Ok(())
     }
-}
-
-fn reload_data(
-    delta_file: &Path,
-    pocket_client: &GetPocketSync,
-    stats: &mut TotalStats,
-) -> anyhow::Result<Vec<PocketItem>> {
-    pocket_client
-        .refresh_delta_block(&delta_file)
-        .context("failed to refresh delta during refresh")?;
-
-    // Load and process delta updates
-    let delta_items = storage::load_delta_pocket_items(&delta_file);
-    let mut seen_item_ids = std::collections::HashSet::new();
-    let today = Utc::now();
 
-    let pocket_snapshot = storage::load_snapshot_file();
-    let mut current_items = pocket_snapshot.pocket_items();
-
-    // Process each delta update
-    for update in delta_items {
-        match update {
-            PocketItemUpdate::Delete {
-                item_id,
-                timestamp: ts_opt,
-            } => {
-                if let Some(ts) = ts_opt {
-                    if let Some(item) = current_items.get(&item_id) {
-                        if !seen_item_ids.contains(&item_id) {
-                            stats.track_as(item, &today, true, ts as i64);
-                            seen_item_ids.insert(item_id.clone());
-                        }
-                    }
-                }
-                current_items.remove(&item_id);
-            }
-            PocketItemUpdate::Add {
-                item_id: id,
-                data: mut new_item,
-            } => {
-                if let Some(existing) = current_items.get(&id) {
-                    // Update existing item
-                    new_item.time_added = existing.time_added().to_string();
-                    let ts: i64 = new_item.time_updated.parse::<i64>().unwrap_or(0);
-                    if new_item.favorite == "1" && !seen_item_ids.contains(&id) {
-                        stats.track_as(existing, &today, true, ts);
-                        seen_item_ids.insert(id.clone());
-                    }
-                    current_items.insert(id, new_item.into()); // Assuming T can be created from PocketItem
-                } else {
-                    // Add new item
-                    stats.track_item(&new_item, &today);
-                    current_items.insert(id, new_item.into());
-                }
-            }
+    /// Bundles downloaded articles of the current view into a single epub, one chapter per article
+    fn export_epub(&mut self) -> anyhow::Result<()> {
+        let chapters: Vec<epub::EpubChapter> = self
+            .items
+            .iter()
+            .filter_map(|item| {
+                let path = Path::new("articles").join(format!("{}.md", item.item_id));
+                let content = fs::read_to_string(path).ok()?;
+                Some(epub::EpubChapter {
+                    title: item.title().to_string(),
+                    url: item.url().to_string(),
+                    date_added: item.date(),
+                    markdown: epub::article_markdown(&content).to_string(),
+                })
+            })
+            .collect();
+        if chapters.is_empty() {
+            self.app_mode = AppMode::Error(
+                "No downloaded articles in the current view. Download them with 'w' first"
+                    .to_string(),
+            );
+            return Ok(());
         }
+
+        let now = Local::now();
+        let path =
+            Path::new("exports").join(format!("pkt-tui-{}.epub", now.format("%Y-%m-%d-%H%M%S")));
+        let title = format!("Pocket articles {}", now.format("%Y-%m-%d"));
+        epub::write_epub(&path, &title, &chapters)?;
+        self.help_popup_state = Some(HelpPopupState {
+            content: format!(
+                " Exported {} articles to {}",
+                chapters.len(),
+                path.display()
+            ),
+        });
+        Ok(())
     }
 
-    // Convert back to a sorted vector
-    let items: Vec<PocketItem> = current_items
-        .into_values()
-        .filter(|a| a.tags().all(|tag| tag != "favorite")) // Skip favorited items
-        .sorted_by(|a, b| b.time_added.partial_cmp(&a.time_added).unwrap())
-        .collect();
+    // /// Checks if a line is a markdown header
+    // fn is_header(line: &str) -> bool {
+    //     line.trim_start().starts_with('#')
+    // }
 
-    return Ok(items);
-}
+    // /// Checks if a line should stay attached to the previous line
+    // fn should_stay_attached(line: &str) -> bool {
+    //     // Headers should be followed by their content
+    //     Self::is_header(line) ||
+    //     // List items should stay together
+    //     line.trim_start().starts_with('*') ||
+    //     line.trim_start().starts_with('-') ||
+    //     line.trim_start().starts_with(|c: char| c.is_ascii_digit() && line.contains(". ")) ||
+    //     // Code blocks should stay together
+    //     line.trim_start().starts_with('`') ||
+    //     // Continuation of a sentence (no capital letter at start)
+    //     (!line.trim_start().is_empty() &&
+    //      !Self::is_header(line) &&
+    //      line.trim_start().chars().next()
+    //          .map(|c| !c.is_uppercase())
+    //          .unwrap_or(false))
+    // }
 
-fn main() -> Result<(), Box<dyn Error>> {
-    let target = Box::new(File::create("log.txt").expect("Can't create file"));
+    // /// Normalizes markdown content by:
+    // /// 1. Removing preamble/postamble content not present in plain text
+    // /// 2. Restoring proper paragraph separation while preserving markdown formatting
+    // pub fn normalize_markdown(markdown: &str, plain: &str) -> String {
+    //     // First, find the start of actual content
+    //     let first_plain_para = plain.split("\n\n").next().unwrap_or("").trim();
 
-    let token_opt = tokenstorage::UserTokenStorage::get_token()?;
-    let token = if let Some(t) = token_opt {
-        t
-    } else {
-        println!("Auth information is not found. Starting authentication procedure...");
-        thread::sleep(Duration::from_secs(4));
-        let pocket_auth = auth::PocketAuth::new()?;
-        let auth_token = pocket_auth.authenticate()?;
-        tokenstorage::UserTokenStorage::store_token(&auth_token)?;
-        auth_token
-    };
+    //     let markdown_lines: Vec<&str> = markdown.lines().collect();
+    //     let mut start_idx = 0;
 
-    let pocket_client = GetPocketSync::new(&token)?;
-
-    if !storage::snapshot_exists() {
-        // let animation = vec!["|", "/", "-", "\\"];
-        // let mut animation_index = 0;
-        // let running = std::sync::Arc::new(std::sync::atomic::AtomicBool::new(true));
-        // let running_clone = running.clone();
-        // let animation_handle = thread::spawn(move || {
-        //     while running_clone.load(std::sync::atomic::Ordering::SeqCst) {
-        //         print!(
-        //             "\rRetrieving snapshot data from pocket. This might take time... {}",
-        //             animation[animation_index]
-        //         );
-        //         io::stdout().flush().unwrap();
-        //         thread::sleep(Duration::from_millis(100));
-        //         animation_index = (animation_index + 1) % animation.len();
-        //     }
-        // });
-
-        println!("\rRetrieving snapshot data from pocket. This might take time... ");
-        let snapshot: storage::Pocket = pocket_client.retrieve_all()?;
-        storage::save_to_snapshot(&snapshot)?;
-        if let Some((item_id, value)) = snapshot.list.iter().max_by_key(|(_id, item)| {
-            item.get("time_added")
-                .and_then(|v| v.as_str())
-                .and_then(|s| s.parse::<i64>().ok())
-                .unwrap_or(0)
-        }) {
-            let delta_file = Path::new(DELTA_FILE);
-            let mut map: serde_json::Map<String, serde_json::Value> =
-                serde_json::Map::with_capacity(1);
-            map.insert(item_id.clone(), value.clone());
-            storage::append_to_delta(
-                delta_file,
-                &storage::Pocket {
-                    status: 1,
-                    complete: 1,
-                    list: map,
-                },
-            )?;
-        } else {
-            todo!("Oh no1");
-        }
-        // running.store(false, std::sync::atomic::Ordering::SeqCst);
-        // let _ = animation_handle.join();
-    }
-
-    env_logger::Builder::new()
-        .target(env_logger::Target::Pipe(target))
-        .filter(None, LevelFilter::Trace)
-        .format(|buf, record| {
-            writeln!(
-                buf,
-                "({} {} {}:{}) {}",
-                Local::now().format("%Y-%m-%d %H:%M:%S%.3f"),
-                record.level(),
-                record.file().unwrap_or("unknown"),
-                record.line().unwrap_or(0),
-                record.args()
-            )
-        })
-        .init();
+    //     // Find content start
+    //     for (i, window) in markdown_lines.windows(3).enumerate() {
+    //         let combined = window.join(" ");
+    //         if combined.contains(first_plain_para) {
+    //             start_idx = i;
+    //             break;
+    //         }
+    //     }
 
-    // setup terminal
-    errors::install_hooks()?;
-    enable_raw_mode()?;
-    let mut stdout = io::stdout();
-    execute!(
-        stdout,
-        EnterAlternateScreen,
-        EnableMouseCapture,
-        PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
-    )?;
-    let backend = CrosstermBackend::new(stdout);
-    let mut terminal = Terminal::new(backend)?;
+    //     // Find content end
+    //     let mut end_idx = markdown_lines.len();
+    //     for (i, line) in markdown_lines.iter().enumerate().rev() {
+    //         if line.contains("## Related posts")
+    //             || line.contains("Blog Comments")
+    //             || line.contains("Contents")
+    //         {
+    //             end_idx = i;
+    //             break;
+    //         }
+    //     }
 
-    let stats = TotalStats::new();
-    let list = Vec::new(); //reload_data(&delta_file, &pocket_client, &mut stats)?;
+    //     // Process content while preserving markdown formatting
+    //     let mut result = Vec::new();
+    //     let mut current_group = Vec::new();
 
-    let mut app: App = App::new(list, pocket_client, stats);
-    app.start_rss_feed_loading()?;
-    let res = run_app(&mut terminal, app);
+    //     for (i, line) in markdown_lines[start_idx..end_idx].iter().enumerate() {
+    //         let trimmed = line.trim();
+    //         if trimmed.is_empty() {
+    //             if !current_group.is_empty() {
+    //                 result.push(current_group.join("\n"));
+    //                 current_group.clear();
+    //             }
+    //             continue;
+    //         }
 
-    // restore terminal
-    disable_raw_mode()?;
-    execute!(
-        terminal.backend_mut(),
-        LeaveAlternateScreen,
-        DisableMouseCapture
-    )?;
-    terminal.show_cursor()?;
+    //         // Check if this line should be kept with the previous content
+    //         if i > 0 && Self::should_stay_attached(trimmed) {
+    //             current_group.push(trimmed);
+    //         } else {
+    //             if !current_group.is_empty() {
+    //                 result.push(current_group.join("\n"));
+    //                 current_group.clear();
+    //             }
+    //             current_group.push(trimmed);
+    //         }
+    //     }
 
-    if let Err(err) = res {
-        println!("{err:?}");
-    }
+    //     // Add final group if any
+    //     if !current_group.is_empty() {
+    //         result.push(current_group.join("\n"));
+    //     }
 
-    Ok(())
-}
+    //     // Join paragraphs with double newlines
+    //     let content = result
+    //         .into_iter()
+    //         .filter(|p| !p.is_empty())
+    //         .collect::<Vec<_>>()
+    //         .join("\n\n");
 
-fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> anyhow::Result<()> {
-    loop {
-        terminal
-            .draw(|f| ui(f, &mut app))
-            .context("Failed to draw UI")?;
-        match &mut app.app_mode {
-            AppMode::Initialize => {
-                app.refresh_data()?;
-                app.app_mode = AppMode::Normal;
-            }
-            AppMode::Normal => process_input_normal_mode(&mut app)?,
-            AppMode::Confirmation(ref confirmation_type) => {
-                let ctype = confirmation_type.clone();
-                process_confirmation(&mut app, ctype)?
-            }
+    //     // Clean up the final string while preserving markdown structure
+    //     content
+    //         .split("\n\n")
+    //         .map(|para| para.trim())
+    //         .filter(|para| !para.is_empty())
+    //         .collect::<Vec<_>>()
+    //         .join("\n\n")
+    // }
 
-            AppMode::Search(current) => {
-                let sstr = current.clone();
-                process_search_mode(&mut app, sstr)?
-            }
-            AppMode::MulticharNormalModeEnter(x) => {
-                let cur_state = x.clone();
-                process_multichar_enter_mode(&mut app, cur_state)?
+    pub fn open_rss_triage(&mut self) -> anyhow::Result<()> {
+        if let Ok(items_guard) = self.rss_feed_state.items.lock() {
+            // cached items can be triaged while the feeds are being fetched
+            let is_loading = self
+                .rss_feed_state
+                .is_loading
+                .lock()
+                .is_ok_and(|is_loading| *is_loading);
+            if is_loading && items_guard.is_empty() {
+                self.app_mode = AppMode::Error("RSS feed is being updated.".to_string());
+                return Ok(());
             }
-            AppMode::CommandEnter(enter) => {
-                let cur_state = enter.clone();
-                process_command_mode(&mut app, cur_state)?
+            if items_guard.is_empty() {
+                self.app_mode = AppMode::Error("No RSS updates available (yet)".to_string());
+                return Ok(());
             }
-            AppMode::Refreshing(ref mut pop) => {
-                if pop.was_redered {
-                    let refresh_result = match pop.refresh_type {
-                        LoadingType::Refresh => app.refresh_data(),
-                        LoadingType::Download => {
-                            if let Some(idx) = app.virtual_state.selected() {
-                                if let Some(item) = app.items.get(idx) {
-                                    match item.item_type() {
-                                        "pdf" => app.download_current_pdf(),
-                                        "article" => app.download_and_convert_article(),
-                                        _ => Ok(()),
-                                    }
-                                } else {
-                                    Ok(())
-                                }
-                            } else {
-                                Ok(())
-                            }
-                        }
-                    };
+        }
+        let visible_items = 33;
+        let items = if let Ok(items_guard) = self.rss_feed_state.items.lock() {
+            items_guard.to_vec()
+        } else {
+            Vec::new()
+        };
 
-                    match refresh_result {
-                        Ok(_) => {
-                            app.switch_to_normal_mode();
-                        }
-                        Err(err) => {
-                            app.app_mode = AppMode::Error(err.to_string());
-                        }
-                    }
-                } else {
-                    pop.was_redered = true;
-                }
+        self.rss_triage_state = Some(if self.pocket_client.is_dry_run() {
+            RssTriageState::with_hidden_items(
+                items,
+                visible_items,
+                prss::hidden_items::HiddenItems::new(),
+            )
+        } else {
+            RssTriageState::new(items, visible_items)?
+        });
+        let saved_urls: std::collections::HashSet<String> = self
+            .items
+            .items
+            .iter()
+            .map(|item| browserhistory::normalize_url(item.url()))
+            .collect();
+        if let Some(triage_state) = &mut self.rss_triage_state {
+            triage_state.mark_saved(&saved_urls, self.config.rss_hide_saved);
+        }
+        self.app_mode = AppMode::RssTriage;
 
-                // if pop.was_redered {
-                //     let refresh_result = match pop.refresh_type {
-                //         LoadingType::Refresh => app.refresh_data(),
-                //         LoadingType::Download => app.download_current_pdf(),
-                //     };
+        // If we need to refresh the items, do it in the background
+        if !self.rss_feed_state.items_processed {
+            self.start_rss_feed_loading()?;
+        }
 
-                //     match refresh_result {
-                //         Ok(_) => {
-                //             app.switch_to_normal_mode();
-                //         }
-                //         Err(err) => {
-                //             app.app_mode = AppMode::Error(err.to_string());
-                //         }
-                //     }
-                // } else {
-                //     pop.was_redered = true;
-                // }
-            }
-            AppMode::Error(err) => {
-                if let Event::Key(key) = event::read()? {
-                    if key.kind == KeyEventKind::Press {
-                        if KeyCode::Esc == key.code {
-                            app.switch_to_normal_mode();
-                        }
-                    }
-                }
+        Ok(())
+    }
+
+    pub fn open_rss_item_in_browser(&mut self) -> anyhow::Result<()> {
+        if let Some(item) = self.rss_triage_state.as_ref().and_then(|s| s.current()) {
+            if !item.url().is_empty() {
+                webbrowser::open(item.url()).context("Failed to open link in browser")?;
             }
         }
+        Ok(())
     }
-}
 
-fn process_command_mode(app: &mut App, mut cur_state: CommandEnterMode) -> anyhow::Result<()> {
-    Ok(if let Event::Key(key) = event::read()? {
-        if key.kind == KeyEventKind::Press {
-            use KeyCode::*;
-            match key.code {
-                Esc => app.switch_to_normal_mode(),
-                Tab => {
-                    if cur_state.complete_suggestion() {
-                        app.app_mode = AppMode::CommandEnter(cur_state);
-                    }
+    /// Fetches the page of the selected feed item and shows its article text in the preview
+    pub fn fetch_rss_full_text(&mut self) -> anyhow::Result<()> {
+        let downloader = self.downloader()?;
+        let Some(triage_state) = self.rss_triage_state.as_mut() else {
+            return Ok(());
+        };
+        let Some(item) = triage_state.current().cloned() else {
+            return Ok(());
+        };
+        if item.link.is_empty() {
+            triage_state.set_status("No page to fetch".to_string());
+            return Ok(());
+        }
+        triage_state.show_preview = true;
+        let full_texts = triage_state.full_texts.clone();
+        if let Ok(mut full_texts) = full_texts.lock() {
+            if matches!(
+                full_texts.get(&item.item_id),
+                Some(FullText::Loading | FullText::Loaded(_))
+            ) {
+                return Ok(());
+            }
+            full_texts.insert(item.item_id.clone(), FullText::Loading);
+        }
+        thread::spawn(move || {
+            let full_text = match downloader.readable_markdown(&item.link) {
+                Ok(markdown) => FullText::Loaded(markdown),
+                Err(e) => {
+                    error!("Failed to fetch full text of {}: {:#}", item.link, e);
+                    FullText::Failed(format!("{:#}", e))
                 }
-                Char(ch) => {
-                    if (key.modifiers.contains(KeyModifiers::CONTROL)
-                        || key.modifiers.contains(KeyModifiers::SUPER))
-                        && (ch == 'v' || ch == 'V')
-                    {
-                        if let Ok(clipboard_content) = cli_clipboard::get_contents() {
-                            cur_state.current_enter =
-                                clipboard_content.replace('\n', " ").trim().to_string();
-                        }
-                    } else {
-                        // For regular typing, add the character as-is
-                        cur_state.current_enter.insert(cur_state.cursor_pos, ch);
-                        cur_state.cursor_pos += 1;
-                    }
-                    cur_state.update_suggestion(&app.cached_tags);
+            };
+            if let Ok(mut full_texts) = full_texts.lock() {
+                full_texts.insert(item.item_id, full_text);
+            }
+            events::wake();
+        });
+        Ok(())
+    }
 
-                    app.app_mode = AppMode::CommandEnter(cur_state);
+    /// Reads the article of the selected feed item full-screen, fetched the same way as `f`
+    pub fn open_rss_reader(&mut self) -> anyhow::Result<()> {
+        let Some(triage_state) = self.rss_triage_state.as_mut() else {
+            return Ok(());
+        };
+        if triage_state.current().is_none() {
+            return Ok(());
+        }
+        triage_state.open_reader();
+        self.fetch_rss_full_text()
+    }
 
-                    // cur_state.current_enter.push(ch);
-                    // app.app_mode = AppMode::CommandEnter(cur_state);
-                }
-                Backspace => {
-                    if cur_state.cursor_pos > 0 {
-                        cur_state.current_enter.remove(cur_state.cursor_pos - 1);
-                        cur_state.cursor_pos -= 1;
+    /// Fetches all feeds again, the ones failing included
+    pub fn retry_rss_feeds(&mut self) -> anyhow::Result<()> {
+        let is_loading = self
+            .rss_feed_state
+            .is_loading
+            .lock()
+            .map(|is_loading| *is_loading)
+            .unwrap_or(false);
+        let failing = self
+            .rss_feed_state
+            .feed_health
+            .lock()
+            .map(|health| health.failing().len())
+            .unwrap_or_default();
+        self.start_rss_feed_loading()?;
+        if let Some(triage_state) = self.rss_triage_state.as_mut() {
+            triage_state.set_status(if is_loading {
+                "Feeds are being fetched already".to_string()
+            } else {
+                format!("Fetching feeds again, {} failing", failing)
+            });
+        }
+        Ok(())
+    }
 
-                        if let Some(tag_popup_state) = &app.tag_popup_state {
-                            cur_state.update_suggestion(
-                                &tag_popup_state
-                                    .tags
-                                    .iter()
-                                    .map(|x| x.0.clone())
-                                    .collect::<Vec<String>>(),
-                            );
-                        }
-                    }
-                    app.app_mode = AppMode::CommandEnter(cur_state);
-                }
-                Left => {
-                    if cur_state.cursor_pos > 0 {
-                        cur_state.cursor_pos -= 1;
-                        app.app_mode = AppMode::CommandEnter(cur_state);
-                    }
-                }
-                Right => {
-                    if cur_state.cursor_pos < cur_state.current_enter.len() {
-                        cur_state.cursor_pos += 1;
-                        app.app_mode = AppMode::CommandEnter(cur_state);
-                    }
-                }
-                Enter => {
-                    match cur_state.command_type {
-                        CommandType::RenameItem => {
-                            app.rename_current_item(cur_state.current_enter)?
-                        }
-                        CommandType::JumpToDate => app.jump_to_date(cur_state.current_enter)?,
-                        CommandType::Tags => app.update_tags(cur_state.current_enter)?,
-                    }
-                    app.switch_to_normal_mode();
-                }
-                _ => {} //do nothing
+    /// Plays the podcast or video attached to the selected feed item
+    pub fn open_rss_enclosure(&mut self) -> anyhow::Result<()> {
+        let Some(triage_state) = self.rss_triage_state.as_mut() else {
+            return Ok(());
+        };
+        let Some(enclosure) = triage_state
+            .current()
+            .and_then(|item| item.enclosure.clone())
+        else {
+            triage_state.set_status("No media attached".to_string());
+            return Ok(());
+        };
+        match &self.config.media_player {
+            Some(player) => {
+                // the player keeps running after the triage is closed
+                std::process::Command::new(player)
+                    .arg(&enclosure)
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::null())
+                    .spawn()
+                    .with_context(|| format!("Failed to start {}", player))?;
             }
+            None => webbrowser::open(&enclosure).context("Failed to open media in browser")?,
         }
-    })
-}
+        Ok(())
+    }
+    fn show_watch_popup(&mut self) {
+        let matches = if let Ok(mut watch_matches) = self.watch_matches.lock() {
+            std::mem::take(&mut *watch_matches)
+        } else {
+            Vec::new()
+        };
+        if matches.is_empty() {
+            self.app_mode = AppMode::Error("No new items matching watch queries".to_string());
+        } else {
+            self.watch_popup_state = Some(WatchPopupState::new(matches, 20));
+        }
+    }
 
-fn process_multichar_enter_mode(app: &mut App, cur_state: String) -> anyhow::Result<()> {
-    Ok(
-        if let Event::Key(key) = event::read().context("Couldn't read user input")? {
-            if key.kind == KeyEventKind::Press {
-                use KeyCode::*;
-                match (cur_state.as_str(), key.code) {
-                    ("g", Char('g')) => {
-                        app.switch_to_normal_mode();
-                        app.scroll_to_begining();
-                    }
-                    ("g", Char('d')) => {
-                        app.app_mode = AppMode::CommandEnter(CommandEnterMode::new_empty(
-                            "Jump to [yyyy-mm-dd]:".to_string(),
-                            CommandType::JumpToDate,
-                        ));
-                    }
-                    ("Z", Char('Z')) => {
-                        panic!("Exit");
-                    }
-                    _ => {
-                        app.switch_to_normal_mode();
-                    }
-                }
+    fn show_favorites_popup(&mut self) -> anyhow::Result<()> {
+        let favorites = storage::load_favorites(Path::new(DELTA_FILE))?;
+        if favorites.is_empty() {
+            self.app_mode = AppMode::Error("No favorites yet".to_string());
+        } else {
+            self.favorites_popup_state = Some(FavoritesPopupState::new(favorites, 10));
+        }
+        Ok(())
+    }
+
+    /// Archived items aren't kept locally in full, the archive is browsed page by page from Pocket
+    fn show_archive(&mut self) -> anyhow::Result<()> {
+        if self.pocket_client.is_dry_run() {
+            anyhow::bail!("The archive is fetched from Pocket, there is none with sample data");
+        }
+        self.archive_popup_state = Some(ArchivePopupState::new(10));
+        self.fetch_archive_page()
+    }
+
+    /// Starts fetching the next page when the selection is close to the end of what's fetched
+    fn fetch_archive_page(&mut self) -> anyhow::Result<()> {
+        let Some(archive) = &mut self.archive_popup_state else {
+            return Ok(());
+        };
+        if !archive.needs_page() {
+            return Ok(());
+        }
+        let client = self.pocket_client.try_clone()?;
+        let offset = archive.offset;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(client.retrieve_archive(offset));
+            events::wake();
+        });
+        archive.loading = Some(receiver);
+        Ok(())
+    }
+
+    /// Adds the page fetched by `fetch_archive_page` once it arrives
+    fn process_archive(&mut self) {
+        let Some(archive) = &mut self.archive_popup_state else {
+            return;
+        };
+        let Some(receiver) = &archive.loading else {
+            return;
+        };
+        let fetched = match receiver.try_recv() {
+            Ok(fetched) => fetched,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Err(anyhow::anyhow!("the archive thread stopped"))
             }
-        },
-    )
-}
+        };
+        archive.loading = None;
+        match fetched {
+            Ok(page) => archive.add_page(page),
+            Err(e) => {
+                error!("Failed to fetch the archive: {:#}", e);
+                self.toasts
+                    .error(format!("Failed to fetch the archive: {:#}", e));
+            }
+        }
+    }
 
-fn process_confirmation(app: &mut App, confirmation_type: Confirmation) -> anyhow::Result<()> {
-    Ok(
-        if let Event::Key(key) = event::read().context("Couldn't read user input")? {
-            if key.kind == KeyEventKind::Press {
-                use KeyCode::*;
-                match key.code {
-                    Char('y') | Char('Y') | Char('d') | Char('D') => {
-                        match confirmation_type {
-                            Confirmation::DeletePocketItem => app.delete_article()?,
-                        };
-                    }
-                    _ => {} // do nothing
-                }
+    /// What takes the keys now, the help popup shows the bindings of it
+    fn key_context(&self) -> keymap::KeyContext {
+        use keymap::KeyContext;
+        if self.context_menu_state.is_some() {
+            return KeyContext::ContextMenu;
+        }
+        if self.rss_triage_state.is_some() {
+            return KeyContext::RssTriage;
+        }
+        match self.active_popup() {
+            Some(PopupKind::Tags) => KeyContext::Tags,
+            Some(PopupKind::DocType) => KeyContext::DocType,
+            Some(PopupKind::DomainStats) => KeyContext::DomainStats,
+            Some(PopupKind::Authors) => KeyContext::Authors,
+            Some(PopupKind::Watch) => KeyContext::Watch,
+            Some(PopupKind::FailedActions) => KeyContext::FailedActions,
+            Some(PopupKind::Downloads) => KeyContext::Downloads,
+            Some(PopupKind::BoundaryPreview) => KeyContext::BoundaryPreview,
+            Some(PopupKind::Favorites) => KeyContext::Favorites,
+            Some(PopupKind::Archive) => KeyContext::Archive,
+            Some(PopupKind::Summary) => KeyContext::Summary,
+            Some(PopupKind::Qr) => KeyContext::Qr,
+            Some(PopupKind::Share) => KeyContext::Share,
+            Some(PopupKind::Reader) => KeyContext::Reader,
+            Some(PopupKind::Logs) => KeyContext::Logs,
+            Some(PopupKind::Conflicts) => KeyContext::Conflicts,
+            Some(PopupKind::SyncDiff) => KeyContext::SyncDiff,
+            Some(PopupKind::Help) | None => KeyContext::List,
+        }
+    }
+
+    fn show_help_popup(&mut self) {
+        self.help_popup_state = Some(HelpPopupState {
+            content: keymap::help_text(self.key_context()),
+        });
+    }
+
+    fn refresh_data(&mut self) -> anyhow::Result<()> {
+        if self.pocket_client.is_dry_run() {
+            // nothing to sync with, the list is kept as is
+            return Ok(());
+        }
+        if let Some(receiver) = self.background_sync.take() {
+            // the startup sync writes the same delta file, this one picks up what it fetched
+            let _ = receiver.recv();
+        }
+        let delta_file = Path::new("snapshot_updates.db");
+        if storage::needs_compaction(delta_file) {
+            if let Err(e) = storage::compact(delta_file) {
+                error!("Delta compaction failed: {:#}", e);
             }
-            app.switch_to_normal_mode()
-        },
-    )
-}
+        }
+        let before = if self.items.items.is_empty() {
+            // the first sync of a session compares with what the last one left on disk
+            load_local_items(delta_file, &mut TotalStats::new())
+                .map(|items| FilteredItems::<PocketItem>::non_archived(items).items)
+                .unwrap_or_default()
+        } else {
+            self.items.items.clone()
+        };
+        let mut stats = TotalStats::with_periods(self.config.stats_periods, self.config.week_start);
+        let items = reload_data(delta_file, &self.pocket_client, &mut stats)?;
+        self.apply_sync(items, stats, before)
+    }
 
-fn process_search_mode(app: &mut App, mut sstr: SearchMode) -> anyhow::Result<()> {
-    if event::poll(Duration::from_millis(100))? {
-        match event::read()? {
-            Event::Key(key) => {
-                if key.kind == KeyEventKind::Press {
-                    use KeyCode::*;
-                    match key.code {
-                        Esc => {
-                            app.clear_all_filters();
-                            app.switch_to_normal_mode_from(AppMode::Search(sstr))
-                        }
-                        Char(ch) => {
-                            sstr.search.push(ch);
-                            app.active_search_filter = Some(sstr.search.clone());
-                            app.app_mode = AppMode::Search(sstr);
-                            app.apply_filter();
-                        }
-                        Backspace => {
-                            sstr.search.pop();
-                            app.active_search_filter = Some(sstr.search.clone());
-                            app.app_mode = AppMode::Search(sstr);
-                            app.apply_filter();
-                        }
-                        Enter => {
-                            app.set_search_filter(sstr.search.clone());
-                            app.switch_to_normal_mode_from(AppMode::Search(sstr));
-                        }
-                        Down => app.next(),
-                        Up => app.previous(),
-                        _ => {} //do nothing
-                    }
+    /// Shows the list as the last sync left it right away and asks Pocket for what changed since
+    /// in the background, `process_background_sync` merges the answer in
+    fn start_from_cache(&mut self) -> anyhow::Result<()> {
+        if self.pocket_client.is_dry_run() {
+            return Ok(());
+        }
+        if self.safe_mode {
+            // compaction and syncs write to the files that may be crashing the app, safe mode
+            // only shows what is cached and keeps going when even that fails
+            let shown = if storage::snapshot_exists() {
+                self.show_cached_list()
+            } else {
+                Ok(())
+            };
+            if let Err(e) = shown {
+                error!("Failed to load the cached list: {:#}", e);
+                self.toasts
+                    .error(format!("Failed to load the cached list: {:#}", e));
+            }
+            return Ok(());
+        }
+        let delta_file = Path::new(DELTA_FILE);
+        if storage::needs_compaction(delta_file) {
+            if let Err(e) = storage::compact(delta_file) {
+                error!("Delta compaction failed: {:#}", e);
+            }
+        }
+        let bootstrap = !storage::snapshot_exists();
+        if !bootstrap {
+            self.show_cached_list()?;
+        }
+
+        // a fresh bootstrap has nothing to miss yet
+        let verify = !bootstrap && syncverify::is_due(self.config.verify_sync_days);
+        self.sync_in_background(bootstrap, verify)
+    }
+
+    fn show_cached_list(&mut self) -> anyhow::Result<()> {
+        let mut stats = TotalStats::with_periods(self.config.stats_periods, self.config.week_start);
+        let items = load_local_items(Path::new(DELTA_FILE), &mut stats)?;
+        self.set_items(items, stats)?;
+        self.order_by_reading_queue();
+        self.apply_filter();
+        Ok(())
+    }
+
+    /// The reading queue and the text of downloaded pdfs. Safe mode keeps both in memory only,
+    /// a broken file there might be what crashes the app
+    fn load_queue_and_pdf_index(&mut self, queue_file: &Path) {
+        if !self.safe_mode {
+            self.reading_queue = ReadingQueue::load(queue_file);
+            self.pdf_index = PdfIndex::load();
+        }
+    }
+
+    /// Fetches what changed in Pocket into the delta on a worker thread, the first start fetches
+    /// the whole list
+    fn sync_in_background(&mut self, bootstrap: bool, verify: bool) -> anyhow::Result<()> {
+        let client = self.pocket_client.try_clone()?;
+        let fetched = Arc::new(AtomicUsize::new(0));
+        self.bootstrap_progress = bootstrap.then(|| fetched.clone());
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            let sync = || -> anyhow::Result<()> {
+                if bootstrap {
+                    // the first start fetches the whole list, the footer counts the items
+                    let snapshot = client.retrieve_all(|count| {
+                        fetched.store(count, Ordering::Relaxed);
+                        events::wake();
+                    })?;
+                    storage::save_snapshot_with_delta(&snapshot, Path::new(DELTA_FILE))?;
+                }
+                client.refresh_delta_block(Path::new(DELTA_FILE))?;
+                if verify {
+                    let local = load_local_items(Path::new(DELTA_FILE), &mut TotalStats::new())?;
+                    let report =
+                        syncverify::verify_and_heal(&client, &local, Path::new(DELTA_FILE))?;
+                    info!(
+                        "Sync check: {} pages fetched again, {} deleted items removed",
+                        report.refetched_pages, report.deleted
+                    );
                 }
+                Ok(())
+            };
+            let _ = sender.send(sync());
+            events::wake();
+        });
+        self.background_sync = Some(receiver);
+        self.background_sync_started = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Merges the startup sync into the list once it is done, keeping the selected item. Waits
+    /// while another mode has the keys, it may point into the list
+    fn process_background_sync(&mut self) {
+        if !matches!(self.app_mode, AppMode::Normal) {
+            return;
+        }
+        let Some(receiver) = &self.background_sync else {
+            return;
+        };
+        let fetched = match receiver.try_recv() {
+            Ok(fetched) => fetched,
+            Err(mpsc::TryRecvError::Empty) => return,
+            Err(mpsc::TryRecvError::Disconnected) => {
+                Err(anyhow::anyhow!("the sync thread stopped"))
+            }
+        };
+        self.background_sync = None;
+        self.bootstrap_progress = None;
+        let edited_meanwhile = self
+            .background_sync_started
+            .is_some_and(|started| self.local_edits.edited_since(started));
+        if fetched.is_ok() && edited_meanwhile {
+            // the edits aren't in what was fetched, merging would drop them from the list and
+            // report them as conflicts. Another fetch picks them up
+            if let Err(e) = self.sync_in_background(false, false) {
+                error!("Background sync failed: {:#}", e);
+                self.toasts.error(format!("Sync failed: {:#}", e));
             }
-            Event::Mouse(mouse_event) => {
-                app.handle_mouse_event(mouse_event)?;
+            return;
+        }
+        let selected = self
+            .virtual_state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+            .map(|item| item.item_id.clone());
+        let result = fetched
+            .context("failed to refresh delta during refresh")
+            .and_then(|()| {
+                let mut stats =
+                    TotalStats::with_periods(self.config.stats_periods, self.config.week_start);
+                let items = load_local_items(Path::new(DELTA_FILE), &mut stats)?;
+                self.apply_sync(items, stats, self.items.items.clone())
+            });
+        match result {
+            Ok(()) => {
+                let index =
+                    selected.and_then(|id| self.items.iter().position(|item| item.item_id == id));
+                if let Some(index) = index {
+                    self.select_item(index);
+                }
             }
-            _ => {
-                // todo: proper logging
-                ()
+            Err(e) => {
+                error!("Background sync failed: {:#}", e);
+                self.toasts.error(format!("Sync failed: {:#}", e));
             }
         }
     }
-    Ok(())
-}
 
-fn process_input_normal_mode(app: &mut App) -> anyhow::Result<()> {
-    Ok(if let Event::Key(key) = event::read()? {
-        if key.kind == KeyEventKind::Press {
-            use KeyCode::*;
-            if let Some(doc_popup_state) = &mut app.doc_type_popup_state {
-                match key.code {
-                    Char(ch) if ch.is_digit(10) => {
-                        if let Some(filter) = doc_popup_state.select_by_number(ch) {
-                            app.select_doc_type(filter);
-                        }
-                    }
-                    Esc => app.doc_type_popup_state = None,
-                    _ => {}
+    /// Replaces the list and everything derived from it with freshly loaded items
+    fn set_items(&mut self, mut items: Vec<PocketItem>, stats: TotalStats) -> anyhow::Result<()> {
+        self.title_overrides.apply(&mut items);
+        self.cached_tags = items
+            .iter()
+            .flat_map(|item| item.tags().map(|tag| tag.to_string()))
+            .collect::<std::collections::HashSet<_>>()
+            .into_iter()
+            .collect();
+        self.stats = stats;
+        let item_ids: std::collections::HashSet<&str> =
+            items.iter().map(|item| item.item_id.as_str()).collect();
+        self.reading_queue
+            .retain_existing(|id| item_ids.contains(id))?;
+        self.items = FilteredItems::<PocketItem>::non_archived(items);
+        Ok(())
+    }
+
+    /// Puts the items a sync brought in place, `before` is the list the sync diff compares with
+    fn apply_sync(
+        &mut self,
+        mut items: Vec<PocketItem>,
+        stats: TotalStats,
+        before: Vec<PocketItem>,
+    ) -> anyhow::Result<()> {
+        self.mark_visited_as_read(&mut items)?;
+        let matches = watch::new_pocket_matches(&self.watch_queries, &items)?;
+        if let Ok(mut watch_matches) = self.watch_matches.lock() {
+            watch_matches.extend(matches);
+        }
+        self.set_items(items, stats)?;
+        let conflicts = self.local_edits.take_conflicts(&self.items.items);
+        if !conflicts.is_empty() {
+            self.conflicts_popup_state = Some(ConflictsPopupState {
+                conflicts,
+                selected_index: 0,
+            });
+        }
+        let diff = syncdiff::SyncDiff::between(&before, &self.items.items);
+        if !before.is_empty() && !diff.is_empty() {
+            self.sync_diff_popup_state = Some(SyncDiffPopupState::new(diff, 15));
+        }
+        self.order_by_reading_queue();
+        self.apply_filter();
+        self.last_synced = Some(Instant::now());
+        Ok(())
+    }
+
+    /// Sends the chosen tags of the selected conflict to Pocket and applies both sides' choice to
+    /// the list, titles stay local
+    fn resolve_conflict(&mut self, resolution: conflicts::Resolution) -> anyhow::Result<()> {
+        let Some(popup) = &mut self.conflicts_popup_state else {
+            return Ok(());
+        };
+        let Some(conflict) = popup.conflicts.get(popup.selected_index).cloned() else {
+            return Ok(());
+        };
+        let resolved = conflict.resolved(resolution);
+        if let Some(item) = self
+            .items
+            .items
+            .iter_mut()
+            .find(|item| item.item_id == conflict.item_id)
+        {
+            let item_id = item.id().parse::<usize>()?;
+            if resolved.title != conflict.remote.title {
+                self.title_overrides.set(&item.item_id, &resolved.title)?;
+                item.rename_title_to(resolved.title.clone());
+            }
+            if resolved.tags != conflict.remote.tags {
+                let tags: Vec<String> = resolved.tags.iter().cloned().collect();
+                self.pocket_client.update_tags(item_id, &tags)?;
+                for tag in &conflict.remote.tags {
+                    item.remove_tag(tag);
                 }
-            } else if let Some(tag_popup_state) = &mut app.tag_popup_state {
-                match app.tag_selection_mode {
-                    TagSelectionMode::Normal => match key.code {
-                        Down => tag_popup_state.move_selection(1),
-                        Up => tag_popup_state.move_selection(-1),
-                        Enter => app.select_tag(),
-                        Esc => app.tag_popup_state = None,
-                        Char(ch) => {
-                            app.tag_selection_mode = TagSelectionMode::Filtering;
-                            tag_popup_state.add_to_filter(ch)
-                        }
-                        _ => {}
-                    },
-                    TagSelectionMode::Filtering => match key.code {
-                        Char(ch) => tag_popup_state.add_to_filter(ch),
-                        Backspace => tag_popup_state.remove_from_filter(),
-                        Esc => {
-                            tag_popup_state.clear_filter();
-                            app.tag_selection_mode = TagSelectionMode::Normal;
-                        }
-                        Enter => {
-                            app.tag_selection_mode = TagSelectionMode::Normal;
-                            app.select_tag();
-                        }
-                        _ => {}
-                    },
+                for tag in &tags {
+                    item.add_tag(tag);
                 }
-            } else if let Some(ref mut domain_state) = &mut app.domain_stats_popup_state {
-                match key.code {
-                    Enter => {
-                        if let Some((domain, _)) =
-                            domain_state.stats.get(domain_state.selected_index)
-                        {
-                            let authors: Vec<String> =
-                                domain.split(", ").map(String::from).collect();
-                            if domain.contains("YT:") {
-                                // This is a video author
-                                app.domain_filter = Some(domain.clone());
-                                app.filter_by_video_authors(&authors);
-                            } else {
-                                // Regular domain
-                                app.domain_filter = Some(domain.clone());
-                                app.apply_filter();
-                            }
-                            app.domain_stats_popup_state = None;
-                        }
-                    }
-                    Esc => {
-                        app.domain_stats_popup_state = None;
-                    }
-                    Char('j') | Down => {
-                        domain_state.move_selection(1);
-                    }
-                    Char('k') | Up => {
-                        domain_state.move_selection(-1);
+            }
+        }
+        if let Some(popup) = &mut self.conflicts_popup_state {
+            popup.conflicts.remove(popup.selected_index);
+            if popup.conflicts.is_empty() {
+                self.conflicts_popup_state = None;
+            } else {
+                popup.move_selection(0);
+            }
+        }
+        Ok(())
+    }
+
+    /// Selects the item in the list, clearing the filters when they hide it
+    fn jump_to_item(&mut self, item_id: &str) -> bool {
+        let position = |items: &FilteredItems<PocketItem>| {
+            items.iter().position(|item| item.item_id == item_id)
+        };
+        let index = position(&self.items).or_else(|| {
+            self.clear_all_filters();
+            position(&self.items)
+        });
+        if let Some(index) = index {
+            self.select_item(index);
+        }
+        index.is_some()
+    }
+
+    /// Handles commands sent with `pkt-tui --remote` while the app is running
+    fn process_remote_requests(&mut self) {
+        let Some(socket) = &self.control_socket else {
+            return;
+        };
+        for remote in socket.pending() {
+            let idle = matches!(self.app_mode, AppMode::Normal);
+            let result = match &remote.request {
+                remote::Request::Add { url, tags } => {
+                    let added = self.pocket_client.add(url, tags).map(|_| ());
+                    if added.is_ok() && idle {
+                        // a sync brings the new item into the list
+                        self.app_mode = AppMode::Refreshing(RefreshingPopup::new(
+                            "Refreshing ⏳".to_string(),
+                            LoadingType::Refresh,
+                        ));
                     }
-                    _ => { /*do nothing */ }
+                    added
                 }
-            } else if let Some(ref mut popup_state) = app.rss_feed_popup_state {
-                match key.code {
-                    Char('j') | Down => popup_state.move_selection(1),
-                    Char('k') | Up => popup_state.move_selection(-1),
-                    Char('p') => popup_state.show_description = !popup_state.show_description,
-                    KeyCode::Char('d') => {
-                        popup_state.hide_current_item()?;
-                        return Ok(());
-                    }
-                    Char('a') => {
-                        app.process_add_to_pocket_with_tags()?;
-                        return Ok(());
-                    }
-                    Enter => app.handle_rss_feed_selection()?,
-                    Esc => {
-                        if (popup_state.show_description) {
-                            popup_state.show_description = false;
-                        } else {
-                            app.close_rss_feed_popup()?;
-                        }
-                        // app.rss_feed_popup_state = None;
-                    }
-                    _ => {}
+                remote::Request::Refresh if idle => self.refresh_all(),
+                remote::Request::Refresh => {
+                    Err(anyhow::anyhow!("The app is busy, try again later"))
                 }
+            };
+            remote.reply(result);
+        }
+    }
+
+    /// Commands entered after `:`
+    fn run_command(&mut self, command: &str) -> anyhow::Result<()> {
+        let command = command.trim();
+        let (name, argument) = command.split_once(' ').unwrap_or((command, ""));
+        match name {
+            "compact" => self.compact_storage(),
+            "verify" => self.verify_storage(),
+            "rebuild" if !self.pocket_client.is_dry_run() => {
+                self.app_mode = AppMode::Refreshing(RefreshingPopup::new(
+                    "Downloading the whole list ⏳".to_string(),
+                    LoadingType::Rebuild,
+                ));
+                Ok(())
+            }
+            "" | "rebuild" => Ok(()),
+            "tagall" | "untagall" => {
+                self.start_bulk_tag(argument.trim(), name == "untagall");
+                Ok(())
+            }
+            "logs" => {
+                self.logs_popup_state = Some(LogsPopupState::new(20));
+                Ok(())
+            }
+            "highlights" => self.export_highlights(false),
+            "readwise" => self.export_highlights(true),
+            _ => {
+                self.app_mode = AppMode::Error(format!("Unknown command: {}", command));
+                Ok(())
+            }
+        }
+    }
+
+    fn start_bulk_tag(&mut self, tag: &str, remove: bool) {
+        if tag.is_empty() || tag.contains(',') {
+            self.app_mode = AppMode::Error("Usage: :tagall <tag> or :untagall <tag>".to_string());
+            return;
+        }
+        let text = if remove {
+            format!("Removing '{}' from {} items ⏳", tag, self.items.len())
+        } else {
+            format!("Tagging {} items with '{}' ⏳", self.items.len(), tag)
+        };
+        self.app_mode = AppMode::Refreshing(RefreshingPopup::new(
+            text,
+            LoadingType::BulkTag {
+                tag: tag.to_string(),
+                remove,
+            },
+        ));
+    }
+
+    /// Tags or untags every item of the current filter with a single request to Pocket, failed
+    /// items go to the retry popup
+    fn bulk_tag(&mut self, tag: &str, remove: bool) -> anyhow::Result<()> {
+        let mut local_changed = 0;
+        for index in 0..self.items.len() {
+            let Some(item) = self.items.get_mut(index) else {
+                continue;
+            };
+            if !storage::is_local_id(&item.item_id) || item.tags().any(|t| t == tag) != remove {
+                continue;
+            }
+            if remove {
+                item.remove_tag(tag);
             } else {
-                //normal mode
-                match key.code {
-                    Enter => {
-                        if app.tag_popup_state.is_some() {
-                            app.select_tag();
-                        } else {
-                            app.open_current_url()?;
-                        }
-                    }
-                    Char('Z') => {
-                        app.app_mode = AppMode::MulticharNormalModeEnter("Z".to_string());
-                    }
-                    Esc => {
-                        if app.active_search_filter.is_some() {
-                            app.clear_search_filter();
-                        } else if app.selected_tag_filter.is_some() {
-                            app.clear_tag_filter();
-                        } else if app.domain_filter.is_some() {
-                            app.clear_domain_filter();
-                        } else if app.item_type_filter != ItemTypeFilter::All {
-                            app.set_item_type_filter(ItemTypeFilter::All);
-                        }
-                        if app.help_popup_state.is_some() {
-                            app.help_popup_state = None;
-                        }
-                    }
-                    Char('j') | Down => {
-                        if let Some(tag_popup_state) = &mut app.tag_popup_state {
-                            tag_popup_state.move_selection(1);
-                        } else {
-                            app.next();
-                        }
-                    }
-                    Char('k') | Up => {
-                        if let Some(tag_popup_state) = &mut app.tag_popup_state {
-                            tag_popup_state.move_selection(-1);
-                        } else {
-                            app.previous();
-                        }
-                    }
-                    Char('/') => app.switch_to_search_mode(),
-                    Char('t') => app.toggle_top_tag()?,
-                    Char('T') => app.switch_to_edit_tags_mode(),
-                    Char('f') | Char('F') => app.fav_and_archive_article()?,
-                    Char('d') => {
-                        if key.modifiers.contains(KeyModifiers::CONTROL) {
-                            app.scroll_down();
-                        } else {
-                            app.switch_to_confirmation(Confirmation::DeletePocketItem);
-                        }
-                    }
-                    Char('u') => {
-                        if key.modifiers.contains(KeyModifiers::CONTROL) {
-                            app.scroll_up();
-                        }
-                    }
-                    Char('g') => app.app_mode = AppMode::MulticharNormalModeEnter("g".to_string()),
-                    Char('G') => {
-                        app.scroll_to_end();
-                    }
-                    Char('r') => app.switch_to_rename_mode(true),
-                    Char('R') => app.switch_to_rename_mode(false),
-                    Char('z') => {
-                        if app.tag_popup_state.is_none() {
-                            app.show_tag_popup();
-                        } else {
-                            app.tag_popup_state = None;
-                        }
-                    }
-                    Char('w') => {
-                        if let Some(idx) = app.virtual_state.selected() {
-                            if let Some(item) = app.items.get(idx) {
-                                match item.item_type() {
-                                    "pdf" | "article" => {
-                                        let message = match item.item_type() {
-                                            "pdf" => "Downloading pdf ⏳",
-                                            "article" => "Downloading article ⏳",
-                                            _ => unreachable!(),
-                                        };
-                                        app.app_mode = AppMode::Refreshing(RefreshingPopup::new(
-                                            message.to_string(),
-                                            LoadingType::Download,
-                                        ));
-                                    }
-                                    _ => {} // Do nothing for other types
-                                }
-                            }
-                        }
-                    }
-                    Char('Q') => {
-                        app.app_mode = AppMode::Refreshing(RefreshingPopup::new(
-                            "Refreshing ⏳".to_string(),
-                            LoadingType::Refresh,
-                        ));
-                    }
-                    Char('s') => {
-                        app.filter_by_current_domain()?;
-                    }
-                    Char('S') => {
-                        app.show_domain_stats();
-                    }
-                    Char('i') => app.show_doc_type_popup(),
-                    Char('n') => {
-                        if app.rss_feed_popup_state.is_none() {
-                            app.show_rss_feed_popup()?;
-                        }
-                    }
-                    Char('b') => {
-                        match app.handle_neovim_edit() {
-                            Ok(Some(content)) => {
-                                // Use the edited content here
-                                // For example, you could store it in the currently selected item
-                                if let Some(idx) = app.virtual_state.selected() {
-                                    if let Some(item) = app.items.get_mut(idx) {
-                                        // Do something with the content
-                                        // For example:
-                                        // item.notes = content;
-                                    }
-                                }
-                            }
-                            Ok(None) => {
-                                // User cancelled or no changes
-                            }
-                            Err(e) => {
-                                // Show error in the footer or status area
-                                error!("Neovim edit failed: {}", e);
-                            }
-                        }
+                item.add_tag(tag);
+            }
+            if !self.pocket_client.is_dry_run() {
+                storage::append_local_item(Path::new(DELTA_FILE), item)?;
+            }
+            local_changed += 1;
+        }
+        let actions = self
+            .items
+            .iter()
+            .filter(|item| item.tags().any(|t| t == tag) == remove)
+            .filter(|item| !storage::is_local_id(&item.item_id))
+            .map(|item| {
+                let item_id = item.id().parse::<usize>()?;
+                let tag = tag.to_string();
+                Ok(if remove {
+                    BatchAction::RemoveTag { item_id, tag }
+                } else {
+                    BatchAction::AddTag { item_id, tag }
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let outcome = self.pocket_client.send_batch(actions)?;
+        let (changed, failed) = (
+            outcome.succeeded.len() + local_changed,
+            outcome.failed.len(),
+        );
+        let mut items = std::mem::take(&mut self.items.items);
+        self.handle_batch_outcome(&mut items, outcome);
+        self.items.items = items;
+        if !remove && changed > 0 && !self.cached_tags.iter().any(|t| t == tag) {
+            self.cached_tags.push(tag.to_string());
+        }
+        self.apply_filter();
+        if failed == 0 {
+            self.help_popup_state = Some(HelpPopupState {
+                content: if remove {
+                    format!(" Removed '{}' from {} items", tag, changed)
+                } else {
+                    format!(" Tagged {} items with '{}'", changed, tag)
+                },
+            });
+        }
+        Ok(())
+    }
+
+    /// Folds old delta lines into the snapshot, see `storage::compact`
+    fn compact_storage(&mut self) -> anyhow::Result<()> {
+        if self.pocket_client.is_dry_run() {
+            return Ok(());
+        }
+        if self.background_sync.is_some() {
+            // lines the sync appends while the delta is rewritten would be lost
+            anyhow::bail!("Sync is in progress, compact once it is done");
+        }
+        let compaction = storage::compact(Path::new(DELTA_FILE))?;
+        let mut content = format!(
+            " Folded {} updates into the snapshot, {} recent ones are kept in {}",
+            compaction.folded, compaction.kept, DELTA_FILE
+        );
+        if compaction.quarantined > 0 {
+            content.push_str(&format!(
+                "\n {} corrupt records moved to {}",
+                compaction.quarantined,
+                Path::new(DELTA_FILE).with_extension("quarantine").display()
+            ));
+        }
+        self.help_popup_state = Some(HelpPopupState { content });
+        Ok(())
+    }
+
+    /// Reports storage problems without fixing them, see `storage::verify`
+    fn verify_storage(&mut self) -> anyhow::Result<()> {
+        if self.pocket_client.is_dry_run() {
+            return Ok(());
+        }
+        let report = storage::verify(Path::new(DELTA_FILE))?;
+        self.help_popup_state = Some(HelpPopupState {
+            content: report.summary(),
+        });
+        Ok(())
+    }
+
+    /// Replaces the snapshot and the delta with the current list from the API
+    fn rebuild_storage(&mut self) -> anyhow::Result<()> {
+        let snapshot = self.pocket_client.retrieve_all(|_| {})?;
+        storage::rebuild_snapshot(&snapshot, Path::new(DELTA_FILE))?;
+        self.refresh_data()
+    }
+
+    /// Syncs with Pocket and fetches RSS feeds again
+    fn refresh_all(&mut self) -> anyhow::Result<()> {
+        self.app_mode = AppMode::Refreshing(RefreshingPopup::new(
+            "Refreshing ⏳".to_string(),
+            LoadingType::Refresh,
+        ));
+        self.start_rss_feed_loading()
+    }
+
+    /// Reconciles items that were read in the browser directly, see `browser_history` in config.json
+    fn mark_visited_as_read(&mut self, items: &mut Vec<PocketItem>) -> anyhow::Result<()> {
+        if !self.config.browser_history.enabled || self.pocket_client.is_read_only() {
+            return Ok(());
+        }
+        let visits = browserhistory::last_visits(&self.config.browser_history);
+        let visited = |item: &PocketItem| {
+            item.tags().all(|tag| tag != "read")
+                && visits
+                    .get(&browserhistory::normalize_url(item.url()))
+                    .is_some_and(|visited| *visited > item.time_added())
+        };
+        // Pocket doesn't know local items, they are marked in the delta only
+        for item in items
+            .iter_mut()
+            .filter(|item| storage::is_local_id(&item.item_id) && visited(item))
+        {
+            item.add_tag("read");
+            if !self.pocket_client.is_dry_run() {
+                storage::append_local_item(Path::new(DELTA_FILE), item)?;
+            }
+        }
+        let actions = items
+            .iter()
+            .filter(|item| !storage::is_local_id(&item.item_id) && visited(item))
+            .map(|item| {
+                Ok(BatchAction::AddTag {
+                    item_id: item.id().parse::<usize>()?,
+                    tag: "read".to_string(),
+                })
+            })
+            .collect::<anyhow::Result<Vec<_>>>()?;
+        let outcome = self.pocket_client.send_batch(actions)?;
+        self.handle_batch_outcome(items, outcome);
+        Ok(())
+    }
+
+    /// Successful actions of a bulk request are applied locally, failed ones go to the retry popup
+    fn handle_batch_outcome(&mut self, items: &mut Vec<PocketItem>, outcome: BatchOutcome) {
+        apply_batch_locally(items, &outcome.succeeded);
+        for action in &outcome.succeeded {
+            if matches!(
+                action,
+                BatchAction::Archive { .. } | BatchAction::Delete { .. }
+            ) {
+                let _ = self.reading_queue.remove(&action.item_id().to_string());
+            }
+        }
+        if !outcome.failed.is_empty() {
+            for (action, err) in &outcome.failed {
+                error!(
+                    "Failed to {} item {}: {}",
+                    action.describe(),
+                    action.item_id(),
+                    err
+                );
+            }
+            let failed = outcome
+                .failed
+                .into_iter()
+                .map(|(action, err)| {
+                    let title = items
+                        .iter()
+                        .find(|item| item.item_id == action.item_id().to_string())
+                        .map_or_else(
+                            || action.item_id().to_string(),
+                            |item| item.title().to_string(),
+                        );
+                    FailedAction {
+                        action,
+                        title,
+                        error: err,
                     }
-                    Char('?') => app.show_help_popup()?,
-                    _ => {}
+                })
+                .collect();
+            self.failed_actions_popup_state = Some(FailedActionsPopupState { failed });
+        }
+    }
+
+    /// The popup stays open when the request doesn't go through, so the retry can be repeated
+    fn retry_failed_actions(&mut self) {
+        let Some(popup_state) = &self.failed_actions_popup_state else {
+            return;
+        };
+        let actions = popup_state
+            .failed
+            .iter()
+            .map(|failed| failed.action.clone())
+            .collect();
+        match self.pocket_client.send_batch(actions) {
+            Ok(outcome) => {
+                self.failed_actions_popup_state = None;
+                let mut items = std::mem::take(&mut self.items.items);
+                self.handle_batch_outcome(&mut items, outcome);
+                self.items.items = items;
+                self.apply_filter();
+            }
+            Err(e) => {
+                error!("Failed to retry: {:#}", e);
+                self.toasts.error(format!("Failed to retry: {:#}", e));
+            }
+        }
+    }
+
+    /// Queued items go first in the queue order, the rest of the list stays sorted by date
+    fn order_by_reading_queue(&mut self) {
+        let queue = &self.reading_queue;
+        self.items.items.sort_by(|a, b| {
+            let a_pos = queue.position(&a.item_id).unwrap_or(usize::MAX);
+            let b_pos = queue.position(&b.item_id).unwrap_or(usize::MAX);
+            a_pos
+                .cmp(&b_pos)
+                .then(b.time_added.partial_cmp(&a.time_added).unwrap())
+        });
+    }
+
+    fn reorder_and_select(&mut self, item_id: &str) {
+        let offset = self.virtual_state.offset();
+        self.order_by_reading_queue();
+        self.apply_filter();
+        if let Some(idx) = self.items.iter().position(|item| item.item_id == item_id) {
+            self.virtual_state.select(Some(idx));
+            *self.virtual_state.offset_mut() = offset.min(idx);
+        }
+    }
+
+    fn toggle_reading_queue(&mut self) -> anyhow::Result<()> {
+        if let Some(idx) = self.virtual_state.selected() {
+            if let Some(item_id) = self.items.get(idx).map(|item| item.item_id.clone()) {
+                self.reading_queue.toggle(&item_id)?;
+                self.reorder_and_select(&item_id);
+            }
+        }
+        Ok(())
+    }
+
+    fn move_in_reading_queue(&mut self, delta: isize) -> anyhow::Result<()> {
+        if let Some(idx) = self.virtual_state.selected() {
+            if let Some(item_id) = self.items.get(idx).map(|item| item.item_id.clone()) {
+                if self.reading_queue.move_item(&item_id, delta)? {
+                    self.reorder_and_select(&item_id);
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Tags are counted within the type, site and date filters, not the search or the tag filter
+    fn show_tag_popup(&mut self) {
+        let scope = self.scope_filter();
+        let unread: Vec<&PocketItem> = self
+            .items
+            .items
+            .iter()
+            .filter(|item| scope.matches(item))
+            .filter(|item| {
+                !item.tags().any(|tag| tag == "read") // Exclude read items
+                                                      // item.favorite != "1" // Exclude favorited items
+            })
+            .collect();
+        // an item tagged `lang/rust` and `lang/go` counts once for `lang`
+        let mut tag_counts: Vec<(String, usize)> = unread
+            .iter()
+            .flat_map(|item| {
+                item.tags()
+                    .flat_map(|tag| tagtree::with_parents(tag))
+                    .map(String::from)
+                    .collect::<std::collections::HashSet<_>>()
+            })
+            .fold(std::collections::HashMap::new(), |mut acc, tag| {
+                *acc.entry(tag).or_insert(0) += 1;
+                acc
+            })
+            .into_iter()
+            .collect();
+        let untagged = unread
+            .iter()
+            .filter(|item| item.tags().next().is_none())
+            .count();
+        if untagged > 0 {
+            tag_counts.push((UNTAGGED.to_string(), untagged));
+        }
+
+        let visible_items = 26; // Adjust this value based on your UI
+        let mut popup = TagPopupState::new(tag_counts, visible_items);
+        popup.fuzzy = self.config.fuzzy_search;
+        self.tag_popup_state = Some(popup);
+        self.tag_selection_mode = TagSelectionMode::Normal;
+    }
+
+    fn show_domain_stats(&mut self) {
+        // Create a hashmap to store domain/author counts
+        let mut counts = std::collections::HashMap::new();
+
+        // Count domains/authors for each item
+        for source in self.items.iter().filter_map(SourceFilter::of) {
+            *counts.entry(source).or_insert(0) += 1;
+        }
+
+        // Convert to vector and sort by count (descending)
+        let mut stats: Vec<(SourceFilter, usize)> = counts.into_iter().collect();
+        stats.sort_by(|a, b| b.1.cmp(&a.1).then(a.0.label().cmp(&b.0.label())));
+
+        // Take top 20
+        stats.truncate(40);
+
+        let visible_items = 23; //todo: this needs to be figoured out based on popup size.
+        self.domain_stats_popup_state = Some(DomainStatsPopupState::new(stats, visible_items));
+    }
+
+    fn search_matches(item: &PocketItem, query: &SearchQuery, pdf_index: &PdfIndex) -> bool {
+        let text = query.text.as_str();
+        let text_matches = text.is_empty()
+            || if query.fuzzy {
+                Self::search_score(item, text).is_some()
+            } else {
+                item.title().to_lowercase().contains(text) || item.url().contains(text)
+            }
+            || pdf_index.matches(&item.item_id, text);
+        text_matches
+            && query.tags.iter().all(|tag| {
+                item.tags()
+                    .any(|item_tag| item_tag.to_lowercase().contains(tag.as_str()))
+            })
+            && query.domains.iter().all(|domain| {
+                Self::extract_domain(item.url())
+                    .is_some_and(|item_domain| item_domain.contains(domain.as_str()))
+            })
+    }
+
+    /// Fuzzy score of the better of title and url
+    fn search_score(item: &PocketItem, text: &str) -> Option<i64> {
+        fuzzy::score(text, item.title()).max(fuzzy::score(text, item.url()))
+    }
+
+    /// Parsed search with the matching set in the config
+    fn search_query(&self, search: &str) -> SearchQuery {
+        let mut query = SearchQuery::parse(&search.to_lowercase());
+        query.fuzzy = self.config.fuzzy_search;
+        query
+    }
+
+    /// Best fuzzy matches first, items found only in PDFs last
+    fn rank_search_results(&mut self, query: &SearchQuery) {
+        if query.is_ranked() {
+            self.items.sort_filtered_by_key(|item| {
+                std::cmp::Reverse(Self::search_score(item, &query.text))
+            });
+        }
+    }
+
+    /// Completes the value of the `tag:` or `domain:` operator at the end of the search
+    fn search_completions(&self, search: &str) -> Completions {
+        let start = completion::word_start(search, search.len(), &[' ']);
+        let word = &search[start..];
+        if word.starts_with(TAG_OPERATOR) {
+            Completions::new(
+                search,
+                start + TAG_OPERATOR.len(),
+                search.len(),
+                &self.cached_tags,
+            )
+        } else if word.starts_with(DOMAIN_OPERATOR) {
+            let domains: Vec<String> = self
+                .items
+                .items
+                .iter()
+                .filter_map(|item| Self::extract_domain(item.url()))
+                .unique()
+                .collect();
+            Completions::new(
+                search,
+                start + DOMAIN_OPERATOR.len(),
+                search.len(),
+                &domains,
+            )
+        } else {
+            Completions::default()
+        }
+    }
+
+    pub fn apply_filter(&mut self) {
+        let query = self
+            .active_search_filter
+            .as_ref()
+            .map(|f| self.search_query(f));
+        let scope = self.scope_filter();
+        self.items.apply_filter(|item| {
+            let title_matches = match &query {
+                Some(query) => Self::search_matches(item, query, &self.pdf_index),
+                None => true,
+            };
+
+            let tag_matches = match &self.selected_tag_filter {
+                Some(tag) if tag == UNTAGGED => item.tags().next().is_none(),
+                Some(tag) => item.tags().any(|t| tagtree::is_within(t, tag)),
+                None => true,
+            };
+
+            title_matches && tag_matches && scope.matches(item)
+        });
+        if let Some(query) = &query {
+            self.rank_search_results(query);
+        }
+        self.view_summary = ViewSummary::of(self.items.iter());
+        self.regroup();
+        self.virtual_state.select(Some(0));
+        *self.virtual_state.offset_mut() = 0;
+    }
+
+    fn scope_filter(&self) -> ScopeFilter {
+        ScopeFilter {
+            item_type: self.item_type_filter.clone(),
+            source: self.source_filter.clone(),
+            date_range: self.date_range.map(|(from, to)| {
+                (
+                    from.format("%Y-%m-%d").to_string(),
+                    to.format("%Y-%m-%d").to_string(),
+                )
+            }),
+        }
+    }
+
+    /// Puts the shown items back into groups after the filter changed
+    fn regroup(&mut self) {
+        let group_by = self.group_by;
+        if group_by == GroupBy::Date {
+            self.group_sizes.clear();
+            return;
+        }
+        let collapsed = &self.collapsed_groups;
+        self.group_sizes = self
+            .items
+            .group(|item| group_by.key(item), |key| collapsed.contains(key));
+    }
+
+    fn cycle_grouping(&mut self) {
+        self.group_by = self.group_by.next();
+        self.collapsed_groups.clear();
+        self.apply_filter();
+    }
+
+    /// Collapses the group of the selected item into its first row, or expands it back
+    fn toggle_group_collapsed(&mut self) {
+        if self.group_by == GroupBy::Date {
+            return;
+        }
+        let Some(key) = self
+            .virtual_state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+            .map(|item| self.group_by.key(item))
+        else {
+            return;
+        };
+        if !self.collapsed_groups.remove(&key) {
+            self.collapsed_groups.insert(key.clone());
+        }
+        let offset = self.virtual_state.offset();
+        self.apply_filter();
+        let group_by = self.group_by;
+        if let Some(idx) = self.items.iter().position(|item| group_by.key(item) == key) {
+            self.virtual_state.select(Some(idx));
+            *self.virtual_state.offset_mut() = offset.min(idx);
+        }
+    }
+
+    fn show_doc_type_popup(&mut self) {
+        self.doc_type_popup_state = Some(DocTypePopupState::new());
+    }
+
+    fn select_doc_type(&mut self, filter: ItemTypeFilter) {
+        self.doc_type_popup_state = None;
+        if self.item_type_filter != filter {
+            self.item_type_filter = filter;
+            self.apply_filter();
+        }
+    }
+
+    fn set_item_type_filter(&mut self, filter: ItemTypeFilter) {
+        self.item_type_filter = filter;
+        self.apply_filter();
+    }
+
+    fn select_tag(&mut self) {
+        if let Some(tag_popup_state) = &self.tag_popup_state {
+            if let Some((selected_tag, _)) = tag_popup_state
+                .filtered_tags
+                .get(tag_popup_state.selected_index)
+            {
+                self.selected_tag_filter = Some(selected_tag.clone());
+                self.tag_popup_state = None;
+                self.apply_filter();
+            }
+        }
+    }
+
+    fn clear_tag_filter(&mut self) {
+        self.selected_tag_filter = None;
+        self.apply_filter();
+    }
+
+    /// Filters by the typed query. When it only got longer, the current matches are all that
+    /// can still match, so only they are checked
+    fn update_search_filter(&mut self, search: &mut SearchMode) {
+        let filter_lower = search.search.to_lowercase();
+        let query = self.search_query(&search.search);
+        // a word turning into an operator doesn't narrow the search, and narrowing a grouped
+        // list would lose the items hidden in collapsed groups
+        let narrowing = !search.applied.is_empty()
+            && filter_lower.contains(&search.applied.to_lowercase())
+            && query.operators() == SearchQuery::parse(&search.applied.to_lowercase()).operators()
+            && self.group_by == GroupBy::Date;
+        self.active_search_filter = Some(search.search.clone());
+        if narrowing {
+            self.items
+                .narrow_filter(|item| Self::search_matches(item, &query, &self.pdf_index));
+            self.rank_search_results(&query);
+            self.virtual_state.select(Some(0));
+            *self.virtual_state.offset_mut() = 0;
+        } else {
+            self.apply_filter();
+        }
+        search.applied = search.search.clone();
+        search.typed_at = None;
+    }
+
+    /// Text of the search highlighted in titles, operators left out
+    fn highlight_term(&self) -> Option<String> {
+        let search = match &self.app_mode {
+            AppMode::Search(search) => Some(&search.search),
+            _ => self
+                .search_navigation
+                .as_ref()
+                .or(self.active_search_filter.as_ref()),
+        }?;
+        let text = SearchQuery::parse(&search.to_lowercase()).text;
+        (!text.trim().is_empty()).then_some(text)
+    }
+
+    /// Selects the next item matching `search` after the selected one, or the previous one going
+    /// back, wrapping around the list. With `include_selected` the selected item counts too
+    fn jump_to_match(&mut self, search: &str, forward: bool, include_selected: bool) -> bool {
+        let len = self.items.len();
+        if len == 0 {
+            return false;
+        }
+        let query = self.search_query(search);
+        let selected = self.virtual_state.selected().unwrap_or(0).min(len - 1);
+        let skip = usize::from(!include_selected);
+        let found = (skip..len + skip)
+            .map(|step| {
+                if forward {
+                    (selected + step) % len
+                } else {
+                    (selected + len - step % len) % len
+                }
+            })
+            .find(|index| {
+                self.items
+                    .get(*index)
+                    .is_some_and(|item| Self::search_matches(item, &query, &self.pdf_index))
+            });
+        if let Some(index) = found {
+            self.select_item(index);
+        }
+        found.is_some()
+    }
+
+    /// `n`/`N` in the list
+    fn jump_to_next_match(&mut self, forward: bool) {
+        if let Some(search) = self.search_navigation.clone() {
+            if !self.jump_to_match(&search, forward, false) {
+                self.toasts.error(format!("No matches for {}", search));
+            }
+        }
+    }
+
+    /// While typing a navigation search the selection moves to the first match from where the
+    /// search started
+    fn update_search_navigation(&mut self, search: &mut SearchMode) {
+        let (offset, selected) = search.normal_mode_positions;
+        *self.virtual_state.offset_mut() = offset.min(selected);
+        self.select_item(selected.min(self.items.len().saturating_sub(1)));
+        self.jump_to_match(&search.search, true, true);
+        search.typed_at = None;
+    }
+
+    fn set_search_filter(&mut self, filter: String) {
+        self.active_search_filter = Some(filter);
+        self.apply_filter();
+        self.advance_tutorial(TutorialAction::Search);
+    }
+
+    fn clear_search_filter(&mut self) {
+        self.active_search_filter = None;
+        self.apply_filter();
+    }
+
+    fn clear_all_filters(&mut self) {
+        self.active_search_filter = None;
+        self.selected_tag_filter = None;
+        self.source_filter = None;
+        self.date_range = None;
+        self.items.clear_filter();
+        self.view_summary = ViewSummary::of(self.items.iter());
+        self.regroup();
+    }
+
+    fn extract_domain(url: &str) -> Option<String> {
+        let url = url
+            .trim_start_matches("http://")
+            .trim_start_matches("https://")
+            .trim_start_matches("www.");
+
+        url.split('/').next().map(|s| s.to_string())
+    }
+
+    fn filter_by_current_domain(&mut self) -> anyhow::Result<()> {
+        let source = self
+            .virtual_state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+            .and_then(SourceFilter::of);
+        if source.is_some() {
+            self.source_filter = source;
+            self.apply_filter();
+        }
+        Ok(())
+    }
+
+    fn clear_source_filter(&mut self) {
+        self.source_filter = None;
+        self.apply_filter();
+    }
+
+    fn open_date_picker(&mut self, purpose: DatePickerPurpose) {
+        let today = timezone::date_of(Utc::now().timestamp());
+        self.app_mode = AppMode::DatePicker(DatePicker::new(
+            purpose,
+            today,
+            self.config.week_start.into(),
+        ));
+    }
+
+    fn set_date_range(&mut self, from: NaiveDate, to: NaiveDate) {
+        self.date_range = Some((from, to));
+        self.apply_filter();
+    }
+
+    fn clear_date_range(&mut self) {
+        self.date_range = None;
+        self.apply_filter();
+    }
+    pub fn next(&mut self) {
+        let i = match self.virtual_state.selected() {
+            Some(i) => {
+                if i < self.items.len() - 1 {
+                    i + 1
+                } else {
+                    self.items.len() - 1
+                }
+            }
+            None => 0,
+        };
+        self.virtual_state.select(Some(i));
+        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+        self.advance_tutorial(TutorialAction::Navigate);
+    }
+
+    pub fn previous(&mut self) {
+        let i = match self.virtual_state.selected() {
+            Some(i) => {
+                if i > 0 {
+                    i - 1
+                } else {
+                    0
+                }
+            }
+            None => 0,
+        };
+        self.virtual_state.select(Some(i));
+        if i < self.virtual_state.offset() {
+            *self.virtual_state.offset_mut() = i
+        }
+        self.scroll_state = self.scroll_state.position(i * ITEM_HEIGHT);
+        self.advance_tutorial(TutorialAction::Navigate);
+    }
+
+    pub fn set_colors(&mut self) {
+        self.colors = TableColors::new(&PALETTES[self.color_index]);
+    }
+
+    fn open_current_url(&mut self) -> anyhow::Result<()> {
+        if let Some(idx) = self.virtual_state.selected() {
+            if let Some(item) = self.items.get_mut(idx) {
+                // browsing stays possible in read-only mode, the item just isn't marked as read
+                if !self.pocket_client.is_read_only() {
+                    let local = storage::is_local_id(&item.item_id);
+                    if !local {
+                        self.pocket_client
+                            .mark_as_read(item.id().parse::<usize>()?)?;
+                    }
+                    if item.tags().all(|tag| tag != "read") && !self.pocket_client.is_dry_run() {
+                        let event = readingstats::ReadEvent {
+                            item_id: item.item_id.clone(),
+                            item_type: item.item_type().to_string(),
+                            timestamp: Utc::now().timestamp(),
+                        };
+                        readingstats::record_read(
+                            Path::new(readingstats::READ_EVENTS_FILE),
+                            &event,
+                        )?;
+                        self.stats
+                            .track_type(&event.item_type, &Utc::now(), true, event.timestamp);
+                    }
+                    item.add_tag("read");
+                    if local && !self.pocket_client.is_dry_run() {
+                        storage::append_local_item(Path::new(DELTA_FILE), item)?;
+                    }
+                }
+                webbrowser::open(&item.url()).context("Failed to open link in a browser")?;
+            }
+        }
+        Ok(())
+    }
+
+    //todo: usize conversion is dumb
+    fn delete_article(&mut self) -> anyhow::Result<()> {
+        if let Some(idx) = self.virtual_state.selected() {
+            if let Some(item) = self.items.get(idx) {
+                // local items only live in the delta, the delete line below is all they need
+                if !storage::is_local_id(&item.item_id) {
+                    self.pocket_client.delete(item.id().parse::<usize>()?)?;
+                }
+
+                // Log the deletion in the storage.delta
+                let delta_record = storage::PocketItemUpdate::Delete {
+                    item_id: item.id(),
+                    timestamp: Some(Utc::now().timestamp().try_into().unwrap()),
+                };
+                // the delta and the reading queue are updated together or not at all
+                let mut transaction = Transaction::new();
+                // this is needed to enrich delete event with timestamp. looks like pocket api erases this info
+                if !self.pocket_client.is_dry_run() {
+                    transaction.append(DELTA_FILE, storage::delete_delta_line(&delta_record)?);
+                }
+                self.reading_queue
+                    .stage_remove(&item.item_id, &mut transaction);
+                transaction.commit()?;
+            }
+            self.items.remove(idx);
+        }
+        Ok(())
+    }
+
+    /// Outcome of an action on the list as a toast, a failed action doesn't stop the app
+    fn report(&mut self, result: anyhow::Result<()>, done: &str, failed: &str) {
+        match result {
+            Ok(()) => self.toasts.success(done),
+            Err(e) => {
+                error!("{}: {:#}", failed, e);
+                self.toasts.error(format!("{}: {:#}", failed, e));
+            }
+        }
+    }
+
+    fn toggle_top_tag(&mut self) -> anyhow::Result<()> {
+        if let Some(idx) = self.virtual_state.selected() {
+            if let Some(item) = self.items.get_mut(idx) {
+                if storage::is_local_id(&item.item_id) {
+                    if item.tags().any(|x| x == "top") {
+                        item.remove_tag("top");
+                    } else {
+                        item.add_tag("top");
+                    }
+                    if !self.pocket_client.is_dry_run() {
+                        storage::append_local_item(Path::new(DELTA_FILE), item)?;
+                    }
+                    return Ok(());
+                }
+                if !item.tags().any(|x| x == "top") {
+                    self.pocket_client
+                        .mark_as_top(item.id().parse::<usize>()?)?;
+                    item.add_tag("top");
+                } else {
+                    self.pocket_client
+                        .unmark_as_top(item.id().parse::<usize>()?)?;
+                    item.remove_tag("top");
+                }
+                self.local_edits.record(item);
+            }
+        }
+        Ok(())
+    }
+
+    fn fav_and_archive_article(&mut self) -> anyhow::Result<()> {
+        if let Some(idx) = self.virtual_state.selected() {
+            if let Some(item) = self.items.get_mut(idx) {
+                if storage::is_local_id(&item.item_id) {
+                    item.favorite = "1".to_string();
+                    item.status = "1".to_string();
+                    item.time_favorited = Utc::now().timestamp().to_string();
+                    if !self.pocket_client.is_dry_run() {
+                        storage::append_local_item(Path::new(DELTA_FILE), item)?;
+                    }
+                } else {
+                    self.pocket_client
+                        .fav_and_archive(item.id().parse::<usize>()?)?;
+                }
+                self.reading_queue.remove(&item.item_id)?;
+            }
+            self.items.remove(idx);
+            self.advance_tutorial(TutorialAction::Archive);
+        }
+        Ok(())
+    }
+
+    fn switch_to_search_mode(&mut self) {
+        self.app_mode = AppMode::Search(SearchMode::new((
+            self.virtual_state.offset(),
+            self.virtual_state.selected().unwrap(),
+        )));
+    }
+
+    fn switch_to_confirmation(&mut self, confirm_type: Confirmation) {
+        self.app_mode = AppMode::Confirmation(confirm_type)
+    }
+
+    /// Popup that receives the input, same order as in `process_input_normal_mode`
+    fn active_popup(&self) -> Option<PopupKind> {
+        if self.doc_type_popup_state.is_some() {
+            Some(PopupKind::DocType)
+        } else if self.tag_popup_state.is_some() {
+            Some(PopupKind::Tags)
+        } else if self.domain_stats_popup_state.is_some() {
+            Some(PopupKind::DomainStats)
+        } else if self.authors_popup_state.is_some() {
+            Some(PopupKind::Authors)
+        } else if self.failed_actions_popup_state.is_some() {
+            Some(PopupKind::FailedActions)
+        } else if self.downloads_popup_state.is_some() {
+            Some(PopupKind::Downloads)
+        } else if self.boundary_preview_state.is_some() {
+            Some(PopupKind::BoundaryPreview)
+        } else if self.watch_popup_state.is_some() {
+            Some(PopupKind::Watch)
+        } else if self.favorites_popup_state.is_some() {
+            Some(PopupKind::Favorites)
+        } else if self.archive_popup_state.is_some() {
+            Some(PopupKind::Archive)
+        } else if self.summary_popup_state.is_some() {
+            Some(PopupKind::Summary)
+        } else if self.qr_popup_state.is_some() {
+            Some(PopupKind::Qr)
+        } else if self.share_popup_state.is_some() {
+            Some(PopupKind::Share)
+        } else if self.reader_popup_state.is_some() {
+            Some(PopupKind::Reader)
+        } else if self.logs_popup_state.is_some() {
+            Some(PopupKind::Logs)
+        } else if self.conflicts_popup_state.is_some() {
+            Some(PopupKind::Conflicts)
+        } else if self.sync_diff_popup_state.is_some() {
+            Some(PopupKind::SyncDiff)
+        } else if self.help_popup_state.is_some() {
+            Some(PopupKind::Help)
+        } else {
+            None
+        }
+    }
+
+    fn show_ambient_screen_if_idle(&mut self) {
+        let idle_secs = self.config.ambient_after_secs;
+        if idle_secs > 0 && self.last_input.elapsed() >= Duration::from_secs(idle_secs) {
+            self.app_mode = AppMode::Ambient(ambient::AmbientScreen::new(self.items.items.iter()));
+        }
+    }
+
+    /// Prompts and errors opened from RSS triage return back to it
+    fn switch_to_normal_mode(&mut self) {
+        self.app_mode = if self.rss_triage_state.is_some() {
+            AppMode::RssTriage
+        } else {
+            AppMode::Normal
+        };
+    }
+
+    fn switch_to_normal_mode_from(&mut self, from: AppMode) {
+        self.app_mode = AppMode::Normal;
+        match from {
+            AppMode::Search(x) => {
+                self.apply_filter();
+                *self.virtual_state.offset_mut() = x.normal_mode_positions.0;
+                self.virtual_state.select(Some(x.normal_mode_positions.1));
+            }
+            _ => {} // do nothing
+        }
+    }
+
+    fn scroll_down(&mut self) {
+        let page_size = 13;
+        let i = match self.virtual_state.selected() {
+            Some(i) => {
+                if (i + page_size) > self.items.len() - 1 {
+                    (i + page_size) % self.items.len()
+                } else {
+                    i + page_size
+                }
+            }
+            None => 0,
+        };
+        if self.virtual_state.offset() < self.virtual_state.selected().unwrap_or(0) {
+            *self.virtual_state.offset_mut() = self.virtual_state.selected().unwrap_or(0);
+        } else {
+            self.virtual_state.select(Some(i));
+            *self.virtual_state.offset_mut() = i;
+        }
+    }
+
+    fn scroll_up(&mut self) {
+        let page_size = 13;
+        let i = match self.virtual_state.selected() {
+            Some(i) => {
+                if i > page_size {
+                    i - page_size
+                } else {
+                    0
+                }
+            }
+            None => 0,
+        };
+        if self.virtual_state.offset() < self.virtual_state.selected().unwrap_or(0) {
+            self.virtual_state.select(Some(self.virtual_state.offset()));
+        } else {
+            self.virtual_state.select(Some(i));
+            *self.virtual_state.offset_mut() = i;
+        }
+    }
+
+    fn scroll_to_end(&mut self) {
+        self.virtual_state.select(Some(self.items.len() - 1));
+    }
+
+    fn scroll_to_begining(&mut self) {
+        self.virtual_state.select(Some(0));
+        *self.virtual_state.offset_mut() = 0;
+    }
+
+    fn switch_to_rename_mode(&mut self, with_current_title: bool) {
+        if let Some(idx) = self.virtual_state.selected() {
+            let initial_text = if with_current_title {
+                self.items.get(idx).map_or("".to_string(), |item| {
+                    if item.title().is_empty() {
+                        item.url().to_string()
+                    } else {
+                        item.title().to_string()
+                    }
+                })
+            } else {
+                String::new()
+            };
+
+            self.app_mode = AppMode::CommandEnter(CommandEnterMode::new(
+                "Rename to (control+v to paste): ".to_string(),
+                initial_text.clone(),
+                CommandType::RenameItem,
+            ));
+        }
+    }
+
+    fn switch_to_edit_url_mode(&mut self) {
+        if let Some(item) = self
+            .virtual_state
+            .selected()
+            .and_then(|idx| self.items.get(idx))
+        {
+            self.app_mode = AppMode::CommandEnter(CommandEnterMode::new(
+                "New URL (control+v to paste): ".to_string(),
+                item.url().to_string(),
+                CommandType::EditUrl,
+            ));
+        }
+    }
+
+    /// Pocket can't change the url of an item: it's added again with the new url, title, tags and
+    /// time, and the old one is deleted. The new item replaces the old one in the list
+    fn edit_current_url(&mut self, new_url: &str) -> anyhow::Result<()> {
+        let new_url = new_url.trim();
+        if !reqwest::Url::parse(new_url).is_ok_and(|url| matches!(url.scheme(), "http" | "https")) {
+            anyhow::bail!("Not a web link: {}", new_url);
+        }
+        let Some(idx) = self.virtual_state.selected() else {
+            return Ok(());
+        };
+        let Some(item) = self.items.get(idx) else {
+            return Ok(());
+        };
+        if item.url() == new_url {
+            return Ok(());
+        }
+        if storage::is_local_id(&item.item_id) {
+            anyhow::bail!("Imported items aren't in Pocket, push them with --import --push first");
+        }
+        let old_id = item.item_id.clone();
+        let tags: Vec<String> = item.tags().cloned().collect();
+        let new_id = self.pocket_client.replace_url(
+            old_id.parse::<usize>()?,
+            new_url,
+            item.title(),
+            &tags,
+            &item.time_added,
+        )?;
+
+        let mut transaction = Transaction::new();
+        if !self.pocket_client.is_dry_run() {
+            // without a timestamp the delete isn't counted as a read in the stats
+            let delta_record = storage::PocketItemUpdate::Delete {
+                item_id: old_id.clone(),
+                timestamp: None,
+            };
+            transaction.append(DELTA_FILE, storage::delete_delta_line(&delta_record)?);
+        }
+        if let Some(new_id) = &new_id {
+            self.reading_queue
+                .stage_replace(&old_id, new_id, &mut transaction);
+        }
+        transaction.commit()?;
+        if let Some(new_id) = &new_id {
+            self.title_overrides.move_to(&old_id, new_id)?;
+        }
+
+        if let Some(item) = self
+            .items
+            .items
+            .iter_mut()
+            .find(|item| item.item_id == old_id)
+        {
+            item.resolved_url = Some(new_url.to_string());
+            if let Some(new_id) = new_id {
+                item.item_id = new_id;
+            }
+        }
+        Ok(())
+    }
+
+    fn rename_current_item(&mut self, current_enter: String) -> anyhow::Result<()> {
+        if let Some(idx) = self.virtual_state.selected() {
+            if let Some(item_id) = self.items.get(idx).map(|item| item.item_id.clone()) {
+                self.rename_item(&item_id, current_enter)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn rename_item(&mut self, item_id: &str, new_title: String) -> anyhow::Result<()> {
+        if let Some(item) = self
+            .items
+            .items
+            .iter_mut()
+            .find(|item| item.item_id == item_id)
+        {
+            let normalized_title = new_title.replace('\n', " ").trim().to_string();
+            self.title_overrides.set(&item.item_id, &normalized_title)?;
+            item.rename_title_to(normalized_title);
+            self.local_edits.record(item);
+        }
+        Ok(())
+    }
+
+    fn jump_to_date(&mut self, current_enter: String) -> anyhow::Result<()> {
+        match self.items.iter().enumerate().find(|(_, data)| {
+            self.reading_queue.position(&data.item_id).is_none() && data.date() <= current_enter
+        }) {
+            Some((idx, _)) => {
+                self.virtual_state.select(Some(idx));
+                *self.virtual_state.offset_mut() = idx;
+                self.scroll_state = self.scroll_state.position(idx * ITEM_HEIGHT);
+            }
+            None => {} /*do nothing*/
+        }
+        Ok(())
+    }
+
+    /// Index of the item shown at a screen row, none outside of the rows drawn last time
+    fn item_at_row(&self, row: u16) -> Option<usize> {
+        self.table_layout
+            .item_at(row)
+            .filter(|index| *index < self.items.len())
+    }
+
+    fn start_type_ahead(&mut self) {
+        self.app_mode = AppMode::TypeAhead(TypeAhead {
+            prefix: String::new(),
+            started_at: self.virtual_state.selected().unwrap_or(0),
+            found: true,
+        });
+    }
+
+    /// Selects the next title matching `prefix` after the item at `after`
+    fn jump_to_title(&mut self, prefix: &str, after: usize) -> bool {
+        let found = type_ahead_match(prefix, self.items.len(), after + 1, |index| {
+            self.items.get(index).map_or("", |item| item.title())
+        });
+        if let Some(index) = found {
+            self.select_item(index);
+        }
+        found.is_some()
+    }
+
+    fn select_item(&mut self, index: usize) {
+        // the table only scrolls down by itself
+        if index < self.virtual_state.offset() {
+            *self.virtual_state.offset_mut() = index;
+        }
+        self.virtual_state.select(Some(index));
+        self.scroll_state = self.scroll_state.position(index * ITEM_HEIGHT);
+    }
+
+    fn run_context_action(&mut self, action: ContextAction) -> anyhow::Result<()> {
+        match action {
+            ContextAction::Open => self.open_current_url()?,
+            ContextAction::Delete => self.switch_to_confirmation(Confirmation::DeletePocketItem),
+            ContextAction::Tag => self.switch_to_edit_tags_mode(),
+            ContextAction::Download => {
+                if let Err(e) = self.queue_current_download() {
+                    self.app_mode = AppMode::Error(e.to_string());
+                }
+            }
+            ContextAction::FilterByDomain => self.filter_by_current_domain()?,
+        }
+        Ok(())
+    }
+
+    /// A click outside of the open menu closes it, a right click opens it again at the new row
+    fn handle_context_menu_mouse(&mut self, mouse_event: MouseEvent) -> anyhow::Result<bool> {
+        let Some(menu) = &self.context_menu_state else {
+            return Ok(false);
+        };
+        let (width, height) = crossterm::terminal::size()?;
+        let frame = Rect::new(0, 0, width, height);
+        match mouse_event.kind {
+            MouseEventKind::Down(event::MouseButton::Left) => {
+                let action = menu.action_at(frame, mouse_event.column, mouse_event.row);
+                self.context_menu_state = None;
+                if let Some(action) = action {
+                    self.run_context_action(action)?;
+                }
+                Ok(true)
+            }
+            MouseEventKind::Down(event::MouseButton::Right) => {
+                self.context_menu_state = None;
+                Ok(false)
+            }
+            _ => Ok(true),
+        }
+    }
+
+    fn click_footer_chip(&mut self, chip: FooterChip) -> anyhow::Result<()> {
+        match chip {
+            FooterChip::Search => self.clear_search_filter(),
+            FooterChip::Navigation => self.search_navigation = None,
+            FooterChip::Tag => self.clear_tag_filter(),
+            FooterChip::Domain => self.clear_source_filter(),
+            FooterChip::DateRange => self.clear_date_range(),
+            FooterChip::DocType => self.set_item_type_filter(ItemTypeFilter::All),
+            FooterChip::Rss => self.open_rss_triage()?,
+        }
+        Ok(())
+    }
+
+    /// Selects the item at the same fraction of the list as `row` is of the scrollbar track
+    fn scroll_to_scrollbar_row(&mut self, row: u16) {
+        let track = self.scrollbar_area;
+        if self.items.len() == 0 || track.height == 0 {
+            return;
+        }
+        let offset = row.clamp(track.y, track.bottom() - 1) - track.y;
+        let last = self.items.len() - 1;
+        let index = (offset as usize * last + (track.height as usize - 1) / 2)
+            / (track.height as usize - 1).max(1);
+        self.select_item(index.min(last));
+    }
+
+    fn handle_mouse_event(&mut self, mouse_event: MouseEvent) -> anyhow::Result<()> {
+        if self.handle_context_menu_mouse(mouse_event)? {
+            return Ok(());
+        }
+        let position = Position::new(mouse_event.column, mouse_event.row);
+        match mouse_event.kind {
+            MouseEventKind::Down(event::MouseButton::Left)
+                if self.scrollbar_area.contains(position) =>
+            {
+                self.dragging_scrollbar = true;
+                self.scroll_to_scrollbar_row(mouse_event.row);
+            }
+            MouseEventKind::Drag(event::MouseButton::Left) if self.dragging_scrollbar => {
+                self.scroll_to_scrollbar_row(mouse_event.row);
+            }
+            MouseEventKind::Up(event::MouseButton::Left) => self.dragging_scrollbar = false,
+            MouseEventKind::Down(event::MouseButton::Left) => {
+                let current_time = std::time::Instant::now();
+                let current_position = (mouse_event.column, mouse_event.row);
+
+                if let (Some(last_time), Some(last_position)) =
+                    (self.last_click_time, self.last_click_position)
+                {
+                    if current_time.duration_since(last_time) < Duration::from_millis(500)
+                        && current_position == last_position
+                    {
+                        // Double click detected
+                        self.open_current_url()?;
+                    }
+                }
+
+                self.last_click_time = Some(current_time);
+                self.last_click_position = Some(current_position);
+
+                if let Some(&(_, chip)) = self
+                    .footer_chips
+                    .iter()
+                    .find(|(area, _)| area.contains(position))
+                {
+                    return self.click_footer_chip(chip);
+                }
+                // the footer shows how fresh the data is, clicking it refreshes
+                let (_, height) = crossterm::terminal::size()?;
+                if mouse_event.row >= height.saturating_sub(FOOTER_HEIGHT) {
+                    return self.refresh_all();
+                }
+                if let Some(index) = self.item_at_row(mouse_event.row) {
+                    self.select_item(index);
+                }
+            }
+            // the menu is for the list only, not for the search input
+            MouseEventKind::Down(event::MouseButton::Right)
+                if matches!(self.app_mode, AppMode::Normal) =>
+            {
+                if let Some(index) = self.item_at_row(mouse_event.row) {
+                    self.select_item(index);
+                    self.context_menu_state = Some(ContextMenuState {
+                        column: mouse_event.column,
+                        row: mouse_event.row,
+                        selected_index: 0,
+                    });
+                }
+            }
+            MouseEventKind::ScrollDown => self.scroll(0.2),
+            MouseEventKind::ScrollUp => self.scroll(-0.2),
+            _ => {}
+        }
+        Ok(())
+    }
+    fn scroll(&mut self, delta: f32) {
+        self.scroll_accumulator += delta;
+
+        while self.scroll_accumulator >= 1.0 {
+            // self.next();
+            self.mousescroll_down();
+            self.scroll_accumulator -= 1.0;
+        }
+
+        while self.scroll_accumulator <= -1.0 {
+            // self.previous();
+            self.mousescroll_up();
+            self.scroll_accumulator += 1.0;
+        }
+    }
+
+    fn mousescroll_down(&mut self) {
+        let new_index = self
+            .virtual_state
+            .selected()
+            .map(|i| (i + SCROLL_STEP).min(self.items.len() - 1))
+            .unwrap_or(0);
+        self.virtual_state.select(Some(new_index));
+        self.scroll_state = self.scroll_state.position(new_index * ITEM_HEIGHT);
+    }
+
+    fn mousescroll_up(&mut self) {
+        let new_index = self
+            .virtual_state
+            .selected()
+            .map(|i| i.saturating_sub(SCROLL_STEP))
+            .unwrap_or(0);
+        self.virtual_state.select(Some(new_index));
+        self.scroll_state = self.scroll_state.position(new_index * ITEM_HEIGHT);
+    }
+}
+
+fn apply_batch_locally(items: &mut Vec<PocketItem>, actions: &[BatchAction]) {
+    for action in actions {
+        let item_id = action.item_id().to_string();
+        match action {
+            BatchAction::AddTag { tag, .. } | BatchAction::RemoveTag { tag, .. } => {
+                if let Some(item) = items.iter_mut().find(|item| item.item_id == item_id) {
+                    if matches!(action, BatchAction::AddTag { .. }) {
+                        item.add_tag(tag);
+                    } else {
+                        item.remove_tag(tag);
+                    }
+                }
+            }
+            BatchAction::Archive { .. } | BatchAction::Delete { .. } => {
+                items.retain(|item| item.item_id != item_id)
+            }
+        }
+    }
+}
+
+fn reload_data(
+    delta_file: &Path,
+    pocket_client: &GetPocketSync,
+    stats: &mut TotalStats,
+) -> anyhow::Result<Vec<PocketItem>> {
+    pocket_client
+        .refresh_delta_block(&delta_file)
+        .context("failed to refresh delta during refresh")?;
+    load_local_items(delta_file, stats)
+}
+
+/// Snapshot with the delta applied, newest first, without asking Pocket for updates
+fn load_local_items(delta_file: &Path, stats: &mut TotalStats) -> anyhow::Result<Vec<PocketItem>> {
+    // Load and process delta updates
+    let delta_items = storage::load_delta_pocket_items(&delta_file);
+    // items with a logged read are counted when they were opened, not when deleted or favorited
+    let read_events = readingstats::load_read_events(Path::new(readingstats::READ_EVENTS_FILE));
+    let mut seen_item_ids: std::collections::HashSet<String> =
+        read_events.keys().cloned().collect();
+    let today = Utc::now();
+    for event in read_events.values() {
+        stats.track_type(&event.item_type, &today, true, event.timestamp);
+    }
+
+    let mut current_items = storage::load_snapshot_items()?;
+
+    // Process each delta update
+    for update in delta_items {
+        match update {
+            PocketItemUpdate::Delete {
+                item_id,
+                timestamp: ts_opt,
+            } => {
+                if let Some(ts) = ts_opt {
+                    if let Some(item) = current_items.get(&item_id) {
+                        if !seen_item_ids.contains(&item_id) {
+                            stats.track_as(item, &today, true, ts as i64);
+                            seen_item_ids.insert(item_id.clone());
+                        }
+                    }
+                }
+                current_items.remove(&item_id);
+            }
+            PocketItemUpdate::Add {
+                item_id: id,
+                data: mut new_item,
+            } => {
+                if let Some(existing) = current_items.get(&id) {
+                    // Update existing item
+                    new_item.time_added = existing.time_added().to_string();
+                    let ts: i64 = new_item.time_updated.parse::<i64>().unwrap_or(0);
+                    if new_item.favorite == "1" && !seen_item_ids.contains(&id) {
+                        stats.track_as(existing, &today, true, ts);
+                        seen_item_ids.insert(id.clone());
+                    }
+                    current_items.insert(id, new_item.into()); // Assuming T can be created from PocketItem
+                } else {
+                    // Add new item
+                    stats.track_item(&new_item, &today);
+                    current_items.insert(id, new_item.into());
+                }
+            }
+        }
+    }
+
+    // Convert back to a sorted vector
+    let items: Vec<PocketItem> = current_items
+        .into_values()
+        .filter(|a| a.tags().all(|tag| tag != "favorite")) // Skip favorited items
+        .sorted_by(|a, b| b.time_added.partial_cmp(&a.time_added).unwrap())
+        .collect();
+
+    return Ok(items);
+}
+
+fn main() -> Result<(), Box<dyn Error>> {
+    let args: Vec<String> = std::env::args().collect();
+    if let Some(pos) = args.iter().position(|arg| arg == "--remote") {
+        // talks to the running instance, must not touch its log or terminal
+        remote::send(&remote::parse_args(&args[pos + 1..])?)?;
+        return Ok(());
+    }
+
+    let startup_marker = Path::new(safemode::STARTUP_MARKER_FILE);
+    let unclean_starts = safemode::register_startup(startup_marker)?;
+    let safe_mode = safemode::should_start_in_safe_mode(unclean_starts)
+        || std::env::args().any(|arg| arg == "--safe-mode");
+
+    // tutorial runs on sample data and doesn't need a Pocket account
+    let tutorial_mode = std::env::args().any(|arg| arg == "--tutorial");
+    // generated items for screenshots and trying the UI, nothing is sent anywhere
+    let demo_mode = std::env::args().any(|arg| arg == "--demo");
+    let sample_data = tutorial_mode || demo_mode;
+    // browsing, searching and opening work as usual, changes to the account are refused
+    let read_only = std::env::args().any(|arg| arg == "--read-only");
+
+    if !sample_data && transaction::recover()? {
+        println!("Rolled back local files updated by an interrupted session");
+    }
+
+    // safe mode ignores user configuration, it might be the reason of the crash
+    let config = if !sample_data && !safe_mode {
+        config::Config::load(Path::new(config::CONFIG_FILE))?
+    } else {
+        config::Config::default()
+    };
+    // the auth and Pocket clients below already go through the proxy
+    network::init(&config.network)?;
+
+    let pocket_client = if sample_data {
+        GetPocketSync::new_dry_run()?
+    } else {
+        let token_opt = tokenstorage::UserTokenStorage::get_token()?;
+        let token = if let Some(t) = token_opt {
+            t
+        } else {
+            println!("Auth information is not found. Starting authentication procedure...");
+            thread::sleep(Duration::from_secs(4));
+            let pocket_auth = auth::PocketAuth::new()?;
+            let auth_token = pocket_auth.authenticate()?;
+            tokenstorage::UserTokenStorage::store_token(&auth_token)?;
+            auth_token
+        };
+        if read_only {
+            GetPocketSync::new_read_only(&token)?
+        } else {
+            GetPocketSync::new(&token)?
+        }
+    };
+
+    logging::init(&config.logging)?;
+
+    if let Some(pos) = args.iter().position(|arg| arg == "--import") {
+        let (Some(format), Some(path)) = (args.get(pos + 1), args.get(pos + 2)) else {
+            return Err("usage: --import bookmarks|instapaper|omnivore|raindrop FILE".into());
+        };
+        let push = args.iter().any(|arg| arg == "--push");
+        let import = || -> anyhow::Result<bookmarks::ImportReport> {
+            let format = bookmarks::ImportFormat::parse(format)?;
+            let delta_file = Path::new(DELTA_FILE);
+            let saved_urls: std::collections::HashSet<String> =
+                load_local_items(delta_file, &mut TotalStats::new())?
+                    .iter()
+                    .map(|item| browserhistory::normalize_url(item.url()))
+                    .collect();
+            bookmarks::import(
+                Path::new(path),
+                format,
+                &saved_urls,
+                delta_file,
+                push.then_some(&pocket_client),
+            )
+        };
+        let report = import();
+        // the app didn't start, this run says nothing about crashes
+        safemode::unregister_startup(startup_marker, unclean_starts)?;
+        println!("{}", report?.summary());
+        return Ok(());
+    }
+
+    // state of the other machines comes in before the queue and the feeds are loaded
+    let state_sync = config
+        .state_sync
+        .clone()
+        .filter(|_| !sample_data && !safe_mode);
+    if let Some(state_sync) = &state_sync {
+        println!("Syncing local state...");
+        sync_local_state(state_sync);
+    }
+
+    // setup terminal
+    errors::install_hooks()?;
+    termcaps::init(&config.terminal);
+    let mut stdout = io::stdout();
+    termcaps::enter(&mut stdout)?;
+    let backend = CrosstermBackend::new(stdout);
+    let mut terminal = Terminal::new(backend)?;
+
+    let stats = TotalStats::new();
+    let list = if tutorial_mode {
+        tutorial::sample_items()
+    } else if demo_mode {
+        demo::sample_items()
+    } else {
+        Vec::new() //reload_data(&delta_file, &pocket_client, &mut stats)?;
+    };
+
+    let mut app: App = App::new(list, pocket_client, stats);
+    app.safe_mode = safe_mode;
+    if !sample_data {
+        app.load_queue_and_pdf_index(Path::new(queue::QUEUE_FILE));
+        app.title_overrides = TitleOverrides::load();
+        app.highlights = Highlights::load();
+        app.reading_progress = progress::load_all(Path::new("articles"));
+    }
+    if !sample_data && !safe_mode {
+        app.config = config;
+        app.stats_panel = app.config.stats_panel;
+        app.watch_queries = WatchQuery::parse_all(&app.config.watch_queries);
+        timezone::init(timezone::DisplayTimezone::parse(&app.config.timezone)?);
+        app.popup_layouts = PopupLayouts::load(app.config.popups.clone());
+        app.download_queue = DownloadQueue::load();
+        if app.config.thumbnails {
+            app.thumbnails = Thumbnails::detect();
+        }
+    }
+    if tutorial_mode {
+        app.tutorial = Some(Tutorial::new());
+    }
+    if sample_data {
+        if let Ok(mut rss_items) = app.rss_feed_state.items.lock() {
+            *rss_items = tutorial::sample_rss_items();
+        }
+    }
+    if safe_mode {
+        app.help_popup_state = Some(HelpPopupState {
+            content: safemode::doctor_report(unclean_starts, Path::new(DELTA_FILE)),
+        });
+    }
+    if !sample_data && !safe_mode {
+        app.control_socket = remote::ControlSocket::listen()
+            .inspect_err(|e| error!("Remote control is disabled: {:#}", e))
+            .ok();
+    }
+    app.start_rss_feed_loading()?;
+    let res = run_app(&mut terminal, app);
+
+    // restore terminal
+    termcaps::leave(terminal.backend_mut())?;
+    terminal.show_cursor()?;
+
+    if let Some(state_sync) = &state_sync {
+        sync_local_state(state_sync);
+    }
+
+    match res {
+        Ok(_) => safemode::register_clean_exit(startup_marker)?,
+        Err(err) => println!("{err:?}"),
+    }
+
+    Ok(())
+}
+
+/// Failures are printed and logged, the app works on the local copy regardless
+fn sync_local_state(config: &statesync::StateSyncConfig) {
+    match statesync::sync(config, Path::new(".")) {
+        Ok(report) => info!(
+            "Local state synced: {} files pushed, {} pulled",
+            report.pushed, report.pulled
+        ),
+        Err(e) => {
+            error!("Local state sync failed: {:#}", e);
+            println!("Local state sync failed: {:#}", e);
+        }
+    }
+}
+
+fn run_app<B: Backend>(terminal: &mut Terminal<B>, mut app: App) -> anyhow::Result<()> {
+    loop {
+        app.process_remote_requests();
+        app.process_background_sync();
+        if let Err(e) = app.process_download_queue() {
+            error!("Download queue: {:#}", e);
+        }
+        app.process_playback();
+        app.process_archive();
+        let frame = terminal
+            .draw(|f| ui(f, &mut app))
+            .context("Failed to draw UI")?;
+        mdview::write_hyperlinks(frame.buffer, &mut io::stdout())?;
+        match &mut app.app_mode {
+            AppMode::Initialize => {
+                app.start_from_cache()?;
+                app.app_mode = AppMode::Normal;
+            }
+            AppMode::Normal => process_input_normal_mode(&mut app)?,
+            AppMode::RssTriage => process_rss_triage_mode(&mut app)?,
+            AppMode::Confirmation(ref confirmation_type) => {
+                let ctype = confirmation_type.clone();
+                process_confirmation(&mut app, ctype)?
+            }
+
+            AppMode::Search(current) => {
+                let sstr = current.clone();
+                process_search_mode(&mut app, sstr)?
+            }
+            AppMode::MulticharNormalModeEnter(x) => {
+                let cur_state = x.clone();
+                process_multichar_enter_mode(&mut app, cur_state)?
+            }
+            AppMode::TypeAhead(jump) => {
+                let jump = jump.clone();
+                process_type_ahead_mode(&mut app, jump)?
+            }
+            AppMode::CommandEnter(enter) => {
+                let cur_state = enter.clone();
+                process_command_mode(&mut app, cur_state)?
+            }
+            AppMode::TagEdit(editor) => {
+                let editor = editor.clone();
+                process_tag_edit_mode(&mut app, editor)?
+            }
+            AppMode::DatePicker(picker) => {
+                let picker = picker.clone();
+                process_date_picker_mode(&mut app, picker)?
+            }
+            AppMode::Refreshing(ref mut pop) => {
+                if pop.was_redered {
+                    let refresh_result = match &pop.refresh_type {
+                        LoadingType::Refresh => app.refresh_data(),
+                        LoadingType::Reextract => app.reextract_article(),
+                        LoadingType::Rebuild => app.rebuild_storage(),
+                        LoadingType::BulkTag { tag, remove } => {
+                            let (tag, remove) = (tag.clone(), *remove);
+                            app.bulk_tag(&tag, remove)
+                        }
+                        LoadingType::Summarize => app.summarize_article(),
+                    };
+
+                    match refresh_result {
+                        Ok(_) => {
+                            app.switch_to_normal_mode();
+                        }
+                        Err(err) => {
+                            app.app_mode = AppMode::Error(err.to_string());
+                        }
+                    }
+                } else {
+                    pop.was_redered = true;
+                }
+
+                // if pop.was_redered {
+                //     let refresh_result = match pop.refresh_type {
+                //         LoadingType::Refresh => app.refresh_data(),
+                //         LoadingType::Download => app.download_current_pdf(),
+                //     };
+
+                //     match refresh_result {
+                //         Ok(_) => {
+                //             app.switch_to_normal_mode();
+                //         }
+                //         Err(err) => {
+                //             app.app_mode = AppMode::Error(err.to_string());
+                //         }
+                //     }
+                // } else {
+                //     pop.was_redered = true;
+                // }
+            }
+            AppMode::Quit => return Ok(()),
+            AppMode::Ambient(_) => {
+                // redraw every second to rotate teasers, any key gets back to the list
+                let event = events::next_event(&app.wakeups, Duration::from_secs(1))?;
+                if let Some(Event::Key(key)) = event {
+                    if key.kind == KeyEventKind::Press {
+                        app.last_input = Instant::now();
+                        app.switch_to_normal_mode();
+                    }
+                }
+            }
+            AppMode::Error(_) => {
+                if let Some(Event::Key(key)) = events::next_event(&app.wakeups, TICK_RATE)? {
+                    if key.kind == KeyEventKind::Press {
+                        if KeyCode::Esc == key.code {
+                            app.switch_to_normal_mode();
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+
+fn process_rss_triage_mode(app: &mut App) -> anyhow::Result<()> {
+    // status messages expire without any input
+    if let Some(Event::Key(key)) = events::next_event(&app.wakeups, TICK_RATE)? {
+        if key.kind != KeyEventKind::Press {
+            return Ok(());
+        }
+        let Some(triage_state) = &mut app.rss_triage_state else {
+            app.switch_to_normal_mode();
+            return Ok(());
+        };
+        use KeyCode::*;
+        if app.help_popup_state.is_some() {
+            if matches!(key.code, Esc | Char('q') | Char('?')) {
+                app.help_popup_state = None;
+            }
+            return Ok(());
+        }
+        if triage_state.hidden_browser.is_some() {
+            match key.code {
+                Char('j') | Down => triage_state.move_hidden_selection(1),
+                Char('k') | Up => triage_state.move_hidden_selection(-1),
+                Enter | Char('u') => {
+                    let feed_items = match app.rss_feed_state.items.lock() {
+                        Ok(items) => items.to_vec(),
+                        Err(_) => Vec::new(),
+                    };
+                    triage_state.restore_hidden(&feed_items)?;
+                }
+                Esc | Char('q') | Char('H') => triage_state.toggle_hidden_browser(),
+                _ => {}
+            }
+            return Ok(());
+        }
+        if triage_state.reader {
+            match key.code {
+                Char('j') | Down | Char('J') => triage_state.scroll_preview(1),
+                Char('k') | Up | Char('K') => triage_state.scroll_preview(-1),
+                PageDown | Char(' ') => {
+                    triage_state.scroll_preview(triage_state.visible_items as isize)
+                }
+                PageUp => triage_state.scroll_preview(-(triage_state.visible_items as isize)),
+                Char('a') => {
+                    triage_state.reader = false;
+                    app.process_add_to_pocket_with_tags()?;
+                }
+                Char('d') => {
+                    triage_state.reader = false;
+                    triage_state.hide_current_items()?;
+                    app.advance_tutorial(TutorialAction::RssTriage);
+                }
+                Enter => app.open_rss_item_in_browser()?,
+                Esc | Char('q') | Char('r') => triage_state.reader = false,
+                _ => {}
+            }
+            return Ok(());
+        }
+        match key.code {
+            Char('?') => app.show_help_popup(),
+            Char('j') | Down => triage_state.move_selection(1),
+            Char('k') | Up => triage_state.move_selection(-1),
+            Char('l') | Right | Tab => triage_state.cycle_source(1),
+            Char('h') | Left | BackTab => triage_state.cycle_source(-1),
+            Char(' ') => triage_state.toggle_mark(),
+            Char('p') => triage_state.show_preview = !triage_state.show_preview,
+            Char('J') => triage_state.scroll_preview(1),
+            Char('K') => triage_state.scroll_preview(-1),
+            Char(']') => triage_state.jump_to_feed(true),
+            Char('[') => triage_state.jump_to_feed(false),
+            Char('c') => triage_state.toggle_collapsed(),
+            Char('s') => triage_state.toggle_sort(),
+            Char('D') => triage_state.hide_current_feed()?,
+            Char('d') => {
+                triage_state.hide_current_items()?;
+                app.advance_tutorial(TutorialAction::RssTriage);
+            }
+            Char('a') => app.process_add_to_pocket_with_tags()?,
+            Char('e') => app.open_rss_enclosure()?,
+            Char('f') => app.fetch_rss_full_text()?,
+            Char('r') => app.open_rss_reader()?,
+            Char('H') => triage_state.toggle_hidden_browser(),
+            Char('R') => app.retry_rss_feeds()?,
+            Char('u') => triage_state.undo_hide()?,
+            Enter if triage_state.on_header() => triage_state.toggle_collapsed(),
+            Enter => app.open_rss_item_in_browser()?,
+            Esc | Char('q') => app.close_rss_triage()?,
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+fn process_tag_edit_mode(app: &mut App, mut editor: TagEditor) -> anyhow::Result<()> {
+    if let Some(Event::Key(key)) = events::next_event(&app.wakeups, TICK_RATE)? {
+        if key.kind == KeyEventKind::Press {
+            match editor.handle_key(key.code) {
+                TagEditorAction::Continue => app.app_mode = AppMode::TagEdit(editor),
+                TagEditorAction::Save(tags) => {
+                    let result = app.update_tags(editor.target, tags);
+                    match editor.target {
+                        TagTarget::PocketItem => {
+                            app.report(result, "Tags updated", "Failed to update tags")
+                        }
+                        TagTarget::PastedUrls => {
+                            let added = result.is_ok();
+                            app.report(result, "Added to Pocket", "Failed to add");
+                            if added {
+                                // a sync brings the new items into the list
+                                return app.refresh_all();
+                            }
+                        }
+                        // the RSS triage shows its own status
+                        TagTarget::RssItems => result?,
+                    }
+                    app.switch_to_normal_mode();
+                }
+                TagEditorAction::Cancel => {
+                    app.pasted_urls.clear();
+                    app.switch_to_normal_mode();
+                }
+            }
+        }
+    }
+    Ok(())
+}
+
+fn process_date_picker_mode(app: &mut App, mut picker: DatePicker) -> anyhow::Result<()> {
+    if let Some(Event::Key(key)) = events::next_event(&app.wakeups, TICK_RATE)? {
+        if key.kind == KeyEventKind::Press {
+            match picker.handle_key(key.code) {
+                DatePickerAction::Continue => app.app_mode = AppMode::DatePicker(picker),
+                DatePickerAction::Jump(date) => {
+                    app.switch_to_normal_mode();
+                    app.jump_to_date(date.format("%Y-%m-%d").to_string())?;
+                }
+                DatePickerAction::Range(from, to) => {
+                    app.switch_to_normal_mode();
+                    app.set_date_range(from, to);
+                }
+                DatePickerAction::Cancel => app.switch_to_normal_mode(),
+            }
+        }
+    }
+    Ok(())
+}
+
+fn process_command_mode(app: &mut App, mut cur_state: CommandEnterMode) -> anyhow::Result<()> {
+    let event = events::next_event(&app.wakeups, TICK_RATE)?;
+    Ok(if let Some(Event::Key(key)) = event {
+        if key.kind == KeyEventKind::Press {
+            use KeyCode::*;
+            match key.code {
+                Esc => app.switch_to_normal_mode(),
+                Tab => {
+                    cur_state.cycle_completions(1);
+                    app.app_mode = AppMode::CommandEnter(cur_state);
+                }
+                BackTab => {
+                    cur_state.cycle_completions(-1);
+                    app.app_mode = AppMode::CommandEnter(cur_state);
+                }
+                Char(ch) => {
+                    if (key.modifiers.contains(KeyModifiers::CONTROL)
+                        || key.modifiers.contains(KeyModifiers::SUPER))
+                        && (ch == 'v' || ch == 'V')
+                    {
+                        if let Ok(clipboard_content) = cli_clipboard::get_contents() {
+                            cur_state.current_enter =
+                                clipboard_content.replace('\n', " ").trim().to_string();
+                        }
+                    } else {
+                        // For regular typing, add the character as-is
+                        cur_state.current_enter.insert(cur_state.cursor_pos, ch);
+                        cur_state.cursor_pos += 1;
+                    }
+                    cur_state.update_completions(&app.cached_tags);
+
+                    app.app_mode = AppMode::CommandEnter(cur_state);
+
+                    // cur_state.current_enter.push(ch);
+                    // app.app_mode = AppMode::CommandEnter(cur_state);
+                }
+                Backspace => {
+                    if cur_state.cursor_pos > 0 {
+                        cur_state.current_enter.remove(cur_state.cursor_pos - 1);
+                        cur_state.cursor_pos -= 1;
+                        cur_state.update_completions(&app.cached_tags);
+                    }
+                    app.app_mode = AppMode::CommandEnter(cur_state);
+                }
+                Left => {
+                    if cur_state.cursor_pos > 0 {
+                        cur_state.cursor_pos -= 1;
+                        cur_state.completions = Completions::default();
+                        app.app_mode = AppMode::CommandEnter(cur_state);
+                    }
+                }
+                Right => {
+                    if cur_state.cursor_pos < cur_state.current_enter.len() {
+                        cur_state.cursor_pos += 1;
+                        cur_state.completions = Completions::default();
+                        app.app_mode = AppMode::CommandEnter(cur_state);
+                    }
+                }
+                Enter => {
+                    match cur_state.command_type {
+                        CommandType::RenameItem => {
+                            let result = app.rename_current_item(cur_state.current_enter);
+                            app.report(result, "Renamed", "Failed to rename");
+                        }
+                        CommandType::EditUrl => {
+                            let result = app.edit_current_url(&cur_state.current_enter);
+                            app.report(result, "URL changed", "Failed to change the URL");
+                        }
+                        CommandType::Command => {
+                            app.switch_to_normal_mode();
+                            return app.run_command(&cur_state.current_enter);
+                        }
+                    }
+                    app.switch_to_normal_mode();
+                }
+                _ => {} //do nothing
+            }
+        }
+    })
+}
+
+fn process_multichar_enter_mode(app: &mut App, cur_state: String) -> anyhow::Result<()> {
+    let event = events::next_event(&app.wakeups, TICK_RATE).context("Couldn't read user input")?;
+    Ok(if let Some(Event::Key(key)) = event {
+        if key.kind == KeyEventKind::Press {
+            use KeyCode::*;
+            match (cur_state.as_str(), key.code) {
+                ("g", Char('g')) => {
+                    app.switch_to_normal_mode();
+                    app.scroll_to_begining();
+                }
+                ("g", Char('d')) => app.open_date_picker(DatePickerPurpose::Jump),
+                ("g", Char('r')) => app.open_date_picker(DatePickerPurpose::Range),
+                ("g", Char('f')) => {
+                    app.switch_to_normal_mode();
+                    app.show_favorites_popup()?;
+                }
+                ("g", Char('a')) => {
+                    app.switch_to_normal_mode();
+                    if let Err(e) = app.show_archive() {
+                        app.app_mode = AppMode::Error(format!("{:#}", e));
+                    }
+                }
+                ("g", Char('q')) => {
+                    app.switch_to_normal_mode();
+                    if let Err(e) = app.show_qr_code() {
+                        app.app_mode = AppMode::Error(format!("{:#}", e));
+                    }
+                }
+                ("g", Char('s')) => {
+                    app.switch_to_normal_mode();
+                    if let Err(e) = app.show_summary() {
+                        app.app_mode = AppMode::Error(format!("{:#}", e));
+                    }
+                }
+                ("Z", Char('Z')) => {
+                    app.app_mode = AppMode::Quit;
+                }
+                _ => {
+                    app.switch_to_normal_mode();
+                }
+            }
+        }
+    })
+}
+
+fn process_type_ahead_mode(app: &mut App, mut jump: TypeAhead) -> anyhow::Result<()> {
+    let event = events::next_event(&app.wakeups, TICK_RATE).context("Couldn't read user input")?;
+    if let Some(Event::Key(key)) = event {
+        if key.kind == KeyEventKind::Press {
+            use KeyCode::*;
+            match key.code {
+                Char(ch) => {
+                    jump.prefix.push(ch);
+                    jump.found = app.jump_to_title(&jump.prefix, jump.started_at);
+                }
+                Backspace => {
+                    jump.prefix.pop();
+                    jump.found = if jump.prefix.is_empty() {
+                        app.select_item(jump.started_at);
+                        true
+                    } else {
+                        app.jump_to_title(&jump.prefix, jump.started_at)
+                    };
+                }
+                Tab if !jump.prefix.is_empty() => {
+                    let selected = app.virtual_state.selected().unwrap_or(0);
+                    jump.found = app.jump_to_title(&jump.prefix, selected);
+                }
+                Esc => {
+                    app.select_item(jump.started_at);
+                    app.switch_to_normal_mode();
+                    return Ok(());
+                }
+                _ => {
+                    app.switch_to_normal_mode();
+                    return Ok(());
+                }
+            }
+            app.app_mode = AppMode::TypeAhead(jump);
+        }
+    }
+    Ok(())
+}
+
+fn process_confirmation(app: &mut App, confirmation_type: Confirmation) -> anyhow::Result<()> {
+    let event = events::next_event(&app.wakeups, TICK_RATE).context("Couldn't read user input")?;
+    Ok(if let Some(Event::Key(key)) = event {
+        if key.kind == KeyEventKind::Press {
+            use KeyCode::*;
+            match key.code {
+                Char('y') | Char('Y') | Char('d') | Char('D') => {
+                    match confirmation_type {
+                        Confirmation::DeletePocketItem => {
+                            let result = app.delete_article();
+                            app.report(result, "Deleted", "Failed to delete");
+                        }
+                    };
+                }
+                _ => {} // do nothing
+            }
+        }
+        app.switch_to_normal_mode()
+    })
+}
+
+fn process_search_mode(app: &mut App, mut sstr: SearchMode) -> anyhow::Result<()> {
+    let timeout = match sstr.typed_at {
+        Some(typed_at) => SEARCH_DEBOUNCE.saturating_sub(typed_at.elapsed()),
+        None => Duration::from_millis(100),
+    };
+    let Some(event) = events::next_event(&app.wakeups, timeout)? else {
+        if sstr
+            .typed_at
+            .is_some_and(|typed_at| typed_at.elapsed() >= SEARCH_DEBOUNCE)
+        {
+            if sstr.navigate {
+                app.update_search_navigation(&mut sstr);
+            } else {
+                app.update_search_filter(&mut sstr);
+            }
+            app.app_mode = AppMode::Search(sstr);
+        }
+        return Ok(());
+    };
+    match event {
+        Event::Key(key) => {
+            if key.kind == KeyEventKind::Press {
+                use KeyCode::*;
+                match key.code {
+                    Esc if sstr.navigate => app.switch_to_normal_mode_from(AppMode::Search(sstr)),
+                    Esc => {
+                        app.clear_all_filters();
+                        app.switch_to_normal_mode_from(AppMode::Search(sstr))
+                    }
+                    Char('n') if key.modifiers.contains(KeyModifiers::CONTROL) => {
+                        sstr.navigate = !sstr.navigate;
+                        if sstr.navigate {
+                            // the list goes back to how it was before the search
+                            app.active_search_filter = None;
+                            app.apply_filter();
+                            sstr.applied = String::new();
+                        }
+                        sstr.typed_at = Some(Instant::now());
+                        app.app_mode = AppMode::Search(sstr);
+                    }
+                    Char(ch) => {
+                        sstr.search.push(ch);
+                        sstr.typed_at = Some(Instant::now());
+                        sstr.completions = app.search_completions(&sstr.search);
+                        app.app_mode = AppMode::Search(sstr);
+                    }
+                    Backspace => {
+                        sstr.search.pop();
+                        sstr.typed_at = Some(Instant::now());
+                        sstr.completions = app.search_completions(&sstr.search);
+                        app.app_mode = AppMode::Search(sstr);
+                    }
+                    Tab | BackTab => {
+                        let step = if key.code == Tab { 1 } else { -1 };
+                        let end = sstr.search.len();
+                        if sstr
+                            .completions
+                            .cycle(&mut sstr.search, end, step)
+                            .is_some()
+                        {
+                            sstr.typed_at = Some(Instant::now());
+                        }
+                        app.app_mode = AppMode::Search(sstr);
+                    }
+                    Enter if sstr.navigate => {
+                        app.update_search_navigation(&mut sstr);
+                        app.search_navigation = Some(sstr.search).filter(|s| !s.is_empty());
+                        app.app_mode = AppMode::Normal;
+                    }
+                    Enter => {
+                        app.set_search_filter(sstr.search.clone());
+                        app.switch_to_normal_mode_from(AppMode::Search(sstr));
+                    }
+                    Down => app.next(),
+                    Up => app.previous(),
+                    _ => {} //do nothing
+                }
+            }
+        }
+        Event::Mouse(mouse_event) => {
+            app.handle_mouse_event(mouse_event)?;
+        }
+        _ => {
+            // todo: proper logging
+            ()
+        }
+    }
+    Ok(())
+}
+
+fn process_input_normal_mode(app: &mut App) -> anyhow::Result<()> {
+    let Some(event) = events::next_event(&app.wakeups, TICK_RATE)? else {
+        app.show_ambient_screen_if_idle();
+        return Ok(());
+    };
+    app.last_input = Instant::now();
+    Ok(if let Event::Key(key) = event {
+        if key.kind == KeyEventKind::Press {
+            use KeyCode::*;
+            if key.modifiers.contains(KeyModifiers::ALT) {
+                if let Some(kind) = app.active_popup() {
+                    if app.popup_layouts.handle_resize_key(kind, key.code)? {
+                        return Ok(());
+                    }
+                }
+            }
+            // help over a popup takes the keys until it's closed
+            if app.help_popup_state.is_some() && app.key_context() != keymap::KeyContext::List {
+                if matches!(key.code, Esc | Char('q') | Char('?')) {
+                    app.help_popup_state = None;
+                }
+                return Ok(());
+            }
+            if key.code == Char('?') && app.tag_selection_mode == TagSelectionMode::Normal {
+                app.show_help_popup();
+                return Ok(());
+            }
+            if let Some(menu) = &mut app.context_menu_state {
+                match key.code {
+                    Char('j') | Down => menu.move_selection(1),
+                    Char('k') | Up => menu.move_selection(-1),
+                    Enter => {
+                        let (action, _) = CONTEXT_MENU[menu.selected_index];
+                        app.context_menu_state = None;
+                        app.run_context_action(action)?;
+                    }
+                    Esc | Char('q') => app.context_menu_state = None,
+                    _ => {}
+                }
+            } else if let Some(doc_popup_state) = &mut app.doc_type_popup_state {
+                match key.code {
+                    Char(ch) if ch.is_digit(10) => {
+                        if let Some(filter) = doc_popup_state.select_by_number(ch) {
+                            app.select_doc_type(filter);
+                        }
+                    }
+                    Esc => app.doc_type_popup_state = None,
+                    _ => {}
+                }
+            } else if let Some(tag_popup_state) = &mut app.tag_popup_state {
+                match app.tag_selection_mode {
+                    TagSelectionMode::Normal => match key.code {
+                        Down => tag_popup_state.move_selection(1),
+                        Up => tag_popup_state.move_selection(-1),
+                        Enter => app.select_tag(),
+                        Esc => app.tag_popup_state = None,
+                        Tab => tag_popup_state.toggle_sort(),
+                        Left => tag_popup_state.fold_selected(true),
+                        Right => tag_popup_state.fold_selected(false),
+                        Char(ch) => {
+                            app.tag_selection_mode = TagSelectionMode::Filtering;
+                            tag_popup_state.add_to_filter(ch)
+                        }
+                        _ => {}
+                    },
+                    TagSelectionMode::Filtering => match key.code {
+                        Char(ch) => tag_popup_state.add_to_filter(ch),
+                        Backspace => tag_popup_state.remove_from_filter(),
+                        Esc => {
+                            tag_popup_state.clear_filter();
+                            app.tag_selection_mode = TagSelectionMode::Normal;
+                        }
+                        Enter => {
+                            app.tag_selection_mode = TagSelectionMode::Normal;
+                            app.select_tag();
+                        }
+                        _ => {}
+                    },
+                }
+            } else if let Some(ref mut domain_state) = &mut app.domain_stats_popup_state {
+                match key.code {
+                    Enter => {
+                        if let Some((source, _)) =
+                            domain_state.stats.get(domain_state.selected_index)
+                        {
+                            app.source_filter = Some(source.clone());
+                            app.apply_filter();
+                            app.domain_stats_popup_state = None;
+                        }
+                    }
+                    Esc => {
+                        app.domain_stats_popup_state = None;
+                    }
+                    Char('j') | Down => {
+                        domain_state.move_selection(1);
+                    }
+                    Char('k') | Up => {
+                        domain_state.move_selection(-1);
+                    }
+                    _ => { /*do nothing */ }
+                }
+            } else if let Some(authors_state) = &mut app.authors_popup_state {
+                match key.code {
+                    Char('j') | Down => authors_state.move_selection(1),
+                    Char('k') | Up => authors_state.move_selection(-1),
+                    Char(' ') => authors_state.toggle_chosen(),
+                    Enter => {
+                        if let Some(filter) = authors_state.filter() {
+                            app.source_filter = Some(filter);
+                            app.apply_filter();
+                        }
+                        app.authors_popup_state = None;
+                    }
+                    Esc | Char('q') => app.authors_popup_state = None,
+                    _ => {}
+                }
+            } else if app.failed_actions_popup_state.is_some() {
+                match key.code {
+                    Char('r') => app.retry_failed_actions(),
+                    Esc => app.failed_actions_popup_state = None,
+                    _ => {}
+                }
+            } else if let Some(ref mut preview) = app.boundary_preview_state {
+                let last = preview.lines.len().saturating_sub(1);
+                match key.code {
+                    Char('j') | Down => preview.scroll = (preview.scroll + 1).min(last),
+                    Char('k') | Up => preview.scroll = preview.scroll.saturating_sub(1),
+                    PageDown => preview.scroll = (preview.scroll + 20).min(last),
+                    PageUp => preview.scroll = preview.scroll.saturating_sub(20),
+                    Char('r') => {
+                        if let Err(e) = app.reload_boundary_preview() {
+                            app.app_mode = AppMode::Error(format!("{:#}", e));
+                        }
+                    }
+                    Char('X') => {
+                        app.boundary_preview_state = None;
+                        app.app_mode = AppMode::Refreshing(RefreshingPopup::new(
+                            "Re-extracting article ⏳".to_string(),
+                            LoadingType::Reextract,
+                        ));
+                    }
+                    Esc | Char('q') => app.boundary_preview_state = None,
+                    _ => {}
+                }
+            } else if let Some(ref mut downloads_state) = app.downloads_popup_state {
+                let queue = &mut app.download_queue;
+                let last = queue.pending().len().saturating_sub(1);
+                match key.code {
+                    Char('j') | Down => {
+                        downloads_state.selected = (downloads_state.selected + 1).min(last)
+                    }
+                    Char('k') | Up => {
+                        downloads_state.selected = downloads_state.selected.saturating_sub(1)
+                    }
+                    Char('J') => {
+                        downloads_state.selected =
+                            queue.move_pending(downloads_state.selected, 1)?
+                    }
+                    Char('K') => {
+                        downloads_state.selected =
+                            queue.move_pending(downloads_state.selected, -1)?
+                    }
+                    Char('x') => {
+                        queue.remove_pending(downloads_state.selected)?;
+                        downloads_state.selected =
+                            downloads_state.selected.min(last.saturating_sub(1));
+                    }
+                    Char(' ') => queue.toggle_pause()?,
+                    Char('r') => queue.retry_failed()?,
+                    Char('a') => {
+                        if let Err(e) = app.queue_view_downloads() {
+                            app.app_mode = AppMode::Error(e.to_string());
+                        }
+                    }
+                    Esc | Char('q') => app.downloads_popup_state = None,
+                    _ => {}
+                }
+            } else if let Some(ref mut watch_state) = app.watch_popup_state {
+                match key.code {
+                    Char('j') | Down => watch_state.move_selection(1),
+                    Char('k') | Up => watch_state.move_selection(-1),
+                    Enter => {
+                        if let Some(watch_match) =
+                            watch_state.matches.get(watch_state.selected_index)
+                        {
+                            webbrowser::open(&watch_match.url)
+                                .context("Failed to open link in browser")?;
+                        }
+                    }
+                    Esc => app.watch_popup_state = None,
+                    _ => {}
+                }
+            } else if let Some(ref mut favorites_state) = app.favorites_popup_state {
+                match key.code {
+                    Char('j') | Down => favorites_state.move_selection(1),
+                    Char('k') | Up => favorites_state.move_selection(-1),
+                    Enter => {
+                        if let Some(item) =
+                            favorites_state.items.get(favorites_state.selected_index)
+                        {
+                            webbrowser::open(item.url())
+                                .context("Failed to open link in browser")?;
+                        }
+                    }
+                    Esc | Char('q') => app.favorites_popup_state = None,
+                    _ => {}
+                }
+            } else if let Some(ref mut archive_state) = app.archive_popup_state {
+                match key.code {
+                    Char('j') | Down => archive_state.move_selection(1),
+                    Char('k') | Up => archive_state.move_selection(-1),
+                    PageDown => archive_state.move_selection(archive_state.visible_items as isize),
+                    PageUp => archive_state.move_selection(-(archive_state.visible_items as isize)),
+                    Enter => {
+                        if let Some(item) = archive_state.items.get(archive_state.selected_index) {
+                            webbrowser::open(item.url())
+                                .context("Failed to open link in browser")?;
+                        }
+                    }
+                    Esc | Char('q') => app.archive_popup_state = None,
+                    _ => {}
+                }
+                app.fetch_archive_page()?;
+            } else if let Some(ref mut summary_state) = app.summary_popup_state {
+                let last = summary_state.summary.lines().count().saturating_sub(1);
+                match key.code {
+                    Char('j') | Down => summary_state.scroll = (summary_state.scroll + 1).min(last),
+                    Char('k') | Up => summary_state.scroll = summary_state.scroll.saturating_sub(1),
+                    Char('r') => {
+                        app.summary_popup_state = None;
+                        app.app_mode = AppMode::Refreshing(RefreshingPopup::new(
+                            "Summarizing article ⏳".to_string(),
+                            LoadingType::Summarize,
+                        ));
+                    }
+                    Esc | Char('q') => app.summary_popup_state = None,
+                    _ => {}
+                }
+            } else if let Some(ref mut share) = app.share_popup_state {
+                let last = app.config.share_templates.len().saturating_sub(1);
+                match key.code {
+                    Char('j') | Down => share.selected_index = (share.selected_index + 1).min(last),
+                    Char('k') | Up => share.selected_index = share.selected_index.saturating_sub(1),
+                    Enter | Char('y') => {
+                        let index = share.selected_index;
+                        app.copy_for_sharing(index);
+                    }
+                    Char(digit @ '1'..='9') => {
+                        app.copy_for_sharing(digit as usize - '1' as usize);
+                    }
+                    Esc | Char('q') => app.share_popup_state = None,
+                    _ => {}
+                }
+            } else if app.qr_popup_state.is_some() {
+                if matches!(key.code, Esc | Enter | Char('q')) {
+                    app.qr_popup_state = None;
+                }
+            } else if let Some(ref mut reader) = app.reader_popup_state {
+                match key.code {
+                    Char('j') | Down => reader.move_cursor(1),
+                    Char('k') | Up => reader.move_cursor(-1),
+                    PageDown => reader.move_cursor(reader.visible_items as isize),
+                    PageUp => reader.move_cursor(-(reader.visible_items as isize)),
+                    Char('v') => reader.toggle_selection(),
+                    Char('y') | Enter => app.save_highlight()?,
+                    Char('c') => reader.continue_reading(),
+                    Esc if reader.anchor.is_some() => reader.anchor = None,
+                    Esc | Char('q') => {
+                        let result = app.close_reader();
+                        if let Err(e) = result {
+                            app.toasts
+                                .error(format!("Failed to save progress: {:#}", e));
+                        }
+                    }
+                    _ => {}
+                }
+            } else if let Some(ref mut logs_state) = app.logs_popup_state {
+                match key.code {
+                    Char('j') | Down => logs_state.move_selection(1),
+                    Char('k') | Up => logs_state.move_selection(-1),
+                    PageDown => logs_state.move_selection(logs_state.visible_items as isize),
+                    PageUp => logs_state.move_selection(-(logs_state.visible_items as isize)),
+                    Char('l') => logs_state.cycle_level(),
+                    Char('r') => logs_state.reload(),
+                    Char('y') | Char('Y') => {
+                        let text = if key.code == Char('y') {
+                            logs_state
+                                .entries
+                                .get(logs_state.selected_index)
+                                .map(|entry| entry.to_line())
+                                .unwrap_or_default()
+                        } else {
+                            logs_state
+                                .entries
+                                .iter()
+                                .map(|entry| entry.to_line())
+                                .collect::<Vec<_>>()
+                                .join("\n")
+                        };
+                        match cli_clipboard::set_contents(text) {
+                            Ok(()) => app.toasts.success("Copied to clipboard"),
+                            Err(e) => app.toasts.error(format!("Failed to copy: {}", e)),
+                        }
+                    }
+                    Esc | Char('q') => app.logs_popup_state = None,
+                    _ => {}
+                }
+            } else if let Some(ref mut conflicts_state) = app.conflicts_popup_state {
+                let resolution = match key.code {
+                    Char('l') => Some(conflicts::Resolution::KeepLocal),
+                    Char('r') => Some(conflicts::Resolution::KeepRemote),
+                    Char('m') => Some(conflicts::Resolution::MergeTags),
+                    Char('j') | Down => {
+                        conflicts_state.move_selection(1);
+                        None
+                    }
+                    Char('k') | Up => {
+                        conflicts_state.move_selection(-1);
+                        None
+                    }
+                    // the list already shows what Pocket has
+                    Esc | Char('q') => {
+                        app.conflicts_popup_state = None;
+                        None
+                    }
+                    _ => None,
+                };
+                if let Some(resolution) = resolution {
+                    let result = app.resolve_conflict(resolution);
+                    app.report(
+                        result,
+                        "Conflict resolved",
+                        "Failed to resolve the conflict",
+                    );
+                }
+            } else if let Some(ref mut diff_state) = app.sync_diff_popup_state {
+                match key.code {
+                    Char('j') | Down => diff_state.move_selection(1),
+                    Char('k') | Up => diff_state.move_selection(-1),
+                    Enter => {
+                        if let Some(syncdiff::Change::Added { item_id, .. }) = diff_state
+                            .diff
+                            .changes
+                            .get(diff_state.selected_index)
+                            .cloned()
+                        {
+                            app.sync_diff_popup_state = None;
+                            if !app.jump_to_item(&item_id) {
+                                app.toasts.error("The item is no longer in the list");
+                            }
+                        }
+                    }
+                    Esc | Char('q') => app.sync_diff_popup_state = None,
+                    _ => {}
+                }
+            } else {
+                //normal mode
+                match key.code {
+                    Enter => {
+                        if app.tag_popup_state.is_some() {
+                            app.select_tag();
+                        } else {
+                            app.open_current_url()?;
+                        }
+                    }
+                    Char('Z') => {
+                        app.app_mode = AppMode::MulticharNormalModeEnter("Z".to_string());
+                    }
+                    Esc => {
+                        if app.search_navigation.is_some() {
+                            app.search_navigation = None;
+                        } else if app.active_search_filter.is_some() {
+                            app.clear_search_filter();
+                        } else if app.selected_tag_filter.is_some() {
+                            app.clear_tag_filter();
+                        } else if app.source_filter.is_some() {
+                            app.clear_source_filter();
+                        } else if app.date_range.is_some() {
+                            app.clear_date_range();
+                        } else if app.item_type_filter != ItemTypeFilter::All {
+                            app.set_item_type_filter(ItemTypeFilter::All);
+                        }
+                        if app.help_popup_state.is_some() {
+                            app.help_popup_state = None;
+                        }
+                    }
+                    Char('j') | Down => {
+                        if let Some(tag_popup_state) = &mut app.tag_popup_state {
+                            tag_popup_state.move_selection(1);
+                        } else {
+                            app.next();
+                        }
+                    }
+                    Char('k') | Up => {
+                        if let Some(tag_popup_state) = &mut app.tag_popup_state {
+                            tag_popup_state.move_selection(-1);
+                        } else {
+                            app.previous();
+                        }
+                    }
+                    Char('/') => app.switch_to_search_mode(),
+                    Char(':') => {
+                        app.app_mode = AppMode::CommandEnter(CommandEnterMode::new_empty(
+                            ":".to_string(),
+                            CommandType::Command,
+                        ))
+                    }
+                    Char('t') => {
+                        let result = app.toggle_top_tag();
+                        app.report(result, "Top tag updated", "Failed to update the top tag");
+                    }
+                    Char('T') => app.switch_to_edit_tags_mode(),
+                    Char('f') | Char('F') => {
+                        let result = app.fav_and_archive_article();
+                        app.report(result, "Favorited and archived", "Failed to favorite");
+                    }
+                    Char('d') => {
+                        if key.modifiers.contains(KeyModifiers::CONTROL) {
+                            app.scroll_down();
+                        } else {
+                            app.switch_to_confirmation(Confirmation::DeletePocketItem);
+                        }
+                    }
+                    Char('u') => {
+                        if key.modifiers.contains(KeyModifiers::CONTROL) {
+                            app.scroll_up();
+                        }
+                    }
+                    Char('g') => app.app_mode = AppMode::MulticharNormalModeEnter("g".to_string()),
+                    Char('\'') => app.start_type_ahead(),
+                    Char('G') => {
+                        app.scroll_to_end();
+                    }
+                    Char('p') => {
+                        let result = app.toggle_reading_queue();
+                        app.report(result, "Reading queue updated", "Failed to pin");
+                    }
+                    Char('J') => app.move_in_reading_queue(1)?,
+                    Char('K') => app.move_in_reading_queue(-1)?,
+                    Char('r') => app.switch_to_rename_mode(true),
+                    Char('U') => app.switch_to_edit_url_mode(),
+                    Char('a') => {
+                        if let Err(e) = app.paste_urls() {
+                            app.app_mode = AppMode::Error(format!("{:#}", e));
+                        }
+                    }
+                    Char('R') => app.switch_to_rename_mode(false),
+                    Char('z') => {
+                        if app.tag_popup_state.is_none() {
+                            app.show_tag_popup();
+                        } else {
+                            app.tag_popup_state = None;
+                        }
+                    }
+                    Char('w') => {
+                        if let Err(e) = app.queue_current_download() {
+                            app.app_mode = AppMode::Error(e.to_string());
+                        }
+                    }
+                    Char('D') => {
+                        app.downloads_popup_state = Some(DownloadsPopupState { selected: 0 });
+                    }
+                    Char('B') => app.show_boundary_preview()?,
+                    Char('y') => {
+                        if app.config.share_templates.is_empty() {
+                            app.app_mode = AppMode::Error(
+                                "No share templates, set \"share_templates\" in config.json"
+                                    .to_string(),
+                            );
+                        } else {
+                            app.share_popup_state = Some(SharePopupState { selected_index: 0 });
+                        }
+                    }
+                    Char('P') => {
+                        if let Err(e) = app.toggle_playback() {
+                            app.app_mode = AppMode::Error(format!("{:#}", e));
+                        }
+                    }
+                    Char('M') => app.playback = None,
+                    Char('e') => {
+                        if let Err(e) = app.show_reader() {
+                            app.app_mode = AppMode::Error(format!("{:#}", e));
+                        }
+                    }
+                    Char('v') => app.show_excerpt = !app.show_excerpt,
+                    Char('o') => app.cycle_grouping(),
+                    Char('c') => app.toggle_group_collapsed(),
+                    Char('Q') => app.refresh_all()?,
+                    Char('s') => {
+                        app.filter_by_current_domain()?;
+                    }
+                    Char('S') => {
+                        app.show_domain_stats();
+                    }
+                    Char('H') => app.stats_panel = app.stats_panel.next(),
+                    Char('A') => {
+                        app.authors_popup_state = Some(AuthorsPopupState::new(&app.items.items));
+                    }
+                    Char('W') => app.show_watch_popup(),
+                    Char('E') => app.export_epub()?,
+                    Char('X') => {
+                        app.app_mode = AppMode::Refreshing(RefreshingPopup::new(
+                            "Re-extracting article ⏳".to_string(),
+                            LoadingType::Reextract,
+                        ));
+                    }
+                    Char('i') => app.show_doc_type_popup(),
+                    Char('n') if app.search_navigation.is_some() => app.jump_to_next_match(true),
+                    Char('N') => app.jump_to_next_match(false),
+                    Char('n') => {
+                        app.open_rss_triage()?;
+                    }
+                    Char('b') => {
+                        match app.handle_neovim_edit() {
+                            Ok(Some(content)) => {
+                                // Use the edited content here
+                                // For example, you could store it in the currently selected item
+                                if let Some(idx) = app.virtual_state.selected() {
+                                    if let Some(item) = app.items.get_mut(idx) {
+                                        // Do something with the content
+                                        // For example:
+                                        // item.notes = content;
+                                    }
+                                }
+                            }
+                            Ok(None) => {
+                                // User cancelled or no changes
+                            }
+                            Err(e) => {
+                                // Show error in the footer or status area
+                                error!("Neovim edit failed: {}", e);
+                            }
+                        }
+                    }
+                    _ => {}
+                }
+            }
+        }
+    } else if let Event::Mouse(mouse_event) = event {
+        // popups over the list don't react to the mouse yet
+        if app.active_popup().is_none() && app.rss_triage_state.is_none() {
+            app.handle_mouse_event(mouse_event)?;
+        }
+    })
+}
+
+fn ui(f: &mut Frame, app: &mut App) {
+    let rects =
+        Layout::vertical([Constraint::Min(5), Constraint::Length(FOOTER_HEIGHT)]).split(f.area());
+    app.set_colors();
+
+    if let AppMode::Initialize = app.app_mode {
+        f.render_widget(Clear, f.area());
+        f.render_widget(
+            Block::default().style(Style::default().bg(OCEANIC_NEXT.base_00)), //app.colors.buffer_bg)),
+            f.area(),
+        );
+        logo::render(f, rects[0]);
+        return;
+    }
+    if let AppMode::Ambient(screen) = &app.app_mode {
+        ambient::render(f, screen, &app.stats.today_stats, f.area());
+        return;
+    }
+
+    if let Some(triage_state) = &app.rss_triage_state {
+        let failing_feeds = app
+            .rss_feed_state
+            .feed_health
+            .lock()
+            .map(|health| health.failing())
+            .unwrap_or_default();
+        rsstriage::render(f, triage_state, &failing_feeds, &app.colors, rects[0]);
+    } else {
+        let [stats_area, list_area] = Layout::vertical([
+            Constraint::Length(app.stats_panel.height()),
+            Constraint::Min(5),
+        ])
+        .areas(rects[0]);
+        render_stats_panel(f, app, stats_area);
+        if app.show_excerpt {
+            let [table_area, excerpt_area] =
+                Layout::vertical([Constraint::Min(5), Constraint::Length(EXCERPT_PANE_HEIGHT)])
+                    .areas(list_area);
+            render_table(f, app, table_area);
+            render_scrollbar(f, app, table_area);
+            render_excerpt_pane(f, app, excerpt_area);
+        } else {
+            render_table(f, app, list_area);
+            render_scrollbar(f, app, list_area);
+        }
+    }
+
+    render_footer(f, app, rects[1]);
+
+    render_domain_stats_popup(f, app, rects[0]);
+    render_authors_popup(f, app, rects[0]);
+
+    render_watch_popup(f, app, rects[0]);
+
+    render_favorites_popup(f, app, rects[0]);
+    render_archive_popup(f, app, rects[0]);
+
+    render_failed_actions_popup(f, app, rects[0]);
+
+    render_downloads_popup(f, app, rects[0]);
+
+    render_boundary_preview_popup(f, app, rects[0]);
+
+    render_summary_popup(f, app, rects[0]);
+    render_qr_popup(f, app, rects[0]);
+    render_share_popup(f, app, rects[0]);
+    render_reader_popup(f, app, rects[0]);
+
+    render_logs_popup(f, app, rects[0]);
+
+    render_sync_diff_popup(f, app, rects[0]);
+
+    render_conflicts_popup(f, app, rects[0]);
+
+    render_context_menu(f, app);
+
+    render_help_popup(f, app, rects[0]);
+
+    render_tutorial_overlay(f, app, rects[0]);
+
+    toast::render(f, &app.toasts, rects[0]);
+
+    if let AppMode::Error(message) = &app.app_mode {
+        render_error_popup(f, message, f.size(), &app.colors);
+    }
+
+    // After tag popup rendering, add:
+    if let Some(doc_popup_state) = &app.doc_type_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::DocType, f.area());
+        f.render_widget(Clear, popup_area);
+
+        let items: Vec<ListItem> = doc_popup_state
+            .items
+            .iter()
+            .enumerate()
+            .map(|(_i, (item_type, key, label))| {
+                let content = format!("{} - {}", key, label);
+
+                let style = if &app.item_type_filter == item_type {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else {
+                    Style::default().fg(app.colors.row_fg)
+                };
+                ListItem::new(content).style(style)
+            })
+            .collect();
+
+        let doc_type_list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Filter by Document Type: ")
+                    .border_style(Style::new().fg(app.colors.footer_border_color))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().bg(Color::Black));
+
+        f.render_widget(doc_type_list, popup_area);
+    }
+
+    if let Some(tag_popup_state) = &app.tag_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::Tags, f.area());
+        f.render_widget(Clear, popup_area);
+
+        let tags_text: Vec<ListItem> = tag_popup_state
+            .filtered_tags
+            .iter()
+            .skip(tag_popup_state.scroll_offset)
+            .take(tag_popup_state.visible_items)
+            .enumerate()
+            .map(|(i, (tag, count))| {
+                let name = if tag_popup_state.filter.is_empty() {
+                    let marker = if !tag_popup_state.parents.contains(tag) {
+                        " "
+                    } else if tag_popup_state.collapsed.contains(tag) {
+                        "▸"
+                    } else {
+                        "▾"
+                    };
+                    format!(
+                        "{}{} {}",
+                        "  ".repeat(tagtree::depth(tag)),
+                        marker,
+                        tagtree::label(tag)
+                    )
+                } else {
+                    tag.clone()
+                };
+                let content = format!("{:<30} {}", name, count);
+                let style = if i + tag_popup_state.scroll_offset == tag_popup_state.selected_index {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else {
+                    Style::default().fg(app.colors.row_fg)
+                };
+                ListItem::new(content).style(style)
+            })
+            .collect();
+
+        let mut block = Block::default()
+            .borders(Borders::ALL)
+            .title(if tag_popup_state.sort_by_count {
+                "All Tags (by count)"
+            } else {
+                "All Tags (a-z)"
+            })
+            .title_bottom(" Tab: sort | ←/→: fold | type to filter ")
+            .border_style(Style::new().fg(app.colors.footer_border_color))
+            .border_type(BorderType::Rounded);
+
+        if app.tag_selection_mode == TagSelectionMode::Filtering {
+            block = block.title(format!("Filter: {}", tag_popup_state.filter));
+        }
+
+        let tags_list = List::new(tags_text)
+            .block(block)
+            .style(Style::new().bg(Color::Black));
+
+        f.render_widget(tags_list, popup_area);
+
+        let scrollbar = Scrollbar::default()
+            .orientation(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(Some("↑".into()))
+            .end_symbol(Some("↓".into()));
+        let mut scroll_state = ScrollbarState::new(tag_popup_state.filtered_tags.len())
+            .position(tag_popup_state.scroll_offset);
+        f.render_stateful_widget(scrollbar, popup_area, &mut scroll_state);
+    }
+
+    if let AppMode::Refreshing(pop) = &app.app_mode {
+        let popup_area = centered_rect(20, 10, f.area());
+        f.render_widget(Clear, popup_area);
+
+        // Create text spans with different styles to create animation effect
+        let text = Text::from(vec![Line::from(vec![Span::styled(
+            &pop.text,
+            Style::new().fg(app.colors.row_fg),
+        )])]);
+
+        let block = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::new().fg(app.colors.footer_border_color))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().bg(Color::Black))
+            .alignment(Alignment::Center);
+
+        f.render_widget(block, popup_area);
+    }
+}
+
+fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
+    let popup_layout = Layout::default()
+        .direction(Direction::Vertical)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_y) / 2),
+                Constraint::Percentage(percent_y),
+                Constraint::Percentage((100 - percent_y) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(r);
+
+    Layout::default()
+        .direction(Direction::Horizontal)
+        .constraints(
+            [
+                Constraint::Percentage((100 - percent_x) / 2),
+                Constraint::Percentage(percent_x),
+                Constraint::Percentage((100 - percent_x) / 2),
+            ]
+            .as_ref(),
+        )
+        .split(popup_layout[1])[1]
+}
+
+fn render_table(f: &mut Frame, app: &mut App, area: Rect) {
+    let length = 14; //todo calc the value
+
+    if app.virtual_state.selected().unwrap() >= app.virtual_state.offset() + length {
+        *app.virtual_state.offset_mut() = app.virtual_state.selected().unwrap() + 1 - length;
+    }
+
+    let offset = app.virtual_state.offset();
+    *app.state.offset_mut() = 0;
+    app.state.select(Some(
+        app.virtual_state.selected().unwrap() - app.virtual_state.offset(),
+    ));
+
+    let selected_style = Style::default().fg(app.colors.selected_style_fg);
+    let grouped = app.group_by != GroupBy::Date;
+    let first_column_width = if grouped {
+        app.group_sizes
+            .keys()
+            .map(|key| key.width() as u16)
+            .max()
+            .unwrap_or(0)
+            .clamp(app.longest_item_lens.0, MAX_GROUP_HEADER_WIDTH)
+    } else {
+        app.longest_item_lens.0
+    };
+
+    let columns = app.config.columns.clone();
+    let widths: Vec<Constraint> = columns
+        .iter()
+        .map(|column| match column {
+            // + 1 is for padding.
+            Column::Date => Constraint::Length(first_column_width + 1),
+            Column::Title => Constraint::Min(app.longest_item_lens.1 + 1),
+            other => Constraint::Length(other.fixed_width().unwrap_or(10)),
+        })
+        .collect();
+    // the same split the table does, after the space for the highlight symbol
+    let [_, columns_area] = Layout::horizontal([
+        Constraint::Length(HIGHLIGHT_SYMBOL.width() as u16),
+        Constraint::Fill(0),
+    ])
+    .areas(area);
+    let title_area = columns
+        .iter()
+        .position(|column| *column == Column::Title)
+        .map(|index| {
+            Layout::horizontal(widths.iter().copied())
+                .spacing(1)
+                .split(columns_area)[index]
+        });
+    let title_width = title_area.map_or(0, |title_area| title_area.width as usize);
+
+    let highlight = app.highlight_term();
+    let rows = app
+        .items
+        .index(offset..(offset + length))
+        .into_iter()
+        .enumerate()
+        .map(|(x, data)| {
+            let actual_index = x + offset;
+            let queue_position = app.reading_queue.position(&data.item_id);
+            let is_same_date = actual_index > 0 && {
+                let prev = app.items.get(actual_index - 1).unwrap();
+                if grouped {
+                    app.group_by.key(prev) == app.group_by.key(data)
+                } else {
+                    app.reading_queue.position(&prev.item_id).is_none()
+                        && data.date() == prev.date()
+                }
+            };
+            let is_read = data.tags().any(|x| x == "read");
+            let is_top = data.tags().any(|x| x == "top");
+            let mut base_style = Style::new();
+            if is_read {
+                base_style = base_style.add_modifier(Modifier::DIM);
+            } else {
+                if is_top {
+                    base_style = base_style.add_modifier(Modifier::BOLD);
+                }
+            }
+            let top_marker = if is_top { "⭐ " } else { "" };
+            let full_title = if !data.title().is_empty() {
+                data.title()
+            } else {
+                data.url()
+            };
+            let first_width = title_width.saturating_sub(textwidth::width(top_marker));
+            let (title, ellipsis, rest) = match textwidth::split_line(full_title, first_width) {
+                (first, rest) if app.config.wrap_titles && !rest.is_empty() => {
+                    (first, "", Some(textwidth::truncate(rest, title_width)))
+                }
+                _ => {
+                    let (title, ellipsis) = textwidth::truncate(full_title, first_width);
+                    (title, ellipsis, None)
+                }
+            };
+            let title_style = base_style.fg(OCEANIC_NEXT.base_07);
+            let second_line = match rest {
+                Some((rest, ellipsis)) => Line::from(
+                    highlighted(
+                        rest,
+                        highlight.as_deref(),
+                        app.config.fuzzy_search,
+                        title_style,
+                    )
+                    .into_iter()
+                    .chain([Span::styled(ellipsis, title_style)])
+                    .collect::<Vec<_>>(),
+                ),
+                None => Line::from(vec![
+                    Span::styled(
+                        format!("[{}]: ", data.item_type()),
+                        base_style.fg(Color::Green).add_modifier(Modifier::ITALIC),
+                    ),
+                    Span::styled(
+                        app.reading_progress
+                            .get(&data.item_id)
+                            .map_or(String::new(), |percent| format!("{}% read ", percent)),
+                        base_style.fg(OCEANIC_NEXT.base_0c),
+                    ),
+                    Span::styled(
+                        format!("{}", data.tags().join(", ")),
+                        base_style.fg(OCEANIC_NEXT.base_0e),
+                    ),
+                ]),
+            };
+            let title_text = Text::from(vec![
+                Line::from(
+                    [Span::styled(top_marker, title_style)]
+                        .into_iter()
+                        .chain(highlighted(
+                            title,
+                            highlight.as_deref(),
+                            app.config.fuzzy_search,
+                            title_style,
+                        ))
+                        .chain([Span::styled(ellipsis, title_style)])
+                        .collect::<Vec<_>>(),
+                ),
+                second_line,
+            ]);
+            let cells = columns.iter().map(|column| match column {
+                Column::Date => Cell::from(if grouped && !is_same_date {
+                    group_header(app, data)
+                } else if let Some(pos) = queue_position {
+                    Text::from(format!("📌 {}", pos + 1))
+                } else if !is_same_date {
+                    Text::from(data.date())
+                } else {
+                    Text::from("")
+                }),
+                Column::Title => Cell::from(title_text.clone()),
+                Column::Tags => {
+                    Cell::from(data.tags().join(", ")).style(base_style.fg(OCEANIC_NEXT.base_0e))
+                }
+                Column::Domain => Cell::from(App::extract_domain(data.url()).unwrap_or_default())
+                    .style(base_style),
+                Column::WordCount => Cell::from(data.word_count.clone()).style(base_style),
+                Column::ReadingTime => {
+                    let words = data.word_count.parse::<usize>().unwrap_or(0);
+                    let text = if words > 0 {
+                        format!("{} min", words.div_ceil(READING_SPEED_WPM))
+                    } else {
+                        String::new()
+                    };
+                    Cell::from(text).style(base_style)
+                }
+                Column::Authors => {
+                    Cell::from(data.authors.iter().flatten().join(", ")).style(base_style)
+                }
+                Column::Source => Cell::from(
+                    SourceFilter::of(data)
+                        .map(|source| source.label())
+                        .unwrap_or_default(),
+                )
+                .style(base_style),
+            });
+            Row::new(cells.collect::<Vec<_>>()).height(ITEM_HEIGHT as u16)
+        });
+    let t = Table::new(rows, widths)
+        .row_highlight_style(selected_style)
+        .highlight_symbol(Text::from(vec![
+            HIGHLIGHT_SYMBOL.into(),
+            "".into(),
+            "".into(),
+        ]))
+        .bg(app.colors.buffer_bg)
+        .highlight_spacing(HighlightSpacing::Always);
+    f.render_stateful_widget(t, area, &mut app.state);
+
+    // the table scrolls on its own when the selected row doesn't fit into the area
+    let rendered = app.items.len().min(offset + length).saturating_sub(offset);
+    let first_row = app.state.offset().min(rendered);
+    app.table_layout = TableLayout {
+        area,
+        first_item: offset + first_row,
+        rows: (rendered - first_row).min(area.height as usize / ITEM_HEIGHT),
+    };
+
+    if let Some(title_area) = title_area.filter(|_| app.config.hyperlinks) {
+        let layout = app.table_layout;
+        for row in 0..layout.rows {
+            let Some(item) = app.items.get(layout.first_item + row) else {
+                continue;
+            };
+            // the star is wide, the link starts after it
+            let marker = if item.tags().any(|tag| tag == "top") {
+                textwidth::width("⭐ ") as u16
+            } else {
+                0
+            };
+            mdview::link_cells(
+                f.buffer_mut(),
+                title_area.x + marker,
+                area.y + (row * ITEM_HEIGHT) as u16,
+                title_area.width.saturating_sub(marker),
+                item.url(),
+            );
+        }
+    }
+}
+
+const HIGHLIGHT_SYMBOL: &str = " > ";
+/// Titles wider than this don't widen the title column, longer ones end with an ellipsis
+const MAX_TITLE_WIDTH: usize = 115;
+
+/// Longer domains and tags are cut off in the first column of a grouped table
+const MAX_GROUP_HEADER_WIDTH: u16 = 24;
+
+/// Name of the item's group with its size, on the first row of the group
+fn group_header<'a>(app: &App, item: &PocketItem) -> Text<'a> {
+    let key = app.group_by.key(item);
+    let size = app.group_sizes.get(&key).copied().unwrap_or(0);
+    let marker = if app.collapsed_groups.contains(&key) {
+        "▸"
+    } else {
+        "▾"
+    };
+    Text::from(vec![
+        Line::from(Span::styled(
+            key,
+            Style::default()
+                .fg(OCEANIC_NEXT.base_0d)
+                .add_modifier(Modifier::BOLD),
+        )),
+        Line::from(format!("{} {} items", marker, size)),
+    ])
+}
+
+//todo: the thrird column is not needed
+/// Reading stats of the week and the counts of the selected day, above the table
+fn render_stats_panel(f: &mut Frame, app: &App, area: Rect) {
+    if app.stats_panel == StatsPanel::Hidden {
+        return;
+    }
+    let day = app
+        .virtual_state
+        .selected()
+        .and_then(|idx| app.items.get(idx))
+        .map(|item| {
+            let date = item.date();
+            let stats = collect_stats(app.items.iter(), &date);
+            format!(
+                "░▒▓ {}: Text: {} | PDFs: {} | Vids: {} ▓▒░",
+                date, stats.articles_total, stats.pdfs_total, stats.videos_total
+            )
+        })
+        .unwrap_or_default();
+    let style = Style::default().fg(app.colors.selected_style_fg);
+    if app.stats_panel == StatsPanel::Collapsed {
+        let line = format!(
+            "{} | {}",
+            readingstats::week_summary(&app.stats.week_stats),
+            day
+        );
+        f.render_widget(Paragraph::new(line).style(style), area);
+        return;
+    }
+    let [bars_area, day_area] =
+        Layout::horizontal([Constraint::Min(40), Constraint::Fill(1)]).areas(area);
+    let bars = render_stats(
+        &app.stats.today_stats,
+        &app.stats.week_stats,
+        &app.stats.month_stats,
+    );
+    f.render_widget(Paragraph::new(bars).style(style), bars_area);
+    f.render_widget(Paragraph::new(day).style(style), day_area);
+}
+
+fn constraint_len_calculator<T: TableRow>(items: &[T]) -> (u16, u16, u16) {
+    let name_len = 10;
+    let title_len = items
+        .iter()
+        .map(TableRow::title)
+        .flat_map(str::lines)
+        .map(textwidth::width)
+        .max()
+        .unwrap_or(0)
+        .min(MAX_TITLE_WIDTH);
+    let email_len = 40;
+
+    #[allow(clippy::cast_possible_truncation)]
+    (name_len as u16, title_len as u16, email_len as u16)
+}
+
+fn render_scrollbar(f: &mut Frame, app: &mut App, area: Rect) {
+    let inner = area.inner(Margin {
+        vertical: 1,
+        horizontal: 1,
+    });
+    app.scroll_state = app
+        .scroll_state
+        .content_length(app.items.len().saturating_sub(1) * ITEM_HEIGHT + 1);
+    f.render_stateful_widget(
+        Scrollbar::default()
+            .orientation(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(None)
+            .end_symbol(None),
+        inner,
+        &mut app.scroll_state,
+    );
+    app.scrollbar_area = Rect::new(inner.right().saturating_sub(1), inner.y, 1, inner.height);
+}
+
+fn render_excerpt_pane(f: &mut Frame, app: &mut App, area: Rect) {
+    let item = app
+        .virtual_state
+        .selected()
+        .and_then(|idx| app.items.get(idx));
+    let (title, excerpt, image_url) = match item {
+        Some(item) if !item.excerpt.is_empty() => (
+            item.title(),
+            item.excerpt.as_str(),
+            item.top_image_url.as_deref(),
+        ),
+        Some(item) => (item.title(), "No excerpt", item.top_image_url.as_deref()),
+        None => ("", "", None),
+    };
+    let block = Block::default()
+        .borders(Borders::ALL)
+        .title(format!(" {} ", title))
+        .title_bottom(" v: hide ")
+        .border_style(Style::new().fg(app.colors.footer_border_color))
+        .border_type(BorderType::Rounded)
+        .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg));
+    let mut text = excerpt.to_string();
+    let quotes = item.map(|item| app.highlights.of(item)).unwrap_or_default();
+    if !quotes.is_empty() {
+        text.push_str(&format!("\n\nHighlights ({}):", quotes.len()));
+        for quote in quotes {
+            text.push_str(&format!("\n> {}", quote.text));
+        }
+    }
+    let mut text_area = block.inner(area);
+    let paragraph = Paragraph::new(text).wrap(Wrap { trim: true });
+    let image_url = image_url
+        .filter(|_| app.thumbnails.is_enabled())
+        .map(String::from);
+    f.render_widget(block, area);
+    if let Some(url) = image_url {
+        // cells are about twice as high as wide
+        let [image_area, rest] = Layout::horizontal([
+            Constraint::Length(text_area.height * 4),
+            Constraint::Min(10),
+        ])
+        .spacing(1)
+        .areas(text_area);
+        if app.thumbnails.render(f, &url, image_area) {
+            text_area = rest;
+        }
+    }
+    f.render_widget(paragraph, text_area);
+}
+
+fn render_help_popup(f: &mut Frame, app: &mut App, area: Rect) {
+    if let Some(help_state) = &app.help_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::Help, area);
+        f.render_widget(Clear, popup_area);
+
+        let text = Text::from(
+            help_state
+                .content
+                .lines()
+                .map(|line| Line::from(Span::styled(line, Style::default().fg(app.colors.row_fg))))
+                .collect::<Vec<_>>(),
+        );
+
+        let help_widget = Paragraph::new(text)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" GetPocket TUI Help ")
+                    .border_style(Style::new().fg(app.colors.header_fg))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().bg(Color::Black))
+            .alignment(Alignment::Left);
+
+        f.render_widget(help_widget, popup_area);
+    }
+}
+
+fn render_tutorial_overlay(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(tutorial) = &app.tutorial {
+        let step = tutorial.current();
+        let (current, total) = tutorial.progress();
+
+        let width = 48.min(area.width);
+        let height = (step.hints.len() as u16 + 4).min(area.height);
+        let overlay_area = Rect::new(
+            area.x + area.width - width,
+            area.y + area.height - height,
+            width,
+            height,
+        );
+        f.render_widget(Clear, overlay_area);
+
+        let mut lines = vec![Line::from(Span::styled(
+            step.title,
+            Style::default()
+                .fg(OCEANIC_NEXT.base_0a)
+                .add_modifier(Modifier::BOLD),
+        ))];
+        lines.extend(
+            step.hints.iter().map(|hint| {
+                Line::from(Span::styled(*hint, Style::default().fg(app.colors.row_fg)))
+            }),
+        );
+
+        let overlay = Paragraph::new(Text::from(lines))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Tutorial {}/{} ", current, total))
+                    .border_style(Style::new().fg(OCEANIC_NEXT.base_0a))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().bg(Color::Black))
+            .alignment(Alignment::Left);
+
+        f.render_widget(overlay, overlay_area);
+    }
+}
+
+fn render_error_popup(f: &mut Frame, message: &str, area: Rect, colors: &TableColors) {
+    let popup_area = centered_rect(60, 20, area);
+    f.render_widget(Clear, popup_area);
+
+    let text = Text::from(vec![
+        Line::from(vec![Span::styled(
+            "Error",
+            Style::default()
+                .fg(OCEANIC_NEXT.base_08)
+                .add_modifier(Modifier::BOLD),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            message,
+            Style::default().fg(colors.row_fg),
+        )]),
+        Line::from(""),
+        Line::from(vec![Span::styled(
+            "Press ESC to dismiss",
+            Style::default().fg(OCEANIC_NEXT.base_03),
+        )]),
+    ]);
+
+    let error_widget = Paragraph::new(text)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .border_style(Style::new().fg(OCEANIC_NEXT.base_08))
+                .border_type(BorderType::Rounded),
+        )
+        .style(Style::new().bg(Color::Black))
+        .alignment(Alignment::Center);
+
+    f.render_widget(error_widget, popup_area);
+}
+
+/// "5m ago", "3h ago" or "2d ago"
+fn format_elapsed(elapsed: Duration) -> String {
+    let minutes = elapsed.as_secs() / 60;
+    match minutes {
+        0 => "just now".to_string(),
+        1..=59 => format!("{}m ago", minutes),
+        60..=1439 => format!("{}h ago", minutes / 60),
+        _ => format!("{}d ago", minutes / 1440),
+    }
+}
+
+/// Time since the last Pocket sync and RSS fetch, red once older than `stale_after_mins`
+fn freshness_spans(app: &App) -> Vec<Span<'static>> {
+    let last_fetched = app
+        .rss_feed_state
+        .last_fetched
+        .lock()
+        .ok()
+        .and_then(|last_fetched| *last_fetched);
+    let stale_after = Duration::from_secs(app.config.stale_after_mins * 60);
+    let mut spans = Vec::new();
+    if app.background_sync.is_some() {
+        let status = match &app.bootstrap_progress {
+            Some(fetched) => format!(
+                "fetching all items ⏳ {} so far",
+                fetched.load(Ordering::Relaxed)
+            ),
+            None => "syncing ⏳".to_string(),
+        };
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            status,
+            Style::default().fg(OCEANIC_NEXT.base_03),
+        ));
+    }
+    for (label, last) in [("synced", app.last_synced), ("RSS", last_fetched)] {
+        let Some(last) = last else {
+            continue;
+        };
+        let elapsed = last.elapsed();
+        let style = if app.config.stale_after_mins > 0 && elapsed > stale_after {
+            Style::default().fg(OCEANIC_NEXT.base_08)
+        } else {
+            Style::default().fg(OCEANIC_NEXT.base_03)
+        };
+        spans.push(Span::raw(" | "));
+        spans.push(Span::styled(
+            format!("{} {}", label, format_elapsed(elapsed)),
+            style,
+        ));
+    }
+    spans
+}
+
+fn render_footer(f: &mut Frame, app: &mut App, area: Rect) {
+    app.footer_chips.clear();
+    match &app.app_mode {
+        AppMode::Initialize => panic!("Should not get here!"),
+        AppMode::RssTriage => {
+            let triage_state = app.rss_triage_state.as_ref();
+            let mut spans = vec![Span::raw(RSS_TRIAGE_INFO_TEXT)];
+            if let Some(marked) = triage_state.map(|s| s.marked_count()).filter(|n| *n > 0) {
+                spans.push(Span::raw(format!(" | {} marked", marked)));
+            }
+            if let Some(status) = triage_state.and_then(|s| s.status()) {
+                spans.extend_from_slice(&[
+                    Span::raw(" | "),
+                    Span::styled(status, Style::default().fg(OCEANIC_NEXT.base_0b)),
+                ]);
+            }
+            let info_footer = Paragraph::new(Line::from(spans))
+                .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+                .alignment(Alignment::Center)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::new().fg(app.colors.footer_border_color))
+                        .border_type(BorderType::Double),
+                );
+            f.render_widget(info_footer, area);
+        }
+        AppMode::Normal
+        | AppMode::MulticharNormalModeEnter(_)
+        | AppMode::Refreshing(_)
+        | AppMode::Error(_)
+        | AppMode::Ambient(_)
+        | AppMode::Quit => {
+            let is_filtered = app.selected_tag_filter.is_some()
+                || app.item_type_filter != ItemTypeFilter::All
+                || app.source_filter.is_some()
+                || app.date_range.is_some()
+                || app.active_search_filter.is_some();
+
+            let mut spans = if is_filtered {
+                vec![Span::raw("[Filter]")]
+            } else {
+                vec![Span::raw(INFO_TEXT)]
+            };
+            let badges = [
+                (app.safe_mode, " SAFE MODE ", OCEANIC_NEXT.base_08),
+                (
+                    app.pocket_client.is_read_only(),
+                    " READ-ONLY ",
+                    OCEANIC_NEXT.base_0a,
+                ),
+                (
+                    app.pocket_client.is_dry_run() && app.tutorial.is_none(),
+                    " DEMO ",
+                    OCEANIC_NEXT.base_0d,
+                ),
+            ];
+            for (_, label, color) in badges.into_iter().rev().filter(|(shown, ..)| *shown) {
+                spans.insert(
+                    0,
+                    Span::styled(
+                        label,
+                        Style::default()
+                            .bg(color)
+                            .fg(OCEANIC_NEXT.base_00)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                );
+                spans.insert(1, Span::raw(" "));
+            }
+
+            let mut chips = Vec::new();
+            if let Some(search) = &app.active_search_filter {
+                spans.push(Span::raw(" | "));
+                chips.push((spans.len()..spans.len() + 2, FooterChip::Search));
+                spans.extend_from_slice(&[Span::raw("/"), Span::raw(search)]);
+            }
+            if let Some(search) = &app.search_navigation {
+                spans.push(Span::raw(" | "));
+                chips.push((spans.len()..spans.len() + 2, FooterChip::Navigation));
+                spans.extend_from_slice(&[Span::raw("n/N: /"), Span::raw(search)]);
+            }
+            if let Some(tag) = &app.selected_tag_filter {
+                spans.push(Span::raw(" | "));
+                chips.push((spans.len()..spans.len() + 2, FooterChip::Tag));
+                spans.extend_from_slice(&[Span::raw("Tag: "), Span::raw(tag)]);
+            }
+            if let Some(source) = &app.source_filter {
+                spans.push(Span::raw(" | "));
+                chips.push((spans.len()..spans.len() + 2, FooterChip::Domain));
+                spans.extend_from_slice(&[Span::raw("Site : "), Span::raw(source.label())]);
+            }
+            if let Some((from, to)) = &app.date_range {
+                spans.push(Span::raw(" | "));
+                chips.push((spans.len()..spans.len() + 1, FooterChip::DateRange));
+                spans.push(Span::raw(format!(
+                    "Added: {}..{}",
+                    from.format("%Y-%m-%d"),
+                    to.format("%Y-%m-%d")
+                )));
+            }
+            if app.group_by != GroupBy::Date {
+                spans.push(Span::raw(format!(
+                    " | Grouped by {} ('c' to fold)",
+                    app.group_by.name()
+                )));
+            }
+            if app.item_type_filter != ItemTypeFilter::All {
+                let filter_text = match app.item_type_filter {
+                    ItemTypeFilter::All => unreachable!(),
+                    ItemTypeFilter::Article => "Articles",
+                    ItemTypeFilter::Video => "Videos",
+                    ItemTypeFilter::PDF => "PDFs",
+                };
+                spans.push(Span::raw(" | "));
+                chips.push((spans.len()..spans.len() + 2, FooterChip::DocType));
+                spans.extend_from_slice(&[Span::raw("Doc type : "), Span::raw(filter_text)]);
+            }
+
+            if app.item_type_filter != ItemTypeFilter::All
+                || app.selected_tag_filter.is_some()
+                || app.active_search_filter.is_some()
+            {
+                let text = format!("[Showing {} items]", app.items.len());
+                spans.extend_from_slice(&[Span::raw(" ('ESC` to clear) | "), Span::raw(text)]);
+            }
+            if let Ok(items) = app.rss_feed_state.items.lock() {
+                if !items.is_empty() {
+                    chips.push((spans.len() + 1..spans.len() + 2, FooterChip::Rss));
+                    spans.extend_from_slice(&[
+                        Span::raw(" | "),
+                        Span::styled(
+                            " RSS updates ",
+                            Style::default()
+                                .bg(OCEANIC_NEXT.base_0e) // Pink background
+                                .fg(OCEANIC_NEXT.base_00) // Dark text for contrast
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                    ]);
+                }
+            }
+            if let Ok(matches) = app.watch_matches.lock() {
+                if !matches.is_empty() {
+                    spans.extend_from_slice(&[
+                        Span::raw(" | "),
+                        Span::styled(
+                            format!(" {} watched (W) ", matches.len()),
+                            Style::default()
+                                .bg(OCEANIC_NEXT.base_0a)
+                                .fg(OCEANIC_NEXT.base_00)
+                                .add_modifier(Modifier::BOLD),
+                        ),
+                    ]);
                 }
             }
+            let downloads = &app.download_queue;
+            if !downloads.is_empty() {
+                spans.extend_from_slice(&[
+                    Span::raw(" | "),
+                    Span::styled(
+                        format!(
+                            " ⬇ {}/{}{} (D) ",
+                            downloads.running().len(),
+                            downloads.running().len() + downloads.pending().len(),
+                            if downloads.is_paused() { " paused" } else { "" }
+                        ),
+                        Style::default()
+                            .bg(OCEANIC_NEXT.base_0b)
+                            .fg(OCEANIC_NEXT.base_00)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                ]);
+            }
+            if let Some(playback) = &app.playback {
+                let (title, ellipsis) = textwidth::truncate(&playback.title, 30);
+                spans.extend_from_slice(&[
+                    Span::raw(" | "),
+                    Span::styled(
+                        format!(
+                            " {} {}{} (P) ",
+                            if playback.is_paused() { "⏸" } else { "🔊" },
+                            title,
+                            ellipsis
+                        ),
+                        Style::default()
+                            .bg(OCEANIC_NEXT.base_0c)
+                            .fg(OCEANIC_NEXT.base_00)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                ]);
+            }
+            spans.push(Span::styled(
+                format!(
+                    " | {}",
+                    app.view_summary.footer_text(Utc::now().timestamp())
+                ),
+                Style::default().fg(OCEANIC_NEXT.base_04),
+            ));
+            spans.extend(freshness_spans(app));
+            let alignment = if is_filtered {
+                Alignment::Left
+            } else {
+                Alignment::Center
+            };
+            let chip_areas =
+                footer_chip_areas(&spans, &chips, alignment, area.inner(Margin::new(1, 1)));
+            let info_footer = Paragraph::new(Line::from(spans))
+                .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
+                .alignment(alignment)
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::new().fg(app.colors.footer_border_color))
+                        .border_type(BorderType::Double),
+                );
+            f.render_widget(info_footer, area);
+            app.footer_chips = chip_areas;
         }
-    })
-}
-
-fn ui(f: &mut Frame, app: &mut App) {
-    let rects = Layout::vertical([Constraint::Min(5), Constraint::Length(3)]).split(f.area());
-    app.set_colors();
-
-    if let AppMode::Initialize = app.app_mode {
-        f.render_widget(Clear, f.area());
-        f.render_widget(
-            Block::default().style(Style::default().bg(OCEANIC_NEXT.base_00)), //app.colors.buffer_bg)),
-            f.area(),
-        );
-        logo::render(f, rects[0]);
-        return;
-    }
-
-    render_table(f, app, rects[0]);
-
-    render_scrollbar(f, app, rects[0]);
+        AppMode::TagEdit(editor) => tageditor::render(f, editor, &app.colors, area),
+        AppMode::DatePicker(picker) => datepicker::render(f, picker, &app.colors, area),
+        AppMode::Search(search) => {
+            let mut final_string = "/".to_string();
+            final_string.push_str(&search.search);
 
-    render_footer(f, app, rects[1]);
+            let mut textarea = TextArea::new(vec![final_string]);
+            textarea.set_style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg));
+            textarea.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(if search.navigate {
+                        " Jump to matches, n/N after Enter | Ctrl+n: filter instead "
+                    } else {
+                        " Filter | Ctrl+n: jump to matches instead "
+                    })
+                    .border_style(Style::new().fg(app.colors.footer_border_color))
+                    .border_type(BorderType::Rounded),
+            );
+            textarea.move_cursor(tui_textarea::CursorMove::End);
+            f.render_widget(&textarea, area);
+            render_completion_hint(
+                f,
+                search.completions.hint(search.search.len()),
+                1 + search.search.len(),
+                area,
+            );
+            completion::render(f, &search.completions, &app.colors, area);
+        }
+        AppMode::TypeAhead(jump) => {
+            let mut textarea = TextArea::new(vec![format!("'{}", jump.prefix)]);
+            textarea.set_style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg));
+            textarea.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(if jump.found {
+                        " Jump to title | Tab: next match | Enter: done | Esc: back "
+                    } else {
+                        " No title matches | Backspace: fix | Esc: back "
+                    })
+                    .border_style(Style::new().fg(if jump.found {
+                        app.colors.footer_border_color
+                    } else {
+                        OCEANIC_NEXT.base_08
+                    }))
+                    .border_type(BorderType::Rounded),
+            );
+            textarea.move_cursor(tui_textarea::CursorMove::End);
+            f.render_widget(&textarea, area);
+        }
+        AppMode::Confirmation(_) => {
+            let mut textarea = TextArea::default();
+            textarea.set_style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg));
+            textarea.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title("Delete ? ['y' or 'd' - to confirm] ")
+                    .border_style(Style::new().fg(app.colors.footer_border_color))
+                    .border_type(BorderType::Rounded),
+            );
+            textarea.move_cursor(tui_textarea::CursorMove::End);
+            f.render_widget(&textarea, area);
+        }
+        AppMode::CommandEnter(x) => {
+            // Create the base TextArea for input
+            let input_text = format!("{}{}", x.prompt, x.current_enter);
+            let mut textarea = TextArea::new(vec![input_text]);
+            textarea.set_style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg));
+            textarea.set_block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .border_style(Style::new().fg(app.colors.footer_border_color))
+                    .border_type(BorderType::Rounded),
+            );
 
-    render_domain_stats_popup(f, app, rects[0]);
+            let prompt_len = x.prompt.len();
+            let cursor_pos = (x.cursor_pos + prompt_len).try_into().unwrap();
+            textarea.move_cursor(CursorMove::Jump(0, cursor_pos));
 
-    render_help_popup(f, app, rects[0]);
+            // Render the base TextArea
+            f.render_widget(&textarea, area);
 
-    render_rss_feed_popup(f, app, rects[0]); //todo: move if out of render
+            render_completion_hint(
+                f,
+                x.completions.hint(x.cursor_pos),
+                prompt_len + x.current_enter.len(),
+                area,
+            );
+            completion::render(f, &x.completions, &app.colors, area);
+        }
+    }
+}
 
-    if let AppMode::Error(message) = &app.app_mode {
-        render_error_popup(f, message, f.size(), &app.colors);
+/// Rest of the first completion, dimmed after the text typed into the footer
+fn render_completion_hint(f: &mut Frame, hint: Option<&str>, typed_len: usize, area: Rect) {
+    let Some(hint) = hint else {
+        return;
+    };
+    let area_with_margin = area.inner(Margin::new(1, 1));
+    let hint_x = (typed_len + 1) as u16;
+    if hint_x < area_with_margin.width {
+        let hint_area = Rect::new(
+            area_with_margin.x + hint_x,
+            area_with_margin.y,
+            area_with_margin.width - hint_x,
+            1,
+        );
+        let hint_text = Paragraph::new(hint).style(
+            Style::new()
+                .fg(OCEANIC_NEXT.base_03)
+                .add_modifier(Modifier::DIM),
+        );
+        f.render_widget(hint_text, hint_area);
     }
+}
 
-    // After tag popup rendering, add:
-    if let Some(doc_popup_state) = &app.doc_type_popup_state {
-        let popup_area = centered_rect(40, 40, f.area());
+fn render_domain_stats_popup(f: &mut Frame, app: &mut App, area: Rect) {
+    if let Some(popup_state) = &app.domain_stats_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::DomainStats, area);
         f.render_widget(Clear, popup_area);
 
-        let items: Vec<ListItem> = doc_popup_state
-            .items
+        let items: Vec<ListItem> = popup_state
+            .stats
             .iter()
+            .skip(popup_state.scroll_offset)
+            .take(popup_state.visible_items)
             .enumerate()
-            .map(|(_i, (item_type, key, label))| {
-                let content = format!("{} - {}", key, label);
-
-                let style = if &app.item_type_filter == item_type {
+            .map(|(i, (source, count))| {
+                let content = format!("{:<40} {}", source.label(), count);
+                let style = if i + popup_state.scroll_offset == popup_state.selected_index {
                     Style::default().fg(Color::Black).bg(Color::White)
                 } else {
                     Style::default().fg(app.colors.row_fg)
@@ -2651,712 +7292,1074 @@ fn ui(f: &mut Frame, app: &mut App) {
             })
             .collect();
 
-        let doc_type_list = List::new(items)
+        let title = " Domain/Author Statistics ";
+        let stats_list = List::new(items)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title(" Filter by Document Type: ")
+                    .title(title)
                     .border_style(Style::new().fg(app.colors.footer_border_color))
                     .border_type(BorderType::Rounded),
             )
             .style(Style::new().bg(Color::Black));
 
-        f.render_widget(doc_type_list, popup_area);
+        f.render_widget(stats_list, popup_area);
+
+        let scrollbar = Scrollbar::default()
+            .orientation(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(Some("↑".into()))
+            .end_symbol(Some("↓".into()));
+        let mut scroll_state =
+            ScrollbarState::new(popup_state.stats.len()).position(popup_state.scroll_offset);
+        f.render_stateful_widget(scrollbar, popup_area, &mut scroll_state);
     }
+}
+fn render_authors_popup(f: &mut Frame, app: &mut App, area: Rect) {
+    let Some(popup_state) = &mut app.authors_popup_state else {
+        return;
+    };
+    let popup_area = app.popup_layouts.area(PopupKind::Authors, area);
+    f.render_widget(Clear, popup_area);
 
-    if let Some(tag_popup_state) = &app.tag_popup_state {
-        let popup_area = centered_rect(60, 60, f.area());
+    let items: Vec<ListItem> = popup_state
+        .authors
+        .iter()
+        .map(|(author, count)| {
+            let mark = if popup_state.chosen.contains(author) {
+                "[x]"
+            } else {
+                "[ ]"
+            };
+            ListItem::new(format!("{} {:<40} {}", mark, author, count))
+        })
+        .collect();
+    let list = List::new(items)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Authors ({}) ", popup_state.authors.len()))
+                .title_bottom(" Space: choose | Enter: filter | Esc: close ")
+                .border_style(Style::new().fg(app.colors.footer_border_color))
+                .border_type(BorderType::Rounded),
+        )
+        .style(Style::new().bg(Color::Black).fg(app.colors.row_fg))
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::White));
+    f.render_stateful_widget(list, popup_area, &mut popup_state.state);
+}
+
+fn render_watch_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(popup_state) = &app.watch_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::Watch, area);
         f.render_widget(Clear, popup_area);
 
-        let tags_text: Vec<ListItem> = tag_popup_state
-            .filtered_tags
+        let items: Vec<ListItem> = popup_state
+            .matches
             .iter()
-            .skip(tag_popup_state.scroll_offset)
-            .take(tag_popup_state.visible_items)
+            .skip(popup_state.scroll_offset)
+            .take(popup_state.visible_items)
             .enumerate()
-            .map(|(i, (tag, count))| {
-                let content = format!("{:<30} {}", tag, count);
-                let style = if i + tag_popup_state.scroll_offset == tag_popup_state.selected_index {
+            .map(|(i, watch_match)| {
+                let style = if i + popup_state.scroll_offset == popup_state.selected_index {
                     Style::default().fg(Color::Black).bg(Color::White)
                 } else {
                     Style::default().fg(app.colors.row_fg)
                 };
-                ListItem::new(content).style(style)
+                ListItem::new(vec![
+                    Line::from(watch_match.title.clone()),
+                    Line::from(Span::styled(
+                        format!("  {} · {}", watch_match.source, watch_match.query),
+                        Style::default().fg(OCEANIC_NEXT.base_03),
+                    )),
+                ])
+                .style(style)
             })
             .collect();
 
-        let mut block = Block::default()
-            .borders(Borders::ALL)
-            .title("All Tags")
-            .border_style(Style::new().fg(app.colors.footer_border_color))
-            .border_type(BorderType::Rounded);
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Watched topics: new items ")
+                    .title_bottom(" Enter: open | Esc: dismiss ")
+                    .border_style(Style::new().fg(OCEANIC_NEXT.base_0a))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().bg(Color::Black));
 
-        if app.tag_selection_mode == TagSelectionMode::Filtering {
-            block = block.title(format!("Filter: {}", tag_popup_state.filter));
-        }
+        f.render_widget(list, popup_area);
+    }
+}
 
-        let tags_list = List::new(tags_text)
-            .block(block)
-            .style(Style::new().bg(Color::Black));
+fn render_favorites_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(popup_state) = &app.favorites_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::Favorites, area);
+        f.render_widget(Clear, popup_area);
 
-        f.render_widget(tags_list, popup_area);
+        let items: Vec<ListItem> = popup_state
+            .items
+            .iter()
+            .skip(popup_state.scroll_offset)
+            .take(popup_state.visible_items)
+            .enumerate()
+            .map(|(i, item)| {
+                let style = if i + popup_state.scroll_offset == popup_state.selected_index {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else {
+                    Style::default().fg(app.colors.row_fg)
+                };
+                let favorited = item
+                    .time_favorited
+                    .parse::<i64>()
+                    .map(|ts| timezone::date_of(ts).format("%Y-%m-%d").to_string())
+                    .unwrap_or_default();
+                ListItem::new(vec![
+                    Line::from(item.title().to_string()),
+                    Line::from(Span::styled(
+                        format!(
+                            "  {} · {}{}",
+                            favorited,
+                            App::extract_domain(item.url()).unwrap_or_default(),
+                            if item.status == "1" {
+                                " · archived"
+                            } else {
+                                ""
+                            }
+                        ),
+                        Style::default().fg(OCEANIC_NEXT.base_03),
+                    )),
+                ])
+                .style(style)
+            })
+            .collect();
 
-        let scrollbar = Scrollbar::default()
-            .orientation(ScrollbarOrientation::VerticalRight)
-            .begin_symbol(Some("↑".into()))
-            .end_symbol(Some("↓".into()));
-        let mut scroll_state = ScrollbarState::new(tag_popup_state.filtered_tags.len())
-            .position(tag_popup_state.scroll_offset);
-        f.render_stateful_widget(scrollbar, popup_area, &mut scroll_state);
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Favorites ({}) ", popup_state.items.len()))
+                    .title_bottom(" Enter: open | Esc: close ")
+                    .border_style(Style::new().fg(OCEANIC_NEXT.base_0a))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().bg(Color::Black));
+
+        f.render_widget(list, popup_area);
     }
+}
 
-    if let AppMode::Refreshing(pop) = &app.app_mode {
-        let popup_area = centered_rect(20, 10, f.area());
+fn render_archive_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(popup_state) = &app.archive_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::Archive, area);
         f.render_widget(Clear, popup_area);
 
-        // Create text spans with different styles to create animation effect
-        let text = Text::from(vec![Line::from(vec![Span::styled(
-            &pop.text,
-            Style::new().fg(app.colors.row_fg),
-        )])]);
+        let items: Vec<ListItem> = popup_state
+            .items
+            .iter()
+            .skip(popup_state.scroll_offset)
+            .take(popup_state.visible_items)
+            .enumerate()
+            .map(|(i, item)| {
+                let style = if i + popup_state.scroll_offset == popup_state.selected_index {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else {
+                    Style::default().fg(app.colors.row_fg)
+                };
+                let archived = item
+                    .time_read
+                    .parse::<i64>()
+                    .map(|ts| timezone::date_of(ts).format("%Y-%m-%d").to_string())
+                    .unwrap_or_default();
+                ListItem::new(vec![
+                    Line::from(item.title().to_string()),
+                    Line::from(Span::styled(
+                        format!(
+                            "  {} · {}",
+                            archived,
+                            App::extract_domain(item.url()).unwrap_or_default()
+                        ),
+                        Style::default().fg(OCEANIC_NEXT.base_03),
+                    )),
+                ])
+                .style(style)
+            })
+            .collect();
 
-        let block = Paragraph::new(text)
+        let count = if popup_state.loading.is_some() {
+            format!("{}, loading…", popup_state.items.len())
+        } else if popup_state.complete {
+            popup_state.items.len().to_string()
+        } else {
+            format!("{}+", popup_state.items.len())
+        };
+        let list = List::new(items)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::new().fg(app.colors.footer_border_color))
+                    .title(format!(" Archive ({}) ", count))
+                    .title_bottom(" Enter: open | Esc: close ")
+                    .border_style(Style::new().fg(OCEANIC_NEXT.base_0a))
                     .border_type(BorderType::Rounded),
             )
-            .style(Style::new().bg(Color::Black))
-            .alignment(Alignment::Center);
+            .style(Style::new().bg(Color::Black));
 
-        f.render_widget(block, popup_area);
+        f.render_widget(list, popup_area);
     }
 }
 
-fn centered_rect(percent_x: u16, percent_y: u16, r: Rect) -> Rect {
-    let popup_layout = Layout::default()
-        .direction(Direction::Vertical)
-        .constraints(
-            [
-                Constraint::Percentage((100 - percent_y) / 2),
-                Constraint::Percentage(percent_y),
-                Constraint::Percentage((100 - percent_y) / 2),
-            ]
-            .as_ref(),
-        )
-        .split(r);
+fn render_failed_actions_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(popup_state) = &app.failed_actions_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::FailedActions, area);
+        f.render_widget(Clear, popup_area);
 
-    Layout::default()
-        .direction(Direction::Horizontal)
-        .constraints(
-            [
-                Constraint::Percentage((100 - percent_x) / 2),
-                Constraint::Percentage(percent_x),
-                Constraint::Percentage((100 - percent_x) / 2),
-            ]
-            .as_ref(),
-        )
-        .split(popup_layout[1])[1]
-}
+        let items: Vec<ListItem> = popup_state
+            .failed
+            .iter()
+            .map(|failed| {
+                ListItem::new(vec![
+                    Line::from(failed.title.clone()),
+                    Line::from(Span::styled(
+                        format!("  {}: {}", failed.action.describe(), failed.error),
+                        Style::default().fg(OCEANIC_NEXT.base_08),
+                    )),
+                ])
+                .style(Style::default().fg(app.colors.row_fg))
+            })
+            .collect();
 
-fn render_table(f: &mut Frame, app: &mut App, area: Rect) {
-    let length = 14; //todo calc the value
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        " {} actions failed, the rest were applied ",
+                        popup_state.failed.len()
+                    ))
+                    .title_bottom(" r: retry failed | Esc: dismiss ")
+                    .border_style(Style::new().fg(OCEANIC_NEXT.base_08))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().bg(Color::Black));
 
-    if app.virtual_state.selected().unwrap() >= app.virtual_state.offset() + length {
-        *app.virtual_state.offset_mut() = app.virtual_state.selected().unwrap() + 1 - length;
+        f.render_widget(list, popup_area);
     }
+}
 
-    let offset = app.virtual_state.offset();
-    *app.state.offset_mut() = 0;
-    app.state.select(Some(
-        app.virtual_state.selected().unwrap() - app.virtual_state.offset(),
-    ));
-
-    let selected_style = Style::default().fg(app.colors.selected_style_fg);
+fn render_downloads_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(popup_state) = &app.downloads_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::Downloads, area);
+        f.render_widget(Clear, popup_area);
+        let queue = &app.download_queue;
 
-    let rows = app
-        .items
-        .index(offset..(offset + length))
-        .into_iter()
-        .enumerate()
-        .map(|(x, data)| {
-            let actual_index = x + offset;
-            let is_same_date =
-                actual_index > 0 && data.date() == app.items.get(actual_index - 1).unwrap().date();
-            let multiple_entries_for_date = !is_same_date
-                && actual_index < app.items.len() - 1
-                && data.date() == app.items.get(actual_index + 1).unwrap().date();
-            let is_read = data.tags().any(|x| x == "read");
-            let is_top = data.tags().any(|x| x == "top");
-            let mut base_style = Style::new();
-            if is_read {
-                base_style = base_style.add_modifier(Modifier::DIM);
+        let running = queue.running().iter().map(|job| {
+            ListItem::new(format!("⏳ {} [{}]", job.title, job.kind))
+                .style(Style::default().fg(OCEANIC_NEXT.base_0b))
+        });
+        let pending = queue.pending().iter().enumerate().map(|(i, job)| {
+            let style = if i == popup_state.selected {
+                Style::default().fg(Color::Black).bg(Color::White)
             } else {
-                if is_top {
-                    base_style = base_style.add_modifier(Modifier::BOLD);
-                }
-            }
-            Row::new(vec![
-                Cell::from(Text::from(if !is_same_date {
-                    format!("{}", data.date())
-                } else {
-                    "".to_string()
-                })),
-                Cell::from(Text::from(vec![
-                    Line::from(Span::styled(
-                        format!(
-                            "{}{}",
-                            if is_top { "⭐ " } else { "" },
-                            if !data.title().is_empty() {
-                                data.title()
-                            } else {
-                                data.url()
-                            }
-                        ),
-                        base_style.fg(OCEANIC_NEXT.base_07),
-                    )),
-                    Line::from(vec![
-                        Span::styled(
-                            format!("[{}]: ", data.item_type()),
-                            base_style.fg(Color::Green).add_modifier(Modifier::ITALIC),
-                        ),
-                        Span::styled(
-                            format!("{}", data.tags().join(", ")),
-                            base_style.fg(OCEANIC_NEXT.base_0e),
-                        ),
-                    ]),
-                ])),
-                if actual_index == 0 || actual_index == 1 {
-                    //todo: this creates garbage
-                    let tmp = render_stats(
-                        &app.stats.today_stats,
-                        &app.stats.week_stats,
-                        &app.stats.month_stats,
-                    );
-                    let stats_table: Vec<&str> =
-                        tmp.split("\n").skip(actual_index * 3).take(3).collect();
-                    Cell::from(Text::from(stats_table.join("\n").to_string())).style(selected_style)
-                } else {
-                    if multiple_entries_for_date {
-                        let stats = collect_stats(&app.items.items, actual_index); //todo! accessing items of items
-                        let stats_str = format!(
-                            "░▒▓ Text: {} | PDFs: {} | Vids: {} ▓▒░",
-                            // "Day [  Text: {} | PDFs: {} |  Vids: {}  ]",
-                            stats.articles_total,
-                            stats.pdfs_total,
-                            stats.videos_total
-                        );
-                        Cell::from(Text::from(format!("{}", stats_str)))
-                    } else {
-                        Cell::from(Text::from("".to_string()))
-                    }
-                },
+                Style::default().fg(app.colors.row_fg)
+            };
+            ListItem::new(format!("{:>3}. {} [{}]", i + 1, job.title, job.kind)).style(style)
+        });
+        let failed = queue.failed().iter().map(|(job, err)| {
+            ListItem::new(vec![
+                Line::from(format!("✗ {} [{}]", job.title, job.kind)),
+                Line::from(format!("  {}", err)),
             ])
-            .height(3)
+            .style(Style::default().fg(OCEANIC_NEXT.base_08))
         });
-    let t = Table::new(
-        rows,
-        [
-            // + 1 is for padding.
-            Constraint::Length(app.longest_item_lens.0 + 1),
-            Constraint::Min(app.longest_item_lens.1 + 1),
-            Constraint::Min(app.longest_item_lens.2),
-        ],
-    )
-    .row_highlight_style(selected_style)
-    .highlight_symbol(Text::from(vec![" > ".into(), "".into(), "".into()]))
-    .bg(app.colors.buffer_bg)
-    .highlight_spacing(HighlightSpacing::Always);
-    f.render_stateful_widget(t, area, &mut app.state);
-}
+        let items: Vec<ListItem> = running.chain(pending).chain(failed).collect();
 
-//todo: the thrird column is not needed
-fn constraint_len_calculator<T: TableRow>(items: &[T]) -> (u16, u16, u16) {
-    let name_len = 10;
-    let mut title_len = items
-        .iter()
-        .map(TableRow::title)
-        .flat_map(str::lines)
-        .map(UnicodeWidthStr::width)
-        .max()
-        .unwrap_or(0);
-    let email_len = 40;
+        let title = format!(
+            " Downloads: {} running, {} queued{} ",
+            queue.running().len(),
+            queue.pending().len(),
+            if queue.is_paused() { " (paused)" } else { "" }
+        );
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(title)
+                    .title_bottom(
+                        " J/K: move | x: remove | space: pause/resume | a: queue view | r: retry failed | Esc: close ",
+                    )
+                    .border_style(Style::new().fg(app.colors.footer_border_color))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().bg(Color::Black));
 
-    //todo: dynamic size detection
-    if title_len > 115 {
-        title_len = 115;
+        f.render_widget(list, popup_area);
     }
-
-    #[allow(clippy::cast_possible_truncation)]
-    (name_len as u16, title_len as u16, email_len as u16)
 }
 
-fn render_scrollbar(f: &mut Frame, app: &mut App, area: Rect) {
-    f.render_stateful_widget(
-        Scrollbar::default()
-            .orientation(ScrollbarOrientation::VerticalRight)
-            .begin_symbol(None)
-            .end_symbol(None),
-        area.inner(Margin {
-            vertical: 1,
-            horizontal: 1,
-        }),
-        &mut app.scroll_state,
-    );
+fn render_boundary_preview_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(preview) = &app.boundary_preview_state {
+        let popup_area = app.popup_layouts.area(PopupKind::BoundaryPreview, area);
+        f.render_widget(Clear, popup_area);
+
+        let kept = preview.lines.iter().filter(|(kept, _)| *kept).count();
+        let lines: Vec<Line> = preview
+            .lines
+            .iter()
+            .map(|(kept, line)| {
+                if *kept {
+                    Line::from(format!("  {}", line)).style(Style::default().fg(app.colors.row_fg))
+                } else {
+                    Line::from(format!("- {}", line))
+                        .style(Style::default().fg(OCEANIC_NEXT.base_08))
+                }
+            })
+            .collect();
+        let paragraph = Paragraph::new(lines)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        " {}: keeping {} of {} lines ",
+                        preview.title,
+                        kept,
+                        preview.lines.len()
+                    ))
+                    .title_bottom(
+                        " j/k: scroll | r: reload config.json | X: re-extract | Esc: close ",
+                    )
+                    .border_style(Style::new().fg(app.colors.footer_border_color))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().bg(Color::Black))
+            .scroll((preview.scroll.min(u16::MAX as usize) as u16, 0));
+
+        f.render_widget(paragraph, popup_area);
+    }
 }
 
-fn render_help_popup(f: &mut Frame, app: &mut App, area: Rect) {
-    if let Some(help_state) = &app.help_popup_state {
-        let popup_area = centered_rect(45, 80, area);
+fn render_reader_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(reader) = &app.reader_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::Reader, area);
         f.render_widget(Clear, popup_area);
 
-        let text = Text::from(
-            help_state
-                .content
-                .lines()
-                .map(|line| Line::from(Span::styled(line, Style::default().fg(app.colors.row_fg))))
-                .collect::<Vec<_>>(),
-        );
-
-        let help_widget = Paragraph::new(text)
+        let (first, last) = reader.selection();
+        let lines: Vec<Line> = reader
+            .lines
+            .iter()
+            .enumerate()
+            .skip(reader.scroll_offset)
+            .take(reader.visible_items)
+            .map(|(i, line)| {
+                let style = if i == reader.cursor {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else if reader.anchor.is_some() && (first..=last).contains(&i) {
+                    Style::default().fg(Color::Black).bg(OCEANIC_NEXT.base_0a)
+                } else {
+                    Style::default().fg(app.colors.row_fg)
+                };
+                Line::from(line.as_str()).style(style)
+            })
+            .collect();
+        let saved = app
+            .items
+            .items
+            .iter()
+            .find(|item| item.item_id == reader.item_id)
+            .map_or(0, |item| app.highlights.of(item).len());
+        let paragraph = Paragraph::new(lines)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title(" GetPocket TUI Help ")
-                    .border_style(Style::new().fg(app.colors.header_fg))
+                    .title(format!(" {}: {} highlights ", reader.title, saved))
+                    .title_bottom(format!(
+                        " j/k: move | v: select | y: save highlight{} | Esc: close ",
+                        reader.resume.map_or(String::new(), |line| format!(
+                            " | c: continue at {}%",
+                            progress::ReadingProgress {
+                                line,
+                                lines: reader.lines.len()
+                            }
+                            .percent()
+                        ))
+                    ))
+                    .border_style(Style::new().fg(app.colors.footer_border_color))
                     .border_type(BorderType::Rounded),
             )
-            .style(Style::new().bg(Color::Black))
-            .alignment(Alignment::Left);
+            .style(Style::new().bg(Color::Black));
 
-        f.render_widget(help_widget, popup_area);
+        f.render_widget(paragraph, popup_area);
     }
 }
 
-fn render_error_popup(f: &mut Frame, message: &str, area: Rect, colors: &TableColors) {
-    let popup_area = centered_rect(60, 20, area);
+fn render_share_popup(f: &mut Frame, app: &App, area: Rect) {
+    let Some(share) = &app.share_popup_state else {
+        return;
+    };
+    let Some(item) = app
+        .virtual_state
+        .selected()
+        .and_then(|idx| app.items.get(idx))
+    else {
+        return;
+    };
+    let popup_area = app.popup_layouts.area(PopupKind::Share, area);
     f.render_widget(Clear, popup_area);
 
-    let text = Text::from(vec![
-        Line::from(vec![Span::styled(
-            "Error",
-            Style::default()
-                .fg(OCEANIC_NEXT.base_08)
-                .add_modifier(Modifier::BOLD),
-        )]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            message,
-            Style::default().fg(colors.row_fg),
-        )]),
-        Line::from(""),
-        Line::from(vec![Span::styled(
-            "Press ESC to dismiss",
-            Style::default().fg(OCEANIC_NEXT.base_03),
-        )]),
-    ]);
+    let items: Vec<ListItem> = app
+        .config
+        .share_templates
+        .iter()
+        .enumerate()
+        .map(|(i, template)| {
+            let style = if i == share.selected_index {
+                Style::default().fg(Color::Black).bg(Color::White)
+            } else {
+                Style::default().fg(app.colors.row_fg)
+            };
+            let preview = template.render(item).replace('\n', " ");
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{} {:<10} ", i + 1, template.name),
+                    Style::default().add_modifier(Modifier::BOLD),
+                ),
+                Span::raw(preview),
+            ]))
+            .style(style)
+        })
+        .collect();
 
-    let error_widget = Paragraph::new(text)
+    let list = List::new(items)
         .block(
             Block::default()
                 .borders(Borders::ALL)
-                .border_style(Style::new().fg(OCEANIC_NEXT.base_08))
+                .title(" Copy for sharing ")
+                .title_bottom(" j/k: move | Enter/1-9: copy | Esc: close ")
+                .border_style(Style::new().fg(app.colors.footer_border_color))
                 .border_type(BorderType::Rounded),
         )
-        .style(Style::new().bg(Color::Black))
-        .alignment(Alignment::Center);
+        .style(Style::new().bg(Color::Black));
 
-    f.render_widget(error_widget, popup_area);
+    f.render_widget(list, popup_area);
 }
 
-fn render_rss_feed_popup(f: &mut Frame, app: &mut App, area: Rect) {
-    if let Some(popup_state) = &app.rss_feed_popup_state {
-        let popup_area = centered_rect(80, 80, area);
+fn render_qr_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(qr_state) = &app.qr_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::Qr, area);
         f.render_widget(Clear, popup_area);
-        // Calculate areas for main content and status bar
-        let chunks = Layout::vertical([
-            Constraint::Min(3),    // Main content
-            Constraint::Length(1), // Status bar
-        ])
-        .split(popup_area);
-        // Group items by source and count them
-        let mut source_counts = std::collections::HashMap::new();
-        for item in &popup_state.items {
-            *source_counts.entry(&item.source).or_insert(0) += 1;
-        }
 
-        // Keep track of which sources we've seen while rendering
-        let mut seen_sources = std::collections::HashSet::new();
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", qr_state.title))
+            .title_bottom(format!(" {} | Esc: close ", qr_state.url))
+            .border_style(Style::new().fg(app.colors.footer_border_color))
+            .border_type(BorderType::Rounded)
+            .style(Style::new().bg(Color::Black));
+        let inner = block.inner(popup_area);
+        f.render_widget(block, popup_area);
 
-        let items: Vec<ListItem> = popup_state
-            .items
-            .iter()
-            .skip(popup_state.scroll_offset)
-            .take(popup_state.visible_items)
-            .enumerate()
-            .map(|(i, item)| {
-                // Show source info only if we haven't seen this source yet
-                let source_column = if !seen_sources.contains(&item.source) {
-                    seen_sources.insert(&item.source);
-                    let count = source_counts.get(&item.source).unwrap_or(&0);
-                    format!(" {} ({})", item.source, count)
-                } else {
-                    String::new()
-                };
+        // scanners expect dark modules on a light background, whatever the terminal theme
+        let width = qr_state
+            .lines
+            .first()
+            .map_or(0, |line| line.chars().count()) as u16;
+        let [code_area] = Layout::horizontal([Constraint::Length(width)])
+            .flex(layout::Flex::Center)
+            .areas(inner);
+        let [code_area] = Layout::vertical([Constraint::Length(qr_state.lines.len() as u16)])
+            .flex(layout::Flex::Center)
+            .areas(code_area);
+        let code = Paragraph::new(
+            qr_state
+                .lines
+                .iter()
+                .map(|line| Line::from(line.as_str()))
+                .collect::<Vec<_>>(),
+        )
+        .style(Style::new().fg(Color::Black).bg(Color::White));
+        f.render_widget(code, code_area);
+    }
+}
 
-                let date_and_title = if let Some(pub_date) = &item.pub_date {
-                    vec![
-                        Span::styled(
-                            format!("{:<10}", &pub_date[0..10]),
-                            Style::default().fg(OCEANIC_NEXT.base_03), // Gray for date
-                        ),
-                        Span::raw(": "),
-                        Span::styled(
-                            &item.title,
-                            Style::default().fg(OCEANIC_NEXT.base_05), // Default text color
-                        ),
-                    ]
-                } else {
-                    vec![
-                        Span::styled(
-                            format!("{:<10}", "unknown"),
-                            Style::default().fg(OCEANIC_NEXT.base_03),
-                        ),
-                        Span::raw(": "),
-                        Span::styled(&item.title, Style::default().fg(OCEANIC_NEXT.base_05)),
-                    ]
-                };
+fn render_summary_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(summary_state) = &app.summary_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::Summary, area);
+        f.render_widget(Clear, popup_area);
 
-                let source_span = Span::styled(
-                    format!("{:<25}", source_column),
-                    Style::default().fg(OCEANIC_NEXT.base_0d), // Distinct color for source
-                );
+        let paragraph = Paragraph::new(summary_state.summary.as_str())
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Summary: {} ", summary_state.title))
+                    .title_bottom(" j/k: scroll | r: summarize again | Esc: close ")
+                    .border_style(Style::new().fg(app.colors.footer_border_color))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().fg(app.colors.row_fg).bg(Color::Black))
+            .wrap(Wrap { trim: false })
+            .scroll((summary_state.scroll.min(u16::MAX as usize) as u16, 0));
 
-                let content = Line::from(
-                    [
-                        vec![
-                            source_span,
-                            Span::raw("│ "), // Table separator
-                        ],
-                        date_and_title,
-                    ]
-                    .concat(),
-                );
+        f.render_widget(paragraph, popup_area);
+    }
+}
 
-                let style = if i + popup_state.scroll_offset == popup_state.selected_index {
+fn render_logs_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(logs_state) = &app.logs_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::Logs, area);
+        f.render_widget(Clear, popup_area);
+
+        let items: Vec<ListItem> = logs_state
+            .entries
+            .iter()
+            .skip(logs_state.scroll_offset)
+            .take(logs_state.visible_items)
+            .enumerate()
+            .map(|(i, entry)| {
+                let level_color = match entry.level {
+                    log::Level::Error => OCEANIC_NEXT.base_08,
+                    log::Level::Warn => OCEANIC_NEXT.base_0a,
+                    _ => OCEANIC_NEXT.base_04,
+                };
+                let line = Line::from(vec![
+                    Span::styled(
+                        format!("{} ", entry.time),
+                        Style::default().fg(OCEANIC_NEXT.base_03),
+                    ),
+                    Span::styled(
+                        format!("{:<5} ", entry.level),
+                        Style::default().fg(level_color),
+                    ),
+                    Span::raw(entry.message.as_str()),
+                ]);
+                let style = if i + logs_state.scroll_offset == logs_state.selected_index {
                     Style::default().fg(Color::Black).bg(Color::White)
                 } else {
-                    Style::default()
+                    Style::default().fg(app.colors.row_fg)
                 };
-
-                ListItem::new(vec![content]).style(style)
+                ListItem::new(line).style(style)
             })
             .collect();
 
-        let feed_list = List::new(items)
+        let list = List::new(items)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title(" RSS Feeds ")
+                    .title(format!(
+                        " Logs: {} and above ({}) ",
+                        logs_state.min_level,
+                        logs_state.entries.len()
+                    ))
+                    .title_bottom(" l: level | y/Y: copy entry/all | r: reload | Esc: close ")
                     .border_style(Style::new().fg(app.colors.footer_border_color))
                     .border_type(BorderType::Rounded),
             )
             .style(Style::new().bg(Color::Black));
 
-        f.render_widget(feed_list, popup_area);
-
-        let scrollbar = Scrollbar::default()
-            .orientation(ScrollbarOrientation::VerticalRight)
-            .begin_symbol(Some("↑".into()))
-            .end_symbol(Some("↓".into()));
-
-        let mut scroll_state =
-            ScrollbarState::new(popup_state.items.len()).position(popup_state.scroll_offset);
+        f.render_widget(list, popup_area);
+    }
+}
 
-        f.render_stateful_widget(scrollbar, popup_area, &mut scroll_state);
-        if popup_state.show_description {
-            if let Some(selected_item) = popup_state.items.get(popup_state.selected_index) {
-                let desc_popup_area = centered_rect(70, 40, f.size());
-                f.render_widget(Clear, desc_popup_area);
-
-                let description = selected_item
-                    .description
-                    .as_deref()
-                    .unwrap_or("No description available");
-
-                // Wrap text to fit popup width
-                let max_width = (desc_popup_area.width as usize).saturating_sub(4);
-                // let wrapped_text = textwrap::fill(description, max_width);
-
-                let wrapped_text = description
-                    .split_whitespace()
-                    .fold((String::new(), 0), |(mut text, len), word| {
-                        if len + word.len() + 1 > max_width {
-                            text.push('\n');
-                            (text + word, word.len())
-                        } else if text.is_empty() {
-                            (word.to_string(), word.len())
-                        } else {
-                            (text + " " + word, len + word.len() + 1)
-                        }
-                    })
-                    .0;
+fn render_sync_diff_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(diff_state) = &app.sync_diff_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::SyncDiff, area);
+        f.render_widget(Clear, popup_area);
 
-                let text = Text::from(vec![
-                    Line::from(vec![
-                        Span::styled("Title: ", Style::default().fg(OCEANIC_NEXT.base_0d)),
-                        Span::styled(
-                            &selected_item.title,
-                            Style::default().fg(OCEANIC_NEXT.base_05),
-                        ),
-                    ]),
-                    Line::from(""),
-                    Line::from(vec![
-                        Span::styled("Source: ", Style::default().fg(OCEANIC_NEXT.base_0d)),
-                        Span::styled(
-                            &selected_item.source,
-                            Style::default().fg(OCEANIC_NEXT.base_05),
-                        ),
-                    ]),
-                    Line::from(""),
-                    Line::from(vec![Span::styled(
-                        "Description:",
-                        Style::default().fg(OCEANIC_NEXT.base_0d),
-                    )]),
-                    Line::from(""),
-                    Line::from(vec![Span::styled(
-                        wrapped_text,
-                        Style::default().fg(OCEANIC_NEXT.base_05),
-                    )]),
+        let items: Vec<ListItem> = diff_state
+            .diff
+            .changes
+            .iter()
+            .skip(diff_state.scroll_offset)
+            .take(diff_state.visible_items)
+            .enumerate()
+            .map(|(i, change)| {
+                let (marker, color, title, detail) = match change {
+                    syncdiff::Change::Added { title, .. } => {
+                        ("+", OCEANIC_NEXT.base_0b, title, String::new())
+                    }
+                    syncdiff::Change::Removed { title } => {
+                        ("-", OCEANIC_NEXT.base_08, title, String::new())
+                    }
+                    syncdiff::Change::Retagged {
+                        title,
+                        added,
+                        removed,
+                    } => (
+                        "~",
+                        OCEANIC_NEXT.base_0a,
+                        title,
+                        added
+                            .iter()
+                            .map(|tag| format!(" +{}", tag))
+                            .chain(removed.iter().map(|tag| format!(" -{}", tag)))
+                            .collect(),
+                    ),
+                };
+                let line = Line::from(vec![
+                    Span::styled(format!("{} ", marker), Style::default().fg(color)),
+                    Span::raw(title.as_str()),
+                    Span::styled(detail, Style::default().fg(OCEANIC_NEXT.base_0e)),
                 ]);
+                let style = if i + diff_state.scroll_offset == diff_state.selected_index {
+                    Style::default().fg(Color::Black).bg(Color::White)
+                } else {
+                    Style::default().fg(app.colors.row_fg)
+                };
+                ListItem::new(line).style(style)
+            })
+            .collect();
 
-                let description_widget = Paragraph::new(text)
-                    .block(
-                        Block::default()
-                            .borders(Borders::ALL)
-                            .title(" Article Preview ")
-                            .border_style(Style::new().fg(app.colors.footer_border_color))
-                            .border_type(BorderType::Rounded),
-                    )
-                    .style(Style::new().bg(Color::Black))
-                    .wrap(Wrap { trim: true })
-                    .scroll((0, 0));
-
-                f.render_widget(description_widget, desc_popup_area);
-            }
-        }
-        if let Some((message, timestamp)) = &popup_state.status_message {
-            if timestamp.elapsed() < Duration::from_secs(5) {
-                // Show message for 5 seconds
-                let status_text = Text::from(Line::from(vec![Span::styled(
-                    message,
-                    Style::default().fg(OCEANIC_NEXT.base_0b), // Green for success
-                )]));
-
-                let status_widget = Paragraph::new(status_text)
-                    .style(Style::default().bg(Color::Black))
-                    .alignment(Alignment::Center);
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(format!(" Since last sync: {} ", diff_state.diff.summary()))
+                    .title_bottom(" Enter: jump to new item | Esc: close ")
+                    .border_style(Style::new().fg(OCEANIC_NEXT.base_0b))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().bg(Color::Black));
 
-                f.render_widget(status_widget, chunks[1]);
-            }
-        }
+        f.render_widget(list, popup_area);
     }
 }
 
-fn render_footer(f: &mut Frame, app: &App, area: Rect) {
-    match &app.app_mode {
-        AppMode::Initialize => panic!("Should not get here!"),
-        AppMode::Normal
-        | AppMode::MulticharNormalModeEnter(_)
-        | AppMode::Refreshing(_)
-        | AppMode::Error(_) => {
-            let is_filtered = app.selected_tag_filter.is_some()
-                || app.item_type_filter != ItemTypeFilter::All
-                || app.domain_filter.is_some()
-                || app.active_search_filter.is_some();
-
-            let mut spans = if is_filtered {
-                vec![Span::raw("[Filter]")]
-            } else {
-                vec![Span::raw(INFO_TEXT)]
-            };
-
-            if let Some(search) = &app.active_search_filter {
-                spans.extend_from_slice(&[Span::raw(" | /"), Span::raw(search)]);
-            }
-            if let Some(tag) = &app.selected_tag_filter {
-                spans.extend_from_slice(&[Span::raw(" | Tag: "), Span::raw(tag)]);
-            }
-            if let Some(domain) = &app.domain_filter {
-                spans.extend_from_slice(&[Span::raw(" | Site : "), Span::raw(domain)]);
-            }
-            if app.item_type_filter != ItemTypeFilter::All {
-                let filter_text = match app.item_type_filter {
-                    ItemTypeFilter::All => unreachable!(),
-                    ItemTypeFilter::Article => "Articles",
-                    ItemTypeFilter::Video => "Videos",
-                    ItemTypeFilter::PDF => "PDFs",
-                };
-                spans.extend_from_slice(&[Span::raw(" | Doc type : "), Span::raw(filter_text)]);
-            }
+fn render_conflicts_popup(f: &mut Frame, app: &App, area: Rect) {
+    if let Some(conflicts_state) = &app.conflicts_popup_state {
+        let popup_area = app.popup_layouts.area(PopupKind::Conflicts, area);
+        f.render_widget(Clear, popup_area);
 
-            if app.item_type_filter != ItemTypeFilter::All
-                || app.selected_tag_filter.is_some()
-                || app.active_search_filter.is_some()
-            {
-                let text = format!("[Showing {} items]", app.items.len());
-                spans.extend_from_slice(&[Span::raw(" ('ESC` to clear) | "), Span::raw(text)]);
-            }
-            if let Ok(items) = app.rss_feed_state.items.lock() {
-                if !items.is_empty() {
-                    spans.extend_from_slice(&[
-                        Span::raw(" | "),
+        let dim = Style::default().fg(OCEANIC_NEXT.base_03);
+        let items: Vec<ListItem> = conflicts_state
+            .conflicts
+            .iter()
+            .enumerate()
+            .map(|(i, conflict)| {
+                let tags = |state: &conflicts::ItemState| state.tags.iter().join(", ");
+                let mut lines = vec![Line::from(conflict.remote.title.clone())];
+                if conflict.local.title != conflict.remote.title {
+                    lines.push(Line::from(vec![
+                        Span::styled("  title  here: ", dim),
+                        Span::raw(conflict.local.title.clone()),
+                    ]));
+                }
+                if conflict.local.tags != conflict.remote.tags {
+                    lines.push(Line::from(vec![
+                        Span::styled("  tags   here: ", dim),
                         Span::styled(
-                            " RSS updates ",
-                            Style::default()
-                                .bg(OCEANIC_NEXT.base_0e) // Pink background
-                                .fg(OCEANIC_NEXT.base_00) // Dark text for contrast
-                                .add_modifier(Modifier::BOLD),
+                            tags(&conflict.local),
+                            Style::default().fg(OCEANIC_NEXT.base_0e),
                         ),
-                    ]);
+                        Span::styled("  Pocket: ", dim),
+                        Span::styled(
+                            tags(&conflict.remote),
+                            Style::default().fg(OCEANIC_NEXT.base_0e),
+                        ),
+                    ]));
                 }
-            }
-            let info_footer = Paragraph::new(Line::from(spans))
-                .style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg))
-                .alignment(if is_filtered {
-                    Alignment::Left
+                let style = if i == conflicts_state.selected_index {
+                    Style::default().fg(Color::Black).bg(Color::White)
                 } else {
-                    Alignment::Center
-                })
-                .block(
-                    Block::default()
-                        .borders(Borders::ALL)
-                        .border_style(Style::new().fg(app.colors.footer_border_color))
-                        .border_type(BorderType::Double),
-                );
-            f.render_widget(info_footer, area);
-        }
-        AppMode::Search(search) => {
-            let mut final_string = "/".to_string();
-            final_string.push_str(&search.search);
-
-            let mut textarea = TextArea::new(vec![final_string]);
-            textarea.set_style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg));
-            textarea.set_block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .border_style(Style::new().fg(app.colors.footer_border_color))
-                    .border_type(BorderType::Rounded),
-            );
-            textarea.move_cursor(tui_textarea::CursorMove::End);
-            f.render_widget(&textarea, area);
-        }
-        AppMode::Confirmation(_) => {
-            let mut textarea = TextArea::default();
-            textarea.set_style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg));
-            textarea.set_block(
-                Block::default()
-                    .borders(Borders::ALL)
-                    .title("Delete ? ['y' or 'd' - to confirm] ")
-                    .border_style(Style::new().fg(app.colors.footer_border_color))
-                    .border_type(BorderType::Rounded),
-            );
-            textarea.move_cursor(tui_textarea::CursorMove::End);
-            f.render_widget(&textarea, area);
-        }
-        AppMode::CommandEnter(x) => {
-            let area_with_margin = area.inner(Margin::new(1, 1));
+                    Style::default().fg(app.colors.row_fg)
+                };
+                ListItem::new(lines).style(style)
+            })
+            .collect();
 
-            // Create the base TextArea for input
-            let input_text = format!("{}{}", x.prompt, x.current_enter);
-            let mut textarea = TextArea::new(vec![input_text]);
-            textarea.set_style(Style::new().fg(app.colors.row_fg).bg(app.colors.buffer_bg));
-            textarea.set_block(
+        let mut list_state =
+            ListState::default().with_selected(Some(conflicts_state.selected_index));
+        let list = List::new(items)
+            .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .border_style(Style::new().fg(app.colors.footer_border_color))
+                    .title(format!(
+                        " Changed here and elsewhere ({}) ",
+                        conflicts_state.conflicts.len()
+                    ))
+                    .title_bottom(" l: keep local | r: keep Pocket | m: merge tags | Esc: keep all from Pocket ")
+                    .border_style(Style::new().fg(OCEANIC_NEXT.base_08))
                     .border_type(BorderType::Rounded),
-            );
-
-            let prompt_len = x.prompt.len();
-            let cursor_pos = (x.cursor_pos + prompt_len).try_into().unwrap();
-            textarea.move_cursor(CursorMove::Jump(0, cursor_pos));
-
-            // Render the base TextArea
-            f.render_widget(&textarea, area);
-
-            // If there's a suggestion, render it as a separate dimmed text
-            if let Some(suggestion) = &x.current_suggestion {
-                // let suggestion = TextSuggestion {
-                //     completion: "Popa".to_string(),
-                //     full_text: "Popa!".to_string(),
-                // };
-                let suggestion_x = (prompt_len + x.current_enter.len() + 1) as u16;
-                if suggestion_x < area_with_margin.width {
-                    let suggestion_area = Rect::new(
-                        area_with_margin.x + suggestion_x,
-                        area_with_margin.y,
-                        area_with_margin.width - suggestion_x,
-                        1,
-                    );
-
-                    let suggestion_text = Paragraph::new(suggestion.completion.as_str()).style(
-                        Style::new()
-                            .fg(OCEANIC_NEXT.base_03)
-                            .add_modifier(Modifier::DIM),
-                    );
+            )
+            .style(Style::new().bg(Color::Black));
 
-                    f.render_widget(suggestion_text, suggestion_area);
-                }
-            }
-        }
+        f.render_stateful_widget(list, popup_area, &mut list_state);
     }
 }
 
-fn render_domain_stats_popup(f: &mut Frame, app: &mut App, area: Rect) {
-    if let Some(popup_state) = &app.domain_stats_popup_state {
-        let popup_area = centered_rect(60, 60, area);
-        f.render_widget(Clear, popup_area);
+fn render_context_menu(f: &mut Frame, app: &App) {
+    if let Some(menu) = &app.context_menu_state {
+        let menu_area = menu.area(f.area());
+        f.render_widget(Clear, menu_area);
 
-        let items: Vec<ListItem> = popup_state
-            .stats
+        let items: Vec<ListItem> = CONTEXT_MENU
             .iter()
-            .skip(popup_state.scroll_offset)
-            .take(popup_state.visible_items)
             .enumerate()
-            .map(|(i, (domain, count))| {
-                let content = format!("{:<40} {}", domain, count);
-                let style = if i + popup_state.scroll_offset == popup_state.selected_index {
+            .map(|(i, (_, label))| {
+                let style = if i == menu.selected_index {
                     Style::default().fg(Color::Black).bg(Color::White)
                 } else {
                     Style::default().fg(app.colors.row_fg)
                 };
-                ListItem::new(content).style(style)
+                ListItem::new(*label).style(style)
             })
             .collect();
 
-        let title = " Domain/Author Statistics ";
-        let stats_list = List::new(items)
+        let list = List::new(items)
             .block(
                 Block::default()
                     .borders(Borders::ALL)
-                    .title(title)
                     .border_style(Style::new().fg(app.colors.footer_border_color))
                     .border_type(BorderType::Rounded),
             )
             .style(Style::new().bg(Color::Black));
 
-        f.render_widget(stats_list, popup_area);
-
-        let scrollbar = Scrollbar::default()
-            .orientation(ScrollbarOrientation::VerticalRight)
-            .begin_symbol(Some("↑".into()))
-            .end_symbol(Some("↓".into()));
-        let mut scroll_state =
-            ScrollbarState::new(popup_state.stats.len()).position(popup_state.scroll_offset);
-        f.render_stateful_widget(scrollbar, popup_area, &mut scroll_state);
+        f.render_widget(list, menu_area);
     }
 }
+
 #[cfg(test)]
-mod tests {}
+mod tests {
+    use super::*;
+
+    #[test]
+    fn safe_mode_starts_with_a_broken_queue() {
+        let dir = tempfile::tempdir().unwrap();
+        let queue_file = dir.path().join(queue::QUEUE_FILE);
+        fs::write(&queue_file, [0xff, 0xfe, b'\n']).unwrap();
+
+        let client = GetPocketSync::new_dry_run().unwrap();
+        let mut app = App::new(Vec::new(), client, TotalStats::new());
+        app.safe_mode = true;
+        app.load_queue_and_pdf_index(&queue_file);
+        app.reading_queue.toggle("1").unwrap();
+        assert_eq!(fs::read(&queue_file).unwrap(), [0xff, 0xfe, b'\n']);
+
+        // outside of safe mode the broken queue starts empty and the next change replaces it
+        app.safe_mode = false;
+        app.load_queue_and_pdf_index(&queue_file);
+        assert_eq!(app.reading_queue.position("1"), None);
+        app.reading_queue.toggle("1").unwrap();
+        assert_eq!(fs::read_to_string(&queue_file).unwrap(), "1");
+    }
+
+    #[test]
+    fn pastes_links_only() {
+        assert_eq!(
+            pasted_urls(" https://a.org/1 \n\nhttp://b.org\n").unwrap(),
+            ["https://a.org/1", "http://b.org"]
+        );
+        assert!(pasted_urls("https://a.org\nnot a link").is_none());
+        assert!(pasted_urls("ftp://a.org").is_none());
+        assert!(pasted_urls("  \n").is_none());
+    }
+
+    #[test]
+    fn archive_pages_in_pocket_order() {
+        let page = |ids: std::ops::Range<usize>| storage::Pocket {
+            list: ids
+                .map(|id| {
+                    let mut item = tutorial::sample_item(id, 0, "Old", "https://a.org", &[], None);
+                    item.sort_id = id as i64;
+                    (id.to_string(), serde_json::to_value(item).unwrap())
+                })
+                .collect(),
+            ..Default::default()
+        };
+        let mut archive = ArchivePopupState::new(10);
+        assert!(archive.needs_page());
+        archive.add_page(page(0..30));
+        assert_eq!(archive.offset, 30);
+        assert!(!archive.complete);
+        assert_eq!(archive.items[9].item_id, "9");
+        assert!(!archive.needs_page());
+
+        archive.move_selection(25);
+        assert!(archive.needs_page());
+        archive.add_page(page(30..35));
+        assert_eq!(archive.items.len(), 35);
+        assert!(archive.complete);
+        assert!(!archive.needs_page());
+    }
+
+    #[test]
+    fn type_ahead_prefers_prefix_over_fuzzy() {
+        let titles = [
+            "Rust borrow checker",
+            "Go modules",
+            "Rustls release",
+            "Ruby",
+        ];
+        let title = |index: usize| titles[index];
+        assert_eq!(type_ahead_match("rust", 4, 1, title), Some(2));
+        assert_eq!(type_ahead_match("RU", 4, 3, title), Some(3));
+        assert_eq!(type_ahead_match("rust", 4, 3, title), Some(0));
+        assert_eq!(type_ahead_match("rbc", 4, 0, title), Some(0));
+        assert_eq!(type_ahead_match("mod", 4, 2, title), Some(1));
+        assert_eq!(type_ahead_match("xyz", 4, 0, title), None);
+    }
+
+    #[test]
+    fn narrowing_keeps_only_current_matches() {
+        let mut items = FilteredItems::new(vec!["rust", "rustls", "ruby", "trust"]);
+        items.apply_filter(|item| item.contains("ru"));
+        assert_eq!(items.len(), 4);
+        items.narrow_filter(|item| item.contains("rus"));
+        assert_eq!(
+            items.iter().copied().collect::<Vec<_>>(),
+            ["rust", "rustls", "trust"]
+        );
+        items.narrow_filter(|item| item.starts_with("rust"));
+        assert_eq!(
+            items.iter().copied().collect::<Vec<_>>(),
+            ["rust", "rustls"]
+        );
+
+        items.clear_filter();
+        items.narrow_filter(|item| item.contains("by"));
+        assert_eq!(items.iter().copied().collect::<Vec<_>>(), ["ruby"]);
+    }
+
+    #[test]
+    fn tag_popup_keeps_untagged_on_top_in_both_orders() {
+        let tags = vec![
+            ("rust".to_string(), 7),
+            (UNTAGGED.to_string(), 1),
+            ("go".to_string(), 5),
+        ];
+        let mut popup = TagPopupState::new(tags, 10);
+        let order = |popup: &TagPopupState| {
+            popup
+                .filtered_tags
+                .iter()
+                .map(|(tag, _)| tag.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(order(&popup), [UNTAGGED, "go", "rust"]);
+        popup.toggle_sort();
+        assert_eq!(order(&popup), [UNTAGGED, "rust", "go"]);
+    }
+
+    #[test]
+    fn view_summary_counts_types_age_and_reading_time() {
+        let item = |url: &str, time_added: &str, word_count: &str| {
+            serde_json::from_value::<PocketItem>(json!({"item_id": "1", "status": "0",
+                "resolved_url": url, "time_added": time_added, "time_updated": "0",
+                "time_read": "0", "time_favorited": "0", "sort_id": 0,
+                "listen_duration_estimate": 0, "word_count": word_count}))
+            .unwrap()
+        };
+        let items = [
+            item("https://lwn.net/a", "86400", "450"),
+            item("https://lwn.net/b", "172800", "3000"),
+            item("https://youtube.com/watch?v=1", "259200", ""),
+            item("https://arxiv.org/paper.pdf", "345600", "0"),
+        ];
+        let summary = ViewSummary::of(items.iter());
+        assert_eq!((summary.articles, summary.videos, summary.pdfs), (2, 1, 1));
+        assert_eq!(summary.oldest_added, Some(86400));
+        assert_eq!(summary.reading_mins, 2 + 14);
+        assert_eq!(
+            summary.footer_text(86400 * 11),
+            "2 art · 1 vid · 1 pdf | oldest 10d | ~16m to read"
+        );
+        assert_eq!(
+            ViewSummary::default().footer_text(0),
+            "0 art · 0 vid · 0 pdf"
+        );
+    }
+
+    #[test]
+    fn groups_keep_their_order_and_fold() {
+        let mut items = FilteredItems::new(vec!["lwn", "hn", "lobsters", "hnrss", "lwn.net"]);
+        let first_letter = |item: &&str| item[..1].to_string();
+        let sizes = items.group(first_letter, |_| false);
+        assert_eq!(
+            items.iter().copied().collect::<Vec<_>>(),
+            ["lwn", "lobsters", "lwn.net", "hn", "hnrss"]
+        );
+        assert_eq!((sizes["l"], sizes["h"]), (3, 2));
+
+        items.clear_filter();
+        let sizes = items.group(first_letter, |key| key == "l");
+        assert_eq!(
+            items.iter().copied().collect::<Vec<_>>(),
+            ["lwn", "hn", "hnrss"]
+        );
+        assert_eq!(sizes["l"], 3);
+    }
+
+    #[test]
+    fn context_menu_stays_on_screen() {
+        let frame = Rect::new(0, 0, 80, 24);
+        let menu = ContextMenuState {
+            column: 75,
+            row: 22,
+            selected_index: 0,
+        };
+        let area = menu.area(frame);
+        assert_eq!((area.right(), area.bottom()), (80, 24));
+        assert_eq!(
+            menu.action_at(frame, area.x + 1, area.y + 2),
+            Some(ContextAction::Delete)
+        );
+        assert_eq!(menu.action_at(frame, area.x + 1, area.y), None);
+        assert_eq!(menu.action_at(frame, 10, 10), None);
+    }
+
+    #[test]
+    fn footer_chips_follow_the_alignment() {
+        let spans = [
+            Span::raw("[Filter]"),
+            Span::raw(" | "),
+            Span::raw("Tag: "),
+            Span::raw("rust"),
+        ];
+        let chips = [(2..4, FooterChip::Tag)];
+        let area = Rect::new(1, 5, 40, 1);
+        assert_eq!(
+            footer_chip_areas(&spans, &chips, Alignment::Left, area),
+            [(Rect::new(12, 5, 9, 1), FooterChip::Tag)]
+        );
+        assert_eq!(
+            footer_chip_areas(&spans, &chips, Alignment::Center, area),
+            [(Rect::new(22, 5, 9, 1), FooterChip::Tag)]
+        );
+    }
+
+    #[test]
+    fn clicks_map_to_the_rows_drawn() {
+        let layout = TableLayout {
+            area: Rect::new(0, 2, 80, 20),
+            first_item: 10,
+            rows: 6,
+        };
+        assert_eq!(layout.item_at(1), None);
+        assert_eq!(layout.item_at(2), Some(10));
+        assert_eq!(layout.item_at(4), Some(10));
+        assert_eq!(layout.item_at(5), Some(11));
+        assert_eq!(layout.item_at(19), Some(15));
+        // the last two lines are below the last full row
+        assert_eq!(layout.item_at(20), None);
+        assert_eq!(layout.item_at(22), None);
+    }
+
+    #[test]
+    fn highlights_matches_ignoring_case() {
+        assert_eq!(
+            match_ranges("Rust and rusty RUST", "rust"),
+            [0..4, 9..13, 15..19]
+        );
+        assert_eq!(
+            match_ranges("Straße in Köln", "köln").first(),
+            Some(&(11..16))
+        );
+        assert!(match_ranges("Rust", "").is_empty());
+
+        let spans = highlighted("Why Rust", Some("rust"), false, Style::default());
+        assert_eq!(
+            spans
+                .iter()
+                .map(|span| span.content.as_ref())
+                .collect::<Vec<_>>(),
+            ["Why ", "Rust"]
+        );
+        assert_eq!(
+            highlighted("Go", Some("rust"), false, Style::default()).len(),
+            1
+        );
+    }
+    #[test]
+    fn title_column_fits_wide_titles() {
+        let items = [
+            tutorial::sample_item(1, 0, "Rust 入門 👨‍👩‍👧", "https://a.jp", &[], None),
+            tutorial::sample_item(2, 0, "Go", "https://go.dev", &[], None),
+        ];
+        assert_eq!(constraint_len_calculator(&items).1, 12);
+        let long = tutorial::sample_item(3, 0, &"長".repeat(100), "https://a.jp", &[], None);
+        assert_eq!(
+            constraint_len_calculator(&[long]).1 as usize,
+            MAX_TITLE_WIDTH
+        );
+    }
+
+    #[test]
+    fn source_filter_matches_authors_of_any_site() {
+        use tutorial::sample_item;
+        let authors = |names: &[&str]| Some(names.iter().map(|name| name.to_string()).collect());
+        let medium = sample_item(
+            1,
+            0,
+            "Post",
+            "https://medium.com/@a/post",
+            &[],
+            authors(&["Ann"]),
+        );
+        let video = sample_item(
+            2,
+            0,
+            "Talk",
+            "https://youtube.com/watch?v=1",
+            &[],
+            authors(&["YT:Ann"]),
+        );
+        let article = sample_item(3, 0, "News", "https://www.lwn.net/1", &[], None);
+
+        let source = SourceFilter::of(&medium).unwrap();
+        assert_eq!(source, SourceFilter::Authors(vec!["Ann".to_string()]));
+        assert!(source.matches(&medium));
+        assert!(!source.matches(&video));
+        assert!(SourceFilter::of(&video).unwrap().matches(&video));
+        assert_eq!(
+            SourceFilter::of(&article),
+            Some(SourceFilter::Domain("lwn.net".to_string()))
+        );
+        let no_authors = sample_item(4, 0, "Talk", "https://youtube.com/watch?v=2", &[], None);
+        assert_eq!(SourceFilter::of(&no_authors), None);
+    }
+
+    #[test]
+    fn authors_popup_filters_by_chosen_authors() {
+        use tutorial::sample_item;
+        let authors = |names: &[&str]| Some(names.iter().map(|name| name.to_string()).collect());
+        let items = [
+            sample_item(
+                1,
+                0,
+                "Post",
+                "https://blog.org/1",
+                &[],
+                authors(&["Ann", "Bob"]),
+            ),
+            sample_item(
+                2,
+                0,
+                "Talk",
+                "https://youtube.com/watch?v=1",
+                &[],
+                authors(&["Ann"]),
+            ),
+            sample_item(
+                3,
+                0,
+                "Paper",
+                "https://arxiv.org/3.pdf",
+                &[],
+                authors(&["Cy"]),
+            ),
+        ];
+        let mut popup = AuthorsPopupState::new(&items);
+        assert_eq!(popup.authors[0], ("Ann".to_string(), 2));
+        assert_eq!(
+            popup.filter(),
+            Some(SourceFilter::Writers(vec!["Ann".to_string()]))
+        );
+
+        popup.move_selection(1);
+        popup.toggle_chosen();
+        popup.move_selection(1);
+        popup.toggle_chosen();
+        let filter = popup.filter().unwrap();
+        let matched: Vec<&str> = items
+            .iter()
+            .filter(|item| filter.matches(item))
+            .map(|item| item.title())
+            .collect();
+        assert_eq!(matched, ["Post", "Paper"]);
+    }
+}
@@ -1,11 +1,49 @@
 //! Markdown normalizer that preserves proper formatting while normalizing spacing.
 
+use serde::{Deserialize, Serialize};
+
+/// Where the article starts and ends inside the converted page, the rest is navigation, comments etc.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct BoundaryRules {
+    /// Content starts at the first line containing one of these. Without a match it starts at
+    /// the first paragraph readability found
+    pub start_markers: Vec<String>,
+    /// Content ends at the last line containing one of these
+    pub end_markers: Vec<String>,
+    /// A `##` heading near the end also ends the content, unless it contains one of `keep_headings`
+    pub end_at_headings: bool,
+    pub keep_headings: Vec<String>,
+}
+
+impl Default for BoundaryRules {
+    fn default() -> Self {
+        Self {
+            start_markers: Vec::new(),
+            end_markers: vec![
+                "## Related posts".to_string(),
+                "Blog Comments".to_string(),
+                "Contents".to_string(),
+            ],
+            end_at_headings: true,
+            keep_headings: vec!["Summary".to_string()],
+        }
+    }
+}
+
 #[derive(Debug, PartialEq, Clone)]
 enum BlockType {
     Header,
-    ListItem { depth: usize, marker: ListMarker },
+    ListItem {
+        depth: usize,
+        marker: ListMarker,
+    },
     CodeBlockStart,
     CodeBlockEnd,
+    /// `[label]: url "title"`
+    LinkDefinition,
+    /// `[^label]: text`, continuation lines are indented
+    Footnote,
     Normal,
 }
 
@@ -26,16 +64,27 @@ fn normalize_for_comparison(text: &str) -> String {
         .join(" ")
 }
 
-fn find_content_boundaries(markdown: &str, plain: &str) -> (usize, usize) {
+fn find_content_boundaries(markdown: &str, plain: &str, rules: &BoundaryRules) -> (usize, usize) {
     let first_plain_para = plain.split("\n\n").next().unwrap_or("").trim();
     let markdown_lines: Vec<&str> = markdown.lines().collect();
+    let start_marker = markdown_lines.iter().position(|line| {
+        rules
+            .start_markers
+            .iter()
+            .any(|marker| line.contains(marker.as_str()))
+    });
     let mut start_idx = 0;
-    for (i, window) in markdown_lines.windows(3).enumerate() {
-        let combined = window.join(" ");
-        if normalize_for_comparison(&combined).contains(&normalize_for_comparison(first_plain_para))
-        {
-            start_idx = i;
-            break;
+    if let Some(idx) = start_marker {
+        start_idx = idx;
+    } else {
+        for (i, window) in markdown_lines.windows(3).enumerate() {
+            let combined = window.join(" ");
+            if normalize_for_comparison(&combined)
+                .contains(&normalize_for_comparison(first_plain_para))
+            {
+                start_idx = i;
+                break;
+            }
         }
     }
     let mut end_idx = markdown_lines.len();
@@ -43,11 +92,17 @@ fn find_content_boundaries(markdown: &str, plain: &str) -> (usize, usize) {
         if i <= start_idx {
             break;
         }
-        if line.contains("## Related posts")
-            || line.contains("Blog Comments")
-            || line.contains("Contents")
-            || (line.starts_with("##") && !line.contains("Summary"))
-        {
+        let is_end_marker = rules
+            .end_markers
+            .iter()
+            .any(|marker| line.contains(marker.as_str()));
+        let is_end_heading = rules.end_at_headings
+            && line.starts_with("##")
+            && !rules
+                .keep_headings
+                .iter()
+                .any(|heading| line.contains(heading.as_str()));
+        if is_end_marker || is_end_heading {
             end_idx = i;
             break;
         }
@@ -55,6 +110,17 @@ fn find_content_boundaries(markdown: &str, plain: &str) -> (usize, usize) {
     (start_idx, end_idx)
 }
 
+/// Lines of the page with a flag whether they are kept as the article content
+pub fn boundary_preview(markdown: &str, plain: &str, rules: &BoundaryRules) -> Vec<(bool, String)> {
+    let lines = Definitions::default().extract(&markdown.lines().collect::<Vec<_>>());
+    let (start_idx, end_idx) = find_content_boundaries(&lines.join("\n"), plain, rules);
+    lines
+        .iter()
+        .enumerate()
+        .map(|(i, line)| (start_idx <= i && i < end_idx, line.to_string()))
+        .collect()
+}
+
 fn get_list_marker(line: &str) -> ListMarker {
     let trimmed = line.trim_start();
     if let Some(first_token) = trimmed.split_whitespace().next() {
@@ -129,6 +195,13 @@ fn get_block_type(line: &str, is_in_code_block: bool, in_list: bool) -> BlockTyp
     if trimmed.starts_with('#') {
         return BlockType::Header;
     }
+    if !is_in_code_block {
+        match parse_definition(line) {
+            Some((true, _, _)) => return BlockType::Footnote,
+            Some((false, _, _)) => return BlockType::LinkDefinition,
+            None => {}
+        }
+    }
     let marker = get_list_marker(trimmed);
     if marker != ListMarker::None {
         let depth = get_list_depth(line);
@@ -143,6 +216,121 @@ fn get_block_type(line: &str, is_in_code_block: bool, in_list: bool) -> BlockTyp
     BlockType::Normal
 }
 
+/// Splits `[label]: rest` (up to 3 spaces of indentation), the flag is true for footnotes (`[^label]`)
+fn parse_definition(line: &str) -> Option<(bool, &str, &str)> {
+    let indent = line.len() - line.trim_start_matches(' ').len();
+    if indent > 3 {
+        return None;
+    }
+    let (label, rest) = line.trim_start().strip_prefix('[')?.split_once("]:")?;
+    let (is_footnote, label) = match label.strip_prefix('^') {
+        Some(label) => (true, label),
+        None => (false, label),
+    };
+    let rest = rest.trim();
+    if label.trim().is_empty() || label.contains(['[', ']']) || (!is_footnote && rest.is_empty()) {
+        return None;
+    }
+    Some((is_footnote, label, rest))
+}
+
+/// Labels match case-insensitively with collapsed whitespace
+fn normalize_label(label: &str) -> String {
+    label
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join(" ")
+        .to_lowercase()
+}
+
+/// Link reference definitions and footnotes, collected from the whole page and written after the content
+#[derive(Default)]
+struct Definitions {
+    links: Vec<(String, String)>,
+    footnotes: Vec<(String, Vec<String>)>,
+}
+
+impl Definitions {
+    /// Returns the lines that aren't definitions. The first definition of a label wins, like in CommonMark
+    fn extract<'a>(&mut self, lines: &[&'a str]) -> Vec<&'a str> {
+        let mut remaining = Vec::new();
+        let mut in_code_block = false;
+        let mut current_footnote: Option<usize> = None;
+        for (i, &line) in lines.iter().enumerate() {
+            let is_indented = line.starts_with("    ") || line.starts_with('\t');
+            if let Some(idx) = current_footnote {
+                let next_is_indented = lines
+                    .get(i + 1)
+                    .is_some_and(|next| next.starts_with("    ") || next.starts_with('\t'));
+                if is_indented && !line.trim().is_empty() {
+                    if let Some((_, footnote)) = self.footnotes.get_mut(idx) {
+                        footnote.push(line.trim().to_string());
+                    }
+                    continue;
+                } else if line.trim().is_empty() && next_is_indented {
+                    continue;
+                }
+            }
+            current_footnote = None;
+            if line.trim_start().starts_with("```") {
+                in_code_block = !in_code_block;
+            }
+            match get_block_type(line, in_code_block, false) {
+                BlockType::LinkDefinition => {
+                    if let Some((_, label, rest)) = parse_definition(line) {
+                        let key = normalize_label(label);
+                        if !self.links.iter().any(|(existing, _)| *existing == key) {
+                            self.links
+                                .push((key, format!("[{}]: {}", label.trim(), rest)));
+                        }
+                    }
+                }
+                BlockType::Footnote => {
+                    if let Some((_, label, rest)) = parse_definition(line) {
+                        let key = normalize_label(label);
+                        match self
+                            .footnotes
+                            .iter()
+                            .position(|(existing, _)| *existing == key)
+                        {
+                            // continuation lines of a duplicate are dropped along with it
+                            Some(_) => current_footnote = Some(usize::MAX),
+                            None => {
+                                self.footnotes
+                                    .push((key, vec![format!("[^{}]: {}", label.trim(), rest)]));
+                                current_footnote = Some(self.footnotes.len() - 1);
+                            }
+                        }
+                    }
+                }
+                _ => remaining.push(line),
+            }
+        }
+        remaining
+    }
+
+    /// Definitions referenced by the content, links first, then footnotes
+    fn render(&self, content: &str) -> Vec<String> {
+        let content = content.to_lowercase();
+        let links: Vec<&str> = self
+            .links
+            .iter()
+            .filter(|(label, _)| content.contains(&format!("[{}]", label)))
+            .map(|(_, line)| line.as_str())
+            .collect();
+        let footnotes: Vec<String> = self
+            .footnotes
+            .iter()
+            .filter(|(label, _)| content.contains(&format!("[^{}]", label)))
+            .map(|(_, lines)| lines.join("\n    "))
+            .collect();
+        [links.join("\n"), footnotes.join("\n")]
+            .into_iter()
+            .filter(|block| !block.is_empty())
+            .collect()
+    }
+}
+
 fn indent_line(line: &str, depth: usize) -> String {
     let spaces = "    ".repeat(depth);
     let trimmed = line.trim_start();
@@ -278,9 +466,10 @@ fn split_header_content(line: &str) -> Vec<String> {
     result
 }
 
-pub fn normalize_markdown(markdown: &str, plain: &str) -> String {
-    let markdown_lines: Vec<&str> = markdown.lines().collect();
-    let (start_idx, end_idx) = find_content_boundaries(markdown, plain);
+pub fn normalize_markdown(markdown: &str, plain: &str, rules: &BoundaryRules) -> String {
+    let mut definitions = Definitions::default();
+    let markdown_lines = definitions.extract(&markdown.lines().collect::<Vec<_>>());
+    let (start_idx, end_idx) = find_content_boundaries(&markdown_lines.join("\n"), plain, rules);
     let mut result = Vec::new();
     let mut current_block: Vec<String> = Vec::new();
     let mut in_code_block = false;
@@ -396,6 +585,8 @@ pub fn normalize_markdown(markdown: &str, plain: &str) -> String {
     if !current_block.is_empty() {
         result.push(current_block.join("\n"));
     }
+    let content = result.join("\n");
+    result.extend(definitions.render(&content));
 
     result
         .into_iter()
@@ -416,7 +607,7 @@ a. Sub item A
 b. Sub item B
 3. Third item
 Some text after the list."#;
-        let normalized = normalize_markdown(input, input);
+        let normalized = normalize_markdown(input, input, &BoundaryRules::default());
         assert_eq!(
             normalized.trim(),
             r#"Text before
@@ -441,7 +632,7 @@ Some text after the list."#
 2.2. Sub item B
 3. Third item
 Some text after the list."#;
-        let normalized = normalize_markdown(input, input);
+        let normalized = normalize_markdown(input, input, &BoundaryRules::default());
         assert_eq!(
             normalized.trim(),
             r#"Text before
@@ -464,7 +655,7 @@ Some text after the list."#
 4.4.`Sender`sends all the schedules from the two regions.### Region recovery example (failover is switched off)
 
 1. SSM regional parameters are changed. Life goes back to normal.[in link ##test](#url)"#;
-        let normalized = normalize_markdown(input.trim(), input);
+        let normalized = normalize_markdown(input.trim(), input, &BoundaryRules::default());
         assert_eq!(
             normalized.trim(),
             r#"### Some title
@@ -484,7 +675,7 @@ Some text after the list."#
 As mentioned in the beginning of this article, this is good.
 It is important to emphasise that this is architecture.
                 "#;
-        let normalized = normalize_markdown(input.trim(), input);
+        let normalized = normalize_markdown(input.trim(), input, &BoundaryRules::default());
         assert_eq!(
             normalized.trim(),
             r#"
@@ -523,7 +714,7 @@ It is important to emphasise that this is architecture.
 4.2`ReaderTaskProducers`generate the`ReaderTasks`
 4.3.`ReaderTaskConsumers`fetch buckets from`us-east-1`"#
             .trim();
-        let normalized = normalize_markdown(str, str);
+        let normalized = normalize_markdown(str, str, &BoundaryRules::default());
         assert_eq!(
             normalized.trim(),
             r#"
@@ -533,4 +724,62 @@ It is important to emphasise that this is architecture.
                 .trim()
         );
     }
+
+    #[test]
+    fn groups_definitions_at_the_end_and_drops_duplicates() {
+        let input = r#"First paragraph with a [link][Docs] and a note[^1].
+[docs]: https://example.com/docs "Docs"
+[^1]: The note
+    continues here.
+Second paragraph[^2].
+[DOCS]: https://example.com/other
+[^1]: Duplicate note
+[^2]: Another note
+[unused]: https://example.com/unused"#;
+        let normalized = normalize_markdown(input, input, &BoundaryRules::default());
+        assert_eq!(
+            normalized.trim(),
+            r#"First paragraph with a [link][Docs] and a note[^1].
+
+Second paragraph[^2].
+
+[docs]: https://example.com/docs "Docs"
+
+[^1]: The note
+    continues here.
+[^2]: Another note"#
+        );
+
+        let code = ["```", "[code]: https://example.com/code", "```"];
+        assert_eq!(Definitions::default().extract(&code), code);
+    }
+
+    #[test]
+    fn boundary_rules_pick_start_and_end() {
+        let page =
+            "Menu\nHome\nIntro line\nArticle text\n## Details\nMore text\nShare this\nFooter";
+        let rules = BoundaryRules {
+            start_markers: vec!["Intro".to_string()],
+            end_markers: vec!["Share this".to_string()],
+            end_at_headings: false,
+            keep_headings: Vec::new(),
+        };
+        let kept: Vec<String> = boundary_preview(page, "", &rules)
+            .into_iter()
+            .filter(|(kept, _)| *kept)
+            .map(|(_, line)| line)
+            .collect();
+        assert_eq!(
+            kept,
+            ["Intro line", "Article text", "## Details", "More text"]
+        );
+
+        let default_cut = boundary_preview(page, "Article text", &BoundaryRules::default());
+        let kept: Vec<&str> = default_cut
+            .iter()
+            .filter(|(kept, _)| *kept)
+            .map(|(_, line)| line.as_str())
+            .collect();
+        assert_eq!(kept, ["Home", "Intro line", "Article text"]);
+    }
 }
@@ -0,0 +1,664 @@
+use std::cell::RefCell;
+use std::io::Write;
+use std::sync::OnceLock;
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Options, Parser, Tag, TagEnd};
+use ratatui::crossterm::{
+    cursor::MoveTo,
+    queue,
+    style::{Attribute, Print, SetAttribute, SetBackgroundColor, SetForegroundColor},
+};
+use ratatui::{
+    buffer::Buffer,
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    widgets::Widget,
+};
+use syntect::{
+    easy::HighlightLines,
+    highlighting::{Theme, ThemeSet},
+    parsing::SyntaxSet,
+    util::LinesWithEndings,
+};
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+use crate::logo::OCEANIC_NEXT;
+
+/// syntect theme closest to the app palette
+const CODE_THEME: &str = "base16-ocean.dark";
+
+fn syntax_set() -> &'static SyntaxSet {
+    static SYNTAXES: OnceLock<SyntaxSet> = OnceLock::new();
+    SYNTAXES.get_or_init(SyntaxSet::load_defaults_newlines)
+}
+
+fn code_theme() -> &'static Theme {
+    static THEME: OnceLock<Theme> = OnceLock::new();
+    THEME.get_or_init(|| ThemeSet::load_defaults().themes[CODE_THEME].clone())
+}
+
+#[derive(Clone, Debug, PartialEq)]
+struct Segment {
+    text: String,
+    style: Style,
+    link: Option<String>,
+}
+
+impl Segment {
+    fn new(text: impl Into<String>, style: Style) -> Self {
+        Self {
+            text: text.into(),
+            style,
+            link: None,
+        }
+    }
+}
+
+/// A paragraph, list item or code line before it's wrapped to the screen width
+#[derive(Debug, Default)]
+struct Block {
+    segments: Vec<Segment>,
+    /// list bullet or quote bar of the first row
+    first_prefix: Option<Segment>,
+    /// indentation of the wrapped rows
+    prefix: Option<Segment>,
+    /// code is wrapped by characters and keeps its whitespace
+    preformatted: bool,
+    rule: bool,
+}
+
+/**
+Markdown parsed into styled blocks: headers, emphasis, lists, quotes, links and code blocks
+highlighted with syntect. Rows are wrapped when rendered.
+*/
+#[derive(Debug, Default)]
+pub struct MarkdownView {
+    blocks: Vec<Block>,
+}
+
+fn heading_style(level: HeadingLevel) -> Style {
+    let style = Style::default().add_modifier(Modifier::BOLD);
+    match level {
+        HeadingLevel::H1 => style
+            .fg(OCEANIC_NEXT.base_0a)
+            .add_modifier(Modifier::UNDERLINED),
+        HeadingLevel::H2 => style.fg(OCEANIC_NEXT.base_0a),
+        _ => style.fg(OCEANIC_NEXT.base_0d),
+    }
+}
+
+fn highlight_code(code: &str, lang: &str) -> Vec<Vec<Segment>> {
+    let syntaxes = syntax_set();
+    let syntax = syntaxes
+        .find_syntax_by_token(lang)
+        .unwrap_or_else(|| syntaxes.find_syntax_plain_text());
+    let mut highlighter = HighlightLines::new(syntax, code_theme());
+    let plain = Style::default().fg(OCEANIC_NEXT.base_05);
+    LinesWithEndings::from(code)
+        .map(|line| {
+            let ranges = highlighter
+                .highlight_line(line, syntaxes)
+                .unwrap_or_else(|_| vec![(Default::default(), line)]);
+            ranges
+                .into_iter()
+                .map(|(style, text)| {
+                    let text = text.trim_end_matches(['\n', '\r']).replace('\t', "    ");
+                    let fg = style.foreground;
+                    let style = if fg.a == 0 {
+                        plain
+                    } else {
+                        Style::default().fg(Color::Rgb(fg.r, fg.g, fg.b))
+                    };
+                    Segment::new(text, style)
+                })
+                .filter(|segment| !segment.text.is_empty())
+                .collect()
+        })
+        .collect()
+}
+
+/// Builds blocks out of pulldown-cmark events
+#[derive(Default)]
+struct Builder {
+    blocks: Vec<Block>,
+    current: Block,
+    styles: Vec<Style>,
+    link: Option<String>,
+    /// next number of every open list, `None` for bullet lists
+    lists: Vec<Option<u64>>,
+    bullet: Option<String>,
+    quote_depth: usize,
+    code: Option<(String, String)>,
+}
+
+impl Builder {
+    fn style(&self) -> Style {
+        self.styles
+            .iter()
+            .fold(Style::default().fg(OCEANIC_NEXT.base_05), |acc, style| {
+                acc.patch(*style)
+            })
+    }
+
+    fn prefixes(&mut self) -> (Option<Segment>, Option<Segment>) {
+        let mut indent = "│ ".repeat(self.quote_depth);
+        indent.push_str(&"  ".repeat(self.lists.len().saturating_sub(1)));
+        let style = Style::default().fg(OCEANIC_NEXT.base_03);
+        let first = match self.bullet.take() {
+            Some(bullet) => format!("{}{}", indent, bullet),
+            None if self.lists.is_empty() => indent.clone(),
+            None => format!("{}   ", indent),
+        };
+        let rest = format!("{}{}", indent, " ".repeat(first.width() - indent.width()));
+        let segment = |text: String| (!text.is_empty()).then(|| Segment::new(text, style));
+        (segment(first), segment(rest))
+    }
+
+    fn push(&mut self, segment: Segment) {
+        if self.current.segments.is_empty() && self.current.first_prefix.is_none() {
+            (self.current.first_prefix, self.current.prefix) = self.prefixes();
+        }
+        self.current.segments.push(segment);
+    }
+
+    fn push_text(&mut self, text: &str, style: Style) {
+        let mut segment = Segment::new(text, style);
+        segment.link = self.link.clone();
+        self.push(segment);
+    }
+
+    fn flush(&mut self) {
+        if self.current.segments.is_empty() && self.bullet.is_some() {
+            // empty list item still shows its bullet
+            self.push(Segment::new("", Style::default()));
+        }
+        if !self.current.segments.is_empty() {
+            self.blocks.push(std::mem::take(&mut self.current));
+        }
+    }
+
+    fn blank(&mut self) {
+        self.flush();
+        if self
+            .blocks
+            .last()
+            .is_some_and(|block| !block.segments.is_empty() || block.rule)
+        {
+            self.blocks.push(Block::default());
+        }
+    }
+
+    fn event(&mut self, event: Event) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                self.blank();
+                self.styles.push(heading_style(level));
+            }
+            Event::End(TagEnd::Heading(_)) => {
+                self.styles.pop();
+                self.blank();
+            }
+            Event::End(TagEnd::Paragraph) if self.lists.is_empty() => self.blank(),
+            Event::End(TagEnd::Paragraph) => self.flush(),
+            Event::Start(Tag::BlockQuote(_)) => {
+                self.flush();
+                self.quote_depth += 1;
+                self.styles
+                    .push(Style::default().add_modifier(Modifier::ITALIC));
+            }
+            Event::End(TagEnd::BlockQuote(_)) => {
+                self.flush();
+                self.quote_depth -= 1;
+                self.styles.pop();
+                self.blank();
+            }
+            Event::Start(Tag::CodeBlock(kind)) => {
+                self.flush();
+                let lang = match kind {
+                    CodeBlockKind::Fenced(lang) => {
+                        lang.split_whitespace().next().unwrap_or("").to_string()
+                    }
+                    CodeBlockKind::Indented => String::new(),
+                };
+                self.code = Some((lang, String::new()));
+            }
+            Event::End(TagEnd::CodeBlock) => {
+                if let Some((lang, code)) = self.code.take() {
+                    for segments in highlight_code(&code, &lang) {
+                        let (first_prefix, prefix) = self.prefixes();
+                        self.blocks.push(Block {
+                            segments,
+                            first_prefix,
+                            prefix,
+                            preformatted: true,
+                            rule: false,
+                        });
+                    }
+                }
+                self.blank();
+            }
+            Event::Start(Tag::List(start)) => {
+                self.flush();
+                self.lists.push(start);
+            }
+            Event::End(TagEnd::List(_)) => {
+                self.flush();
+                self.lists.pop();
+                if self.lists.is_empty() {
+                    self.blank();
+                }
+            }
+            Event::Start(Tag::Item) => {
+                self.flush();
+                self.bullet = Some(match self.lists.last_mut() {
+                    Some(Some(number)) => {
+                        *number += 1;
+                        format!("{}. ", *number - 1)
+                    }
+                    _ => "• ".to_string(),
+                });
+            }
+            Event::End(TagEnd::Item) => self.flush(),
+            Event::Start(Tag::Emphasis) => self
+                .styles
+                .push(Style::default().add_modifier(Modifier::ITALIC)),
+            Event::Start(Tag::Strong) => self
+                .styles
+                .push(Style::default().add_modifier(Modifier::BOLD)),
+            Event::Start(Tag::Strikethrough) => self
+                .styles
+                .push(Style::default().add_modifier(Modifier::CROSSED_OUT)),
+            Event::Start(Tag::Link { dest_url, .. } | Tag::Image { dest_url, .. }) => {
+                self.link = Some(dest_url.to_string());
+                self.styles.push(
+                    Style::default()
+                        .fg(OCEANIC_NEXT.base_0c)
+                        .add_modifier(Modifier::UNDERLINED),
+                );
+            }
+            Event::End(TagEnd::Link | TagEnd::Image) => {
+                self.link = None;
+                self.styles.pop();
+            }
+            Event::End(TagEnd::Emphasis | TagEnd::Strong | TagEnd::Strikethrough) => {
+                self.styles.pop();
+            }
+            Event::Text(text) => match &mut self.code {
+                Some((_, code)) => code.push_str(&text),
+                None => self.push_text(&text, self.style()),
+            },
+            Event::Code(code) => self.push_text(
+                &code,
+                Style::default()
+                    .fg(OCEANIC_NEXT.base_0b)
+                    .bg(OCEANIC_NEXT.base_01),
+            ),
+            Event::SoftBreak => self.push_text(" ", self.style()),
+            Event::HardBreak => self.flush(),
+            Event::TaskListMarker(checked) => {
+                self.push_text(if checked { "[x] " } else { "[ ] " }, self.style())
+            }
+            Event::Rule => {
+                self.blank();
+                self.blocks.push(Block {
+                    rule: true,
+                    ..Default::default()
+                });
+                self.blank();
+            }
+            _ => {}
+        }
+    }
+}
+
+/// Greedy word wrapping of a block into rows of segments
+struct Wrapper<'a> {
+    rows: Vec<Vec<Segment>>,
+    row: Vec<Segment>,
+    used: usize,
+    available: usize,
+    prefix: &'a Option<Segment>,
+}
+
+impl Wrapper<'_> {
+    fn start_row(&mut self, prefix: &Option<Segment>) {
+        self.row = prefix.iter().cloned().collect();
+        self.used = 0;
+    }
+
+    fn break_row(&mut self) {
+        let row = std::mem::take(&mut self.row);
+        self.rows.push(row);
+        self.start_row(self.prefix);
+    }
+
+    fn append(&mut self, text: &str, from: &Segment) {
+        self.used += text.width();
+        match self.row.last_mut() {
+            Some(last) if last.style == from.style && last.link == from.link => {
+                last.text.push_str(text)
+            }
+            _ => self.row.push(Segment {
+                text: text.to_string(),
+                style: from.style,
+                link: from.link.clone(),
+            }),
+        }
+    }
+
+    fn push_char(&mut self, ch: char, from: &Segment) {
+        if self.used + ch.width().unwrap_or(0) > self.available && self.used > 0 {
+            self.break_row();
+        }
+        self.append(ch.encode_utf8(&mut [0; 4]), from);
+    }
+
+    fn push_word(&mut self, word: &str, from: &Segment) {
+        let is_space = word.starts_with(char::is_whitespace);
+        if self.used + word.width() > self.available && self.used > 0 {
+            self.break_row();
+        }
+        if is_space && self.used == 0 {
+            // wrapped rows don't start with whitespace
+            return;
+        }
+        if word.width() > self.available {
+            word.chars().for_each(|ch| self.push_char(ch, from));
+        } else {
+            self.append(word, from);
+        }
+    }
+}
+
+/// Escapes characters that would otherwise be taken as markdown syntax
+pub fn escape(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for ch in text.chars() {
+        if "\\`*_[]<>#!|~".contains(ch) {
+            escaped.push('\\');
+        }
+        escaped.push(ch);
+    }
+    escaped
+}
+
+/// Splits text into runs of whitespace and runs of everything else
+fn split_words(text: &str) -> Vec<&str> {
+    let mut words = Vec::new();
+    let mut start = 0;
+    let mut chars = text.char_indices().peekable();
+    while let Some((_, ch)) = chars.next() {
+        if let Some(&(idx, next)) = chars.peek() {
+            if ch.is_whitespace() != next.is_whitespace() {
+                words.push(&text[start..idx]);
+                start = idx;
+            }
+        }
+    }
+    if start < text.len() {
+        words.push(&text[start..]);
+    }
+    words
+}
+
+impl MarkdownView {
+    pub fn parse(markdown: &str) -> Self {
+        let mut builder = Builder::default();
+        let options = Options::ENABLE_STRIKETHROUGH | Options::ENABLE_TASKLISTS;
+        for event in Parser::new_ext(markdown, options) {
+            builder.event(event);
+        }
+        builder.flush();
+        let mut blocks = builder.blocks;
+        if blocks
+            .last()
+            .is_some_and(|block| block.segments.is_empty() && !block.rule)
+        {
+            blocks.pop();
+        }
+        Self { blocks }
+    }
+
+    fn rows(&self, width: usize) -> Vec<Vec<Segment>> {
+        let mut rows = Vec::new();
+        for block in &self.blocks {
+            if block.rule {
+                rows.push(vec![Segment::new(
+                    "─".repeat(width),
+                    Style::default().fg(OCEANIC_NEXT.base_03),
+                )]);
+                continue;
+            }
+            let prefix_width = [&block.first_prefix, &block.prefix]
+                .iter()
+                .filter_map(|prefix| prefix.as_ref().map(|prefix| prefix.text.width()))
+                .max()
+                .unwrap_or(0);
+            let mut wrapper = Wrapper {
+                rows: Vec::new(),
+                row: Vec::new(),
+                used: 0,
+                available: width.saturating_sub(prefix_width).max(1),
+                prefix: &block.prefix,
+            };
+            wrapper.start_row(&block.first_prefix);
+            for segment in &block.segments {
+                if block.preformatted {
+                    segment
+                        .text
+                        .chars()
+                        .for_each(|ch| wrapper.push_char(ch, segment));
+                } else {
+                    for word in split_words(&segment.text) {
+                        wrapper.push_word(word, segment);
+                    }
+                }
+            }
+            wrapper.break_row();
+            rows.extend(wrapper.rows);
+        }
+        rows
+    }
+
+    /// Number of rows at the given width, for scrolling
+    pub fn height(&self, width: u16) -> usize {
+        self.rows(width as usize).len()
+    }
+}
+
+/// Link text on screen, written again as an OSC 8 hyperlink once the frame is drawn
+struct Hyperlink {
+    x: u16,
+    y: u16,
+    text: String,
+    style: Style,
+    url: String,
+}
+
+thread_local! {
+    static HYPERLINKS: RefCell<Vec<Hyperlink>> = const { RefCell::new(Vec::new()) };
+}
+
+/**
+Writes links rendered in the last frame as OSC 8 hyperlinks. Ratatui counts escape sequences into
+the cell width (https://github.com/ratatui/ratatui/issues/902), so they can't go into the buffer and
+the link text is printed over itself once the frame is on screen. Links covered by a popup are skipped.
+*/
+pub fn write_hyperlinks(buffer: &Buffer, out: &mut impl Write) -> std::io::Result<()> {
+    let hyperlinks = HYPERLINKS.with(|links| std::mem::take(&mut *links.borrow_mut()));
+    if hyperlinks.is_empty() {
+        return Ok(());
+    }
+    for link in hyperlinks {
+        let on_screen: String = (0..link.text.width() as u16)
+            .filter_map(|dx| buffer.cell((link.x + dx, link.y)))
+            .map(|cell| cell.symbol())
+            .collect();
+        if on_screen != link.text || buffer.cell((link.x, link.y)).map(|c| c.fg) != link.style.fg {
+            continue;
+        }
+        queue!(
+            out,
+            MoveTo(link.x, link.y),
+            Print(format!("\x1B]8;;{}\x07", link.url))
+        )?;
+        // cells keep their own style, titles mix highlighted matches into the text
+        for cell in
+            (0..link.text.width() as u16).filter_map(|dx| buffer.cell((link.x + dx, link.y)))
+        {
+            queue!(
+                out,
+                SetAttribute(Attribute::Reset),
+                SetForegroundColor(cell.fg.into()),
+                SetBackgroundColor(cell.bg.into())
+            )?;
+            for (modifier, attribute) in [
+                (Modifier::BOLD, Attribute::Bold),
+                (Modifier::DIM, Attribute::Dim),
+                (Modifier::ITALIC, Attribute::Italic),
+                (Modifier::UNDERLINED, Attribute::Underlined),
+            ] {
+                if cell.modifier.contains(modifier) {
+                    queue!(out, SetAttribute(attribute))?;
+                }
+            }
+            queue!(out, Print(cell.symbol()))?;
+        }
+        queue!(out, Print("\x1B]8;;\x07"), SetAttribute(Attribute::Reset))?;
+    }
+    out.flush()
+}
+
+/// Text drawn into `width` cells at `x`, `y` becomes a link to `url`, trailing blanks excluded.
+/// Called after the text is rendered, rows with wide characters are left as they are
+pub fn link_cells(buffer: &Buffer, x: u16, y: u16, width: u16, url: &str) {
+    let cells: Vec<_> = (0..width)
+        .filter_map(|dx| buffer.cell((x + dx, y)))
+        .collect();
+    let text: String = cells.iter().map(|cell| cell.symbol()).collect();
+    let text = text.trim_end();
+    if text.is_empty() || text.chars().any(|ch| ch.width() != Some(1)) {
+        return;
+    }
+    HYPERLINKS.with(|links| {
+        links.borrow_mut().push(Hyperlink {
+            x,
+            y,
+            text: text.to_string(),
+            style: Style::new().fg(cells[0].fg),
+            url: url.to_string(),
+        })
+    });
+}
+
+pub struct MarkdownWidget<'a> {
+    view: &'a MarkdownView,
+    scroll: usize,
+}
+
+impl<'a> MarkdownWidget<'a> {
+    pub fn new(view: &'a MarkdownView) -> Self {
+        Self { view, scroll: 0 }
+    }
+
+    pub fn scroll(mut self, scroll: usize) -> Self {
+        self.scroll = scroll;
+        self
+    }
+}
+
+impl Widget for MarkdownWidget<'_> {
+    fn render(self, area: Rect, buf: &mut Buffer) {
+        let rows = self.view.rows(area.width as usize);
+        for (row_idx, row) in rows
+            .iter()
+            .skip(self.scroll)
+            .take(area.height as usize)
+            .enumerate()
+        {
+            let y = area.y + row_idx as u16;
+            let mut x = area.x;
+            for segment in row {
+                let max_width = area.right().saturating_sub(x) as usize;
+                let (next_x, _) = buf.set_stringn(x, y, &segment.text, max_width, segment.style);
+                if let Some(url) = &segment.link {
+                    let text: String = segment.text.chars().take((next_x - x) as usize).collect();
+                    // wide characters would throw the cell positions off
+                    if !text.is_empty() && text.chars().all(|ch| ch.width() == Some(1)) {
+                        HYPERLINKS.with(|links| {
+                            links.borrow_mut().push(Hyperlink {
+                                x,
+                                y,
+                                text,
+                                style: segment.style,
+                                url: url.clone(),
+                            })
+                        });
+                    }
+                }
+                x = next_x;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn row_text(row: &[Segment]) -> String {
+        row.iter().map(|segment| segment.text.as_str()).collect()
+    }
+
+    #[test]
+    fn renders_structure_and_wraps() {
+        let view = MarkdownView::parse(
+            "# Title\n\nSome *emphasis* and a [link](https://example.com) here.\n\n\
+             1. first item that is long\n2. second\n\n> quoted\n\n```rust\nfn main() {}\n```\n",
+        );
+        let rows: Vec<String> = view.rows(20).iter().map(|row| row_text(row)).collect();
+        assert_eq!(
+            rows,
+            [
+                "Title",
+                "",
+                "Some emphasis and a ",
+                "link here.",
+                "",
+                "1. first item that ",
+                "   is long",
+                "2. second",
+                "",
+                "│ quoted",
+                "",
+                "fn main() {}",
+            ]
+        );
+
+        let rows = view.rows(20);
+        let link = rows[3].iter().find(|s| s.text == "link").unwrap();
+        assert_eq!(link.link.as_deref(), Some("https://example.com"));
+        assert!(rows[0][0].style.add_modifier.contains(Modifier::BOLD));
+        // code is highlighted, so it's split into differently colored tokens
+        assert!(rows[11].len() > 1);
+    }
+
+    #[test]
+    fn links_rendered_cells() {
+        let mut buffer = Buffer::empty(Rect::new(0, 0, 20, 2));
+        buffer.set_string(0, 0, "Rust ", Style::new().fg(Color::White));
+        buffer.set_string(5, 0, "async", Style::new().fg(Color::Yellow));
+        buffer.set_string(0, 1, "入門", Style::new());
+        link_cells(&buffer, 0, 0, 20, "https://a.org");
+        link_cells(&buffer, 0, 1, 20, "https://b.org");
+
+        let mut out = Vec::new();
+        write_hyperlinks(&buffer, &mut out).unwrap();
+        let out = String::from_utf8(out).unwrap();
+        assert!(out.contains("\x1B]8;;https://a.org\x07"));
+        assert!(!out.contains("https://b.org"));
+        // trailing blanks aren't part of the link
+        assert_eq!(out.matches('c').count(), 1);
+        assert!(out.ends_with(&format!("c\x1B]8;;\x07{}", "\x1B[0m")));
+    }
+}
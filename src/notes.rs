@@ -0,0 +1,261 @@
+use std::fs;
+use std::path::PathBuf;
+
+use pulldown_cmark::{CodeBlockKind, Event, HeadingLevel, Parser, Tag, TagEnd};
+use serde::{Deserialize, Serialize};
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteFormat {
+    /// markdown with YAML frontmatter
+    #[default]
+    Obsidian,
+    /// org-mode file with keywords and headings
+    Org,
+}
+
+/// Copy of every downloaded article written as a note, e.g. straight into an Obsidian vault
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct NotesExportConfig {
+    pub format: NoteFormat,
+    pub directory: PathBuf,
+    /**
+    Replaces the built-in layout. Placeholders: {{title}}, {{url}}, {{date}}, {{tags}},
+    {{authors}}, {{highlights}} and {{content}}.
+    */
+    pub template: Option<String>,
+}
+
+impl Default for NotesExportConfig {
+    fn default() -> Self {
+        Self {
+            format: NoteFormat::default(),
+            directory: PathBuf::from("notes"),
+            template: None,
+        }
+    }
+}
+
+pub struct NoteData<'a> {
+    pub item_id: &'a str,
+    pub title: &'a str,
+    pub url: &'a str,
+    pub date: &'a str,
+    pub tags: Vec<&'a str>,
+    pub authors: Vec<&'a str>,
+    pub highlights: &'a [String],
+    pub markdown: &'a str,
+}
+
+const OBSIDIAN_TEMPLATE: &str = "---
+title: \"{{title}}\"
+url: {{url}}
+date_added: {{date}}
+tags: [{{tags}}]
+authors: [{{authors}}]
+---
+
+# {{title}}
+
+{{highlights}}{{content}}
+";
+
+const ORG_TEMPLATE: &str = "#+TITLE: {{title}}
+#+DATE: {{date}}
+#+FILETAGS: {{tags}}
+:PROPERTIES:
+:URL: {{url}}
+:AUTHORS: {{authors}}
+:END:
+
+{{highlights}}{{content}}
+";
+
+pub fn write_note(config: &NotesExportConfig, note: &NoteData) -> anyhow::Result<PathBuf> {
+    fs::create_dir_all(&config.directory)?;
+    let extension = match config.format {
+        NoteFormat::Obsidian => "md",
+        NoteFormat::Org => "org",
+    };
+    let path = config
+        .directory
+        .join(format!("{}.{}", file_stem(note), extension));
+    fs::write(&path, render_note(config, note))?;
+    Ok(path)
+}
+
+/// Title with the item id: downloading an article again replaces its note, articles with the same
+/// title get one each
+fn file_stem(note: &NoteData) -> String {
+    let title = sanitize_filename::sanitize(note.title);
+    let item_id = sanitize_filename::sanitize(note.item_id);
+    match title.trim() {
+        "" => item_id,
+        title => format!("{} ({})", title, item_id),
+    }
+}
+
+pub fn render_note(config: &NotesExportConfig, note: &NoteData) -> String {
+    let (template, tags, highlights, content) = match config.format {
+        NoteFormat::Obsidian => (
+            OBSIDIAN_TEMPLATE,
+            note.tags.join(", "),
+            note.highlights
+                .iter()
+                .map(|quote| format!("> {}\n\n", quote.replace('\n', "\n> ")))
+                .collect::<String>(),
+            note.markdown.to_string(),
+        ),
+        NoteFormat::Org => (
+            ORG_TEMPLATE,
+            if note.tags.is_empty() {
+                String::new()
+            } else {
+                format!(":{}:", note.tags.join(":"))
+            },
+            if note.highlights.is_empty() {
+                String::new()
+            } else {
+                let quotes: String = note
+                    .highlights
+                    .iter()
+                    .map(|quote| format!("#+begin_quote\n{}\n#+end_quote\n", quote))
+                    .collect();
+                format!("* Highlights\n{}\n", quotes)
+            },
+            markdown_to_org(note.markdown),
+        ),
+    };
+    config
+        .template
+        .as_deref()
+        .unwrap_or(template)
+        .replace("{{title}}", &note.title.replace('"', "'"))
+        .replace("{{url}}", note.url)
+        .replace("{{date}}", note.date)
+        .replace("{{tags}}", &tags)
+        .replace("{{authors}}", &note.authors.join(", "))
+        .replace("{{highlights}}", &highlights)
+        .replace("{{content}}", &content)
+}
+
+/// Good enough conversion for article text: headings, emphasis, links, lists, quotes and code
+fn markdown_to_org(markdown: &str) -> String {
+    let mut out = String::new();
+    let mut list_depth = 0usize;
+    let mut link_stack: Vec<(String, usize)> = Vec::new();
+    for event in Parser::new(markdown) {
+        match event {
+            Event::Start(Tag::Heading { level, .. }) => {
+                let depth = match level {
+                    HeadingLevel::H1 => 1,
+                    HeadingLevel::H2 => 2,
+                    HeadingLevel::H3 => 3,
+                    HeadingLevel::H4 => 4,
+                    HeadingLevel::H5 => 5,
+                    HeadingLevel::H6 => 6,
+                };
+                out.push_str(&"*".repeat(depth));
+                out.push(' ');
+            }
+            Event::End(TagEnd::Heading(_)) => out.push_str("\n\n"),
+            Event::End(TagEnd::Paragraph) => {
+                out.push_str(if list_depth > 0 { "\n" } else { "\n\n" })
+            }
+            Event::Start(Tag::Emphasis) | Event::End(TagEnd::Emphasis) => out.push('/'),
+            Event::Start(Tag::Strong) | Event::End(TagEnd::Strong) => out.push('*'),
+            Event::Start(Tag::Strikethrough) | Event::End(TagEnd::Strikethrough) => out.push('+'),
+            Event::Start(Tag::Link { dest_url, .. }) => {
+                link_stack.push((dest_url.to_string(), out.len()));
+            }
+            Event::End(TagEnd::Link) => {
+                if let Some((url, start)) = link_stack.pop() {
+                    let text = out.split_off(start);
+                    out.push_str(&format!("[[{}][{}]]", url, text));
+                }
+            }
+            Event::Start(Tag::List(_)) => list_depth += 1,
+            Event::End(TagEnd::List(_)) => {
+                list_depth = list_depth.saturating_sub(1);
+                if list_depth == 0 {
+                    out.push('\n');
+                }
+            }
+            Event::Start(Tag::Item) => {
+                out.push_str(&"  ".repeat(list_depth.saturating_sub(1)));
+                out.push_str("- ");
+            }
+            Event::End(TagEnd::Item) if !out.ends_with('\n') => out.push('\n'),
+            Event::Start(Tag::BlockQuote(_)) => out.push_str("#+begin_quote\n"),
+            Event::End(TagEnd::BlockQuote(_)) => out.push_str("#+end_quote\n\n"),
+            Event::Start(Tag::CodeBlock(kind)) => {
+                let lang = match kind {
+                    CodeBlockKind::Fenced(lang) => lang.to_string(),
+                    CodeBlockKind::Indented => String::new(),
+                };
+                out.push_str(&format!("#+begin_src {}\n", lang).replace(" \n", "\n"));
+            }
+            Event::End(TagEnd::CodeBlock) => out.push_str("#+end_src\n\n"),
+            Event::Code(code) => out.push_str(&format!("~{}~", code)),
+            Event::Text(text) | Event::Html(text) | Event::InlineHtml(text) => out.push_str(&text),
+            Event::SoftBreak | Event::HardBreak => out.push('\n'),
+            Event::Rule => out.push_str("-----\n\n"),
+            _ => {}
+        }
+    }
+    out.trim_end().to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn note<'a>(highlights: &'a [String]) -> NoteData<'a> {
+        NoteData {
+            item_id: "42",
+            title: "Notes on \"B-trees\"",
+            url: "https://example.com/btree",
+            date: "2024-03-01",
+            tags: vec!["db", "read"],
+            authors: vec![],
+            highlights,
+            markdown: "## Intro\n\nSee [the paper](https://example.com/p) for *details*.",
+        }
+    }
+
+    #[test]
+    fn renders_obsidian_and_org_notes() {
+        let highlights = vec!["nodes are pages".to_string()];
+        let obsidian = render_note(&NotesExportConfig::default(), &note(&highlights));
+        assert!(obsidian.starts_with("---\ntitle: \"Notes on 'B-trees'\"\n"));
+        assert!(obsidian.contains("tags: [db, read]"));
+        assert!(obsidian.contains("> nodes are pages\n"));
+
+        let org_config = NotesExportConfig {
+            format: NoteFormat::Org,
+            ..Default::default()
+        };
+        let org = render_note(&org_config, &note(&highlights));
+        assert!(org.contains("#+FILETAGS: :db:read:"));
+        assert!(org.contains("** Intro\n\nSee [[https://example.com/p][the paper]] for /details/."));
+
+        let custom = NotesExportConfig {
+            template: Some("{{title}} <{{url}}>".to_string()),
+            ..Default::default()
+        };
+        assert_eq!(
+            render_note(&custom, &note(&[])),
+            "Notes on 'B-trees' <https://example.com/btree>"
+        );
+    }
+
+    #[test]
+    fn note_names_keep_same_titles_apart() {
+        let highlights = [];
+        let mut note = note(&highlights);
+        assert_eq!(file_stem(&note), "Notes on B-trees (42)");
+        note.title = " ";
+        assert_eq!(file_stem(&note), "42");
+    }
+}
@@ -0,0 +1,39 @@
+use log::error;
+
+/// Shows a desktop notification without blocking the caller, the notification daemon can be slow
+pub fn notify(summary: String, body: String) {
+    std::thread::spawn(move || {
+        if let Err(e) = notify_rust::Notification::new()
+            .appname("pkt-tui")
+            .summary(&summary)
+            .body(&body)
+            .show()
+        {
+            error!("Failed to show notification: {}", e);
+        }
+    });
+}
+
+/// "3 new items from LWN, Hacker News"
+pub fn new_rss_items_body(sources: &[&str], count: usize) -> String {
+    let items = if count == 1 {
+        "1 new item".to_string()
+    } else {
+        format!("{} new items", count)
+    };
+    format!("{} from {}", items, sources.join(", "))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn describes_new_items() {
+        assert_eq!(
+            new_rss_items_body(&["LWN", "Hacker News"], 3),
+            "3 new items from LWN, Hacker News"
+        );
+        assert_eq!(new_rss_items_body(&["LWN"], 1), "1 new item from LWN");
+    }
+}
@@ -0,0 +1,106 @@
+use std::collections::HashMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+/// Text extracted from downloaded pdfs, one file per item
+const PDF_TEXT_DIR: &str = "pdfs/text";
+
+fn text_path(item_id: &str) -> PathBuf {
+    Path::new(PDF_TEXT_DIR).join(format!("{}.txt", item_id))
+}
+
+/// Stores the text of a downloaded pdf, runs on the download thread
+pub fn store(item_id: &str, text: &str) -> anyhow::Result<()> {
+    fs::create_dir_all(PDF_TEXT_DIR)?;
+    fs::write(text_path(item_id), text)?;
+    Ok(())
+}
+
+/// Content of downloaded pdfs for search, so papers are found by what's in them and not only by title
+pub struct PdfIndex {
+    /// lowercased text by item id
+    texts: HashMap<String, String>,
+    persistent: bool,
+}
+
+impl PdfIndex {
+    /// In-memory only index, nothing is read from disk
+    pub fn new() -> Self {
+        Self {
+            texts: HashMap::new(),
+            persistent: false,
+        }
+    }
+
+    /// Texts that can't be read are logged and left out, their pdfs are found by title only
+    pub fn load() -> Self {
+        Self {
+            texts: Self::load_dir(Path::new(PDF_TEXT_DIR)),
+            persistent: true,
+        }
+    }
+
+    fn load_dir(dir: &Path) -> HashMap<String, String> {
+        let mut texts = HashMap::new();
+        let entries = match fs::read_dir(dir) {
+            Ok(entries) => entries,
+            Err(e) if e.kind() == ErrorKind::NotFound => return texts,
+            Err(e) => {
+                log::error!("Failed to read {}: {}", dir.display(), e);
+                return texts;
+            }
+        };
+        for path in entries.flatten().map(|entry| entry.path()) {
+            if path.extension().is_some_and(|ext| ext == "txt") {
+                if let Some(item_id) = path.file_stem().and_then(|stem| stem.to_str()) {
+                    match fs::read_to_string(&path) {
+                        Ok(text) => {
+                            texts.insert(item_id.to_string(), text.to_lowercase());
+                        }
+                        Err(e) => log::error!("Ignoring broken {}: {}", path.display(), e),
+                    }
+                }
+            }
+        }
+        texts
+    }
+
+    /// Picks up the text stored by a finished download
+    pub fn reload(&mut self, item_id: &str) -> anyhow::Result<()> {
+        if self.persistent {
+            let path = text_path(item_id);
+            if path.exists() {
+                self.texts.insert(
+                    item_id.to_string(),
+                    fs::read_to_string(path)?.to_lowercase(),
+                );
+            }
+        }
+        Ok(())
+    }
+
+    /// `query` is expected to be lowercased already
+    pub fn matches(&self, item_id: &str, query: &str) -> bool {
+        self.texts
+            .get(item_id)
+            .is_some_and(|text| text.contains(query))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn matches_lowercased_text() {
+        let mut index = PdfIndex::new();
+        index.texts.insert(
+            "42".to_string(),
+            "Scaled Dot-Product Attention".to_lowercase(),
+        );
+        assert!(index.matches("42", "dot-product"));
+        assert!(!index.matches("42", "convolution"));
+        assert!(!index.matches("7", "attention"));
+    }
+}
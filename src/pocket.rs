@@ -13,6 +13,10 @@ use tokio::runtime::Runtime;
 
 const SEND_ENDPOINT: &str = "https://getpocket.com/v3/send";
 const GET_ENDPOINT: &str = "https://getpocket.com/v3/get";
+/// Article view API, serves the copy Pocket saved for Premium permanent library
+const ARTICLE_ENDPOINT: &str = "https://text.getpocket.com/v3/text";
+/// Items in a page of the archive, the view fetches the next one as the selection gets close
+pub const ARCHIVE_PAGE_SIZE: u32 = 30;
 
 pub static CONSUMER_KEY: &'static str = "110856-cba018037b073c92d23edc4";
 
@@ -81,6 +85,127 @@ pub enum SendResponse {
     Extended(ExtendedResponse),
 }
 
+impl SendResponse {
+    /// Result of every action in the request, in the same order the actions were sent
+    pub fn action_outcomes(&self, actions_count: usize) -> Vec<Result<(), String>> {
+        (0..actions_count)
+            .map(|i| match self {
+                SendResponse::Standart(res) => match res.action_errors.get(i) {
+                    Some(Some(err)) => Err(err.clone()),
+                    _ if res.action_results.get(i) == Some(&false) => {
+                        Err("action failed".to_string())
+                    }
+                    _ => Ok(()),
+                },
+                SendResponse::Extended(res) => match res.action_errors.get(i) {
+                    Some(Some(err)) => Err(err.message.clone()),
+                    _ if res.action_results.get(i) == Some(&serde_json::Value::Bool(false)) => {
+                        Err("action failed".to_string())
+                    }
+                    _ => Ok(()),
+                },
+            })
+            .collect()
+    }
+
+    /// Id of the item created by the `add` action at `index`, only extended responses have it
+    pub fn added_item_id(&self, index: usize) -> Option<usize> {
+        match self {
+            SendResponse::Extended(res) => res.action_results.get(index)?["item_id"]
+                .as_str()?
+                .parse()
+                .ok(),
+            SendResponse::Standart(_) => None,
+        }
+    }
+}
+
+/// Url to add with [`GetPocketSync::add_all`], with the state it has in another service
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct NewItem<'a> {
+    pub url: &'a str,
+    pub tags: &'a [String],
+    /// Unix seconds it was saved at, now when there is none
+    pub time_added: Option<i64>,
+    pub archived: bool,
+    pub favorite: bool,
+}
+
+/// Single action of a bulk request, see [`GetPocketSync::send_batch`]
+#[derive(Debug, Clone, PartialEq)]
+pub enum BatchAction {
+    AddTag { item_id: usize, tag: String },
+    RemoveTag { item_id: usize, tag: String },
+    Archive { item_id: usize },
+    Delete { item_id: usize },
+}
+
+impl BatchAction {
+    pub fn item_id(&self) -> usize {
+        match self {
+            BatchAction::AddTag { item_id, .. }
+            | BatchAction::RemoveTag { item_id, .. }
+            | BatchAction::Archive { item_id }
+            | BatchAction::Delete { item_id } => *item_id,
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            BatchAction::AddTag { tag, .. } => format!("add tag '{}'", tag),
+            BatchAction::RemoveTag { tag, .. } => format!("remove tag '{}'", tag),
+            BatchAction::Archive { .. } => "archive".to_string(),
+            BatchAction::Delete { .. } => "delete".to_string(),
+        }
+    }
+
+    fn to_json(&self) -> serde_json::Value {
+        let now = chrono::Utc::now().timestamp().to_string();
+        match self {
+            BatchAction::AddTag { item_id, tag } => json!({
+                "item_id": item_id.to_string(),
+                "tags": tag,
+                "action": "tags_add"
+            }),
+            BatchAction::RemoveTag { item_id, tag } => json!({
+                "item_id": item_id.to_string(),
+                "tags": tag,
+                "action": "tags_remove"
+            }),
+            BatchAction::Archive { item_id } => json!({
+                "item_id": item_id.to_string(),
+                "action": "archive"
+            }),
+            BatchAction::Delete { item_id } => json!({
+                "item_id": item_id.to_string(),
+                "timestamp": now,
+                "action": "delete"
+            }),
+        }
+    }
+}
+
+/// Per-action split of a bulk request: one failed action doesn't fail the rest
+#[derive(Debug, Default)]
+pub struct BatchOutcome {
+    pub succeeded: Vec<BatchAction>,
+    pub failed: Vec<(BatchAction, String)>,
+}
+
+impl BatchOutcome {
+    fn from_response(actions: Vec<BatchAction>, response: &SendResponse) -> Self {
+        let mut outcome = BatchOutcome::default();
+        let results = response.action_outcomes(actions.len());
+        for (action, result) in actions.into_iter().zip(results) {
+            match result {
+                Ok(()) => outcome.succeeded.push(action),
+                Err(err) => outcome.failed.push((action, err)),
+            }
+        }
+        outcome
+    }
+}
+
 #[derive(Debug, Clone)]
 pub struct Reqwester {
     pub client: reqwest::Client,
@@ -118,6 +243,23 @@ impl GetPocket {
         .await
     }
 
+    /// Every url with its tags and the time it was saved at, in one request
+    pub async fn add_all(&self, items: &[NewItem<'_>]) -> Result<SendResponse> {
+        let now = chrono::Utc::now().timestamp();
+        let actions: Vec<serde_json::Value> = items
+            .iter()
+            .map(|item| {
+                json!({
+                    "action": "add",
+                    "url": item.url,
+                    "tags": item.tags.join(","),
+                    "timestamp": item.time_added.unwrap_or(now).to_string()
+                })
+            })
+            .collect();
+        self.send_unchecked(actions).await
+    }
+
     pub async fn update_tags(
         &self,
         item_id: usize,
@@ -132,6 +274,25 @@ impl GetPocket {
     }
 
     async fn send<T>(&self, params: T) -> Result<SendResponse>
+    where
+        T: Serialize,
+    {
+        match self.send_unchecked(params).await? {
+            SendResponse::Extended(extended_res) => {
+                if !extended_res.action_errors.iter().all(|e| e.is_none()) {
+                    bail!(format_err!(
+                        "Action errors: {:?}",
+                        extended_res.action_errors
+                    ));
+                }
+                Ok(SendResponse::Extended(extended_res))
+            }
+            other_res => Ok(other_res),
+        }
+    }
+
+    /// Sends actions without failing on per-action errors, the caller inspects the response
+    async fn send_unchecked<T>(&self, params: T) -> Result<SendResponse>
     where
         T: Serialize,
     {
@@ -176,21 +337,12 @@ impl GetPocket {
         let res_body = &res.text().await?;
         log::info!("GetPocket API communication response: {}", &res_body);
 
-        let res_ser: Result<SendResponse, serde_json::Error> = serde_json::from_str(&res_body);
+        serde_json::from_str(res_body).map_err(|err| ClientError::JsonError(err).into())
+    }
 
-        match res_ser {
-            Ok(SendResponse::Extended(extended_res)) => {
-                if !extended_res.action_errors.iter().all(|e| e.is_none()) {
-                    bail!(format_err!(
-                        "Action errors: {:?}",
-                        extended_res.action_errors
-                    ));
-                }
-                Ok(SendResponse::Extended(extended_res))
-            }
-            Ok(other_res) => Ok(other_res),
-            Err(err) => Err(ClientError::JsonError(err).into()),
-        }
+    pub async fn send_batch(&self, actions: &[BatchAction]) -> Result<SendResponse> {
+        let params: Vec<serde_json::Value> = actions.iter().map(BatchAction::to_json).collect();
+        self.send_unchecked(params).await
     }
 
     fn init_reqwester() -> Reqwester {
@@ -206,7 +358,7 @@ impl GetPocket {
             header::HeaderValue::from_static("application/json"),
         );
 
-        let client = reqwest::Client::builder()
+        let client = crate::network::async_client_builder()
             .connection_verbose(true)
             .default_headers(headers)
             .build()
@@ -227,15 +379,48 @@ impl GetPocket {
         since: Option<&str>,
         offset: Option<u32>,
         oldest_to_newest: bool,
+    ) -> Result<Pocket> {
+        self.retrieve_detailed(since, offset, oldest_to_newest, "complete", "all", 100)
+            .await
+    }
+
+    /// Page of the whole list with ids, status and times only
+    pub async fn retrieve_simple(&self, offset: u32) -> Result<Pocket> {
+        self.retrieve_detailed(Some("0"), Some(offset), true, "simple", "all", 100)
+            .await
+    }
+
+    /// Page of archived items, most recent first
+    pub async fn retrieve_archive(&self, offset: u32) -> Result<Pocket> {
+        self.retrieve_detailed(
+            None,
+            Some(offset),
+            false,
+            "complete",
+            "archive",
+            ARCHIVE_PAGE_SIZE,
+        )
+        .await
+    }
+
+    async fn retrieve_detailed(
+        &self,
+        since: Option<&str>,
+        offset: Option<u32>,
+        oldest_to_newest: bool,
+        detail_type: &str,
+        state: &str,
+        count: u32,
     ) -> Result<Pocket> {
         let client = &self.reqwester.client;
         let mut params = json!({
             "consumer_key": self.consumer_key,
             "access_token": self.access_token,
-            "detailType":"complete",
+            "detailType": detail_type,
+            "annotations": 1,
             "sort": (if oldest_to_newest { "oldest" } else {"newest"}),
-            "state": "all",
-            "count": 100, //api claims that this will be capped at 30 eventually
+            "state": state,
+            "count": count, //api claims that this will be capped at 30 eventually
         });
         if let Some(timestamp) = since {
             params["since"] = json!(timestamp);
@@ -256,6 +441,42 @@ impl GetPocket {
         Ok(res_ser)
     }
 
+    /// Html of the saved copy, `None` when the account (or the key) has no access to it
+    pub async fn permanent_copy(&self, url: &str) -> Result<Option<String>> {
+        let params = json!({
+            "consumer_key": self.consumer_key,
+            "access_token": self.access_token,
+            "url": url,
+            "images": 1,
+            "output": "json",
+        });
+        let res = self
+            .reqwester
+            .client
+            .post(ARTICLE_ENDPOINT)
+            .json(&params)
+            .send()
+            .await?;
+        if !res.status().is_success() {
+            log::info!(
+                "No permanent copy for {}: {} {}",
+                url,
+                res.status(),
+                res.headers()
+                    .get("X-Error")
+                    .and_then(|v| v.to_str().ok())
+                    .unwrap_or_default()
+            );
+            return Ok(None);
+        }
+        let body: serde_json::Value = res.json().await?;
+        Ok(body
+            .get("article")
+            .and_then(|article| article.as_str())
+            .filter(|article| !article.trim().is_empty())
+            .map(str::to_string))
+    }
+
     pub async fn delete(&self, item_id: usize) -> Result<SendResponse> {
         let now = chrono::Utc::now().timestamp();
         self.send(json!([{
@@ -266,6 +487,24 @@ impl GetPocket {
         .await
     }
 
+    /// Adds `url` with the title, tags and time of an existing item, the response has its new id
+    pub async fn add_as(
+        &self,
+        url: &str,
+        title: &str,
+        tags: &[String],
+        time_added: &str,
+    ) -> Result<SendResponse> {
+        self.send_unchecked(json!([{
+            "action": "add",
+            "url": url,
+            "title": title,
+            "tags": tags.join(","),
+            "timestamp": time_added
+        }]))
+        .await
+    }
+
     pub async fn fav_and_archive(&self, item_id: usize) -> Result<SendResponse> {
         self.send(json!([{
             "item_id": item_id.to_string(),
@@ -296,28 +535,15 @@ impl GetPocket {
         }]))
         .await
     }
-
-    pub async fn rename(
-        &self,
-        item_id: usize,
-        url: &str,
-        title: &str,
-        timestamp: u64,
-    ) -> Result<SendResponse> {
-        self.send(json!([{
-            "item_id": item_id.to_string(),
-            "title": title,
-            "url": url,
-            "action": "add",
-            "time": timestamp
-        }]))
-        .await
-    }
 }
 
+const READ_ONLY_ERROR: &str = "Read-only mode, nothing is sent to Pocket";
+
 pub struct GetPocketSync {
     get_pocket: GetPocket,
     runtime: Runtime,
+    dry_run: bool,
+    read_only: bool,
 }
 
 impl GetPocketSync {
@@ -329,82 +555,187 @@ impl GetPocketSync {
         Ok(GetPocketSync {
             get_pocket: client,
             runtime: rt,
+            dry_run: false,
+            read_only: false,
+        })
+    }
+
+    /// Client that never talks to Pocket: every send action succeeds without a network call
+    pub fn new_dry_run() -> Result<Self> {
+        let mut client = Self::new("")?;
+        client.dry_run = true;
+        Ok(client)
+    }
+
+    pub fn is_dry_run(&self) -> bool {
+        self.dry_run
+    }
+
+    /// Client that reads the account but refuses every change to it
+    pub fn new_read_only(access_token: &str) -> Result<Self> {
+        let mut client = Self::new(access_token)?;
+        client.read_only = true;
+        Ok(client)
+    }
+
+    pub fn is_read_only(&self) -> bool {
+        self.read_only
+    }
+
+    /// Client with its own runtime, for use from another thread
+    pub fn try_clone(&self) -> Result<Self> {
+        let rt = tokio::runtime::Builder::new_current_thread()
+            .enable_all()
+            .build()?;
+        Ok(GetPocketSync {
+            get_pocket: self.get_pocket.clone(),
+            runtime: rt,
+            dry_run: self.dry_run,
+            read_only: self.read_only,
         })
     }
 
+    fn send_blocking<F>(&self, action: F) -> Result<SendResponse>
+    where
+        F: std::future::Future<Output = Result<SendResponse>>,
+    {
+        if self.read_only {
+            bail!(READ_ONLY_ERROR);
+        }
+        if self.dry_run {
+            return Ok(SendResponse::Standart(Response {
+                action_results: vec![true],
+                action_errors: vec![None],
+                status: 1,
+            }));
+        }
+        self.runtime.block_on(action)
+    }
+
     pub fn delete(&self, item_id: usize) -> Result<SendResponse> {
-        self.runtime
-            .block_on(self.get_pocket.delete(item_id))
+        self.send_blocking(self.get_pocket.delete(item_id))
             .context(format!("Faile to delet an Item {}", item_id))
     }
 
     pub fn mark_as_read(&self, item_id: usize) -> Result<SendResponse> {
-        self.runtime
-            .block_on(self.get_pocket.add_tag(item_id, "read"))
+        self.send_blocking(self.get_pocket.add_tag(item_id, "read"))
             .context(format!("Faile to mark as read Item {}", item_id))
     }
 
     pub fn mark_as_downloaded(&self, item_id: usize) -> Result<SendResponse> {
-        self.runtime
-            .block_on(self.get_pocket.add_tag(item_id, "downloaded"))
+        self.send_blocking(self.get_pocket.add_tag(item_id, "downloaded"))
             .context(format!("Failed to mark as downloaded Item {}", item_id))
     }
 
     pub fn mark_as_top(&self, item_id: usize) -> Result<SendResponse> {
-        self.runtime
-            .block_on(self.get_pocket.add_tag(item_id, "top"))
+        self.send_blocking(self.get_pocket.add_tag(item_id, "top"))
             .context(format!("Faile to mark as top Item {}", item_id))
     }
 
     pub fn unmark_as_top(&self, item_id: usize) -> Result<SendResponse> {
-        self.runtime
-            .block_on(self.get_pocket.remove_tag(item_id, "top"))
+        self.send_blocking(self.get_pocket.remove_tag(item_id, "top"))
             .context(format!("Faile to mark as read Item {}", item_id))
     }
 
     pub fn fav_and_archive(&self, item_id: usize) -> Result<SendResponse> {
-        self.runtime
-            .block_on(self.get_pocket.fav_and_archive(item_id))
+        self.send_blocking(self.get_pocket.fav_and_archive(item_id))
             .context(format!("Faile to fav_and_archive an Item {}", item_id))
     }
     pub fn add(&self, url: &str, tags: &[String]) -> Result<SendResponse> {
-        self.runtime
-            .block_on(self.get_pocket.add(url, tags))
+        self.send_blocking(self.get_pocket.add(url, tags))
             .context(format!("Failed to add URL: {}", url))
     }
+    /// Id of the item added in place of `item_id`, `None` when Pocket didn't say (or in the demo)
+    pub fn replace_url(
+        &self,
+        item_id: usize,
+        url: &str,
+        title: &str,
+        tags: &[String],
+        time_added: &str,
+    ) -> Result<Option<String>> {
+        // Pocket runs the actions of a request one by one, the old item has to stay when the
+        // add is rejected
+        let response = self
+            .send_blocking(self.get_pocket.add_as(url, title, tags, time_added))
+            .context(format!("Failed to change the URL of Item {}", item_id))?;
+        if let Err(err) = &response.action_outcomes(1)[0] {
+            bail!("Failed to add {}: {}", url, err);
+        }
+        self.send_blocking(self.get_pocket.delete(item_id))
+            .with_context(|| format!("Added {}, but the old item wasn't deleted", url))?;
+        Ok(match response {
+            SendResponse::Extended(res) => res.action_results[0]["item_id"]
+                .as_str()
+                .map(str::to_string),
+            SendResponse::Standart(_) => None,
+        })
+    }
+
+    /// Adds all urls with their tags in one request, the outcome of each url in the same order.
+    /// Archived and favorite items get that state in a second request, with the ids Pocket gave
+    /// them, an url is failed when that request is
+    pub fn add_all(&self, items: &[NewItem]) -> Result<Vec<Result<(), String>>> {
+        if items.is_empty() {
+            return Ok(Vec::new());
+        }
+        let response = self
+            .send_blocking(self.get_pocket.add_all(items))
+            .context(format!("Failed to add {} urls", items.len()))?;
+        let mut outcomes = response.action_outcomes(items.len());
+        if self.dry_run {
+            return Ok(outcomes);
+        }
+
+        // index of the url, the action and the new item
+        let mut follow_ups = Vec::new();
+        for (index, (item, outcome)) in items.iter().zip(outcomes.iter_mut()).enumerate() {
+            if outcome.is_err() || !(item.archived || item.favorite) {
+                continue;
+            }
+            let Some(item_id) = response.added_item_id(index) else {
+                *outcome = Err("added, but Pocket didn't return its id to archive it".to_string());
+                continue;
+            };
+            if item.favorite {
+                follow_ups.push((index, "favorite", item_id));
+            }
+            if item.archived {
+                follow_ups.push((index, "archive", item_id));
+            }
+        }
+        if follow_ups.is_empty() {
+            return Ok(outcomes);
+        }
+        let actions: Vec<serde_json::Value> = follow_ups
+            .iter()
+            .map(|(_, action, item_id)| json!({"item_id": item_id.to_string(), "action": action}))
+            .collect();
+        let results = match self.send_blocking(self.get_pocket.send_unchecked(&actions)) {
+            Ok(response) => response.action_outcomes(actions.len()),
+            Err(e) => vec![Err(format!("{:#}", e)); actions.len()],
+        };
+        for ((index, action, _), result) in follow_ups.iter().zip(results) {
+            if let Err(err) = result {
+                outcomes[*index] = Err(format!("added, but failed to {}: {}", action, err));
+            }
+        }
+        Ok(outcomes)
+    }
     pub fn update_tags(&self, item_id: usize, tags: &[String]) -> anyhow::Result<SendResponse> {
-        self.runtime
-            .block_on(self.get_pocket.update_tags(item_id, tags))
+        self.send_blocking(self.get_pocket.update_tags(item_id, tags))
             .context(format!("Failed to update tags: {}", tags.join(",")))
     }
 
     //todo: this might blow up if pocket list size is very long
-    //todo: this does fetching & priting a the same time
-    pub fn retrieve_all(&self) -> Result<Pocket> {
+    /// Fetches the whole list batch by batch, `on_progress` gets the number of items so far.
+    /// Batches are kept in `storage::BOOTSTRAP_FILE` until the snapshot is saved, an interrupted
+    /// fetch continues from there
+    pub fn retrieve_all(&self, on_progress: impl Fn(usize)) -> Result<Pocket> {
+        let progress_file = Path::new(storage::BOOTSTRAP_FILE);
         self.runtime.block_on(async {
-            let mut offset = 0;
-            let mut all_items = Pocket::default();
-            let loading_chars = ["|", "/", "-", "\\"];
-            let mut loading_idx = 0;
-            let dots = std::sync::Arc::new(std::sync::Mutex::new(String::new()));
-
-            // print!("\nFetch in progress: ");
-
-            let (tx, rx) = std::sync::mpsc::channel();
-            let dots_clone = dots.clone();
-            std::thread::spawn(move || {
-                while rx.try_recv().is_err() {
-                    let dots_str = dots_clone.lock().unwrap().clone();
-                    print!(
-                        "\rFetch in progress: {}{} ",
-                        dots_str, loading_chars[loading_idx]
-                    );
-                    loading_idx = (loading_idx + 1) % loading_chars.len();
-                    std::io::Write::flush(&mut std::io::stdout()).unwrap();
-                    std::thread::sleep(std::time::Duration::from_millis(100));
-                }
-            });
-
+            let (mut offset, mut all_items) = storage::load_bootstrap_progress(progress_file);
+            on_progress(all_items.list.len());
             loop {
                 let batch = self
                     .get_pocket
@@ -414,16 +745,13 @@ impl GetPocketSync {
                     break;
                 }
 
-                let list_size = batch.list.len() as u32;
+                offset += batch.list.len() as u32;
+                storage::append_bootstrap_batch(progress_file, offset, &batch)?;
                 // Merge the items
                 all_items.list.extend(batch.list);
-
-                offset += list_size;
-                dots.lock().unwrap().push('.');
+                on_progress(all_items.list.len());
             }
 
-            let _ = tx.send(());
-
             all_items.list.retain(|_id, item| {
                 item.get("status")
                     .map_or(true, |s| s.as_str().unwrap_or("") != "2")
@@ -432,22 +760,58 @@ impl GetPocketSync {
         })
     }
 
+    /// Page of the whole list at `offset`, in the order `retrieve_all` fetches it. `simple` pages
+    /// have ids, status and times only
+    pub fn retrieve_page(&self, offset: u32, simple: bool) -> Result<Pocket> {
+        self.runtime.block_on(async {
+            if simple {
+                self.get_pocket.retrieve_simple(offset).await
+            } else {
+                self.get_pocket
+                    .retrieve(Some("0"), Some(offset), true)
+                    .await
+            }
+        })
+    }
+
+    /// Archived items from `offset` on, a page shorter than `ARCHIVE_PAGE_SIZE` is the last one
+    pub fn retrieve_archive(&self, offset: u32) -> Result<Pocket> {
+        self.runtime
+            .block_on(self.get_pocket.retrieve_archive(offset))
+    }
+
     pub fn refresh_delta_block(&self, delta_file: &Path) -> Result<()> {
         self.runtime
             .block_on(refresh_delta(delta_file, &self.get_pocket))
             .context("Failed to refresh pocket delta")
     }
 
-    pub fn rename(
-        &self,
-        item_id: usize,
-        url: &str,
-        title: &str,
-        timestamp: u64,
-    ) -> Result<SendResponse> {
+    pub fn permanent_copy(&self, url: &str) -> Result<Option<String>> {
+        if self.dry_run {
+            return Ok(None);
+        }
         self.runtime
-            .block_on(self.get_pocket.rename(item_id, url, title, timestamp))
-            .context("Failed to rename pocket item")
+            .block_on(self.get_pocket.permanent_copy(url))
+            .context(format!("Failed to get permanent copy of {}", url))
+    }
+
+    /// Sends all actions in one request. Only a failure of the request itself is an error,
+    /// failed actions are reported in the outcome so they can be retried
+    pub fn send_batch(&self, actions: Vec<BatchAction>) -> Result<BatchOutcome> {
+        if self.read_only && !actions.is_empty() {
+            bail!(READ_ONLY_ERROR);
+        }
+        if actions.is_empty() || self.dry_run {
+            return Ok(BatchOutcome {
+                succeeded: actions,
+                failed: Vec::new(),
+            });
+        }
+        let response = self
+            .runtime
+            .block_on(self.get_pocket.send_batch(&actions))
+            .context(format!("Failed to send {} actions", actions.len()))?;
+        Ok(BatchOutcome::from_response(actions, &response))
     }
 }
 
@@ -478,6 +842,12 @@ pub async fn refresh_delta(delta_file: &Path, pocket: &GetPocket) -> Result<()>
                 item_id: _,
                 timestamp: _,
             } => 0,
+            // imported items carry their bookmark dates, Pocket has never seen them
+            storage::PocketItemUpdate::Add { item_id, data: _ }
+                if storage::is_local_id(item_id) =>
+            {
+                0
+            }
             storage::PocketItemUpdate::Add {
                 item_id: _,
                 data: x,
@@ -533,6 +903,42 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn splits_batch_outcome_per_action() {
+        use super::{BatchAction, BatchOutcome, SendResponse};
+
+        let response: SendResponse = serde_json::from_str(
+            r#"{"action_results": [true, false], "action_errors": [null, {"message": "Invalid item", "type": "Bad Request", "code": 422}], "status": 1}"#,
+        )
+        .unwrap();
+        let actions = vec![
+            BatchAction::Archive { item_id: 1 },
+            BatchAction::Archive { item_id: 2 },
+        ];
+        let outcome = BatchOutcome::from_response(actions, &response);
+        assert_eq!(outcome.succeeded, [BatchAction::Archive { item_id: 1 }]);
+        assert_eq!(
+            outcome.failed,
+            [(
+                BatchAction::Archive { item_id: 2 },
+                "Invalid item".to_string()
+            )]
+        );
+    }
+
+    #[test]
+    fn reads_ids_of_added_items() {
+        use super::SendResponse;
+
+        let response: SendResponse = serde_json::from_str(
+            r#"{"action_results": [{"item_id": "42", "normal_url": "http://a.org"}, false], "action_errors": [null, null], "status": 1}"#,
+        )
+        .unwrap();
+        assert_eq!(response.added_item_id(0), Some(42));
+        assert_eq!(response.added_item_id(1), None);
+        assert_eq!(response.added_item_id(2), None);
+    }
+
     #[tokio::test]
     async fn fetch_delta() -> anyhow::Result<()> {
         let get_pocket = GetPocket::new(CONSUMER_KEY.to_string(), ACCESS_TOKEN.to_string());
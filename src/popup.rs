@@ -0,0 +1,229 @@
+use std::collections::HashMap;
+
+use crossterm::event::KeyCode;
+use ratatui::layout::Rect;
+use serde::{Deserialize, Serialize};
+
+use crate::statefile::StateFile;
+
+const POPUP_SIZES_FILE: &str = "popup_sizes.json";
+
+/// Step in percents for resizing a popup with Alt+arrows
+const RESIZE_STEP: i16 = 5;
+const MIN_PERCENT: u16 = 10;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PopupKind {
+    Tags,
+    DocType,
+    Help,
+    DomainStats,
+    Authors,
+    Watch,
+    FailedActions,
+    Downloads,
+    BoundaryPreview,
+    Favorites,
+    Archive,
+    Summary,
+    Reader,
+    Qr,
+    Share,
+    Logs,
+    SyncDiff,
+    Conflicts,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PopupPosition {
+    #[default]
+    Center,
+    Top,
+    Bottom,
+    Left,
+    Right,
+}
+
+/// Size in percents of the screen, fullscreen ignores size and position
+#[derive(Clone, Copy, Debug, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PopupGeometry {
+    pub width: u16,
+    pub height: u16,
+    pub position: PopupPosition,
+    pub fullscreen: bool,
+}
+
+impl Default for PopupGeometry {
+    fn default() -> Self {
+        Self::centered(60, 60)
+    }
+}
+
+impl PopupGeometry {
+    fn centered(width: u16, height: u16) -> Self {
+        Self {
+            width,
+            height,
+            position: PopupPosition::Center,
+            fullscreen: false,
+        }
+    }
+
+    pub fn area(&self, r: Rect) -> Rect {
+        if self.fullscreen {
+            return r;
+        }
+        let percent_of = |len: u16, percent: u16| {
+            (len as u32 * percent.clamp(MIN_PERCENT, 100) as u32 / 100) as u16
+        };
+        let width = percent_of(r.width, self.width);
+        let height = percent_of(r.height, self.height);
+        let center_x = r.x + (r.width - width) / 2;
+        let center_y = r.y + (r.height - height) / 2;
+        let (x, y) = match self.position {
+            PopupPosition::Center => (center_x, center_y),
+            PopupPosition::Top => (center_x, r.y),
+            PopupPosition::Bottom => (center_x, r.y + r.height - height),
+            PopupPosition::Left => (r.x, center_y),
+            PopupPosition::Right => (r.x + r.width - width, center_y),
+        };
+        Rect::new(x, y, width, height)
+    }
+}
+
+impl PopupKind {
+    fn default_geometry(self) -> PopupGeometry {
+        match self {
+            PopupKind::Tags
+            | PopupKind::DomainStats
+            | PopupKind::Authors
+            | PopupKind::Downloads => PopupGeometry::centered(60, 60),
+            PopupKind::DocType => PopupGeometry::centered(40, 40),
+            PopupKind::Help => PopupGeometry::centered(45, 80),
+            PopupKind::Watch | PopupKind::Favorites | PopupKind::Archive | PopupKind::SyncDiff => {
+                PopupGeometry::centered(70, 60)
+            }
+            PopupKind::FailedActions | PopupKind::Conflicts => PopupGeometry::centered(70, 50),
+            PopupKind::BoundaryPreview => PopupGeometry::centered(80, 80),
+            PopupKind::Summary => PopupGeometry::centered(60, 50),
+            PopupKind::Reader => PopupGeometry::centered(70, 90),
+            PopupKind::Qr => PopupGeometry::centered(50, 80),
+            PopupKind::Share => PopupGeometry::centered(60, 30),
+            PopupKind::Logs => PopupGeometry::centered(80, 60),
+        }
+    }
+}
+
+/**
+Popup geometry: resized by the user (remembered in `popup_sizes.json`), then `popups` from config.json,
+then built-in defaults.
+*/
+pub struct PopupLayouts {
+    configured: HashMap<PopupKind, PopupGeometry>,
+    resized: HashMap<PopupKind, PopupGeometry>,
+    file: StateFile,
+}
+
+impl PopupLayouts {
+    /// Resizes are kept in memory only
+    pub fn new(configured: HashMap<PopupKind, PopupGeometry>) -> Self {
+        Self {
+            configured,
+            resized: HashMap::new(),
+            file: StateFile::default(),
+        }
+    }
+
+    pub fn load(configured: HashMap<PopupKind, PopupGeometry>) -> Self {
+        let file = StateFile::at(POPUP_SIZES_FILE);
+        Self {
+            configured,
+            resized: file.load(),
+            file,
+        }
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        self.file.save(&self.resized)
+    }
+
+    pub fn geometry(&self, kind: PopupKind) -> PopupGeometry {
+        self.resized
+            .get(&kind)
+            .or_else(|| self.configured.get(&kind))
+            .copied()
+            .unwrap_or_else(|| kind.default_geometry())
+    }
+
+    pub fn area(&self, kind: PopupKind, r: Rect) -> Rect {
+        self.geometry(kind).area(r)
+    }
+
+    /// Alt+arrows resize the popup, Alt+f toggles fullscreen. Returns false for any other key
+    pub fn handle_resize_key(&mut self, kind: PopupKind, key: KeyCode) -> anyhow::Result<bool> {
+        let mut geometry = self.geometry(kind);
+        let (dw, dh) = match key {
+            KeyCode::Left => (-RESIZE_STEP, 0),
+            KeyCode::Right => (RESIZE_STEP, 0),
+            KeyCode::Up => (0, RESIZE_STEP),
+            KeyCode::Down => (0, -RESIZE_STEP),
+            KeyCode::Char('f') => {
+                geometry.fullscreen = !geometry.fullscreen;
+                (0, 0)
+            }
+            _ => return Ok(false),
+        };
+        let resize = |percent: u16, delta: i16| {
+            (percent as i16 + delta).clamp(MIN_PERCENT as i16, 100) as u16
+        };
+        geometry.width = resize(geometry.width, dw);
+        geometry.height = resize(geometry.height, dh);
+        if dw != 0 || dh != 0 {
+            geometry.fullscreen = false;
+        }
+        self.resized.insert(kind, geometry);
+        self.save()?;
+        Ok(true)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn resized_geometry_wins_over_config_and_defaults() {
+        let configured =
+            serde_json::from_str(r#"{"help": {"position": "right", "width": 30}}"#).unwrap();
+        let mut layouts = PopupLayouts::new(configured);
+        let screen = Rect::new(0, 0, 100, 50);
+
+        assert_eq!(
+            layouts.area(PopupKind::Tags, screen),
+            Rect::new(20, 10, 60, 30)
+        );
+        assert_eq!(
+            layouts.area(PopupKind::Help, screen),
+            Rect::new(70, 10, 30, 30)
+        );
+
+        layouts
+            .handle_resize_key(PopupKind::Help, KeyCode::Left)
+            .unwrap();
+        assert_eq!(
+            layouts.area(PopupKind::Help, screen),
+            Rect::new(75, 10, 25, 30)
+        );
+
+        layouts
+            .handle_resize_key(PopupKind::Help, KeyCode::Char('f'))
+            .unwrap();
+        assert_eq!(layouts.area(PopupKind::Help, screen), screen);
+        assert!(!layouts
+            .handle_resize_key(PopupKind::Help, KeyCode::Esc)
+            .unwrap());
+    }
+}
@@ -0,0 +1,76 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Where the reader stopped in a downloaded article, in lines of the reader
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct ReadingProgress {
+    pub line: usize,
+    pub lines: usize,
+}
+
+impl ReadingProgress {
+    pub fn percent(&self) -> u8 {
+        if self.lines == 0 {
+            return 0;
+        }
+        ((self.line + 1) * 100 / self.lines).min(100) as u8
+    }
+}
+
+/// Kept next to the downloaded article, `articles/<id>.progress`
+fn sidecar_path(article: &Path) -> PathBuf {
+    article.with_extension("progress")
+}
+
+pub fn load(article: &Path) -> Option<ReadingProgress> {
+    serde_json::from_str(&fs::read_to_string(sidecar_path(article)).ok()?).ok()
+}
+
+pub fn save(article: &Path, progress: ReadingProgress) -> anyhow::Result<()> {
+    fs::write(sidecar_path(article), serde_json::to_string(&progress)?)?;
+    Ok(())
+}
+
+/// Percent read of every article in `dir` with saved progress, by item id
+pub fn load_all(dir: &Path) -> HashMap<String, u8> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return HashMap::new();
+    };
+    entries
+        .flatten()
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().is_some_and(|ext| ext == "progress"))
+        .filter_map(|path| {
+            let item_id = path.file_stem()?.to_str()?.to_string();
+            let article = path.with_extension("md");
+            Some((item_id, load(&article)?.percent()))
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn keeps_progress_next_to_the_article() {
+        let dir = tempfile::tempdir().unwrap();
+        let article = dir.path().join("42.md");
+        assert_eq!(load(&article), None);
+
+        let progress = ReadingProgress {
+            line: 49,
+            lines: 200,
+        };
+        save(&article, progress).unwrap();
+        assert_eq!(load(&article), Some(progress));
+        assert_eq!(progress.percent(), 25);
+        assert_eq!(
+            load_all(dir.path()),
+            HashMap::from([("42".to_string(), 25)])
+        );
+    }
+}
@@ -2,10 +2,21 @@ use anyhow::Context;
 use chrono::{DateTime, Local, Utc};
 use log::{error, LevelFilter};
 use std::fs::{self, File};
+use std::hash::{BuildHasher, Hasher};
 use std::io::{self, BufRead, BufReader};
 use std::path::{Path, PathBuf};
+use std::thread;
+use std::time::{Duration, Instant};
 
-#[derive(Clone, Debug)]
+use reqwest::header::{ETAG, IF_MODIFIED_SINCE, IF_NONE_MATCH, LAST_MODIFIED};
+use serde::{Deserialize, Serialize};
+
+/// Feeds still failing after this long are left for the next refresh
+/// One feed url per line, `#` starts a comment
+pub const SUBSCRIPTIONS_FILE: &str = "rss/subscriptions";
+pub const MAX_TOTAL_FETCH_TIME: Duration = Duration::from_secs(60);
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
 pub struct RssFeedItem {
     pub title: String,
     pub link: String,
@@ -13,6 +24,80 @@ pub struct RssFeedItem {
     pub description: Option<String>,
     pub pub_date: Option<String>,
     pub item_id: String,
+    /// Marked as adult/explicit by the feed
+    #[serde(default)]
+    pub nsfw: bool,
+    /// Podcast audio or video attached to the item
+    #[serde(default)]
+    pub enclosure: Option<String>,
+}
+
+impl RssFeedItem {
+    /// Page of the item, or the media file for feeds that only link the enclosure
+    pub fn url(&self) -> &str {
+        match &self.enclosure {
+            Some(enclosure) if self.link.is_empty() => enclosure,
+            _ => &self.link,
+        }
+    }
+}
+
+/// Result of a conditional feed request
+pub enum FeedFetch {
+    /// The server confirmed the cached copy is current
+    NotModified,
+    Fetched {
+        items: Vec<RssFeedItem>,
+        validators: feed_cache::Validators,
+    },
+}
+
+/// Line of `rss/subscriptions`: the feed url, optionally followed by `|`-separated
+/// `basic <user>:<password>` and `header <Name>: <value>` options. Values starting with `$` are
+/// read from the environment
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Subscription {
+    pub url: String,
+    pub basic_auth: Option<(String, String)>,
+    pub headers: Vec<(String, String)>,
+}
+
+impl Subscription {
+    pub fn parse(line: &str) -> anyhow::Result<Self> {
+        let mut parts = line.split('|').map(str::trim);
+        let mut subscription = Subscription {
+            url: parts.next().unwrap_or_default().to_string(),
+            ..Default::default()
+        };
+        for option in parts {
+            let (kind, value) = option.split_once(' ').unwrap_or((option, ""));
+            match kind {
+                "basic" => {
+                    let (user, password) = value.trim().split_once(':').unwrap_or((value, ""));
+                    subscription.basic_auth = Some((resolve(user)?, resolve(password)?));
+                }
+                "header" => {
+                    let (name, value) = value
+                        .split_once(':')
+                        .with_context(|| format!("Header without a value: {}", option))?;
+                    subscription
+                        .headers
+                        .push((name.trim().to_string(), resolve(value)?));
+                }
+                _ => anyhow::bail!("Unknown subscription option: {}", kind),
+            }
+        }
+        Ok(subscription)
+    }
+}
+
+/// `$NAME` is read from the environment, anything else is taken as is
+pub fn resolve(value: &str) -> anyhow::Result<String> {
+    let value = value.trim();
+    match value.strip_prefix('$') {
+        Some(var) => std::env::var(var).with_context(|| format!("{} is not set", var)),
+        None => Ok(value.to_string()),
+    }
 }
 
 pub struct RssManager {
@@ -22,7 +107,7 @@ pub struct RssManager {
 impl RssManager {
     pub fn new() -> Self {
         Self {
-            subscriptions_path: PathBuf::from("rss/subscriptions"),
+            subscriptions_path: PathBuf::from(SUBSCRIPTIONS_FILE),
         }
     }
 
@@ -40,32 +125,47 @@ impl RssManager {
         Ok(())
     }
 
-    pub fn load_subscriptions(&self) -> anyhow::Result<Vec<String>> {
+    /// Lines with feed urls and their options, comments and blank lines are skipped
+    fn subscription_lines(&self) -> anyhow::Result<Vec<String>> {
         self.ensure_subscriptions_file()?;
 
         let file = File::open(&self.subscriptions_path)
             .context("Failed to open RSS subscriptions file")?;
         let reader = BufReader::new(file);
 
-        let mut subscriptions = Vec::new();
+        let mut lines = Vec::new();
         for line in reader.lines() {
             let line = line?;
             let trimmed = line.trim();
             if !trimmed.is_empty() && !trimmed.starts_with('#') {
-                subscriptions.push(trimmed.to_string());
+                lines.push(trimmed.to_string());
             }
         }
 
-        Ok(subscriptions)
+        Ok(lines)
     }
 
-    pub fn add_subscription(&self, url: &str) -> anyhow::Result<()> {
-        self.ensure_subscriptions_file()?;
+    /// Broken lines are logged and skipped, the other feeds still load
+    pub fn load_subscriptions(&self) -> anyhow::Result<Vec<Subscription>> {
+        Ok(self
+            .subscription_lines()?
+            .iter()
+            .filter_map(|line| {
+                Subscription::parse(line)
+                    .inspect_err(|e| error!("Ignoring subscription {}: {:#}", line, e))
+                    .ok()
+            })
+            .collect())
+    }
 
-        let mut subscriptions = self.load_subscriptions()?;
-        if !subscriptions.contains(&url.to_string()) {
-            subscriptions.push(url.to_string());
-            let content = subscriptions.join("\n");
+    pub fn add_subscription(&self, url: &str) -> anyhow::Result<()> {
+        let mut lines = self.subscription_lines()?;
+        if !lines
+            .iter()
+            .any(|line| line.split('|').next().map(str::trim) == Some(url))
+        {
+            lines.push(url.to_string());
+            let content = lines.join("\n");
             fs::write(&self.subscriptions_path, content)?;
         }
 
@@ -73,36 +173,80 @@ impl RssManager {
     }
 
     pub fn remove_subscription(&self, url: &str) -> anyhow::Result<()> {
-        self.ensure_subscriptions_file()?;
-
-        let mut subscriptions = self.load_subscriptions()?;
-        if let Some(pos) = subscriptions.iter().position(|x| x == url) {
-            subscriptions.remove(pos);
-            let content = subscriptions.join("\n");
+        let mut lines = self.subscription_lines()?;
+        if let Some(pos) = lines
+            .iter()
+            .position(|line| line.split('|').next().map(str::trim) == Some(url))
+        {
+            lines.remove(pos);
+            let content = lines.join("\n");
             fs::write(&self.subscriptions_path, content)?;
         }
 
         Ok(())
     }
 
+    /// Sends `If-None-Match`/`If-Modified-Since` from the cached copy, so unchanged feeds aren't
+    /// downloaded again
     pub fn fetch_and_parse_feed(
         client: &reqwest::blocking::Client,
-        url: &str,
-    ) -> anyhow::Result<Vec<RssFeedItem>> {
-        let response = client
+        subscription: &Subscription,
+        cached: Option<&feed_cache::Validators>,
+    ) -> anyhow::Result<FeedFetch> {
+        let url = subscription.url.as_str();
+        let mut request = client
                     .get(url)
                     .header(
                         "User-Agent",
                         "Mozilla/5.0 (Windows NT 10.0; Win64; x64) AppleWebKit/537.36 (KHTML, like Gecko) Chrome/91.0.4472.124 Safari/537.36"
-                    )
-                    .send()?;
+                    );
+        if let Some((user, password)) = &subscription.basic_auth {
+            request = request.basic_auth(user, Some(password));
+        }
+        for (name, value) in &subscription.headers {
+            request = request.header(name, value);
+        }
+        if let Some(etag) = cached.and_then(|validators| validators.etag.as_deref()) {
+            request = request.header(IF_NONE_MATCH, etag);
+        }
+        if let Some(modified) = cached.and_then(|validators| validators.last_modified.as_deref()) {
+            request = request.header(IF_MODIFIED_SINCE, modified);
+        }
+        let response = request.send()?;
 
+        if response.status() == reqwest::StatusCode::NOT_MODIFIED {
+            return Ok(FeedFetch::NotModified);
+        }
         if !response.status().is_success() {
             error!("Failed to fetch {}: Status {}", url, response.status());
-            return Err(anyhow::anyhow!("HTTP error: {}", response.status()));
+            return Err(HttpStatusError(response.status()).into());
         }
 
+        let header = |name| {
+            response
+                .headers()
+                .get(name)
+                .and_then(|value| value.to_str().ok())
+                .map(String::from)
+        };
+        let validators = feed_cache::Validators {
+            etag: header(ETAG),
+            last_modified: header(LAST_MODIFIED),
+        };
         let content = response.text()?;
+        Ok(FeedFetch::Fetched {
+            items: Self::parse_feed(url, &content)?,
+            validators,
+        })
+    }
+
+    fn parse_feed(url: &str, content: &str) -> anyhow::Result<Vec<RssFeedItem>> {
+        if content.trim_start().starts_with('{') {
+            return json_feed::parse(content).map_err(|e| {
+                error!("Failed to parse JSON feed from {}: {}", url, e);
+                anyhow::anyhow!("Invalid JSON feed: {}", e)
+            });
+        }
 
         // Try parsing as Atom first
         if let Ok(atom_feed) = atom_syndication::Feed::read_from(content.as_bytes()) {
@@ -116,9 +260,15 @@ impl RssManager {
                         title: entry.title().to_string(),
                         link: entry
                             .links()
-                            .first()
+                            .iter()
+                            .find(|l| l.rel() != "enclosure")
                             .map(|l| l.href().to_string())
                             .unwrap_or_default(),
+                        enclosure: entry
+                            .links()
+                            .iter()
+                            .find(|l| l.rel() == "enclosure")
+                            .map(|l| l.href().to_string()),
                         description: entry.content().and_then(|c| c.value()).map(String::from),
                         pub_date: Some(
                             entry
@@ -128,6 +278,11 @@ impl RssManager {
                         ),
                         source: source_name.clone(),
                         item_id,
+                        nsfw: entry
+                            .categories()
+                            .iter()
+                            .any(|category| filters::is_nsfw_marker(category.term()))
+                            || filters::has_nsfw_title(entry.title()),
                     }
                 })
                 .collect());
@@ -159,6 +314,8 @@ impl RssManager {
                                 .or(item.pub_date().map(String::from)),
                             source: source_name.clone(),
                             item_id,
+                            nsfw: filters::is_nsfw_rss_item(item),
+                            enclosure: item.enclosure().map(|e| e.url().to_string()),
                         }
                     })
                     .collect())
@@ -181,64 +338,921 @@ impl RssManager {
     }
 }
 
+#[derive(Debug)]
+pub struct HttpStatusError(pub reqwest::StatusCode);
+
+impl std::fmt::Display for HttpStatusError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "HTTP error: {}", self.0)
+    }
+}
+
+impl std::error::Error for HttpStatusError {}
+
+/// Timeouts, dropped connections, 5xx and 429 can go away on their own, anything else won't
+fn is_transient(e: &anyhow::Error) -> bool {
+    if let Some(HttpStatusError(status)) = e.downcast_ref() {
+        return status.is_server_error() || *status == reqwest::StatusCode::TOO_MANY_REQUESTS;
+    }
+    if let Some(e) = e.downcast_ref::<reqwest::Error>() {
+        return e.is_timeout() || e.is_connect() || e.is_request() || e.is_body();
+    }
+    false
+}
+
+/// Random number in [0, 1), good enough for spreading retries
+fn random_fraction() -> f64 {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    let now = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap_or_default();
+    hasher.write_u128(now.as_nanos());
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+#[derive(Clone, Debug)]
+pub struct RetryPolicy {
+    pub max_attempts: u32,
+    pub base_delay: Duration,
+    pub max_delay: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(500),
+            max_delay: Duration::from_secs(8),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Exponential backoff, the upper half of each delay is random so feeds on one host don't retry in lockstep
+    fn delay(&self, attempt: u32) -> Duration {
+        let backoff = self
+            .base_delay
+            .saturating_mul(2u32.saturating_pow(attempt))
+            .min(self.max_delay);
+        backoff / 2 + backoff.mul_f64(random_fraction() / 2.0)
+    }
+
+    /// Retries transient failures until attempts run out or the next try would end after `deadline`
+    pub fn run<T>(
+        &self,
+        deadline: Instant,
+        mut attempt: impl FnMut() -> anyhow::Result<T>,
+    ) -> anyhow::Result<T> {
+        let mut attempts = 0;
+        loop {
+            let err = match attempt() {
+                Ok(value) => return Ok(value),
+                Err(err) => err,
+            };
+            attempts += 1;
+            if attempts >= self.max_attempts || !is_transient(&err) {
+                return Err(err);
+            }
+            let delay = self.delay(attempts - 1);
+            if Instant::now() + delay > deadline {
+                return Err(err.context("Out of time for retries"));
+            }
+            thread::sleep(delay);
+        }
+    }
+}
+
+/// Per-feed language and safe-search filters, applied before items reach the triage screen
+pub mod filters {
+    use std::collections::HashMap;
+
+    use chrono::{DateTime, Utc};
+    use serde::{Deserialize, Serialize};
+
+    use super::RssFeedItem;
+
+    /// Key in `rss_filters` that applies to feeds without their own entry
+    const ALL_FEEDS: &str = "*";
+
+    /// Text shorter than that doesn't tell the language reliably, such items are kept
+    const MIN_DETECTION_CHARS: usize = 40;
+
+    #[derive(Debug, Clone, Default, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct FeedFilter {
+        /// ISO 639-3 codes like `eng` or `deu`, empty keeps every language
+        pub languages: Vec<String>,
+        pub hide_nsfw: bool,
+        /// Keywords of which at least one has to be in the title or description, empty keeps all
+        pub include: Vec<String>,
+        /// Items mentioning any of these keywords are muted
+        pub exclude: Vec<String>,
+        /// Only the newest items of the feed are kept
+        pub max_items: Option<usize>,
+        /// Items published longer ago than that are dropped
+        pub max_age_days: Option<u64>,
+        /// Items younger than that are held back until the feed settles, e.g. for edited posts
+        pub min_age_hours: Option<u64>,
+    }
+
+    /// Dates as stored in `RssFeedItem::pub_date`: RFC 3339 for RSS and JSON Feed, chrono's
+    /// display format for Atom; raw RFC 2822 when the RSS date couldn't be normalized
+    pub fn published_at(item: &RssFeedItem) -> Option<DateTime<Utc>> {
+        let date = item.pub_date.as_deref()?;
+        DateTime::parse_from_rfc3339(date)
+            .or_else(|_| DateTime::parse_from_str(date, "%Y-%m-%d %H:%M:%S %:z"))
+            .or_else(|_| DateTime::parse_from_rfc2822(date))
+            .ok()
+            .map(|date| date.to_utc())
+    }
+
+    pub fn is_nsfw_marker(value: &str) -> bool {
+        matches!(
+            value.trim().to_lowercase().as_str(),
+            "nsfw" | "adult" | "explicit" | "yes" | "true" | "18+"
+        )
+    }
+
+    pub fn has_nsfw_title(title: &str) -> bool {
+        title
+            .split(|c: char| !c.is_alphanumeric())
+            .any(|word| word.eq_ignore_ascii_case("nsfw"))
+    }
+
+    /// Categories, `itunes:explicit` and `media:rating`
+    pub fn is_nsfw_rss_item(item: &rss::Item) -> bool {
+        let categories = item
+            .categories()
+            .iter()
+            .any(|category| is_nsfw_marker(category.name()));
+        let explicit = item
+            .itunes_ext()
+            .and_then(|itunes| itunes.explicit())
+            .is_some_and(is_nsfw_marker);
+        let rating = item
+            .extensions()
+            .get("media")
+            .and_then(|media| media.get("rating"))
+            .into_iter()
+            .flatten()
+            .filter_map(|rating| rating.value())
+            .any(is_nsfw_marker);
+        categories || explicit || rating || has_nsfw_title(item.title().unwrap_or_default())
+    }
+
+    fn strip_tags(html: &str) -> String {
+        let mut text = String::with_capacity(html.len());
+        let mut in_tag = false;
+        for c in html.chars() {
+            match c {
+                '<' => in_tag = true,
+                '>' => {
+                    in_tag = false;
+                    text.push(' ');
+                }
+                _ if !in_tag => text.push(c),
+                _ => {}
+            }
+        }
+        text
+    }
+
+    impl FeedFilter {
+        fn keeps_language(&self, item: &RssFeedItem) -> bool {
+            if self.languages.is_empty() {
+                return true;
+            }
+            let text = format!(
+                "{} {}",
+                item.title,
+                strip_tags(item.description.as_deref().unwrap_or_default())
+            );
+            if text.trim().chars().count() < MIN_DETECTION_CHARS {
+                return true;
+            }
+            match whatlang::detect(&text) {
+                Some(info) if info.is_reliable() => self
+                    .languages
+                    .iter()
+                    .any(|lang| lang.eq_ignore_ascii_case(info.lang().code())),
+                _ => true,
+            }
+        }
+
+        fn keeps_keywords(&self, item: &RssFeedItem) -> bool {
+            if self.include.is_empty() && self.exclude.is_empty() {
+                return true;
+            }
+            let text = format!(
+                "{} {}",
+                item.title,
+                strip_tags(item.description.as_deref().unwrap_or_default())
+            )
+            .to_lowercase();
+            let mentions = |keyword: &String| text.contains(&keyword.to_lowercase());
+            (self.include.is_empty() || self.include.iter().any(mentions))
+                && !self.exclude.iter().any(mentions)
+        }
+
+        /// Items without a readable date are never held back nor dropped as too old
+        fn keeps_age(&self, item: &RssFeedItem, now: DateTime<Utc>) -> bool {
+            let Some(age) =
+                published_at(item).map(|published| now.signed_duration_since(published))
+            else {
+                return true;
+            };
+            let settled = self
+                .min_age_hours
+                .and_then(|hours| chrono::TimeDelta::try_hours(hours as i64))
+                .is_none_or(|min_age| age >= min_age);
+            let recent = self
+                .max_age_days
+                .and_then(|days| chrono::TimeDelta::try_days(days as i64))
+                .is_none_or(|max_age| age <= max_age);
+            settled && recent
+        }
+
+        pub fn keeps(&self, item: &RssFeedItem) -> bool {
+            !(self.hide_nsfw && item.nsfw) && self.keeps_keywords(item) && self.keeps_language(item)
+        }
+
+        /// All rules of the feed, `max_items` counts the items left after the other rules
+        pub fn apply(&self, items: Vec<RssFeedItem>, now: DateTime<Utc>) -> Vec<RssFeedItem> {
+            let mut items: Vec<RssFeedItem> = items
+                .into_iter()
+                .filter(|item| self.keeps(item) && self.keeps_age(item, now))
+                .collect();
+            newest_first(&mut items, self.max_items);
+            items
+        }
+    }
+
+    /// Sorts by publication date, newest first, and keeps at most `max_items`. The sort is
+    /// stable: items without a date keep their order at the end
+    pub fn newest_first(items: &mut Vec<RssFeedItem>, max_items: Option<usize>) {
+        items.sort_by_cached_key(|item| std::cmp::Reverse(published_at(item)));
+        items.truncate(max_items.unwrap_or(usize::MAX));
+    }
+
+    /// Filter for a feed url: its own entry, or the `*` one
+    pub fn for_feed<'a>(
+        filters: &'a HashMap<String, FeedFilter>,
+        url: &str,
+    ) -> Option<&'a FeedFilter> {
+        filters.get(url).or_else(|| filters.get(ALL_FEEDS))
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        fn item(title: &str, description: &str, nsfw: bool) -> RssFeedItem {
+            RssFeedItem {
+                title: title.to_string(),
+                link: String::new(),
+                source: "feed".to_string(),
+                description: Some(description.to_string()),
+                pub_date: None,
+                item_id: title.to_string(),
+                nsfw,
+                enclosure: None,
+            }
+        }
+
+        #[test]
+        fn drops_other_languages_and_nsfw_items() {
+            let filters: HashMap<String, FeedFilter> = serde_json::from_str(
+                r#"{"*": {"hide_nsfw": true}, "https://example.com/rss": {"languages": ["eng"]}}"#,
+            )
+            .unwrap();
+            let english = item(
+                "Rust release notes",
+                "<p>The new compiler version brings faster builds and better error messages for everyone.</p>",
+                true,
+            );
+            let german = item(
+                "Neue Version",
+                "<p>Der Übersetzer ist schneller geworden und die Fehlermeldungen sind viel verständlicher als früher, weil sie jetzt auch Hinweise enthalten.</p>",
+                false,
+            );
+
+            let feed = for_feed(&filters, "https://example.com/rss").unwrap();
+            assert!(feed.keeps(&english));
+            assert!(!feed.keeps(&german));
+            assert!(feed.keeps(&item("Kurz", "", false)));
+
+            let other = for_feed(&filters, "https://other.org/rss").unwrap();
+            assert!(!other.keeps(&english));
+            assert!(other.keeps(&german));
+            assert!(has_nsfw_title("[NSFW] something"));
+            assert!(!has_nsfw_title("Unsafe code"));
+        }
+
+        #[test]
+        fn mutes_keywords_and_limits_items() {
+            let filter: FeedFilter = serde_json::from_str(
+                r#"{"include": ["rust", "zig"], "exclude": ["Sponsored"], "max_items": 2, "min_age_hours": 1}"#,
+            )
+            .unwrap();
+            let now = Utc::now();
+            let dated = |title: &str, hours_ago: i64| RssFeedItem {
+                pub_date: Some(
+                    (now - chrono::TimeDelta::try_hours(hours_ago).unwrap()).to_rfc3339(),
+                ),
+                ..item(title, "", false)
+            };
+            let items = vec![
+                dated("Rust 2.0 announced", 0),
+                dated("Sponsored: learn Rust", 3),
+                dated("Python tips", 3),
+                dated("Zig comptime explained", 3),
+                item("Rust in the kernel", "", false),
+                dated("Rust async book", 5),
+            ];
+            let titles: Vec<String> = filter
+                .apply(items, now)
+                .into_iter()
+                .map(|item| item.title)
+                .collect();
+            assert_eq!(titles, vec!["Zig comptime explained", "Rust async book"]);
+
+            let filter = FeedFilter {
+                max_age_days: Some(1),
+                ..FeedFilter::default()
+            };
+            let mut items = vec![
+                item("Undated", "", false),
+                dated("Last week", 24 * 7),
+                dated("Earlier today", 5),
+                dated("Just now", 0),
+            ];
+            assert_eq!(filter.apply(items.clone(), now).len(), 3);
+            newest_first(&mut items, Some(3));
+            let titles: Vec<&str> = items.iter().map(|item| item.title.as_str()).collect();
+            assert_eq!(titles, vec!["Just now", "Earlier today", "Last week"]);
+        }
+    }
+}
+
 //this needs to be encapsulated and hidden
 pub mod hidden_items {
-    use std::collections::HashSet;
+    use std::collections::HashMap;
     use std::fs::{File, OpenOptions};
-    use std::io::{self, BufRead, BufReader, Write};
+    use std::io::{BufRead, BufReader, Write};
     use std::path::Path;
 
-    const HIDDEN_ITEMS_FILE: &str = "rss/hidden_rss_items.txt";
+    use super::RssFeedItem;
+    use crate::transaction::Transaction;
+
+    pub const HIDDEN_ITEMS_FILE: &str = "rss/hidden_rss_items.txt";
+
+    const DAY_SECS: i64 = 24 * 60 * 60;
+
+    /// One line per hidden item: `id`, or `id<TAB>unix time<TAB>title` since hiding got dated
+    #[derive(Debug, Clone, PartialEq)]
+    pub struct HiddenEntry {
+        pub item_id: String,
+        /// Unknown for the ids hidden before dates were kept
+        pub hidden_at: Option<i64>,
+        pub title: String,
+    }
+
+    impl HiddenEntry {
+        fn parse(line: &str) -> Option<Self> {
+            let mut fields = line.splitn(3, '\t');
+            let item_id = fields.next().filter(|id| !id.trim().is_empty())?;
+            Some(Self {
+                item_id: item_id.to_string(),
+                hidden_at: fields.next().and_then(|time| time.parse().ok()),
+                title: fields.next().unwrap_or_default().to_string(),
+            })
+        }
+
+        fn line(&self) -> String {
+            match self.hidden_at {
+                Some(hidden_at) => format!("{}\t{}\t{}\n", self.item_id, hidden_at, self.title),
+                None => format!("{}\n", self.item_id),
+            }
+        }
+    }
 
     pub struct HiddenItems {
-        items: HashSet<String>,
+        items: HashMap<String, HiddenEntry>,
+        persistent: bool,
+        /// Lines in the file, more than `items` once ids were hidden twice
+        lines_on_disk: usize,
     }
 
     impl HiddenItems {
+        /// In-memory only set, nothing is written to disk
         pub fn new() -> Self {
             Self {
-                items: HashSet::new(),
+                items: HashMap::new(),
+                persistent: false,
+                lines_on_disk: 0,
             }
         }
 
         pub fn load() -> anyhow::Result<Self> {
-            let mut items = HashSet::new();
+            let mut items = HashMap::new();
+            let mut lines_on_disk = 0;
 
             if Path::new(HIDDEN_ITEMS_FILE).exists() {
                 let file = File::open(HIDDEN_ITEMS_FILE)?;
                 let reader = BufReader::new(file);
 
                 for line in reader.lines() {
-                    let line = line?;
-                    if !line.trim().is_empty() {
-                        items.insert(line);
+                    if let Some(entry) = HiddenEntry::parse(&line?) {
+                        lines_on_disk += 1;
+                        items.insert(entry.item_id.clone(), entry);
                     }
                 }
             }
 
-            Ok(Self { items })
+            Ok(Self {
+                items,
+                persistent: true,
+                lines_on_disk,
+            })
+        }
+
+        fn entry(item: &RssFeedItem) -> HiddenEntry {
+            HiddenEntry {
+                item_id: item.item_id.clone(),
+                hidden_at: Some(chrono::Utc::now().timestamp()),
+                // one line per entry
+                title: item.title.split_whitespace().collect::<Vec<_>>().join(" "),
+            }
         }
 
         // No need for full save, we'll just append new items
-        pub fn hide_item(&mut self, item_id: String) -> anyhow::Result<()> {
-            if !self.items.contains(&item_id) {
-                // Open file in append mode, create if doesn't exist
-                let mut file = OpenOptions::new()
-                    .create(true)
-                    .append(true)
-                    .open(HIDDEN_ITEMS_FILE)?;
+        pub fn hide_item(&mut self, item: &RssFeedItem) -> anyhow::Result<()> {
+            if !self.items.contains_key(&item.item_id) {
+                let entry = Self::entry(item);
+                if self.persistent {
+                    // Open file in append mode, create if doesn't exist
+                    let mut file = OpenOptions::new()
+                        .create(true)
+                        .append(true)
+                        .open(HIDDEN_ITEMS_FILE)?;
 
-                // Write the new item with a newline
-                writeln!(file, "{}", item_id)?;
+                    // Write the new item with a newline
+                    file.write_all(entry.line().as_bytes())?;
+                    self.lines_on_disk += 1;
+                }
 
                 // Add to our in-memory set
-                self.items.insert(item_id);
+                self.items.insert(entry.item_id.clone(), entry);
             }
             Ok(())
         }
 
+        /// Same as `hide_item`, but the file is appended as part of the transaction
+        pub fn stage_hide(&mut self, item: &RssFeedItem, transaction: &mut Transaction) {
+            if self.items.contains_key(&item.item_id) {
+                return;
+            }
+            let entry = Self::entry(item);
+            if self.persistent {
+                transaction.append(HIDDEN_ITEMS_FILE, entry.line());
+                self.lines_on_disk += 1;
+            }
+            self.items.insert(entry.item_id.clone(), entry);
+        }
+
         pub fn is_hidden(&self, item_id: &str) -> bool {
-            self.items.contains(item_id)
+            self.items.contains_key(item_id)
+        }
+
+        /// Newest first, the undated ones last
+        pub fn entries(&self) -> Vec<&HiddenEntry> {
+            let mut entries: Vec<&HiddenEntry> = self.items.values().collect();
+            entries.sort_by(|a, b| b.hidden_at.cmp(&a.hidden_at).then(a.title.cmp(&b.title)));
+            entries
+        }
+
+        /// The items show up again the next time their feed has them
+        pub fn unhide(&mut self, item_ids: &[&str]) -> anyhow::Result<()> {
+            let before = self.items.len();
+            for item_id in item_ids {
+                self.items.remove(*item_id);
+            }
+            if self.items.len() < before {
+                self.compact()?;
+            }
+            Ok(())
+        }
+
+        /// Forgets ids hidden more than `max_age_days` ago, 0 keeps them forever. Undated ids
+        /// are dated `now` so they expire in turn. Returns how many were forgotten
+        pub fn expire(&mut self, max_age_days: u64, now: i64) -> anyhow::Result<usize> {
+            let mut changed = self.lines_on_disk > self.items.len();
+            for entry in self.items.values_mut() {
+                if entry.hidden_at.is_none() {
+                    entry.hidden_at = Some(now);
+                    changed = true;
+                }
+            }
+            let before = self.items.len();
+            if max_age_days > 0 {
+                let cutoff = now - max_age_days as i64 * DAY_SECS;
+                self.items
+                    .retain(|_, entry| entry.hidden_at.is_none_or(|time| time >= cutoff));
+            }
+            let expired = before - self.items.len();
+            if changed || expired > 0 {
+                self.compact()?;
+            }
+            Ok(expired)
+        }
+
+        /// Rewrites the file with one line per hidden id
+        fn compact(&mut self) -> anyhow::Result<()> {
+            if self.persistent {
+                let mut transaction = Transaction::new();
+                let content = self.entries().iter().map(|entry| entry.line()).collect();
+                transaction.replace(HIDDEN_ITEMS_FILE, content);
+                transaction.commit()?;
+            }
+            self.lines_on_disk = self.items.len();
+            Ok(())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn old_ids_expire_and_undated_ones_get_dated() {
+            let mut hidden = HiddenItems::new();
+            for (id, hidden_at) in [
+                ("old", Some(0)),
+                ("new", Some(90 * DAY_SECS)),
+                ("legacy", None),
+            ] {
+                hidden.items.insert(
+                    id.to_string(),
+                    HiddenEntry {
+                        item_id: id.to_string(),
+                        hidden_at,
+                        title: String::new(),
+                    },
+                );
+            }
+            assert_eq!(hidden.expire(30, 100 * DAY_SECS).unwrap(), 1);
+            assert!(!hidden.is_hidden("old"));
+            assert_eq!(
+                hidden
+                    .entries()
+                    .iter()
+                    .map(|e| e.item_id.as_str())
+                    .collect::<Vec<_>>(),
+                ["legacy", "new"]
+            );
+            hidden.unhide(&["new"]).unwrap();
+            assert!(!hidden.is_hidden("new"));
+
+            let entry = HiddenEntry::parse("id-1\t42\tA title\twith a tab").unwrap();
+            assert_eq!(entry.hidden_at, Some(42));
+            assert_eq!(HiddenEntry::parse(entry.line().trim_end()), Some(entry));
+            assert_eq!(HiddenEntry::parse("id-2").unwrap().hidden_at, None);
+        }
+    }
+}
+
+/// JSON Feed (https://jsonfeed.org) version 1 and 1.1
+mod json_feed {
+    use serde::Deserialize;
+
+    use super::{filters, RssFeedItem};
+
+    #[derive(Deserialize)]
+    struct Feed {
+        version: String,
+        title: String,
+        #[serde(default)]
+        items: Vec<Item>,
+    }
+
+    #[derive(Deserialize)]
+    struct Item {
+        id: serde_json::Value,
+        url: Option<String>,
+        external_url: Option<String>,
+        title: Option<String>,
+        content_html: Option<String>,
+        content_text: Option<String>,
+        summary: Option<String>,
+        date_published: Option<String>,
+        #[serde(default)]
+        tags: Vec<String>,
+        #[serde(default)]
+        attachments: Vec<Attachment>,
+    }
+
+    #[derive(Deserialize)]
+    struct Attachment {
+        url: String,
+    }
+
+    pub fn parse(content: &str) -> anyhow::Result<Vec<RssFeedItem>> {
+        let feed: Feed = serde_json::from_str(content)?;
+        if !feed.version.starts_with("https://jsonfeed.org/version/") {
+            anyhow::bail!("unknown version {}", feed.version);
+        }
+        Ok(feed
+            .items
+            .into_iter()
+            .map(|item| {
+                // ids are strings in 1.1, version 1 feeds sometimes have numbers
+                let id = match item.id {
+                    serde_json::Value::String(id) => id,
+                    other => other.to_string(),
+                };
+                let title = item
+                    .title
+                    .or_else(|| item.summary.clone())
+                    .unwrap_or_else(|| "Untitled".to_string());
+                RssFeedItem {
+                    nsfw: item.tags.iter().any(|tag| filters::is_nsfw_marker(tag))
+                        || filters::has_nsfw_title(&title),
+                    title,
+                    link: item.url.or(item.external_url).unwrap_or_default(),
+                    source: feed.title.clone(),
+                    description: item.content_html.or(item.content_text).or(item.summary),
+                    pub_date: item.date_published,
+                    item_id: format!("{}:{}", feed.title, id),
+                    enclosure: item.attachments.into_iter().next().map(|a| a.url),
+                }
+            })
+            .collect())
+    }
+}
+
+/// Items of every feed from the last fetch with the validators for conditional requests, so
+/// startup shows feeds right away and unchanged feeds aren't downloaded again
+pub mod feed_cache {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use super::RssFeedItem;
+    use crate::statefile::StateFile;
+
+    const FEED_CACHE_FILE: &str = "rss/feed_cache.json";
+
+    /// `ETag` and `Last-Modified` of the cached copy
+    #[derive(Clone, Debug, Default, PartialEq, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct Validators {
+        pub etag: Option<String>,
+        pub last_modified: Option<String>,
+    }
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct CachedFeed {
+        pub validators: Validators,
+        pub items: Vec<RssFeedItem>,
+    }
+
+    pub struct FeedCache {
+        feeds: HashMap<String, CachedFeed>,
+        file: StateFile,
+    }
+
+    impl FeedCache {
+        #[cfg(test)]
+        pub fn new() -> Self {
+            Self {
+                feeds: HashMap::new(),
+                file: StateFile::default(),
+            }
+        }
+
+        pub fn load() -> Self {
+            let file = StateFile::at(FEED_CACHE_FILE);
+            Self {
+                feeds: file.load(),
+                file,
+            }
+        }
+
+        pub fn save(&self) -> anyhow::Result<()> {
+            self.file.save_compact(&self.feeds)
+        }
+
+        pub fn get(&self, url: &str) -> Option<&CachedFeed> {
+            self.feeds.get(url)
+        }
+
+        pub fn store(&mut self, url: &str, feed: CachedFeed) {
+            self.feeds.insert(url.to_string(), feed);
+        }
+    }
+}
+
+/// Consecutive failures per feed url, kept across restarts for spotting dead feeds
+pub mod feed_health {
+    use std::collections::HashMap;
+
+    use serde::{Deserialize, Serialize};
+
+    use crate::statefile::StateFile;
+
+    const FEED_HEALTH_FILE: &str = "rss/feed_health.json";
+
+    #[derive(Clone, Debug, Default, Serialize, Deserialize)]
+    #[serde(default)]
+    pub struct FeedHealth {
+        pub failure_streak: u32,
+        pub last_error: Option<String>,
+        /// Unix timestamp of the last successful fetch
+        pub last_success: Option<i64>,
+    }
+
+    pub struct FeedHealthLog {
+        feeds: HashMap<String, FeedHealth>,
+        file: StateFile,
+    }
+
+    impl FeedHealthLog {
+        pub fn new() -> Self {
+            Self {
+                feeds: HashMap::new(),
+                file: StateFile::default(),
+            }
+        }
+
+        pub fn load() -> Self {
+            let file = StateFile::at(FEED_HEALTH_FILE);
+            Self {
+                feeds: file.load(),
+                file,
+            }
+        }
+
+        pub fn save(&self) -> anyhow::Result<()> {
+            self.file.save(&self.feeds)
+        }
+
+        pub fn record_success(&mut self, url: &str) {
+            let health = self.feeds.entry(url.to_string()).or_default();
+            health.failure_streak = 0;
+            health.last_error = None;
+            health.last_success = Some(chrono::Utc::now().timestamp());
+        }
+
+        /// Feeds whose last fetch failed, by url
+        pub fn failing(&self) -> Vec<(String, FeedHealth)> {
+            let mut failing: Vec<(String, FeedHealth)> = self
+                .feeds
+                .iter()
+                .filter(|(_, health)| health.last_error.is_some())
+                .map(|(url, health)| (url.clone(), health.clone()))
+                .collect();
+            failing.sort_by(|a, b| a.0.cmp(&b.0));
+            failing
+        }
+
+        /// Returns the failure streak including this failure
+        pub fn record_failure(&mut self, url: &str, error: &anyhow::Error) -> u32 {
+            let health = self.feeds.entry(url.to_string()).or_default();
+            health.failure_streak += 1;
+            health.last_error = Some(format!("{:#}", error));
+            health.failure_streak
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn status_error(code: u16) -> anyhow::Error {
+        HttpStatusError(reqwest::StatusCode::from_u16(code).unwrap()).into()
+    }
+
+    #[test]
+    fn failing_feeds_recover_on_success() {
+        let mut health = feed_health::FeedHealthLog::new();
+        health.record_failure("https://b.org/feed", &status_error(500));
+        health.record_failure("https://a.org/feed", &status_error(404));
+        health.record_failure("https://a.org/feed", &status_error(404));
+        let failing = health.failing();
+        assert_eq!(failing.len(), 2);
+        assert_eq!(failing[0].0, "https://a.org/feed");
+        assert_eq!(failing[0].1.failure_streak, 2);
+        health.record_success("https://b.org/feed");
+        assert_eq!(health.failing().len(), 1);
+    }
+
+    #[test]
+    fn retries_only_transient_failures() {
+        let policy = RetryPolicy {
+            max_attempts: 3,
+            base_delay: Duration::from_millis(1),
+            max_delay: Duration::from_millis(2),
+        };
+        let deadline = Instant::now() + Duration::from_secs(5);
+
+        let mut calls = 0;
+        let result = policy.run(deadline, || {
+            calls += 1;
+            if calls < 3 {
+                Err(status_error(503))
+            } else {
+                Ok(calls)
+            }
+        });
+        assert_eq!(result.unwrap(), 3);
+
+        calls = 0;
+        assert!(policy
+            .run(deadline, || -> anyhow::Result<()> {
+                calls += 1;
+                Err(status_error(404))
+            })
+            .is_err());
+        assert_eq!(calls, 1);
+
+        calls = 0;
+        assert!(policy
+            .run(Instant::now(), || -> anyhow::Result<()> {
+                calls += 1;
+                Err(status_error(429))
+            })
+            .is_err());
+        assert_eq!(calls, 1);
+
+        let mut health = feed_health::FeedHealthLog::new();
+        assert_eq!(health.record_failure("feed", &status_error(500)), 1);
+        assert_eq!(health.record_failure("feed", &status_error(500)), 2);
+        health.record_success("feed");
+        assert_eq!(health.record_failure("feed", &status_error(500)), 1);
+    }
+
+    #[test]
+    fn parses_subscription_options() {
+        std::env::set_var("PKT_TUI_TEST_FEED_TOKEN", "s3cret");
+        let subscription = Subscription::parse(
+            "https://rss.example.com/feed | basic alice:$PKT_TUI_TEST_FEED_TOKEN | header X-Auth-Token: abc:def",
+        )
+        .unwrap();
+        assert_eq!(subscription.url, "https://rss.example.com/feed");
+        assert_eq!(
+            subscription.basic_auth,
+            Some(("alice".to_string(), "s3cret".to_string()))
+        );
+        assert_eq!(
+            subscription.headers,
+            vec![("X-Auth-Token".to_string(), "abc:def".to_string())]
+        );
+        assert_eq!(
+            Subscription::parse("https://lwn.net/rss").unwrap(),
+            Subscription {
+                url: "https://lwn.net/rss".to_string(),
+                ..Default::default()
+            }
+        );
+        assert!(Subscription::parse("https://lwn.net/rss | token abc").is_err());
+    }
+
+    #[test]
+    fn cached_feed_round_trip() {
+        let content = r#"<?xml version="1.0"?>
+            <rss version="2.0"><channel><title>LWN</title><link>https://lwn.net</link>
+            <description>news</description>
+            <item><title>Kernel release</title><link>https://lwn.net/1</link><guid>1</guid></item>
+            </channel></rss>"#;
+        let items = RssManager::parse_feed("https://lwn.net/rss", content).unwrap();
+        assert_eq!(items[0].item_id, "LWN:1");
+
+        let podcast = r#"{
+            "version": "https://jsonfeed.org/version/1.1",
+            "title": "Podcast",
+            "items": [{"id": 7, "title": "Episode 7", "content_text": "notes",
+                       "attachments": [{"url": "https://cdn.example.com/7.mp3", "mime_type": "audio/mpeg"}]}]
+        }"#;
+        let episodes = RssManager::parse_feed("https://example.com/feed.json", podcast).unwrap();
+        assert_eq!(episodes[0].item_id, "Podcast:7");
+        assert_eq!(episodes[0].url(), "https://cdn.example.com/7.mp3");
+
+        let mut cache = feed_cache::FeedCache::new();
+        cache.store(
+            "https://lwn.net/rss",
+            feed_cache::CachedFeed {
+                validators: feed_cache::Validators {
+                    etag: Some("\"abc\"".to_string()),
+                    last_modified: None,
+                },
+                items,
+            },
+        );
+        let json = serde_json::to_string(cache.get("https://lwn.net/rss").unwrap()).unwrap();
+        let restored: feed_cache::CachedFeed = serde_json::from_str(&json).unwrap();
+        assert_eq!(restored.validators.etag.as_deref(), Some("\"abc\""));
+        assert_eq!(restored.items[0].link, "https://lwn.net/1");
+    }
+}
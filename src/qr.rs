@@ -0,0 +1,417 @@
+//! QR codes for handing a link over to a phone: byte mode with medium error correction, the
+//! smallest version the text fits into and the mask with the lowest penalty
+
+/// Error correction codewords per block for level M, by version
+const ECC_PER_BLOCK: [usize; 41] = [
+    0, 10, 16, 26, 18, 24, 16, 18, 22, 22, 26, 30, 22, 22, 24, 24, 28, 28, 26, 26, 26, 26, 28, 28,
+    28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28, 28,
+];
+/// Blocks the data is split into for level M, by version
+const BLOCKS: [usize; 41] = [
+    0, 1, 1, 1, 2, 2, 4, 4, 4, 5, 5, 5, 8, 9, 9, 10, 10, 11, 13, 14, 16, 17, 17, 18, 20, 21, 23,
+    25, 26, 28, 29, 31, 33, 35, 37, 38, 40, 43, 45, 47, 49,
+];
+/// Level M in the format information
+const FORMAT_ECC_BITS: u32 = 0;
+
+pub struct QrCode {
+    size: usize,
+    modules: Vec<Vec<bool>>,
+    function: Vec<Vec<bool>>,
+}
+
+/// Modules left for data and error correction once the patterns are drawn
+fn raw_data_modules(version: usize) -> usize {
+    let mut result = (16 * version + 128) * version + 64;
+    if version >= 2 {
+        let aligns = version / 7 + 2;
+        result -= (25 * aligns - 10) * aligns - 55;
+        if version >= 7 {
+            result -= 36;
+        }
+    }
+    result
+}
+
+fn data_codewords(version: usize) -> usize {
+    raw_data_modules(version) / 8 - ECC_PER_BLOCK[version] * BLOCKS[version]
+}
+
+fn gf_multiply(x: u8, y: u8) -> u8 {
+    let mut z: u32 = 0;
+    for i in (0..8).rev() {
+        z = (z << 1) ^ ((z >> 7) * 0x11D);
+        z ^= ((y as u32 >> i) & 1) * x as u32;
+    }
+    z as u8
+}
+
+fn rs_divisor(degree: usize) -> Vec<u8> {
+    let mut result = vec![0; degree];
+    result[degree - 1] = 1;
+    let mut root = 1;
+    for _ in 0..degree {
+        for j in 0..degree {
+            result[j] = gf_multiply(result[j], root);
+            if j + 1 < degree {
+                result[j] ^= result[j + 1];
+            }
+        }
+        root = gf_multiply(root, 0x02);
+    }
+    result
+}
+
+fn rs_remainder(data: &[u8], divisor: &[u8]) -> Vec<u8> {
+    let mut result = vec![0; divisor.len()];
+    for byte in data {
+        let factor = byte ^ result.remove(0);
+        result.push(0);
+        for (x, y) in result.iter_mut().zip(divisor) {
+            *x ^= gf_multiply(*y, factor);
+        }
+    }
+    result
+}
+
+/// Data split into blocks with their error correction, interleaved
+fn add_ecc_and_interleave(data: &[u8], version: usize) -> Vec<u8> {
+    let blocks = BLOCKS[version];
+    let ecc_len = ECC_PER_BLOCK[version];
+    let raw_codewords = raw_data_modules(version) / 8;
+    let short_blocks = blocks - raw_codewords % blocks;
+    let short_len = raw_codewords / blocks;
+    let divisor = rs_divisor(ecc_len);
+
+    let mut split = Vec::with_capacity(blocks);
+    let mut start = 0;
+    for i in 0..blocks {
+        let len = short_len - ecc_len + usize::from(i >= short_blocks);
+        let mut block = data[start..start + len].to_vec();
+        start += len;
+        let ecc = rs_remainder(&block, &divisor);
+        if i < short_blocks {
+            block.push(0);
+        }
+        block.extend(ecc);
+        split.push(block);
+    }
+    let mut result = Vec::with_capacity(raw_codewords);
+    for i in 0..split[0].len() {
+        for (j, block) in split.iter().enumerate() {
+            // the padding byte of short blocks isn't sent
+            if i != short_len - ecc_len || j >= short_blocks {
+                result.push(block[i]);
+            }
+        }
+    }
+    result
+}
+
+fn alignment_positions(version: usize, size: usize) -> Vec<usize> {
+    if version == 1 {
+        return Vec::new();
+    }
+    let aligns = version / 7 + 2;
+    let step = if version == 32 {
+        26
+    } else {
+        (version * 4 + aligns * 2 + 1) / (aligns * 2 - 2) * 2
+    };
+    let mut result = vec![6];
+    for i in 0..aligns - 1 {
+        result.insert(1, size - 7 - i * step);
+    }
+    result
+}
+
+fn mask_applies(mask: usize, x: usize, y: usize) -> bool {
+    match mask {
+        0 => (x + y).is_multiple_of(2),
+        1 => y.is_multiple_of(2),
+        2 => x.is_multiple_of(3),
+        3 => (x + y).is_multiple_of(3),
+        4 => (x / 3 + y / 2).is_multiple_of(2),
+        5 => x * y % 2 + x * y % 3 == 0,
+        6 => (x * y % 2 + x * y % 3).is_multiple_of(2),
+        _ => ((x + y) % 2 + x * y % 3).is_multiple_of(2),
+    }
+}
+
+impl QrCode {
+    /// `None` when the text doesn't fit into the largest version
+    pub fn encode(text: &str) -> Option<Self> {
+        let bytes = text.as_bytes();
+        let version = (1..=40).find(|version| {
+            let count_bits = if *version < 10 { 8 } else { 16 };
+            4 + count_bits + bytes.len() * 8 <= data_codewords(*version) * 8
+        })?;
+
+        let mut bits: Vec<bool> = Vec::new();
+        let mut push = |value: usize, len: usize| {
+            bits.extend((0..len).rev().map(|i| (value >> i) & 1 == 1));
+        };
+        push(0b0100, 4);
+        push(bytes.len(), if version < 10 { 8 } else { 16 });
+        for byte in bytes {
+            push(*byte as usize, 8);
+        }
+        let capacity = data_codewords(version) * 8;
+        let terminator = (capacity - bits.len()).min(4);
+        bits.extend(std::iter::repeat_n(false, terminator));
+        bits.extend(std::iter::repeat_n(false, (8 - bits.len() % 8) % 8));
+        let mut data: Vec<u8> = bits
+            .chunks(8)
+            .map(|byte| byte.iter().fold(0, |acc, bit| (acc << 1) | u8::from(*bit)))
+            .collect();
+        for pad in [0xEC, 0x11].into_iter().cycle() {
+            if data.len() == capacity / 8 {
+                break;
+            }
+            data.push(pad);
+        }
+
+        let size = version * 4 + 17;
+        let mut qr = QrCode {
+            size,
+            modules: vec![vec![false; size]; size],
+            function: vec![vec![false; size]; size],
+        };
+        qr.draw_function_patterns(version);
+        qr.draw_codewords(&add_ecc_and_interleave(&data, version));
+
+        let mask = (0..8)
+            .min_by_key(|mask| {
+                qr.apply_mask(*mask);
+                qr.draw_format_bits(*mask);
+                let penalty = qr.penalty();
+                qr.apply_mask(*mask);
+                penalty
+            })
+            .unwrap_or(0);
+        qr.apply_mask(mask);
+        qr.draw_format_bits(mask);
+        Some(qr)
+    }
+
+    pub fn is_dark(&self, x: usize, y: usize) -> bool {
+        self.modules[y][x]
+    }
+
+    fn set_function(&mut self, x: usize, y: usize, dark: bool) {
+        self.modules[y][x] = dark;
+        self.function[y][x] = true;
+    }
+
+    fn draw_function_patterns(&mut self, version: usize) {
+        let size = self.size;
+        for i in 0..size {
+            self.set_function(6, i, i % 2 == 0);
+            self.set_function(i, 6, i % 2 == 0);
+        }
+        for (cx, cy) in [(3, 3), (size - 4, 3), (3, size - 4)] {
+            for dy in -4isize..=4 {
+                for dx in -4isize..=4 {
+                    let (x, y) = (cx as isize + dx, cy as isize + dy);
+                    if (0..size as isize).contains(&x) && (0..size as isize).contains(&y) {
+                        let distance = dx.abs().max(dy.abs());
+                        self.set_function(x as usize, y as usize, distance != 2 && distance != 4);
+                    }
+                }
+            }
+        }
+        let positions = alignment_positions(version, size);
+        let last = positions.len().saturating_sub(1);
+        for (i, cx) in positions.iter().enumerate() {
+            for (j, cy) in positions.iter().enumerate() {
+                // the finder patterns are there
+                if (i == 0 || j == 0) && (i + j == 0 || i + j == last) {
+                    continue;
+                }
+                for dy in -2isize..=2 {
+                    for dx in -2isize..=2 {
+                        let (x, y) = ((*cx as isize + dx) as usize, (*cy as isize + dy) as usize);
+                        self.set_function(x, y, dx.abs().max(dy.abs()) != 1);
+                    }
+                }
+            }
+        }
+        // reserved for the format information, drawn once the mask is known
+        self.draw_format_bits(0);
+        if version >= 7 {
+            let mut remainder = version as u32;
+            for _ in 0..12 {
+                remainder = (remainder << 1) ^ ((remainder >> 11) * 0x1F25);
+            }
+            let bits = (version as u32) << 12 | remainder;
+            for i in 0..18 {
+                let dark = (bits >> i) & 1 == 1;
+                let (a, b) = (size - 11 + i % 3, i / 3);
+                self.set_function(a, b, dark);
+                self.set_function(b, a, dark);
+            }
+        }
+    }
+
+    fn draw_format_bits(&mut self, mask: usize) {
+        let data = FORMAT_ECC_BITS << 3 | mask as u32;
+        let mut remainder = data;
+        for _ in 0..10 {
+            remainder = (remainder << 1) ^ ((remainder >> 9) * 0x537);
+        }
+        let bits = (data << 10 | remainder) ^ 0x5412;
+        let bit = |i: usize| (bits >> i) & 1 == 1;
+        let size = self.size;
+        for i in 0..=5 {
+            self.set_function(8, i, bit(i));
+        }
+        self.set_function(8, 7, bit(6));
+        self.set_function(8, 8, bit(7));
+        self.set_function(7, 8, bit(8));
+        for i in 9..15 {
+            self.set_function(14 - i, 8, bit(i));
+        }
+        for i in 0..8 {
+            self.set_function(size - 1 - i, 8, bit(i));
+        }
+        for i in 8..15 {
+            self.set_function(8, size - 15 + i, bit(i));
+        }
+        self.set_function(8, size - 8, true);
+    }
+
+    /// Zigzags up and down in pairs of columns from the bottom right corner
+    fn draw_codewords(&mut self, data: &[u8]) {
+        let size = self.size;
+        let mut i = 0;
+        let mut right = size - 1;
+        while right >= 1 {
+            if right == 6 {
+                right = 5;
+            }
+            for vertical in 0..size {
+                for j in 0..2 {
+                    let x = right - j;
+                    let upward = (right + 1) & 2 == 0;
+                    let y = if upward {
+                        size - 1 - vertical
+                    } else {
+                        vertical
+                    };
+                    if !self.function[y][x] && i < data.len() * 8 {
+                        self.modules[y][x] = (data[i >> 3] >> (7 - (i & 7))) & 1 == 1;
+                        i += 1;
+                    }
+                }
+            }
+            if right < 2 {
+                break;
+            }
+            right -= 2;
+        }
+    }
+
+    /// Applying the same mask again takes it off
+    fn apply_mask(&mut self, mask: usize) {
+        for y in 0..self.size {
+            for x in 0..self.size {
+                if !self.function[y][x] && mask_applies(mask, x, y) {
+                    self.modules[y][x] = !self.modules[y][x];
+                }
+            }
+        }
+    }
+
+    /// Runs, 2x2 blocks, finder-like patterns and the dark/light balance, lower reads better
+    fn penalty(&self) -> usize {
+        let size = self.size;
+        let lines = (0..size)
+            .map(|y| (0..size).map(|x| self.modules[y][x]).collect::<Vec<_>>())
+            .chain((0..size).map(|x| (0..size).map(|y| self.modules[y][x]).collect()));
+        let finder = [
+            true, false, true, true, true, false, true, false, false, false, false,
+        ];
+        let mut result = 0;
+        for line in lines {
+            for run in line.chunk_by(|a, b| a == b) {
+                if run.len() >= 5 {
+                    result += run.len() - 2;
+                }
+            }
+            for window in line.windows(finder.len()) {
+                if window == finder || window.iter().rev().eq(finder.iter()) {
+                    result += 40;
+                }
+            }
+        }
+        for y in 0..size - 1 {
+            for x in 0..size - 1 {
+                let dark = self.modules[y][x];
+                if self.modules[y][x + 1] == dark
+                    && self.modules[y + 1][x] == dark
+                    && self.modules[y + 1][x + 1] == dark
+                {
+                    result += 3;
+                }
+            }
+        }
+        let dark = self.modules.iter().flatten().filter(|dark| **dark).count();
+        let total = size * size;
+        let deviation = (dark * 20).abs_diff(total * 10);
+        result + deviation.div_ceil(total).saturating_sub(1) * 10
+    }
+
+    /// Two rows of modules per line of half blocks with a quiet zone around, dark modules are
+    /// drawn with the foreground color
+    pub fn to_lines(&self, quiet_zone: usize) -> Vec<String> {
+        let total = self.size + quiet_zone * 2;
+        let dark = |x: usize, y: usize| {
+            x >= quiet_zone
+                && y >= quiet_zone
+                && x < quiet_zone + self.size
+                && y < quiet_zone + self.size
+                && self.is_dark(x - quiet_zone, y - quiet_zone)
+        };
+        (0..total)
+            .step_by(2)
+            .map(|y| {
+                (0..total)
+                    .map(|x| match (dark(x, y), dark(x, y + 1)) {
+                        (true, true) => '█',
+                        (true, false) => '▀',
+                        (false, true) => '▄',
+                        (false, false) => ' ',
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn encodes_a_link() {
+        let qr = QrCode::encode("https://example.com/articles/1").unwrap();
+        // 30 bytes need version 3 at level M
+        assert_eq!(qr.size, 29);
+        for (x, y) in [(0, 0), (22, 0), (0, 22)] {
+            assert!(qr.is_dark(x, y) && qr.is_dark(x + 6, y + 6) && qr.is_dark(x + 3, y + 3));
+            assert!(!qr.is_dark(x + 1, y + 1));
+        }
+        let lines = qr.to_lines(2);
+        assert_eq!(lines.len(), 17);
+        assert!(lines[1].starts_with("  █▀▀▀▀▀█"));
+
+        // the codewords of a block with their error correction divide evenly by the generator
+        let data: Vec<u8> = (0..16).collect();
+        let divisor = rs_divisor(10);
+        let mut codewords = data.clone();
+        codewords.extend(rs_remainder(&data, &divisor));
+        assert!(rs_remainder(&codewords, &divisor).iter().all(|x| *x == 0));
+
+        assert!(QrCode::encode(&"x".repeat(3000)).is_none());
+    }
+}
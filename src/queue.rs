@@ -0,0 +1,150 @@
+use std::fs;
+use std::io::ErrorKind;
+use std::path::{Path, PathBuf};
+
+use crate::transaction::Transaction;
+
+pub const QUEUE_FILE: &str = "reading_queue.txt";
+
+/// Local-only ordered list of item ids pinned above the date-sorted list
+pub struct ReadingQueue {
+    ids: Vec<String>,
+    file: Option<PathBuf>,
+}
+
+impl ReadingQueue {
+    /// In-memory only queue, nothing is written to disk
+    pub fn new() -> Self {
+        Self {
+            ids: Vec::new(),
+            file: None,
+        }
+    }
+
+    /// An unreadable file is logged and the queue starts empty, the next change replaces it
+    pub fn load(file: &Path) -> Self {
+        let ids = match fs::read_to_string(file) {
+            Ok(content) => content
+                .lines()
+                .map(|line| line.trim().to_string())
+                .filter(|line| !line.is_empty())
+                .collect(),
+            Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+            Err(e) => {
+                log::error!("Ignoring broken {}: {}", file.display(), e);
+                Vec::new()
+            }
+        };
+        Self {
+            ids,
+            file: Some(file.to_path_buf()),
+        }
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        if let Some(file) = &self.file {
+            fs::write(file, self.ids.join("\n"))?;
+        }
+        Ok(())
+    }
+
+    pub fn position(&self, item_id: &str) -> Option<usize> {
+        self.ids.iter().position(|id| id == item_id)
+    }
+
+    /// Pins the item to the end of the queue or unpins it if it's already there
+    pub fn toggle(&mut self, item_id: &str) -> anyhow::Result<()> {
+        match self.position(item_id) {
+            Some(pos) => {
+                self.ids.remove(pos);
+            }
+            None => self.ids.push(item_id.to_string()),
+        }
+        self.save()
+    }
+
+    pub fn remove(&mut self, item_id: &str) -> anyhow::Result<()> {
+        if let Some(pos) = self.position(item_id) {
+            self.ids.remove(pos);
+            self.save()?;
+        }
+        Ok(())
+    }
+
+    /// Same as `remove`, but the file is written as part of the transaction
+    pub fn stage_remove(&mut self, item_id: &str, transaction: &mut Transaction) {
+        if let Some(pos) = self.position(item_id) {
+            self.ids.remove(pos);
+            if let Some(file) = &self.file {
+                transaction.replace(file, self.ids.join("\n"));
+            }
+        }
+    }
+
+    /// Keeps the place of an item that got a new id, as part of the transaction
+    pub fn stage_replace(&mut self, item_id: &str, new_id: &str, transaction: &mut Transaction) {
+        if let Some(pos) = self.position(item_id) {
+            self.ids[pos] = new_id.to_string();
+            if let Some(file) = &self.file {
+                transaction.replace(file, self.ids.join("\n"));
+            }
+        }
+    }
+
+    /// Moves the item by `delta` positions within the queue. Returns false if the item isn't queued
+    pub fn move_item(&mut self, item_id: &str, delta: isize) -> anyhow::Result<bool> {
+        if let Some(pos) = self.position(item_id) {
+            let new_pos = (pos as isize + delta).clamp(0, self.ids.len() as isize - 1) as usize;
+            if new_pos != pos {
+                let id = self.ids.remove(pos);
+                self.ids.insert(new_pos, id);
+                self.save()?;
+            }
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    /// Drops ids of items that no longer exist in the list
+    pub fn retain_existing<F>(&mut self, exists: F) -> anyhow::Result<()>
+    where
+        F: Fn(&str) -> bool,
+    {
+        let before = self.ids.len();
+        self.ids.retain(|id| exists(id));
+        if self.ids.len() != before {
+            self.save()?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn toggle_and_reorder() {
+        let mut queue = ReadingQueue::new();
+        queue.toggle("1").unwrap();
+        queue.toggle("2").unwrap();
+        queue.toggle("3").unwrap();
+        assert_eq!(queue.ids, ["1", "2", "3"]);
+
+        assert!(queue.move_item("3", -1).unwrap());
+        assert_eq!(queue.ids, ["1", "3", "2"]);
+        assert!(queue.move_item("1", -5).unwrap());
+        assert_eq!(queue.ids, ["1", "3", "2"]);
+        assert!(!queue.move_item("42", 1).unwrap());
+
+        queue.toggle("3").unwrap();
+        assert_eq!(queue.ids, ["1", "2"]);
+
+        queue.stage_replace("1", "7", &mut Transaction::new());
+        assert_eq!(queue.ids, ["7", "2"]);
+
+        queue.retain_existing(|id| id == "2").unwrap();
+        assert_eq!(queue.ids, ["2"]);
+    }
+}
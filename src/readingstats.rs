@@ -1,7 +1,13 @@
-use chrono::{DateTime, Utc};
+use std::collections::HashMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::Path;
+
+use chrono::{DateTime, Datelike, NaiveDate, Utc, Weekday};
 use log::error;
+use serde::{Deserialize, Serialize};
 
-use crate::{storage::PocketItem, TableRow};
+use crate::{storage::PocketItem, timezone, TableRow};
 //----
 pub struct Stats {
     articles_added: usize,
@@ -12,18 +18,98 @@ pub struct Stats {
     videos_read: usize,
 }
 
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsPeriods {
+    /// last 7 and last 30 days
+    #[default]
+    Rolling,
+    /// current calendar week and current calendar month
+    Calendar,
+}
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum WeekStart {
+    #[default]
+    Monday,
+    Sunday,
+}
+
+impl From<WeekStart> for Weekday {
+    fn from(week_start: WeekStart) -> Self {
+        match week_start {
+            WeekStart::Monday => Weekday::Mon,
+            WeekStart::Sunday => Weekday::Sun,
+        }
+    }
+}
+
+/// Reading stats above the table, `H` cycles through the states
+#[derive(Clone, Copy, Debug, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum StatsPanel {
+    /// Bars of the week and the counts of the selected day
+    #[default]
+    Expanded,
+    /// A single line with the totals
+    Collapsed,
+    Hidden,
+}
+
+impl StatsPanel {
+    pub fn next(self) -> Self {
+        match self {
+            StatsPanel::Expanded => StatsPanel::Collapsed,
+            StatsPanel::Collapsed => StatsPanel::Hidden,
+            StatsPanel::Hidden => StatsPanel::Expanded,
+        }
+    }
+
+    /// Lines taken from the table
+    pub fn height(self) -> u16 {
+        match self {
+            StatsPanel::Expanded => 7,
+            StatsPanel::Collapsed => 1,
+            StatsPanel::Hidden => 0,
+        }
+    }
+}
+
 pub struct TotalStats {
     pub today_stats: Stats,
     pub week_stats: Stats,
     pub month_stats: Stats,
+    periods: StatsPeriods,
+    week_start: WeekStart,
 }
 
 impl TotalStats {
     pub fn new() -> Self {
+        Self::with_periods(StatsPeriods::default(), WeekStart::default())
+    }
+
+    pub fn with_periods(periods: StatsPeriods, week_start: WeekStart) -> Self {
         TotalStats {
             today_stats: Stats::new(),
             week_stats: Stats::new(),
             month_stats: Stats::new(),
+            periods,
+            week_start,
+        }
+    }
+
+    /// (same week, same month) for the date relative to today
+    fn buckets(&self, today: NaiveDate, date: NaiveDate, days_ago: i64) -> (bool, bool) {
+        match self.periods {
+            StatsPeriods::Rolling => (days_ago <= 7, days_ago <= 30),
+            StatsPeriods::Calendar => {
+                let week_start = Weekday::from(self.week_start);
+                (
+                    today.week(week_start).first_day() == date.week(week_start).first_day(),
+                    today.year() == date.year() && today.month() == date.month(),
+                )
+            }
         }
     }
 
@@ -33,20 +119,33 @@ impl TotalStats {
         today: &chrono::DateTime<Utc>,
         is_read: bool,
         read_ts: i64,
+    ) {
+        self.track_type(item.item_type(), today, is_read, read_ts);
+    }
+
+    pub fn track_type(
+        &mut self,
+        item_type: &str,
+        today: &chrono::DateTime<Utc>,
+        is_read: bool,
+        read_ts: i64,
     ) {
         let datetime_ts = DateTime::from_timestamp(read_ts, 0).expect("invalid timestamp");
         let datetime: DateTime<Utc> = datetime_ts.to_utc();
         let duration = *today - datetime;
 
-        if today.date_naive() == datetime.date_naive() {
-            self.today_stats.increment(item.item_type(), is_read);
-            self.week_stats.increment(item.item_type(), is_read);
-            self.month_stats.increment(item.item_type(), is_read);
-        } else if duration.num_days() <= 7 {
-            self.week_stats.increment(item.item_type(), is_read);
-            self.month_stats.increment(item.item_type(), is_read);
-        } else if duration.num_days() <= 30 {
-            self.month_stats.increment(item.item_type(), is_read);
+        let today_date = timezone::date_of(today.timestamp());
+        let date = timezone::date_of(read_ts);
+        let (same_week, same_month) = self.buckets(today_date, date, duration.num_days());
+
+        if today_date == date {
+            self.today_stats.increment(item_type, is_read);
+        }
+        if today_date == date || same_week {
+            self.week_stats.increment(item_type, is_read);
+        }
+        if today_date == date || same_month {
+            self.month_stats.increment(item_type, is_read);
         }
     }
 
@@ -69,6 +168,14 @@ impl Stats {
         }
     }
 
+    pub fn added(&self) -> usize {
+        self.articles_added + self.pdfs_added + self.videos_added
+    }
+
+    pub fn read(&self) -> usize {
+        self.articles_read + self.pdfs_read + self.videos_read
+    }
+
     fn increment(&mut self, item_type: &str, is_read: bool) {
         match item_type {
             "pdf" => {
@@ -99,6 +206,47 @@ impl Stats {
     }
 }
 
+/// Items opened from the list, one JSON line per read. Deletes and favorites only tell that an
+/// item was read at some point, this log tells when
+pub const READ_EVENTS_FILE: &str = "read_events.log";
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReadEvent {
+    pub item_id: String,
+    pub item_type: String,
+    pub timestamp: i64,
+}
+
+pub fn record_read(path: &Path, event: &ReadEvent) -> anyhow::Result<()> {
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    writeln!(file, "{}", serde_json::to_string(event)?)?;
+    Ok(())
+}
+
+/// First read of every item, an item opened again isn't read twice. Broken lines are skipped
+pub fn load_read_events(path: &Path) -> HashMap<String, ReadEvent> {
+    let mut events: HashMap<String, ReadEvent> = HashMap::new();
+    let Ok(content) = fs::read_to_string(path) else {
+        return events;
+    };
+    for line in content.lines().filter(|line| !line.trim().is_empty()) {
+        match serde_json::from_str::<ReadEvent>(line) {
+            Ok(event) => {
+                events
+                    .entry(event.item_id.clone())
+                    .and_modify(|first| {
+                        if event.timestamp < first.timestamp {
+                            *first = event.clone();
+                        }
+                    })
+                    .or_insert(event);
+            }
+            Err(e) => error!("Skipping read event {}: {}", line, e),
+        }
+    }
+    events
+}
+
 /**
 Text: │  23 added
      _│_   2 read
@@ -173,4 +321,70 @@ pub fn render_stats(_today_stats: &Stats, week_stats: &Stats, _month_stats: &Sta
     output
 }
 
+/// "Week: 12 added · 5 read", the collapsed stats panel
+pub fn week_summary(week_stats: &Stats) -> String {
+    format!(
+        "Week: {} added · {} read",
+        week_stats.added(),
+        week_stats.read()
+    )
+}
+
 //----
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stats_panel_cycles_back_to_expanded() {
+        let mut panel = StatsPanel::default();
+        let heights: Vec<u16> = (0..3)
+            .map(|_| {
+                panel = panel.next();
+                panel.height()
+            })
+            .collect();
+        assert_eq!(heights, [1, 0, 7]);
+        let mut week = Stats::new();
+        week.articles_added = 3;
+        week.videos_read = 2;
+        assert_eq!(week_summary(&week), "Week: 3 added · 2 read");
+    }
+
+    #[test]
+    fn calendar_week_respects_week_start() {
+        let date = |s: &str| NaiveDate::parse_from_str(s, "%Y-%m-%d").unwrap();
+        // 2024-04-01 is a Monday
+        let today = date("2024-04-01");
+        let sunday = date("2024-03-31");
+
+        let monday_weeks = TotalStats::with_periods(StatsPeriods::Calendar, WeekStart::Monday);
+        assert_eq!(monday_weeks.buckets(today, sunday, 1), (false, false));
+
+        let sunday_weeks = TotalStats::with_periods(StatsPeriods::Calendar, WeekStart::Sunday);
+        assert_eq!(sunday_weeks.buckets(today, sunday, 1), (true, false));
+
+        let rolling = TotalStats::new();
+        assert_eq!(rolling.buckets(today, sunday, 1), (true, true));
+    }
+
+    #[test]
+    fn keeps_the_first_read_of_an_item() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join(READ_EVENTS_FILE);
+        let read = |item_id: &str, timestamp| ReadEvent {
+            item_id: item_id.to_string(),
+            item_type: "article".to_string(),
+            timestamp,
+        };
+        record_read(&path, &read("1", 200)).unwrap();
+        record_read(&path, &read("1", 100)).unwrap();
+        record_read(&path, &read("2", 300)).unwrap();
+        fs::write(&path, fs::read_to_string(&path).unwrap() + "not an event\n").unwrap();
+
+        let events = load_read_events(&path);
+        assert_eq!(events.len(), 2);
+        assert_eq!(events["1"], read("1", 100));
+    }
+}
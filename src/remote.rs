@@ -0,0 +1,171 @@
+use std::io::{BufRead, BufReader, Write};
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::Path;
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+use std::time::Duration;
+
+use anyhow::Context;
+use log::error;
+use serde::{Deserialize, Serialize};
+
+use crate::events;
+
+/// Socket of the running instance, next to the other data files
+pub const SOCKET_FILE: &str = "control.sock";
+
+/// The UI thread might be busy with a refresh, a script shouldn't wait forever
+const REPLY_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// Connections are served one by one, a client that doesn't send its line or doesn't read the
+/// answer must not hold up the next ones
+const CLIENT_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// One JSON object per line, e.g. `{"command": "add", "url": "https://lwn.net/Articles/1/"}`
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "command", rename_all = "lowercase")]
+pub enum Request {
+    Add {
+        url: String,
+        #[serde(default)]
+        tags: Vec<String>,
+    },
+    Refresh,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+pub struct Response {
+    pub ok: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+impl From<anyhow::Result<()>> for Response {
+    fn from(result: anyhow::Result<()>) -> Self {
+        Response {
+            ok: result.is_ok(),
+            error: result.err().map(|e| format!("{:#}", e)),
+        }
+    }
+}
+
+/// Request waiting for the UI thread, the connection is answered through `reply`
+pub struct RemoteRequest {
+    pub request: Request,
+    reply: Sender<Response>,
+}
+
+impl RemoteRequest {
+    pub fn reply(self, result: anyhow::Result<()>) {
+        // the client may have given up already
+        let _ = self.reply.send(result.into());
+    }
+}
+
+/// Accepts connections on a background thread and hands the requests to the UI thread
+pub struct ControlSocket {
+    receiver: Receiver<RemoteRequest>,
+}
+
+impl ControlSocket {
+    pub fn listen() -> anyhow::Result<Self> {
+        let path = Path::new(SOCKET_FILE);
+        if path.exists() {
+            if UnixStream::connect(path).is_ok() {
+                anyhow::bail!("Another instance is already listening on {}", SOCKET_FILE);
+            }
+            // left over from a crash
+            std::fs::remove_file(path)?;
+        }
+        let listener = UnixListener::bind(path)
+            .with_context(|| format!("Failed to listen on {}", SOCKET_FILE))?;
+        let (sender, receiver) = mpsc::channel();
+        thread::spawn(move || {
+            for stream in listener.incoming() {
+                let result = stream
+                    .map_err(anyhow::Error::from)
+                    .and_then(|stream| serve(stream, &sender));
+                if let Err(e) = result {
+                    error!("Control socket: {:#}", e);
+                }
+            }
+        });
+        Ok(Self { receiver })
+    }
+
+    pub fn pending(&self) -> Vec<RemoteRequest> {
+        self.receiver.try_iter().collect()
+    }
+}
+
+impl Drop for ControlSocket {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(SOCKET_FILE);
+    }
+}
+
+fn serve(stream: UnixStream, sender: &Sender<RemoteRequest>) -> anyhow::Result<()> {
+    stream.set_read_timeout(Some(CLIENT_TIMEOUT))?;
+    stream.set_write_timeout(Some(CLIENT_TIMEOUT))?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let response = match serde_json::from_str::<Request>(&line) {
+        Ok(request) => {
+            let (reply, answer) = mpsc::channel();
+            sender.send(RemoteRequest { request, reply })?;
+            events::wake();
+            answer
+                .recv_timeout(REPLY_TIMEOUT)
+                .unwrap_or_else(|_| Err(anyhow::anyhow!("No answer from the app")).into())
+        }
+        Err(e) => Err(anyhow::anyhow!("Invalid request: {}", e)).into(),
+    };
+    writeln!(&stream, "{}", serde_json::to_string(&response)?)?;
+    Ok(())
+}
+
+/// `add <url> [tags...]` or `refresh`
+pub fn parse_args(args: &[String]) -> anyhow::Result<Request> {
+    match args {
+        [command, url, tags @ ..] if command == "add" => Ok(Request::Add {
+            url: url.clone(),
+            tags: tags.to_vec(),
+        }),
+        [command] if command == "refresh" => Ok(Request::Refresh),
+        _ => anyhow::bail!("Usage: pkt-tui --remote add <url> [tags...] | --remote refresh"),
+    }
+}
+
+/// Sends the request to the running instance, for `pkt-tui --remote ...`
+pub fn send(request: &Request) -> anyhow::Result<()> {
+    let stream = UnixStream::connect(SOCKET_FILE)
+        .with_context(|| format!("pkt-tui isn't running here, no {}", SOCKET_FILE))?;
+    writeln!(&stream, "{}", serde_json::to_string(request)?)?;
+    let mut line = String::new();
+    BufReader::new(&stream).read_line(&mut line)?;
+    let response: Response = serde_json::from_str(&line)?;
+    match response.error {
+        Some(e) => Err(anyhow::anyhow!(e)),
+        None => Ok(()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_remote_arguments() {
+        let args = |args: &[&str]| args.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let add = parse_args(&args(&["add", "https://lwn.net", "linux"])).unwrap();
+        assert_eq!(
+            serde_json::to_string(&add).unwrap(),
+            r#"{"command":"add","url":"https://lwn.net","tags":["linux"]}"#
+        );
+        assert_eq!(
+            serde_json::from_str::<Request>(r#"{"command": "refresh"}"#).unwrap(),
+            Request::Refresh
+        );
+        assert!(parse_args(&args(&["delete", "1"])).is_err());
+    }
+}
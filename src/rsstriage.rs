@@ -0,0 +1,930 @@
+use std::cell::Cell;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    layout::{Constraint, Layout, Rect},
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{
+        Block, BorderType, Borders, Clear, List, ListItem, ListState, Paragraph, Scrollbar,
+        ScrollbarOrientation, ScrollbarState,
+    },
+    Frame,
+};
+
+use crate::browserhistory;
+use crate::logo::OCEANIC_NEXT;
+use crate::mdview::{self, MarkdownView, MarkdownWidget};
+use crate::pocket::{GetPocketSync, NewItem};
+use crate::popup::PopupGeometry;
+use crate::prss::{self, feed_health::FeedHealth, RssFeedItem};
+use crate::transaction::Transaction;
+use crate::{FilteredItems, TableColors};
+
+/// How long the result of the last action stays in the footer
+const STATUS_TIMEOUT: Duration = Duration::from_secs(5);
+
+const SIDEBAR_WIDTH: u16 = 30;
+
+/// Line of the item list: a feed header when items are grouped, or an index into `items`
+#[derive(Debug, Clone, PartialEq)]
+enum Row {
+    Header(String),
+    Item(usize),
+}
+
+/// Article fetched for an item whose feed only has a summary
+#[derive(Debug, Clone)]
+pub enum FullText {
+    Loading,
+    Loaded(String),
+    Failed(String),
+}
+
+/// Full-screen RSS triage: source sidebar, item list and a preview of the current item
+pub struct RssTriageState {
+    pub items: FilteredItems<RssFeedItem>,
+    /// Index into `rows()`
+    pub selected_index: usize,
+    pub scroll_offset: usize,
+    pub visible_items: usize,
+    hidden_items: prss::hidden_items::HiddenItems,
+    status_message: Option<(String, Instant)>,
+    /// Items waiting for tags before being added to Pocket
+    pending_pocket_items: Vec<RssFeedItem>,
+    marked: HashSet<String>,
+    /// Items whose link is already in the Pocket library
+    saved: HashSet<String>,
+    source_filter: Option<String>,
+    /// Sources whose items are folded under their header
+    collapsed: HashSet<String>,
+    /// Newest first across feeds instead of grouped by feed
+    sort_by_date: bool,
+    pub show_preview: bool,
+    /// The preview of the current item over the whole screen, for reading before deciding
+    pub reader: bool,
+    /// Items of each `d`/`D` with their position in `items`, the last one is undone by `u`
+    undo_stack: Vec<Vec<(usize, RssFeedItem)>>,
+    /// Selected row of the hidden items browser, when it's open
+    pub hidden_browser: Option<usize>,
+    /// Clamped to the preview height when rendered
+    preview_scroll: Cell<usize>,
+    /// Filled by background fetches, by item id
+    pub full_texts: Arc<Mutex<HashMap<String, FullText>>>,
+    pub changes_made: bool,
+}
+
+impl RssTriageState {
+    pub fn new(items: Vec<RssFeedItem>, visible_items: usize) -> anyhow::Result<Self> {
+        let hidden_items = prss::hidden_items::HiddenItems::load()?;
+        Ok(Self::with_hidden_items(items, visible_items, hidden_items))
+    }
+
+    pub fn with_hidden_items(
+        mut items: Vec<RssFeedItem>,
+        visible_items: usize,
+        hidden_items: prss::hidden_items::HiddenItems,
+    ) -> Self {
+        items.retain(|item| !hidden_items.is_hidden(&item.item_id));
+
+        let mut state = Self {
+            items: FilteredItems::new(items),
+            selected_index: 0,
+            scroll_offset: 0,
+            visible_items,
+            hidden_items,
+            status_message: None,
+            pending_pocket_items: Vec::new(),
+            marked: HashSet::new(),
+            saved: HashSet::new(),
+            source_filter: None,
+            collapsed: HashSet::new(),
+            sort_by_date: false,
+            show_preview: true,
+            reader: false,
+            undo_stack: Vec::new(),
+            hidden_browser: None,
+            preview_scroll: Cell::new(0),
+            full_texts: Arc::new(Mutex::new(HashMap::new())),
+            changes_made: false,
+        };
+        state.select_first_item();
+        state
+    }
+
+    /// Marks (or drops, with `hide`) items already saved to Pocket. Urls are normalized with
+    /// `browserhistory::normalize_url`
+    pub fn mark_saved(&mut self, saved_urls: &HashSet<String>, hide: bool) {
+        self.saved = self
+            .items
+            .items
+            .iter()
+            .filter(|item| saved_urls.contains(&browserhistory::normalize_url(item.url())))
+            .map(|item| item.item_id.clone())
+            .collect();
+        if hide {
+            let saved = std::mem::take(&mut self.saved);
+            self.remove_items(&saved);
+        }
+    }
+
+    pub fn current(&self) -> Option<&RssFeedItem> {
+        match self.rows().get(self.selected_index) {
+            Some(Row::Item(index)) => self.items.get(*index),
+            _ => None,
+        }
+    }
+
+    /// Feeds are grouped under headers unless a single source is shown or items are sorted by date
+    fn rows(&self) -> Vec<Row> {
+        let mut indexed: Vec<(usize, &RssFeedItem)> = self.items.iter().enumerate().collect();
+        if self.sort_by_date {
+            // stable, so items without a date keep the feed order at the end
+            indexed.sort_by_key(|(_, item)| std::cmp::Reverse(prss::filters::published_at(item)));
+            return indexed.into_iter().map(|(i, _)| Row::Item(i)).collect();
+        }
+        if self.source_filter.is_some() {
+            return indexed.into_iter().map(|(i, _)| Row::Item(i)).collect();
+        }
+        indexed.sort_by(|(_, a), (_, b)| a.source.cmp(&b.source));
+        let mut rows = Vec::with_capacity(indexed.len());
+        for (i, item) in indexed {
+            if rows.is_empty() || self.row_source(&rows[rows.len() - 1]) != Some(&item.source) {
+                rows.push(Row::Header(item.source.clone()));
+            }
+            if !self.collapsed.contains(&item.source) {
+                rows.push(Row::Item(i));
+            }
+        }
+        rows
+    }
+
+    fn row_source<'a>(&'a self, row: &'a Row) -> Option<&'a String> {
+        match row {
+            Row::Header(source) => Some(source),
+            Row::Item(index) => self.items.get(*index).map(|item| &item.source),
+        }
+    }
+
+    /// Source of the selected item or header
+    fn current_source(&self) -> Option<String> {
+        let rows = self.rows();
+        rows.get(self.selected_index)
+            .and_then(|row| self.row_source(row))
+            .cloned()
+    }
+
+    pub fn on_header(&self) -> bool {
+        matches!(self.rows().get(self.selected_index), Some(Row::Header(_)))
+    }
+
+    fn select_first_item(&mut self) {
+        self.selected_index = self
+            .rows()
+            .iter()
+            .position(|row| matches!(row, Row::Item(_)))
+            .unwrap_or(0);
+        self.scroll_offset = 0;
+        self.preview_scroll.set(0);
+        self.move_selection(0);
+    }
+
+    /// Folds or unfolds the feed of the selected row, the selection moves to its header
+    pub fn toggle_collapsed(&mut self) {
+        let Some(source) = self.current_source() else {
+            return;
+        };
+        if !self.collapsed.remove(&source) {
+            self.collapsed.insert(source.clone());
+        }
+        let header = Row::Header(source);
+        if let Some(pos) = self.rows().iter().position(|row| *row == header) {
+            self.move_selection(pos as isize - self.selected_index as isize);
+        }
+    }
+
+    pub fn toggle_sort(&mut self) {
+        self.sort_by_date = !self.sort_by_date;
+        self.select_first_item();
+    }
+
+    /// Moves to the first row of the next (or previous) feed
+    pub fn jump_to_feed(&mut self, forward: bool) {
+        let rows = self.rows();
+        let sources: Vec<Option<&String>> = rows.iter().map(|row| self.row_source(row)).collect();
+        let group_start = |i: usize| i == 0 || sources[i] != sources[i - 1];
+        let target = if forward {
+            (self.selected_index + 1..rows.len()).find(|&i| group_start(i))
+        } else {
+            // start of the current feed, or of the previous one when already there
+            (0..self.selected_index).rev().find(|&i| group_start(i))
+        };
+        if let Some(target) = target {
+            self.move_selection(target as isize - self.selected_index as isize);
+        }
+    }
+
+    /// Sources with item counts, sorted by name
+    fn sources(&self) -> Vec<(&str, usize)> {
+        let mut counts = BTreeMap::new();
+        for item in &self.items.items {
+            *counts.entry(item.source.as_str()).or_insert(0) += 1;
+        }
+        counts.into_iter().collect()
+    }
+
+    /// Switches the sidebar selection, `None` (all sources) sits before the first source
+    pub fn cycle_source(&mut self, delta: isize) {
+        let sources: Vec<String> = self
+            .sources()
+            .into_iter()
+            .map(|(source, _)| source.to_string())
+            .collect();
+        let current = self
+            .source_filter
+            .as_ref()
+            .and_then(|filter| sources.iter().position(|s| s == filter))
+            .map_or(0, |pos| pos as isize + 1);
+        let next = (current + delta).rem_euclid(sources.len() as isize + 1);
+        self.source_filter = (next > 0).then(|| sources[next as usize - 1].clone());
+        self.refilter();
+        self.select_first_item();
+    }
+
+    fn refilter(&mut self) {
+        match self.source_filter.clone() {
+            Some(source) => self.items.apply_filter(|item| item.source == source),
+            None => self.items.clear_filter(),
+        }
+        self.move_selection(0);
+    }
+
+    pub fn move_selection(&mut self, delta: isize) {
+        let new_index = self.selected_index as isize + delta;
+        let row_count = self.rows().len() as isize;
+        self.selected_index = new_index.clamp(0, (row_count - 1).max(0)) as usize;
+        self.preview_scroll.set(0);
+        if self.selected_index < self.scroll_offset {
+            self.scroll_offset = self.selected_index;
+        } else if self.selected_index >= self.scroll_offset + self.visible_items {
+            self.scroll_offset = self.selected_index - self.visible_items + 1;
+        }
+    }
+
+    pub fn scroll_preview(&mut self, delta: isize) {
+        let scroll = self.preview_scroll.get().saturating_add_signed(delta);
+        self.preview_scroll.set(scroll);
+    }
+
+    pub fn open_reader(&mut self) {
+        self.reader = true;
+        self.preview_scroll.set(0);
+    }
+
+    pub fn toggle_mark(&mut self) {
+        if let Some(item_id) = self.current().map(|item| item.item_id.clone()) {
+            if !self.marked.remove(&item_id) {
+                self.marked.insert(item_id);
+            }
+            self.move_selection(1);
+        }
+    }
+
+    /// Marked items, or the current one when nothing is marked
+    fn targets(&self) -> Vec<RssFeedItem> {
+        if self.marked.is_empty() {
+            self.current().cloned().into_iter().collect()
+        } else {
+            self.items
+                .items
+                .iter()
+                .filter(|item| self.marked.contains(&item.item_id))
+                .cloned()
+                .collect()
+        }
+    }
+
+    /// Takes note of where hidden items were, so `undo_hide` can put them back
+    fn push_undo(&mut self, item_ids: &HashSet<String>) {
+        let positions: Vec<(usize, RssFeedItem)> = self
+            .items
+            .items
+            .iter()
+            .enumerate()
+            .filter(|(_, item)| item_ids.contains(&item.item_id))
+            .map(|(position, item)| (position, item.clone()))
+            .collect();
+        if !positions.is_empty() {
+            self.undo_stack.push(positions);
+        }
+    }
+
+    /// Unhides the items of the last `d`/`D` and selects the first of them
+    pub fn undo_hide(&mut self) -> anyhow::Result<()> {
+        let Some(restored) = self.undo_stack.pop() else {
+            self.set_status("Nothing to undo".to_string());
+            return Ok(());
+        };
+        let item_ids: Vec<&str> = restored
+            .iter()
+            .map(|(_, item)| item.item_id.as_str())
+            .collect();
+        self.hidden_items.unhide(&item_ids)?;
+        let first_id = restored[0].1.item_id.clone();
+        let count = restored.len();
+        // ascending positions, each insert lands where the item was before
+        for (position, item) in restored {
+            let position = position.min(self.items.items.len());
+            self.items.items.insert(position, item);
+        }
+        self.refilter();
+        let row = self
+            .items
+            .iter()
+            .position(|item| item.item_id == first_id)
+            .and_then(|index| self.rows().iter().position(|row| *row == Row::Item(index)));
+        if let Some(row) = row {
+            self.move_selection(row as isize - self.selected_index as isize);
+        }
+        self.set_status(if count == 1 {
+            "Unhid 1 item".to_string()
+        } else {
+            format!("Unhid {} items", count)
+        });
+        Ok(())
+    }
+
+    fn remove_items(&mut self, item_ids: &HashSet<String>) {
+        self.items
+            .items
+            .retain(|item| !item_ids.contains(&item.item_id));
+        self.marked.retain(|id| !item_ids.contains(id));
+        self.refilter();
+    }
+
+    /// Items waiting for their tags before being added to Pocket
+    pub fn pending_items(&self) -> &[RssFeedItem] {
+        &self.pending_pocket_items
+    }
+
+    pub fn prepare_add_to_pocket(&mut self) -> bool {
+        let targets = self.targets();
+        let target_count = targets.len();
+        self.pending_pocket_items = targets
+            .into_iter()
+            .filter(|item| !self.saved.contains(&item.item_id))
+            .collect();
+        if target_count > 0 && self.pending_pocket_items.is_empty() {
+            self.set_status("Already in Pocket".to_string());
+        }
+        !self.pending_pocket_items.is_empty()
+    }
+
+    /// Hides every item of the selected feed at once
+    pub fn hide_current_feed(&mut self) -> anyhow::Result<()> {
+        let Some(source) = self.current_source() else {
+            return Ok(());
+        };
+        let feed_items: Vec<RssFeedItem> = self
+            .items
+            .iter()
+            .filter(|item| item.source == source)
+            .cloned()
+            .collect();
+        let mut transaction = Transaction::new();
+        for item in &feed_items {
+            self.hidden_items.stage_hide(item, &mut transaction);
+        }
+        transaction.commit()?;
+        let feed_items: HashSet<String> = feed_items.into_iter().map(|item| item.item_id).collect();
+        self.push_undo(&feed_items);
+        self.remove_items(&feed_items);
+        self.set_status(format!("Hid {} items from {}", feed_items.len(), source));
+        Ok(())
+    }
+
+    pub fn hide_current_items(&mut self) -> anyhow::Result<()> {
+        let mut hidden = HashSet::new();
+        for item in self.targets() {
+            self.hidden_items.hide_item(&item)?;
+            hidden.insert(item.item_id);
+        }
+        self.push_undo(&hidden);
+        self.remove_items(&hidden);
+        Ok(())
+    }
+
+    pub fn toggle_hidden_browser(&mut self) {
+        self.hidden_browser = match self.hidden_browser {
+            Some(_) => None,
+            None => Some(0),
+        };
+    }
+
+    pub fn move_hidden_selection(&mut self, delta: isize) {
+        let count = self.hidden_items.entries().len();
+        if let Some(selected) = &mut self.hidden_browser {
+            *selected = selected
+                .saturating_add_signed(delta)
+                .min(count.saturating_sub(1));
+        }
+    }
+
+    /// Unhides the item selected in the hidden items browser. It's back in the list right away
+    /// when `feed_items` still has it, otherwise once its feed is fetched again
+    pub fn restore_hidden(&mut self, feed_items: &[RssFeedItem]) -> anyhow::Result<()> {
+        let Some(selected) = self.hidden_browser else {
+            return Ok(());
+        };
+        let Some(entry) = self
+            .hidden_items
+            .entries()
+            .get(selected)
+            .map(|e| (*e).clone())
+        else {
+            return Ok(());
+        };
+        self.hidden_items.unhide(&[entry.item_id.as_str()])?;
+        match feed_items.iter().find(|item| item.item_id == entry.item_id) {
+            Some(item) => {
+                self.items.items.push(item.clone());
+                self.refilter();
+                self.set_status(format!("Restored {}", entry.title));
+            }
+            None => self.set_status(format!(
+                "Restored {}, it's back with the next feed update",
+                entry.title
+            )),
+        }
+        self.move_hidden_selection(0);
+        Ok(())
+    }
+
+    pub fn set_status(&mut self, message: String) {
+        self.status_message = Some((message, Instant::now()));
+    }
+
+    pub fn status(&self) -> Option<&str> {
+        self.status_message
+            .as_ref()
+            .filter(|(_, timestamp)| timestamp.elapsed() < STATUS_TIMEOUT)
+            .map(|(message, _)| message.as_str())
+    }
+
+    pub fn marked_count(&self) -> usize {
+        self.marked.len()
+    }
+
+    pub fn add_pending_to_pocket(
+        &mut self,
+        pocket_client: &GetPocketSync,
+        tags: &[String],
+    ) -> anyhow::Result<()> {
+        let pending = std::mem::take(&mut self.pending_pocket_items);
+        if pending.is_empty() {
+            anyhow::bail!("No item selected");
+        }
+        let urls: Vec<NewItem> = pending
+            .iter()
+            .map(|item| NewItem {
+                url: item.url(),
+                tags,
+                ..Default::default()
+            })
+            .collect();
+        let outcomes = pocket_client.add_all(&urls)?;
+        let mut added = HashSet::new();
+        let mut failed = Vec::new();
+        // items added to pocket are hidden all at once
+        let mut transaction = Transaction::new();
+        for (item, outcome) in pending.iter().zip(outcomes) {
+            match outcome {
+                Ok(()) => {
+                    self.hidden_items.stage_hide(item, &mut transaction);
+                    added.insert(item.item_id.clone());
+                }
+                Err(e) => failed.push(format!("{}: {}", item.title, e)),
+            }
+        }
+        transaction.commit()?;
+        if !added.is_empty() {
+            self.changes_made = true;
+        }
+        let added_count = added.len();
+        self.remove_items(&added);
+        if !failed.is_empty() {
+            anyhow::bail!(
+                "{} of {} not added, {}",
+                failed.len(),
+                pending.len(),
+                failed.join("; ")
+            );
+        }
+        self.set_status(format!(
+            "✓ Added {} to Pocket with {} tags",
+            if added_count == 1 {
+                "1 item".to_string()
+            } else {
+                format!("{} items", added_count)
+            },
+            tags.len()
+        ));
+        Ok(())
+    }
+}
+
+/// Feed errors listed below the items, more are summed up in the title
+const MAX_FAILING_FEEDS_SHOWN: usize = 5;
+
+pub fn render(
+    f: &mut Frame,
+    state: &RssTriageState,
+    failing_feeds: &[(String, FeedHealth)],
+    colors: &TableColors,
+    area: Rect,
+) {
+    f.render_widget(Clear, area);
+    let block = |title: &str| {
+        Block::default()
+            .borders(Borders::ALL)
+            .title(format!(" {} ", title))
+            .border_style(Style::new().fg(colors.footer_border_color))
+            .border_type(BorderType::Rounded)
+    };
+    if state.reader {
+        let reader = block("Reader")
+            .title_bottom(Line::from(" j/k scroll · a add · d hide · Esc back ").centered());
+        render_preview(f, state, reader, colors, area);
+        return;
+    }
+    let mut constraints = vec![Constraint::Length(SIDEBAR_WIDTH), Constraint::Min(30)];
+    if state.show_preview {
+        constraints.push(Constraint::Percentage(35));
+    }
+    let mut columns = Layout::horizontal(constraints).split(area).to_vec();
+    if !failing_feeds.is_empty() {
+        let shown = failing_feeds.len().min(MAX_FAILING_FEEDS_SHOWN);
+        let [items_area, errors_area] =
+            Layout::vertical([Constraint::Min(5), Constraint::Length(shown as u16 + 2)])
+                .areas(columns[1]);
+        columns[1] = items_area;
+        render_failing_feeds(f, failing_feeds, block, errors_area);
+    }
+
+    let total = state.items.items.len();
+    let sidebar: Vec<ListItem> = std::iter::once(("All sources", total, None))
+        .chain(
+            state
+                .sources()
+                .into_iter()
+                .map(|(source, count)| (source, count, Some(source))),
+        )
+        .map(|(label, count, source)| {
+            let style = if state.source_filter.as_deref() == source {
+                Style::default().fg(Color::Black).bg(Color::White)
+            } else {
+                Style::default().fg(OCEANIC_NEXT.base_0d)
+            };
+            ListItem::new(format!("{} ({})", label, count)).style(style)
+        })
+        .collect();
+    f.render_widget(
+        List::new(sidebar)
+            .block(block("Sources"))
+            .style(Style::new().bg(colors.buffer_bg)),
+        columns[0],
+    );
+
+    let all_rows = state.rows();
+    let feed_counts: BTreeMap<&str, usize> = state.sources().into_iter().collect();
+    let rows: Vec<ListItem> = all_rows
+        .iter()
+        .enumerate()
+        .skip(state.scroll_offset)
+        .take(state.visible_items)
+        .filter_map(|(i, row)| {
+            let style = if i == state.selected_index {
+                Style::default().fg(Color::Black).bg(Color::White)
+            } else {
+                Style::default()
+            };
+            let item = match row {
+                Row::Header(source) => {
+                    let fold = if state.collapsed.contains(source) {
+                        "▸"
+                    } else {
+                        "▾"
+                    };
+                    let count = feed_counts.get(source.as_str()).copied().unwrap_or(0);
+                    let header = Span::styled(
+                        format!("{} {} ({})", fold, source, count),
+                        Style::default()
+                            .fg(OCEANIC_NEXT.base_0d)
+                            .add_modifier(Modifier::BOLD),
+                    );
+                    return Some(ListItem::new(Line::from(header)).style(style));
+                }
+                Row::Item(index) => state.items.get(*index)?,
+            };
+            let marker = if state.marked.contains(&item.item_id) {
+                "● "
+            } else {
+                "  "
+            };
+            let saved = if state.saved.contains(&item.item_id) {
+                "✓ saved "
+            } else {
+                ""
+            };
+            let date = item
+                .pub_date
+                .as_deref()
+                .and_then(|date| date.get(0..10))
+                .unwrap_or("unknown");
+            let line = Line::from(vec![
+                Span::styled(marker, Style::default().fg(OCEANIC_NEXT.base_0b)),
+                Span::styled(
+                    format!("{:<10}", date),
+                    Style::default().fg(OCEANIC_NEXT.base_03),
+                ),
+                Span::raw(": "),
+                Span::styled(saved, Style::default().fg(OCEANIC_NEXT.base_0b)),
+                Span::styled(&item.title, Style::default().fg(OCEANIC_NEXT.base_05)),
+            ]);
+            Some(ListItem::new(line).style(style))
+        })
+        .collect();
+    let mut title = match &state.source_filter {
+        Some(source) => format!("RSS updates: {}", source),
+        None => "RSS updates".to_string(),
+    };
+    if state.sort_by_date {
+        title.push_str(" (newest first)");
+    }
+    f.render_widget(
+        List::new(rows)
+            .block(block(&title))
+            .style(Style::new().bg(colors.buffer_bg)),
+        columns[1],
+    );
+    let mut scroll_state = ScrollbarState::new(all_rows.len()).position(state.scroll_offset);
+    f.render_stateful_widget(
+        Scrollbar::default()
+            .orientation(ScrollbarOrientation::VerticalRight)
+            .begin_symbol(Some("↑"))
+            .end_symbol(Some("↓")),
+        columns[1],
+        &mut scroll_state,
+    );
+
+    if state.show_preview {
+        render_preview(f, state, block("Preview"), colors, columns[2]);
+    }
+    if let Some(selected) = state.hidden_browser {
+        render_hidden_browser(f, state, selected, colors, area);
+    }
+}
+
+fn render_hidden_browser(
+    f: &mut Frame,
+    state: &RssTriageState,
+    selected: usize,
+    colors: &TableColors,
+    area: Rect,
+) {
+    let area = PopupGeometry::default().area(area);
+    f.render_widget(Clear, area);
+    let entries = state.hidden_items.entries();
+    let rows: Vec<ListItem> = entries
+        .iter()
+        .map(|entry| {
+            let date = entry
+                .hidden_at
+                .and_then(|time| chrono::DateTime::from_timestamp(time, 0))
+                .map(|time| time.format("%Y-%m-%d").to_string())
+                .unwrap_or_else(|| "unknown".to_string());
+            let title = if entry.title.is_empty() {
+                &entry.item_id
+            } else {
+                &entry.title
+            };
+            ListItem::new(Line::from(vec![
+                Span::styled(
+                    format!("{:<10}  ", date),
+                    Style::default().fg(OCEANIC_NEXT.base_03),
+                ),
+                Span::styled(title.as_str(), Style::default().fg(OCEANIC_NEXT.base_05)),
+            ]))
+        })
+        .collect();
+    let list = List::new(rows)
+        .block(
+            Block::default()
+                .borders(Borders::ALL)
+                .title(format!(" Hidden items ({}) ", entries.len()))
+                .title_bottom(Line::from(" Enter restore · Esc close ").centered())
+                .border_style(Style::new().fg(colors.footer_border_color))
+                .border_type(BorderType::Rounded),
+        )
+        .style(Style::new().bg(Color::Black))
+        .highlight_style(Style::default().fg(Color::Black).bg(Color::White));
+    let mut list_state = ListState::default().with_selected(Some(selected));
+    f.render_stateful_widget(list, area, &mut list_state);
+}
+
+fn render_failing_feeds(
+    f: &mut Frame,
+    failing_feeds: &[(String, FeedHealth)],
+    block: impl Fn(&str) -> Block<'static>,
+    area: Rect,
+) {
+    let lines: Vec<Line> = failing_feeds
+        .iter()
+        .take(MAX_FAILING_FEEDS_SHOWN)
+        .map(|(url, health)| {
+            let last_success = health
+                .last_success
+                .and_then(|time| chrono::DateTime::from_timestamp(time, 0))
+                .map(|time| format!("ok on {}", time.format("%Y-%m-%d")))
+                .unwrap_or_else(|| "never ok".to_string());
+            Line::from(vec![
+                Span::styled(url.as_str(), Style::default().fg(OCEANIC_NEXT.base_08)),
+                Span::styled(
+                    format!(" ({}x, {}): ", health.failure_streak, last_success),
+                    Style::default().fg(OCEANIC_NEXT.base_03),
+                ),
+                Span::raw(health.last_error.as_deref().unwrap_or_default()),
+            ])
+        })
+        .collect();
+    let title = format!("Failing feeds: {} · R retries", failing_feeds.len());
+    f.render_widget(Paragraph::new(lines).block(block(&title)), area);
+}
+
+fn render_preview(
+    f: &mut Frame,
+    state: &RssTriageState,
+    block: Block,
+    colors: &TableColors,
+    area: Rect,
+) {
+    let block = block.style(Style::new().bg(colors.buffer_bg));
+    let inner = block.inner(area);
+    f.render_widget(block, area);
+    match state.current() {
+        Some(item) => {
+            let full_text = state
+                .full_texts
+                .lock()
+                .ok()
+                .and_then(|full_texts| full_texts.get(&item.item_id).cloned());
+            let view = preview_markdown(item, full_text);
+            let max_scroll = view
+                .height(inner.width)
+                .saturating_sub(inner.height as usize);
+            state
+                .preview_scroll
+                .set(state.preview_scroll.get().min(max_scroll));
+            f.render_widget(
+                MarkdownWidget::new(&view).scroll(state.preview_scroll.get()),
+                inner,
+            )
+        }
+        None => f.render_widget(Paragraph::new("Nothing left to triage"), inner),
+    }
+}
+
+fn preview_markdown(item: &RssFeedItem, full_text: Option<FullText>) -> MarkdownView {
+    let description = || {
+        item.description
+            .as_deref()
+            .map(|html| html2md::rewrite_html(html, false))
+            .unwrap_or_else(|| "*No description available*".to_string())
+    };
+    let description = match full_text {
+        None => description(),
+        Some(FullText::Loading) => format!("*Fetching the full article…*\n\n{}", description()),
+        Some(FullText::Failed(e)) => format!(
+            "*Couldn't fetch the full article: {}*\n\n{}",
+            mdview::escape(&e),
+            description()
+        ),
+        Some(FullText::Loaded(markdown)) => markdown,
+    };
+    let media = item
+        .enclosure
+        .as_ref()
+        .map(|url| format!("  \n**Media:** [{}]({})", mdview::escape(url), url))
+        .unwrap_or_default();
+    MarkdownView::parse(&format!(
+        "## {}\n\n**Source:** {}  \n**Link:** [{}]({}){}\n\n---\n\n{}",
+        mdview::escape(&item.title),
+        mdview::escape(&item.source),
+        mdview::escape(&item.link),
+        item.link,
+        media,
+        description
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn item(id: &str, source: &str) -> RssFeedItem {
+        RssFeedItem {
+            title: format!("title {}", id),
+            link: format!("https://example.com/{}", id),
+            source: source.to_string(),
+            description: None,
+            pub_date: None,
+            item_id: id.to_string(),
+            nsfw: false,
+            enclosure: None,
+        }
+    }
+
+    #[test]
+    fn marks_and_hides_within_source_filter() {
+        let mut state = RssTriageState::with_hidden_items(
+            vec![item("1", "lwn"), item("2", "hn"), item("3", "lwn")],
+            10,
+            prss::hidden_items::HiddenItems::new(),
+        );
+        state.cycle_source(1);
+        assert_eq!(state.source_filter.as_deref(), Some("hn"));
+        state.cycle_source(1);
+        assert_eq!(state.items.len(), 2);
+
+        state.toggle_mark();
+        state.toggle_mark();
+        assert_eq!(state.marked_count(), 2);
+        state.hide_current_items().unwrap();
+        assert_eq!(state.items.len(), 0);
+        assert_eq!(state.marked_count(), 0);
+
+        state.cycle_source(-1);
+        assert_eq!(state.current().map(|i| i.item_id.as_str()), Some("2"));
+
+        // both lwn items come back where they were, the first one selected
+        state.cycle_source(1);
+        assert!(state.source_filter.is_none());
+        state.undo_hide().unwrap();
+        assert_eq!(state.items.len(), 3);
+        let ids: Vec<&str> = state
+            .items
+            .items
+            .iter()
+            .map(|i| i.item_id.as_str())
+            .collect();
+        assert_eq!(ids, ["1", "2", "3"]);
+        assert_eq!(state.current().map(|i| i.item_id.as_str()), Some("1"));
+    }
+
+    #[test]
+    fn groups_collapse_and_jump_between_feeds() {
+        let mut state = RssTriageState::with_hidden_items(
+            vec![item("1", "lwn"), item("2", "hn"), item("3", "lwn")],
+            10,
+            prss::hidden_items::HiddenItems::new(),
+        );
+        // hn header, 2, lwn header, 1, 3
+        assert_eq!(state.current().map(|i| i.item_id.as_str()), Some("2"));
+        state.jump_to_feed(true);
+        assert!(state.on_header());
+        state.move_selection(1);
+        assert_eq!(state.current().map(|i| i.item_id.as_str()), Some("1"));
+
+        state.toggle_collapsed();
+        assert_eq!(state.rows().len(), 3);
+        assert!(state.on_header());
+        state.jump_to_feed(false);
+        assert_eq!(state.selected_index, 0);
+
+        state.hide_current_feed().unwrap();
+        assert_eq!(state.items.len(), 2);
+        assert_eq!(state.rows(), vec![Row::Header("lwn".to_string())]);
+        state.toggle_collapsed();
+        state.toggle_sort();
+        assert_eq!(state.rows(), vec![Row::Item(0), Row::Item(1)]);
+    }
+
+    #[test]
+    fn saved_items_are_not_added_again() {
+        let mut state = RssTriageState::with_hidden_items(
+            vec![item("1", "lwn"), item("2", "lwn")],
+            10,
+            prss::hidden_items::HiddenItems::new(),
+        );
+        let saved_urls = HashSet::from(["example.com/1".to_string()]);
+        state.mark_saved(&saved_urls, false);
+        assert!(!state.prepare_add_to_pocket());
+        assert_eq!(state.status(), Some("Already in Pocket"));
+
+        state.mark_saved(&saved_urls, true);
+        assert_eq!(state.items.len(), 1);
+        assert!(state.prepare_add_to_pocket());
+    }
+}
@@ -0,0 +1,169 @@
+use std::fmt::Write as _;
+use std::fs;
+use std::path::Path;
+
+use crate::config::{Config, CONFIG_FILE};
+use crate::prss;
+use crate::storage::{self, Pocket};
+
+pub const STARTUP_MARKER_FILE: &str = "startup.marker";
+
+/// Number of consecutive unclean shutdowns after which the app boots into safe mode
+const UNCLEAN_STARTS_BEFORE_SAFE_MODE: u32 = 2;
+
+/**
+The marker file is written on every startup and removed on a clean exit.
+If the marker is still around on the next startup, the previous run crashed (or was killed).
+The file keeps the number of consecutive unclean startups.
+*/
+pub fn register_startup(marker: &Path) -> anyhow::Result<u32> {
+    let unclean_starts = match fs::read_to_string(marker) {
+        Ok(content) => content.trim().parse::<u32>().unwrap_or(0) + 1,
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => 0,
+        Err(e) => return Err(e.into()),
+    };
+    fs::write(marker, unclean_starts.to_string())?;
+    Ok(unclean_starts)
+}
+
+pub fn register_clean_exit(marker: &Path) -> anyhow::Result<()> {
+    match fs::remove_file(marker) {
+        Err(e) if e.kind() != std::io::ErrorKind::NotFound => Err(e.into()),
+        _ => Ok(()),
+    }
+}
+
+/// Takes back `register_startup` for runs that don't start the app, like `--import`. Unclean
+/// starts before this one still count
+pub fn unregister_startup(marker: &Path, unclean_starts: u32) -> anyhow::Result<()> {
+    match unclean_starts.checked_sub(1) {
+        Some(before) => Ok(fs::write(marker, before.to_string())?),
+        None => register_clean_exit(marker),
+    }
+}
+
+pub fn should_start_in_safe_mode(unclean_starts: u32) -> bool {
+    unclean_starts >= UNCLEAN_STARTS_BEFORE_SAFE_MODE
+}
+
+/// Checks local state files and reports anything that can cause a crash loop
+pub fn doctor_report(unclean_starts: u32, delta_file: &Path) -> String {
+    let mut report = String::new();
+    let _ = writeln!(report, " SAFE MODE");
+    let _ = writeln!(report);
+    let _ = writeln!(
+        report,
+        " The app didn't shut down properly {} times in a row.",
+        unclean_starts
+    );
+    let _ = writeln!(report, " RSS loading and background jobs are disabled.");
+    let _ = writeln!(report);
+
+    let _ = writeln!(report, " Doctor report:");
+    let _ = writeln!(
+        report,
+        "   user.key            {}",
+        if Path::new("user.key").exists() {
+            "ok"
+        } else {
+            "missing"
+        }
+    );
+    let _ = writeln!(report, "   snapshot.db         {}", check_snapshot());
+    let _ = writeln!(
+        report,
+        "   {:<19} {}",
+        delta_file.display(),
+        check_delta(delta_file)
+    );
+    let _ = writeln!(report, "   rss/subscriptions   {}", check_subscriptions());
+    let _ = writeln!(report, "   config.json         {}", check_config());
+    let _ = writeln!(report);
+    let _ = writeln!(
+        report,
+        " Fix or remove broken files and restart. Use ZZ to quit cleanly."
+    );
+    report
+}
+
+fn check_snapshot() -> String {
+    if !storage::snapshot_exists() {
+        return "missing".to_string();
+    }
+    match fs::read_to_string("snapshot.db")
+        .map_err(anyhow::Error::from)
+        .and_then(|data| serde_json::from_str::<Pocket>(&data).map_err(anyhow::Error::from))
+    {
+        Ok(pocket) => format!("ok ({} items)", pocket.list.len()),
+        Err(e) => format!("corrupt: {}", e),
+    }
+}
+
+fn check_delta(delta_file: &Path) -> String {
+    match fs::read_to_string(delta_file) {
+        Ok(content) => {
+            let (total, broken) = content.lines().filter(|l| !l.trim().is_empty()).fold(
+                (0, 0),
+                |(total, broken), line| {
+                    let is_broken = serde_json::from_str::<serde_json::Value>(line).is_err();
+                    (total + 1, broken + usize::from(is_broken))
+                },
+            );
+            if broken == 0 {
+                format!("ok ({} records)", total)
+            } else {
+                format!("{} of {} records are corrupt", broken, total)
+            }
+        }
+        Err(e) => format!("unreadable: {}", e),
+    }
+}
+
+fn check_subscriptions() -> String {
+    match fs::read_to_string(prss::SUBSCRIPTIONS_FILE) {
+        Ok(content) => format!(
+            "ok ({} feeds)",
+            content
+                .lines()
+                .filter(|l| !l.trim().is_empty() && !l.trim().starts_with('#'))
+                .count()
+        ),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => "not configured".to_string(),
+        Err(e) => format!("unreadable: {}", e),
+    }
+}
+
+fn check_config() -> String {
+    if !Path::new(CONFIG_FILE).exists() {
+        return "not configured (ignored in safe mode)".to_string();
+    }
+    match Config::load(Path::new(CONFIG_FILE)) {
+        Ok(_) => "ok (ignored in safe mode)".to_string(),
+        Err(e) => format!("corrupt: {:#}", e),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn counts_consecutive_unclean_startups() {
+        let dir = tempfile::tempdir().unwrap();
+        let marker = dir.path().join(STARTUP_MARKER_FILE);
+
+        assert_eq!(register_startup(&marker).unwrap(), 0);
+        assert_eq!(register_startup(&marker).unwrap(), 1);
+        assert_eq!(register_startup(&marker).unwrap(), 2);
+        assert!(should_start_in_safe_mode(2));
+
+        register_clean_exit(&marker).unwrap();
+        assert_eq!(register_startup(&marker).unwrap(), 0);
+        assert!(!should_start_in_safe_mode(0));
+
+        // an import started after a crash leaves the count as it was
+        let unclean_starts = register_startup(&marker).unwrap();
+        unregister_startup(&marker, unclean_starts).unwrap();
+        assert_eq!(register_startup(&marker).unwrap(), 1);
+    }
+}
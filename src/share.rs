@@ -0,0 +1,92 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
+
+use crate::storage::PocketItem;
+use crate::TableRow;
+
+/// How `y` formats an item for pasting elsewhere. Placeholders: `{title}`, `{url}`, `{domain}`,
+/// `{tags}`, `{authors}`, `{excerpt}`; `{title_html}` is the title escaped for html
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ShareTemplate {
+    pub name: String,
+    pub template: String,
+}
+
+impl ShareTemplate {
+    fn new(name: &str, template: &str) -> Self {
+        Self {
+            name: name.to_string(),
+            template: template.to_string(),
+        }
+    }
+
+    pub fn defaults() -> Vec<Self> {
+        vec![
+            Self::new("Markdown", "[{title}]({url})"),
+            Self::new("Plain", "\"{title}\" — {url}"),
+            Self::new("HTML", "<a href=\"{url}\">{title_html}</a>"),
+            Self::new("Org", "[[{url}][{title}]]"),
+        ]
+    }
+
+    pub fn render(&self, item: &PocketItem) -> String {
+        let host = reqwest::Url::parse(item.url())
+            .ok()
+            .and_then(|url| {
+                url.host_str()
+                    .map(|host| host.trim_start_matches("www.").to_string())
+            })
+            .unwrap_or_default();
+        let title_html = item
+            .title()
+            .replace('&', "&amp;")
+            .replace('<', "&lt;")
+            .replace('>', "&gt;")
+            .replace('"', "&quot;");
+        [
+            ("{title_html}", title_html),
+            ("{title}", item.title().to_string()),
+            ("{url}", item.url().to_string()),
+            ("{domain}", host),
+            ("{tags}", item.tags().join(", ")),
+            ("{authors}", item.authors.iter().flatten().join(", ")),
+            ("{excerpt}", item.excerpt.clone()),
+        ]
+        .iter()
+        .fold(self.template.clone(), |text, (placeholder, value)| {
+            text.replace(placeholder, value)
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tutorial::sample_item;
+
+    #[test]
+    fn fills_placeholders() {
+        let item = sample_item(
+            1,
+            0,
+            "Fast & safe",
+            "https://www.rust-lang.org/learn",
+            &["rust", "lang"],
+            None,
+        );
+        let templates = ShareTemplate::defaults();
+        assert_eq!(
+            templates[0].render(&item),
+            "[Fast & safe](https://www.rust-lang.org/learn)"
+        );
+        assert_eq!(
+            templates[2].render(&item),
+            "<a href=\"https://www.rust-lang.org/learn\">Fast &amp; safe</a>"
+        );
+        let custom = ShareTemplate::new("Chat", "{title} ({domain}, {tags}) via @pocket");
+        assert_eq!(
+            custom.render(&item),
+            "Fast & safe (rust-lang.org, lang, rust) via @pocket"
+        );
+    }
+}
@@ -0,0 +1,80 @@
+use std::fs;
+use std::io::ErrorKind;
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+
+/// Json file with local state kept between runs. The default has no file: the state lives in
+/// memory only (tutorial, demo, tests) and nothing is read or written
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct StateFile(Option<&'static str>);
+
+impl StateFile {
+    pub const fn at(path: &'static str) -> Self {
+        Self(Some(path))
+    }
+
+    /// Saved state, the default when there is none yet. A broken file (state sync brings them
+    /// from other machines too) is logged and ignored, it must not stop the app from starting.
+    /// The next save replaces it
+    pub fn load<T: DeserializeOwned + Default>(self) -> T {
+        let Some(path) = self.0 else {
+            return T::default();
+        };
+        match fs::read_to_string(path) {
+            Ok(content) => serde_json::from_str(&content).unwrap_or_else(|e| {
+                log::error!("Ignoring broken {}: {}", path, e);
+                T::default()
+            }),
+            Err(e) if e.kind() == ErrorKind::NotFound => T::default(),
+            Err(e) => {
+                log::error!("Failed to read {}: {}", path, e);
+                T::default()
+            }
+        }
+    }
+
+    pub fn save<T: Serialize>(self, state: &T) -> anyhow::Result<()> {
+        if let Some(path) = self.0 {
+            fs::write(path, serde_json::to_string_pretty(state)?)?;
+        }
+        Ok(())
+    }
+
+    /// Without indentation, for state that gets big
+    pub fn save_compact<T: Serialize>(self, state: &T) -> anyhow::Result<()> {
+        if let Some(path) = self.0 {
+            fs::write(path, serde_json::to_string(state)?)?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashMap;
+
+    #[test]
+    fn broken_state_starts_empty() {
+        let dir = tempfile::tempdir().unwrap();
+        let path: &'static str = dir
+            .path()
+            .join("state.json")
+            .to_str()
+            .unwrap()
+            .to_string()
+            .leak();
+        let file = StateFile::at(path);
+        assert_eq!(file.load::<HashMap<String, u32>>(), HashMap::new());
+
+        file.save(&HashMap::from([("a".to_string(), 1)])).unwrap();
+        assert_eq!(file.load::<HashMap<String, u32>>()["a"], 1);
+
+        fs::write(path, "{\"a\": ").unwrap();
+        assert_eq!(file.load::<HashMap<String, u32>>(), HashMap::new());
+
+        StateFile::default().save(&1).unwrap();
+        assert_eq!(StateFile::default().load::<u32>(), 0);
+    }
+}
@@ -0,0 +1,280 @@
+use std::collections::HashMap;
+use std::fs::{self, File};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::{Duration, UNIX_EPOCH};
+
+use anyhow::{bail, Context};
+use serde::{Deserialize, Serialize};
+
+use crate::{highlights, network, prss, queue, readingstats, titles, watch};
+
+/// Modification times of the synced files as of their last push, next to the files themselves
+const MANIFEST: &str = "manifest.json";
+
+/// Local state that Pocket doesn't keep
+const SYNCED_FILES: [&str; 7] = [
+    highlights::HIGHLIGHTS_FILE,
+    queue::QUEUE_FILE,
+    prss::hidden_items::HIDDEN_ITEMS_FILE,
+    prss::SUBSCRIPTIONS_FILE,
+    readingstats::READ_EVENTS_FILE,
+    titles::TITLES_FILE,
+    watch::WATERMARK_FILE,
+];
+
+/// Where the local state of several machines meets
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "provider", rename_all = "snake_case")]
+pub enum StateSyncConfig {
+    /// Collection the files are put into, e.g. `https://dav.example.com/pkt-tui/`
+    Webdav {
+        url: String,
+        username: Option<String>,
+        password: Option<String>,
+    },
+    /// Clone of a repository with an upstream branch, pulled before and pushed after
+    Git { repo: PathBuf },
+}
+
+trait Provider {
+    fn read(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>>;
+    fn write(&self, name: &str, content: &[u8]) -> anyhow::Result<()>;
+    /// Called once all files are written
+    fn publish(&self) -> anyhow::Result<()>;
+}
+
+/// Nested files are stored flat, collections don't need to be created
+fn remote_name(name: &str) -> String {
+    name.replace('/', "_")
+}
+
+struct WebDav {
+    url: String,
+    username: Option<String>,
+    password: Option<String>,
+    client: reqwest::blocking::Client,
+}
+
+impl WebDav {
+    fn request(&self, method: reqwest::Method, name: &str) -> reqwest::blocking::RequestBuilder {
+        let url = format!("{}/{}", self.url.trim_end_matches('/'), remote_name(name));
+        let request = self.client.request(method, url);
+        match &self.username {
+            Some(username) => request.basic_auth(username, self.password.as_ref()),
+            None => request,
+        }
+    }
+}
+
+impl Provider for WebDav {
+    fn read(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let response = self
+            .request(reqwest::Method::GET, name)
+            .send()
+            .with_context(|| format!("Failed to download {}", name))?;
+        if response.status() == reqwest::StatusCode::NOT_FOUND {
+            return Ok(None);
+        }
+        Ok(Some(response.error_for_status()?.bytes()?.to_vec()))
+    }
+
+    fn write(&self, name: &str, content: &[u8]) -> anyhow::Result<()> {
+        self.request(reqwest::Method::PUT, name)
+            .body(content.to_vec())
+            .send()
+            .with_context(|| format!("Failed to upload {}", name))?
+            .error_for_status()?;
+        Ok(())
+    }
+
+    fn publish(&self) -> anyhow::Result<()> {
+        Ok(())
+    }
+}
+
+struct Git {
+    repo: PathBuf,
+}
+
+impl Git {
+    /// Runs git in the repository, `Ok(false)` when it exits with 1. It runs before the UI and
+    /// after it is gone, so it can't ask for credentials and gives up on a stalled remote
+    fn git(&self, args: &[&str]) -> anyhow::Result<bool> {
+        let output = Command::new("git")
+            .env("GIT_TERMINAL_PROMPT", "0")
+            .stdin(Stdio::null())
+            .arg("-C")
+            .arg(&self.repo)
+            .args(["-c", "http.lowSpeedLimit=1", "-c", "http.lowSpeedTime=30"])
+            .args(args)
+            .output()
+            .context("Failed to start git")?;
+        match output.status.code() {
+            Some(0) => Ok(true),
+            Some(1) if args[0] == "diff" => Ok(false),
+            _ => bail!(
+                "git {} failed: {}",
+                args[0],
+                String::from_utf8_lossy(&output.stderr).trim()
+            ),
+        }
+    }
+
+    fn open(repo: &Path) -> anyhow::Result<Self> {
+        let git = Git {
+            repo: repo.to_path_buf(),
+        };
+        git.git(&["pull", "--ff-only", "--quiet"])?;
+        Ok(git)
+    }
+}
+
+impl Provider for Git {
+    fn read(&self, name: &str) -> anyhow::Result<Option<Vec<u8>>> {
+        let path = self.repo.join(remote_name(name));
+        Ok(path.exists().then(|| fs::read(path)).transpose()?)
+    }
+
+    fn write(&self, name: &str, content: &[u8]) -> anyhow::Result<()> {
+        Ok(fs::write(self.repo.join(remote_name(name)), content)?)
+    }
+
+    fn publish(&self) -> anyhow::Result<()> {
+        self.git(&["add", "--all"])?;
+        if self.git(&["diff", "--cached", "--quiet"])? {
+            return Ok(());
+        }
+        self.git(&["commit", "--quiet", "--message", "Update pkt-tui state"])?;
+        self.git(&["push", "--quiet"])?;
+        Ok(())
+    }
+}
+
+#[derive(Debug, PartialEq)]
+enum Step {
+    Push,
+    Pull,
+}
+
+/// Last writer wins: the side changed more recently overwrites the other one. The times are file
+/// modification times of each machine, so a clock running ahead wins over later changes elsewhere
+fn plan<'a>(
+    files: &[&'a str],
+    local: &HashMap<String, i64>,
+    remote: &HashMap<String, i64>,
+) -> Vec<(&'a str, Step)> {
+    files
+        .iter()
+        .filter_map(|name| {
+            let step = match (local.get(*name), remote.get(*name)) {
+                (Some(local), Some(remote)) if local > remote => Step::Push,
+                (Some(local), Some(remote)) if local < remote => Step::Pull,
+                (Some(_), None) => Step::Push,
+                (None, Some(_)) => Step::Pull,
+                _ => return None,
+            };
+            Some((*name, step))
+        })
+        .collect()
+}
+
+fn modified_secs(path: &Path) -> Option<i64> {
+    let modified = fs::metadata(path).ok()?.modified().ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).ok()?.as_secs() as i64)
+}
+
+#[derive(Debug, Default)]
+pub struct SyncReport {
+    pub pushed: usize,
+    pub pulled: usize,
+}
+
+/// Exchanges the local state files in `dir` with the provider
+pub fn sync(config: &StateSyncConfig, dir: &Path) -> anyhow::Result<SyncReport> {
+    let provider: Box<dyn Provider> = match config {
+        StateSyncConfig::Webdav {
+            url,
+            username,
+            password,
+        } => Box::new(WebDav {
+            url: url.clone(),
+            username: username.clone(),
+            password: password.clone(),
+            client: network::blocking_client(Some(Duration::from_secs(30)))?,
+        }),
+        StateSyncConfig::Git { repo } => Box::new(Git::open(repo)?),
+    };
+    let mut manifest: HashMap<String, i64> = match provider.read(MANIFEST)? {
+        Some(content) => serde_json::from_slice(&content).context("Broken state manifest")?,
+        None => HashMap::new(),
+    };
+    let local: HashMap<String, i64> = SYNCED_FILES
+        .iter()
+        .filter_map(|name| Some((name.to_string(), modified_secs(&dir.join(name))?)))
+        .collect();
+
+    let mut report = SyncReport::default();
+    for (name, step) in plan(&SYNCED_FILES, &local, &manifest) {
+        let path = dir.join(name);
+        match step {
+            Step::Push => {
+                provider.write(name, &fs::read(&path)?)?;
+                manifest.insert(name.to_string(), local[name]);
+                report.pushed += 1;
+            }
+            Step::Pull => {
+                let Some(content) = provider.read(name)? else {
+                    continue;
+                };
+                if let Some(parent) = path.parent() {
+                    fs::create_dir_all(parent)?;
+                }
+                fs::write(&path, content)?;
+                // the same time on both sides, so the next sync leaves the file alone
+                let modified = UNIX_EPOCH + Duration::from_secs(manifest[name].max(0) as u64);
+                File::options()
+                    .write(true)
+                    .open(&path)?
+                    .set_modified(modified)?;
+                report.pulled += 1;
+            }
+        }
+    }
+    if report.pushed > 0 {
+        provider.write(MANIFEST, &serde_json::to_vec_pretty(&manifest)?)?;
+        provider.publish()?;
+    }
+    Ok(report)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn newer_side_wins() {
+        let times = |pairs: &[(&str, i64)]| {
+            pairs
+                .iter()
+                .map(|(name, time)| (name.to_string(), *time))
+                .collect::<HashMap<_, _>>()
+        };
+        let local = times(&[("queue", 20), ("hidden", 10), ("events", 5), ("new", 1)]);
+        let remote = times(&[("queue", 10), ("hidden", 20), ("events", 5), ("gone", 3)]);
+        assert_eq!(
+            plan(
+                &["queue", "hidden", "events", "new", "gone", "missing"],
+                &local,
+                &remote
+            ),
+            [
+                ("queue", Step::Push),
+                ("hidden", Step::Pull),
+                ("new", Step::Push),
+                ("gone", Step::Pull)
+            ]
+        );
+        assert_eq!(remote_name("rss/subscriptions"), "rss_subscriptions");
+    }
+}
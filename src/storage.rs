@@ -1,9 +1,15 @@
 use std::path::Path;
-use std::{collections::HashMap, fs};
+use std::{
+    collections::{HashMap, HashSet},
+    fs,
+};
 
+use anyhow::Context;
 use log::error;
 use serde::{Deserialize, Deserializer, Serialize};
 use serde_json::{json, Map, Value};
+
+use crate::transaction::Transaction;
 use std::fs::{File, OpenOptions};
 use std::io::{prelude::*, BufReader};
 
@@ -30,10 +36,16 @@ impl Pocket {
             .map(|opt| serde_json::from_value(opt.clone()).unwrap())
     }
 
+    /// Entries that aren't valid items are skipped, `verify` reports them
     pub fn pocket_items(self) -> HashMap<String, PocketItem> {
         let mut items: HashMap<String, PocketItem> = HashMap::new();
         for (key, value) in self.list {
-            items.insert(key, serde_json::from_value(value).unwrap());
+            match serde_json::from_value(value) {
+                Ok(item) => {
+                    items.insert(key, item);
+                }
+                Err(e) => error!("Skipping snapshot item {}: {}", key, e),
+            }
         }
         items
     }
@@ -66,7 +78,9 @@ pub struct PocketItem {
     pub given_title: Option<String>,
     #[serde(rename = "resolved_url")]
     pub resolved_url: Option<String>,
-    // pub excerpt: String,
+    /// First sentences of the article, shown in the excerpt pane
+    #[serde(default)]
+    pub excerpt: String,
     #[serde(rename = "is_article")]
     pub is_article: Option<String>,
     #[serde(default)]
@@ -82,8 +96,9 @@ pub struct PocketItem {
     pub word_count: String,
     #[serde(default)]
     pub lang: String,
-    // #[serde(rename = "top_image_url")]
-    // pub top_image_url: String,
+    /// Header image of the article, shown as a thumbnail next to the excerpt
+    #[serde(default)]
+    pub top_image_url: Option<String>,
     #[serde(default)]
     pub tags: Map<String, Value>,
 
@@ -96,18 +111,63 @@ pub struct PocketItem {
     // pub domain_metadata: DomainMetadata,
     #[serde(rename = "listen_duration_estimate")]
     pub listen_duration_estimate: i64,
+    /// Quotes of user highlights, only present for items that have them
+    #[serde(default)]
+    #[serde(deserialize_with = "PocketItem::deserialize_annotations")]
+    pub annotations: Vec<String>,
+    /// Title given in the app, see `titles::TitleOverrides`. Never written back
+    #[serde(skip)]
+    pub title_override: Option<String>,
+    /// Everything else the API sent (images, domain_metadata, ...), kept so that writing
+    /// an item back doesn't lose it
+    #[serde(flatten)]
+    pub extra: Map<String, Value>,
 }
 
 impl PocketItem {
+    /* json shape:
+        "annotations":[{"annotation_id":"...","item_id":"4026299054","quote":"highlighted text","patch":"...","version":"2","created_at":"2024-01-01 10:00:00"}]
+    */
+    fn deserialize_annotations<'de, D>(deserializer: D) -> Result<Vec<String>, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let annotations: Option<Value> = Option::deserialize(deserializer)?;
+        let quotes = match annotations {
+            Some(Value::Array(values)) => values
+                .iter()
+                .filter_map(|v| match v {
+                    Value::String(quote) => Some(quote.clone()),
+                    other => other
+                        .get("quote")
+                        .and_then(|q| q.as_str())
+                        .map(String::from),
+                })
+                .collect(),
+            _ => Vec::new(),
+        };
+        Ok(quotes)
+    }
+
     /* json shape:
         "authors":{"189194339":{"author_id":"189194339","item_id":"4026299054","name":"BrnoJUG","url":"https://www.youtube.com/channel/UCTgGnw_UUCd1hvqJbiVdnvA"}}
+        or the already converted list of names of a serialized item
     */
     fn deserialize_authors<'de, D>(deserializer: D) -> Result<Option<Vec<String>>, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let authors_map: Option<HashMap<String, serde_json::Value>> =
-            Option::deserialize(deserializer)?;
+        #[derive(Deserialize)]
+        #[serde(untagged)]
+        enum Authors {
+            Api(HashMap<String, serde_json::Value>),
+            Names(Vec<String>),
+        }
+        let authors_map = match Option::<Authors>::deserialize(deserializer)? {
+            Some(Authors::Names(names)) => return Ok(Some(names)),
+            Some(Authors::Api(map)) => Some(map),
+            None => None,
+        };
 
         // If there are no authors, return None
         Ok(authors_map.map(|map| {
@@ -146,31 +206,149 @@ pub fn snapshot_exists() -> bool {
     Path::new(SNAPSHOT_FILE).exists()
 }
 
-pub fn save_to_snapshot(pocket: &Pocket) -> anyhow::Result<()> {
-    let json = serde_json::to_string_pretty(&pocket)?;
-    fs::write(SNAPSHOT_FILE, json)?;
+fn newest_item(pocket: &Pocket) -> Option<&Value> {
+    pocket.list.values().max_by_key(|value| {
+        value["time_added"]
+            .as_str()
+            .and_then(|ts| ts.parse::<i64>().ok())
+    })
+}
+
+/// Writes the snapshot and starts the delta over with the entry of the newest item, so a crash
+/// can't leave a snapshot without the delta that later syncs start from
+pub fn save_snapshot_with_delta(pocket: &Pocket, delta_file: &Path) -> anyhow::Result<()> {
+    let newest = newest_item(pocket).context("Pocket returned no items")?;
+    let mut transaction = Transaction::new();
+    transaction.replace(SNAPSHOT_FILE, serde_json::to_string_pretty(&pocket)?);
+    transaction.replace(delta_file, format!("{}\n", newest));
+    transaction.commit()?;
+    // the bootstrap that fetched the list is done
+    if let Err(e) = fs::remove_file(BOOTSTRAP_FILE) {
+        if e.kind() != std::io::ErrorKind::NotFound {
+            error!("Failed to remove {}: {}", BOOTSTRAP_FILE, e);
+        }
+    }
+    Ok(())
+}
+
+/// Batches fetched by an unfinished `retrieve_all`, a line per batch, so a restart resumes it
+pub const BOOTSTRAP_FILE: &str = "snapshot_bootstrap.jsonl";
+
+#[derive(Serialize, Deserialize)]
+struct BootstrapBatch {
+    /// Offset of the next batch
+    offset: u32,
+    list: Map<String, Value>,
+}
+
+/// Items of an interrupted bootstrap and the offset to continue from. A line cut by a crash
+/// is dropped, its batch is fetched again
+pub fn load_bootstrap_progress(path: &Path) -> (u32, Pocket) {
+    let mut pocket = Pocket::default();
+    let mut offset = 0;
+    let Ok(file) = File::open(path) else {
+        return (offset, pocket);
+    };
+    for line in BufReader::new(file).lines().map_while(Result::ok) {
+        let Ok(batch) = serde_json::from_str::<BootstrapBatch>(&line) else {
+            break;
+        };
+        offset = batch.offset;
+        pocket.list.extend(batch.list);
+    }
+    (offset, pocket)
+}
+
+pub fn append_bootstrap_batch(path: &Path, offset: u32, batch: &Pocket) -> anyhow::Result<()> {
+    let line = serde_json::to_string(&BootstrapBatch {
+        offset,
+        list: batch.list.clone(),
+    })?;
+    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+    file.write_all(format!("{}\n", line).as_bytes())?;
     Ok(())
 }
 
-pub fn load_snapshot_file() -> Pocket {
-    let data = fs::read_to_string(SNAPSHOT_FILE).expect("file should exist");
-    let json: Pocket = serde_json::from_str(&data).expect("incorrect format");
-    json
+/// Copies the snapshot and the delta to `.bak` files, overwriting the previous backup
+fn backup(delta_file: &Path) -> anyhow::Result<()> {
+    fs::copy(
+        SNAPSHOT_FILE,
+        Path::new(SNAPSHOT_FILE).with_extension("db.bak"),
+    )?;
+    if delta_file.exists() {
+        fs::copy(delta_file, delta_file.with_extension("db.bak"))?;
+    }
+    Ok(())
+}
+
+/// Replaces the local data with a fresh copy of the whole list, the old files are kept as backups
+pub fn rebuild_snapshot(pocket: &Pocket, delta_file: &Path) -> anyhow::Result<()> {
+    if snapshot_exists() {
+        backup(delta_file)?;
+    }
+    save_snapshot_with_delta(pocket, delta_file)
+}
+
+/// Snapshot with the list deserialized straight into items, without a `Value` per item
+#[derive(Deserialize)]
+struct SnapshotItems {
+    list: HashMap<String, PocketItem>,
+}
+
+fn read_snapshot_items(path: &Path) -> anyhow::Result<HashMap<String, PocketItem>> {
+    let open = || -> anyhow::Result<BufReader<File>> {
+        let file =
+            File::open(path).with_context(|| format!("Failed to open {}", path.display()))?;
+        Ok(BufReader::new(file))
+    };
+    match serde_json::from_reader::<_, SnapshotItems>(open()?) {
+        Ok(snapshot) => Ok(snapshot.list),
+        Err(e) => {
+            // a single broken entry fails the whole list, parse item by item to skip it
+            error!(
+                "Snapshot isn't a list of valid items ({}), skipping broken ones",
+                e
+            );
+            let snapshot: Pocket = serde_json::from_reader(open()?)
+                .with_context(|| format!("Incorrect format of {}", path.display()))?;
+            Ok(snapshot.pocket_items())
+        }
+    }
+}
+
+/// Streams the snapshot into items, large libraries never hold the whole file in memory
+pub fn load_snapshot_items() -> anyhow::Result<HashMap<String, PocketItem>> {
+    read_snapshot_items(Path::new(SNAPSHOT_FILE))
 }
 
 // pub fn delta_file() -> Path {
 //     format!("{}/{}", DATA_DIRECTORY, DELTA_PREFIX).into()
 // }
-pub fn append_delete_to_delta(
-    delta_file: &Path,
-    pocket_update: &PocketItemUpdate,
-) -> anyhow::Result<()> {
-    let mut file = OpenOptions::new()
-        .write(true)
-        .create(true)
-        .append(true)
-        .open(delta_file)?;
+/// Items imported without going through Pocket have ids starting with it
+pub const LOCAL_ID_PREFIX: &str = "local-";
+
+/// Item kept only on this machine, Pocket doesn't know it
+pub fn is_local_id(item_id: &str) -> bool {
+    item_id.starts_with(LOCAL_ID_PREFIX)
+}
 
+/// Local items are changed by appending their new state to the delta, the last line of an item
+/// wins when the delta is loaded
+pub fn append_local_item(delta_file: &Path, item: &mut PocketItem) -> anyhow::Result<()> {
+    item.time_updated = chrono::Utc::now().timestamp().to_string();
+    let mut list = Map::new();
+    list.insert(item.item_id.clone(), serde_json::to_value(&*item)?);
+    append_to_delta(
+        delta_file,
+        &Pocket {
+            list,
+            ..Pocket::default()
+        },
+    )
+}
+
+/// Delta line of a delete update, other updates aren't stored in the delta this way
+pub fn delete_delta_line(pocket_update: &PocketItemUpdate) -> anyhow::Result<String> {
     let json = match pocket_update {
         PocketItemUpdate::Delete { item_id, timestamp } => {
             json!({
@@ -181,17 +359,19 @@ pub fn append_delete_to_delta(
         }
         _ => return Err(anyhow::anyhow!("Only delete updates are supported")),
     };
-
-    writeln!(&mut file, "{}", json.to_string())?;
-    Ok(())
+    Ok(format!("{}\n", json))
 }
 
-pub fn append_to_delta(delta_file: &Path, pocket: &Pocket) -> anyhow::Result<()> {
-    let content: Vec<String> = pocket
+fn delta_lines(pocket: &Pocket) -> String {
+    pocket
         .list
         .values()
-        .map(|v| serde_json::to_string(v).expect(&format!("can't convert to json {:?}", v)))
-        .collect();
+        .map(|v| serde_json::to_string(v).expect(&format!("can't convert to json {:?}", v)) + "\n")
+        .collect()
+}
+
+pub fn append_to_delta(delta_file: &Path, pocket: &Pocket) -> anyhow::Result<()> {
+    let content = delta_lines(pocket);
 
     let mut file = OpenOptions::new()
         .write(true)
@@ -200,12 +380,151 @@ pub fn append_to_delta(delta_file: &Path, pocket: &Pocket) -> anyhow::Result<()>
         .open(delta_file)
         .unwrap();
 
-    for line in content {
-        writeln!(&mut file, "{}", line)?;
-    }
+    file.write_all(content.as_bytes())?;
     Ok(())
 }
 
+/// Reading stats look back a month at most, younger delta lines stay for them
+const STATS_RETENTION_DAYS: i64 = 62;
+
+/// Refresh compacts the delta on its own once it has more lines than that
+const AUTO_COMPACT_LINES: usize = 5_000;
+
+pub struct Compaction {
+    pub folded: usize,
+    pub kept: usize,
+    pub quarantined: usize,
+}
+
+/// Time of the change a delta line records: the delete or the last update
+fn delta_line_time(value: &Value) -> i64 {
+    let field = |name: &str| {
+        value[name]
+            .as_str()
+            .and_then(|ts| ts.parse::<i64>().ok())
+            .or_else(|| value[name].as_i64())
+            .unwrap_or(0)
+    };
+    if value["status"] == json!("2") {
+        field("timestamp")
+    } else {
+        field("time_updated").max(field("time_added"))
+    }
+}
+
+/**
+Applies the leading delta lines older than `keep_since` to the snapshot and returns the lines that
+stay. Only a prefix is folded, so replaying the snapshot and the rest gives the same items as
+before. Sync continues from the newest `time_added` in the delta, so when no saved item is left
+there the newest one of the snapshot is put back.
+*/
+fn fold_delta(snapshot: &mut Pocket, lines: Vec<String>, keep_since: i64) -> (usize, Vec<String>) {
+    let values: Vec<Option<Value>> = lines
+        .iter()
+        .map(|line| serde_json::from_str(line).ok())
+        .collect();
+    // unreadable lines are left for the verification to deal with
+    let folded = values
+        .iter()
+        .position(|value| {
+            value
+                .as_ref()
+                .is_none_or(|v| delta_line_time(v) >= keep_since)
+        })
+        .unwrap_or(values.len());
+    if folded == 0 {
+        return (0, lines);
+    }
+    for mut value in values.into_iter().take(folded).flatten() {
+        let Some(item_id) = value["item_id"].as_str().map(String::from) else {
+            continue;
+        };
+        if value["status"] == json!("2") {
+            snapshot.list.remove(&item_id);
+        } else {
+            if let Some(existing) = snapshot.list.get(&item_id) {
+                value["time_added"] = existing["time_added"].clone();
+            }
+            snapshot.list.insert(item_id, value);
+        }
+    }
+    let mut kept: Vec<String> = lines.into_iter().skip(folded).collect();
+    let kept_saved_item = kept.iter().any(|line| {
+        serde_json::from_str::<Value>(line).is_ok_and(|value| value["status"] != json!("2"))
+    });
+    if !kept_saved_item {
+        match newest_item(snapshot) {
+            Some(newest) => kept.insert(0, newest.to_string()),
+            // nothing to continue the sync from, keep the delta as it is
+            None => return (0, Vec::new()),
+        }
+    }
+    (folded, kept)
+}
+
+fn read_delta_lines(delta_file: &Path) -> anyhow::Result<Vec<String>> {
+    Ok(fs::read_to_string(delta_file)?
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(String::from)
+        .collect())
+}
+
+/// Lines that can be used and the corrupt ones
+fn split_corrupt(lines: Vec<String>) -> (Vec<String>, Vec<String>) {
+    lines
+        .into_iter()
+        .partition(|line| parse_delta_line(line).is_ok())
+}
+
+/// The delta got long or has corrupt lines to quarantine
+pub fn needs_compaction(delta_file: &Path) -> bool {
+    read_delta_lines(delta_file).is_ok_and(|lines| {
+        lines.len() > AUTO_COMPACT_LINES || lines.iter().any(|line| parse_delta_line(line).is_err())
+    })
+}
+
+/**
+Folds delta lines older than the reading stats window into a new snapshot and truncates the delta.
+Corrupt lines are moved to `<delta>.quarantine`, where they can be looked at. Callers make sure
+no sync appends to the delta meanwhile.
+The previous snapshot and delta are kept as `.bak` files, overwriting the backup of the last run.
+*/
+pub fn compact(delta_file: &Path) -> anyhow::Result<Compaction> {
+    let keep_since = chrono::Utc::now().timestamp() - STATS_RETENTION_DAYS * 24 * 60 * 60;
+    let (lines, corrupt) = split_corrupt(read_delta_lines(delta_file)?);
+    let mut snapshot: Pocket = serde_json::from_str(&fs::read_to_string(SNAPSHOT_FILE)?)?;
+    let (folded, kept) = match fold_delta(&mut snapshot, lines.clone(), keep_since) {
+        (0, _) => (0, lines),
+        folding => folding,
+    };
+    if folded == 0 && corrupt.is_empty() {
+        return Ok(Compaction {
+            folded,
+            kept: kept.len(),
+            quarantined: 0,
+        });
+    }
+
+    backup(delta_file)?;
+    let text = |lines: &[String]| lines.iter().map(|line| format!("{}\n", line)).collect();
+    let mut transaction = Transaction::new();
+    if folded > 0 {
+        transaction.replace(SNAPSHOT_FILE, serde_json::to_string_pretty(&snapshot)?);
+    }
+    transaction.replace(delta_file, text(&kept));
+    if !corrupt.is_empty() {
+        error!("Quarantining {} corrupt delta records", corrupt.len());
+        transaction.append(delta_file.with_extension("quarantine"), text(&corrupt));
+    }
+    transaction.commit()?;
+    Ok(Compaction {
+        folded,
+        kept: kept.len(),
+        quarantined: corrupt.len(),
+    })
+}
+
 pub fn load_delta_for_tests(delta_file: &Path) -> Map<String, Value> {
     match File::open(delta_file) {
         Ok(file) => {
@@ -232,35 +551,63 @@ pub fn load_delta_for_tests(delta_file: &Path) -> Map<String, Value> {
     }
 }
 
-pub fn load_delta_pocket_items(delta_file: &Path) -> Vec<PocketItemUpdate> {
-    match File::open(delta_file) {
-        Ok(file) => {
-            let buf = BufReader::new(file);
+/// Why a delta line can't be used
+enum DeltaProblem {
+    Unparseable(String),
+    InvalidRecord(String),
+}
 
-            buf.lines()
-                .map(|l| {
-                    let json_str = l.expect("couldn't parse line");
-                    let js_value: Value = serde_json::from_str(&json_str)
-                        .expect(&("couldn't parse json: ".to_owned() + &json_str));
-                    if js_value["status"] != json!("2") {
-                        let value: PocketItem = serde_json::from_value(js_value)
-                            .expect(&("couldn't parse json: ".to_owned() + &json_str));
-                        PocketItemUpdate::Add {
-                            item_id: value.item_id.clone(),
-                            data: value,
-                        }
-                    } else {
-                        // deleted items
-                        let item_id = js_value["item_id"].as_str().unwrap_or("-1");
-                        let ts_opt = js_value["timestamp"].as_u64();
-                        PocketItemUpdate::Delete {
-                            item_id: item_id.to_string(),
-                            timestamp: ts_opt,
-                        }
-                    }
-                })
-                .collect()
+impl std::fmt::Display for DeltaProblem {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DeltaProblem::Unparseable(e) => write!(f, "not json: {}", e),
+            DeltaProblem::InvalidRecord(e) => write!(f, "invalid record: {}", e),
         }
+    }
+}
+
+fn parse_delta_line(line: &str) -> Result<PocketItemUpdate, DeltaProblem> {
+    let js_value: Value =
+        serde_json::from_str(line).map_err(|e| DeltaProblem::Unparseable(e.to_string()))?;
+    if js_value["status"] != json!("2") {
+        let value: PocketItem = serde_json::from_value(js_value)
+            .map_err(|e| DeltaProblem::InvalidRecord(e.to_string()))?;
+        Ok(PocketItemUpdate::Add {
+            item_id: value.item_id.clone(),
+            data: value,
+        })
+    } else {
+        // deleted items
+        let item_id = js_value["item_id"]
+            .as_str()
+            .ok_or_else(|| DeltaProblem::InvalidRecord("missing field `item_id`".to_string()))?;
+        Ok(PocketItemUpdate::Delete {
+            item_id: item_id.to_string(),
+            timestamp: js_value["timestamp"].as_u64(),
+        })
+    }
+}
+
+fn read_delta(delta_file: &Path) -> std::io::Result<String> {
+    // a single broken byte shouldn't make the whole file unreadable
+    fs::read(delta_file).map(|bytes| String::from_utf8_lossy(&bytes).into_owned())
+}
+
+/// Corrupt lines are skipped instead of failing the whole load. The file isn't changed here, a
+/// sync may be appending to it; `compact` moves them out
+pub fn load_delta_pocket_items(delta_file: &Path) -> Vec<PocketItemUpdate> {
+    match read_delta(delta_file) {
+        Ok(content) => content
+            .lines()
+            .filter(|line| !line.trim().is_empty())
+            .filter_map(|line| {
+                parse_delta_line(line)
+                    .inspect_err(|problem| {
+                        error!("Skipping corrupt delta record ({}): {}", problem, line)
+                    })
+                    .ok()
+            })
+            .collect(),
         Err(e) => {
             //todo: propagte error back to the caller
             error!("Delta file wasn't found! {:?}", e);
@@ -269,6 +616,130 @@ pub fn load_delta_pocket_items(delta_file: &Path) -> Vec<PocketItemUpdate> {
     }
 }
 
+/// Favorited items with the delta applied, archived ones included, the most recently favorited
+/// first
+fn favorites(
+    mut items: HashMap<String, PocketItem>,
+    updates: Vec<PocketItemUpdate>,
+) -> Vec<PocketItem> {
+    for update in updates {
+        match update {
+            PocketItemUpdate::Delete { item_id, .. } => {
+                items.remove(&item_id);
+            }
+            PocketItemUpdate::Add { item_id, data } => {
+                items.insert(item_id, data);
+            }
+        }
+    }
+    let mut favorites: Vec<PocketItem> = items
+        .into_values()
+        .filter(|item| item.favorite == "1" || item.tags.contains_key("favorite"))
+        .collect();
+    favorites
+        .sort_by_key(|item| std::cmp::Reverse(item.time_favorited.parse::<u64>().unwrap_or(0)));
+    favorites
+}
+
+/// The main list leaves favorites out, they are only read from local storage on request
+pub fn load_favorites(delta_file: &Path) -> anyhow::Result<Vec<PocketItem>> {
+    Ok(favorites(
+        load_snapshot_items()?,
+        load_delta_pocket_items(delta_file),
+    ))
+}
+
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub snapshot_items: usize,
+    /// Ids of snapshot entries that aren't valid items
+    pub broken_snapshot_items: Vec<String>,
+    pub delta_records: usize,
+    /// Line number and what's wrong with the line
+    pub corrupt_records: Vec<(usize, String)>,
+    /// Deletes of items that are neither in the snapshot nor added earlier in the delta
+    pub orphaned_deletes: Vec<String>,
+}
+
+impl VerifyReport {
+    pub fn is_healthy(&self) -> bool {
+        self.broken_snapshot_items.is_empty() && self.corrupt_records.is_empty()
+    }
+
+    pub fn summary(&self) -> String {
+        let mut lines = vec![
+            format!(" snapshot: {} items", self.snapshot_items),
+            format!(" delta: {} records", self.delta_records),
+        ];
+        if !self.broken_snapshot_items.is_empty() {
+            lines.push(format!(
+                " invalid snapshot items: {}",
+                self.broken_snapshot_items.join(", ")
+            ));
+        }
+        for (line, problem) in &self.corrupt_records {
+            lines.push(format!(" delta line {}: {}", line, problem));
+        }
+        if !self.orphaned_deletes.is_empty() {
+            lines.push(format!(
+                " {} deletes of unknown items (harmless)",
+                self.orphaned_deletes.len()
+            ));
+        }
+        lines.push(String::new());
+        lines.push(if self.is_healthy() {
+            " No problems found".to_string()
+        } else {
+            " Corrupt delta records get quarantined on the next refresh,\n :rebuild downloads a fresh snapshot".to_string()
+        });
+        lines.join("\n")
+    }
+}
+
+fn verify_data(snapshot: &Pocket, delta: &str) -> VerifyReport {
+    let mut report = VerifyReport {
+        snapshot_items: snapshot.list.len(),
+        ..Default::default()
+    };
+    for (item_id, value) in &snapshot.list {
+        if serde_json::from_value::<PocketItem>(value.clone()).is_err() {
+            report.broken_snapshot_items.push(item_id.clone());
+        }
+    }
+
+    let mut known: HashSet<String> = snapshot.list.keys().cloned().collect();
+    for (number, line) in delta.lines().enumerate() {
+        if line.trim().is_empty() {
+            continue;
+        }
+        report.delta_records += 1;
+        match parse_delta_line(line) {
+            Ok(PocketItemUpdate::Add { item_id, .. }) => {
+                known.insert(item_id);
+            }
+            Ok(PocketItemUpdate::Delete { item_id, .. }) => {
+                if !known.contains(&item_id) {
+                    report.orphaned_deletes.push(item_id);
+                }
+            }
+            Err(problem) => report
+                .corrupt_records
+                .push((number + 1, problem.to_string())),
+        }
+    }
+    report
+}
+
+/// Checks the snapshot and the delta without changing them
+pub fn verify(delta_file: &Path) -> anyhow::Result<VerifyReport> {
+    let snapshot: Pocket = serde_json::from_str(
+        &fs::read_to_string(SNAPSHOT_FILE).context("Failed to read the snapshot")?,
+    )
+    .context("The snapshot is corrupt, :rebuild downloads a fresh one")?;
+    let delta = read_delta(delta_file).context("Failed to read the delta")?;
+    Ok(verify_data(&snapshot, &delta))
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -411,4 +882,200 @@ mod tests {
         assert_eq!(map.len(), 2);
         Ok(())
     }
+
+    #[test]
+    fn local_item_changes_go_to_the_delta() {
+        use crate::TableRow;
+        let dir = tempfile::tempdir().unwrap();
+        let delta = dir.path().join("delta.db");
+        let mut item = crate::tutorial::sample_item(1, 0, "Local", "https://a.org", &[], None);
+        item.item_id = format!("{}1", LOCAL_ID_PREFIX);
+        append_local_item(&delta, &mut item).unwrap();
+        item.add_tag("top");
+        append_local_item(&delta, &mut item).unwrap();
+
+        let updates = load_delta_pocket_items(&delta);
+        assert_eq!(updates.len(), 2);
+        let PocketItemUpdate::Add { item_id, data } = &updates[1] else {
+            panic!("expected an item line");
+        };
+        assert!(is_local_id(item_id));
+        assert!(data.tags().any(|tag| tag == "top"));
+    }
+
+    #[test]
+    fn folds_old_delta_lines_into_snapshot() {
+        let item = |id: &str, added: &str, updated: &str| json!({"item_id": id, "status": "0", "time_added": added, "time_updated": updated});
+        let mut snapshot = Pocket::default();
+        snapshot
+            .list
+            .insert("1".to_string(), item("1", "100", "100"));
+        let lines = vec![
+            item("2", "200", "200").to_string(),
+            item("1", "999", "300").to_string(),
+            json!({"item_id": "2", "status": "2", "timestamp": 400}).to_string(),
+            item("3", "1000", "1000").to_string(),
+            json!({"item_id": "1", "status": "2", "timestamp": 500}).to_string(),
+        ];
+
+        let (folded, kept) = fold_delta(&mut snapshot, lines.clone(), 1000);
+        assert_eq!(folded, 3);
+        assert_eq!(kept, lines[3..].to_vec());
+        assert_eq!(snapshot.list.len(), 1);
+        // an update keeps the time the item was originally added
+        assert_eq!(snapshot.list["1"]["time_added"], json!("100"));
+        assert_eq!(snapshot.list["1"]["time_updated"], json!("300"));
+
+        // without saved items left in the delta the newest one of the snapshot goes back there
+        let mut snapshot = Pocket::default();
+        snapshot
+            .list
+            .insert("1".to_string(), item("1", "100", "100"));
+        let (folded, kept) = fold_delta(&mut snapshot, lines[..3].to_vec(), 1000);
+        assert_eq!(folded, 3);
+        assert_eq!(kept, vec![item("1", "100", "300").to_string()]);
+    }
+
+    #[test]
+    fn verify_finds_corrupt_and_orphaned_records() {
+        let mut snapshot = Pocket::default();
+        snapshot.list.insert(
+            "1".to_string(),
+            json!({"item_id": "1", "status": "0", "time_added": "1", "time_updated": "1",
+                "time_read": "0", "time_favorited": "0", "sort_id": 0, "listen_duration_estimate": 0}),
+        );
+        snapshot
+            .list
+            .insert("2".to_string(), json!({"item_id": "2"}));
+        let delta = [
+            r#"{"item_id": "3", "status": "0", "time_added": "5", "time_updated": "5", "time_read": "0", "time_favorited": "0", "sort_id": 0, "listen_duration_estimate": 0}"#,
+            r#"{"item_id": "3", "status": "2", "timestamp": 6}"#,
+            r#"{"item_id": "7", "status": "2", "timestamp": 7}"#,
+            r#"{"item_id": "4", "status": "#,
+            r#"{"item_id": "5", "status": "0"}"#,
+        ]
+        .join("\n");
+
+        let report = verify_data(&snapshot, &delta);
+        assert_eq!(report.snapshot_items, 2);
+        assert_eq!(report.broken_snapshot_items, vec!["2".to_string()]);
+        assert_eq!(report.delta_records, 5);
+        let corrupt_lines: Vec<usize> = report.corrupt_records.iter().map(|(l, _)| *l).collect();
+        assert_eq!(corrupt_lines, vec![4, 5]);
+        assert_eq!(report.orphaned_deletes, vec!["7".to_string()]);
+        assert!(!report.is_healthy());
+    }
+
+    #[test]
+    fn corrupt_delta_lines_wait_for_compaction() {
+        let dir = tempfile::tempdir().unwrap();
+        let delta_file = dir.path().join("delta.db");
+        let delta = [
+            r#"{"item_id": "3", "status": "2", "timestamp": 6}"#,
+            r#"{"item_id": "4", "status": "#,
+            r#"{"item_id": "5", "status": "2", "timestamp": 7}"#,
+        ];
+        fs::write(&delta_file, delta.join("\n")).unwrap();
+
+        assert_eq!(load_delta_pocket_items(&delta_file).len(), 2);
+        assert_eq!(fs::read_to_string(&delta_file).unwrap(), delta.join("\n"));
+        assert!(needs_compaction(&delta_file));
+
+        let (kept, corrupt) = split_corrupt(read_delta_lines(&delta_file).unwrap());
+        assert_eq!(kept, [delta[0], delta[2]]);
+        assert_eq!(corrupt, [delta[1]]);
+    }
+
+    #[test]
+    fn streams_snapshot_items_and_skips_broken_ones() {
+        let item = |id: &str| {
+            json!({"item_id": id, "status": "0", "time_added": "1", "time_updated": "1",
+                "time_read": "0", "time_favorited": "0", "sort_id": 0, "listen_duration_estimate": 0})
+        };
+        let mut snapshot = Pocket::default();
+        snapshot.list.insert("1".to_string(), item("1"));
+        snapshot.list.insert("2".to_string(), item("2"));
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(serde_json::to_string(&snapshot).unwrap().as_bytes())
+            .unwrap();
+        assert_eq!(read_snapshot_items(file.path()).unwrap().len(), 2);
+
+        snapshot
+            .list
+            .insert("3".to_string(), json!({"item_id": "3"}));
+        let mut file = NamedTempFile::new().unwrap();
+        file.write_all(serde_json::to_string(&snapshot).unwrap().as_bytes())
+            .unwrap();
+        let items = read_snapshot_items(file.path()).unwrap();
+        assert_eq!(items.len(), 2);
+        assert!(!items.contains_key("3"));
+    }
+
+    #[test]
+    fn favorites_include_archived_items() {
+        let item = |id: &str, status: &str, favorite: &str, time_favorited: &str| {
+            serde_json::from_value::<PocketItem>(json!({"item_id": id, "status": status,
+                "favorite": favorite, "time_added": "1", "time_updated": "1", "time_read": "0",
+                "time_favorited": time_favorited, "sort_id": 0, "listen_duration_estimate": 0}))
+            .unwrap()
+        };
+        let snapshot = HashMap::from([
+            ("1".to_string(), item("1", "0", "1", "10")),
+            ("2".to_string(), item("2", "0", "0", "0")),
+            ("3".to_string(), item("3", "1", "1", "20")),
+        ]);
+        let updates = vec![
+            PocketItemUpdate::Add {
+                item_id: "2".to_string(),
+                data: item("2", "1", "1", "30"),
+            },
+            PocketItemUpdate::Delete {
+                item_id: "3".to_string(),
+                timestamp: None,
+            },
+        ];
+        let ids: Vec<String> = favorites(snapshot, updates)
+            .into_iter()
+            .map(|item| item.item_id)
+            .collect();
+        assert_eq!(ids, ["2", "1"]);
+    }
+
+    #[test]
+    fn resumes_bootstrap_after_the_last_complete_batch() {
+        let file = NamedTempFile::new().unwrap();
+        assert_eq!(load_bootstrap_progress(Path::new("missing.jsonl")).0, 0);
+        let batch = |ids: &[&str]| Pocket {
+            list: ids
+                .iter()
+                .map(|id| (id.to_string(), json!({"item_id": id})))
+                .collect(),
+            ..Pocket::default()
+        };
+        append_bootstrap_batch(file.path(), 2, &batch(&["1", "2"])).unwrap();
+        append_bootstrap_batch(file.path(), 3, &batch(&["3"])).unwrap();
+        // a crash in the middle of writing the third batch
+        let mut torn = OpenOptions::new().append(true).open(file.path()).unwrap();
+        torn.write_all(br#"{"offset": 4, "list": {"4""#).unwrap();
+
+        let (offset, pocket) = load_bootstrap_progress(file.path());
+        assert_eq!(offset, 3);
+        assert_eq!(pocket.list.len(), 3);
+    }
+
+    #[test]
+    fn keeps_unknown_fields_on_round_trip() {
+        let value = json!({"item_id": "1", "status": "0", "time_added": "1", "time_updated": "1",
+            "time_read": "0", "time_favorited": "0", "sort_id": 0, "listen_duration_estimate": 0,
+            "excerpt": "Networking changes", "domain_metadata": {"name": "Phoronix"},
+            "authors": {"7": {"author_id": "7", "name": "Michael Larabel", "url": ""}}});
+        let item: PocketItem = serde_json::from_value(value).unwrap();
+        assert_eq!(item.excerpt, "Networking changes");
+        assert_eq!(item.extra["domain_metadata"], json!({"name": "Phoronix"}));
+
+        let written = serde_json::to_value(&item).unwrap();
+        assert_eq!(written["domain_metadata"], json!({"name": "Phoronix"}));
+        let read_back: PocketItem = serde_json::from_value(written).unwrap();
+        assert_eq!(read_back, item);
+    }
 }
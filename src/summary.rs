@@ -0,0 +1,117 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use crate::epub;
+use crate::network;
+use crate::prss;
+
+const PROMPT: &str = "Summarize the article in 3 to 5 short markdown bullet points. \
+Reply with the bullet points only.";
+
+/// OpenAI-compatible endpoint that summarizes downloaded articles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct SummaryConfig {
+    /// Base url, `/chat/completions` is appended
+    pub endpoint: String,
+    pub model: String,
+    /// Sent as a bearer token, `$NAME` reads it from the environment
+    pub api_key: Option<String>,
+    /// Longer articles are cut to fit into the model's context
+    pub max_chars: usize,
+}
+
+impl Default for SummaryConfig {
+    fn default() -> Self {
+        Self {
+            endpoint: "https://api.openai.com/v1".to_string(),
+            model: "gpt-4o-mini".to_string(),
+            api_key: None,
+            max_chars: 24000,
+        }
+    }
+}
+
+/// Summaries are kept next to the downloaded article, `articles/<id>.summary.md`
+fn cache_path(article: &Path) -> PathBuf {
+    article.with_extension("summary.md")
+}
+
+pub fn cached(article: &Path) -> Option<String> {
+    fs::read_to_string(cache_path(article)).ok()
+}
+
+fn request_body(config: &SummaryConfig, title: &str, markdown: &str) -> serde_json::Value {
+    let content: String = markdown.chars().take(config.max_chars).collect();
+    json!({
+        "model": config.model,
+        "messages": [
+            { "role": "system", "content": PROMPT },
+            { "role": "user", "content": format!("# {}\n\n{}", title, content) },
+        ],
+    })
+}
+
+fn parse_response(response: &serde_json::Value) -> anyhow::Result<String> {
+    match response["choices"][0]["message"]["content"].as_str() {
+        Some(summary) if !summary.trim().is_empty() => Ok(summary.trim().to_string()),
+        _ => match response["error"]["message"].as_str() {
+            Some(error) => anyhow::bail!("Summary failed: {}", error),
+            None => anyhow::bail!("Summary failed, unexpected response: {}", response),
+        },
+    }
+}
+
+/// Sends the normalized markdown of the downloaded article and caches the answer
+pub fn summarize(config: &SummaryConfig, article: &Path, title: &str) -> anyhow::Result<String> {
+    let content = fs::read_to_string(article)?;
+    let client = network::blocking_client(Some(Duration::from_secs(120)))?;
+    let mut request = client
+        .post(format!(
+            "{}/chat/completions",
+            config.endpoint.trim_end_matches('/')
+        ))
+        .json(&request_body(
+            config,
+            title,
+            epub::article_markdown(&content),
+        ));
+    if let Some(api_key) = &config.api_key {
+        request = request.bearer_auth(prss::resolve(api_key)?);
+    }
+    let response: serde_json::Value = request.send()?.json()?;
+    let summary = parse_response(&response)?;
+    fs::write(cache_path(article), &summary)?;
+    Ok(summary)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn builds_request_and_reads_answer() {
+        let config = SummaryConfig {
+            max_chars: 5,
+            ..SummaryConfig::default()
+        };
+        let body = request_body(&config, "Title", "long article");
+        assert_eq!(body["messages"][1]["content"], "# Title\n\nlong ");
+        assert_eq!(
+            cache_path(Path::new("articles/42.md")),
+            Path::new("articles/42.summary.md")
+        );
+
+        let answer = json!({ "choices": [{ "message": { "content": "\n- one\n- two\n" } }] });
+        assert_eq!(parse_response(&answer).unwrap(), "- one\n- two");
+        let error = json!({ "error": { "message": "invalid api key" } });
+        assert!(parse_response(&error)
+            .unwrap_err()
+            .to_string()
+            .contains("invalid api key"));
+    }
+}
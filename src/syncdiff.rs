@@ -0,0 +1,138 @@
+use std::collections::{BTreeSet, HashMap};
+
+use crate::storage::PocketItem;
+use crate::TableRow;
+
+#[derive(Clone, Debug, PartialEq)]
+pub enum Change {
+    Added {
+        item_id: String,
+        title: String,
+    },
+    /// Archived, deleted or favorited on another device
+    Removed {
+        title: String,
+    },
+    Retagged {
+        title: String,
+        added: Vec<String>,
+        removed: Vec<String>,
+    },
+}
+
+/// What a sync changed in the list: new items first in list order, then removed and retagged ones
+#[derive(Debug, Default)]
+pub struct SyncDiff {
+    pub changes: Vec<Change>,
+}
+
+fn title(item: &PocketItem) -> String {
+    if item.title().is_empty() {
+        item.url().to_string()
+    } else {
+        item.title().to_string()
+    }
+}
+
+impl SyncDiff {
+    pub fn between(before: &[PocketItem], after: &[PocketItem]) -> Self {
+        let before_by_id: HashMap<&str, &PocketItem> = before
+            .iter()
+            .map(|item| (item.item_id.as_str(), item))
+            .collect();
+        let after_by_id: HashMap<&str, &PocketItem> = after
+            .iter()
+            .map(|item| (item.item_id.as_str(), item))
+            .collect();
+        let mut added = Vec::new();
+        let mut retagged = Vec::new();
+        for item in after {
+            let Some(old) = before_by_id.get(item.item_id.as_str()) else {
+                added.push(Change::Added {
+                    item_id: item.item_id.clone(),
+                    title: title(item),
+                });
+                continue;
+            };
+            let old_tags: BTreeSet<&str> = old.tags().map(String::as_str).collect();
+            let new_tags: BTreeSet<&str> = item.tags().map(String::as_str).collect();
+            if old_tags != new_tags {
+                retagged.push(Change::Retagged {
+                    title: title(item),
+                    added: new_tags
+                        .difference(&old_tags)
+                        .map(|t| t.to_string())
+                        .collect(),
+                    removed: old_tags
+                        .difference(&new_tags)
+                        .map(|t| t.to_string())
+                        .collect(),
+                });
+            }
+        }
+        let removed = before
+            .iter()
+            .filter(|item| !after_by_id.contains_key(item.item_id.as_str()))
+            .map(|item| Change::Removed { title: title(item) });
+        Self {
+            changes: added.into_iter().chain(removed).chain(retagged).collect(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    /// "3 new · 1 removed · 2 retagged", zero counts left out
+    pub fn summary(&self) -> String {
+        let count = |pred: fn(&Change) -> bool| self.changes.iter().filter(|c| pred(c)).count();
+        [
+            (count(|c| matches!(c, Change::Added { .. })), "new"),
+            (count(|c| matches!(c, Change::Removed { .. })), "removed"),
+            (count(|c| matches!(c, Change::Retagged { .. })), "retagged"),
+        ]
+        .into_iter()
+        .filter(|(count, _)| *count > 0)
+        .map(|(count, label)| format!("{} {}", count, label))
+        .collect::<Vec<_>>()
+        .join(" · ")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tutorial::sample_item;
+
+    #[test]
+    fn finds_new_removed_and_retagged_items() {
+        let before = [
+            sample_item(1, 3, "Kept", "https://a.org/1", &["rust"], None),
+            sample_item(2, 4, "Gone", "https://a.org/2", &[], None),
+        ];
+        let after = [
+            sample_item(3, 0, "", "https://a.org/3", &[], None),
+            sample_item(1, 3, "Kept", "https://a.org/1", &["go", "top"], None),
+        ];
+        let diff = SyncDiff::between(&before, &after);
+        assert_eq!(
+            diff.changes,
+            [
+                Change::Added {
+                    item_id: after[0].item_id.clone(),
+                    title: "https://a.org/3".to_string()
+                },
+                Change::Removed {
+                    title: "Gone".to_string()
+                },
+                Change::Retagged {
+                    title: "Kept".to_string(),
+                    added: vec!["go".to_string(), "top".to_string()],
+                    removed: vec!["rust".to_string()]
+                },
+            ]
+        );
+        assert_eq!(diff.summary(), "1 new · 1 removed · 1 retagged");
+        assert!(SyncDiff::between(&after, &after).is_empty());
+    }
+}
@@ -0,0 +1,204 @@
+use std::collections::{HashMap, HashSet};
+use std::fs;
+use std::io::Write;
+use std::path::Path;
+
+use anyhow::Context;
+use chrono::Utc;
+use serde_json::Value;
+
+use crate::pocket::GetPocketSync;
+use crate::storage::{self, Pocket, PocketItem, PocketItemUpdate};
+
+/// When the last check ran, unix seconds
+pub const VERIFIED_AT_FILE: &str = "sync_verified_at";
+
+/// What an item is compared by: a newer update time or another status means it changed
+#[derive(Clone, Debug, PartialEq)]
+pub struct Stamp {
+    pub status: String,
+    pub time_updated: i64,
+}
+
+impl Stamp {
+    fn of_value(value: &Value) -> Self {
+        Self {
+            status: value["status"].as_str().unwrap_or("").to_string(),
+            time_updated: value["time_updated"]
+                .as_str()
+                .and_then(|time| time.parse().ok())
+                .unwrap_or(0),
+        }
+    }
+
+    fn of_item(item: &PocketItem) -> Self {
+        Self {
+            status: item.status.clone(),
+            time_updated: item.time_updated.parse().unwrap_or(0),
+        }
+    }
+}
+
+/// Page of the listing, keyed by item id
+pub struct Page {
+    pub offset: u32,
+    pub stamps: HashMap<String, Stamp>,
+}
+
+#[derive(Debug, Default, PartialEq)]
+pub struct Gaps {
+    /// Offsets of pages with items missing or outdated locally
+    pub stale_pages: Vec<u32>,
+    /// Local items Pocket doesn't list anymore
+    pub deleted: Vec<String>,
+}
+
+/// Compares the local items with the whole listing
+pub fn find_gaps(local: &HashMap<String, Stamp>, pages: &[Page]) -> Gaps {
+    let mut listed = HashSet::new();
+    let mut gaps = Gaps::default();
+    for page in pages {
+        let mut stale = false;
+        for (item_id, remote) in &page.stamps {
+            if remote.status == "2" {
+                continue;
+            }
+            listed.insert(item_id.as_str());
+            stale |= match local.get(item_id) {
+                Some(local) => {
+                    local.status != remote.status || local.time_updated < remote.time_updated
+                }
+                None => true,
+            };
+        }
+        if stale {
+            gaps.stale_pages.push(page.offset);
+        }
+    }
+    gaps.deleted = local
+        .keys()
+        .filter(|item_id| !listed.contains(item_id.as_str()) && !storage::is_local_id(item_id))
+        .cloned()
+        .collect();
+    gaps.deleted.sort();
+    gaps
+}
+
+#[derive(Debug, Default)]
+pub struct VerifyReport {
+    pub refetched_pages: usize,
+    pub deleted: usize,
+}
+
+/// True once `every_days` passed since the last check, never when it is 0
+pub fn is_due(every_days: u64) -> bool {
+    if every_days == 0 {
+        return false;
+    }
+    let last = fs::read_to_string(VERIFIED_AT_FILE)
+        .ok()
+        .and_then(|content| content.trim().parse::<i64>().ok())
+        .unwrap_or(0);
+    Utc::now().timestamp() - last >= (every_days * 24 * 3600) as i64
+}
+
+/// Lists the whole account page by page and appends what the incremental syncs missed to the
+/// delta: pages with missing or outdated items are fetched again in full, items gone from
+/// Pocket are recorded as deleted
+pub fn verify_and_heal(
+    client: &GetPocketSync,
+    local: &[PocketItem],
+    delta_file: &Path,
+) -> anyhow::Result<VerifyReport> {
+    let mut pages = Vec::new();
+    let mut offset = 0;
+    loop {
+        let page = client
+            .retrieve_page(offset, true)
+            .context("Failed to list items for the sync check")?;
+        if page.list.is_empty() {
+            break;
+        }
+        let stamps: HashMap<String, Stamp> = page
+            .list
+            .iter()
+            .map(|(item_id, value)| (item_id.clone(), Stamp::of_value(value)))
+            .collect();
+        pages.push(Page { offset, stamps });
+        offset += page.list.len() as u32;
+    }
+    let local: HashMap<String, Stamp> = local
+        .iter()
+        .map(|item| (item.item_id.clone(), Stamp::of_item(item)))
+        .collect();
+    let gaps = find_gaps(&local, &pages);
+
+    for offset in &gaps.stale_pages {
+        let page: Pocket = client.retrieve_page(*offset, false)?;
+        storage::append_to_delta(delta_file, &page)?;
+    }
+    let now = Utc::now().timestamp() as u64;
+    let deletions: String = gaps
+        .deleted
+        .iter()
+        .map(|item_id| {
+            storage::delete_delta_line(&PocketItemUpdate::Delete {
+                item_id: item_id.clone(),
+                timestamp: Some(now),
+            })
+        })
+        .collect::<anyhow::Result<_>>()?;
+    if !deletions.is_empty() {
+        fs::OpenOptions::new()
+            .append(true)
+            .open(delta_file)?
+            .write_all(deletions.as_bytes())?;
+    }
+    fs::write(VERIFIED_AT_FILE, Utc::now().timestamp().to_string())?;
+    Ok(VerifyReport {
+        refetched_pages: gaps.stale_pages.len(),
+        deleted: gaps.deleted.len(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn pages_with_missing_or_newer_items_are_stale() {
+        let stamp = |status: &str, time_updated| Stamp {
+            status: status.to_string(),
+            time_updated,
+        };
+        let page = |offset, stamps: &[(&str, Stamp)]| Page {
+            offset,
+            stamps: stamps
+                .iter()
+                .map(|(id, stamp)| (id.to_string(), stamp.clone()))
+                .collect(),
+        };
+        let local: HashMap<String, Stamp> = [
+            ("1", stamp("0", 10)),
+            ("2", stamp("0", 10)),
+            ("3", stamp("0", 10)),
+            ("4", stamp("0", 10)),
+            ("gone", stamp("0", 10)),
+        ]
+        .into_iter()
+        .map(|(id, stamp)| (id.to_string(), stamp))
+        .collect();
+        let pages = [
+            page(0, &[("1", stamp("0", 10)), ("2", stamp("0", 5))]),
+            page(2, &[("3", stamp("1", 10)), ("4", stamp("0", 10))]),
+            page(4, &[("5", stamp("0", 1)), ("deleted", stamp("2", 20))]),
+        ];
+        assert_eq!(
+            find_gaps(&local, &pages),
+            Gaps {
+                stale_pages: vec![2, 4],
+                deleted: vec!["gone".to_string()],
+            }
+        );
+    }
+}
@@ -0,0 +1,324 @@
+use ratatui::{
+    layout::Rect,
+    style::{Color, Modifier, Style},
+    text::{Line, Span},
+    widgets::{Block, BorderType, Borders, Clear, List, ListItem, Paragraph},
+    Frame,
+};
+
+use crate::logo::OCEANIC_NEXT;
+use crate::TableColors;
+
+const MAX_SUGGESTIONS: usize = 6;
+
+/// What the edited tags are applied to
+#[derive(Clone, Copy, PartialEq)]
+pub enum TagTarget {
+    PocketItem,
+    RssItems,
+    /// Links pasted from the clipboard with `a`, added to Pocket with the tags
+    PastedUrls,
+}
+
+/**
+Tag editor with every tag shown as a chip. Typed text becomes a chip on `,`/Enter, so a tag can't
+end up with commas or stray whitespace. Chips are selected with ←/→ and removed with Backspace/Delete.
+*/
+#[derive(Clone)]
+pub struct TagEditor {
+    pub target: TagTarget,
+    pub tags: Vec<String>,
+    input: String,
+    /// Chip under the cursor, `None` when the cursor is in the input
+    selected_chip: Option<usize>,
+    known_tags: Vec<String>,
+    /// Ranked tags for the edited item, offered while nothing is typed
+    suggested: Vec<String>,
+    suggestions: Vec<String>,
+    suggestion_index: usize,
+}
+
+pub enum TagEditorAction {
+    Continue,
+    Save(Vec<String>),
+    Cancel,
+}
+
+/// Trims and collapses inner whitespace, `None` for an empty tag
+fn normalize_tag(tag: &str) -> Option<String> {
+    let tag = tag
+        .split(',')
+        .flat_map(str::split_whitespace)
+        .collect::<Vec<_>>()
+        .join(" ");
+    (!tag.is_empty()).then_some(tag)
+}
+
+impl TagEditor {
+    pub fn new(
+        target: TagTarget,
+        tags: Vec<String>,
+        known_tags: Vec<String>,
+        suggested: Vec<String>,
+    ) -> Self {
+        let mut editor = Self {
+            target,
+            tags,
+            input: String::new(),
+            selected_chip: None,
+            known_tags,
+            suggested,
+            suggestions: Vec::new(),
+            suggestion_index: 0,
+        };
+        editor.update_suggestions();
+        editor
+    }
+
+    fn update_suggestions(&mut self) {
+        let typed = self.input.trim().to_lowercase();
+        self.suggestion_index = 0;
+        self.suggestions = if typed.is_empty() {
+            self.suggested
+                .iter()
+                .filter(|tag| !self.tags.contains(tag))
+                .take(MAX_SUGGESTIONS)
+                .cloned()
+                .collect()
+        } else {
+            self.known_tags
+                .iter()
+                .filter(|tag| tag.to_lowercase().contains(&typed) && !self.tags.contains(tag))
+                .take(MAX_SUGGESTIONS)
+                .cloned()
+                .collect()
+        };
+    }
+
+    fn commit_input(&mut self) {
+        if let Some(tag) = normalize_tag(&self.input) {
+            if !self.tags.contains(&tag) {
+                self.tags.push(tag);
+            }
+        }
+        self.input.clear();
+        self.update_suggestions();
+    }
+
+    fn accept_suggestion(&mut self) -> bool {
+        match self.suggestions.get(self.suggestion_index).cloned() {
+            Some(tag) => {
+                self.input = tag;
+                self.commit_input();
+                true
+            }
+            None => false,
+        }
+    }
+
+    fn remove_selected_chip(&mut self) {
+        if let Some(idx) = self.selected_chip {
+            self.tags.remove(idx);
+            self.selected_chip = if self.tags.is_empty() {
+                None
+            } else {
+                Some(idx.min(self.tags.len() - 1))
+            };
+            self.update_suggestions();
+        }
+    }
+
+    pub fn handle_key(&mut self, key: crossterm::event::KeyCode) -> TagEditorAction {
+        use crossterm::event::KeyCode::*;
+        match key {
+            Esc if !self.suggestions.is_empty() => self.suggestions.clear(),
+            Esc => return TagEditorAction::Cancel,
+            Enter if self.input.trim().is_empty() => {
+                return TagEditorAction::Save(self.tags.clone());
+            }
+            Enter => {
+                if self.suggestions.is_empty() {
+                    self.commit_input();
+                } else {
+                    self.accept_suggestion();
+                }
+            }
+            Tab => {
+                self.accept_suggestion();
+            }
+            Char(',') => self.commit_input(),
+            Char(ch) => {
+                self.selected_chip = None;
+                self.input.push(ch);
+                self.update_suggestions();
+            }
+            Down if !self.suggestions.is_empty() => {
+                self.suggestion_index = (self.suggestion_index + 1) % self.suggestions.len();
+            }
+            Up if !self.suggestions.is_empty() => {
+                self.suggestion_index = self
+                    .suggestion_index
+                    .checked_sub(1)
+                    .unwrap_or(self.suggestions.len() - 1);
+            }
+            Left if self.input.is_empty() && !self.tags.is_empty() => {
+                self.selected_chip = Some(
+                    self.selected_chip
+                        .map_or(self.tags.len() - 1, |idx| idx.saturating_sub(1)),
+                );
+            }
+            Right => {
+                self.selected_chip = self
+                    .selected_chip
+                    .map(|idx| idx + 1)
+                    .filter(|idx| *idx < self.tags.len());
+            }
+            Delete => self.remove_selected_chip(),
+            Backspace if self.selected_chip.is_some() => self.remove_selected_chip(),
+            Backspace if self.input.is_empty() => {
+                // first backspace selects the last chip, the second one removes it
+                self.selected_chip = self.tags.len().checked_sub(1);
+            }
+            Backspace => {
+                self.input.pop();
+                self.update_suggestions();
+            }
+            _ => {}
+        }
+        TagEditorAction::Continue
+    }
+}
+
+/// Chips with the input in the footer area, suggestions drop up above it
+pub fn render(f: &mut Frame, editor: &TagEditor, colors: &TableColors, area: Rect) {
+    let mut spans = vec![Span::raw("Tags: ")];
+    for (i, tag) in editor.tags.iter().enumerate() {
+        let style = if editor.selected_chip == Some(i) {
+            Style::default()
+                .bg(OCEANIC_NEXT.base_08)
+                .fg(OCEANIC_NEXT.base_00)
+        } else {
+            Style::default()
+                .bg(OCEANIC_NEXT.base_0e)
+                .fg(OCEANIC_NEXT.base_00)
+        };
+        spans.push(Span::styled(format!(" {} × ", tag), style));
+        spans.push(Span::raw(" "));
+    }
+    spans.push(Span::raw(editor.input.as_str()));
+    spans.push(Span::styled(
+        "█",
+        Style::default()
+            .fg(colors.row_fg)
+            .add_modifier(Modifier::SLOW_BLINK),
+    ));
+    f.render_widget(
+        Paragraph::new(Line::from(spans))
+            .style(Style::new().fg(colors.row_fg).bg(colors.buffer_bg))
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title(" Enter - save | , - add tag | ←/→ Backspace - remove | Esc - cancel ")
+                    .border_style(Style::new().fg(colors.footer_border_color))
+                    .border_type(BorderType::Rounded),
+            ),
+        area,
+    );
+
+    if editor.suggestions.is_empty() {
+        return;
+    }
+    let height = editor.suggestions.len() as u16 + 2;
+    let dropdown = Rect::new(
+        area.x + 1,
+        area.y.saturating_sub(height),
+        32.min(area.width.saturating_sub(2)),
+        height.min(area.y),
+    );
+    let title = if editor.input.is_empty() {
+        " Suggested: Tab - add "
+    } else {
+        ""
+    };
+    let items: Vec<ListItem> = editor
+        .suggestions
+        .iter()
+        .enumerate()
+        .map(|(i, tag)| {
+            let style = if i == editor.suggestion_index {
+                Style::default().fg(Color::Black).bg(Color::White)
+            } else {
+                Style::default().fg(colors.row_fg)
+            };
+            ListItem::new(tag.as_str()).style(style)
+        })
+        .collect();
+    f.render_widget(Clear, dropdown);
+    f.render_widget(
+        List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .title_bottom(title)
+                    .border_style(Style::new().fg(colors.footer_border_color))
+                    .border_type(BorderType::Rounded),
+            )
+            .style(Style::new().bg(Color::Black)),
+        dropdown,
+    );
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crossterm::event::KeyCode;
+
+    fn type_text(editor: &mut TagEditor, text: &str) {
+        for ch in text.chars() {
+            editor.handle_key(KeyCode::Char(ch));
+        }
+    }
+
+    #[test]
+    fn builds_clean_tags_from_chips() {
+        let mut editor = TagEditor::new(
+            TagTarget::PocketItem,
+            vec!["read".to_string()],
+            vec!["rust".to_string(), "databases".to_string()],
+            Vec::new(),
+        );
+        type_text(&mut editor, "  machine   learning ,ru");
+        assert_eq!(editor.tags, ["read", "machine learning"]);
+        assert_eq!(editor.suggestions, ["rust"]);
+        editor.handle_key(KeyCode::Tab);
+        type_text(&mut editor, "read,");
+        assert_eq!(editor.tags, ["read", "machine learning", "rust"]);
+
+        editor.handle_key(KeyCode::Backspace);
+        editor.handle_key(KeyCode::Left);
+        editor.handle_key(KeyCode::Backspace);
+        assert_eq!(editor.tags, ["read", "rust"]);
+
+        match editor.handle_key(KeyCode::Enter) {
+            TagEditorAction::Save(tags) => assert_eq!(tags, ["read", "rust"]),
+            _ => panic!("expected save"),
+        }
+    }
+
+    #[test]
+    fn offers_suggested_tags_until_typing() {
+        let mut editor = TagEditor::new(
+            TagTarget::PocketItem,
+            vec!["linux".to_string()],
+            Vec::new(),
+            vec!["linux".to_string(), "kernel".to_string()],
+        );
+        assert_eq!(editor.suggestions, ["kernel"]);
+        type_text(&mut editor, "k");
+        assert!(editor.suggestions.is_empty());
+        editor.handle_key(KeyCode::Backspace);
+        editor.handle_key(KeyCode::Tab);
+        assert_eq!(editor.tags, ["linux", "kernel"]);
+        assert!(editor.suggestions.is_empty());
+    }
+}
@@ -0,0 +1,78 @@
+use std::cmp::Ordering;
+use std::collections::HashMap;
+
+/// Levels of hierarchical tags like `lang/rust`. Pocket only stores flat tags, parents like `lang`
+/// exist in the tag popup and in filters
+const SEPARATOR: char = '/';
+
+/// The tag and every parent of it, `lang/rust/async` gives `lang`, `lang/rust` and itself
+pub fn with_parents(tag: &str) -> impl Iterator<Item = &str> {
+    tag.match_indices(SEPARATOR)
+        .map(move |(i, _)| &tag[..i])
+        .filter(|parent| !parent.is_empty() && !parent.ends_with(SEPARATOR))
+        .chain(std::iter::once(tag))
+}
+
+/// The tag itself or one of its children
+pub fn is_within(tag: &str, parent: &str) -> bool {
+    tag.strip_prefix(parent)
+        .is_some_and(|rest| rest.is_empty() || rest.starts_with(SEPARATOR))
+}
+
+pub fn parent(tag: &str) -> Option<&str> {
+    tag.rsplit_once(SEPARATOR).map(|(parent, _)| parent)
+}
+
+pub fn depth(tag: &str) -> usize {
+    tag.matches(SEPARATOR).count()
+}
+
+/// Last part of the tag, what the tree shows under its parent
+pub fn label(tag: &str) -> &str {
+    tag.rsplit_once(SEPARATOR).map_or(tag, |(_, label)| label)
+}
+
+/// Children right after their parent, siblings by name or by count. `counts` has the parents too
+pub fn tree_order(a: &str, b: &str, counts: &HashMap<&str, usize>, by_count: bool) -> Ordering {
+    let a_parts: Vec<&str> = a.split(SEPARATOR).collect();
+    let b_parts: Vec<&str> = b.split(SEPARATOR).collect();
+    for i in 0..a_parts.len().min(b_parts.len()) {
+        if a_parts[i] != b_parts[i] {
+            let count = |parts: &[&str]| {
+                counts
+                    .get(parts[..=i].join(&SEPARATOR.to_string()).as_str())
+                    .copied()
+                    .unwrap_or(0)
+            };
+            let by_name = a_parts[i].cmp(b_parts[i]);
+            return if by_count {
+                count(&b_parts).cmp(&count(&a_parts)).then(by_name)
+            } else {
+                by_name.then(count(&b_parts).cmp(&count(&a_parts)))
+            };
+        }
+    }
+    a_parts.len().cmp(&b_parts.len())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn orders_children_under_their_parent() {
+        assert_eq!(
+            with_parents("lang/rust/async").collect::<Vec<_>>(),
+            ["lang", "lang/rust", "lang/rust/async"]
+        );
+        assert!(is_within("lang/rust", "lang"));
+        assert!(!is_within("language", "lang"));
+
+        let counts = HashMap::from([("lang", 5), ("lang/go", 1), ("lang/rust", 4), ("lang-x", 9)]);
+        let mut tags = vec!["lang-x", "lang/rust", "lang/go", "lang"];
+        tags.sort_by(|a, b| tree_order(a, b, &counts, false));
+        assert_eq!(tags, ["lang", "lang/go", "lang/rust", "lang-x"]);
+        tags.sort_by(|a, b| tree_order(a, b, &counts, true));
+        assert_eq!(tags, ["lang-x", "lang", "lang/rust", "lang/go"]);
+    }
+}
@@ -0,0 +1,145 @@
+use std::io::{self, Write};
+use std::sync::OnceLock;
+
+use crossterm::{
+    event::{
+        DisableMouseCapture, EnableMouseCapture, KeyboardEnhancementFlags,
+        PopKeyboardEnhancementFlags, PushKeyboardEnhancementFlags,
+    },
+    execute,
+    terminal::{
+        disable_raw_mode, enable_raw_mode, supports_keyboard_enhancement, EnterAlternateScreen,
+        LeaveAlternateScreen,
+    },
+};
+use log::info;
+use serde::{Deserialize, Serialize};
+
+/// Terminal features that can be turned off, `--no-mouse` and `--no-keyboard-enhancement`
+/// override them for a single run
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(default)]
+pub struct TerminalConfig {
+    /// Clicks, scrolling and dragging in the app; off leaves text selection to the terminal
+    pub mouse: bool,
+    /// Kitty keyboard protocol, used only when the terminal reports it
+    pub keyboard_enhancement: bool,
+}
+
+impl Default for TerminalConfig {
+    fn default() -> Self {
+        Self {
+            mouse: true,
+            keyboard_enhancement: true,
+        }
+    }
+}
+
+/// What was turned on when entering the app screen, to be turned off the same way
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Features {
+    pub mouse: bool,
+    pub keyboard_enhancement: bool,
+}
+
+static FEATURES: OnceLock<Features> = OnceLock::new();
+
+/// Terminals that can't do more than print text
+fn is_dumb(term: Option<&str>) -> bool {
+    matches!(term, Some("dumb") | Some(""))
+}
+
+fn resolve(config: &TerminalConfig, args: &[String], term: Option<&str>) -> Features {
+    let flag = |name: &str| args.iter().any(|arg| arg == name);
+    let dumb = is_dumb(term);
+    Features {
+        mouse: config.mouse && !flag("--no-mouse") && !dumb,
+        keyboard_enhancement: config.keyboard_enhancement
+            && !flag("--no-keyboard-enhancement")
+            && !dumb,
+    }
+}
+
+/// Set once on startup from the config, the flags and what the terminal answers to a query.
+/// Nothing beyond the alternate screen is turned on if never called
+pub fn init(config: &TerminalConfig) {
+    let args: Vec<String> = std::env::args().collect();
+    let mut features = resolve(config, &args, std::env::var("TERM").ok().as_deref());
+    if features.keyboard_enhancement {
+        features.keyboard_enhancement = supports_keyboard_enhancement().unwrap_or(false);
+        if !features.keyboard_enhancement {
+            info!("The terminal doesn't support keyboard enhancements, they stay off");
+        }
+    }
+    let _ = FEATURES.set(features);
+}
+
+pub fn features() -> Features {
+    FEATURES.get().copied().unwrap_or_default()
+}
+
+/// Raw mode, alternate screen and the enabled features
+pub fn enter(out: &mut impl Write) -> io::Result<()> {
+    let features = features();
+    enable_raw_mode()?;
+    execute!(out, EnterAlternateScreen)?;
+    if features.mouse {
+        execute!(out, EnableMouseCapture)?;
+    }
+    if features.keyboard_enhancement {
+        execute!(
+            out,
+            PushKeyboardEnhancementFlags(KeyboardEnhancementFlags::DISAMBIGUATE_ESCAPE_CODES)
+        )?;
+    }
+    Ok(())
+}
+
+/// Back to the normal screen, for quitting or handing the terminal to another program
+pub fn leave(out: &mut impl Write) -> io::Result<()> {
+    let features = features();
+    disable_raw_mode()?;
+    if features.keyboard_enhancement {
+        execute!(out, PopKeyboardEnhancementFlags)?;
+    }
+    if features.mouse {
+        execute!(out, DisableMouseCapture)?;
+    }
+    execute!(out, LeaveAlternateScreen)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn flags_and_dumb_terminals_turn_features_off() {
+        let args = |list: &[&str]| list.iter().map(|arg| arg.to_string()).collect::<Vec<_>>();
+        let config = TerminalConfig::default();
+        let all = Features {
+            mouse: true,
+            keyboard_enhancement: true,
+        };
+        assert_eq!(resolve(&config, &args(&[]), Some("xterm-256color")), all);
+        assert_eq!(
+            resolve(&config, &args(&["--no-mouse"]), Some("xterm")),
+            Features {
+                mouse: false,
+                ..all
+            }
+        );
+        assert_eq!(
+            resolve(&config, &args(&[]), Some("dumb")),
+            Features::default()
+        );
+
+        let config = TerminalConfig {
+            keyboard_enhancement: false,
+            ..config
+        };
+        assert_eq!(
+            resolve(&config, &args(&["--no-mouse"]), None),
+            Features::default()
+        );
+    }
+}
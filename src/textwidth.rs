@@ -0,0 +1,98 @@
+use unicode_segmentation::UnicodeSegmentation;
+use unicode_width::{UnicodeWidthChar, UnicodeWidthStr};
+
+pub const ELLIPSIS: &str = "…";
+
+/// Columns a terminal gives one user-perceived character: emoji sequences joined with ZWJ,
+/// flags and emoji-style symbols take two, combining marks none of their own
+fn grapheme_width(grapheme: &str) -> usize {
+    let is_regional = |ch: char| ('\u{1F1E6}'..='\u{1F1FF}').contains(&ch);
+    if grapheme.contains('\u{FE0F}') || grapheme.chars().filter(|ch| is_regional(*ch)).count() == 2
+    {
+        return 2;
+    }
+    grapheme
+        .chars()
+        .next()
+        .map_or(0, |ch| ch.width().unwrap_or(0))
+}
+
+/// Width of `text` in terminal columns
+pub fn width(text: &str) -> usize {
+    text.graphemes(true).map(grapheme_width).sum()
+}
+
+/// The part of `text` that fits into `max_width` columns and `ELLIPSIS` when something was cut,
+/// never splitting a grapheme or a wide character. Spaces before the ellipsis are dropped
+pub fn truncate(text: &str, max_width: usize) -> (&str, &'static str) {
+    if width(text) <= max_width {
+        return (text, "");
+    }
+    if max_width == 0 {
+        return ("", "");
+    }
+    let available = max_width.saturating_sub(ELLIPSIS.width());
+    let mut used = 0;
+    let mut end = 0;
+    for (start, grapheme) in text.grapheme_indices(true) {
+        used += grapheme_width(grapheme);
+        if used > available {
+            break;
+        }
+        end = start + grapheme.len();
+    }
+    (text[..end].trim_end(), ELLIPSIS)
+}
+
+/// The first line of `text` wrapped at `max_width` columns and the rest of it. Lines break after
+/// the last space that fits, words longer than the line are cut
+pub fn split_line(text: &str, max_width: usize) -> (&str, &str) {
+    if width(text) <= max_width {
+        return (text, "");
+    }
+    let mut used = 0;
+    let mut end = 0;
+    let mut last_space = None;
+    for (start, grapheme) in text.grapheme_indices(true) {
+        used += grapheme_width(grapheme);
+        if used > max_width {
+            break;
+        }
+        if grapheme.trim().is_empty() {
+            last_space = Some(start);
+        }
+        end = start + grapheme.len();
+    }
+    let end = last_space.filter(|space| *space > 0).unwrap_or(end);
+    (text[..end].trim_end(), text[end..].trim_start())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_and_cuts_wide_titles() {
+        assert_eq!(width("Rust 入門"), 9);
+        assert_eq!(width("👨‍👩‍👧 family ❤️"), 12);
+        assert_eq!(width("cafe\u{301}"), 4);
+
+        assert_eq!(truncate("Rust", 4), ("Rust", ""));
+        // the wide character that doesn't fit next to the ellipsis is left out whole
+        assert_eq!(truncate("入門ガイド", 6), ("入門", ELLIPSIS));
+        assert_eq!(truncate("入門ガイド", 5), ("入門", ELLIPSIS));
+        assert_eq!(truncate("👨‍👩‍👧 family", 4), ("👨‍👩‍👧", ELLIPSIS));
+        assert_eq!(
+            truncate("cafe\u{301} au lait", 5),
+            ("cafe\u{301}", ELLIPSIS)
+        );
+        assert_eq!(truncate("Rust", 0), ("", ""));
+
+        assert_eq!(
+            split_line("Rust async in depth", 12),
+            ("Rust async", "in depth")
+        );
+        assert_eq!(split_line("入門ガイド", 5), ("入門", "ガイド"));
+        assert_eq!(split_line("Short", 12), ("Short", ""));
+    }
+}
@@ -0,0 +1,158 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::thread;
+use std::time::Duration;
+
+use image::DynamicImage;
+use log::{error, info};
+use ratatui::layout::Rect;
+use ratatui::Frame;
+use ratatui_image::picker::{Picker, ProtocolType};
+use ratatui_image::protocol::StatefulProtocol;
+use ratatui_image::StatefulImage;
+
+use crate::events;
+use crate::images;
+use crate::network;
+
+/// Downloaded top images, shared by all items with the same url
+pub const THUMBNAILS_DIR: &str = "thumbnails";
+
+/// Top images are article headers, anything bigger isn't worth the wait
+const MAX_THUMBNAIL_BYTES: u64 = 2 * 1024 * 1024;
+
+enum Thumbnail {
+    Loading,
+    Decoded(DynamicImage),
+    Shown(Box<StatefulProtocol>),
+    Missing,
+}
+
+/// Top images of items drawn with the terminal's graphics protocol
+pub struct Thumbnails {
+    /// None when disabled or the terminal supports neither kitty nor sixel graphics
+    picker: Option<Picker>,
+    images: Arc<Mutex<HashMap<String, Thumbnail>>>,
+}
+
+impl Thumbnails {
+    pub fn disabled() -> Self {
+        Self {
+            picker: None,
+            images: Arc::new(Mutex::new(HashMap::new())),
+        }
+    }
+
+    /// Asks the terminal which graphics it supports, has to run before input events are read
+    pub fn detect() -> Self {
+        let picker = match Picker::from_query_stdio() {
+            // half blocks are too blurry for a thumbnail of a few cells
+            Ok(picker) if picker.protocol_type() != ProtocolType::Halfblocks => Some(picker),
+            Ok(_) => {
+                info!("Terminal has no graphics support, thumbnails are disabled");
+                None
+            }
+            Err(e) => {
+                error!("Failed to query terminal graphics support: {}", e);
+                None
+            }
+        };
+        Self {
+            picker,
+            ..Self::disabled()
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.picker.is_some()
+    }
+
+    /// Draws the image of the url, starting its download on first use. Returns false while there
+    /// is nothing to show, so the caller can use the area for something else
+    pub fn render(&mut self, f: &mut Frame, url: &str, area: Rect) -> bool {
+        let Some(picker) = &self.picker else {
+            return false;
+        };
+        let Ok(mut images) = self.images.lock() else {
+            return false;
+        };
+        let mut thumbnail = match images.remove(url) {
+            Some(Thumbnail::Decoded(image)) => {
+                Thumbnail::Shown(Box::new(picker.new_resize_protocol(image)))
+            }
+            Some(thumbnail) => thumbnail,
+            None => {
+                self.start_loading(url);
+                Thumbnail::Loading
+            }
+        };
+        let shown = if let Thumbnail::Shown(protocol) = &mut thumbnail {
+            f.render_stateful_widget(StatefulImage::default(), area, protocol.as_mut());
+            true
+        } else {
+            false
+        };
+        images.insert(url.to_string(), thumbnail);
+        shown
+    }
+
+    fn start_loading(&self, url: &str) {
+        let images = self.images.clone();
+        let url = url.to_string();
+        thread::spawn(move || {
+            let thumbnail = match load(&url, Path::new(THUMBNAILS_DIR)) {
+                Ok(image) => Thumbnail::Decoded(image),
+                Err(e) => {
+                    error!("Thumbnail {}: {:#}", url, e);
+                    Thumbnail::Missing
+                }
+            };
+            if let Ok(mut images) = images.lock() {
+                images.insert(url, thumbnail);
+            }
+            events::wake();
+        });
+    }
+}
+
+fn cache_path(url: &str, dir: &Path) -> PathBuf {
+    dir.join(images::local_file_name(url))
+}
+
+/// Reads the image from the cache, downloading it first when it isn't there yet
+fn load(url: &str, dir: &Path) -> anyhow::Result<DynamicImage> {
+    let path = cache_path(url, dir);
+    let bytes = match fs::read(&path) {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            let client = network::blocking_client(Some(Duration::from_secs(10)))?;
+            let bytes = images::download_image(&client, url, MAX_THUMBNAIL_BYTES)?;
+            fs::create_dir_all(dir)?;
+            fs::write(&path, &bytes)?;
+            bytes
+        }
+    };
+    Ok(image::load_from_memory(&bytes)?)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn decodes_cached_thumbnails() {
+        let dir = tempfile::tempdir().unwrap();
+        let url = "https://lwn.net/images/header.png";
+        image::RgbImage::new(4, 2)
+            .save(cache_path(url, dir.path()))
+            .unwrap();
+        let image = load(url, dir.path()).unwrap();
+        assert_eq!((image.width(), image.height()), (4, 2));
+
+        let broken = "https://lwn.net/images/broken.png";
+        fs::write(cache_path(broken, dir.path()), b"not an image").unwrap();
+        assert!(load(broken, dir.path()).is_err());
+    }
+}
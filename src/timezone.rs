@@ -0,0 +1,73 @@
+use std::sync::OnceLock;
+
+use anyhow::anyhow;
+use chrono::{DateTime, Local, NaiveDate, Utc};
+use chrono_tz::Tz;
+
+/// Time zone used to show dates and to group items by day
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum DisplayTimezone {
+    Local,
+    Utc,
+    Named(Tz),
+}
+
+impl DisplayTimezone {
+    /// `local`, `utc` or an IANA name like `Europe/Berlin`
+    pub fn parse(name: &str) -> anyhow::Result<Self> {
+        match name.trim().to_lowercase().as_str() {
+            "" | "local" => Ok(DisplayTimezone::Local),
+            "utc" => Ok(DisplayTimezone::Utc),
+            _ => name
+                .trim()
+                .parse::<Tz>()
+                .map(DisplayTimezone::Named)
+                .map_err(|e| anyhow!("Unknown timezone '{}': {}", name, e)),
+        }
+    }
+
+    pub fn date_of(&self, timestamp: i64) -> NaiveDate {
+        let utc = DateTime::<Utc>::from_timestamp(timestamp, 0).expect("invalid timestamp");
+        match self {
+            DisplayTimezone::Local => utc.with_timezone(&Local).date_naive(),
+            DisplayTimezone::Utc => utc.date_naive(),
+            DisplayTimezone::Named(tz) => utc.with_timezone(tz).date_naive(),
+        }
+    }
+}
+
+static DISPLAY_TIMEZONE: OnceLock<DisplayTimezone> = OnceLock::new();
+
+/// Set once on startup, before anything is rendered. Local time zone is used if never called
+pub fn init(timezone: DisplayTimezone) {
+    let _ = DISPLAY_TIMEZONE.set(timezone);
+}
+
+/// Calendar date of the unix timestamp in the configured time zone
+pub fn date_of(timestamp: i64) -> NaiveDate {
+    DISPLAY_TIMEZONE
+        .get()
+        .unwrap_or(&DisplayTimezone::Local)
+        .date_of(timestamp)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn late_night_save_belongs_to_local_day() {
+        // 2024-03-01 23:30 in New York is already March 2nd in UTC
+        let ts = 1709353800;
+        let new_york = DisplayTimezone::parse("America/New_York").unwrap();
+        assert_eq!(new_york.date_of(ts).to_string(), "2024-03-01");
+        assert_eq!(
+            DisplayTimezone::parse("UTC")
+                .unwrap()
+                .date_of(ts)
+                .to_string(),
+            "2024-03-02"
+        );
+        assert!(DisplayTimezone::parse("Mars/Olympus").is_err());
+    }
+}
@@ -0,0 +1,80 @@
+use std::collections::HashMap;
+
+use crate::statefile::StateFile;
+use crate::storage::PocketItem;
+
+pub const TITLES_FILE: &str = "title_overrides.json";
+
+/// Titles given to items here. Pocket changes a title only when the item is added again, which
+/// resets its state and update time, so renames stay local and are shown over Pocket's title
+pub struct TitleOverrides {
+    titles: HashMap<String, String>,
+    file: StateFile,
+}
+
+impl TitleOverrides {
+    pub fn new() -> Self {
+        Self {
+            titles: HashMap::new(),
+            file: StateFile::default(),
+        }
+    }
+
+    pub fn load() -> Self {
+        let file = StateFile::at(TITLES_FILE);
+        Self {
+            titles: file.load(),
+            file,
+        }
+    }
+
+    fn save(&self) -> anyhow::Result<()> {
+        self.file.save(&self.titles)
+    }
+
+    pub fn set(&mut self, item_id: &str, title: &str) -> anyhow::Result<()> {
+        self.titles.insert(item_id.to_string(), title.to_string());
+        self.save()
+    }
+
+    /// Keeps the local title of an item that Pocket knows under a new id now
+    pub fn move_to(&mut self, item_id: &str, new_id: &str) -> anyhow::Result<()> {
+        match self.titles.remove(item_id) {
+            Some(title) => self.set(new_id, &title),
+            None => Ok(()),
+        }
+    }
+
+    /// Puts the local titles on freshly loaded items
+    pub fn apply(&self, items: &mut [PocketItem]) {
+        for item in items {
+            item.title_override = self.titles.get(&item.item_id).cloned();
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tutorial::sample_item;
+    use crate::TableRow;
+
+    #[test]
+    fn local_titles_replace_pocket_titles() {
+        let mut overrides = TitleOverrides::new();
+        overrides.set("1", "Mine").unwrap();
+        let mut items = [
+            sample_item(1, 0, "Pocket's", "https://a.org/1", &[], None),
+            sample_item(2, 0, "Untouched", "https://a.org/2", &[], None),
+        ];
+        overrides.apply(&mut items);
+        assert_eq!(items[0].title(), "Mine");
+        assert_eq!(items[1].title(), "Untouched");
+        assert_eq!(items[0].resolved_title.as_deref(), Some("Pocket's"));
+
+        overrides.move_to("1", "2").unwrap();
+        overrides.apply(&mut items);
+        assert_eq!(items[0].title(), "Pocket's");
+        assert_eq!(items[1].title(), "Mine");
+    }
+}
@@ -0,0 +1,135 @@
+use std::time::{Duration, Instant};
+
+use ratatui::{
+    layout::Rect,
+    style::{Color, Style},
+    widgets::{Block, BorderType, Borders, Clear, Paragraph, Wrap},
+    Frame,
+};
+
+use crate::logo::OCEANIC_NEXT;
+
+const SUCCESS_TIMEOUT: Duration = Duration::from_secs(3);
+/// Errors stay longer, they usually need reading
+const ERROR_TIMEOUT: Duration = Duration::from_secs(8);
+const MAX_SHOWN: usize = 3;
+const WIDTH: u16 = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum ToastLevel {
+    Success,
+    Error,
+}
+
+struct Toast {
+    message: String,
+    level: ToastLevel,
+    shown_at: Instant,
+}
+
+impl Toast {
+    fn is_visible(&self, now: Instant) -> bool {
+        let timeout = match self.level {
+            ToastLevel::Success => SUCCESS_TIMEOUT,
+            ToastLevel::Error => ERROR_TIMEOUT,
+        };
+        now.saturating_duration_since(self.shown_at) < timeout
+    }
+}
+
+/// Outcomes of actions on the main list, shown in the bottom-right corner until they expire
+#[derive(Default)]
+pub struct Toasts {
+    toasts: Vec<Toast>,
+}
+
+impl Toasts {
+    pub fn success(&mut self, message: impl Into<String>) {
+        self.push(message.into(), ToastLevel::Success, Instant::now());
+    }
+
+    pub fn error(&mut self, message: impl Into<String>) {
+        self.push(message.into(), ToastLevel::Error, Instant::now());
+    }
+
+    fn push(&mut self, message: String, level: ToastLevel, now: Instant) {
+        self.toasts.retain(|toast| toast.is_visible(now));
+        self.toasts.push(Toast {
+            message,
+            level,
+            shown_at: now,
+        });
+        let extra = self.toasts.len().saturating_sub(MAX_SHOWN);
+        self.toasts.drain(..extra);
+    }
+
+    /// Newest last
+    fn visible(&self, now: Instant) -> impl Iterator<Item = &Toast> {
+        self.toasts
+            .iter()
+            .filter(move |toast| toast.is_visible(now))
+    }
+}
+
+/// Stacks the visible toasts up from the bottom-right corner of `area`, the newest one lowest
+pub fn render(f: &mut Frame, toasts: &Toasts, area: Rect) {
+    let width = WIDTH.min(area.width);
+    let mut bottom = area.y + area.height;
+    let visible: Vec<&Toast> = toasts.visible(Instant::now()).collect();
+    for toast in visible.into_iter().rev() {
+        let lines = (toast.message.chars().count() as u16).div_ceil(width.saturating_sub(2).max(1));
+        let height = lines.clamp(1, 3) + 2;
+        if bottom < area.y + height {
+            break;
+        }
+        bottom -= height;
+        let color = match toast.level {
+            ToastLevel::Success => OCEANIC_NEXT.base_0b,
+            ToastLevel::Error => OCEANIC_NEXT.base_08,
+        };
+        let toast_area = Rect::new(area.x + area.width - width, bottom, width, height);
+        f.render_widget(Clear, toast_area);
+        f.render_widget(
+            Paragraph::new(toast.message.as_str())
+                .wrap(Wrap { trim: true })
+                .style(Style::new().fg(color).bg(Color::Black))
+                .block(
+                    Block::default()
+                        .borders(Borders::ALL)
+                        .border_style(Style::new().fg(color))
+                        .border_type(BorderType::Rounded),
+                ),
+            toast_area,
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn errors_outlive_successes() {
+        let start = Instant::now();
+        let mut toasts = Toasts::default();
+        toasts.push("Failed to delete".to_string(), ToastLevel::Error, start);
+        for i in 0..3 {
+            toasts.push(format!("Renamed {}", i), ToastLevel::Success, start);
+        }
+        let messages = |toasts: &Toasts, now| {
+            toasts
+                .visible(now)
+                .map(|toast| toast.message.clone())
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            messages(&toasts, start),
+            ["Renamed 0", "Renamed 1", "Renamed 2"]
+        );
+
+        toasts.push("Failed to rename".to_string(), ToastLevel::Error, start);
+        let later = start + Duration::from_secs(5);
+        assert_eq!(messages(&toasts, later), ["Failed to rename"]);
+        assert!(messages(&toasts, start + ERROR_TIMEOUT).is_empty());
+    }
+}
@@ -0,0 +1,186 @@
+use std::fs::{self, File, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+
+use serde::{Deserialize, Serialize};
+
+/// Intent log of the transaction being applied, it only exists while files are being written
+pub const INTENT_LOG_FILE: &str = "transaction.log";
+
+enum Change {
+    Replace(String),
+    Append(String),
+}
+
+/// How to bring a file back to its state before the transaction
+#[derive(Debug, Serialize, Deserialize)]
+enum Undo {
+    Remove { path: PathBuf },
+    Restore { path: PathBuf, content: String },
+    Truncate { path: PathBuf, len: u64 },
+}
+
+impl Undo {
+    fn apply(&self) -> anyhow::Result<()> {
+        match self {
+            Undo::Remove { path } => {
+                if path.exists() {
+                    fs::remove_file(path)?;
+                }
+            }
+            Undo::Restore { path, content } => fs::write(path, content)?,
+            Undo::Truncate { path, len } => {
+                OpenOptions::new().write(true).open(path)?.set_len(*len)?
+            }
+        }
+        Ok(())
+    }
+}
+
+/**
+Multi-file update that either fully applies or is rolled back. Before touching any file the
+previous state of every file is written to the intent log, the log is removed once all files are
+written. A log left after a crash is rolled back by `recover` on the next start.
+*/
+pub struct Transaction {
+    log_path: PathBuf,
+    changes: Vec<(PathBuf, Change)>,
+}
+
+fn write_synced(path: &Path, content: &str) -> anyhow::Result<()> {
+    let mut file = File::create(path)?;
+    file.write_all(content.as_bytes())?;
+    file.sync_all()?;
+    Ok(())
+}
+
+impl Transaction {
+    pub fn new() -> Self {
+        Self::with_log(Path::new(INTENT_LOG_FILE))
+    }
+
+    fn with_log(log_path: &Path) -> Self {
+        Self {
+            log_path: log_path.to_path_buf(),
+            changes: Vec::new(),
+        }
+    }
+
+    pub fn replace(&mut self, path: impl Into<PathBuf>, content: String) {
+        self.changes.push((path.into(), Change::Replace(content)));
+    }
+
+    pub fn append(&mut self, path: impl Into<PathBuf>, content: String) {
+        self.changes.push((path.into(), Change::Append(content)));
+    }
+
+    pub fn commit(self) -> anyhow::Result<()> {
+        if self.changes.is_empty() {
+            return Ok(());
+        }
+        let mut undo = Vec::new();
+        for (path, change) in &self.changes {
+            undo.push(match change {
+                _ if !path.exists() => Undo::Remove { path: path.clone() },
+                Change::Replace(_) => Undo::Restore {
+                    path: path.clone(),
+                    content: fs::read_to_string(path)?,
+                },
+                Change::Append(_) => Undo::Truncate {
+                    path: path.clone(),
+                    len: fs::metadata(path)?.len(),
+                },
+            });
+        }
+        // the log is renamed into place, so it's never seen half-written
+        let tmp_log = self.log_path.with_extension("tmp");
+        write_synced(&tmp_log, &serde_json::to_string(&undo)?)?;
+        fs::rename(&tmp_log, &self.log_path)?;
+
+        for (path, change) in &self.changes {
+            match change {
+                Change::Replace(content) => write_synced(path, content)?,
+                Change::Append(content) => {
+                    let mut file = OpenOptions::new().create(true).append(true).open(path)?;
+                    file.write_all(content.as_bytes())?;
+                    file.sync_all()?;
+                }
+            }
+        }
+        fs::remove_file(&self.log_path)?;
+        Ok(())
+    }
+}
+
+/// Rolls back a transaction interrupted by a crash. Returns true if there was one
+pub fn recover() -> anyhow::Result<bool> {
+    recover_log(Path::new(INTENT_LOG_FILE))
+}
+
+fn recover_log(log_path: &Path) -> anyhow::Result<bool> {
+    let tmp_log = log_path.with_extension("tmp");
+    if tmp_log.exists() {
+        // crashed before the log was complete, no file was touched yet
+        fs::remove_file(&tmp_log)?;
+    }
+    if !log_path.exists() {
+        return Ok(false);
+    }
+    let undo: Vec<Undo> = serde_json::from_str(&fs::read_to_string(log_path)?)?;
+    for step in undo.iter().rev() {
+        step.apply()?;
+    }
+    fs::remove_file(log_path)?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn interrupted_transaction_is_rolled_back() {
+        let dir = tempfile::tempdir().unwrap();
+        let log = dir.path().join(INTENT_LOG_FILE);
+        let replaced = dir.path().join("queue.txt");
+        let appended = dir.path().join("delta.db");
+        let created = dir.path().join("snapshot.db");
+        fs::write(&replaced, "1\n2").unwrap();
+        fs::write(&appended, "first\n").unwrap();
+
+        let mut transaction = Transaction::with_log(&log);
+        transaction.replace(&replaced, "1".to_string());
+        transaction.append(&appended, "second\n".to_string());
+        transaction.replace(&created, "{}".to_string());
+        transaction.commit().unwrap();
+        assert!(!log.exists());
+        assert_eq!(fs::read_to_string(&appended).unwrap(), "first\nsecond\n");
+        assert!(!recover_log(&log).unwrap());
+
+        // crash after the log was written and files were partially updated
+        let new_file = dir.path().join("notes.md");
+        let undo = vec![
+            Undo::Restore {
+                path: replaced.clone(),
+                content: "1".to_string(),
+            },
+            Undo::Truncate {
+                path: appended.clone(),
+                len: fs::metadata(&appended).unwrap().len(),
+            },
+            Undo::Remove {
+                path: new_file.clone(),
+            },
+        ];
+        fs::write(&log, serde_json::to_string(&undo).unwrap()).unwrap();
+        fs::write(&replaced, "3").unwrap();
+        fs::write(&appended, "first\nsecond\nthi").unwrap();
+        fs::write(&new_file, "note").unwrap();
+
+        assert!(recover_log(&log).unwrap());
+        assert_eq!(fs::read_to_string(&replaced).unwrap(), "1");
+        assert_eq!(fs::read_to_string(&appended).unwrap(), "first\nsecond\n");
+        assert!(!new_file.exists());
+        assert!(!log.exists());
+    }
+}
@@ -0,0 +1,105 @@
+use std::io::Write;
+use std::os::unix::process::CommandExt;
+use std::process::{Child, Command, Stdio};
+use std::thread;
+
+use anyhow::Context;
+
+/// Article read aloud by the `tts` command, which gets the text on its stdin
+pub struct Playback {
+    pub title: String,
+    child: Child,
+    paused: bool,
+}
+
+impl Playback {
+    /// Runs `command` with `sh`, so pipelines like `piper ... | aplay` work
+    pub fn start(command: &str, title: &str, text: String) -> anyhow::Result<Self> {
+        let mut child = Command::new("sh")
+            .arg("-c")
+            .arg(command)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            // own group, so pausing and stopping reach every process of a pipeline
+            .process_group(0)
+            .spawn()
+            .with_context(|| format!("Failed to start {}", command))?;
+        let mut stdin = child.stdin.take().context("No stdin for the tts command")?;
+        // the command reads as slow as it speaks, writing would block the ui
+        thread::spawn(move || {
+            let _ = stdin.write_all(text.as_bytes());
+        });
+        Ok(Self {
+            title: title.to_string(),
+            child,
+            paused: false,
+        })
+    }
+
+    fn signal(&self, signal: &str) -> anyhow::Result<()> {
+        Command::new("kill")
+            .args([signal, "--", &format!("-{}", self.child.id())])
+            .stderr(Stdio::null())
+            .status()
+            .context("Failed to run kill")?;
+        Ok(())
+    }
+
+    pub fn is_paused(&self) -> bool {
+        self.paused
+    }
+
+    pub fn toggle_pause(&mut self) -> anyhow::Result<()> {
+        self.signal(if self.paused { "-CONT" } else { "-STOP" })?;
+        self.paused = !self.paused;
+        Ok(())
+    }
+
+    /// `false` once the whole text is spoken or the command failed
+    pub fn is_running(&mut self) -> bool {
+        matches!(self.child.try_wait(), Ok(None))
+    }
+
+    pub fn stop(&mut self) {
+        if !self.is_running() {
+            return;
+        }
+        // stopped processes only see the termination after they continue
+        let _ = self.signal("-TERM");
+        if self.paused {
+            let _ = self.signal("-CONT");
+        }
+        let _ = self.child.wait();
+    }
+}
+
+impl Drop for Playback {
+    fn drop(&mut self) {
+        self.stop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::{Duration, Instant};
+
+    #[test]
+    fn pauses_and_stops_the_command() {
+        let mut playback =
+            Playback::start("cat > /dev/null; sleep 5", "Title", "Text".into()).unwrap();
+        assert!(playback.is_running());
+        playback.toggle_pause().unwrap();
+        assert!(playback.is_paused());
+        playback.stop();
+        assert!(!playback.is_running());
+
+        let mut done = Playback::start("cat > /dev/null", "Title", "Text".into()).unwrap();
+        let started = Instant::now();
+        while done.is_running() && started.elapsed() < Duration::from_secs(5) {
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert!(!done.is_running());
+    }
+}
@@ -0,0 +1,253 @@
+use chrono::Utc;
+use serde_json::{json, Map, Value};
+
+use crate::prss::RssFeedItem;
+use crate::storage::PocketItem;
+
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum TutorialAction {
+    Navigate,
+    Search,
+    Tag,
+    Archive,
+    RssTriage,
+}
+
+pub struct TutorialStep {
+    pub title: &'static str,
+    pub hints: &'static [&'static str],
+    pub completed_by: Option<TutorialAction>,
+}
+
+const STEPS: [TutorialStep; 6] = [
+    TutorialStep {
+        title: "Navigate",
+        hints: &[
+            "Move the selection with j/k or ↓/↑.",
+            "Ctrl+d/Ctrl+u page down/up, gg and G jump",
+            "to the start and to the end of the list.",
+        ],
+        completed_by: Some(TutorialAction::Navigate),
+    },
+    TutorialStep {
+        title: "Search",
+        hints: &[
+            "Press / and type a part of a title,",
+            "for example 'rust', then press Enter.",
+            "Esc clears the active filter.",
+        ],
+        completed_by: Some(TutorialAction::Search),
+    },
+    TutorialStep {
+        title: "Tag",
+        hints: &[
+            "Press T to edit tags of the selected item.",
+            "Type a tag and press , to add it, Tab",
+            "completes a known tag. Enter saves.",
+        ],
+        completed_by: Some(TutorialAction::Tag),
+    },
+    TutorialStep {
+        title: "Archive",
+        hints: &[
+            "Press f to favorite & archive the item",
+            "you are done with. It disappears from",
+            "the reading list.",
+        ],
+        completed_by: Some(TutorialAction::Archive),
+    },
+    TutorialStep {
+        title: "RSS triage",
+        hints: &[
+            "Press n to open RSS updates.",
+            "d hides an entry, a adds it to Pocket,",
+            "space marks several, Esc goes back.",
+        ],
+        completed_by: Some(TutorialAction::RssTriage),
+    },
+    TutorialStep {
+        title: "Done!",
+        hints: &[
+            "That's it. Press ? any time for help.",
+            "Nothing was sent to Pocket during",
+            "the tutorial. ZZ quits.",
+        ],
+        completed_by: None,
+    },
+];
+
+pub struct Tutorial {
+    step: usize,
+}
+
+impl Tutorial {
+    pub fn new() -> Self {
+        Tutorial { step: 0 }
+    }
+
+    pub fn advance(&mut self, action: TutorialAction) {
+        if self.current().completed_by == Some(action) {
+            self.step += 1;
+        }
+    }
+
+    pub fn current(&self) -> &'static TutorialStep {
+        &STEPS[self.step]
+    }
+
+    /// (current step number starting from 1, total number of steps)
+    pub fn progress(&self) -> (usize, usize) {
+        (self.step + 1, STEPS.len())
+    }
+}
+
+pub fn sample_item(
+    id: usize,
+    days_ago: i64,
+    title: &str,
+    url: &str,
+    tags: &[&str],
+    authors: Option<Vec<String>>,
+) -> PocketItem {
+    let time_added = (Utc::now().timestamp() - days_ago * 24 * 60 * 60 - id as i64).to_string();
+    let tags: Map<String, Value> = tags
+        .iter()
+        .map(|tag| {
+            (
+                tag.to_string(),
+                json!({ "item_id": id.to_string(), "tag": tag }),
+            )
+        })
+        .collect();
+    PocketItem {
+        item_id: id.to_string(),
+        favorite: "0".to_string(),
+        status: "0".to_string(),
+        time_added: time_added.clone(),
+        time_updated: time_added,
+        time_read: "0".to_string(),
+        time_favorited: "0".to_string(),
+        resolved_title: Some(title.to_string()),
+        resolved_url: Some(url.to_string()),
+        tags,
+        authors,
+        ..Default::default()
+    }
+}
+
+/// Fake reading list used by the tutorial so that nothing touches the real account
+pub fn sample_items() -> Vec<PocketItem> {
+    vec![
+        sample_item(
+            1,
+            0,
+            "Rust 2024 edition is out",
+            "https://blog.rust-lang.org/2025/02/20/Rust-1.85.0.html",
+            &["rust", "top"],
+            None,
+        ),
+        sample_item(
+            2,
+            0,
+            "How to write a TUI application in Rust",
+            "https://ratatui.rs/tutorials/",
+            &["rust", "tui"],
+            None,
+        ),
+        sample_item(
+            3,
+            1,
+            "What every programmer should know about memory",
+            "https://people.freebsd.org/~lstewart/articles/cpumemory.pdf",
+            &["lowlevel"],
+            None,
+        ),
+        sample_item(
+            4,
+            1,
+            "Designing data-intensive applications: a talk",
+            "https://www.youtube.com/watch?v=PdtlXdse7pw",
+            &[],
+            Some(vec!["YT:Sample Channel".to_string()]),
+        ),
+        sample_item(
+            5,
+            2,
+            "Vim tips for people who just learned hjkl",
+            "https://vim.fandom.com/wiki/Best_Vim_Tips",
+            &["vim", "read"],
+            None,
+        ),
+        sample_item(
+            6,
+            3,
+            "Understanding Rust lifetimes",
+            "https://doc.rust-lang.org/nomicon/lifetimes.html",
+            &["rust"],
+            None,
+        ),
+        sample_item(
+            7,
+            3,
+            "The log: what every software engineer should know",
+            "https://engineering.linkedin.com/distributed-systems/log-what-every-software-engineer-should-know-about-real-time-datas-unifying",
+            &["distributed"],
+            None,
+        ),
+        sample_item(
+            8,
+            6,
+            "Reading list zero: a practical guide",
+            "https://example.com/reading-list-zero",
+            &[],
+            None,
+        ),
+    ]
+}
+
+pub fn sample_rss_items() -> Vec<RssFeedItem> {
+    let now = Utc::now().to_rfc3339();
+    [
+        "Announcing a new async runtime",
+        "Notes on B-trees",
+        "Weekly links #42",
+    ]
+    .iter()
+    .enumerate()
+    .map(|(i, title)| RssFeedItem {
+        title: title.to_string(),
+        link: format!("https://example.com/feed/{}", i),
+        source: "Sample feed".to_string(),
+        description: Some(format!("A short description of \"{}\".", title)),
+        pub_date: Some(now.clone()),
+        item_id: format!("Sample feed:{}", i),
+        nsfw: false,
+        enclosure: None,
+    })
+    .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn advances_only_on_expected_action() {
+        let mut tutorial = Tutorial::new();
+        assert_eq!(tutorial.progress(), (1, STEPS.len()));
+
+        tutorial.advance(TutorialAction::Archive);
+        assert_eq!(tutorial.current().title, "Navigate");
+
+        tutorial.advance(TutorialAction::Navigate);
+        tutorial.advance(TutorialAction::Search);
+        tutorial.advance(TutorialAction::Tag);
+        tutorial.advance(TutorialAction::Archive);
+        tutorial.advance(TutorialAction::RssTriage);
+        assert_eq!(tutorial.current().title, "Done!");
+
+        // the last step can't be completed
+        tutorial.advance(TutorialAction::Navigate);
+        assert_eq!(tutorial.progress(), (STEPS.len(), STEPS.len()));
+    }
+}
@@ -1,23 +1,107 @@
 use anyhow::Context;
 use extractous::Extractor;
 use log::{debug, error};
+use std::collections::HashMap;
 use std::path::Path;
 
+/// The text goes into the search index, the title only needs the first page
+const PDF_TEXT_LIMIT: i32 = 500_000;
+
 pub struct PDFData {
     pub title: Option<String>,
+    pub author: Option<String>,
+    pub page_count: Option<usize>,
     pub text: String,
 }
 
+impl PDFData {
+    /// Title for the renamed item, e.g. "Attention Is All You Need (Ashish Vaswani, 15 pages)"
+    pub fn display_title(&self) -> Option<String> {
+        let title = self.title.as_ref()?;
+        let details: Vec<String> = self
+            .author
+            .iter()
+            .cloned()
+            .chain(self.page_count.map(|pages| format!("{} pages", pages)))
+            .collect();
+        if details.is_empty() {
+            Some(title.clone())
+        } else {
+            Some(format!("{} ({})", title, details.join(", ")))
+        }
+    }
+}
+
+/// Titles that document tools put into the metadata instead of the real one
+const JUNK_TITLES: &[&str] = &[
+    "untitled",
+    "title",
+    "no title",
+    "document",
+    "document1",
+    "slide 1",
+    "powerpoint presentation",
+    "layout 1",
+    "print",
+    "main",
+    "paper",
+    "article",
+    "manuscript",
+];
+
+const JUNK_AUTHORS: &[&str] = &[
+    "administrator",
+    "admin",
+    "user",
+    "owner",
+    "author",
+    "unknown",
+];
+
+const FILE_EXTENSIONS: &[&str] = &[
+    ".doc", ".docx", ".pdf", ".tex", ".dvi", ".ps", ".indd", ".ppt", ".pptx", ".odt", ".rtf",
+    ".txt", ".qxd", ".eps",
+];
+
+/**
+Metadata titles are often garbage: file hashes, "Microsoft Word - draft.docx", names of the tool
+that produced the file. Such titles are replaced by the heading found on the first page.
+*/
+pub fn is_garbage_title(title: &str) -> bool {
+    let title = title.trim();
+    let lowercase = title.to_lowercase();
+    let words: Vec<&str> = title.split_whitespace().collect();
+    title.chars().filter(|ch| ch.is_alphabetic()).count() < 3
+        || JUNK_TITLES.contains(&lowercase.as_str())
+        || FILE_EXTENSIONS.iter().any(|ext| lowercase.ends_with(ext))
+        || (lowercase.starts_with("microsoft ") && title.contains(" - "))
+        // hashes and generated file names: one long token mixing letters and digits
+        || (words.len() == 1
+            && title.len() >= 16
+            && title.chars().any(|ch| ch.is_ascii_digit()))
+        // all-caps tool names, e.g. PDFCREATOR or "ACROBAT DISTILLER"
+        || (words.len() <= 2
+            && title.chars().any(|ch| ch.is_alphabetic())
+            && !title.chars().any(|ch| ch.is_lowercase()))
+}
+
+fn metadata_value(metadata: &HashMap<String, Vec<String>>, keys: &[&str]) -> Option<String> {
+    keys.iter()
+        .filter_map(|key| metadata.get(*key))
+        .filter_map(|values| values.first())
+        .map(|value| value.trim().to_string())
+        .find(|value| !value.is_empty())
+}
+
 pub fn extract_pdf_title(path: &Path) -> anyhow::Result<Option<PDFData>> {
     // Read the file content
     let data =
         std::fs::read(path).with_context(|| format!("Failed to read file: {}", path.display()))?;
-    let mut extractor = Extractor::new().set_extract_string_max_length(10000);
+    let mut extractor = Extractor::new().set_extract_string_max_length(PDF_TEXT_LIMIT);
     let (text, metadata) = extractor
         .extract_file_to_string(path.to_str().unwrap())
         .unwrap();
 
-    let mut title_opt: Option<String> = None;
     if None == metadata.get("pdf:PDFVersion") {
         error!("PDF Metadate that doesn't have PDFVersion: {:?}", metadata);
         return anyhow::Result::Err(anyhow::anyhow!(
@@ -25,57 +109,53 @@ pub fn extract_pdf_title(path: &Path) -> anyhow::Result<Option<PDFData>> {
         ));
     }
 
-    //todo: sometimes title metadata contains garbage
-    if let Some(title) = metadata.get("dc:title") {
-        title_opt = title
-            .first()
-            .and_then(|x| (!x.is_empty()).then(|| x.clone()));
-    }
-    if title_opt.is_none() {
-        if let Some(title) = metadata.get("pdf:docinfo:title") {
-            title_opt = title
-                .first()
-                .and_then(|x| (!x.is_empty()).then(|| x.clone()));
-        }
-    }
-    if title_opt.is_none() {
-        if let Some(extracted_title) = extract_title(&text) {
-            if !extracted_title.is_empty() {
-                title_opt = Some(extracted_title);
-            }
-        }
-    }
+    let title_opt = metadata_value(&metadata, &["dc:title", "pdf:docinfo:title"])
+        .filter(|title| !is_garbage_title(title))
+        .or_else(|| extract_title(&text).filter(|title| !title.is_empty()));
+    let author = metadata_value(
+        &metadata,
+        &["dc:creator", "pdf:docinfo:creator", "meta:author"],
+    )
+    .filter(|author| !JUNK_AUTHORS.contains(&author.to_lowercase().as_str()));
+    let page_count = metadata_value(&metadata, &["xmpTPg:NPages"])
+        .and_then(|pages| pages.parse::<usize>().ok())
+        .filter(|pages| *pages > 0);
 
     debug!(
-        "PDF Meta: {:?},\nTitle: {:?},\nText: {:?}",
+        "PDF Meta: {:?},\nTitle: {:?},\nText: {:?}, size: {}",
         metadata,
         title_opt,
-        &text[0..500]
+        text.chars().take(500).collect::<String>(),
+        data.len()
     );
-    // Ok(None)
 
     Ok(Some(PDFData {
         title: title_opt,
+        author,
+        page_count,
         text,
     }))
 }
 
+/// Looks for the heading on the first page: the first paragraph of a title-like length
 fn extract_title(text: &str) -> Option<String> {
     let min_words = 3;
     let max_words = 50;
+    // pages are separated by form feeds
+    let first_page = text.split('\x0c').find(|page| !page.trim().is_empty())?;
 
-    let by_paragraphs = text
+    let by_paragraphs = first_page
         .trim_start()
         .split("\n\n")
+        .map(|s| s.split_whitespace().collect::<Vec<_>>().join(" "))
         .find(|s| {
             let words = s.split_whitespace().count();
-            words >= min_words && words <= max_words
-        })
-        .map(|s| s.replace('\n', " ").trim().to_string());
+            words >= min_words && words <= max_words && !is_garbage_title(s)
+        });
 
     match by_paragraphs {
         Some(title) => Some(title),
-        None => text
+        None => first_page
             .split_whitespace()
             .take(10)
             .collect::<Vec<_>>()
@@ -83,3 +163,43 @@ fn extract_title(text: &str) -> Option<String> {
             .into(),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rejects_garbage_titles() {
+        for garbage in [
+            "Microsoft Word - draft_v3.docx",
+            "d41d8cd98f00b204e9800998ecf8427e",
+            "PDFCREATOR",
+            "untitled",
+            "paper.tex",
+            "123-456",
+        ] {
+            assert!(is_garbage_title(garbage), "{}", garbage);
+        }
+        assert!(!is_garbage_title("Attention Is All You Need"));
+        assert!(!is_garbage_title(
+            "BERT: Pre-training of Deep Bidirectional Transformers"
+        ));
+
+        let text = "\n\narXiv:1706.03762\n\nAttention Is All\nYou Need\n\nAbstract text\x0cPage 2";
+        assert_eq!(
+            extract_title(text).as_deref(),
+            Some("Attention Is All You Need")
+        );
+
+        let pdf = PDFData {
+            title: Some("Attention Is All You Need".to_string()),
+            author: Some("Ashish Vaswani".to_string()),
+            page_count: Some(15),
+            text: String::new(),
+        };
+        assert_eq!(
+            pdf.display_title().as_deref(),
+            Some("Attention Is All You Need (Ashish Vaswani, 15 pages)")
+        );
+    }
+}
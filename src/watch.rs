@@ -0,0 +1,236 @@
+use std::fs;
+use std::path::Path;
+
+use crate::prss::RssFeedItem;
+use crate::storage::PocketItem;
+use crate::TableRow;
+
+/// Keeps `time_added` of the newest Pocket item that was already checked against watch queries
+pub const WATERMARK_FILE: &str = "watch_watermark.txt";
+
+/// Anything that can be checked against watch queries
+pub trait Watchable {
+    fn watch_title(&self) -> &str;
+    fn watch_url(&self) -> &str;
+    fn watch_tags(&self) -> Vec<&str>;
+    /// Where the item came from, shown in the alerts popup
+    fn watch_source(&self) -> &str;
+}
+
+impl Watchable for PocketItem {
+    fn watch_title(&self) -> &str {
+        self.title()
+    }
+
+    fn watch_url(&self) -> &str {
+        self.url()
+    }
+
+    fn watch_tags(&self) -> Vec<&str> {
+        self.tags().map(|tag| tag.as_str()).collect()
+    }
+
+    fn watch_source(&self) -> &str {
+        "Pocket"
+    }
+}
+
+impl Watchable for RssFeedItem {
+    fn watch_title(&self) -> &str {
+        &self.title
+    }
+
+    fn watch_url(&self) -> &str {
+        &self.link
+    }
+
+    fn watch_tags(&self) -> Vec<&str> {
+        Vec::new()
+    }
+
+    fn watch_source(&self) -> &str {
+        &self.source
+    }
+}
+
+#[derive(Clone, Debug, PartialEq)]
+enum Term {
+    Tag(String),
+    Domain(String),
+    Text(String),
+}
+
+impl Term {
+    fn matches(&self, item: &impl Watchable) -> bool {
+        match self {
+            Term::Tag(tag) => item.watch_tags().iter().any(|t| t.to_lowercase() == *tag),
+            Term::Domain(domain) => {
+                let host = domain_of(item.watch_url());
+                host == *domain || host.ends_with(&format!(".{}", domain))
+            }
+            Term::Text(text) => {
+                item.watch_title().to_lowercase().contains(text)
+                    || item.watch_url().to_lowercase().contains(text)
+            }
+        }
+    }
+}
+
+fn domain_of(url: &str) -> String {
+    url.trim_start_matches("http://")
+        .trim_start_matches("https://")
+        .trim_start_matches("www.")
+        .split('/')
+        .next()
+        .unwrap_or("")
+        .to_lowercase()
+}
+
+/**
+A query is a list of alternatives separated by `OR`. Every term of an alternative has to match:
+  `tag:security OR domain:lwn.net`
+  `tag:rust async`
+Terms are `tag:<tag>`, `domain:<domain>` or plain text looked up in the title and the url.
+*/
+#[derive(Clone, Debug)]
+pub struct WatchQuery {
+    pub raw: String,
+    alternatives: Vec<Vec<Term>>,
+}
+
+impl WatchQuery {
+    pub fn parse(raw: &str) -> Option<Self> {
+        let mut alternatives = vec![Vec::new()];
+        for token in raw.split_whitespace() {
+            if token == "OR" {
+                alternatives.push(Vec::new());
+                continue;
+            }
+            let token = token.to_lowercase();
+            let term = if let Some(tag) = token.strip_prefix("tag:") {
+                Term::Tag(tag.to_string())
+            } else if let Some(domain) = token.strip_prefix("domain:") {
+                Term::Domain(domain.trim_start_matches("www.").to_string())
+            } else {
+                Term::Text(token)
+            };
+            alternatives.last_mut().unwrap().push(term);
+        }
+        alternatives.retain(|terms| !terms.is_empty());
+        if alternatives.is_empty() {
+            None
+        } else {
+            Some(Self {
+                raw: raw.trim().to_string(),
+                alternatives,
+            })
+        }
+    }
+
+    pub fn parse_all(raw_queries: &[String]) -> Vec<Self> {
+        raw_queries.iter().filter_map(|q| Self::parse(q)).collect()
+    }
+
+    pub fn matches(&self, item: &impl Watchable) -> bool {
+        self.alternatives
+            .iter()
+            .any(|terms| terms.iter().all(|term| term.matches(item)))
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct WatchMatch {
+    pub query: String,
+    pub title: String,
+    pub url: String,
+    pub source: String,
+}
+
+/// Reports every item that matches at least one query (only the first matching query is shown)
+pub fn find_matches<'a, T: Watchable + 'a>(
+    queries: &[WatchQuery],
+    items: impl Iterator<Item = &'a T>,
+) -> Vec<WatchMatch> {
+    if queries.is_empty() {
+        return Vec::new();
+    }
+    items
+        .filter_map(|item| {
+            queries
+                .iter()
+                .find(|query| query.matches(item))
+                .map(|query| WatchMatch {
+                    query: query.raw.clone(),
+                    title: item.watch_title().to_string(),
+                    url: item.watch_url().to_string(),
+                    source: item.watch_source().to_string(),
+                })
+        })
+        .collect()
+}
+
+/**
+Pocket items added after the stored watermark are the new ones.
+When there is no watermark yet (first run with watch queries) nothing is reported,
+otherwise the whole reading list would show up as "new".
+*/
+pub fn new_pocket_matches(
+    queries: &[WatchQuery],
+    items: &[PocketItem],
+) -> anyhow::Result<Vec<WatchMatch>> {
+    if queries.is_empty() {
+        return Ok(Vec::new());
+    }
+    let watermark_file = Path::new(WATERMARK_FILE);
+    let watermark = fs::read_to_string(watermark_file)
+        .ok()
+        .and_then(|content| content.trim().parse::<u64>().ok());
+    let newest = items.iter().map(|item| item.time_added()).max();
+
+    let matches = match watermark {
+        Some(watermark) => find_matches(
+            queries,
+            items.iter().filter(|item| item.time_added() > watermark),
+        ),
+        None => Vec::new(),
+    };
+    if let Some(newest) = newest {
+        if watermark.is_none_or(|w| newest > w) {
+            fs::write(watermark_file, newest.to_string())?;
+        }
+    }
+    Ok(matches)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rss(title: &str, link: &str) -> RssFeedItem {
+        RssFeedItem {
+            title: title.to_string(),
+            link: link.to_string(),
+            source: "feed".to_string(),
+            description: None,
+            pub_date: None,
+            item_id: link.to_string(),
+            nsfw: false,
+            enclosure: None,
+        }
+    }
+
+    #[test]
+    fn matches_alternatives_and_terms() {
+        let query = WatchQuery::parse("domain:lwn.net OR rust async").unwrap();
+        assert!(query.matches(&rss("Kernel news", "https://lwn.net/Articles/1")));
+        assert!(query.matches(&rss("Async Rust in practice", "https://example.com")));
+        assert!(!query.matches(&rss("Rust 2024", "https://example.com")));
+        assert!(!query.matches(&rss("Kernel news", "https://notlwn.net/")));
+
+        // rss items have no tags
+        let query = WatchQuery::parse("tag:security").unwrap();
+        assert!(!query.matches(&rss("security", "https://example.com")));
+
+        assert!(WatchQuery::parse("  OR ").is_none());
+    }
+}